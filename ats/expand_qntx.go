@@ -90,12 +90,12 @@ func GroupClaimsByKey(claims []IndividualClaim) map[ClaimKey][]IndividualClaim {
 
 	groups := make(map[ClaimKey][]IndividualClaim, len(output.Groups))
 	for _, g := range output.Groups {
-		var key ClaimKey
-		// Parse "subject|predicate|context" back to struct
-		parts := splitClaimKey(g.Key)
-		if len(parts) == 3 {
-			key = ClaimKey{Subject: parts[0], Predicate: parts[1], Context: parts[2]}
+		if len(g.Claims) == 0 {
+			continue
 		}
+		// Every claim in a group shares (subject, predicate, context); read the key
+		// from the first claim rather than parsing g.Key.
+		key := ClaimKey{Subject: g.Claims[0].Subject, Predicate: g.Claims[0].Predicate, Context: g.Claims[0].Context}
 
 		groupClaims := make([]IndividualClaim, len(g.Claims))
 		for i, c := range g.Claims {
@@ -155,17 +155,3 @@ func ConvertClaimsToAttestations(claims []IndividualClaim) []types.As {
 
 	return result
 }
-
-// splitClaimKey splits a "subject|predicate|context" key into parts.
-func splitClaimKey(key string) []string {
-	parts := make([]string, 0, 3)
-	start := 0
-	for i := 0; i < len(key); i++ {
-		if key[i] == '|' {
-			parts = append(parts, key[start:i])
-			start = i + 1
-		}
-	}
-	parts = append(parts, key[start:])
-	return parts
-}
@@ -81,7 +81,7 @@ const (
 // IsAxKeyword checks if a value is an ax grammar keyword
 // Used for lookahead to determine when to stop parsing temporal expressions
 func IsAxKeyword(value string) bool {
-	keywords := []string{"is", "are", "of", "from", "by", "via", "since", "until", "on", "between", "so", "therefore"}
+	keywords := []string{"is", "are", "of", "from", "by", "via", "since", "until", "on", "between", "so", "therefore", "where"}
 	lower := strings.ToLower(value)
 	for _, keyword := range keywords {
 		if lower == keyword {
@@ -32,6 +32,17 @@ type resolvedOutput struct {
 	Temporal   *resolvedTimeral `json:"temporal,omitempty"`
 	Actions    []string         `json:"actions"`
 	Error      string           `json:"error,omitempty"`
+
+	// "or" alternatives of Subjects and Contexts, as in rustAxQuery
+	SubjectGroups [][]string `json:"subject_groups,omitempty"`
+	ContextGroups [][]string `json:"context_groups,omitempty"`
+
+	// "not"/"except" terms, as in rustAxQuery
+	ExcludedSubjects []string `json:"excluded_subjects"`
+	ExcludedContexts []string `json:"excluded_contexts"`
+	ExcludedActors   []string `json:"excluded_actors"`
+
+	AttributeConditions []rustAttrCondition `json:"attribute_conditions,omitempty"`
 }
 
 // resolvedTimeral represents resolved temporal clauses from Rust.
@@ -54,9 +65,9 @@ type resolvedBetween struct {
 }
 
 type resolvedOverClause struct {
-	Raw   string   `json:"raw"`
-	Value *float64 `json:"value"`
-	Unit  *string  `json:"unit"`
+	Raw        string  `json:"raw"`
+	Ms         *int64  `json:"ms"`
+	Normalized *string `json:"normalized"`
 }
 
 func parseAxQueryWasm(args []string) (*types.AxFilter, error) {
@@ -94,6 +105,13 @@ func parseAxQueryWasm(args []string) (*types.AxFilter, error) {
 
 // convertResolvedOutput maps the resolved WASM output to Go's AxFilter.
 func convertResolvedOutput(out *resolvedOutput) (*types.AxFilter, error) {
+	if len(out.AttributeConditions) > 0 {
+		return nil, errAttributeConditions(out.AttributeConditions)
+	}
+	if err := errExclusions(out.ExcludedSubjects, out.ExcludedContexts, out.ExcludedActors); err != nil {
+		return nil, err
+	}
+
 	filter := &types.AxFilter{
 		Limit:  100,
 		Format: "table",
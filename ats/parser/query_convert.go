@@ -17,6 +17,29 @@ type rustAxQuery struct {
 	Temporal   *rustTemporalClause `json:"temporal"`
 	Actions    []string            `json:"actions"`
 	Error      string              `json:"error,omitempty"`
+
+	// SubjectGroups and ContextGroups split Subjects and Contexts into "or"
+	// alternatives; absent unless the query uses "or". The flat lists
+	// already match any of their terms, so conversion ignores them.
+	SubjectGroups [][]string `json:"subject_groups,omitempty"`
+	ContextGroups [][]string `json:"context_groups,omitempty"`
+
+	// Excluded* hold the terms after "not"/"except". Go's AxFilter can't
+	// exclude, so queries carrying them are rejected.
+	ExcludedSubjects []string `json:"excluded_subjects"`
+	ExcludedContexts []string `json:"excluded_contexts"`
+	ExcludedActors   []string `json:"excluded_actors"`
+
+	// AttributeConditions come from a "where" clause. Go's AxFilter has no
+	// attribute filtering, so queries carrying them are rejected.
+	AttributeConditions []rustAttrCondition `json:"attribute_conditions,omitempty"`
+}
+
+// rustAttrCondition mirrors qntx-core's AttrCondition.
+type rustAttrCondition struct {
+	Key   string `json:"key"`
+	Op    string `json:"op"`
+	Value any    `json:"value"`
 }
 
 // rustTemporalClause handles the enum serialization.
@@ -36,9 +59,31 @@ func nilIfEmpty(s []string) []string {
 	return s
 }
 
+// errAttributeConditions rejects "where" clauses, which Go's AxFilter can't carry.
+func errAttributeConditions(conditions []rustAttrCondition) error {
+	return errors.Newf("'where' clauses are not supported by this query path (%d attribute conditions, first on %q)",
+		len(conditions), conditions[0].Key)
+}
+
+// errExclusions rejects "not"/"except" clauses, which Go's AxFilter can't carry.
+func errExclusions(subjects, contexts, actors []string) error {
+	if len(subjects)+len(contexts)+len(actors) == 0 {
+		return nil
+	}
+	return errors.Newf("'not' clauses are not supported by this query path (excluded subjects %v, contexts %v, actors %v)",
+		subjects, contexts, actors)
+}
+
 // convertRustQuery maps the parser output to Go's AxFilter,
 // applying case normalization and temporal resolution.
 func convertRustQuery(rq *rustAxQuery) (*types.AxFilter, error) {
+	if len(rq.AttributeConditions) > 0 {
+		return nil, errAttributeConditions(rq.AttributeConditions)
+	}
+	if err := errExclusions(rq.ExcludedSubjects, rq.ExcludedContexts, rq.ExcludedActors); err != nil {
+		return nil, err
+	}
+
 	filter := &types.AxFilter{
 		Limit:  100,
 		Format: "table",
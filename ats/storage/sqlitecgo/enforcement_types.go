@@ -43,3 +43,9 @@ type StorageStats struct {
 	UniqueContexts    int `json:"unique_contexts"`
 	UniqueActors      int `json:"unique_actors"`
 }
+
+// IntegrityReport is the structured PRAGMA integrity_check result from Rust.
+type IntegrityReport struct {
+	OK       bool     `json:"ok"`
+	Problems []string `json:"problems"`
+}
@@ -114,6 +114,30 @@ func NewFileStore(path string) (*RustStore, error) {
 		return nil, errors.Newf("failed to create file store at %s", path)
 	}
 
+	return openFileStore(store, path)
+}
+
+// NewScopedFileStore creates a file-backed Rust storage backend bound to one
+// workspace namespace. Reads and writes through it, including its read pool,
+// only see attestations of that namespace; attestation IDs are unique per namespace.
+// The caller must call Close() when done to free resources.
+func NewScopedFileStore(path, namespace string) (*RustStore, error) {
+	cPath := C.CString(path)
+	defer C.free(unsafe.Pointer(cPath))
+	cNamespace := C.CString(namespace)
+	defer C.free(unsafe.Pointer(cNamespace))
+
+	store := C.storage_new_file_scoped(cPath, cNamespace)
+	if store == nil {
+		return nil, errors.Newf("failed to create file store at %s for namespace %q", path, namespace)
+	}
+
+	return openFileStore(store, path)
+}
+
+// openFileStore opens the read connections for a freshly created file store.
+// Takes ownership of store and frees it on failure.
+func openFileStore(store *C.SqliteStore, path string) (*RustStore, error) {
 	// Open primary read connection (used by the rustdriver for database/sql queries)
 	readConn := C.storage_open_read_conn(store)
 	if readConn == nil {
@@ -998,6 +1022,45 @@ func (rs *RustStore) IntegrityCheck() ([]string, error) {
 	return values, nil
 }
 
+// IntegrityReport runs PRAGMA integrity_check via Rust FFI and returns the
+// structured report. It runs on a pooled read connection when one is free,
+// so writes are not held up; the in-memory fallback uses the write connection.
+func (rs *RustStore) IntegrityReport() (*IntegrityReport, error) {
+	var result C.AttestationResultC
+	entry := rs.acquireReadConn()
+	if entry != nil {
+		result = C.read_conn_integrity_report(entry.conn)
+		rs.releaseReadConn(entry)
+	} else {
+		rs.muWrite.Lock()
+		if rs.store == nil {
+			rs.muWrite.Unlock()
+			return nil, errors.New("store is closed")
+		}
+		result = C.storage_integrity_report(rs.store)
+		rs.muWrite.Unlock()
+	}
+	var success bool
+	var errMsg, jsonStr string
+	success = bool(result.success)
+	if !success {
+		errMsg = C.GoString(result.error_msg)
+	} else if result.attestation_json != nil {
+		jsonStr = C.GoString(result.attestation_json)
+	}
+	C.attestation_result_free(result)
+
+	if !success {
+		return nil, errors.Newf("integrity check failed: %s", errMsg)
+	}
+
+	var report IntegrityReport
+	if err := json.Unmarshal([]byte(jsonStr), &report); err != nil {
+		return nil, errors.Wrap(err, "failed to parse integrity report")
+	}
+	return &report, nil
+}
+
 // Backup creates a hot backup of the database to destPath.
 // Opens its own read-only source connection — does not touch the store pointer,
 // so it's safe to call concurrently with storage_put.
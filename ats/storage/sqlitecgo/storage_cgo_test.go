@@ -261,3 +261,19 @@ func TestRustStore_Update(t *testing.T) {
 		t.Errorf("Subjects after update = %v, want [EEG_Beta_Wave]", retrieved.Subjects)
 	}
 }
+
+func TestRustStore_IntegrityReport(t *testing.T) {
+	store, err := NewMemoryStore()
+	if err != nil {
+		t.Fatalf("NewMemoryStore() error: %v", err)
+	}
+	defer store.Close()
+
+	report, err := store.IntegrityReport()
+	if err != nil {
+		t.Fatalf("IntegrityReport() error: %v", err)
+	}
+	if !report.OK || len(report.Problems) != 0 {
+		t.Errorf("IntegrityReport() = %+v, want ok with no problems", report)
+	}
+}
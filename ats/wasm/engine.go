@@ -11,15 +11,18 @@
 // This compiles qntx-core to wasm32-unknown-unknown and copies the artifact here.
 package wasm
 
-//go:generate cargo build --release --target wasm32-unknown-unknown --package qntx-wasm --manifest-path ../../Cargo.toml
+//go:generate cargo build --release --target wasm32-unknown-unknown --package qntx-wasm --features bench --manifest-path ../../Cargo.toml
 //go:generate cp ../../target/wasm32-unknown-unknown/release/qntx_wasm.wasm qntx_core.wasm
 
 import (
 	"context"
 	"crypto/rand"
 	_ "embed"
+	"encoding/binary"
 	"encoding/json"
+	"math"
 	"sync"
+	"time"
 
 	"github.com/teranos/errors"
 	"github.com/tetratelabs/wazero"
@@ -31,7 +34,8 @@ var wasmBytes []byte
 
 // Engine wraps a wazero runtime with a compiled qntx-core WASM module.
 // A single module instance is reused for all calls (the exported functions
-// are stateless pure functions). Access is serialized by a mutex.
+// are pure, apart from the defaults set by LoadCoreConfig). Access is
+// serialized by a mutex.
 type Engine struct {
 	runtime  wazero.Runtime
 	compiled wazero.CompiledModule
@@ -59,6 +63,20 @@ func newEngine() (*Engine, error) {
 
 	r := wazero.NewRuntime(ctx)
 
+	// Host clock for run_benchmarks: wasm32-unknown-unknown has none of its own
+	clockOrigin := time.Now()
+	_, err := r.NewHostModuleBuilder("env").
+		NewFunctionBuilder().
+		WithFunc(func() float64 {
+			return float64(time.Since(clockOrigin).Nanoseconds()) / 1e6
+		}).
+		Export("qntx_now_ms").
+		Instantiate(ctx)
+	if err != nil {
+		r.Close(ctx)
+		return nil, errors.Wrap(err, "wasm host clock")
+	}
+
 	compiled, err := r.CompileModule(ctx, wasmBytes)
 	if err != nil {
 		r.Close(ctx)
@@ -125,11 +143,19 @@ type ClassifyTemporalConfig struct {
 	ObsolescenceWindowMs int64 `json:"obsolescence_window_ms"`
 }
 
-// ClassifyInput is the full input for classify_claims.
+// ClassifyStaleConfig controls the stale_claims section of the output.
+type ClassifyStaleConfig struct {
+	MaxReported         int  `json:"max_reported"`
+	IncludeAutoResolved bool `json:"include_auto_resolved"`
+}
+
+// ClassifyInput is the full input for classify_claims. A nil Stale reports
+// up to 50 stale claims, auto-resolved groups included.
 type ClassifyInput struct {
 	ClaimGroups []ClassifyClaimGroup   `json:"claim_groups"`
 	Config      ClassifyTemporalConfig `json:"config"`
 	NowMs       int64                  `json:"now_ms"`
+	Stale       *ClassifyStaleConfig   `json:"stale,omitempty"`
 }
 
 // ClassifyConflictOutput represents a single classified conflict.
@@ -144,6 +170,9 @@ type ClassifyConflictOutput struct {
 	TemporalPattern string              `json:"temporal_pattern"`
 	AutoResolved    bool                `json:"auto_resolved"`
 	SourceIDs       []string            `json:"source_ids"`
+	// WinningClaim is the source ID of the single claim an auto-resolution
+	// kept (evolution, supersession); empty otherwise.
+	WinningClaim    string              `json:"winning_claim,omitempty"`
 }
 
 // ClassifyActorRank represents an actor with credibility ranking.
@@ -153,6 +182,20 @@ type ClassifyActorRank struct {
 	Timestamp   *int64 `json:"timestamp,omitempty"`
 }
 
+// ClassifyStaleClaim is a claim not re-attested within the obsolescence
+// window, with an AX query that finds attestations to re-verify it.
+type ClassifyStaleClaim struct {
+	Subject        string `json:"subject"`
+	Predicate      string `json:"predicate"`
+	Context        string `json:"context"`
+	LastAttestedMs int64  `json:"last_attested_ms"`
+	AgeMs          int64  `json:"age_ms"`
+	LastActor      string `json:"last_actor"`
+	SourceID       string `json:"source_id"`
+	AutoResolved   bool   `json:"auto_resolved"`
+	Query          string `json:"query"`
+}
+
 // ClassifyOutput is the result of classify_claims.
 type ClassifyOutput struct {
 	Conflicts         []ClassifyConflictOutput `json:"conflicts"`
@@ -160,6 +203,8 @@ type ClassifyOutput struct {
 	ReviewRequired    int                      `json:"review_required"`
 	TotalAnalyzed     int                      `json:"total_analyzed"`
 	ResolvedSourceIDs []string                 `json:"resolved_source_ids"`
+	StaleClaims       []ClassifyStaleClaim     `json:"stale_claims"`
+	StaleTotal        int                      `json:"stale_total"`
 }
 
 // ClassifyClaims invokes the WASM classify_claims function.
@@ -190,6 +235,66 @@ func (e *Engine) ClassifyClaims(input ClassifyInput) (*ClassifyOutput, error) {
 	return &output, nil
 }
 
+// ClassifyDefaultConfig returns the time windows classify_claims falls back
+// to when its config omits them, reflecting any LoadCoreConfig document.
+func (e *Engine) ClassifyDefaultConfig() (*ClassifyTemporalConfig, error) {
+	raw, err := e.CallNoArgs("classify_default_config")
+	if err != nil {
+		return nil, err
+	}
+
+	var config ClassifyTemporalConfig
+	if err := json.Unmarshal([]byte(raw), &config); err != nil {
+		return nil, errors.Wrapf(err, "unmarshal classify_default_config result: %s", raw)
+	}
+	return &config, nil
+}
+
+// LoadCoreConfig installs a qntx-core config document (JSON, e.g.
+// {"classify": {"verification_window_ms": 30000}}) as the module-wide
+// defaults. Fields omitted from the document keep their built-in values.
+// Validation reports every problem at once; on error the previous config
+// stays in effect.
+func (e *Engine) LoadCoreConfig(document string) error {
+	raw, err := e.Call("load_core_config", document)
+	if err != nil {
+		return err
+	}
+
+	var resp struct {
+		Error string `json:"error,omitempty"`
+	}
+	if err := json.Unmarshal([]byte(raw), &resp); err != nil {
+		return errors.Wrapf(err, "unmarshal load_core_config result: %s", raw)
+	}
+	if resp.Error != "" {
+		return errors.Newf("load_core_config: %s", resp.Error)
+	}
+	return nil
+}
+
+// RunBenchmarks runs the seeded qntx-core self-benchmark inside the module
+// (about 1s with the defaults) and returns the report JSON. config is a
+// BenchConfig document ({"iterations": 5, "budget_ms": 1000, ...}); empty
+// uses the defaults.
+func (e *Engine) RunBenchmarks(config string) (json.RawMessage, error) {
+	raw, err := e.Call("run_benchmarks", config)
+	if err != nil {
+		return nil, err
+	}
+
+	var resp struct {
+		Error string `json:"error,omitempty"`
+	}
+	if err := json.Unmarshal([]byte(raw), &resp); err != nil {
+		return nil, errors.Wrapf(err, "unmarshal run_benchmarks result: %s", raw)
+	}
+	if resp.Error != "" {
+		return nil, errors.Newf("run_benchmarks: %s", resp.Error)
+	}
+	return json.RawMessage(raw), nil
+}
+
 // ExpandAttestationInput represents a compact attestation for WASM cartesian expansion.
 type ExpandAttestationInput struct {
 	ID          string   `json:"id"`
@@ -251,9 +356,14 @@ func (e *Engine) ExpandCartesianClaims(input ExpandInput) (*ExpandOutput, error)
 // GroupClaimsInput is the input for group_claims.
 type GroupClaimsInput struct {
 	Claims []ExpandClaimOutput `json:"claims"`
+
+	// KeyFormat selects the group key format: "json" (default) or "legacy".
+	KeyFormat string `json:"key_format,omitempty"`
 }
 
 // GroupClaimsGroupOutput represents a group of claims with the same key.
+// With the "json" key format, Key is a JSON array ["subject","predicate","context"];
+// with "legacy" it is "subject|predicate|context", which is ambiguous when a value contains "|".
 type GroupClaimsGroupOutput struct {
 	Key    string              `json:"key"`
 	Claims []ExpandClaimOutput `json:"claims"`
@@ -263,6 +373,7 @@ type GroupClaimsGroupOutput struct {
 type GroupClaimsOutput struct {
 	Groups      []GroupClaimsGroupOutput `json:"groups"`
 	TotalGroups int                      `json:"total_groups"`
+	KeyFormat   string                   `json:"key_format"`
 }
 
 // GroupClaims invokes the WASM group_claims function.
@@ -330,6 +441,60 @@ func (e *Engine) DedupSourceIDs(input DedupInput) (*DedupOutput, error) {
 	return &output, nil
 }
 
+// MMRPick is one candidate chosen by SimilarityMMR.
+type MMRPick struct {
+	ID        int     `json:"id"` // index into the candidates slice
+	Relevance float32 `json:"relevance"`
+	Marginal  float32 `json:"marginal"`
+}
+
+// MMROutput is the result of similarity_mmr.
+type MMROutput struct {
+	Picks   []MMRPick `json:"picks"`
+	Skipped []int     `json:"skipped"` // all-zero candidates
+}
+
+// SimilarityMMR re-ranks candidates by maximal marginal relevance against
+// query: up to k picks, with lambda trading cosine relevance (1) against
+// diversity from earlier picks (0). Every candidate must have len(query)
+// dimensions.
+func (e *Engine) SimilarityMMR(query []float32, candidates [][]float32, k int, lambda float32) (*MMROutput, error) {
+	buf := make([]byte, 0, 4*(3+len(query)*(1+len(candidates))))
+	buf = binary.LittleEndian.AppendUint32(buf, uint32(len(query)))
+	buf = binary.LittleEndian.AppendUint32(buf, uint32(k))
+	buf = binary.LittleEndian.AppendUint32(buf, math.Float32bits(lambda))
+	for _, f := range query {
+		buf = binary.LittleEndian.AppendUint32(buf, math.Float32bits(f))
+	}
+	for i, row := range candidates {
+		if len(row) != len(query) {
+			return nil, errors.Newf("similarity_mmr: candidate %d has %d dimensions, query has %d", i, len(row), len(query))
+		}
+		for _, f := range row {
+			buf = binary.LittleEndian.AppendUint32(buf, math.Float32bits(f))
+		}
+	}
+
+	raw, err := e.Call("similarity_mmr", string(buf))
+	if err != nil {
+		return nil, err
+	}
+
+	var errResp struct {
+		Error string `json:"error,omitempty"`
+	}
+	if json.Unmarshal([]byte(raw), &errResp) == nil && errResp.Error != "" {
+		return nil, errors.Newf("similarity_mmr: %s", errResp.Error)
+	}
+
+	var output MMROutput
+	if err := json.Unmarshal([]byte(raw), &output); err != nil {
+		return nil, errors.Wrapf(err, "unmarshal similarity_mmr result: %s", raw)
+	}
+
+	return &output, nil
+}
+
 // AsuidResult holds the full and short forms of a generated ASUID.
 type AsuidResult struct {
 	Full  string `json:"full"`
@@ -5,12 +5,14 @@
 
 use std::time::Instant;
 
-use qntx_core::classify::{ClaimGroup, ClaimInput, ClassifyInput, SmartClassifier, TemporalConfig};
+use qntx_core::classify::{
+    ClaimGroup, ClaimInput, ClassificationPolicy, ClassifyInput, SmartClassifier, StaleClaimsConfig,
+};
 
 fn bench_many_claims_in_group(num_claims: usize) {
     let now = 1_000_000_000_i64;
-    let config = TemporalConfig::default();
-    let classifier = SmartClassifier::new(config.clone());
+    let config = ClassificationPolicy::default();
+    let classifier = SmartClassifier::with_policy(config.clone());
 
     // Simulate a group with num_claims claims, each from a unique actor
     // (this is what happens before distillation — many actors on same SPC)
@@ -22,6 +24,7 @@ fn bench_many_claims_in_group(num_claims: usize) {
             actor: format!("levi-node-{:06}", i),
             timestamp_ms: now - (num_claims as i64 - i as i64) * 1000,
             source_id: format!("as-{:06}", i),
+            confidence: None,
         })
         .collect();
 
@@ -32,6 +35,7 @@ fn bench_many_claims_in_group(num_claims: usize) {
         }],
         config,
         now_ms: now,
+        stale: StaleClaimsConfig::default(),
     };
 
     // Warm up
@@ -55,8 +59,8 @@ fn bench_many_claims_in_group(num_claims: usize) {
 
 fn bench_supersession_with_large_group(num_claims: usize) {
     let now = 1_000_000_000_i64;
-    let config = TemporalConfig::default();
-    let classifier = SmartClassifier::new(config.clone());
+    let config = ClassificationPolicy::default();
+    let classifier = SmartClassifier::with_policy(config.clone());
 
     // N-1 levi claims + 1 human claim → supersession
     let mut claims: Vec<ClaimInput> = (0..num_claims - 1)
@@ -67,6 +71,7 @@ fn bench_supersession_with_large_group(num_claims: usize) {
             actor: format!("levi-node-{:06}", i),
             timestamp_ms: now - (num_claims as i64 - i as i64) * 1000,
             source_id: format!("as-{:06}", i),
+            confidence: None,
         })
         .collect();
 
@@ -77,6 +82,7 @@ fn bench_supersession_with_large_group(num_claims: usize) {
         actor: "human:brandon".to_string(),
         timestamp_ms: now,
         source_id: "as-human".to_string(),
+        confidence: None,
     });
 
     let input = ClassifyInput {
@@ -86,6 +92,7 @@ fn bench_supersession_with_large_group(num_claims: usize) {
         }],
         config,
         now_ms: now,
+        stale: StaleClaimsConfig::default(),
     };
 
     // Warm up
@@ -0,0 +1,332 @@
+//! Actor aliases: one identity under several actor strings.
+//!
+//! Ingestion paths spell the same person differently (`alice`,
+//! `human:alice`, `human:alice@verified`). An [`ActorAliasMap`] names one
+//! canonical actor per identity and lists its aliases. Stored data is never
+//! rewritten; the map is applied when classifying
+//! ([`ClassificationPolicy::actor_aliases`](crate::ClassificationPolicy)) and
+//! when querying ([`AxFilter::expand_actor_aliases`]).
+//!
+//! Resolution is a single hop: an alias maps straight to its canonical actor.
+//! Maps are written as JSON (`{"human:alice": ["alice", "human:alice@verified"]}`)
+//! or attested as `[alias] actor_alias [canonical]`, so they sync like any
+//! other data.
+
+use std::collections::{BTreeMap, HashMap};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::attestation::{Attestation, AxFilter};
+
+/// Predicate of an alias attestation: subjects are aliases of each context
+pub const ACTOR_ALIAS_PREDICATE: &str = "actor_alias";
+
+/// Invalid alias definition
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum ActorAliasError {
+    #[error("actor names must not be empty")]
+    EmptyActor,
+    #[error("alias '{alias}' is claimed by both '{first}' and '{second}'")]
+    Conflict {
+        alias: String,
+        first: String,
+        second: String,
+    },
+    #[error("'{0}' is both an alias and a canonical actor; aliases resolve in one hop")]
+    Chained(String),
+    #[error("invalid alias map: {0}")]
+    Parse(String),
+}
+
+/// Canonical actor → aliases, with the reverse lookup
+///
+/// Serializes as `{"canonical": ["alias", ...]}` with sorted aliases.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(
+    try_from = "BTreeMap<String, Vec<String>>",
+    into = "BTreeMap<String, Vec<String>>"
+)]
+pub struct ActorAliasMap {
+    aliases: BTreeMap<String, Vec<String>>,
+    canonical: HashMap<String, String>,
+}
+
+impl ActorAliasMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parse `{"canonical": ["alias", ...]}`
+    pub fn from_json(json: &str) -> Result<Self, ActorAliasError> {
+        serde_json::from_str(json).map_err(|e| ActorAliasError::Parse(e.to_string()))
+    }
+
+    /// Build from `[alias] actor_alias [canonical]` attestations.
+    ///
+    /// When an alias is attested for several canonical actors the newest
+    /// attestation (by timestamp, then ID) wins. Attestations that would
+    /// chain aliases are skipped.
+    pub fn from_attestations<'a>(attestations: impl IntoIterator<Item = &'a Attestation>) -> Self {
+        let mut definitions: Vec<&Attestation> = attestations
+            .into_iter()
+            .filter(|a| a.predicates.iter().any(|p| p == ACTOR_ALIAS_PREDICATE))
+            .collect();
+        definitions.sort_by(|a, b| (a.timestamp, &a.id).cmp(&(b.timestamp, &b.id)));
+
+        let mut map = Self::new();
+        for attestation in definitions {
+            for canonical in &attestation.contexts {
+                for alias in &attestation.subjects {
+                    map.reassign(canonical, alias);
+                }
+            }
+        }
+        map
+    }
+
+    /// Record `alias` as another name for `canonical`.
+    pub fn insert(&mut self, canonical: &str, alias: &str) -> Result<(), ActorAliasError> {
+        if canonical.is_empty() || alias.is_empty() {
+            return Err(ActorAliasError::EmptyActor);
+        }
+        if alias == canonical {
+            return Ok(());
+        }
+        if self.aliases.contains_key(alias) {
+            return Err(ActorAliasError::Chained(alias.to_string()));
+        }
+        if self.canonical.contains_key(canonical) {
+            return Err(ActorAliasError::Chained(canonical.to_string()));
+        }
+        match self.canonical.get(alias) {
+            Some(existing) if existing == canonical => Ok(()),
+            Some(existing) => Err(ActorAliasError::Conflict {
+                alias: alias.to_string(),
+                first: existing.clone(),
+                second: canonical.to_string(),
+            }),
+            None => {
+                self.link(canonical, alias);
+                Ok(())
+            }
+        }
+    }
+
+    /// Like `insert`, but a newer claim on `alias` replaces the old one.
+    fn reassign(&mut self, canonical: &str, alias: &str) {
+        if canonical.is_empty()
+            || alias.is_empty()
+            || alias == canonical
+            || self.aliases.contains_key(alias)
+            || self.canonical.contains_key(canonical)
+        {
+            return;
+        }
+        if let Some(previous) = self.canonical.remove(alias) {
+            if let Some(list) = self.aliases.get_mut(&previous) {
+                list.retain(|a| a != alias);
+                if list.is_empty() {
+                    self.aliases.remove(&previous);
+                }
+            }
+        }
+        self.link(canonical, alias);
+    }
+
+    fn link(&mut self, canonical: &str, alias: &str) {
+        let list = self.aliases.entry(canonical.to_string()).or_default();
+        if let Err(at) = list.binary_search_by(|a| a.as_str().cmp(alias)) {
+            list.insert(at, alias.to_string());
+        }
+        self.canonical
+            .insert(alias.to_string(), canonical.to_string());
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.aliases.is_empty()
+    }
+
+    /// Number of identities with at least one alias
+    pub fn len(&self) -> usize {
+        self.aliases.len()
+    }
+
+    /// Canonical actor for `actor`, or `actor` itself when it is not an alias
+    pub fn normalize<'a>(&'a self, actor: &'a str) -> &'a str {
+        self.canonical.get(actor).map_or(actor, String::as_str)
+    }
+
+    /// Every spelling of `actor`'s identity: the canonical actor first, then
+    /// its aliases in order. Unknown actors yield just themselves.
+    pub fn alias_set<'a>(&'a self, actor: &'a str) -> Vec<&'a str> {
+        let canonical = self.normalize(actor);
+        let mut set = vec![canonical];
+        if let Some(aliases) = self.aliases.get(canonical) {
+            set.extend(aliases.iter().map(String::as_str));
+        }
+        set
+    }
+
+    /// Expand each actor to its alias set, keeping first-seen order
+    pub fn expand_actors(&self, actors: &[String]) -> Vec<String> {
+        let mut expanded: Vec<String> = Vec::new();
+        for actor in actors {
+            for name in self.alias_set(actor) {
+                if !expanded.iter().any(|e| e == name) {
+                    expanded.push(name.to_string());
+                }
+            }
+        }
+        expanded
+    }
+
+    /// `filter` with its actors expanded and the flag cleared when it sets
+    /// [`AxFilter::expand_actor_aliases`]; otherwise an unchanged copy
+    pub fn expand_filter(&self, filter: &AxFilter) -> AxFilter {
+        let mut expanded = filter.clone();
+        if filter.expand_actor_aliases {
+            expanded.actors = self.expand_actors(&filter.actors);
+            expanded.expand_actor_aliases = false;
+        }
+        expanded
+    }
+}
+
+impl TryFrom<BTreeMap<String, Vec<String>>> for ActorAliasMap {
+    type Error = ActorAliasError;
+
+    fn try_from(definitions: BTreeMap<String, Vec<String>>) -> Result<Self, Self::Error> {
+        let mut map = Self::new();
+        for (canonical, aliases) in &definitions {
+            if canonical.is_empty() {
+                return Err(ActorAliasError::EmptyActor);
+            }
+            for alias in aliases {
+                map.insert(canonical, alias)?;
+            }
+        }
+        Ok(map)
+    }
+}
+
+impl From<ActorAliasMap> for BTreeMap<String, Vec<String>> {
+    fn from(map: ActorAliasMap) -> Self {
+        map.aliases
+    }
+}
+
+/// Canonical actor for `actor` under `aliases`
+pub fn normalize_actor<'a>(actor: &'a str, aliases: &'a ActorAliasMap) -> &'a str {
+    aliases.normalize(actor)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::attestation::AttestationBuilder;
+    use crate::storage::{AttestationStore, MemoryStore, QueryStore};
+
+    fn alice() -> ActorAliasMap {
+        ActorAliasMap::from_json(r#"{"human:alice": ["alice", "human:alice@verified"]}"#).unwrap()
+    }
+
+    #[test]
+    fn test_normalize() {
+        let map = alice();
+        assert_eq!(normalize_actor("alice", &map), "human:alice");
+        assert_eq!(normalize_actor("human:alice@verified", &map), "human:alice");
+        assert_eq!(normalize_actor("human:alice", &map), "human:alice");
+        assert_eq!(normalize_actor("bob", &map), "bob");
+        assert_eq!(
+            map.alias_set("alice"),
+            vec!["human:alice", "alice", "human:alice@verified"]
+        );
+    }
+
+    #[test]
+    fn test_json_rejects_conflicts_and_chains() {
+        assert!(matches!(
+            ActorAliasMap::from_json(r#"{"human:alice": ["alice"], "human:al": ["alice"]}"#),
+            Err(ActorAliasError::Parse(_))
+        ));
+        let mut map = alice();
+        assert_eq!(
+            map.insert("human:al", "alice"),
+            Err(ActorAliasError::Conflict {
+                alias: "alice".into(),
+                first: "human:alice".into(),
+                second: "human:al".into(),
+            })
+        );
+        assert_eq!(
+            map.insert("alice", "al"),
+            Err(ActorAliasError::Chained("alice".into()))
+        );
+
+        let json = serde_json::to_string(&map).unwrap();
+        assert_eq!(ActorAliasMap::from_json(&json).unwrap(), map);
+    }
+
+    #[test]
+    fn test_from_attestations_newest_wins() {
+        let older = AttestationBuilder::new()
+            .id("AS-1")
+            .subject("alice")
+            .predicate(ACTOR_ALIAS_PREDICATE)
+            .context("human:alice.old")
+            .timestamp(1)
+            .build();
+        let newer = AttestationBuilder::new()
+            .id("AS-2")
+            .subject("alice")
+            .subject("human:alice@verified")
+            .predicate(ACTOR_ALIAS_PREDICATE)
+            .context("human:alice")
+            .timestamp(2)
+            .build();
+        let map = ActorAliasMap::from_attestations([&newer, &older]);
+        assert_eq!(map, alice());
+    }
+
+    #[test]
+    fn test_expanded_query_finds_every_alias() {
+        let mut store = MemoryStore::new();
+        for (i, actor) in ["alice", "human:alice", "human:alice@verified", "bob"]
+            .iter()
+            .enumerate()
+        {
+            store
+                .put(
+                    AttestationBuilder::new()
+                        .id(format!("AS-{}", i))
+                        .subject("PROJECT")
+                        .predicate("reviewed")
+                        .context("qntx")
+                        .actor(*actor)
+                        .build(),
+                )
+                .unwrap();
+        }
+
+        let map = alice();
+        let mut filter = AxFilter {
+            actors: vec!["alice".into()],
+            ..Default::default()
+        };
+        let plain = store.query(&map.expand_filter(&filter)).unwrap();
+        assert_eq!(plain.attestations.len(), 1);
+
+        filter.expand_actor_aliases = true;
+        assert!(store.query(&filter).is_err());
+        assert!(!map.expand_filter(&filter).expand_actor_aliases);
+        let expanded = store.query(&map.expand_filter(&filter)).unwrap();
+        let mut ids: Vec<&str> = expanded
+            .attestations
+            .iter()
+            .map(|a| a.id.as_str())
+            .collect();
+        ids.sort();
+        assert_eq!(ids, vec!["AS-0", "AS-1", "AS-2"]);
+    }
+}
@@ -0,0 +1,533 @@
+//! Field-level diff and patch between two attestations
+//!
+//! [`diff`] records what changed from one attestation to another: values
+//! added to or removed from each slot, attribute keys added, removed or
+//! modified, and timestamp/source changes. [`apply_patch`] replays such a
+//! diff on a base attestation. Every change carries the value it expects to
+//! find, so a patch made against one version of a claim is refused (with a
+//! [`PatchConflict`]) when applied to a base that has since moved on.
+//!
+//! Slots are compared as sets: reordering values is not a change. A diff can
+//! be trimmed before applying it, e.g. to accept only part of a proposed
+//! correction.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+use super::Attestation;
+
+/// Values added to and removed from one slot, in their original order.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SlotDiff {
+    #[serde(default)]
+    pub added: Vec<String>,
+    #[serde(default)]
+    pub removed: Vec<String>,
+}
+
+impl SlotDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty()
+    }
+
+    fn between(old: &[String], new: &[String]) -> Self {
+        SlotDiff {
+            added: new.iter().filter(|v| !old.contains(v)).cloned().collect(),
+            removed: old.iter().filter(|v| !new.contains(v)).cloned().collect(),
+        }
+    }
+}
+
+/// A value that changed from `old` to `new`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Change<T> {
+    pub old: T,
+    pub new: T,
+}
+
+/// Attribute changes by key. Removed entries keep the old value so a patch
+/// can check it before removing.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AttributeDiff {
+    #[serde(default)]
+    pub added: BTreeMap<String, Value>,
+    #[serde(default)]
+    pub removed: BTreeMap<String, Value>,
+    #[serde(default)]
+    pub modified: BTreeMap<String, Change<Value>>,
+}
+
+impl AttributeDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.modified.is_empty()
+    }
+
+    /// Differences between two attribute maps, keys sorted.
+    pub fn between<'a>(
+        old: impl IntoIterator<Item = (&'a String, &'a Value)>,
+        new: impl IntoIterator<Item = (&'a String, &'a Value)>,
+    ) -> Self {
+        let old: BTreeMap<&String, &Value> = old.into_iter().collect();
+        let new: BTreeMap<&String, &Value> = new.into_iter().collect();
+        let mut diff = AttributeDiff::default();
+        for (key, old_value) in &old {
+            match new.get(key) {
+                None => {
+                    diff.removed.insert((*key).clone(), (*old_value).clone());
+                }
+                Some(new_value) if new_value != old_value => {
+                    diff.modified.insert(
+                        (*key).clone(),
+                        Change {
+                            old: (*old_value).clone(),
+                            new: (*new_value).clone(),
+                        },
+                    );
+                }
+                Some(_) => {}
+            }
+        }
+        for (key, new_value) in &new {
+            if !old.contains_key(key) {
+                diff.added.insert((*key).clone(), (*new_value).clone());
+            }
+        }
+        diff
+    }
+}
+
+/// Everything that differs between two attestations' claims. `id` and
+/// storage metadata (`created_at`, `revision`, `confidence`, signature) are
+/// not compared.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AttestationDiff {
+    #[serde(default)]
+    pub subjects: SlotDiff,
+    #[serde(default)]
+    pub predicates: SlotDiff,
+    #[serde(default)]
+    pub contexts: SlotDiff,
+    #[serde(default)]
+    pub actors: SlotDiff,
+    #[serde(default)]
+    pub attributes: AttributeDiff,
+    #[serde(default)]
+    pub timestamp: Option<Change<i64>>,
+    #[serde(default)]
+    pub source: Option<Change<String>>,
+}
+
+impl AttestationDiff {
+    pub fn is_empty(&self) -> bool {
+        self.subjects.is_empty()
+            && self.predicates.is_empty()
+            && self.contexts.is_empty()
+            && self.actors.is_empty()
+            && self.attributes.is_empty()
+            && self.timestamp.is_none()
+            && self.source.is_none()
+    }
+}
+
+/// One change whose expectation did not hold on the base. `field` is a slot
+/// name, `attributes.<key>`, `timestamp` or `source`; `None` means absent.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PatchMismatch {
+    pub field: String,
+    pub expected: Option<Value>,
+    pub actual: Option<Value>,
+}
+
+/// A patch that does not fit its base, with every mismatch found.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, thiserror::Error)]
+#[error("patch conflicts with base attestation on {}", fields(.mismatches))]
+pub struct PatchConflict {
+    pub mismatches: Vec<PatchMismatch>,
+}
+
+fn fields(mismatches: &[PatchMismatch]) -> String {
+    let mut names: Vec<&str> = mismatches.iter().map(|m| m.field.as_str()).collect();
+    names.dedup();
+    names.join(", ")
+}
+
+/// Field-level differences taking `a` to `b`.
+pub fn diff(a: &Attestation, b: &Attestation) -> AttestationDiff {
+    AttestationDiff {
+        subjects: SlotDiff::between(&a.subjects, &b.subjects),
+        predicates: SlotDiff::between(&a.predicates, &b.predicates),
+        contexts: SlotDiff::between(&a.contexts, &b.contexts),
+        actors: SlotDiff::between(&a.actors, &b.actors),
+        attributes: AttributeDiff::between(&a.attributes, &b.attributes),
+        timestamp: (a.timestamp != b.timestamp).then_some(Change {
+            old: a.timestamp,
+            new: b.timestamp,
+        }),
+        source: (a.source != b.source).then(|| Change {
+            old: a.source.clone(),
+            new: b.source.clone(),
+        }),
+    }
+}
+
+/// Apply `patch` to `base`. Removed slot values must be present and added
+/// ones absent; attribute and metadata changes must find their old value.
+/// Otherwise nothing is applied and every mismatch is returned.
+///
+/// The result keeps `base`'s id and storage metadata. A non-empty patch
+/// drops the signature, which no longer covers the content.
+pub fn apply_patch(
+    base: &Attestation,
+    patch: &AttestationDiff,
+) -> Result<Attestation, PatchConflict> {
+    let mut mismatches = Vec::new();
+    let mut patched = base.clone();
+
+    for (name, slot, change) in [
+        ("subjects", &mut patched.subjects, &patch.subjects),
+        ("predicates", &mut patched.predicates, &patch.predicates),
+        ("contexts", &mut patched.contexts, &patch.contexts),
+        ("actors", &mut patched.actors, &patch.actors),
+    ] {
+        patch_slot(name, slot, change, &mut mismatches);
+    }
+
+    let attributes = &mut patched.attributes;
+    for (key, old) in &patch.attributes.removed {
+        if check(
+            &format!("attributes.{}", key),
+            Some(old),
+            attributes.get(key),
+            &mut mismatches,
+        ) {
+            attributes.remove(key);
+        }
+    }
+    for (key, change) in &patch.attributes.modified {
+        if check(
+            &format!("attributes.{}", key),
+            Some(&change.old),
+            attributes.get(key),
+            &mut mismatches,
+        ) {
+            attributes.insert(key.clone(), change.new.clone());
+        }
+    }
+    for (key, new) in &patch.attributes.added {
+        if check(
+            &format!("attributes.{}", key),
+            None,
+            attributes.get(key),
+            &mut mismatches,
+        ) {
+            attributes.insert(key.clone(), new.clone());
+        }
+    }
+
+    if let Some(change) = &patch.timestamp {
+        if base.timestamp != change.old {
+            mismatches.push(PatchMismatch {
+                field: "timestamp".to_string(),
+                expected: Some(change.old.into()),
+                actual: Some(base.timestamp.into()),
+            });
+        }
+        patched.timestamp = change.new;
+    }
+    if let Some(change) = &patch.source {
+        if base.source != change.old {
+            mismatches.push(PatchMismatch {
+                field: "source".to_string(),
+                expected: Some(change.old.clone().into()),
+                actual: Some(base.source.clone().into()),
+            });
+        }
+        patched.source = change.new.clone();
+    }
+
+    if !mismatches.is_empty() {
+        return Err(PatchConflict { mismatches });
+    }
+    if !patch.is_empty() {
+        patched.signature = None;
+        patched.signer_did = None;
+    }
+    Ok(patched)
+}
+
+fn patch_slot(
+    name: &str,
+    slot: &mut Vec<String>,
+    change: &SlotDiff,
+    mismatches: &mut Vec<PatchMismatch>,
+) {
+    for value in &change.removed {
+        match slot.iter().position(|v| v == value) {
+            Some(i) => {
+                slot.remove(i);
+            }
+            None => mismatches.push(PatchMismatch {
+                field: name.to_string(),
+                expected: Some(value.clone().into()),
+                actual: None,
+            }),
+        }
+    }
+    for value in &change.added {
+        if slot.contains(value) {
+            mismatches.push(PatchMismatch {
+                field: name.to_string(),
+                expected: None,
+                actual: Some(value.clone().into()),
+            });
+        } else {
+            slot.push(value.clone());
+        }
+    }
+}
+
+/// Record a mismatch unless `actual` is `expected`; returns whether it was.
+fn check(
+    field: &str,
+    expected: Option<&Value>,
+    actual: Option<&Value>,
+    mismatches: &mut Vec<PatchMismatch>,
+) -> bool {
+    if expected == actual {
+        return true;
+    }
+    mismatches.push(PatchMismatch {
+        field: field.to_string(),
+        expected: expected.cloned(),
+        actual: actual.cloned(),
+    });
+    false
+}
+
+fn error_json(what: &str, e: &dyn std::fmt::Display) -> String {
+    serde_json::json!({ "error": format!("{}: {}", what, e) }).to_string()
+}
+
+/// JSON entry point for [`diff`]: two attestation JSON objects in, the
+/// [`AttestationDiff`] JSON out, or `{"error":"..."}`.
+pub fn diff_attestations_json(a_json: &str, b_json: &str) -> String {
+    let a: Attestation = match serde_json::from_str(a_json) {
+        Ok(a) => a,
+        Err(e) => return error_json("invalid attestation a", &e),
+    };
+    let b: Attestation = match serde_json::from_str(b_json) {
+        Ok(b) => b,
+        Err(e) => return error_json("invalid attestation b", &e),
+    };
+    serde_json::to_string(&diff(&a, &b)).unwrap_or_else(|e| error_json("serialization failed", &e))
+}
+
+/// JSON entry point for [`apply_patch`]. Returns the patched attestation, or
+/// `{"error":"...","conflict":{"mismatches":[...]}}` when the patch doesn't
+/// fit the base.
+pub fn apply_attestation_patch_json(base_json: &str, diff_json: &str) -> String {
+    let base: Attestation = match serde_json::from_str(base_json) {
+        Ok(a) => a,
+        Err(e) => return error_json("invalid base attestation", &e),
+    };
+    let patch: AttestationDiff = match serde_json::from_str(diff_json) {
+        Ok(d) => d,
+        Err(e) => return error_json("invalid attestation diff", &e),
+    };
+    match apply_patch(&base, &patch) {
+        Ok(patched) => serde_json::to_string(&patched)
+            .unwrap_or_else(|e| error_json("serialization failed", &e)),
+        Err(conflict) => serde_json::json!({
+            "error": conflict.to_string(),
+            "conflict": conflict,
+        })
+        .to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::attestation::AttestationBuilder;
+    use serde_json::json;
+
+    fn before() -> Attestation {
+        AttestationBuilder::new()
+            .id("AS-1")
+            .subject("LUKE")
+            .predicate("trained_by")
+            .context("DAGOBAH")
+            .actor("human:yoda")
+            .timestamp(1000)
+            .source("cli")
+            .attribute("rank", json!("padawan"))
+            .attribute("hours", json!(12))
+            .build()
+    }
+
+    fn after() -> Attestation {
+        AttestationBuilder::new()
+            .id("AS-2")
+            .subject("LUKE")
+            .predicate("trained_by")
+            .context("BESPIN")
+            .actors(["human:yoda", "human:obiwan"])
+            .timestamp(2000)
+            .source("review")
+            .attribute("rank", json!("knight"))
+            .attribute("lightsaber", json!("green"))
+            .build()
+    }
+
+    #[test]
+    fn test_diff_captures_slots_attributes_and_metadata() {
+        let d = diff(&before(), &after());
+        assert!(d.subjects.is_empty());
+        assert_eq!(d.contexts.added, vec!["BESPIN"]);
+        assert_eq!(d.contexts.removed, vec!["DAGOBAH"]);
+        assert_eq!(d.actors.added, vec!["human:obiwan"]);
+        assert_eq!(d.attributes.added["lightsaber"], json!("green"));
+        assert_eq!(d.attributes.removed["hours"], json!(12));
+        assert_eq!(
+            d.attributes.modified["rank"],
+            Change {
+                old: json!("padawan"),
+                new: json!("knight")
+            }
+        );
+        assert_eq!(
+            d.timestamp,
+            Some(Change {
+                old: 1000,
+                new: 2000
+            })
+        );
+        assert_eq!(d.source.as_ref().unwrap().new, "review");
+    }
+
+    #[test]
+    fn test_round_trip_reproduces_target() {
+        let (a, b) = (before(), after());
+        let patched = apply_patch(&a, &diff(&a, &b)).unwrap();
+        assert_eq!(
+            patched,
+            Attestation {
+                id: a.id.clone(),
+                ..b
+            }
+        );
+
+        // Through the JSON entry points as well
+        let d = diff_attestations_json(
+            &serde_json::to_string(&a).unwrap(),
+            &serde_json::to_string(&after()).unwrap(),
+        );
+        let patched: Attestation = serde_json::from_str(&apply_attestation_patch_json(
+            &serde_json::to_string(&a).unwrap(),
+            &d,
+        ))
+        .unwrap();
+        assert!(diff(&patched, &after()).is_empty());
+    }
+
+    #[test]
+    fn test_identical_attestations_diff_empty() {
+        let a = before();
+        let mut copy = a.clone();
+        copy.id = "AS-9".to_string();
+        copy.revision = 4;
+        let d = diff(&a, &copy);
+        assert!(d.is_empty());
+        assert_eq!(apply_patch(&a, &d).unwrap(), a);
+    }
+
+    #[test]
+    fn test_conflict_reports_exact_mismatches() {
+        let patch = diff(&before(), &after());
+        let mut moved_on = before();
+        moved_on.contexts = vec!["HOTH".to_string()];
+        moved_on
+            .attributes
+            .insert("rank".to_string(), json!("master"));
+        moved_on
+            .attributes
+            .insert("lightsaber".to_string(), json!("blue"));
+
+        let conflict = apply_patch(&moved_on, &patch).unwrap_err();
+        assert_eq!(
+            conflict.mismatches,
+            vec![
+                PatchMismatch {
+                    field: "contexts".to_string(),
+                    expected: Some(json!("DAGOBAH")),
+                    actual: None,
+                },
+                PatchMismatch {
+                    field: "attributes.rank".to_string(),
+                    expected: Some(json!("padawan")),
+                    actual: Some(json!("master")),
+                },
+                PatchMismatch {
+                    field: "attributes.lightsaber".to_string(),
+                    expected: None,
+                    actual: Some(json!("blue")),
+                },
+            ]
+        );
+        assert_eq!(
+            conflict.to_string(),
+            "patch conflicts with base attestation on contexts, attributes.rank, attributes.lightsaber"
+        );
+
+        let result: Value = serde_json::from_str(&apply_attestation_patch_json(
+            &serde_json::to_string(&moved_on).unwrap(),
+            &serde_json::to_string(&patch).unwrap(),
+        ))
+        .unwrap();
+        assert_eq!(
+            result["conflict"]["mismatches"][1]["field"],
+            "attributes.rank"
+        );
+    }
+
+    #[test]
+    fn test_partial_patch_and_signature() {
+        let mut base = before();
+        base.signature = Some(vec![1, 2, 3]);
+        let mut patch = diff(&base, &after());
+        // Reviewer accepts only the rank correction
+        patch = AttestationDiff {
+            attributes: AttributeDiff {
+                modified: std::mem::take(&mut patch.attributes.modified),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let patched = apply_patch(&base, &patch).unwrap();
+        assert_eq!(patched.attributes["rank"], json!("knight"));
+        assert_eq!(patched.attributes["hours"], json!(12));
+        assert_eq!(patched.contexts, vec!["DAGOBAH"]);
+        assert!(patched.signature.is_none());
+    }
+
+    #[test]
+    fn test_diff_json_schema_is_stable() {
+        let d: Value =
+            serde_json::from_str(&serde_json::to_string(&AttestationDiff::default()).unwrap())
+                .unwrap();
+        assert_eq!(
+            d,
+            json!({
+                "subjects": {"added": [], "removed": []},
+                "predicates": {"added": [], "removed": []},
+                "contexts": {"added": [], "removed": []},
+                "actors": {"added": [], "removed": []},
+                "attributes": {"added": {}, "removed": {}, "modified": {}},
+                "timestamp": null,
+                "source": null
+            })
+        );
+        assert!(diff_attestations_json("{}", "not json").contains("invalid attestation"));
+    }
+}
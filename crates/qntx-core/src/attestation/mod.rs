@@ -18,6 +18,16 @@
 //! assert_eq!(attestation.subjects, vec!["ALICE"]);
 //! ```
 
+mod diff;
 mod types;
 
-pub use types::{Attestation, AttestationBuilder, AxFilter, AxResult, AxSummary, Conflict};
+pub use diff::{
+    apply_attestation_patch_json, apply_patch, diff, diff_attestations_json, AttestationDiff,
+    AttributeDiff, Change, PatchConflict, PatchMismatch, SlotDiff,
+};
+
+pub use types::{
+    confidence_bucket, Attestation, AttestationBuilder, AttrCondition, AttrOp, AttrValue, AxFilter,
+    AxResult, AxSummary, Conflict, MatchingSummary, OverFilter, TermCount, CONFIDENCE_BUCKETS,
+    SUMMARY_TOP_N,
+};
@@ -47,6 +47,21 @@ pub struct Attestation {
     /// did:key of the signing node
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub signer_did: Option<String>,
+
+    /// Storage revision for optimistic concurrency: 1 when first stored,
+    /// incremented by every update, 0 if never stored. Not part of the claim,
+    /// so it is excluded from content identity (hashing and signing).
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub revision: u64,
+
+    /// Creator-assigned confidence in the claim, 0.0–1.0 (e.g. a detector
+    /// score). Metadata like `revision`: excluded from content identity.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub confidence: Option<f32>,
+}
+
+fn is_zero(n: &u64) -> bool {
+    *n == 0
 }
 
 impl Attestation {
@@ -68,6 +83,31 @@ impl Attestation {
     pub fn cartesian_count(&self) -> usize {
         self.subjects.len() * self.predicates.len() * self.contexts.len()
     }
+
+    /// Check that `confidence`, if set, lies in 0.0–1.0. Stores reject
+    /// attestations that fail this on write.
+    pub fn validate_confidence(&self) -> Result<(), String> {
+        match self.confidence {
+            Some(c) if !(0.0..=1.0).contains(&c) => Err(format!(
+                "attestation {}: confidence {} is outside 0.0-1.0",
+                self.id, c
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Hex SHA-256 of the canonical JSON of the claim, leaving out storage
+    /// metadata (`created_at`, `revision`, `confidence`). Equal for copies of
+    /// the same content in any store.
+    pub fn content_hash(&self) -> String {
+        let mut value = serde_json::to_value(self).unwrap_or_default();
+        if let Some(fields) = value.as_object_mut() {
+            fields.remove("created_at");
+            fields.remove("revision");
+            fields.remove("confidence");
+        }
+        crate::canonical::canonical_hash_hex(&value)
+    }
 }
 
 impl Default for Attestation {
@@ -84,6 +124,8 @@ impl Default for Attestation {
             created_at: 0,
             signature: None,
             signer_did: None,
+            revision: 0,
+            confidence: None,
         }
     }
 }
@@ -190,6 +232,18 @@ impl AttestationBuilder {
         self
     }
 
+    pub fn revision(mut self, revision: u64) -> Self {
+        self.attestation.revision = revision;
+        self
+    }
+
+    /// Creator-assigned confidence, 0.0–1.0. Not checked here; stores
+    /// reject out-of-range values.
+    pub fn confidence(mut self, confidence: f32) -> Self {
+        self.attestation.confidence = Some(confidence);
+        self
+    }
+
     pub fn build(self) -> Attestation {
         self.attestation
     }
@@ -223,8 +277,222 @@ pub struct AxFilter {
     /// Filter by source (exact match, e.g., "cli", "distill")
     pub source: Option<String>,
 
+    /// Minimum creator-assigned confidence; attestations without one don't match
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_confidence: Option<f32>,
+
     /// Maximum results
     pub limit: Option<usize>,
+
+    /// Only keep (subject, predicate, context) groups spanning at least this long
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub over: Option<OverFilter>,
+
+    /// Also aggregate the full matching set (before `limit`) into `AxSummary::matching`.
+    /// Off by default; when off, stores run no extra work.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub include_summary: bool,
+
+    /// Match every alias of each queried actor. Stores hold no alias map and
+    /// reject a filter with this set; callers apply it first with
+    /// [`ActorAliasMap::expand_filter`](crate::actor_alias::ActorAliasMap::expand_filter),
+    /// which clears it.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub expand_actor_aliases: bool,
+
+    /// Conditions on `attributes`; an attestation must satisfy all of them
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub attribute_conditions: Vec<AttrCondition>,
+}
+
+impl AxFilter {
+    /// Whether `attributes` satisfy every attribute condition.
+    pub fn matches_attributes(&self, attributes: &HashMap<String, serde_json::Value>) -> bool {
+        self.attribute_conditions
+            .iter()
+            .all(|c| c.matches(attributes))
+    }
+
+    /// Check that `expand_actor_aliases` has been applied. Stores reject
+    /// filters that fail this rather than match only the literal actors.
+    pub fn validate_aliases_expanded(&self) -> Result<(), String> {
+        if self.expand_actor_aliases {
+            return Err(
+                "expand_actor_aliases must be applied with an actor alias map before querying"
+                    .into(),
+            );
+        }
+        Ok(())
+    }
+}
+
+/// "over 5y" semantics for a query.
+///
+/// Attestations that pass every other filter (including `time_start`/`time_end`)
+/// are grouped by each (subject, predicate, context) they carry. A group
+/// qualifies when its latest timestamp minus its earliest is at least
+/// `min_span_ms`. The span is therefore measured *within* the time range, not
+/// across the whole history. An attestation is kept if any of its groups
+/// qualifies. `limit` applies after this step.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OverFilter {
+    /// Minimum span between earliest and latest attestation in a group (ms)
+    pub min_span_ms: i64,
+}
+
+impl OverFilter {
+    pub fn new(min_span_ms: i64) -> Self {
+        Self { min_span_ms }
+    }
+
+    /// Keep only attestations belonging to a group whose span meets the minimum.
+    /// Input order is preserved.
+    pub fn retain_spanning(&self, attestations: Vec<Attestation>) -> Vec<Attestation> {
+        let mut spans: HashMap<(&str, &str, &str), (i64, i64)> = HashMap::new();
+        for a in &attestations {
+            for key in group_keys(a) {
+                let span = spans.entry(key).or_insert((a.timestamp, a.timestamp));
+                span.0 = span.0.min(a.timestamp);
+                span.1 = span.1.max(a.timestamp);
+            }
+        }
+
+        let keep: Vec<bool> = attestations
+            .iter()
+            .map(|a| {
+                group_keys(a).any(|key| {
+                    spans
+                        .get(&key)
+                        .is_some_and(|(min, max)| max - min >= self.min_span_ms)
+                })
+            })
+            .collect();
+
+        attestations
+            .into_iter()
+            .zip(keep)
+            .filter_map(|(a, keep)| keep.then_some(a))
+            .collect()
+    }
+}
+
+/// Every (subject, predicate, context) combination an attestation asserts.
+fn group_keys(a: &Attestation) -> impl Iterator<Item = (&str, &str, &str)> {
+    a.subjects.iter().flat_map(move |s| {
+        a.predicates.iter().flat_map(move |p| {
+            a.contexts
+                .iter()
+                .map(move |c| (s.as_str(), p.as_str(), c.as_str()))
+        })
+    })
+}
+
+/// A condition on one attestation attribute, e.g. `severity >= 3`.
+///
+/// The type of `value` decides the comparison, with no coercion:
+///
+/// - a number compares numerically, and only against number attributes
+/// - a string compares byte-wise, and only against string attributes
+/// - a bool compares only against bool attributes (`false < true`)
+///
+/// An attribute of another type fails every operator except `ne`. A missing
+/// attribute fails every operator except `exists` with `false`; an attribute
+/// holding JSON `null` is present.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AttrCondition {
+    pub key: String,
+    pub op: AttrOp,
+    pub value: AttrValue,
+}
+
+/// Comparison applied by an [`AttrCondition`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AttrOp {
+    Eq,
+    Ne,
+    Gt,
+    Gte,
+    Lt,
+    Lte,
+    /// Substring of a string attribute, or an element of an array attribute
+    Contains,
+    /// Key presence; the value is a bool, `false` matching absent keys
+    Exists,
+}
+
+impl AttrOp {
+    /// Operator as written in an AX `where` clause
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AttrOp::Eq => "==",
+            AttrOp::Ne => "!=",
+            AttrOp::Gt => ">",
+            AttrOp::Gte => ">=",
+            AttrOp::Lt => "<",
+            AttrOp::Lte => "<=",
+            AttrOp::Contains => "contains",
+            AttrOp::Exists => "exists",
+        }
+    }
+}
+
+/// Typed operand of an [`AttrCondition`]; serialized as a plain JSON value
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum AttrValue {
+    Bool(bool),
+    Number(f64),
+    String(String),
+}
+
+impl AttrValue {
+    /// Compare with an attribute of the same type; `None` across types.
+    fn compare(&self, attribute: &serde_json::Value) -> Option<std::cmp::Ordering> {
+        use serde_json::Value;
+        match (self, attribute) {
+            (AttrValue::Bool(v), Value::Bool(a)) => Some(a.cmp(v)),
+            (AttrValue::Number(v), Value::Number(a)) => a.as_f64()?.partial_cmp(v),
+            (AttrValue::String(v), Value::String(a)) => Some(a.as_str().cmp(v.as_str())),
+            _ => None,
+        }
+    }
+}
+
+impl AttrCondition {
+    pub fn new(key: impl Into<String>, op: AttrOp, value: AttrValue) -> Self {
+        Self {
+            key: key.into(),
+            op,
+            value,
+        }
+    }
+
+    /// Evaluate against an attestation's attributes.
+    pub fn matches(&self, attributes: &HashMap<String, serde_json::Value>) -> bool {
+        use std::cmp::Ordering;
+
+        let Some(attribute) = attributes.get(&self.key) else {
+            return self.op == AttrOp::Exists && self.value == AttrValue::Bool(false);
+        };
+        let ordering = || self.value.compare(attribute);
+        match self.op {
+            AttrOp::Exists => self.value != AttrValue::Bool(false),
+            AttrOp::Eq => ordering() == Some(Ordering::Equal),
+            AttrOp::Ne => ordering() != Some(Ordering::Equal),
+            AttrOp::Gt => ordering() == Some(Ordering::Greater),
+            AttrOp::Gte => matches!(ordering(), Some(Ordering::Greater | Ordering::Equal)),
+            AttrOp::Lt => ordering() == Some(Ordering::Less),
+            AttrOp::Lte => matches!(ordering(), Some(Ordering::Less | Ordering::Equal)),
+            AttrOp::Contains => match (attribute, &self.value) {
+                (serde_json::Value::String(a), AttrValue::String(v)) => a.contains(v.as_str()),
+                (serde_json::Value::Array(items), _) => items
+                    .iter()
+                    .any(|item| self.value.compare(item) == Some(Ordering::Equal)),
+                _ => false,
+            },
+        }
+    }
 }
 
 /// Result of an ax query
@@ -241,6 +509,9 @@ pub struct AxResult {
 }
 
 /// Aggregated information about query results
+///
+/// The `total_attestations` and `unique_*` counts describe the returned rows.
+/// `matching` describes everything the filter matched, ignoring `limit`.
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct AxSummary {
     pub total_attestations: usize,
@@ -248,6 +519,110 @@ pub struct AxSummary {
     pub unique_predicates: HashMap<String, usize>,
     pub unique_contexts: HashMap<String, usize>,
     pub unique_actors: HashMap<String, usize>,
+
+    /// Present only when the filter set `include_summary`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub matching: Option<MatchingSummary>,
+}
+
+/// Number of entries kept in `MatchingSummary::top_predicates` / `top_contexts`
+pub const SUMMARY_TOP_N: usize = 10;
+
+/// Aggregates over the full matching set of a query, before `limit`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MatchingSummary {
+    /// Matching attestations before `limit` ("showing 50 of 3,214")
+    pub total: usize,
+    pub distinct_subjects: usize,
+    pub distinct_predicates: usize,
+    pub distinct_contexts: usize,
+    pub distinct_actors: usize,
+    /// Most common predicates by number of matching attestations, at most `SUMMARY_TOP_N`
+    pub top_predicates: Vec<TermCount>,
+    /// Most common contexts by number of matching attestations, at most `SUMMARY_TOP_N`
+    pub top_contexts: Vec<TermCount>,
+    /// Earliest attestation timestamp (Unix ms); `None` when nothing matched
+    pub earliest: Option<i64>,
+    /// Latest attestation timestamp (Unix ms); `None` when nothing matched
+    pub latest: Option<i64>,
+    /// Matching attestations per confidence decile (`[0.0, 0.1)` … `[0.9, 1.0]`);
+    /// empty when none of them carries a confidence
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub confidence_histogram: Vec<usize>,
+}
+
+/// Number of buckets in `MatchingSummary::confidence_histogram`
+pub const CONFIDENCE_BUCKETS: usize = 10;
+
+/// Histogram bucket of a confidence value; 1.0 falls in the last bucket.
+pub fn confidence_bucket(confidence: f32) -> usize {
+    ((confidence * CONFIDENCE_BUCKETS as f32) as usize).min(CONFIDENCE_BUCKETS - 1)
+}
+
+/// A term and the number of attestations carrying it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TermCount {
+    pub value: String,
+    pub count: usize,
+}
+
+impl MatchingSummary {
+    /// Aggregate a matching set in one pass. Used by scan-based stores; SQL
+    /// stores compute the same figures with aggregate queries.
+    pub fn from_attestations<'a>(attestations: impl IntoIterator<Item = &'a Attestation>) -> Self {
+        use std::collections::HashSet;
+
+        let mut summary = MatchingSummary::default();
+        let mut subjects = HashSet::new();
+        let mut actors = HashSet::new();
+        let mut predicates: HashMap<&str, usize> = HashMap::new();
+        let mut contexts: HashMap<&str, usize> = HashMap::new();
+
+        for a in attestations {
+            summary.total += 1;
+            summary.earliest = Some(summary.earliest.map_or(a.timestamp, |t| t.min(a.timestamp)));
+            summary.latest = Some(summary.latest.map_or(a.timestamp, |t| t.max(a.timestamp)));
+            subjects.extend(a.subjects.iter().map(String::as_str));
+            actors.extend(a.actors.iter().map(String::as_str));
+            // Count each term once per attestation, even if listed twice
+            let own: HashSet<&str> = a.predicates.iter().map(String::as_str).collect();
+            for p in own {
+                *predicates.entry(p).or_insert(0) += 1;
+            }
+            let own: HashSet<&str> = a.contexts.iter().map(String::as_str).collect();
+            for c in own {
+                *contexts.entry(c).or_insert(0) += 1;
+            }
+            if let Some(confidence) = a.confidence {
+                if summary.confidence_histogram.is_empty() {
+                    summary.confidence_histogram = vec![0; CONFIDENCE_BUCKETS];
+                }
+                summary.confidence_histogram[confidence_bucket(confidence)] += 1;
+            }
+        }
+
+        summary.distinct_subjects = subjects.len();
+        summary.distinct_actors = actors.len();
+        summary.distinct_predicates = predicates.len();
+        summary.distinct_contexts = contexts.len();
+        summary.top_predicates = top_terms(predicates, SUMMARY_TOP_N);
+        summary.top_contexts = top_terms(contexts, SUMMARY_TOP_N);
+        summary
+    }
+}
+
+/// The `n` most frequent terms, ties broken alphabetically.
+fn top_terms(counts: HashMap<&str, usize>, n: usize) -> Vec<TermCount> {
+    let mut terms: Vec<TermCount> = counts
+        .into_iter()
+        .map(|(value, count)| TermCount {
+            value: value.to_string(),
+            count,
+        })
+        .collect();
+    terms.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.value.cmp(&b.value)));
+    terms.truncate(n);
+    terms
 }
 
 /// Conflicting attestations
@@ -330,4 +705,72 @@ mod tests {
 
         assert!(multi.has_multiple_dimensions());
     }
+
+    #[test]
+    fn test_attr_condition_operators() {
+        let attributes: HashMap<String, serde_json::Value> =
+            serde_json::from_value(serde_json::json!({
+                "severity": 3,
+                "status": "open",
+                "draft": false,
+                "tags": ["urgent", 7],
+                "note": null,
+            }))
+            .unwrap();
+        let check = |key: &str, op: AttrOp, value: serde_json::Value| {
+            let value = serde_json::from_value(value).unwrap();
+            AttrCondition::new(key, op, value).matches(&attributes)
+        };
+        use serde_json::json;
+
+        assert!(check("severity", AttrOp::Eq, json!(3)));
+        assert!(check("severity", AttrOp::Eq, json!(3.0)));
+        assert!(!check("severity", AttrOp::Eq, json!("3")));
+        assert!(check("severity", AttrOp::Ne, json!("3")));
+        assert!(!check("severity", AttrOp::Ne, json!(3)));
+        assert!(!check("missing", AttrOp::Ne, json!(3)));
+
+        assert!(check("severity", AttrOp::Gt, json!(2.5)));
+        assert!(!check("severity", AttrOp::Gt, json!(3)));
+        assert!(check("severity", AttrOp::Gte, json!(3)));
+        assert!(check("severity", AttrOp::Lt, json!(10)));
+        assert!(check("severity", AttrOp::Lte, json!(3)));
+        // Strings compare byte-wise, never against numbers
+        assert!(check("status", AttrOp::Gt, json!("closed")));
+        assert!(check("status", AttrOp::Lt, json!("p")));
+        assert!(!check("severity", AttrOp::Lt, json!("9")));
+        assert!(check("draft", AttrOp::Lt, json!(true)));
+        assert!(check("draft", AttrOp::Eq, json!(false)));
+
+        assert!(check("status", AttrOp::Contains, json!("pe")));
+        assert!(!check("status", AttrOp::Contains, json!("PE")));
+        assert!(check("tags", AttrOp::Contains, json!("urgent")));
+        assert!(check("tags", AttrOp::Contains, json!(7)));
+        assert!(!check("tags", AttrOp::Contains, json!("7")));
+        assert!(!check("severity", AttrOp::Contains, json!(3)));
+
+        assert!(check("note", AttrOp::Exists, json!(true)));
+        assert!(!check("note", AttrOp::Exists, json!(false)));
+        assert!(check("missing", AttrOp::Exists, json!(false)));
+        assert!(!check("missing", AttrOp::Exists, json!(true)));
+        assert!(!check("note", AttrOp::Eq, json!("null")));
+    }
+
+    #[test]
+    fn test_attr_condition_serde() {
+        let filter: AxFilter = serde_json::from_str(
+            r#"{"attribute_conditions":[{"key":"severity","op":"gte","value":3},
+                {"key":"status","op":"eq","value":"open"}]}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            filter.attribute_conditions,
+            vec![
+                AttrCondition::new("severity", AttrOp::Gte, AttrValue::Number(3.0)),
+                AttrCondition::new("status", AttrOp::Eq, AttrValue::String("open".into())),
+            ]
+        );
+        let json = serde_json::to_string(&AxFilter::default()).unwrap();
+        assert!(!json.contains("attribute_conditions"));
+    }
 }
@@ -0,0 +1,489 @@
+//! Self-benchmark: fixed, seeded workloads timed on the running device.
+//!
+//! Hosts run this once (e.g. on first load) to see whether parsing,
+//! classification, expansion or hashing is the slow part on a given device
+//! and adapt their limits. Inputs are generated from [`BenchConfig::seed`], so
+//! numbers are comparable across runs and devices.
+//!
+//! There is no portable clock in `wasm32-unknown-unknown`, so the caller
+//! passes one: [`run_benchmarks`] takes a `now_ms` function, and
+//! [`run_benchmarks_native`] uses `std::time::Instant`.
+//!
+//! Runtime is capped by [`BenchConfig::budget_ms`]: every workload runs at
+//! least once, further iterations only while the budget lasts.
+
+use crate::canonical::canonical_hash;
+use crate::classify::{
+    ClaimGroup, ClaimInput, ClassificationPolicy, ClassifyInput, StaleClaimsConfig,
+};
+use crate::expand::{expand_cartesian, ExpandAttestation};
+use crate::parser::Parser;
+use crate::similarity::cosine_similarity;
+use crate::vocabulary::{extract_vocabulary, VocabularyAttestation};
+use serde::{Deserialize, Serialize};
+
+/// Workload names, in the order they run
+pub const WORKLOADS: &[&str] = &[
+    "vocabulary",
+    "parse",
+    "classify",
+    "expand",
+    "canonical_hash",
+    "similarity",
+];
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BenchConfig {
+    pub seed: u64,
+    /// Timed iterations per workload, budget permitting
+    pub iterations: usize,
+    /// Soft cap on total runtime
+    pub budget_ms: f64,
+    /// Multiplier on the workload sizes; the defaults are sized for 1.0
+    pub scale: f64,
+    /// Run only these workloads; empty runs all of them
+    pub workloads: Vec<String>,
+}
+
+impl Default for BenchConfig {
+    fn default() -> Self {
+        Self {
+            seed: 0x5eed,
+            iterations: 5,
+            budget_ms: 1000.0,
+            scale: 1.0,
+            workloads: Vec::new(),
+        }
+    }
+}
+
+/// Timings for one workload, in milliseconds.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WorkloadTiming {
+    pub name: String,
+    /// Items processed per iteration (terms, queries, claims, bytes, vectors)
+    pub size: usize,
+    pub iterations: usize,
+    pub median_ms: f64,
+    pub p95_ms: f64,
+    pub min_ms: f64,
+}
+
+/// What the numbers were measured on.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BenchEnvironment {
+    /// `"wasm"` or `"native"`
+    pub target: String,
+    pub arch: String,
+    /// qntx-core features compiled in
+    pub features: Vec<String>,
+    pub version: String,
+}
+
+impl BenchEnvironment {
+    pub fn current() -> Self {
+        let features = [
+            ("std", cfg!(feature = "std")),
+            ("wasm", cfg!(feature = "wasm")),
+            ("import", cfg!(feature = "import")),
+            ("bench", cfg!(feature = "bench")),
+        ];
+        Self {
+            target: if cfg!(target_arch = "wasm32") {
+                "wasm"
+            } else {
+                "native"
+            }
+            .to_string(),
+            arch: std::env::consts::ARCH.to_string(),
+            features: features
+                .iter()
+                .filter(|(_, on)| *on)
+                .map(|(name, _)| name.to_string())
+                .collect(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BenchReport {
+    pub environment: BenchEnvironment,
+    pub config: BenchConfig,
+    pub workloads: Vec<WorkloadTiming>,
+    pub total_ms: f64,
+    /// The budget ran out before every workload had all its iterations
+    pub budget_exhausted: bool,
+}
+
+/// Run the benchmark workloads, timing with `now_ms` (milliseconds from any
+/// fixed origin).
+pub fn run_benchmarks(config: &BenchConfig, now_ms: impl Fn() -> f64) -> BenchReport {
+    let started = now_ms();
+    let deadline = started + config.budget_ms;
+    let mut budget_exhausted = false;
+    let mut workloads = Vec::new();
+
+    for &name in WORKLOADS {
+        if !config.workloads.is_empty() && !config.workloads.iter().any(|w| w == name) {
+            continue;
+        }
+        let mut workload = Workload::generate(name, config);
+        let mut samples = Vec::with_capacity(config.iterations.max(1));
+        loop {
+            let t = now_ms();
+            workload.run();
+            let end = now_ms();
+            samples.push(end - t);
+            if samples.len() >= config.iterations.max(1) {
+                break;
+            }
+            if end >= deadline {
+                budget_exhausted = true;
+                break;
+            }
+        }
+        workloads.push(summarize(name, workload.size, samples));
+    }
+
+    BenchReport {
+        environment: BenchEnvironment::current(),
+        config: config.clone(),
+        workloads,
+        total_ms: now_ms() - started,
+        budget_exhausted,
+    }
+}
+
+/// [`run_benchmarks`] with `std::time::Instant` as the clock.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn run_benchmarks_native(config: &BenchConfig) -> BenchReport {
+    let origin = std::time::Instant::now();
+    run_benchmarks(config, || origin.elapsed().as_secs_f64() * 1000.0)
+}
+
+/// JSON entry point: `BenchConfig` JSON (empty or `null` for defaults) in,
+/// `BenchReport` JSON or `{"error": "..."}` out.
+pub fn run_benchmarks_json(config_json: &str, now_ms: impl Fn() -> f64) -> String {
+    let config = match config_json.trim() {
+        "" | "null" => BenchConfig::default(),
+        c => match serde_json::from_str(c) {
+            Ok(c) => c,
+            Err(e) => {
+                return serde_json::json!({ "error": format!("invalid bench config: {}", e) })
+                    .to_string()
+            }
+        },
+    };
+    serde_json::to_string(&run_benchmarks(&config, now_ms)).unwrap_or_else(|e| {
+        serde_json::json!({ "error": format!("serialization failed: {}", e) }).to_string()
+    })
+}
+
+fn summarize(name: &str, size: usize, mut samples: Vec<f64>) -> WorkloadTiming {
+    samples.sort_by(f64::total_cmp);
+    let at = |q: f64| samples[((samples.len() - 1) as f64 * q).round() as usize];
+    WorkloadTiming {
+        name: name.to_string(),
+        size,
+        iterations: samples.len(),
+        median_ms: at(0.5),
+        p95_ms: at(0.95),
+        min_ms: samples[0],
+    }
+}
+
+/// Generated input for one workload; generation isn't timed.
+struct Workload {
+    size: usize,
+    input: Input,
+}
+
+enum Input {
+    Vocabulary(Vec<VocabularyAttestation>),
+    Parse(Vec<String>),
+    Classify(String),
+    Expand(Vec<ExpandAttestation>),
+    Hash(Vec<serde_json::Value>),
+    Similarity(Vec<f32>, Vec<Vec<f32>>),
+}
+
+impl Workload {
+    fn generate(name: &str, config: &BenchConfig) -> Self {
+        let mut rng = Rng::new(config.seed ^ fnv(name));
+        let n = |base: usize| ((base as f64 * config.scale) as usize).max(1);
+        let (size, input) = match name {
+            "vocabulary" => {
+                // 10k distinct terms spread over the four slots
+                let atts: Vec<_> = (0..n(2_500))
+                    .map(|i| VocabularyAttestation {
+                        subjects: vec![rng.word("s", i)],
+                        predicates: vec![rng.word("p", i)],
+                        contexts: vec![rng.word("c", i)],
+                        actors: vec![rng.word("a", i)],
+                    })
+                    .collect();
+                (atts.len() * 4, Input::Vocabulary(atts))
+            }
+            "parse" => {
+                let queries = (0..n(1_000))
+                    .map(|i| {
+                        format!(
+                            "{} is {} of {} by {} since 2024-01-01",
+                            rng.word("s", i).to_uppercase(),
+                            rng.word("p", i),
+                            rng.word("c", i),
+                            rng.word("a", i)
+                        )
+                    })
+                    .collect::<Vec<_>>();
+                (queries.len(), Input::Parse(queries))
+            }
+            "classify" => {
+                let groups: Vec<ClaimGroup> = (0..n(500))
+                    .map(|g| ClaimGroup {
+                        key: format!("S{}|p|c", g),
+                        claims: (0..1 + rng.below(6))
+                            .map(|c| ClaimInput {
+                                subject: format!("S{}", g),
+                                predicate: "p".to_string(),
+                                context: "c".to_string(),
+                                actor: format!("actor-{}", rng.below(8)),
+                                timestamp_ms: 1_704_067_200_000 + rng.below(90) as i64 * 86_400_000,
+                                source_id: format!("AS-{}-{}", g, c),
+                                confidence: None,
+                            })
+                            .collect(),
+                    })
+                    .collect();
+                let claims = groups.iter().map(|g| g.claims.len()).sum();
+                let input = ClassifyInput {
+                    claim_groups: groups,
+                    config: ClassificationPolicy::default(),
+                    now_ms: 1_712_000_000_000,
+                    stale: StaleClaimsConfig::default(),
+                };
+                let json = serde_json::to_string(&input).unwrap_or_default();
+                (claims, Input::Classify(json))
+            }
+            "expand" => {
+                // One wide attestation: 20 × 20 × 5 × 5 = 10k claims at scale 1
+                let side = |base: usize| ((base as f64 * config.scale.sqrt()) as usize).max(1);
+                let words = |prefix: &str, count: usize, rng: &mut Rng| {
+                    (0..count).map(|i| rng.word(prefix, i)).collect::<Vec<_>>()
+                };
+                let att = ExpandAttestation {
+                    id: "AS-bench".to_string(),
+                    subjects: words("s", side(20), &mut rng),
+                    predicates: words("p", side(20), &mut rng),
+                    contexts: words("c", 5, &mut rng),
+                    actors: words("a", 5, &mut rng),
+                    timestamp_ms: 1_704_067_200_000,
+                    confidence: None,
+                };
+                let claims = att.subjects.len() * att.predicates.len() * 25;
+                (claims, Input::Expand(vec![att]))
+            }
+            "canonical_hash" => {
+                let docs: Vec<serde_json::Value> = (0..n(2_000))
+                    .map(|i| {
+                        serde_json::json!({
+                            "id": format!("AS-{}", i),
+                            "subjects": [rng.word("s", i)],
+                            "predicates": [rng.word("p", i)],
+                            "contexts": [rng.word("c", i)],
+                            "actors": [rng.word("a", i)],
+                            "timestamp": 1_704_067_200_000i64 + i as i64,
+                            "attributes": {"score": rng.below(1000), "note": rng.word("n", i)},
+                        })
+                    })
+                    .collect();
+                let bytes = docs.iter().map(|d| d.to_string().len()).sum();
+                (bytes, Input::Hash(docs))
+            }
+            "similarity" => {
+                let dims = 384;
+                let vector = |rng: &mut Rng| (0..dims).map(|_| rng.unit()).collect::<Vec<_>>();
+                let query = vector(&mut rng);
+                let candidates = (0..n(1_000)).map(|_| vector(&mut rng)).collect::<Vec<_>>();
+                (candidates.len(), Input::Similarity(query, candidates))
+            }
+            _ => unreachable!("unknown workload {}", name),
+        };
+        Self { size, input }
+    }
+
+    fn run(&mut self) {
+        match &self.input {
+            Input::Vocabulary(atts) => {
+                black_box(extract_vocabulary(atts));
+            }
+            Input::Parse(queries) => {
+                for q in queries {
+                    let _ = black_box(Parser::parse(q));
+                }
+            }
+            Input::Classify(json) => {
+                black_box(crate::classify::classify_claims(json));
+            }
+            Input::Expand(atts) => {
+                black_box(expand_cartesian(atts));
+            }
+            Input::Hash(docs) => {
+                for d in docs {
+                    black_box(canonical_hash(d));
+                }
+            }
+            Input::Similarity(query, candidates) => {
+                for c in candidates {
+                    let _ = black_box(cosine_similarity(query, c));
+                }
+            }
+        }
+    }
+}
+
+fn black_box<T>(value: T) -> T {
+    std::hint::black_box(value)
+}
+
+fn fnv(s: &str) -> u64 {
+    s.bytes().fold(0xcbf2_9ce4_8422_2325, |h, b| {
+        (h ^ b as u64).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// xorshift64*: small, deterministic, no dependency
+struct Rng(u64);
+
+impl Rng {
+    fn new(seed: u64) -> Self {
+        Self(seed.max(1))
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+
+    fn below(&mut self, n: u64) -> u64 {
+        self.next() % n
+    }
+
+    fn unit(&mut self) -> f32 {
+        (self.next() >> 40) as f32 / (1u64 << 24) as f32 - 0.5
+    }
+
+    /// A distinct lowercase term: random stem plus the index
+    fn word(&mut self, prefix: &str, i: usize) -> String {
+        let stem: String = (0..4 + self.below(6))
+            .map(|_| (b'a' + self.below(26) as u8) as char)
+            .collect();
+        format!("{}{}_{}", prefix, stem, i)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn small() -> BenchConfig {
+        BenchConfig {
+            iterations: 2,
+            scale: 0.01,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_all_workloads_reported() {
+        let report = run_benchmarks_native(&small());
+        let names: Vec<_> = report.workloads.iter().map(|w| w.name.as_str()).collect();
+        assert_eq!(names, WORKLOADS);
+        for w in &report.workloads {
+            assert_eq!(w.iterations, 2, "{}", w.name);
+            assert!(w.size > 0, "{}", w.name);
+            assert!(w.min_ms <= w.median_ms && w.median_ms <= w.p95_ms);
+        }
+        assert_eq!(report.environment.target, "native");
+        assert_eq!(report.environment.version, env!("CARGO_PKG_VERSION"));
+    }
+
+    #[test]
+    fn test_json_schema() {
+        let out: serde_json::Value = serde_json::from_str(&run_benchmarks_json(
+            r#"{"iterations":1,"scale":0.01}"#,
+            || 0.0,
+        ))
+        .unwrap();
+        for key in [
+            "environment",
+            "config",
+            "workloads",
+            "total_ms",
+            "budget_exhausted",
+        ] {
+            assert!(out.get(key).is_some(), "missing {}", key);
+        }
+        let first = &out["workloads"][0];
+        for key in [
+            "name",
+            "size",
+            "iterations",
+            "median_ms",
+            "p95_ms",
+            "min_ms",
+        ] {
+            assert!(first.get(key).is_some(), "missing workloads[].{}", key);
+        }
+        assert_eq!(out["workloads"].as_array().unwrap().len(), WORKLOADS.len());
+
+        let err: serde_json::Value =
+            serde_json::from_str(&run_benchmarks_json("{\"iterations\":\"x\"}", || 0.0)).unwrap();
+        assert!(err["error"]
+            .as_str()
+            .unwrap()
+            .starts_with("invalid bench config"));
+    }
+
+    #[test]
+    fn test_budget_caps_iterations() {
+        // A clock that jumps 1s per reading exhausts the budget at once
+        let tick = std::cell::Cell::new(0.0);
+        let report = run_benchmarks(
+            &BenchConfig {
+                iterations: 10,
+                workloads: vec!["parse".to_string(), "expand".to_string()],
+                ..small()
+            },
+            || {
+                tick.set(tick.get() + 1000.0);
+                tick.get()
+            },
+        );
+        assert!(report.budget_exhausted);
+        assert_eq!(report.workloads.len(), 2);
+        assert!(report.workloads.iter().all(|w| w.iterations == 1));
+    }
+
+    #[test]
+    fn test_workloads_are_deterministic() {
+        let config = small();
+        for name in WORKLOADS {
+            let a = Workload::generate(name, &config);
+            let b = Workload::generate(name, &config);
+            assert_eq!(a.size, b.size, "{}", name);
+        }
+        let (Input::Parse(a), Input::Parse(b)) = (
+            Workload::generate("parse", &config).input,
+            Workload::generate("parse", &config).input,
+        ) else {
+            unreachable!()
+        };
+        assert_eq!(a, b);
+        assert!(Parser::parse(&a[0]).is_ok(), "{}", a[0]);
+    }
+}
@@ -0,0 +1,312 @@
+//! Canonical JSON for hashing and cross-language comparison.
+//!
+//! Two implementations that agree on this form produce identical bytes, and
+//! therefore identical hashes, for the same JSON value. It follows RFC 8785
+//! (JSON Canonicalization Scheme) with one exception for large integers:
+//!
+//! - **Whitespace**: none outside strings.
+//! - **Objects**: keys sorted recursively by their UTF-16 code units (the
+//!   JavaScript `sort()` order; equal to code point order outside the
+//!   supplementary planes).
+//! - **Strings**: `"` and `\` escaped, `\b \f \n \r \t` for those controls,
+//!   `\u00xx` (lowercase hex) for the other characters below U+0020, and
+//!   everything else written as-is, so `"\u00e9"` and `"é"` come out the same.
+//!   Strings are not Unicode-normalized.
+//! - **Integers** that serde_json holds as `i64`/`u64` are written exactly.
+//!   RFC 8785 would round those beyond ±2^53 through a double; keep such values
+//!   out of hashed content if other implementations read numbers as doubles.
+//! - **Other numbers** use the ECMAScript `Number.prototype.toString` layout
+//!   of the shortest digits that round-trip: `1.0` → `1`, `0.1` → `0.1`,
+//!   `1e21` → `1e+21`, `1.5e-7` → `1.5e-7`, `-0.0` → `0`.
+//!
+//! Go's `json.Marshal` differs from this form in float formatting and in
+//! escaping `<`, `>`, `&`, U+2028 and U+2029, so it can't be used directly.
+//! serde_json is built with `float_roundtrip` here; without it, parsing can
+//! land one ulp off and canonicalize to different digits than the input had.
+//! `tests/fixtures/canonical_json.json` lists input/output pairs that any
+//! implementation can check itself against.
+
+use sha2::{Digest, Sha256};
+
+use serde_json::Value;
+
+use crate::slow_ops::{self, SlowOpCategory, SlowOpDetail};
+
+/// Serialize `value` in canonical form (see the module docs).
+pub fn to_canonical_json(value: &Value) -> String {
+    let mut out = String::new();
+    write_value(&mut out, value);
+    out
+}
+
+/// SHA-256 over the canonical form of `value`.
+pub fn canonical_hash(value: &Value) -> [u8; 32] {
+    let Some(timer) = slow_ops::start(SlowOpCategory::Hash) else {
+        return Sha256::digest(to_canonical_json(value).as_bytes()).into();
+    };
+    let canonical = to_canonical_json(value);
+    let hash = Sha256::digest(canonical.as_bytes()).into();
+    timer.finish(SlowOpDetail {
+        input_len: Some(canonical.len()),
+        result_count: None,
+    });
+    hash
+}
+
+/// [`canonical_hash`] as a lowercase hex string.
+pub fn canonical_hash_hex(value: &Value) -> String {
+    canonical_hash(value)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+fn write_value(out: &mut String, value: &Value) {
+    match value {
+        Value::Null => out.push_str("null"),
+        Value::Bool(b) => out.push_str(if *b { "true" } else { "false" }),
+        Value::Number(n) => {
+            if let Some(i) = n.as_i64() {
+                out.push_str(&i.to_string());
+            } else if let Some(u) = n.as_u64() {
+                out.push_str(&u.to_string());
+            } else {
+                write_f64(out, n.as_f64().unwrap_or(0.0));
+            }
+        }
+        Value::String(s) => write_string(out, s),
+        Value::Array(items) => {
+            out.push('[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_value(out, item);
+            }
+            out.push(']');
+        }
+        Value::Object(map) => {
+            let mut entries: Vec<(&String, &Value)> = map.iter().collect();
+            entries.sort_by(|(a, _), (b, _)| a.encode_utf16().cmp(b.encode_utf16()));
+            out.push('{');
+            for (i, (key, item)) in entries.into_iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_string(out, key);
+                out.push(':');
+                write_value(out, item);
+            }
+            out.push('}');
+        }
+    }
+}
+
+fn write_string(out: &mut String, s: &str) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\u{08}' => out.push_str("\\b"),
+            '\u{0c}' => out.push_str("\\f"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if c < '\u{20}' => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+/// ECMAScript Number::toString for a finite double.
+fn write_f64(out: &mut String, x: f64) {
+    if x == 0.0 {
+        out.push('0');
+        return;
+    }
+    if x < 0.0 {
+        out.push('-');
+    }
+    // `{:e}` gives the shortest round-trip digits as `d.ddde±x`
+    let sci = format!("{:e}", x.abs());
+    let (mantissa, exponent) = sci.split_once('e').unwrap_or((&sci, "0"));
+    let digits: String = mantissa.chars().filter(|c| *c != '.').collect();
+    let k = digits.len() as i32;
+    // Value is 0.digits × 10^n
+    let n = exponent.parse::<i32>().unwrap_or(0) + 1;
+
+    if k <= n && n <= 21 {
+        out.push_str(&digits);
+        out.extend(std::iter::repeat_n('0', (n - k) as usize));
+    } else if 0 < n && n <= 21 {
+        out.push_str(&digits[..n as usize]);
+        out.push('.');
+        out.push_str(&digits[n as usize..]);
+    } else if -6 < n && n <= 0 {
+        out.push_str("0.");
+        out.extend(std::iter::repeat_n('0', (-n) as usize));
+        out.push_str(&digits);
+    } else {
+        out.push_str(&digits[..1]);
+        if k > 1 {
+            out.push('.');
+            out.push_str(&digits[1..]);
+        }
+        out.push('e');
+        out.push(if n > 0 { '+' } else { '-' });
+        out.push_str(&(n - 1).abs().to_string());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const FIXTURE: &str = include_str!("../tests/fixtures/canonical_json.json");
+
+    #[test]
+    fn fixture_pairs() {
+        let cases: Vec<Value> = serde_json::from_str(FIXTURE).unwrap();
+        for case in cases {
+            let input = case["input"].as_str().unwrap();
+            let value: Value = serde_json::from_str(input).unwrap();
+            assert_eq!(
+                to_canonical_json(&value),
+                case["canonical"].as_str().unwrap(),
+                "input: {}",
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn numbers_follow_ecmascript_layout() {
+        let cases = [
+            (1.0, "1"),
+            (-2.5, "-2.5"),
+            (0.1, "0.1"),
+            (1e20, "100000000000000000000"),
+            (1e21, "1e+21"),
+            (123456789.125, "123456789.125"),
+            (0.000001, "0.000001"),
+            (1.5e-7, "1.5e-7"),
+            (-0.0, "0"),
+            (f64::MAX, "1.7976931348623157e+308"),
+            (5e-324, "5e-324"),
+        ];
+        for (x, expected) in cases {
+            assert_eq!(to_canonical_json(&json!(x)), expected, "{:?}", x);
+        }
+        assert_eq!(to_canonical_json(&json!(u64::MAX)), "18446744073709551615");
+        assert_eq!(to_canonical_json(&json!(i64::MIN)), "-9223372036854775808");
+    }
+
+    #[test]
+    fn keys_sort_by_utf16_code_units() {
+        // U+FF61 sorts after U+1F600 in UTF-16 (0xD83D) but before it by code point
+        let value = json!({ "\u{ff61}": 1, "\u{1f600}": 2, "b": 3, "a": 4 });
+        assert_eq!(
+            to_canonical_json(&value),
+            "{\"a\":4,\"b\":3,\"\u{1f600}\":2,\"\u{ff61}\":1}"
+        );
+    }
+
+    #[test]
+    fn hash_is_sha256_of_canonical_form() {
+        let a: Value = serde_json::from_str(r#"{"b": [1.0, "x"], "a": null}"#).unwrap();
+        let b: Value = serde_json::from_str(r#"{"a":null,"b":[1,"x"]}"#).unwrap();
+        assert_eq!(canonical_hash(&a), canonical_hash(&b));
+        let expected: [u8; 32] = Sha256::digest(br#"{"a":null,"b":[1,"x"]}"#).into();
+        assert_eq!(canonical_hash(&a), expected);
+        assert_eq!(
+            canonical_hash_hex(&a),
+            expected
+                .iter()
+                .map(|b| format!("{:02x}", b))
+                .collect::<String>()
+        );
+    }
+
+    /// Small deterministic generator so the property tests need no extra crate.
+    struct XorShift(u64);
+
+    impl XorShift {
+        fn next(&mut self) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0
+        }
+
+        fn below(&mut self, n: u64) -> u64 {
+            self.next() % n
+        }
+
+        fn string(&mut self) -> String {
+            const CHARS: &[char] = &['a', 'Z', '0', '"', '\\', '\n', '\u{1}', 'é', '€', '😀', ' '];
+            (0..self.below(6))
+                .map(|_| CHARS[self.below(CHARS.len() as u64) as usize])
+                .collect()
+        }
+
+        fn value(&mut self, depth: u32) -> Value {
+            let kinds = if depth == 0 { 5 } else { 7 };
+            match self.below(kinds) {
+                0 => Value::Null,
+                1 => Value::Bool(self.below(2) == 1),
+                2 => json!(self.next() as i64 >> self.below(64)),
+                3 => json!(
+                    f64::from_bits(self.next() >> 2) * if self.below(2) == 0 { 1.0 } else { -1.0 }
+                ),
+                4 => Value::String(self.string()),
+                5 => Value::Array((0..self.below(4)).map(|_| self.value(depth - 1)).collect()),
+                _ => Value::Object(
+                    (0..self.below(4))
+                        .map(|_| (self.string(), self.value(depth - 1)))
+                        .collect(),
+                ),
+            }
+        }
+    }
+
+    /// Plain JSON text of `value` with object keys in reverse order.
+    fn reversed_keys(value: &Value) -> String {
+        match value {
+            Value::Array(items) => format!(
+                "[{}]",
+                items
+                    .iter()
+                    .map(reversed_keys)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            Value::Object(map) => format!(
+                "{{ {} }}",
+                map.iter()
+                    .rev()
+                    .map(|(k, v)| format!("{}: {}", Value::String(k.clone()), reversed_keys(v)))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            other => other.to_string(),
+        }
+    }
+
+    #[test]
+    fn canonicalizing_is_idempotent_and_ignores_key_order() {
+        let mut rng = XorShift(0x9e37_79b9_7f4a_7c15);
+        for _ in 0..500 {
+            let value = rng.value(3);
+            let canonical = to_canonical_json(&value);
+
+            let reparsed: Value = serde_json::from_str(&canonical).unwrap();
+            assert_eq!(to_canonical_json(&reparsed), canonical);
+
+            let shuffled: Value = serde_json::from_str(&reversed_keys(&value)).unwrap();
+            assert_eq!(to_canonical_json(&shuffled), canonical);
+        }
+    }
+}
@@ -3,12 +3,17 @@
 //! Orchestrates temporal analysis, credibility ranking, and confidence scoring
 //! to classify claim conflicts and determine resolution strategies.
 
+use std::borrow::Cow;
+
 use serde::{Deserialize, Serialize};
 
 use super::confidence::{ClaimWithTiming, ConfidenceCalculator};
 use super::credibility::ActorCredibility;
-use super::temporal::{TemporalAnalyzer, TemporalConfig};
-use super::types::{ActorRanking, ConflictType};
+use super::policy::ClassificationPolicy;
+use super::temporal::{ClaimTiming, TemporalAnalyzer, TemporalConfig};
+use super::types::{ActorRanking, ClaimProvenance, ConflictType};
+use crate::parser::AxQuery;
+use crate::slow_ops::{SlowOpCategory, SlowOpDetail};
 
 /// Input claim for classification (JSON-friendly for WASM boundary)
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -20,6 +25,9 @@ pub struct ClaimInput {
     pub timestamp_ms: i64,
     /// Source attestation ID
     pub source_id: String,
+    /// Creator-assigned confidence of the source attestation, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub confidence: Option<f32>,
 }
 
 /// Input for classify_claims WASM function
@@ -27,15 +35,37 @@ pub struct ClaimInput {
 pub struct ClassifyInput {
     /// Claims grouped by key (subject|predicate|context|actor)
     pub claim_groups: Vec<ClaimGroup>,
-    /// Temporal configuration (uses defaults if omitted)
+    /// Time windows and decision policy (uses defaults if omitted)
     #[serde(default)]
-    pub config: TemporalConfig,
+    pub config: ClassificationPolicy,
     /// Current time in milliseconds (for recency calculation)
     pub now_ms: i64,
+    /// What to report in `stale_claims`
+    #[serde(default)]
+    pub stale: StaleClaimsConfig,
+}
+
+/// Controls the `stale_claims` section of the output
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StaleClaimsConfig {
+    /// Report at most this many stale claims (oldest first)
+    pub max_reported: usize,
+    /// Also report claims whose group was auto-resolved
+    pub include_auto_resolved: bool,
+}
+
+impl Default for StaleClaimsConfig {
+    fn default() -> Self {
+        Self {
+            max_reported: 50,
+            include_auto_resolved: true,
+        }
+    }
 }
 
 /// A group of claims sharing the same key
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ClaimGroup {
     pub key: String,
     pub claims: Vec<ClaimInput>,
@@ -52,6 +82,32 @@ pub struct ClassifyOutput {
     /// Claims from single-claim groups are always included. For multi-claim groups,
     /// the resolution strategy determines which claims survive.
     pub resolved_source_ids: Vec<String>,
+    /// Claims not re-attested within the obsolescence window, oldest first,
+    /// capped at `stale.max_reported`
+    #[serde(default)]
+    pub stale_claims: Vec<StaleClaim>,
+    /// Number of stale claims before the cap
+    #[serde(default)]
+    pub stale_total: usize,
+}
+
+/// A claim whose latest attestation is older than the obsolescence window
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StaleClaim {
+    pub subject: String,
+    pub predicate: String,
+    pub context: String,
+    /// Timestamp of the most recent attestation of this claim
+    pub last_attested_ms: i64,
+    pub age_ms: i64,
+    pub last_actor: String,
+    /// Source ID of the most recent attestation
+    pub source_id: String,
+    /// Whether the claim's group was auto-resolved (single claims count as not)
+    pub auto_resolved: bool,
+    /// AX query finding every attestation of this subject, predicate and
+    /// context, to corroborate or contradict the claim
+    pub query: String,
 }
 
 /// A single classified conflict
@@ -67,18 +123,30 @@ pub struct ConflictOutput {
     pub temporal_pattern: String,
     pub auto_resolved: bool,
     pub source_ids: Vec<String>,
+    /// Source ID of the one claim an auto-resolution kept (Evolution,
+    /// Supersession); absent when several claims survive or review is needed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub winning_claim: Option<String>,
+    /// `source_ids` partitioned by whether their claims survive the strategy
+    #[serde(flatten)]
+    pub provenance: ClaimProvenance,
 }
 
 /// Smart classifier that performs conflict classification on claim groups
 pub struct SmartClassifier {
     temporal: TemporalAnalyzer,
-    config: TemporalConfig,
+    policy: ClassificationPolicy,
 }
 
 impl SmartClassifier {
+    /// Classifier with the given windows and the default policy
     pub fn new(config: TemporalConfig) -> Self {
-        let temporal = TemporalAnalyzer::new(config.clone());
-        Self { temporal, config }
+        Self::with_policy(config.into())
+    }
+
+    pub fn with_policy(policy: ClassificationPolicy) -> Self {
+        let temporal = TemporalAnalyzer::new(policy.temporal.clone());
+        Self { temporal, policy }
     }
 
     /// Classify all claim groups and return structured results.
@@ -91,21 +159,25 @@ impl SmartClassifier {
 
         // Track resolved claims with their sort keys: (source_id, confidence, timestamp_ms)
         let mut resolved: Vec<(String, f64, i64)> = Vec::new();
+        let mut stale: Vec<StaleClaim> = Vec::new();
 
         for group in &input.claim_groups {
+            let group = self.canonical_group(group);
+            let group = group.as_ref();
+            let timings = Self::timings(&group.claims);
+
             if group.claims.len() <= 1 {
-                // Single claim — always survives with neutral confidence
+                // Single claim — always survives, with its own confidence or neutral
                 for claim in &group.claims {
-                    resolved.push((claim.source_id.clone(), 0.5, claim.timestamp_ms));
+                    let confidence = claim.confidence.map_or(0.5, f64::from);
+                    resolved.push((claim.source_id.clone(), confidence, claim.timestamp_ms));
                 }
+                stale.extend(self.stale_claim(group, &timings, false, input.now_ms));
                 continue;
             }
 
             total_analyzed += 1;
-            let conflict = self.classify_group(group, input.now_ms);
-
-            // Apply resolution strategy to determine surviving claims
-            let survivor_ids = self.apply_strategy(&conflict.strategy, &group.claims);
+            let (conflict, survivor_ids) = self.classify_group(group, &timings, input.now_ms);
 
             // Map survivor IDs to their timestamps, attach conflict confidence
             let confidence = conflict.confidence;
@@ -125,6 +197,15 @@ impl SmartClassifier {
                 review_required += 1;
             }
 
+            if input.stale.include_auto_resolved || !conflict.auto_resolved {
+                stale.extend(self.stale_claim(
+                    group,
+                    &timings,
+                    conflict.auto_resolved,
+                    input.now_ms,
+                ));
+            }
+
             conflicts.push(conflict);
         }
 
@@ -138,13 +219,106 @@ impl SmartClassifier {
 
         let resolved_source_ids = resolved.into_iter().map(|(id, _, _)| id).collect();
 
+        // Oldest first, then by slots for determinism
+        stale.sort_by(|a, b| {
+            b.age_ms
+                .cmp(&a.age_ms)
+                .then_with(|| a.subject.cmp(&b.subject))
+                .then_with(|| a.predicate.cmp(&b.predicate))
+                .then_with(|| a.context.cmp(&b.context))
+        });
+        let stale_total = stale.len();
+        stale.truncate(input.stale.max_reported);
+
         ClassifyOutput {
             conflicts,
             auto_resolved,
             review_required,
             total_analyzed,
             resolved_source_ids,
+            stale_claims: stale,
+            stale_total,
+        }
+    }
+
+    /// Classify one group of at least two claims, as [`classify`](Self::classify)
+    /// would; `None` for single-claim groups, which are never in conflict.
+    pub fn classify_one(&self, group: &ClaimGroup, now_ms: i64) -> Option<ConflictOutput> {
+        if group.claims.len() <= 1 {
+            return None;
+        }
+        let group = self.canonical_group(group);
+        let timings = Self::timings(&group.claims);
+        Some(self.classify_group(&group, &timings, now_ms).0)
+    }
+
+    /// `group` with every actor replaced by its canonical actor, so alias
+    /// spellings compare and rank as one identity
+    fn canonical_group<'g>(&self, group: &'g ClaimGroup) -> Cow<'g, ClaimGroup> {
+        let aliases = &self.policy.actor_aliases;
+        if aliases.is_empty() {
+            return Cow::Borrowed(group);
         }
+        let claims = group
+            .claims
+            .iter()
+            .map(|c| ClaimInput {
+                actor: aliases.normalize(&c.actor).to_string(),
+                ..c.clone()
+            })
+            .collect();
+        Cow::Owned(ClaimGroup {
+            key: group.key.clone(),
+            claims,
+        })
+    }
+
+    /// Timing info for each claim, in claim order
+    fn timings(claims: &[ClaimInput]) -> Vec<ClaimTiming> {
+        claims
+            .iter()
+            .map(|c| ClaimTiming {
+                actor: c.actor.clone(),
+                timestamp_ms: c.timestamp_ms,
+                predicate: c.predicate.clone(),
+            })
+            .collect()
+    }
+
+    /// Report the group if its latest claim is past the obsolescence window
+    fn stale_claim(
+        &self,
+        group: &ClaimGroup,
+        timings: &[ClaimTiming],
+        auto_resolved: bool,
+        now_ms: i64,
+    ) -> Option<StaleClaim> {
+        let latest = self.temporal.latest(timings)?;
+        if !self.temporal.is_obsolete(latest.timestamp_ms, now_ms) {
+            return None;
+        }
+        // timings are in claim order, so the index carries over
+        let index = timings.iter().position(|t| std::ptr::eq(t, latest))?;
+        let claim = &group.claims[index];
+
+        let mut query = AxQuery::new();
+        query.subjects.push(&claim.subject);
+        query.predicates.push(&claim.predicate);
+        if !claim.context.is_empty() {
+            query.contexts.push(&claim.context);
+        }
+
+        Some(StaleClaim {
+            subject: claim.subject.clone(),
+            predicate: claim.predicate.clone(),
+            context: claim.context.clone(),
+            last_attested_ms: latest.timestamp_ms,
+            age_ms: now_ms - latest.timestamp_ms,
+            last_actor: latest.actor.clone(),
+            source_id: claim.source_id.clone(),
+            auto_resolved,
+            query: query.to_query_string(),
+        })
     }
 
     /// Apply a resolution strategy to a group of claims, returning the surviving source IDs.
@@ -180,8 +354,14 @@ impl SmartClassifier {
         }
     }
 
-    /// Classify a single group of claims
-    fn classify_group(&self, group: &ClaimGroup, now_ms: i64) -> ConflictOutput {
+    /// Classify a single group of claims, returning the conflict and the
+    /// source IDs of the claims its strategy keeps (one per surviving claim)
+    fn classify_group(
+        &self,
+        group: &ClaimGroup,
+        timings: &[ClaimTiming],
+        now_ms: i64,
+    ) -> (ConflictOutput, Vec<String>) {
         let claims = &group.claims;
 
         // Convert to ClaimWithTiming for confidence calculation
@@ -193,6 +373,7 @@ impl SmartClassifier {
                 predicate: c.predicate.clone(),
                 subject: c.subject.clone(),
                 context: c.context.clone(),
+                confidence: c.confidence,
             })
             .collect();
 
@@ -211,15 +392,7 @@ impl SmartClassifier {
         };
 
         // Analyze temporal pattern
-        let timings: Vec<_> = claims_with_timing
-            .iter()
-            .map(|c| super::temporal::ClaimTiming {
-                actor: c.actor.clone(),
-                timestamp_ms: c.timestamp_ms,
-                predicate: c.predicate.clone(),
-            })
-            .collect();
-        let temporal_pattern = self.temporal.analyze_pattern(&timings).to_string();
+        let temporal_pattern = self.temporal.analyze_pattern(timings).to_string();
 
         // Build actor hierarchy
         let actor_hierarchy = self.rank_actors(claims);
@@ -233,9 +406,18 @@ impl SmartClassifier {
             .collect();
         source_ids.sort();
 
+        // Apply resolution strategy to determine surviving claims
+        let survivor_ids = self.apply_strategy(&strategy, claims);
+        let provenance =
+            ClaimProvenance::partition(source_ids.iter().map(String::as_str), &survivor_ids);
+
         let auto_resolved = conflict_type.is_auto_resolvable();
+        let winning_claim = match survivor_ids.as_slice() {
+            [winner] if auto_resolved => Some(winner.clone()),
+            _ => None,
+        };
 
-        ConflictOutput {
+        let conflict = ConflictOutput {
             subject: claims[0].subject.clone(),
             predicate: claims[0].predicate.clone(),
             context: claims[0].context.clone(),
@@ -246,7 +428,10 @@ impl SmartClassifier {
             temporal_pattern,
             auto_resolved,
             source_ids,
-        }
+            winning_claim,
+            provenance,
+        };
+        (conflict, survivor_ids)
     }
 
     /// Determine the type of conflict resolution needed.
@@ -273,7 +458,8 @@ impl SmartClassifier {
         ConflictType::Review
     }
 
-    /// Same actor updated their claim over time (with meaningful time gaps)
+    /// Same actor updated their claim over time (with meaningful time gaps,
+    /// unless `same_actor_always_evolution`)
     fn is_same_actor_evolution(&self, claims: &[ClaimInput]) -> bool {
         if claims.len() < 2 {
             return false;
@@ -284,13 +470,17 @@ impl SmartClassifier {
             return false;
         }
 
+        if self.policy.same_actor_always_evolution {
+            return true;
+        }
+
         // Check for meaningful gaps between timestamps
         let mut timestamps: Vec<i64> = claims.iter().map(|c| c.timestamp_ms).collect();
         timestamps.sort();
 
         for i in 1..timestamps.len() {
             let gap = timestamps[i] - timestamps[i - 1];
-            if gap > self.config.verification_window_ms {
+            if gap > self.policy.temporal.verification_window_ms {
                 return true;
             }
         }
@@ -298,12 +488,23 @@ impl SmartClassifier {
         false
     }
 
-    /// Multiple actors agree simultaneously
+    /// Multiple actors agree simultaneously (from at least two credibility
+    /// kinds if `verification_requires_distinct_actor_kinds`)
     fn is_simultaneous_verification(&self, claims: &[ClaimInput]) -> bool {
         if claims.len() < 2 {
             return false;
         }
 
+        if self.policy.verification_requires_distinct_actor_kinds {
+            let first_kind = ActorCredibility::from_actor(&claims[0].actor);
+            if claims
+                .iter()
+                .all(|c| ActorCredibility::from_actor(&c.actor) == first_kind)
+            {
+                return false;
+            }
+        }
+
         // All claims must have same predicate
         let first_predicate = &claims[0].predicate;
         if !claims.iter().all(|c| &c.predicate == first_predicate) {
@@ -326,21 +527,19 @@ impl SmartClassifier {
         contexts.len() > 1
     }
 
-    /// A human actor overrides non-human actors
+    /// A human actor overrides actors at least
+    /// `min_credibility_gap_for_supersession` levels below
     fn has_human_supersession(&self, claims: &[ClaimInput]) -> bool {
-        let mut has_human = false;
-        let mut has_non_human = false;
+        let credibilities: Vec<ActorCredibility> = claims
+            .iter()
+            .map(|c| ActorCredibility::from_actor(&c.actor))
+            .collect();
 
-        for claim in claims {
-            let cred = ActorCredibility::from_actor(&claim.actor);
-            if cred.is_human() {
-                has_human = true;
-            } else {
-                has_non_human = true;
-            }
+        let has_human = credibilities.iter().any(|c| c.is_human());
+        match credibilities.iter().min() {
+            Some(&lowest) if has_human => self.policy.supersedes(lowest),
+            _ => false,
         }
-
-        has_human && has_non_human
     }
 
     /// Rank actors by credibility (highest first)
@@ -366,7 +565,24 @@ impl SmartClassifier {
 /// Top-level function: classify claim groups from JSON input, return JSON output.
 /// This is the function exposed through WASM.
 pub fn classify_claims(input: &str) -> String {
-    let parsed: ClassifyInput = match serde_json::from_str(input) {
+    classify_claims_with_defaults(input, &ClassificationPolicy::default())
+}
+
+/// Like [`classify_claims`], but fields missing from the input's `config`
+/// (or the whole `config`) fall back to `defaults` instead of the built-in
+/// policy. WASM targets pass the config loaded with `load_core_config`.
+pub fn classify_claims_with_defaults(input: &str, defaults: &ClassificationPolicy) -> String {
+    let parsed = serde_json::from_str::<serde_json::Value>(input).and_then(|mut value| {
+        if let Some(obj) = value.as_object_mut() {
+            let mut config = serde_json::to_value(defaults)?;
+            if let Some(per_call) = obj.get("config") {
+                crate::config::merge_value(&mut config, per_call);
+            }
+            obj.insert("config".to_string(), config);
+        }
+        serde_json::from_value::<ClassifyInput>(value)
+    });
+    let parsed = match parsed {
         Ok(v) => v,
         Err(e) => {
             return format!(
@@ -376,8 +592,15 @@ pub fn classify_claims(input: &str) -> String {
         }
     };
 
-    let classifier = SmartClassifier::new(parsed.config.clone());
-    let output = classifier.classify(&parsed);
+    let classifier = SmartClassifier::with_policy(parsed.config.clone());
+    let output = crate::slow_ops::timed(
+        SlowOpCategory::Classify,
+        || classifier.classify(&parsed),
+        |output| SlowOpDetail {
+            input_len: Some(input.len()),
+            result_count: Some(output.conflicts.len()),
+        },
+    );
 
     match serde_json::to_string(&output) {
         Ok(json) => json,
@@ -391,7 +614,6 @@ pub fn classify_claims(input: &str) -> String {
 //   is Evolution > Verification > Coexistence > Supersession > Review.
 // - Boundary conditions: claims exactly at verification_window_ms apart (evolution vs
 //   verification edge), custom TemporalConfig values.
-// - Multiple claim groups in a single input (auto_resolved/review_required counters).
 // - Actor hierarchy ordering within a conflict output.
 // - Low-confidence override: confidence below review_threshold should produce
 //   "human_review" strategy regardless of conflict type.
@@ -415,6 +637,7 @@ mod tests {
             actor: actor.to_string(),
             timestamp_ms: ts,
             source_id: format!("as-{}", ts),
+            confidence: None,
         }
     }
 
@@ -441,11 +664,12 @@ mod tests {
                     ),
                 ],
             }],
-            config: TemporalConfig::default(),
+            config: ClassificationPolicy::default(),
             now_ms: now,
+            stale: StaleClaimsConfig::default(),
         };
 
-        let classifier = SmartClassifier::new(input.config.clone());
+        let classifier = SmartClassifier::with_policy(input.config.clone());
         let output = classifier.classify(&input);
 
         assert_eq!(output.total_analyzed, 1);
@@ -457,6 +681,10 @@ mod tests {
         assert_eq!(c.strategy, "show_latest");
         assert!(c.auto_resolved);
         assert!(c.confidence > 0.8, "evolution confidence={}", c.confidence);
+        assert_eq!(
+            c.winning_claim.as_deref(),
+            Some(format!("as-{}", now - 1000).as_str())
+        );
     }
 
     #[test]
@@ -470,11 +698,12 @@ mod tests {
                     make_claim("ALICE", "is_author", "GitHub", "human:bob", now - 5_000),
                 ],
             }],
-            config: TemporalConfig::default(),
+            config: ClassificationPolicy::default(),
             now_ms: now,
+            stale: StaleClaimsConfig::default(),
         };
 
-        let classifier = SmartClassifier::new(input.config.clone());
+        let classifier = SmartClassifier::with_policy(input.config.clone());
         let output = classifier.classify(&input);
 
         let c = &output.conflicts[0];
@@ -486,6 +715,7 @@ mod tests {
             "verification confidence={}",
             c.confidence
         );
+        assert_eq!(c.winning_claim, None);
     }
 
     #[test]
@@ -499,11 +729,12 @@ mod tests {
                     make_claim("ALICE", "is_maintainer", "GitLab", "human:bob", now - 5_000),
                 ],
             }],
-            config: TemporalConfig::default(),
+            config: ClassificationPolicy::default(),
             now_ms: now,
+            stale: StaleClaimsConfig::default(),
         };
 
-        let classifier = SmartClassifier::new(input.config.clone());
+        let classifier = SmartClassifier::with_policy(input.config.clone());
         let output = classifier.classify(&input);
 
         let c = &output.conflicts[0];
@@ -540,11 +771,12 @@ mod tests {
                     ),
                 ],
             }],
-            config: TemporalConfig::default(),
+            config: ClassificationPolicy::default(),
             now_ms: now,
+            stale: StaleClaimsConfig::default(),
         };
 
-        let classifier = SmartClassifier::new(input.config.clone());
+        let classifier = SmartClassifier::with_policy(input.config.clone());
         let output = classifier.classify(&input);
 
         let c = &output.conflicts[0];
@@ -556,6 +788,60 @@ mod tests {
             "supersession confidence={}",
             c.confidence
         );
+        assert_eq!(
+            c.winning_claim.as_deref(),
+            Some(format!("as-{}", now - 5_000).as_str())
+        );
+    }
+
+    #[test]
+    fn aliased_actor_evolution() {
+        let now = 1_000_000_000;
+        let group = || ClaimGroup {
+            key: "ALICE|role|GitHub".to_string(),
+            claims: vec![
+                make_claim("ALICE", "is_junior_dev", "GitHub", "alice", now - 200_000),
+                make_claim(
+                    "ALICE",
+                    "is_senior_dev",
+                    "GitHub",
+                    "human:alice@verified",
+                    now - 1000,
+                ),
+            ],
+        };
+        let classify = |config: ClassificationPolicy| {
+            let input = ClassifyInput {
+                claim_groups: vec![group()],
+                config,
+                now_ms: now,
+                stale: StaleClaimsConfig::default(),
+            };
+            SmartClassifier::with_policy(input.config.clone()).classify(&input)
+        };
+
+        // Unaliased, the human claim outranks the unknown "alice"
+        let output = classify(ClassificationPolicy::default());
+        assert_eq!(
+            output.conflicts[0].conflict_type,
+            ConflictType::Supersession
+        );
+
+        let policy = ClassificationPolicy {
+            actor_aliases: crate::ActorAliasMap::from_json(
+                r#"{"human:alice": ["alice", "human:alice@verified"]}"#,
+            )
+            .unwrap(),
+            ..ClassificationPolicy::default()
+        };
+        let output = classify(policy);
+        let c = &output.conflicts[0];
+        assert_eq!(c.conflict_type, ConflictType::Evolution);
+        assert_eq!(
+            output.resolved_source_ids,
+            vec![format!("as-{}", now - 1000)]
+        );
+        assert!(c.actor_hierarchy.iter().all(|r| r.actor == "human:alice"));
     }
 
     #[test]
@@ -566,11 +852,12 @@ mod tests {
                 key: "ALICE|is_dev|GitHub".to_string(),
                 claims: vec![make_claim("ALICE", "is_dev", "GitHub", "human:alice", now)],
             }],
-            config: TemporalConfig::default(),
+            config: ClassificationPolicy::default(),
             now_ms: now,
+            stale: StaleClaimsConfig::default(),
         };
 
-        let classifier = SmartClassifier::new(input.config.clone());
+        let classifier = SmartClassifier::with_policy(input.config.clone());
         let output = classifier.classify(&input);
 
         assert_eq!(output.total_analyzed, 0);
@@ -608,6 +895,51 @@ mod tests {
         assert!(parsed["conflicts"][0]["auto_resolved"].as_bool().unwrap());
     }
 
+    #[test]
+    fn classify_claims_falls_back_to_defaults() {
+        let now = 1_000_000_000_i64;
+        let input = |config: serde_json::Value| {
+            let mut input = serde_json::json!({
+                "claim_groups": [{
+                    "key": "ALICE|is_dev|GitHub",
+                    "claims": [
+                        {"subject": "ALICE", "predicate": "is_dev", "context": "GitHub", "actor": "human:bob", "timestamp_ms": now - 30_000, "source_id": "as-1"},
+                        {"subject": "ALICE", "predicate": "is_dev", "context": "GitHub", "actor": "human:carol", "timestamp_ms": now, "source_id": "as-2"}
+                    ]
+                }],
+                "now_ms": now
+            });
+            if !config.is_null() {
+                input["config"] = config;
+            }
+            input.to_string()
+        };
+        let pattern = |result: String| {
+            let parsed: serde_json::Value = serde_json::from_str(&result).unwrap();
+            assert!(parsed["error"].is_null(), "unexpected error: {}", result);
+            parsed["conflicts"][0]["temporal_pattern"].clone()
+        };
+        let narrow = ClassificationPolicy::from(TemporalConfig {
+            verification_window_ms: 10_000,
+            ..TemporalConfig::default()
+        });
+
+        // No per-call config: the supplied defaults decide
+        let default_pattern = pattern(classify_claims(&input(serde_json::Value::Null)));
+        let narrow_pattern = pattern(classify_claims_with_defaults(
+            &input(serde_json::Value::Null),
+            &narrow,
+        ));
+        assert_ne!(default_pattern, narrow_pattern);
+
+        // A partial per-call config overrides only the fields it names
+        let overridden = pattern(classify_claims_with_defaults(
+            &input(serde_json::json!({"verification_window_ms": 60_000})),
+            &narrow,
+        ));
+        assert_eq!(overridden, default_pattern);
+    }
+
     #[test]
     fn classify_claims_invalid_json() {
         let result = classify_claims("not json");
@@ -617,4 +949,229 @@ mod tests {
             .unwrap()
             .contains("invalid classify input"));
     }
+
+    #[test]
+    fn stale_claims_straddle_obsolescence_boundary() {
+        let now = 100_000_000_000;
+        let window = TemporalConfig::default().obsolescence_window_ms;
+        let mut fresh = make_claim("BOB", "works at", "ACME Corp", "human:bob", now - window);
+        fresh.source_id = "as-fresh".to_string();
+        let mut old = make_claim("ALICE", "is_dev", "GitHub", "llm:gpt", now - window - 1);
+        old.source_id = "as-old".to_string();
+        let mut older = make_claim("ALICE", "is_dev", "GitHub", "human:alice", now - 2 * window);
+        older.source_id = "as-older".to_string();
+        let mut ancient = make_claim("CAROL", "of", "", "system:import", now - 3 * window);
+        ancient.source_id = "as-ancient".to_string();
+
+        let mut input = ClassifyInput {
+            claim_groups: vec![
+                ClaimGroup {
+                    key: "BOB".to_string(),
+                    claims: vec![fresh],
+                },
+                ClaimGroup {
+                    key: "ALICE".to_string(),
+                    claims: vec![older, old],
+                },
+                ClaimGroup {
+                    key: "CAROL".to_string(),
+                    claims: vec![ancient],
+                },
+            ],
+            config: ClassificationPolicy::default(),
+            now_ms: now,
+            stale: StaleClaimsConfig::default(),
+        };
+        let classifier = SmartClassifier::with_policy(input.config.clone());
+
+        let output = classifier.classify(&input);
+        assert_eq!(output.stale_total, 2);
+        let carol = &output.stale_claims[0];
+        assert_eq!(carol.subject, "CAROL");
+        assert_eq!(carol.age_ms, 3 * window);
+        assert!(!carol.auto_resolved);
+        // Exactly at the window is not yet stale; the latest ALICE claim is 1ms past it
+        let alice = &output.stale_claims[1];
+        assert_eq!(alice.source_id, "as-old");
+        assert_eq!(alice.last_actor, "llm:gpt");
+        assert_eq!(alice.last_attested_ms, now - window - 1);
+
+        // Every query finds exactly the claim's subject, predicate and context
+        for stale in &output.stale_claims {
+            let query = crate::parser::Parser::parse(&stale.query).unwrap();
+            let filter = query.to_filter(now).unwrap();
+            assert_eq!(
+                filter.subjects,
+                vec![stale.subject.clone()],
+                "{}",
+                stale.query
+            );
+            assert_eq!(
+                filter.predicates,
+                vec![stale.predicate.clone()],
+                "{}",
+                stale.query
+            );
+            if stale.context.is_empty() {
+                assert!(filter.contexts.is_empty());
+            } else {
+                assert_eq!(
+                    filter.contexts,
+                    vec![stale.context.clone()],
+                    "{}",
+                    stale.query
+                );
+            }
+        }
+
+        input.stale.max_reported = 1;
+        let output = classifier.classify(&input);
+        assert_eq!(output.stale_total, 2);
+        assert_eq!(output.stale_claims.len(), 1);
+        assert_eq!(output.stale_claims[0].subject, "CAROL");
+    }
+
+    #[test]
+    fn stale_claims_can_skip_auto_resolved_groups() {
+        let now = 100_000_000_000;
+        let window = TemporalConfig::default().obsolescence_window_ms;
+        let input = |include_auto_resolved: bool| ClassifyInput {
+            claim_groups: vec![ClaimGroup {
+                key: "ALICE|is_dev|GitHub".to_string(),
+                claims: vec![
+                    make_claim("ALICE", "is_dev", "GitHub", "human:alice", now - 3 * window),
+                    make_claim("ALICE", "is_dev", "GitHub", "human:alice", now - 2 * window),
+                ],
+            }],
+            config: ClassificationPolicy::default(),
+            now_ms: now,
+            stale: StaleClaimsConfig {
+                include_auto_resolved,
+                ..StaleClaimsConfig::default()
+            },
+        };
+        let classifier = SmartClassifier::new(TemporalConfig::default());
+
+        let included = classifier.classify(&input(true));
+        assert_eq!(included.auto_resolved, 1);
+        assert_eq!(included.stale_claims.len(), 1);
+        assert!(included.stale_claims[0].auto_resolved);
+
+        let skipped = classifier.classify(&input(false));
+        assert!(skipped.stale_claims.is_empty());
+        assert_eq!(skipped.stale_total, 0);
+    }
+
+    #[test]
+    fn stated_confidence_prior_sends_group_to_review() {
+        let now = 1_000_000_000;
+        let input = |confidence: Option<f32>| {
+            let claims = vec![
+                make_claim("ALICE", "is_dev", "GitHub", "human:alice", now - 2000),
+                make_claim("ALICE", "is_dev", "GitHub", "human:bob", now - 1000),
+            ];
+            ClassifyInput {
+                claim_groups: vec![ClaimGroup {
+                    key: "ALICE|is_dev|GitHub".to_string(),
+                    claims: claims
+                        .into_iter()
+                        .map(|c| ClaimInput { confidence, ..c })
+                        .collect(),
+                }],
+                config: ClassificationPolicy::default(),
+                now_ms: now,
+                stale: StaleClaimsConfig::default(),
+            }
+        };
+        let classifier = SmartClassifier::new(TemporalConfig::default());
+
+        let unstated = classifier.classify(&input(None));
+        assert_eq!(unstated.conflicts[0].strategy, "show_all_sources");
+
+        // Two humans agree, but both say they were barely sure
+        let doubtful = classifier.classify(&input(Some(0.1)));
+        let c = &doubtful.conflicts[0];
+        assert!(c.confidence < 0.3, "confidence={}", c.confidence);
+        assert_eq!(c.strategy, "human_review");
+        assert_eq!(c.winning_claim, None);
+    }
+
+    /// Three groups, each sitting on one policy decision:
+    /// - `llm:gpt` repeating itself within the verification window
+    /// - a human and `system:ldap` (two levels apart) agreeing
+    /// - two different LLMs agreeing
+    fn policy_fixture() -> serde_json::Value {
+        let now = 1_000_000_000_i64;
+        let claim = |predicate: &str, context: &str, actor: &str, age: i64, id: &str| {
+            serde_json::json!({
+                "subject": "BOB", "predicate": predicate, "context": context, "actor": actor,
+                "timestamp_ms": now - age, "source_id": id
+            })
+        };
+        serde_json::json!({
+            "claim_groups": [
+                {"key": "BOB|is_admin|prod", "claims": [
+                    claim("is_admin", "prod", "llm:gpt", 30_000, "as-a1"),
+                    claim("is_admin", "prod", "llm:gpt", 5_000, "as-a2"),
+                ]},
+                {"key": "BOB|team|platform", "claims": [
+                    claim("team", "platform", "human:alice", 20_000, "as-b1"),
+                    claim("team", "platform", "system:ldap", 10_000, "as-b2"),
+                ]},
+                {"key": "BOB|location|hq", "claims": [
+                    claim("location", "hq", "llm:gpt", 15_000, "as-c1"),
+                    claim("location", "hq", "llm:claude", 12_000, "as-c2"),
+                ]},
+            ],
+            "now_ms": now
+        })
+    }
+
+    /// Conflict types of the fixture under a per-call policy
+    fn fixture_types(policy: serde_json::Value) -> Vec<ConflictType> {
+        let mut input = policy_fixture();
+        input["config"] = policy;
+        let output: ClassifyOutput =
+            serde_json::from_str(&classify_claims(&input.to_string())).unwrap();
+        output.conflicts.iter().map(|c| c.conflict_type).collect()
+    }
+
+    #[test]
+    fn default_policy_matches_golden_output() {
+        const GOLDEN: &str = r#"{"conflicts":[{"subject":"BOB","predicate":"is_admin","context":"prod","conflict_type":"Verification","confidence":1.0,"strategy":"show_all_sources","actor_hierarchy":[{"actor":"llm:gpt","credibility":"Llm","timestamp":999970000},{"actor":"llm:gpt","credibility":"Llm","timestamp":999995000}],"temporal_pattern":"simultaneous","auto_resolved":true,"source_ids":["as-a1","as-a2"],"supporting_source_ids":["as-a1","as-a2"],"superseded_source_ids":[]},{"subject":"BOB","predicate":"team","context":"platform","conflict_type":"Supersession","confidence":1.0,"strategy":"show_highest_authority","actor_hierarchy":[{"actor":"human:alice","credibility":"Human","timestamp":999980000},{"actor":"system:ldap","credibility":"System","timestamp":999990000}],"temporal_pattern":"simultaneous","auto_resolved":true,"source_ids":["as-b1","as-b2"],"winning_claim":"as-b1","supporting_source_ids":["as-b1"],"superseded_source_ids":["as-b2"]},{"subject":"BOB","predicate":"location","context":"hq","conflict_type":"Verification","confidence":1.0,"strategy":"show_all_sources","actor_hierarchy":[{"actor":"llm:gpt","credibility":"Llm","timestamp":999985000},{"actor":"llm:claude","credibility":"Llm","timestamp":999988000}],"temporal_pattern":"simultaneous","auto_resolved":true,"source_ids":["as-c1","as-c2"],"supporting_source_ids":["as-c1","as-c2"],"superseded_source_ids":[]}],"auto_resolved":3,"review_required":0,"total_analyzed":3,"resolved_source_ids":["as-a2","as-c2","as-c1","as-b1","as-a1"],"stale_claims":[],"stale_total":0}"#;
+
+        assert_eq!(classify_claims(&policy_fixture().to_string()), GOLDEN);
+        // Spelling out the defaults changes nothing
+        let mut input = policy_fixture();
+        input["config"] = serde_json::to_value(ClassificationPolicy::default()).unwrap();
+        assert_eq!(classify_claims(&input.to_string()), GOLDEN);
+    }
+
+    #[test]
+    fn policies_change_fixture_outcomes() {
+        use ConflictType::*;
+
+        assert_eq!(
+            fixture_types(serde_json::Value::Null),
+            vec![Verification, Supersession, Verification]
+        );
+
+        // The repeated LLM claim becomes an update rather than agreement
+        assert_eq!(
+            fixture_types(serde_json::json!({"same_actor_always_evolution": true})),
+            vec![Evolution, Supersession, Verification]
+        );
+
+        // Human vs system is only two levels apart, so the two merely agree
+        assert_eq!(
+            fixture_types(serde_json::json!({"min_credibility_gap_for_supersession": 3})),
+            vec![Verification, Verification, Verification]
+        );
+
+        // Claimants of a single kind no longer verify each other
+        assert_eq!(
+            fixture_types(serde_json::json!({"verification_requires_distinct_actor_kinds": true})),
+            vec![Review, Supersession, Review]
+        );
+    }
 }
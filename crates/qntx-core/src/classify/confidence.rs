@@ -6,6 +6,11 @@
 //! - Temporal pattern bonus (simultaneous vs distributed)
 //! - Recency bonus (how recent the most recent claim is)
 //! - Consistency bonus (all claims agree on predicate)
+//!
+//! Claims may carry the confidence their creator assigned (a detector score,
+//! an extractor's certainty). That acts as a prior: the mean stated confidence
+//! scales the score, so claims nobody was sure of can't add up to a confident
+//! classification.
 
 use super::credibility::ActorCredibility;
 use super::temporal::{ClaimTiming, TemporalAnalyzer};
@@ -18,6 +23,8 @@ pub struct ClaimWithTiming {
     pub predicate: String,
     pub subject: String,
     pub context: String,
+    /// Creator-assigned confidence (0.0–1.0), if any
+    pub confidence: Option<f32>,
 }
 
 impl ClaimWithTiming {
@@ -57,7 +64,7 @@ impl<'a> ConfidenceCalculator<'a> {
         }
 
         if claims.len() == 1 {
-            return self.single_claim_confidence(&claims[0], now_ms);
+            return self.single_claim_confidence(&claims[0], now_ms) * Self::prior(claims);
         }
 
         let base_score = 0.5;
@@ -74,7 +81,22 @@ impl<'a> ConfidenceCalculator<'a> {
             + recency_bonus
             + consistency_bonus;
 
-        total.min(1.0)
+        total.min(1.0) * Self::prior(claims)
+    }
+
+    /// Mean creator-assigned confidence of the claims that carry one;
+    /// 1.0 (no effect) when none do.
+    fn prior(claims: &[ClaimWithTiming]) -> f64 {
+        let stated: Vec<f64> = claims
+            .iter()
+            .filter_map(|c| c.confidence)
+            .map(|c| f64::from(c.clamp(0.0, 1.0)))
+            .collect();
+        if stated.is_empty() {
+            1.0
+        } else {
+            stated.iter().sum::<f64>() / stated.len() as f64
+        }
     }
 
     /// Whether a confidence score requires human review
@@ -185,6 +207,7 @@ mod tests {
             predicate: predicate.to_string(),
             subject: "ALICE".to_string(),
             context: "GitHub".to_string(),
+            confidence: None,
         }
     }
 
@@ -233,4 +256,26 @@ mod tests {
         assert!(cc.requires_review(0.3));
         assert!(!cc.requires_review(0.6));
     }
+
+    #[test]
+    fn stated_confidence_scales_score() {
+        let (ta, now) = setup();
+        let cc = ConfidenceCalculator::new(&ta);
+        let claims = vec![
+            claim("human:alice", now - 1000, "is_author_of"),
+            claim("human:bob", now - 2000, "is_author_of"),
+        ];
+        let unstated = cc.calculate(&claims, now);
+
+        let mut stated = claims.clone();
+        stated[0].confidence = Some(0.2);
+        stated[1].confidence = Some(0.4);
+        let score = cc.calculate(&stated, now);
+        assert!((score - unstated * 0.3).abs() < 1e-6, "got {}", score);
+
+        // Claims without a stated confidence don't dilute the prior
+        stated[1].confidence = None;
+        let score = cc.calculate(&stated, now);
+        assert!((score - unstated * 0.2).abs() < 1e-6, "got {}", score);
+    }
 }
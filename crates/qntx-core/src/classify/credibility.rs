@@ -2,6 +2,8 @@
 
 use serde::{Deserialize, Serialize};
 
+use crate::actor_alias::ActorAliasMap;
+
 /// Actor credibility levels
 ///
 /// Higher values indicate more trustworthy sources.
@@ -49,6 +51,12 @@ impl ActorCredibility {
         }
     }
 
+    /// Like [`from_actor`](Self::from_actor), after resolving `actor` to its
+    /// canonical actor, so `alice` ranks as `human:alice` when aliased to it
+    pub fn from_actor_with_aliases(actor: &str, aliases: &ActorAliasMap) -> Self {
+        Self::from_actor(aliases.normalize(actor))
+    }
+
     /// Check if this is a human actor
     pub fn is_human(&self) -> bool {
         *self == Self::Human
@@ -137,6 +145,19 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_aliases_resolve_before_prefix_matching() {
+        let aliases = ActorAliasMap::from_json(r#"{"human:alice": ["alice"]}"#).unwrap();
+        assert_eq!(
+            ActorCredibility::from_actor_with_aliases("alice", &aliases),
+            ActorCredibility::Human
+        );
+        assert_eq!(
+            ActorCredibility::from_actor("alice"),
+            ActorCredibility::External
+        );
+    }
+
     #[test]
     fn test_ordering() {
         assert!(ActorCredibility::Human > ActorCredibility::Llm);
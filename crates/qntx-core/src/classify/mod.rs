@@ -26,13 +26,20 @@
 pub mod classifier;
 pub mod confidence;
 mod credibility;
+mod policy;
+pub mod provenance;
 pub mod temporal;
 mod types;
 
 pub use classifier::{
-    classify_claims, ClaimGroup, ClaimInput, ClassifyInput, ClassifyOutput, SmartClassifier,
+    classify_claims, classify_claims_with_defaults, ClaimGroup, ClaimInput, ClassifyInput,
+    ClassifyOutput, ConflictOutput, SmartClassifier, StaleClaim, StaleClaimsConfig,
 };
 pub use confidence::{ClaimWithTiming, ConfidenceCalculator};
 pub use credibility::ActorCredibility;
+pub use policy::ClassificationPolicy;
+pub use provenance::{
+    claims_supported_by, resolve_provenance, supported_claims, ResolvedProvenance, SupportedClaim,
+};
 pub use temporal::{ClaimTiming, TemporalAnalyzer, TemporalConfig, TemporalPattern};
-pub use types::{ActorRanking, ClassificationResult, ConflictType};
+pub use types::{ActorRanking, ClaimProvenance, ClassificationResult, ConflictType};
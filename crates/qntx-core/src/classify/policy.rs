@@ -0,0 +1,69 @@
+//! Classification policy: the dials behind each resolution decision
+
+use serde::{Deserialize, Serialize};
+
+use super::credibility::ActorCredibility;
+use super::temporal::TemporalConfig;
+use crate::actor_alias::ActorAliasMap;
+
+/// Tunable decision rules for [`SmartClassifier`](super::SmartClassifier).
+///
+/// The defaults reproduce the classifier's built-in behavior exactly. In
+/// JSON the time windows sit next to the policy fields, so an existing
+/// `TemporalConfig` document is a valid policy.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ClassificationPolicy {
+    /// Time windows used by every temporal decision
+    #[serde(flatten)]
+    pub temporal: TemporalConfig,
+    /// Classify a single actor's claims as Evolution even when they all fall
+    /// within the verification window (default: false, a gap wider than the
+    /// window is required)
+    pub same_actor_always_evolution: bool,
+    /// How many credibility levels the lowest claimant must sit below a human
+    /// claimant for Supersession (default: 1, any non-human). Values below 1
+    /// act as 1, since equal credibility cannot supersede; values above 3
+    /// disable Supersession.
+    pub min_credibility_gap_for_supersession: u8,
+    /// Only classify as Verification when the claimants span at least two
+    /// credibility kinds (default: false, any claimants qualify, even a single
+    /// actor repeating itself)
+    pub verification_requires_distinct_actor_kinds: bool,
+    /// Actors treated as one identity (default: none). Claims are compared
+    /// and ranked by canonical actor, so `alice` and `human:alice@verified`
+    /// aliased to `human:alice` count as the same actor.
+    #[serde(skip_serializing_if = "ActorAliasMap::is_empty")]
+    pub actor_aliases: ActorAliasMap,
+}
+
+impl Default for ClassificationPolicy {
+    fn default() -> Self {
+        Self {
+            temporal: TemporalConfig::default(),
+            same_actor_always_evolution: false,
+            min_credibility_gap_for_supersession: 1,
+            verification_requires_distinct_actor_kinds: false,
+            actor_aliases: ActorAliasMap::new(),
+        }
+    }
+}
+
+impl From<TemporalConfig> for ClassificationPolicy {
+    /// Default policy with the given windows
+    fn from(temporal: TemporalConfig) -> Self {
+        Self {
+            temporal,
+            ..Self::default()
+        }
+    }
+}
+
+impl ClassificationPolicy {
+    /// Whether the credibility spread between a human and the lowest claimant
+    /// is wide enough for Supersession
+    pub(crate) fn supersedes(&self, lowest: ActorCredibility) -> bool {
+        let gap = ActorCredibility::Human as u8 - lowest as u8;
+        gap >= self.min_credibility_gap_for_supersession.max(1)
+    }
+}
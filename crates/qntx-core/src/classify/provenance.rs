@@ -0,0 +1,283 @@
+//! Claim provenance
+//!
+//! Classification names the attestations behind every outcome
+//! ([`ClaimProvenance`]). This module goes both ways from there:
+//!
+//! - [`resolve_provenance`] fetches those attestations for display
+//! - [`claims_supported_by`] starts from one attestation and finds the resolved
+//!   claims it supports, e.g. to warn before deleting or revoking it
+
+use serde::{Deserialize, Serialize};
+
+use super::classifier::{ClaimGroup, ClaimInput, SmartClassifier};
+use super::policy::ClassificationPolicy;
+use super::types::{ClaimProvenance, ConflictType};
+use crate::attestation::{Attestation, AxFilter};
+use crate::expand::{expand_cartesian, group_by_key, ExpandAttestation};
+use crate::storage::{AttestationStore, QueryStore, StoreError};
+
+/// The attestations of a [`ClaimProvenance`]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ResolvedProvenance {
+    pub supporting: Vec<Attestation>,
+    pub superseded: Vec<Attestation>,
+    /// Source IDs no longer in the store
+    pub missing: Vec<String>,
+}
+
+/// Fetch the attestations behind a classification outcome, in the order of
+/// its ID lists.
+pub fn resolve_provenance<S: AttestationStore + ?Sized>(
+    store: &S,
+    provenance: &ClaimProvenance,
+) -> Result<ResolvedProvenance, StoreError> {
+    let mut resolved = ResolvedProvenance::default();
+    for (ids, into) in [
+        (&provenance.supporting_source_ids, &mut resolved.supporting),
+        (&provenance.superseded_source_ids, &mut resolved.superseded),
+    ] {
+        for id in ids {
+            match store.get(id)? {
+                Some(attestation) => into.push(attestation),
+                None => resolved.missing.push(id.clone()),
+            }
+        }
+    }
+    Ok(resolved)
+}
+
+/// A resolved (subject, predicate, context) claim and its sources
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SupportedClaim {
+    pub subject: String,
+    pub predicate: String,
+    pub context: String,
+    /// How the claim's sources relate; `None` when it has a single source
+    pub conflict_type: Option<ConflictType>,
+    /// Conflict confidence, or the single source's own (0.5 when unset)
+    pub confidence: f64,
+    #[serde(flatten)]
+    pub provenance: ClaimProvenance,
+}
+
+impl SupportedClaim {
+    /// Whether `id` is the only attestation supporting the claim, so removing
+    /// it removes the claim rather than weakening it
+    pub fn solely_supported_by(&self, id: &str) -> bool {
+        self.provenance.supporting_source_ids == [id]
+    }
+}
+
+/// Resolved claims that the attestation `id` supports, classified among
+/// `attestations` under `policy`. Every claim of `id` is listed unless
+/// resolution set it aside (an older evolution step, a superseded authority).
+///
+/// `attestations` must contain `id` itself; other attestations that don't
+/// share one of its claims are ignored. Claims come out sorted by
+/// (subject, predicate, context).
+pub fn supported_claims(
+    id: &str,
+    attestations: &[Attestation],
+    policy: &ClassificationPolicy,
+    now_ms: i64,
+) -> Vec<SupportedClaim> {
+    let Some(target) = attestations.iter().find(|a| a.id == id) else {
+        return Vec::new();
+    };
+    let target_claims = expand_cartesian(&[ExpandAttestation::from(target)]);
+    let keys: std::collections::HashSet<(&str, &str, &str)> = target_claims
+        .iter()
+        .map(|c| (c.subject.as_str(), c.predicate.as_str(), c.context.as_str()))
+        .collect();
+
+    let expand: Vec<ExpandAttestation> = attestations.iter().map(ExpandAttestation::from).collect();
+    let claims: Vec<_> = expand_cartesian(&expand)
+        .into_iter()
+        .filter(|c| keys.contains(&(c.subject.as_str(), c.predicate.as_str(), c.context.as_str())))
+        .collect();
+
+    let classifier = SmartClassifier::with_policy(policy.clone());
+    group_by_key(&claims)
+        .into_iter()
+        .filter_map(|group| {
+            let group = ClaimGroup {
+                key: group.key,
+                claims: group
+                    .claims
+                    .into_iter()
+                    .map(|c| ClaimInput {
+                        subject: c.subject,
+                        predicate: c.predicate,
+                        context: c.context,
+                        actor: c.actor,
+                        timestamp_ms: c.timestamp_ms,
+                        source_id: c.source_id,
+                        confidence: c.confidence,
+                    })
+                    .collect(),
+            };
+            let first = &group.claims[0];
+            let claim = match classifier.classify_one(&group, now_ms) {
+                Some(conflict) => SupportedClaim {
+                    subject: conflict.subject,
+                    predicate: conflict.predicate,
+                    context: conflict.context,
+                    conflict_type: Some(conflict.conflict_type),
+                    confidence: conflict.confidence,
+                    provenance: conflict.provenance,
+                },
+                None => SupportedClaim {
+                    subject: first.subject.clone(),
+                    predicate: first.predicate.clone(),
+                    context: first.context.clone(),
+                    conflict_type: None,
+                    confidence: first.confidence.map_or(0.5, f64::from),
+                    provenance: ClaimProvenance {
+                        supporting_source_ids: vec![first.source_id.clone()],
+                        superseded_source_ids: Vec::new(),
+                    },
+                },
+            };
+            claim.provenance.supports(id).then_some(claim)
+        })
+        .collect()
+}
+
+/// Resolved claims that the stored attestation `id` supports: loads every
+/// attestation sharing its subjects, predicates and contexts and runs
+/// [`supported_claims`] over them. Empty if `id` isn't stored.
+pub fn claims_supported_by<S: QueryStore + ?Sized>(
+    store: &S,
+    id: &str,
+    policy: &ClassificationPolicy,
+    now_ms: i64,
+) -> Result<Vec<SupportedClaim>, StoreError> {
+    let Some(target) = store.get(id)? else {
+        return Ok(Vec::new());
+    };
+    let result = store.query(&supporting_filter(&target))?;
+    Ok(supported_claims(id, &result.attestations, policy, now_ms))
+}
+
+/// Filter loading every attestation that may share a claim with `target`
+pub fn supporting_filter(target: &Attestation) -> AxFilter {
+    AxFilter {
+        subjects: target.subjects.clone(),
+        predicates: target.predicates.clone(),
+        contexts: target.contexts.clone(),
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::MemoryStore;
+    use crate::AttestationBuilder;
+
+    const NOW: i64 = 1_000_000_000;
+
+    fn attestation(id: &str, predicate: &str, actor: &str, ts: i64) -> Attestation {
+        AttestationBuilder::new()
+            .id(id)
+            .subject("ALICE")
+            .predicate(predicate)
+            .context("acme")
+            .actor(actor)
+            .timestamp(ts)
+            .source("test")
+            .build()
+    }
+
+    /// Three actors agreeing within the verification window, plus an
+    /// unrelated claim of one of them
+    fn verification_store() -> MemoryStore {
+        let mut store = MemoryStore::new();
+        for (id, actor, ts) in [
+            ("as-v1", "llm:claude", NOW - 30_000),
+            ("as-v2", "llm:gpt", NOW - 20_000),
+            ("as-v3", "system:hr", NOW - 10_000),
+        ] {
+            store.put(attestation(id, "works_at", actor, ts)).unwrap();
+        }
+        store
+            .put(attestation("as-other", "lives_in", "llm:gpt", NOW))
+            .unwrap();
+        store
+    }
+
+    #[test]
+    fn verification_group_supports_from_every_source() {
+        let store = verification_store();
+        let policy = ClassificationPolicy::default();
+
+        for id in ["as-v1", "as-v2", "as-v3"] {
+            let claims = claims_supported_by(&store, id, &policy, NOW).unwrap();
+            assert_eq!(claims.len(), 1, "{}", id);
+            let claim = &claims[0];
+            assert_eq!(claim.predicate, "works_at");
+            assert_eq!(claim.conflict_type, Some(ConflictType::Verification));
+            assert_eq!(
+                claim.provenance.supporting_source_ids,
+                ["as-v1", "as-v2", "as-v3"]
+            );
+            assert!(claim.provenance.superseded_source_ids.is_empty());
+            assert!(!claim.solely_supported_by(id));
+        }
+
+        let resolved = resolve_provenance(
+            &store,
+            &claims_supported_by(&store, "as-v1", &policy, NOW).unwrap()[0].provenance,
+        )
+        .unwrap();
+        let ids: Vec<&str> = resolved.supporting.iter().map(|a| a.id.as_str()).collect();
+        assert_eq!(ids, ["as-v1", "as-v2", "as-v3"]);
+        assert!(resolved.missing.is_empty());
+    }
+
+    #[test]
+    fn superseded_sources_support_nothing() {
+        let mut store = MemoryStore::new();
+        store
+            .put(attestation("as-llm", "works_at", "llm:gpt", NOW - 20_000))
+            .unwrap();
+        store
+            .put(attestation(
+                "as-human",
+                "works_at",
+                "human:alice",
+                NOW - 10_000,
+            ))
+            .unwrap();
+        let policy = ClassificationPolicy::default();
+
+        assert!(claims_supported_by(&store, "as-llm", &policy, NOW)
+            .unwrap()
+            .is_empty());
+        let claims = claims_supported_by(&store, "as-human", &policy, NOW).unwrap();
+        assert_eq!(claims[0].conflict_type, Some(ConflictType::Supersession));
+        assert_eq!(claims[0].provenance.superseded_source_ids, ["as-llm"]);
+        assert!(claims[0].solely_supported_by("as-human"));
+
+        // A deleted source shows up as missing
+        store.delete("as-llm").unwrap();
+        let resolved = resolve_provenance(&store, &claims[0].provenance).unwrap();
+        assert_eq!(resolved.supporting.len(), 1);
+        assert_eq!(resolved.missing, ["as-llm"]);
+    }
+
+    #[test]
+    fn single_source_claims_and_unknown_ids() {
+        let store = verification_store();
+        let policy = ClassificationPolicy::default();
+
+        let claims = claims_supported_by(&store, "as-other", &policy, NOW).unwrap();
+        assert_eq!(claims.len(), 1);
+        assert_eq!(claims[0].conflict_type, None);
+        assert!(claims[0].solely_supported_by("as-other"));
+
+        assert!(claims_supported_by(&store, "as-missing", &policy, NOW)
+            .unwrap()
+            .is_empty());
+    }
+}
@@ -5,9 +5,11 @@
 
 use serde::{Deserialize, Serialize};
 
+use crate::duration::{DurationUnit, CALENDAR_POLICY};
+
 /// Configurable time windows for temporal classification.
 /// All values are in milliseconds.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct TemporalConfig {
     /// Window within which claims are considered simultaneous (default: 60_000ms = 1 minute)
     pub verification_window_ms: i64,
@@ -20,9 +22,9 @@ pub struct TemporalConfig {
 impl Default for TemporalConfig {
     fn default() -> Self {
         Self {
-            verification_window_ms: 60_000,         // 1 minute
-            evolution_window_ms: 86_400_000,        // 24 hours
-            obsolescence_window_ms: 31_536_000_000, // ~365 days
+            verification_window_ms: 60_000, // 1 minute
+            evolution_window_ms: DurationUnit::Days.millis(CALENDAR_POLICY),
+            obsolescence_window_ms: DurationUnit::Years.millis(CALENDAR_POLICY),
         }
     }
 }
@@ -162,6 +164,22 @@ impl TemporalAnalyzer {
     pub fn most_recent(&self, timings: &[ClaimTiming]) -> Option<i64> {
         timings.iter().map(|t| t.timestamp_ms).max()
     }
+
+    /// Get the most recent timing (first one on ties)
+    pub fn latest<'a>(&self, timings: &'a [ClaimTiming]) -> Option<&'a ClaimTiming> {
+        timings.iter().reduce(|best, t| {
+            if t.timestamp_ms > best.timestamp_ms {
+                t
+            } else {
+                best
+            }
+        })
+    }
+
+    /// Check if a timestamp is older than the obsolescence window
+    pub fn is_obsolete(&self, timestamp_ms: i64, now_ms: i64) -> bool {
+        now_ms - timestamp_ms > self.config.obsolescence_window_ms
+    }
 }
 
 #[cfg(test)]
@@ -61,6 +61,52 @@ pub struct ClassificationResult {
     pub auto_resolved: bool,
     /// Suggested resolution strategy
     pub strategy: &'static str,
+    /// Attestations behind the outcome, by role
+    #[serde(flatten)]
+    pub provenance: ClaimProvenance,
+}
+
+/// Source attestations of a classified claim, partitioned by the role their
+/// claims play once the resolution strategy is applied. An attestation listed
+/// as supporting is never also listed as superseded.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ClaimProvenance {
+    /// Attestations whose claims survive resolution (every source of a
+    /// verification, the winner of an evolution or supersession), sorted
+    #[serde(default)]
+    pub supporting_source_ids: Vec<String>,
+    /// Attestations whose claims resolution set aside, sorted
+    #[serde(default)]
+    pub superseded_source_ids: Vec<String>,
+}
+
+impl ClaimProvenance {
+    /// Partition `sources` into those in `survivors` and the rest. Both lists
+    /// come out sorted and deduplicated.
+    pub fn partition<'a>(sources: impl IntoIterator<Item = &'a str>, survivors: &[String]) -> Self {
+        let survivors: std::collections::BTreeSet<&str> =
+            survivors.iter().map(String::as_str).collect();
+        let sources: std::collections::BTreeSet<&str> = sources.into_iter().collect();
+        let (supporting, superseded): (Vec<&str>, Vec<&str>) =
+            sources.into_iter().partition(|id| survivors.contains(id));
+        Self {
+            supporting_source_ids: supporting.into_iter().map(String::from).collect(),
+            superseded_source_ids: superseded.into_iter().map(String::from).collect(),
+        }
+    }
+
+    /// Whether `id` supports the claim
+    pub fn supports(&self, id: &str) -> bool {
+        self.supporting_source_ids.iter().any(|s| s == id)
+    }
+
+    /// Every source, supporting first
+    pub fn all_source_ids(&self) -> impl Iterator<Item = &str> {
+        self.supporting_source_ids
+            .iter()
+            .chain(&self.superseded_source_ids)
+            .map(String::as_str)
+    }
 }
 
 /// Actor with credibility ranking
@@ -84,6 +130,13 @@ impl ClassificationResult {
             conflict_type,
             confidence,
             actor_rankings,
+            provenance: ClaimProvenance::default(),
         }
     }
+
+    /// Attach the attestations behind the outcome
+    pub fn with_provenance(mut self, provenance: ClaimProvenance) -> Self {
+        self.provenance = provenance;
+        self
+    }
 }
@@ -0,0 +1,275 @@
+//! Unified configuration for the core engine.
+//!
+//! One document configures every tunable in qntx-core, so the native server,
+//! the wazero module and the browser build agree instead of each carrying its
+//! own defaults:
+//!
+//! ```json
+//! {
+//!   "classify": {"verification_window_ms": 30000},
+//!   "lint": {"disabled": ["future_date"]},
+//!   "slow_ops": {"classify_ms": 50}
+//! }
+//! ```
+//!
+//! Sections and fields are optional; anything omitted keeps the built-in
+//! default. Layers resolve as defaults < document < per-call overrides, see
+//! [`QntxCoreConfig::resolve`].
+//!
+//! With the `toml` feature the same document can be written as TOML, one
+//! table per section (`[classify]`), see [`QntxCoreConfig::from_toml`].
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use thiserror::Error;
+
+use crate::classify::{ClassificationPolicy, TemporalConfig};
+use crate::lint::LintConfig;
+use crate::slow_ops::SlowOpConfig;
+
+/// Every qntx-core tunable in one serde document.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct QntxCoreConfig {
+    /// Time windows and decision policy used by claim classification
+    pub classify: ClassificationPolicy,
+    /// AX query lint rules
+    pub lint: LintConfig,
+    /// Slow-operation thresholds, applied with [`crate::set_slow_op_config`]
+    pub slow_ops: SlowOpConfig,
+}
+
+/// A single problem found while validating a config.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConfigViolation {
+    /// Dotted path of the offending field, e.g. `classify.evolution_window_ms`
+    pub field: String,
+    pub message: String,
+}
+
+/// Config loading failure. Validation collects every violation rather than
+/// stopping at the first, so operators can fix a document in one pass.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum ConfigError {
+    #[error("invalid config document: {0}")]
+    Parse(String),
+
+    #[error("invalid config: {}", format_violations(.0))]
+    Invalid(Vec<ConfigViolation>),
+}
+
+fn format_violations(violations: &[ConfigViolation]) -> String {
+    violations
+        .iter()
+        .map(|v| format!("{}: {}", v.field, v.message))
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+impl QntxCoreConfig {
+    /// Parse and validate a JSON document.
+    pub fn from_json(document: &str) -> Result<Self, ConfigError> {
+        let value: Value =
+            serde_json::from_str(document).map_err(|e| ConfigError::Parse(e.to_string()))?;
+        Self::resolve(&[&value])
+    }
+
+    /// Parse and validate a TOML document laid out like the JSON one.
+    #[cfg(feature = "toml")]
+    pub fn from_toml(document: &str) -> Result<Self, ConfigError> {
+        let value: Value =
+            toml::from_str(document).map_err(|e| ConfigError::Parse(e.to_string()))?;
+        Self::resolve(&[&value])
+    }
+
+    /// Layer partial documents over the defaults, later layers winning
+    /// field by field, then validate the result.
+    ///
+    /// Pass `[document, overrides]` for the usual defaults < document <
+    /// per-call precedence. `null` layers are skipped.
+    pub fn resolve(layers: &[&Value]) -> Result<Self, ConfigError> {
+        let mut merged =
+            serde_json::to_value(Self::default()).map_err(|e| ConfigError::Parse(e.to_string()))?;
+        for layer in layers {
+            merge_value(&mut merged, layer);
+        }
+        let config: Self =
+            serde_json::from_value(merged).map_err(|e| ConfigError::Parse(e.to_string()))?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Check every field, returning all violations at once.
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        let mut violations = Vec::new();
+        validate_temporal(&self.classify.temporal, "classify", &mut violations);
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(ConfigError::Invalid(violations))
+        }
+    }
+}
+
+/// Classification windows must be positive and ordered.
+fn validate_temporal(
+    config: &TemporalConfig,
+    section: &str,
+    violations: &mut Vec<ConfigViolation>,
+) {
+    let windows = [
+        ("verification_window_ms", config.verification_window_ms),
+        ("evolution_window_ms", config.evolution_window_ms),
+        ("obsolescence_window_ms", config.obsolescence_window_ms),
+    ];
+    for (name, value) in windows {
+        if value <= 0 {
+            violations.push(ConfigViolation {
+                field: format!("{}.{}", section, name),
+                message: format!("must be positive, got {}", value),
+            });
+        }
+    }
+    // The classifier assumes verification < evolution < obsolescence
+    if config.verification_window_ms > config.evolution_window_ms {
+        violations.push(ConfigViolation {
+            field: format!("{}.verification_window_ms", section),
+            message: "must not exceed evolution_window_ms".to_string(),
+        });
+    }
+    if config.evolution_window_ms > config.obsolescence_window_ms {
+        violations.push(ConfigViolation {
+            field: format!("{}.evolution_window_ms", section),
+            message: "must not exceed obsolescence_window_ms".to_string(),
+        });
+    }
+}
+
+/// Recursively merge `patch` into `base`: objects merge key by key, any
+/// other value replaces. A `null` patch leaves `base` untouched.
+pub(crate) fn merge_value(base: &mut Value, patch: &Value) {
+    match (base, patch) {
+        (_, Value::Null) => {}
+        (Value::Object(base), Value::Object(patch)) => {
+            for (key, value) in patch {
+                match base.get_mut(key) {
+                    Some(existing) => merge_value(existing, value),
+                    None => {
+                        base.insert(key.clone(), value.clone());
+                    }
+                }
+            }
+        }
+        (base, patch) => *base = patch.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn empty_document_matches_defaults() {
+        let config = QntxCoreConfig::from_json("{}").unwrap();
+        assert_eq!(config, QntxCoreConfig::default());
+        assert_eq!(config.classify.temporal.verification_window_ms, 60_000);
+    }
+
+    #[test]
+    fn partial_document_keeps_other_defaults() {
+        let config =
+            QntxCoreConfig::from_json(r#"{"classify": {"verification_window_ms": 30000}}"#)
+                .unwrap();
+        assert_eq!(config.classify.temporal.verification_window_ms, 30_000);
+        assert_eq!(
+            config.classify.temporal.evolution_window_ms,
+            TemporalConfig::default().evolution_window_ms
+        );
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn toml_document_matches_json() {
+        let toml = QntxCoreConfig::from_toml(
+            "[classify]\nverification_window_ms = 30000\n\n[lint]\ndisabled = [\"future_date\"]\n",
+        )
+        .unwrap();
+        let json = QntxCoreConfig::from_json(
+            r#"{"classify": {"verification_window_ms": 30000}, "lint": {"disabled": ["future_date"]}}"#,
+        )
+        .unwrap();
+        assert_eq!(toml, json);
+
+        let err = QntxCoreConfig::from_toml("[classify]\nevolution_window_ms = -5\n").unwrap_err();
+        assert!(matches!(err, ConfigError::Invalid(_)), "{:?}", err);
+        let err = QntxCoreConfig::from_toml("[classify").unwrap_err();
+        assert!(matches!(err, ConfigError::Parse(_)), "{:?}", err);
+    }
+
+    #[test]
+    fn classify_policy_sits_beside_windows() {
+        let config = QntxCoreConfig::from_json(
+            r#"{"classify": {"same_actor_always_evolution": true, "evolution_window_ms": 3600000}}"#,
+        )
+        .unwrap();
+        assert!(config.classify.same_actor_always_evolution);
+        assert_eq!(config.classify.temporal.evolution_window_ms, 3_600_000);
+        assert_eq!(config.classify.min_credibility_gap_for_supersession, 1);
+    }
+
+    #[test]
+    fn precedence_defaults_document_overrides() {
+        let document =
+            json!({"classify": {"verification_window_ms": 30000, "evolution_window_ms": 3600000}});
+        let overrides = json!({"classify": {"verification_window_ms": 10000}});
+
+        let config = QntxCoreConfig::resolve(&[&document, &overrides]).unwrap();
+        assert_eq!(config.classify.temporal.verification_window_ms, 10_000); // override
+        assert_eq!(config.classify.temporal.evolution_window_ms, 3_600_000); // document
+        assert_eq!(
+            config.classify.temporal.obsolescence_window_ms,
+            31_536_000_000
+        ); // default
+
+        // A null layer (no per-call config) changes nothing
+        let config = QntxCoreConfig::resolve(&[&document, &Value::Null]).unwrap();
+        assert_eq!(config.classify.temporal.verification_window_ms, 30_000);
+    }
+
+    #[test]
+    fn validation_lists_every_violation() {
+        let err = QntxCoreConfig::from_json(
+            r#"{"classify": {"verification_window_ms": 0, "evolution_window_ms": -5}}"#,
+        )
+        .unwrap_err();
+
+        let ConfigError::Invalid(violations) = &err else {
+            panic!("expected validation failure, got {:?}", err);
+        };
+        let fields: Vec<&str> = violations.iter().map(|v| v.field.as_str()).collect();
+        assert_eq!(
+            fields,
+            vec![
+                "classify.verification_window_ms",
+                "classify.evolution_window_ms",
+                "classify.verification_window_ms",
+            ]
+        );
+        let message = err.to_string();
+        assert!(message.contains("classify.verification_window_ms: must be positive, got 0"));
+        assert!(message.contains("classify.evolution_window_ms: must be positive, got -5"));
+    }
+
+    #[test]
+    fn malformed_document_is_a_parse_error() {
+        assert!(matches!(
+            QntxCoreConfig::from_json(r#"{"classify": {"verification_window_ms": "soon"}}"#),
+            Err(ConfigError::Parse(_))
+        ));
+        assert!(matches!(
+            QntxCoreConfig::from_json("not json"),
+            Err(ConfigError::Parse(_))
+        ));
+    }
+}
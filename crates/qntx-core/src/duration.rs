@@ -0,0 +1,400 @@
+//! Calendar durations ("5y", "1y6m", "2w3d") and their conversion to milliseconds.
+//!
+//! Months and years have no fixed length, so every conversion names a
+//! [`CalendarPolicy`]. Code inside qntx-core converts through
+//! [`CALENDAR_POLICY`] so that "over 1m", "1 month ago" and the classify
+//! windows all mean the same span.
+
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use thiserror::Error;
+
+use crate::temporal::{civil_from_days, days_from_epoch, days_in_month};
+
+pub const MS_PER_DAY: i64 = 86_400_000;
+
+/// How months and years become milliseconds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CalendarPolicy {
+    /// Month = 30 days, year = 365 days, regardless of date
+    Fixed,
+    /// Months and years are stepped on the calendar forward from `anchor_ms`
+    /// (UTC), so "1m" from Jan 31 ends on Feb 28/29. A day past the end of
+    /// the target month is clamped to its last day.
+    Calendar { anchor_ms: i64 },
+}
+
+/// The policy every internal conversion uses (`over` filters, relative
+/// temporal expressions, classify defaults).
+pub const CALENDAR_POLICY: CalendarPolicy = CalendarPolicy::Fixed;
+
+/// Duration unit types
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum DurationUnit {
+    Years,
+    Months,
+    Weeks,
+    Days,
+}
+
+impl DurationUnit {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "y" | "yr" | "yrs" | "year" | "years" => Some(DurationUnit::Years),
+            "m" | "mo" | "mos" | "month" | "months" => Some(DurationUnit::Months),
+            "w" | "wk" | "wks" | "week" | "weeks" => Some(DurationUnit::Weeks),
+            "d" | "day" | "days" => Some(DurationUnit::Days),
+            _ => None,
+        }
+    }
+
+    /// Length of one unit in milliseconds under `policy`.
+    pub fn millis(self, policy: CalendarPolicy) -> i64 {
+        CalendarDuration::of(1.0, self).to_millis(policy)
+    }
+}
+
+impl fmt::Display for DurationUnit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DurationUnit::Years => write!(f, "y"),
+            DurationUnit::Months => write!(f, "m"),
+            DurationUnit::Weeks => write!(f, "w"),
+            DurationUnit::Days => write!(f, "d"),
+        }
+    }
+}
+
+/// Why a duration string was rejected.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum DurationError {
+    #[error("empty duration")]
+    Empty,
+
+    /// A part doesn't start with a number (e.g. "y", "abc")
+    #[error("missing value in '{0}'")]
+    MissingValue(String),
+
+    /// A number without a recognised unit (e.g. "5", "5q")
+    #[error("missing unit in '{0}'")]
+    MissingUnit(String),
+
+    /// Units must appear once each, largest first ("1y6m", not "6m1y")
+    #[error("units out of order in '{0}' (expected y, m, w, d)")]
+    UnitOrder(String),
+}
+
+/// A duration in calendar units, e.g. "1y6m" or "2w3d".
+///
+/// Equality and ordering compare milliseconds under [`CalendarPolicy::Fixed`],
+/// so "4w" == "28d" and "1y" > "12m". Serializes as its compact string form.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CalendarDuration {
+    pub years: f64,
+    pub months: f64,
+    pub weeks: f64,
+    pub days: f64,
+}
+
+impl CalendarDuration {
+    /// A duration of `value` units.
+    pub fn of(value: f64, unit: DurationUnit) -> Self {
+        let mut duration = Self::default();
+        *duration.field_mut(unit) = value;
+        duration
+    }
+
+    fn field(&self, unit: DurationUnit) -> f64 {
+        match unit {
+            DurationUnit::Years => self.years,
+            DurationUnit::Months => self.months,
+            DurationUnit::Weeks => self.weeks,
+            DurationUnit::Days => self.days,
+        }
+    }
+
+    fn field_mut(&mut self, unit: DurationUnit) -> &mut f64 {
+        match unit {
+            DurationUnit::Years => &mut self.years,
+            DurationUnit::Months => &mut self.months,
+            DurationUnit::Weeks => &mut self.weeks,
+            DurationUnit::Days => &mut self.days,
+        }
+    }
+
+    /// Length in milliseconds under `policy`.
+    ///
+    /// With [`CalendarPolicy::Calendar`], whole months (years count as 12)
+    /// are stepped on the calendar; a fractional month remainder, weeks and
+    /// days are fixed-length.
+    pub fn to_millis(&self, policy: CalendarPolicy) -> i64 {
+        let weeks_days = (self.weeks * 7.0 + self.days) * MS_PER_DAY as f64;
+        match policy {
+            CalendarPolicy::Fixed => {
+                let days = self.years * 365.0 + self.months * 30.0;
+                (days * MS_PER_DAY as f64 + weeks_days).round() as i64
+            }
+            CalendarPolicy::Calendar { anchor_ms } => {
+                let total_months = self.years * 12.0 + self.months;
+                let whole = total_months.trunc();
+                let fraction_ms = (total_months - whole) * 30.0 * MS_PER_DAY as f64;
+                add_months_ms(anchor_ms, whole as i64) + (fraction_ms + weeks_days).round() as i64
+            }
+        }
+    }
+
+    /// Carry whole months into years (12m = 1y) and whole days into weeks
+    /// (7d = 1w), for display: "18m" becomes "1y6m". Fractional parts are
+    /// left as written.
+    ///
+    /// Under [`CalendarPolicy::Fixed`] the result can differ in length from
+    /// the input (12 × 30 days is not 365 days); convert the original.
+    pub fn normalized(&self) -> Self {
+        let mut out = *self;
+        if out.years.fract() == 0.0 && out.months.fract() == 0.0 && out.months >= 12.0 {
+            out.years += (out.months / 12.0).floor();
+            out.months %= 12.0;
+        }
+        if out.weeks.fract() == 0.0 && out.days.fract() == 0.0 && out.days >= 7.0 {
+            out.weeks += (out.days / 7.0).floor();
+            out.days %= 7.0;
+        }
+        out
+    }
+}
+
+/// Milliseconds from `anchor_ms` to the same UTC time `months` calendar
+/// months later.
+fn add_months_ms(anchor_ms: i64, months: i64) -> i64 {
+    if months == 0 {
+        return 0;
+    }
+    let anchor_days = anchor_ms.div_euclid(MS_PER_DAY);
+    let (year, month, day) = civil_from_days(anchor_days);
+    let index = i64::from(year) * 12 + i64::from(month) - 1 + months;
+    let target_year = index.div_euclid(12) as i32;
+    let target_month = index.rem_euclid(12) as u32 + 1;
+    let target_day = day.min(days_in_month(target_year, target_month));
+    let target_days =
+        days_from_epoch(target_year, target_month, target_day).expect("days_from_epoch is total");
+    (target_days - anchor_days) * MS_PER_DAY
+}
+
+impl PartialEq for CalendarDuration {
+    fn eq(&self, other: &Self) -> bool {
+        self.to_millis(CalendarPolicy::Fixed) == other.to_millis(CalendarPolicy::Fixed)
+    }
+}
+
+impl PartialOrd for CalendarDuration {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.to_millis(CalendarPolicy::Fixed)
+            .partial_cmp(&other.to_millis(CalendarPolicy::Fixed))
+    }
+}
+
+impl fmt::Display for CalendarDuration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let units = [
+            DurationUnit::Years,
+            DurationUnit::Months,
+            DurationUnit::Weeks,
+            DurationUnit::Days,
+        ];
+        let mut wrote = false;
+        for unit in units {
+            let value = self.field(unit);
+            if value != 0.0 {
+                write!(f, "{}{}", value, unit)?;
+                wrote = true;
+            }
+        }
+        if !wrote {
+            write!(f, "0d")?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for CalendarDuration {
+    type Err = DurationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_duration(s)
+    }
+}
+
+impl Serialize for CalendarDuration {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for CalendarDuration {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        parse_duration(&s).map_err(serde::de::Error::custom)
+    }
+}
+
+/// Parse a duration such as "5y", "1.5y", "1y6m" or "2w3d".
+///
+/// Each part is a number followed by a unit (see [`DurationUnit::parse`]);
+/// units appear at most once, largest first.
+pub fn parse_duration(s: &str) -> Result<CalendarDuration, DurationError> {
+    let trimmed = s.trim();
+    if trimmed.is_empty() {
+        return Err(DurationError::Empty);
+    }
+
+    let mut duration = CalendarDuration::default();
+    let mut previous: Option<DurationUnit> = None;
+    let mut rest = trimmed;
+    while !rest.is_empty() {
+        let num_end = rest
+            .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+            .unwrap_or(rest.len());
+        let value = rest[..num_end]
+            .parse::<f64>()
+            .map_err(|_| DurationError::MissingValue(trimmed.to_string()))?;
+        rest = &rest[num_end..];
+
+        let unit_end = rest
+            .find(|c: char| !c.is_ascii_alphabetic())
+            .unwrap_or(rest.len());
+        let unit = DurationUnit::parse(&rest[..unit_end])
+            .ok_or_else(|| DurationError::MissingUnit(trimmed.to_string()))?;
+        rest = rest[unit_end..].trim_start();
+
+        if previous.is_some_and(|p| p >= unit) {
+            return Err(DurationError::UnitOrder(trimmed.to_string()));
+        }
+        previous = Some(unit);
+        *duration.field_mut(unit) = value;
+    }
+
+    Ok(duration)
+}
+
+/// Parse a duration for display in the UI. Returns
+/// `{"raw":"18m","normalized":"1y6m","ms":46656000000}` (milliseconds under
+/// [`CALENDAR_POLICY`]) or `{"error":"..."}`.
+pub fn parse_duration_json(input: &str) -> String {
+    match parse_duration(input) {
+        Ok(duration) => serde_json::json!({
+            "raw": input,
+            "normalized": duration.normalized().to_string(),
+            "ms": duration.to_millis(CALENDAR_POLICY),
+        })
+        .to_string(),
+        Err(e) => serde_json::json!({ "error": e.to_string() }).to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 2024-01-31T00:00:00Z
+    const JAN_31_2024: i64 = 1_706_659_200_000;
+
+    #[test]
+    fn unit_millis_are_pinned() {
+        let fixed = CalendarPolicy::Fixed;
+        assert_eq!(DurationUnit::Days.millis(fixed), 86_400_000);
+        assert_eq!(DurationUnit::Weeks.millis(fixed), 604_800_000);
+        assert_eq!(DurationUnit::Months.millis(fixed), 2_592_000_000);
+        assert_eq!(DurationUnit::Years.millis(fixed), 31_536_000_000);
+        assert_eq!(CALENDAR_POLICY, CalendarPolicy::Fixed);
+    }
+
+    #[test]
+    fn calendar_policy_steps_real_months() {
+        let policy = CalendarPolicy::Calendar {
+            anchor_ms: JAN_31_2024,
+        };
+        // Jan 31 + 1 month clamps to Feb 29 (leap year)
+        assert_eq!(DurationUnit::Months.millis(policy), 29 * MS_PER_DAY);
+        // 2024-01-31 → 2025-01-31
+        assert_eq!(DurationUnit::Years.millis(policy), 366 * MS_PER_DAY);
+        assert_eq!(DurationUnit::Weeks.millis(policy), 7 * MS_PER_DAY);
+        assert_eq!(
+            parse_duration("1y1m").unwrap().to_millis(policy),
+            (366 + 28) * MS_PER_DAY
+        );
+    }
+
+    #[test]
+    fn parses_compound_expressions() {
+        let d = parse_duration("1y6m").unwrap();
+        assert_eq!((d.years, d.months, d.weeks, d.days), (1.0, 6.0, 0.0, 0.0));
+        let d = parse_duration("2w3d").unwrap();
+        assert_eq!(d.to_millis(CalendarPolicy::Fixed), 17 * MS_PER_DAY);
+        let d = parse_duration("1.5years").unwrap();
+        assert_eq!(d.years, 1.5);
+
+        assert_eq!(parse_duration(" "), Err(DurationError::Empty));
+        assert!(matches!(
+            parse_duration("5q"),
+            Err(DurationError::MissingUnit(_))
+        ));
+        assert!(matches!(
+            parse_duration("5"),
+            Err(DurationError::MissingUnit(_))
+        ));
+        assert!(matches!(
+            parse_duration("y"),
+            Err(DurationError::MissingValue(_))
+        ));
+        assert!(matches!(
+            parse_duration("6m1y"),
+            Err(DurationError::UnitOrder(_))
+        ));
+        assert!(matches!(
+            parse_duration("1y1y"),
+            Err(DurationError::UnitOrder(_))
+        ));
+    }
+
+    #[test]
+    fn compares_under_fixed_policy() {
+        let d = |s: &str| parse_duration(s).unwrap();
+        assert_eq!(d("4w"), d("28d"));
+        assert!(d("1y") > d("12m"));
+        assert!(d("1m") < d("31d"));
+        assert!(d("1y6m") > d("1y"));
+    }
+
+    #[test]
+    fn normalizes_for_display() {
+        let d = |s: &str| parse_duration(s).unwrap().normalized().to_string();
+        assert_eq!(d("18m"), "1y6m");
+        assert_eq!(d("10d"), "1w3d");
+        assert_eq!(d("1y26m"), "3y2m");
+        assert_eq!(d("1.5y"), "1.5y");
+        assert_eq!(d("0d"), "0d");
+    }
+
+    #[test]
+    fn serde_round_trips_string_form() {
+        let d = parse_duration("1y6m").unwrap();
+        let json = serde_json::to_string(&d).unwrap();
+        assert_eq!(json, r#""1y6m""#);
+        let back: CalendarDuration = serde_json::from_str(&json).unwrap();
+        assert_eq!(back.to_string(), "1y6m");
+        assert!(serde_json::from_str::<CalendarDuration>(r#""6m1y""#).is_err());
+    }
+
+    #[test]
+    fn json_bridge() {
+        let out: serde_json::Value = serde_json::from_str(&parse_duration_json("18m")).unwrap();
+        assert_eq!(out["normalized"], "1y6m");
+        assert_eq!(out["ms"], 18 * 30 * MS_PER_DAY);
+        let err: serde_json::Value = serde_json::from_str(&parse_duration_json("5q")).unwrap();
+        assert_eq!(err["error"], "missing unit in '5q'");
+    }
+}
@@ -7,10 +7,17 @@
 //!
 //! `expand` explodes compact attestations into individual claims.
 //! `group_by_key` re-groups claims by (subject, predicate, context) for classification.
-//! `dedup_source_ids` collapses claims back to unique source attestation IDs.
+//! Group keys are JSON arrays (`["subject","predicate","context"]`) so values may
+//! contain any character; the legacy `subject|predicate|context` form is still
+//! available via [`ClaimKeyFormat::Legacy`] while callers migrate.
+//! `dedup_source_ids` collapses claims back to unique source attestation IDs;
+//! `dedup_source_ids_by_role` keeps them partitioned by a classification's roles.
 
 use serde::{Deserialize, Serialize};
 
+use crate::attestation::Attestation;
+use crate::classify::ClaimProvenance;
+
 /// A compact attestation as received from Go / the storage layer.
 /// Mirrors the JSON shape of `types.As` on the Go side.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -22,6 +29,23 @@ pub struct ExpandAttestation {
     pub actors: Vec<String>,
     /// Unix timestamp in milliseconds
     pub timestamp_ms: i64,
+    /// Creator-assigned confidence (0.0–1.0), if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub confidence: Option<f32>,
+}
+
+impl From<&Attestation> for ExpandAttestation {
+    fn from(a: &Attestation) -> Self {
+        Self {
+            id: a.id.clone(),
+            subjects: a.subjects.clone(),
+            predicates: a.predicates.clone(),
+            contexts: a.contexts.clone(),
+            actors: a.actors.clone(),
+            timestamp_ms: a.timestamp,
+            confidence: a.confidence,
+        }
+    }
 }
 
 /// A single claim extracted from a multi-dimensional attestation.
@@ -34,6 +58,9 @@ pub struct IndividualClaim {
     pub timestamp_ms: i64,
     /// ID of the source attestation
     pub source_id: String,
+    /// Confidence of the source attestation, carried onto each of its claims
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub confidence: Option<f32>,
 }
 
 /// A group of claims sharing the same (subject, predicate, context) key.
@@ -43,8 +70,29 @@ pub struct ClaimGroup {
     pub claims: Vec<IndividualClaim>,
 }
 
-/// Separator used to join subject, predicate, and context into a unique key.
-const CLAIM_KEY_SEP: &str = "|";
+/// How a group's (subject, predicate, context) is rendered into its `key`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ClaimKeyFormat {
+    /// `["subject","predicate","context"]`: a JSON array, unambiguous for any value.
+    #[default]
+    Json,
+    /// `subject|predicate|context`: the pre-migration format. Keys of different
+    /// groups coincide when a value contains `|`.
+    Legacy,
+}
+
+/// Separator used by [`ClaimKeyFormat::Legacy`] keys.
+const LEGACY_CLAIM_KEY_SEP: &str = "|";
+
+/// Render the key of a (subject, predicate, context) group.
+pub fn claim_key(subject: &str, predicate: &str, context: &str, format: ClaimKeyFormat) -> String {
+    match format {
+        ClaimKeyFormat::Json => serde_json::to_string(&[subject, predicate, context])
+            .expect("string array always serializes"),
+        ClaimKeyFormat::Legacy => [subject, predicate, context].join(LEGACY_CLAIM_KEY_SEP),
+    }
+}
 
 /// Expand a list of compact attestations into individual claims via cartesian product.
 ///
@@ -70,6 +118,7 @@ pub fn expand_cartesian(attestations: &[ExpandAttestation]) -> Vec<IndividualCla
                             actor: actor.clone(),
                             timestamp_ms: a.timestamp_ms,
                             source_id: a.id.clone(),
+                            confidence: a.confidence,
                         });
                     }
                 }
@@ -80,25 +129,43 @@ pub fn expand_cartesian(attestations: &[ExpandAttestation]) -> Vec<IndividualCla
     claims
 }
 
-/// Group claims by their (subject, predicate, context) key.
+/// Group claims by (subject, predicate, context), with JSON array keys.
 ///
-/// Returns groups in a deterministic order: sorted by key.
+/// Returns groups in a deterministic order: sorted by (subject, predicate, context).
 pub fn group_by_key(claims: &[IndividualClaim]) -> Vec<ClaimGroup> {
+    group_by_key_with_format(claims, ClaimKeyFormat::Json)
+}
+
+/// Group claims by (subject, predicate, context), rendering keys in `format`.
+///
+/// Grouping always compares the three values themselves, so claims whose values
+/// contain `|` never merge. Legacy keys of distinct groups may still be equal;
+/// legacy output is sorted by key string, as it was before JSON keys.
+pub fn group_by_key_with_format(
+    claims: &[IndividualClaim],
+    format: ClaimKeyFormat,
+) -> Vec<ClaimGroup> {
     use std::collections::BTreeMap;
 
-    let mut map: BTreeMap<String, Vec<IndividualClaim>> = BTreeMap::new();
+    let mut map: BTreeMap<(&str, &str, &str), Vec<IndividualClaim>> = BTreeMap::new();
 
     for claim in claims {
-        let key = format!(
-            "{}{}{}{}{}",
-            claim.subject, CLAIM_KEY_SEP, claim.predicate, CLAIM_KEY_SEP, claim.context
-        );
-        map.entry(key).or_default().push(claim.clone());
+        map.entry((&claim.subject, &claim.predicate, &claim.context))
+            .or_default()
+            .push(claim.clone());
     }
 
-    map.into_iter()
-        .map(|(key, claims)| ClaimGroup { key, claims })
-        .collect()
+    let mut groups: Vec<ClaimGroup> = map
+        .into_iter()
+        .map(|((subject, predicate, context), claims)| ClaimGroup {
+            key: claim_key(subject, predicate, context, format),
+            claims,
+        })
+        .collect();
+    if format == ClaimKeyFormat::Legacy {
+        groups.sort_by(|a, b| a.key.cmp(&b.key));
+    }
+    groups
 }
 
 /// Deduplicate claims back to unique source attestation IDs, preserving order.
@@ -115,6 +182,25 @@ pub fn dedup_source_ids(claims: &[IndividualClaim]) -> Vec<String> {
     ids
 }
 
+/// Like [`dedup_source_ids`], but partitioned by the roles of `roles` (a
+/// classification's provenance) instead of flattened, each list in claim
+/// order. Sources `roles` doesn't list count as supporting: an uncontested
+/// claim stands.
+pub fn dedup_source_ids_by_role(
+    claims: &[IndividualClaim],
+    roles: &ClaimProvenance,
+) -> ClaimProvenance {
+    let mut partitioned = ClaimProvenance::default();
+    for id in dedup_source_ids(claims) {
+        if roles.superseded_source_ids.contains(&id) && !roles.supports(&id) {
+            partitioned.superseded_source_ids.push(id);
+        } else {
+            partitioned.supporting_source_ids.push(id);
+        }
+    }
+    partitioned
+}
+
 /// Input for the WASM expand_cartesian_claims function.
 #[derive(Debug, Deserialize)]
 pub struct ExpandInput {
@@ -151,6 +237,9 @@ pub fn expand_claims_json(input: &str) -> String {
 #[derive(Debug, Deserialize)]
 pub struct GroupInput {
     pub claims: Vec<IndividualClaim>,
+    /// `"json"` (default) or `"legacy"`.
+    #[serde(default)]
+    pub key_format: ClaimKeyFormat,
 }
 
 /// Output of the WASM group_claims function.
@@ -158,6 +247,8 @@ pub struct GroupInput {
 pub struct GroupOutput {
     pub groups: Vec<ClaimGroup>,
     pub total_groups: usize,
+    /// Format of every group `key`: `"json"` keys parse as `[subject, predicate, context]`.
+    pub key_format: ClaimKeyFormat,
 }
 
 /// JSON entry point: deserialize claims, group by key, serialize output.
@@ -169,12 +260,13 @@ pub fn group_claims_json(input: &str) -> String {
         }
     };
 
-    let groups = group_by_key(&parsed.claims);
+    let groups = group_by_key_with_format(&parsed.claims, parsed.key_format);
     let total_groups = groups.len();
 
     match serde_json::to_string(&GroupOutput {
         groups,
         total_groups,
+        key_format: parsed.key_format,
     }) {
         Ok(json) => json,
         Err(e) => format!(r#"{{"error":"serialization failed: {}"}}"#, e),
@@ -185,6 +277,10 @@ pub fn group_claims_json(input: &str) -> String {
 #[derive(Debug, Deserialize)]
 pub struct DedupInput {
     pub claims: Vec<IndividualClaim>,
+    /// Provenance (`supporting_source_ids`, `superseded_source_ids`) to
+    /// partition the IDs by, e.g. a conflict of `classify_claims`
+    #[serde(default)]
+    pub roles: Option<ClaimProvenance>,
 }
 
 /// Output of the WASM dedup_source_ids function.
@@ -192,6 +288,9 @@ pub struct DedupInput {
 pub struct DedupOutput {
     pub ids: Vec<String>,
     pub total: usize,
+    /// `ids` partitioned by the input's `roles`, when given
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub roles: Option<ClaimProvenance>,
 }
 
 /// JSON entry point: deserialize claims, dedup source IDs, serialize output.
@@ -205,8 +304,11 @@ pub fn dedup_source_ids_json(input: &str) -> String {
 
     let ids = dedup_source_ids(&parsed.claims);
     let total = ids.len();
+    let roles = parsed
+        .roles
+        .map(|roles| dedup_source_ids_by_role(&parsed.claims, &roles));
 
-    match serde_json::to_string(&DedupOutput { ids, total }) {
+    match serde_json::to_string(&DedupOutput { ids, total, roles }) {
         Ok(json) => json,
         Err(e) => format!(r#"{{"error":"serialization failed: {}"}}"#, e),
     }
@@ -231,6 +333,7 @@ mod tests {
             contexts: contexts.iter().map(|s| s.to_string()).collect(),
             actors: actors.iter().map(|s| s.to_string()).collect(),
             timestamp_ms,
+            confidence: None,
         }
     }
 
@@ -316,6 +419,35 @@ mod tests {
         assert_eq!(claims[2].source_id, "A2");
     }
 
+    #[test]
+    fn confidence_carries_onto_claims() {
+        let mut detection = make_attestation(
+            "CAM-7",
+            &["BOBA"],
+            &["spotted_at", "armed_with"],
+            &["SLAVE-1"],
+            &["detector"],
+            400,
+        );
+        detection.confidence = Some(0.82);
+        let attestations = vec![
+            detection,
+            make_attestation(
+                "LOG-1",
+                &["BOBA"],
+                &["spotted_at"],
+                &["BESPIN"],
+                &["log"],
+                500,
+            ),
+        ];
+
+        let claims = expand_cartesian(&attestations);
+        assert_eq!(claims.len(), 3);
+        assert!(claims[..2].iter().all(|c| c.confidence == Some(0.82)));
+        assert_eq!(claims[2].confidence, None);
+    }
+
     #[test]
     fn group_claims_by_key() {
         // Two independent sources confirm Han is frozen in Jabba's palace;
@@ -328,6 +460,7 @@ mod tests {
                 actor: "palace-records".into(),
                 timestamp_ms: 100,
                 source_id: "jabba-01".into(),
+                confidence: None,
             },
             IndividualClaim {
                 subject: "HAN".into(),
@@ -336,6 +469,7 @@ mod tests {
                 actor: "rebel-intelligence".into(),
                 timestamp_ms: 200,
                 source_id: "rescue-01".into(),
+                confidence: None,
             },
             IndividualClaim {
                 subject: "LEIA".into(),
@@ -344,6 +478,7 @@ mod tests {
                 actor: "rebel-intelligence".into(),
                 timestamp_ms: 300,
                 source_id: "rescue-02".into(),
+                confidence: None,
             },
         ];
 
@@ -351,10 +486,65 @@ mod tests {
         assert_eq!(groups.len(), 2);
 
         // BTreeMap ensures sorted order
-        assert_eq!(groups[0].key, "HAN|frozen_in|JABBAS-PALACE");
+        assert_eq!(groups[0].key, r#"["HAN","frozen_in","JABBAS-PALACE"]"#);
         assert_eq!(groups[0].claims.len(), 2);
-        assert_eq!(groups[1].key, "LEIA|disguised_as|BOUSHH");
+        assert_eq!(groups[1].key, r#"["LEIA","disguised_as","BOUSHH"]"#);
         assert_eq!(groups[1].claims.len(), 1);
+
+        let legacy = group_by_key_with_format(&claims, ClaimKeyFormat::Legacy);
+        assert_eq!(legacy[0].key, "HAN|frozen_in|JABBAS-PALACE");
+        assert_eq!(legacy[1].key, "LEIA|disguised_as|BOUSHH");
+    }
+
+    fn claim(subject: &str, predicate: &str, context: &str, source_id: &str) -> IndividualClaim {
+        IndividualClaim {
+            subject: subject.into(),
+            predicate: predicate.into(),
+            context: context.into(),
+            actor: "holonet".into(),
+            timestamp_ms: 0,
+            source_id: source_id.into(),
+            confidence: None,
+        }
+    }
+
+    #[test]
+    fn delimiter_in_values_does_not_merge_groups() {
+        // Joined with "|" both claims read "R2|D2|astromech|NABOO": the old
+        // key construction put a droid's name and its role in the same group.
+        let claims = vec![
+            claim("R2|D2", "astromech", "NABOO", "a"),
+            claim("R2", "D2|astromech", "NABOO", "b"),
+        ];
+        let legacy_joined: Vec<String> = claims
+            .iter()
+            .map(|c| format!("{}|{}|{}", c.subject, c.predicate, c.context))
+            .collect();
+        assert_eq!(legacy_joined[0], legacy_joined[1]);
+
+        let groups = group_by_key(&claims);
+        assert_eq!(groups.len(), 2);
+        assert_ne!(groups[0].key, groups[1].key);
+        for g in &groups {
+            let parts: Vec<String> = serde_json::from_str(&g.key).unwrap();
+            let c = &g.claims[0];
+            assert_eq!(
+                parts,
+                vec![c.subject.clone(), c.predicate.clone(), c.context.clone()]
+            );
+        }
+
+        // Legacy keys still collide, but the groups themselves stay apart.
+        let legacy = group_by_key_with_format(&claims, ClaimKeyFormat::Legacy);
+        assert_eq!(legacy.len(), 2);
+        assert_eq!(legacy[0].key, legacy[1].key);
+    }
+
+    #[test]
+    fn json_key_escapes_quotes_and_brackets() {
+        let key = claim_key(r#"say "hi""#, "p", "[c]", ClaimKeyFormat::Json);
+        let parts: Vec<String> = serde_json::from_str(&key).unwrap();
+        assert_eq!(parts, vec![r#"say "hi""#, "p", "[c]"]);
     }
 
     #[test]
@@ -369,6 +559,7 @@ mod tests {
                 actor: "rebel-intelligence".into(),
                 timestamp_ms: 1,
                 source_id: "rescue-plan".into(),
+                confidence: None,
             },
             IndividualClaim {
                 subject: "R2D2".into(),
@@ -377,6 +568,7 @@ mod tests {
                 actor: "rebel-intelligence".into(),
                 timestamp_ms: 2,
                 source_id: "rescue-plan".into(), // same source
+                confidence: None,
             },
             IndividualClaim {
                 subject: "LEIA".into(),
@@ -385,6 +577,7 @@ mod tests {
                 actor: "palace-surveillance".into(),
                 timestamp_ms: 3,
                 source_id: "carbonite-heist".into(),
+                confidence: None,
             },
         ];
 
@@ -440,6 +633,14 @@ mod tests {
 
         assert!(parsed["error"].is_null(), "unexpected error: {}", result);
         assert_eq!(parsed["total_groups"], 2);
+        assert_eq!(parsed["key_format"], "json");
+
+        let mut legacy_input = input.clone();
+        legacy_input["key_format"] = "legacy".into();
+        let legacy: serde_json::Value =
+            serde_json::from_str(&group_claims_json(&legacy_input.to_string())).unwrap();
+        assert_eq!(legacy["key_format"], "legacy");
+        assert_eq!(legacy["groups"][0]["key"], "LANDO|fought_on|DESERT-SKIFF");
     }
 
     #[test]
@@ -463,5 +664,34 @@ mod tests {
             parsed["ids"].as_array().unwrap(),
             &["droid-plan", "carbonite-heist"]
         );
+        assert!(parsed.get("roles").is_none());
+    }
+
+    #[test]
+    fn dedup_source_ids_keeps_roles() {
+        // Two reports of Han's location: the bounty hunter's was overruled
+        let input = serde_json::json!({
+            "claims": [
+                {"subject": "HAN", "predicate": "located_at", "context": "BESPIN", "actor": "llm:bounty-board", "timestamp_ms": 1, "source_id": "boba-report"},
+                {"subject": "HAN", "predicate": "located_at", "context": "BESPIN", "actor": "human:lando", "timestamp_ms": 2, "source_id": "lando-report"},
+                {"subject": "HAN", "predicate": "frozen_in", "context": "BESPIN", "actor": "human:lando", "timestamp_ms": 3, "source_id": "carbonite-log"}
+            ],
+            "roles": {
+                "supporting_source_ids": ["lando-report"],
+                "superseded_source_ids": ["boba-report"]
+            }
+        });
+
+        let parsed: serde_json::Value =
+            serde_json::from_str(&dedup_source_ids_json(&input.to_string())).unwrap();
+        assert_eq!(parsed["total"], 3);
+        assert_eq!(
+            parsed["roles"]["supporting_source_ids"],
+            serde_json::json!(["lando-report", "carbonite-log"])
+        );
+        assert_eq!(
+            parsed["roles"]["superseded_source_ids"],
+            serde_json::json!(["boba-report"])
+        );
     }
 }
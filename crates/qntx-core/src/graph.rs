@@ -0,0 +1,595 @@
+//! Graph projection of attestations.
+//!
+//! Turns attestations into nodes and edges under one set of rules, so the web
+//! graph view, Tauri, and Go hosts draw the same graph from the same data.
+//!
+//! By default subjects and contexts become nodes and each predicate becomes a
+//! labelled edge from subject to context. [`GraphProjection`] picks which slots
+//! are the edge endpoints, how edges are labelled, whether actors are nodes or
+//! edge attributes, and how edge weight is computed.
+//!
+//! # Node IDs
+//!
+//! Node IDs are the slot-prefixed value (`subject:ALICE`, `context:GitHub`), so
+//! the same value in different slots gives different nodes. With `merge_slots`
+//! set, IDs become `entity:<value>` and a value used as both subject and context
+//! is one node; an attestation linking it to itself gives a self-loop edge.
+//! Values are used as they are: no trimming, and case is preserved.
+//!
+//! # Merging
+//!
+//! Edges are merged by (source, target, label). A merged edge keeps:
+//! - `count`: number of attestations that produced it
+//! - `weight`: the count, or the highest confidence seen: the attestation's
+//!   `confidence`, else a numeric `attributes.confidence` (attestations with
+//!   neither count as 0.0)
+//! - `actors`: distinct actors in first-seen order (attribute mode only)
+//!
+//! Nodes and edges are listed in first-seen order, so the output is stable for a
+//! given input order. A node's `count` is the number of attestations it appears
+//! in.
+
+use crate::attestation::Attestation;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+/// An attestation slot that can become a node or an edge label.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Slot {
+    Subject,
+    Predicate,
+    Context,
+    Actor,
+}
+
+impl Slot {
+    fn prefix(self) -> &'static str {
+        match self {
+            Slot::Subject => "subject",
+            Slot::Predicate => "predicate",
+            Slot::Context => "context",
+            Slot::Actor => "actor",
+        }
+    }
+
+    fn values(self, attestation: &Attestation) -> &[String] {
+        match self {
+            Slot::Subject => &attestation.subjects,
+            Slot::Predicate => &attestation.predicates,
+            Slot::Context => &attestation.contexts,
+            Slot::Actor => &attestation.actors,
+        }
+    }
+}
+
+/// How actors appear in the projected graph.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ActorMode {
+    /// Actors are listed on the edges they attested
+    #[default]
+    EdgeAttribute,
+    /// Actors become nodes with an `attested` edge to each source node
+    Node,
+}
+
+/// How the weight of a merged edge is computed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EdgeWeight {
+    /// Number of attestations merged into the edge
+    #[default]
+    Count,
+    /// Highest confidence among merged attestations (`confidence`, falling
+    /// back to `attributes.confidence`)
+    MaxConfidence,
+}
+
+/// Rules for projecting attestations into a graph.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GraphProjection {
+    /// Slot whose values are edge sources
+    pub source: Slot,
+    /// Slot whose values are edge targets
+    pub target: Slot,
+    /// Slot whose values label edges; `None` merges edges regardless of label
+    pub edge_label: Option<Slot>,
+    pub actors: ActorMode,
+    pub weight: EdgeWeight,
+    /// Use `entity:<value>` node IDs so equal values in different slots are one node
+    pub merge_slots: bool,
+}
+
+impl Default for GraphProjection {
+    fn default() -> Self {
+        Self {
+            source: Slot::Subject,
+            target: Slot::Context,
+            edge_label: Some(Slot::Predicate),
+            actors: ActorMode::EdgeAttribute,
+            weight: EdgeWeight::Count,
+            merge_slots: false,
+        }
+    }
+}
+
+/// A projected node.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GraphNode {
+    pub id: String,
+    pub label: String,
+    /// Slots the value appeared in, in first-seen order
+    pub slots: Vec<Slot>,
+    /// Number of attestations the node appears in
+    pub count: u64,
+}
+
+/// A projected (merged) edge.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GraphEdge {
+    pub source: String,
+    pub target: String,
+    pub label: Option<String>,
+    pub count: u64,
+    pub weight: f64,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub actors: Vec<String>,
+}
+
+/// Nodes and edges projected from attestations.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Graph {
+    pub nodes: Vec<GraphNode>,
+    pub edges: Vec<GraphEdge>,
+}
+
+/// Label of edges from actor nodes in [`ActorMode::Node`].
+pub const ACTOR_EDGE_LABEL: &str = "attested";
+
+struct Builder<'a> {
+    config: &'a GraphProjection,
+    graph: Graph,
+    node_index: HashMap<String, usize>,
+    edge_index: HashMap<(String, String, Option<String>), usize>,
+}
+
+impl Builder<'_> {
+    fn node_id(&self, slot: Slot, value: &str) -> String {
+        if self.config.merge_slots {
+            format!("entity:{}", value)
+        } else {
+            format!("{}:{}", slot.prefix(), value)
+        }
+    }
+
+    /// Register a node occurrence; `seen` counts each node once per attestation.
+    fn node(&mut self, slot: Slot, value: &str, seen: &mut HashSet<String>) -> String {
+        let id = self.node_id(slot, value);
+        let i = match self.node_index.get(&id) {
+            Some(&i) => i,
+            None => {
+                self.node_index.insert(id.clone(), self.graph.nodes.len());
+                self.graph.nodes.push(GraphNode {
+                    id: id.clone(),
+                    label: value.to_string(),
+                    slots: Vec::new(),
+                    count: 0,
+                });
+                self.graph.nodes.len() - 1
+            }
+        };
+        let node = &mut self.graph.nodes[i];
+        if !node.slots.contains(&slot) {
+            node.slots.push(slot);
+        }
+        if seen.insert(id.clone()) {
+            node.count += 1;
+        }
+        id
+    }
+
+    fn edge(
+        &mut self,
+        source: String,
+        target: String,
+        label: Option<String>,
+        confidence: f64,
+        actors: &[String],
+    ) {
+        let key = (source, target, label);
+        let i = match self.edge_index.get(&key) {
+            Some(&i) => i,
+            None => {
+                let (source, target, label) = key.clone();
+                self.edge_index.insert(key, self.graph.edges.len());
+                self.graph.edges.push(GraphEdge {
+                    source,
+                    target,
+                    label,
+                    count: 0,
+                    weight: 0.0,
+                    actors: Vec::new(),
+                });
+                self.graph.edges.len() - 1
+            }
+        };
+        let edge = &mut self.graph.edges[i];
+        edge.count += 1;
+        edge.weight = match self.config.weight {
+            EdgeWeight::Count => edge.count as f64,
+            EdgeWeight::MaxConfidence if edge.count == 1 => confidence,
+            EdgeWeight::MaxConfidence => edge.weight.max(confidence),
+        };
+        for actor in actors {
+            if !edge.actors.contains(actor) {
+                edge.actors.push(actor.clone());
+            }
+        }
+    }
+
+    fn add(&mut self, attestation: &Attestation) {
+        let config = self.config;
+        let mut seen = HashSet::new();
+        let confidence = attestation
+            .confidence
+            .map(f64::from)
+            .or_else(|| attestation.attributes.get("confidence")?.as_f64())
+            .unwrap_or(0.0);
+        let edge_actors: &[String] = match config.actors {
+            ActorMode::EdgeAttribute => &attestation.actors,
+            ActorMode::Node => &[],
+        };
+        let labels: Vec<Option<String>> = match config.edge_label {
+            Some(slot) if !slot.values(attestation).is_empty() => slot
+                .values(attestation)
+                .iter()
+                .map(|v| Some(v.clone()))
+                .collect(),
+            _ => vec![None],
+        };
+
+        let sources: Vec<String> = config
+            .source
+            .values(attestation)
+            .iter()
+            .map(|v| self.node(config.source, v, &mut seen))
+            .collect();
+        let targets: Vec<String> = config
+            .target
+            .values(attestation)
+            .iter()
+            .map(|v| self.node(config.target, v, &mut seen))
+            .collect();
+
+        for source in &sources {
+            for target in &targets {
+                for label in &labels {
+                    self.edge(
+                        source.clone(),
+                        target.clone(),
+                        label.clone(),
+                        confidence,
+                        edge_actors,
+                    );
+                }
+            }
+        }
+
+        if config.actors == ActorMode::Node {
+            for actor in &attestation.actors {
+                let actor_id = self.node(Slot::Actor, actor, &mut seen);
+                for source in &sources {
+                    self.edge(
+                        actor_id.clone(),
+                        source.clone(),
+                        Some(ACTOR_EDGE_LABEL.to_string()),
+                        confidence,
+                        &[],
+                    );
+                }
+            }
+        }
+    }
+}
+
+/// Project attestations into a graph. See the module docs for merge semantics.
+pub fn project(attestations: &[Attestation], config: &GraphProjection) -> Graph {
+    let mut builder = Builder {
+        config,
+        graph: Graph::default(),
+        node_index: HashMap::new(),
+        edge_index: HashMap::new(),
+    };
+    for attestation in attestations {
+        builder.add(attestation);
+    }
+    builder.graph
+}
+
+/// Node in the force layout: links refer to nodes by array index.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ForceNode {
+    pub id: String,
+    pub label: String,
+}
+
+/// Index-based link in the force layout.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ForceLink {
+    pub source: usize,
+    pub target: usize,
+    pub weight: f64,
+    pub label: Option<String>,
+}
+
+/// Graph arrays in the layout force-rs consumes.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ForceGraph {
+    pub nodes: Vec<ForceNode>,
+    pub links: Vec<ForceLink>,
+}
+
+impl Graph {
+    /// Convert to force-layout arrays, replacing node IDs in links with indices.
+    pub fn to_force_layout(&self) -> ForceGraph {
+        let index: HashMap<&str, usize> = self
+            .nodes
+            .iter()
+            .enumerate()
+            .map(|(i, n)| (n.id.as_str(), i))
+            .collect();
+        ForceGraph {
+            nodes: self
+                .nodes
+                .iter()
+                .map(|n| ForceNode {
+                    id: n.id.clone(),
+                    label: n.label.clone(),
+                })
+                .collect(),
+            links: self
+                .edges
+                .iter()
+                .filter_map(|e| {
+                    Some(ForceLink {
+                        source: *index.get(e.source.as_str())?,
+                        target: *index.get(e.target.as_str())?,
+                        weight: e.weight,
+                        label: e.label.clone(),
+                    })
+                })
+                .collect(),
+        }
+    }
+}
+
+fn parse_projection_input(
+    attestations_json: &str,
+    config_json: &str,
+) -> Result<(Vec<Attestation>, GraphProjection), String> {
+    let attestations: Vec<Attestation> = serde_json::from_str(attestations_json)
+        .map_err(|e| format!("invalid graph attestations: {}", e))?;
+    let config = match config_json.trim() {
+        "" | "null" => GraphProjection::default(),
+        c => serde_json::from_str(c).map_err(|e| format!("invalid graph config: {}", e))?,
+    };
+    Ok((attestations, config))
+}
+
+fn error_json(msg: &str) -> String {
+    serde_json::json!({ "error": msg }).to_string()
+}
+
+/// JSON entry point: project a JSON array of attestations with a JSON
+/// `GraphProjection` (empty or `null` for defaults). Returns `Graph` JSON or
+/// `{"error": "..."}`.
+pub fn project_graph_json(attestations_json: &str, config_json: &str) -> String {
+    match parse_projection_input(attestations_json, config_json) {
+        Ok((attestations, config)) => serde_json::to_string(&project(&attestations, &config))
+            .unwrap_or_else(|e| error_json(&format!("serialization failed: {}", e))),
+        Err(e) => error_json(&e),
+    }
+}
+
+/// Like [`project_graph_json`], but returns the force layout (`ForceGraph`).
+pub fn project_force_graph_json(attestations_json: &str, config_json: &str) -> String {
+    match parse_projection_input(attestations_json, config_json) {
+        Ok((attestations, config)) => {
+            serde_json::to_string(&project(&attestations, &config).to_force_layout())
+                .unwrap_or_else(|e| error_json(&format!("serialization failed: {}", e)))
+        }
+        Err(e) => error_json(&e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::attestation::AttestationBuilder;
+
+    fn att(id: &str, subject: &str, predicate: &str, context: &str, actor: &str) -> Attestation {
+        AttestationBuilder::new()
+            .id(id)
+            .subject(subject)
+            .predicate(predicate)
+            .context(context)
+            .actor(actor)
+            .timestamp(1000)
+            .source("test")
+            .build()
+    }
+
+    fn with_confidence(mut a: Attestation, confidence: f32) -> Attestation {
+        a.confidence = Some(confidence);
+        a
+    }
+
+    #[test]
+    fn test_duplicate_edges_merge_with_count() {
+        let atts = vec![
+            att("AS-1", "LUKE", "trained_at", "DAGOBAH", "human:yoda"),
+            att("AS-2", "LUKE", "trained_at", "DAGOBAH", "human:obiwan"),
+            att("AS-3", "LUKE", "visited", "DAGOBAH", "human:yoda"),
+        ];
+        let graph = project(&atts, &GraphProjection::default());
+
+        let ids: Vec<&str> = graph.nodes.iter().map(|n| n.id.as_str()).collect();
+        assert_eq!(ids, vec!["subject:LUKE", "context:DAGOBAH"]);
+        assert_eq!(graph.nodes[0].count, 3);
+
+        assert_eq!(graph.edges.len(), 2);
+        let trained = &graph.edges[0];
+        assert_eq!(trained.source, "subject:LUKE");
+        assert_eq!(trained.target, "context:DAGOBAH");
+        assert_eq!(trained.label.as_deref(), Some("trained_at"));
+        assert_eq!(trained.count, 2);
+        assert_eq!(trained.weight, 2.0);
+        assert_eq!(trained.actors, vec!["human:yoda", "human:obiwan"]);
+    }
+
+    #[test]
+    fn test_unlabelled_edges_merge_across_predicates() {
+        let atts = vec![
+            att("AS-1", "LUKE", "trained_at", "DAGOBAH", "human:yoda"),
+            att("AS-2", "LUKE", "visited", "DAGOBAH", "human:yoda"),
+        ];
+        let config = GraphProjection {
+            edge_label: None,
+            ..GraphProjection::default()
+        };
+        let graph = project(&atts, &config);
+        assert_eq!(graph.edges.len(), 1);
+        assert_eq!(graph.edges[0].label, None);
+        assert_eq!(graph.edges[0].count, 2);
+    }
+
+    #[test]
+    fn test_max_confidence_weight() {
+        let atts = vec![
+            with_confidence(att("AS-1", "HAN", "pilots", "FALCON", "human:chewie"), 0.25),
+            with_confidence(att("AS-2", "HAN", "pilots", "FALCON", "human:leia"), 0.75),
+            att("AS-3", "HAN", "pilots", "FALCON", "human:lando"),
+        ];
+        let config = GraphProjection {
+            weight: EdgeWeight::MaxConfidence,
+            ..GraphProjection::default()
+        };
+        let graph = project(&atts, &config);
+        assert_eq!(graph.edges[0].count, 3);
+        assert_eq!(graph.edges[0].weight, 0.75);
+
+        // The attribute still counts when the field is unset, and the field wins
+        // over it when both are present
+        let mut legacy = att("AS-4", "HAN", "pilots", "FALCON", "human:rey");
+        legacy
+            .attributes
+            .insert("confidence".to_string(), serde_json::json!(0.9));
+        let mut both = with_confidence(att("AS-5", "HAN", "pilots", "FALCON", "human:finn"), 0.5);
+        both.attributes
+            .insert("confidence".to_string(), serde_json::json!(1.0));
+        assert_eq!(project(&[legacy], &config).edges[0].weight, 0.9);
+        assert_eq!(project(&[both], &config).edges[0].weight, 0.5);
+    }
+
+    #[test]
+    fn test_subject_equal_to_context() {
+        let atts = vec![att("AS-1", "VADER", "betrays", "VADER", "human:luke")];
+
+        // Slot-prefixed IDs keep the roles apart
+        let graph = project(&atts, &GraphProjection::default());
+        assert_eq!(graph.nodes.len(), 2);
+        assert_ne!(graph.edges[0].source, graph.edges[0].target);
+
+        // Merged slots give a single node with a self-loop
+        let config = GraphProjection {
+            merge_slots: true,
+            ..GraphProjection::default()
+        };
+        let graph = project(&atts, &config);
+        assert_eq!(graph.nodes.len(), 1);
+        assert_eq!(graph.nodes[0].id, "entity:VADER");
+        assert_eq!(graph.nodes[0].slots, vec![Slot::Subject, Slot::Context]);
+        assert_eq!(graph.nodes[0].count, 1);
+        assert_eq!(graph.edges.len(), 1);
+        assert_eq!(graph.edges[0].source, "entity:VADER");
+        assert_eq!(graph.edges[0].target, "entity:VADER");
+
+        let force = graph.to_force_layout();
+        assert_eq!(force.links[0].source, 0);
+        assert_eq!(force.links[0].target, 0);
+    }
+
+    #[test]
+    fn test_actors_as_nodes() {
+        let atts = vec![
+            att("AS-1", "LEIA", "leads", "REBELLION", "human:mon"),
+            att("AS-2", "LEIA", "leads", "REBELLION", "human:mon"),
+        ];
+        let config = GraphProjection {
+            actors: ActorMode::Node,
+            ..GraphProjection::default()
+        };
+        let graph = project(&atts, &config);
+
+        let ids: Vec<&str> = graph.nodes.iter().map(|n| n.id.as_str()).collect();
+        assert_eq!(
+            ids,
+            vec!["subject:LEIA", "context:REBELLION", "actor:human:mon"]
+        );
+        assert_eq!(graph.edges.len(), 2);
+        assert!(graph.edges[0].actors.is_empty());
+        let attested = &graph.edges[1];
+        assert_eq!(attested.source, "actor:human:mon");
+        assert_eq!(attested.target, "subject:LEIA");
+        assert_eq!(attested.label.as_deref(), Some(ACTOR_EDGE_LABEL));
+        assert_eq!(attested.count, 2);
+    }
+
+    #[test]
+    fn test_force_layout_indices() {
+        let atts = vec![
+            att("AS-1", "R2D2", "serves", "LEIA", "human:luke"),
+            att("AS-2", "C3PO", "serves", "LEIA", "human:luke"),
+        ];
+        let force = project(&atts, &GraphProjection::default()).to_force_layout();
+        assert_eq!(force.nodes.len(), 3);
+        assert_eq!(force.links.len(), 2);
+        assert_eq!(
+            (force.links[1].source, force.links[1].target),
+            (2, 1),
+            "C3PO → LEIA"
+        );
+    }
+
+    #[test]
+    fn test_project_graph_json() {
+        let atts = serde_json::to_string(&vec![att(
+            "AS-1",
+            "YODA",
+            "lives_on",
+            "DAGOBAH",
+            "human:luke",
+        )])
+        .unwrap();
+
+        let out: Graph = serde_json::from_str(&project_graph_json(&atts, "")).unwrap();
+        assert_eq!(out.edges.len(), 1);
+
+        let out: serde_json::Value =
+            serde_json::from_str(&project_graph_json(&atts, r#"{"source":"actor"}"#)).unwrap();
+        assert_eq!(out["nodes"][0]["id"], "actor:human:luke");
+
+        let force: ForceGraph =
+            serde_json::from_str(&project_force_graph_json(&atts, "null")).unwrap();
+        assert_eq!(force.links[0].source, 0);
+
+        let err: serde_json::Value =
+            serde_json::from_str(&project_graph_json(&atts, r#"{"source":"nope"}"#)).unwrap();
+        assert!(err["error"]
+            .as_str()
+            .unwrap()
+            .starts_with("invalid graph config"));
+    }
+}
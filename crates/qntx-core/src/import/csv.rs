@@ -0,0 +1,568 @@
+//! CSV import: one attestation per row.
+
+use std::collections::{HashMap, HashSet};
+use std::io::Read;
+
+use serde::{Deserialize, Serialize};
+
+use super::{
+    default_source, default_true, ImportError, ImportReport, ReportBuilder, RowErrorKind,
+    TimestampFormat,
+};
+use crate::attestation::AttestationBuilder;
+
+/// Where a slot's value comes from.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SlotSource {
+    /// The same value for every row
+    Fixed(String),
+    /// The named column
+    Column(String),
+}
+
+impl SlotSource {
+    pub fn fixed(value: impl Into<String>) -> Self {
+        SlotSource::Fixed(value.into())
+    }
+
+    pub fn column(name: impl Into<String>) -> Self {
+        SlotSource::Column(name.into())
+    }
+
+    fn column_name(&self) -> Option<&str> {
+        match self {
+            SlotSource::Fixed(_) => None,
+            SlotSource::Column(name) => Some(name),
+        }
+    }
+}
+
+/// Timestamp column and how its cells are written.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TimestampSpec {
+    pub column: String,
+    #[serde(default)]
+    pub format: TimestampFormat,
+}
+
+/// Maps CSV columns onto attestation slots.
+///
+/// Deserializable so a CLI can take the mapping as a JSON document:
+///
+/// ```json
+/// {
+///   "subject_column": "name",
+///   "predicate": {"fixed": "member_of"},
+///   "context": {"column": "team"},
+///   "timestamp": {"column": "joined", "format": {"pattern": "%d/%m/%Y"}}
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CsvImportSpec {
+    pub subject_column: String,
+    pub predicate: SlotSource,
+    /// Rows with an empty context cell get the default `_` context
+    #[serde(default)]
+    pub context: Option<SlotSource>,
+    /// Rows with an empty actor cell get no actor
+    #[serde(default)]
+    pub actor: Option<SlotSource>,
+    #[serde(default)]
+    pub timestamp: Option<TimestampSpec>,
+    /// Timestamp (Unix ms) for every row when there is no timestamp column
+    #[serde(default)]
+    pub default_timestamp_ms: Option<i64>,
+    #[serde(default = "default_source")]
+    pub source: String,
+    /// Store every column not mapped to a slot (and not ignored) as a string
+    /// attribute; empty cells are skipped
+    #[serde(default = "default_true")]
+    pub remaining_as_attributes: bool,
+    #[serde(default)]
+    pub ignore_columns: Vec<String>,
+    #[serde(default = "default_delimiter")]
+    pub delimiter: char,
+    /// Drop rows identical to an earlier one (counted in `duplicates`)
+    #[serde(default = "default_true")]
+    pub dedupe: bool,
+}
+
+fn default_delimiter() -> char {
+    ','
+}
+
+impl CsvImportSpec {
+    /// Spec with the given subject column and predicate; everything else at
+    /// its default. Set `timestamp` or `default_timestamp_ms` before importing.
+    pub fn new(subject_column: impl Into<String>, predicate: SlotSource) -> Self {
+        Self {
+            subject_column: subject_column.into(),
+            predicate,
+            context: None,
+            actor: None,
+            timestamp: None,
+            default_timestamp_ms: None,
+            source: default_source(),
+            remaining_as_attributes: true,
+            ignore_columns: Vec::new(),
+            delimiter: default_delimiter(),
+            dedupe: true,
+        }
+    }
+
+    /// Columns this spec maps to slots.
+    fn slot_columns(&self) -> Vec<&str> {
+        let mut columns = vec![self.subject_column.as_str()];
+        columns.extend(self.predicate.column_name());
+        columns.extend(self.context.as_ref().and_then(SlotSource::column_name));
+        columns.extend(self.actor.as_ref().and_then(SlotSource::column_name));
+        columns.extend(self.timestamp.as_ref().map(|t| t.column.as_str()));
+        columns
+    }
+
+    fn check(&self, header: &[String]) -> Result<(), ImportError> {
+        if self.timestamp.is_none() && self.default_timestamp_ms.is_none() {
+            return Err(ImportError::Spec(
+                "set either a timestamp column or default_timestamp_ms".to_string(),
+            ));
+        }
+
+        let mut seen = HashSet::new();
+        let duplicated: Vec<&str> = header
+            .iter()
+            .filter(|h| !h.is_empty() && !seen.insert(h.as_str()))
+            .map(String::as_str)
+            .collect();
+        if !duplicated.is_empty() {
+            return Err(ImportError::Spec(format!(
+                "duplicate header columns: {}",
+                duplicated.join(", ")
+            )));
+        }
+
+        let missing: Vec<&str> = self
+            .slot_columns()
+            .into_iter()
+            .filter(|c| !seen.contains(c))
+            .collect();
+        if !missing.is_empty() {
+            return Err(ImportError::Spec(format!(
+                "columns not in header: {}",
+                missing.join(", ")
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Import CSV rows as attestations. The first record is the header.
+///
+/// Reads the whole input. Accepts a UTF-8 BOM and UTF-16 input with a BOM;
+/// rows containing invalid UTF-8 are reported rather than imported.
+pub fn import_csv<R: Read>(
+    mut reader: R,
+    spec: &CsvImportSpec,
+) -> Result<ImportReport, ImportError> {
+    let mut bytes = Vec::new();
+    reader.read_to_end(&mut bytes)?;
+    let (text, lossy) = decode(&bytes)?;
+
+    let mut records = parse_records(&text, spec.delimiter).into_iter();
+    let header = match records.next() {
+        Some(Record {
+            error: None,
+            fields,
+            ..
+        }) => fields
+            .into_iter()
+            .map(|f| f.trim().to_string())
+            .collect::<Vec<_>>(),
+        Some(Record { error: Some(e), .. }) => {
+            return Err(ImportError::Input(format!("malformed header row: {}", e)))
+        }
+        None => return Err(ImportError::Input("missing header row".to_string())),
+    };
+    spec.check(&header)?;
+
+    let index: HashMap<&str, usize> = header
+        .iter()
+        .enumerate()
+        .map(|(i, name)| (name.as_str(), i))
+        .collect();
+    let slot_columns: HashSet<&str> = spec.slot_columns().into_iter().collect();
+    let attribute_columns: Vec<(usize, &str)> = header
+        .iter()
+        .enumerate()
+        .filter(|(_, name)| {
+            spec.remaining_as_attributes
+                && !name.is_empty()
+                && !slot_columns.contains(name.as_str())
+                && !spec.ignore_columns.contains(name)
+        })
+        .map(|(i, name)| (i, name.as_str()))
+        .collect();
+
+    let mut out = ReportBuilder::new(spec.dedupe);
+    for record in records {
+        let line = record.line;
+        if let Some(error) = record.error {
+            out.error(line, None, RowErrorKind::Malformed, error);
+            continue;
+        }
+        let fields = record.fields;
+        if lossy {
+            if let Some(i) = fields.iter().position(|f| f.contains('\u{FFFD}')) {
+                let column = header.get(i).map(String::as_str);
+                out.error(line, column, RowErrorKind::Encoding, "invalid UTF-8");
+                continue;
+            }
+        }
+        if fields.len() != header.len() {
+            out.error(
+                line,
+                None,
+                RowErrorKind::Malformed,
+                format!("expected {} fields, found {}", header.len(), fields.len()),
+            );
+            continue;
+        }
+
+        let cell = |column: &str| cell_value(&fields, &index, column);
+        let slot = |source: &SlotSource| match source {
+            SlotSource::Fixed(value) => value.trim().to_string(),
+            SlotSource::Column(column) => cell(column).to_string(),
+        };
+
+        let subject = cell(&spec.subject_column);
+        if subject.is_empty() {
+            out.error(
+                line,
+                Some(&spec.subject_column),
+                RowErrorKind::MissingValue,
+                "empty subject",
+            );
+            continue;
+        }
+        let predicate = slot(&spec.predicate);
+        if predicate.is_empty() {
+            out.error(
+                line,
+                spec.predicate.column_name(),
+                RowErrorKind::MissingValue,
+                "empty predicate",
+            );
+            continue;
+        }
+
+        let timestamp = match &spec.timestamp {
+            Some(ts) => match ts.format.parse(cell(&ts.column)) {
+                Ok(ms) => ms,
+                Err(e) => {
+                    out.error(line, Some(&ts.column), RowErrorKind::Timestamp, e);
+                    continue;
+                }
+            },
+            // CsvImportSpec::check guarantees one of the two is set
+            None => spec.default_timestamp_ms.unwrap_or_default(),
+        };
+
+        let mut builder = AttestationBuilder::new()
+            .subject(subject)
+            .predicate(predicate)
+            .timestamp(timestamp)
+            .source(spec.source.as_str());
+        if let Some(context) = spec.context.as_ref().map(&slot).filter(|c| !c.is_empty()) {
+            builder = builder.context(context);
+        }
+        if let Some(actor) = spec.actor.as_ref().map(&slot).filter(|a| !a.is_empty()) {
+            builder = builder.actor(actor);
+        }
+        for &(i, name) in &attribute_columns {
+            let value = fields[i].trim();
+            if !value.is_empty() {
+                builder = builder.attribute(name, serde_json::Value::String(value.to_string()));
+            }
+        }
+        out.push(line, builder.build());
+    }
+
+    Ok(out.finish())
+}
+
+fn cell_value<'a>(fields: &'a [String], index: &HashMap<&str, usize>, column: &str) -> &'a str {
+    fields[index[column]].trim()
+}
+
+/// Decode input bytes. Returns the text and whether invalid UTF-8 had to be
+/// replaced (those rows are then reported instead of imported).
+fn decode(bytes: &[u8]) -> Result<(String, bool), ImportError> {
+    let utf16 = |body: &[u8], from: fn([u8; 2]) -> u16| {
+        if !body.len().is_multiple_of(2) {
+            return Err(ImportError::Input("truncated UTF-16 input".to_string()));
+        }
+        let units: Vec<u16> = body.chunks_exact(2).map(|c| from([c[0], c[1]])).collect();
+        String::from_utf16(&units)
+            .map(|s| (s, false))
+            .map_err(|_| ImportError::Input("invalid UTF-16 input".to_string()))
+    };
+
+    match bytes {
+        [0xEF, 0xBB, 0xBF, rest @ ..] => Ok(decode_utf8(rest)),
+        [0xFF, 0xFE, rest @ ..] => utf16(rest, u16::from_le_bytes),
+        [0xFE, 0xFF, rest @ ..] => utf16(rest, u16::from_be_bytes),
+        _ => Ok(decode_utf8(bytes)),
+    }
+}
+
+fn decode_utf8(bytes: &[u8]) -> (String, bool) {
+    match std::str::from_utf8(bytes) {
+        Ok(s) => (s.to_string(), false),
+        Err(_) => (String::from_utf8_lossy(bytes).into_owned(), true),
+    }
+}
+
+/// One CSV record. `line` is where it starts; quoted fields may span lines.
+struct Record {
+    line: usize,
+    fields: Vec<String>,
+    error: Option<String>,
+}
+
+/// Split text into records (RFC 4180: quoted fields, `""` escapes, embedded
+/// newlines; LF, CRLF and CR line endings). Blank lines are skipped.
+fn parse_records(text: &str, delimiter: char) -> Vec<Record> {
+    let mut records = Vec::new();
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut error: Option<String> = None;
+    let mut in_quotes = false;
+    let mut quoted = false;
+    let mut line = 1;
+    let mut start = 1;
+
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if in_quotes {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.push('"');
+                }
+                '"' => in_quotes = false,
+                '\r' | '\n' => {
+                    if c == '\r' && chars.peek() == Some(&'\n') {
+                        chars.next();
+                    }
+                    line += 1;
+                    field.push('\n');
+                }
+                _ => field.push(c),
+            }
+            continue;
+        }
+
+        match c {
+            '"' if field.is_empty() && !quoted => {
+                in_quotes = true;
+                quoted = true;
+            }
+            '\r' | '\n' => {
+                if c == '\r' && chars.peek() == Some(&'\n') {
+                    chars.next();
+                }
+                fields.push(std::mem::take(&mut field));
+                let blank = fields.len() == 1 && fields[0].is_empty() && !quoted;
+                if !blank {
+                    records.push(Record {
+                        line: start,
+                        fields: std::mem::take(&mut fields),
+                        error: error.take(),
+                    });
+                }
+                fields.clear();
+                quoted = false;
+                line += 1;
+                start = line;
+            }
+            c if c == delimiter => {
+                fields.push(std::mem::take(&mut field));
+                quoted = false;
+            }
+            '"' => {
+                error.get_or_insert_with(|| format!("stray quote in field {}", fields.len() + 1));
+                field.push(c);
+            }
+            _ if quoted => {
+                error.get_or_insert_with(|| {
+                    format!("text after closing quote in field {}", fields.len() + 1)
+                });
+                field.push(c);
+            }
+            _ => field.push(c),
+        }
+    }
+
+    if in_quotes {
+        error.get_or_insert_with(|| "unterminated quoted field".to_string());
+    }
+    fields.push(field);
+    let blank = fields.len() == 1 && fields[0].is_empty() && !quoted;
+    if !blank {
+        records.push(Record {
+            line: start,
+            fields,
+            error,
+        });
+    }
+    records
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::import::RowError;
+
+    const FIXTURE: &str = include_str!("../../tests/fixtures/import_people.csv");
+    const EXPECTED: &str = include_str!("../../tests/fixtures/import_people.expected.json");
+
+    fn fixture_spec() -> CsvImportSpec {
+        CsvImportSpec {
+            context: Some(SlotSource::column("team")),
+            actor: Some(SlotSource::fixed("import:hr-sheet")),
+            timestamp: Some(TimestampSpec {
+                column: "joined".to_string(),
+                format: TimestampFormat::Pattern("%d/%m/%Y".to_string()),
+            }),
+            ignore_columns: vec!["internal_id".to_string()],
+            ..CsvImportSpec::new("name", SlotSource::fixed("member_of"))
+        }
+    }
+
+    #[test]
+    fn golden_messy_fixture() {
+        let report = import_csv(FIXTURE.as_bytes(), &fixture_spec()).unwrap();
+
+        // Attestation order follows the file; attributes are compared as maps.
+        let actual = serde_json::json!({
+            "attestations": report.attestations,
+            "errors": report.errors,
+            "duplicates": report.duplicates,
+        });
+        let expected: serde_json::Value = serde_json::from_str(EXPECTED).unwrap();
+        pretty_assertions::assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn bom_and_utf16_inputs_match_plain_utf8() {
+        let plain = import_csv(FIXTURE.as_bytes(), &fixture_spec()).unwrap();
+
+        let mut bom = vec![0xEF, 0xBB, 0xBF];
+        bom.extend_from_slice(FIXTURE.as_bytes());
+        let with_bom = import_csv(&bom[..], &fixture_spec()).unwrap();
+        assert_eq!(with_bom.attestations, plain.attestations);
+
+        let mut utf16 = vec![0xFF, 0xFE];
+        for unit in FIXTURE.encode_utf16() {
+            utf16.extend_from_slice(&unit.to_le_bytes());
+        }
+        let from_utf16 = import_csv(&utf16[..], &fixture_spec()).unwrap();
+        assert_eq!(from_utf16.attestations, plain.attestations);
+        assert_eq!(from_utf16.errors, plain.errors);
+    }
+
+    #[test]
+    fn invalid_utf8_is_reported_per_row() {
+        let mut input = b"name,team\nALICE,core\nB".to_vec();
+        input.push(0xFF);
+        input.extend_from_slice(b"B,ops\nCAROL,ops\n");
+        let spec = CsvImportSpec {
+            context: Some(SlotSource::column("team")),
+            default_timestamp_ms: Some(0),
+            ..CsvImportSpec::new("name", SlotSource::fixed("member_of"))
+        };
+
+        let report = import_csv(&input[..], &spec).unwrap();
+        assert_eq!(report.attestations.len(), 2);
+        assert_eq!(
+            report.errors,
+            vec![RowError {
+                line: 3,
+                column: Some("name".to_string()),
+                kind: RowErrorKind::Encoding,
+                message: "invalid UTF-8".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn spec_problems_fail_up_front() {
+        let spec = CsvImportSpec {
+            context: Some(SlotSource::column("team")),
+            default_timestamp_ms: Some(0),
+            ..CsvImportSpec::new("person", SlotSource::column("role"))
+        };
+        let err = import_csv("name,team\nALICE,core\n".as_bytes(), &spec).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "invalid import spec: columns not in header: person, role"
+        );
+
+        let spec = CsvImportSpec::new("name", SlotSource::fixed("knows"));
+        assert!(matches!(
+            import_csv("name\nALICE\n".as_bytes(), &spec),
+            Err(ImportError::Spec(_))
+        ));
+
+        let spec = CsvImportSpec {
+            default_timestamp_ms: Some(0),
+            ..CsvImportSpec::new("name", SlotSource::fixed("knows"))
+        };
+        assert!(matches!(
+            import_csv("".as_bytes(), &spec),
+            Err(ImportError::Input(_))
+        ));
+    }
+
+    #[test]
+    fn dedupe_off_keeps_repeated_rows() {
+        let spec = CsvImportSpec {
+            default_timestamp_ms: Some(0),
+            dedupe: false,
+            ..CsvImportSpec::new("name", SlotSource::fixed("knows"))
+        };
+        let report = import_csv("name\nALICE\nALICE\n".as_bytes(), &spec).unwrap();
+        assert_eq!(report.duplicates, 0);
+        assert_eq!(report.attestations.len(), 2);
+        assert_eq!(
+            report.attestations[1].id,
+            format!("{}-3", report.attestations[0].id)
+        );
+    }
+
+    #[test]
+    fn crlf_and_quoted_newlines() {
+        let records = parse_records("a,b\r\n\"x\r\ny\",2\r\n\r\nlast,3", ',');
+        let lines: Vec<usize> = records.iter().map(|r| r.line).collect();
+        assert_eq!(lines, vec![1, 2, 5]);
+        assert_eq!(records[1].fields, vec!["x\ny", "2"]);
+        assert!(records.iter().all(|r| r.error.is_none()));
+    }
+
+    #[test]
+    fn spec_deserializes_with_defaults() {
+        let spec: CsvImportSpec = serde_json::from_str(
+            r#"{
+                "subject_column": "name",
+                "predicate": {"fixed": "member_of"},
+                "context": {"column": "team"},
+                "timestamp": {"column": "joined", "format": {"pattern": "%d/%m/%Y"}}
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(spec.context, Some(SlotSource::column("team")));
+        assert_eq!(spec.source, "import");
+        assert_eq!(spec.delimiter, ',');
+        assert!(spec.dedupe && spec.remaining_as_attributes);
+    }
+}
@@ -0,0 +1,360 @@
+//! JSON-LD import: schema.org-style nodes, mapped property by property.
+//!
+//! Each mapped property on a node becomes one attestation: the node is the
+//! subject, the mapping names the predicate, and the property's values become
+//! contexts (`worksFor: {"name": "ACME"}` → `ALICE member_of ACME`).
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::{
+    default_source, default_true, ImportError, ImportReport, ReportBuilder, RowErrorKind,
+    TimestampFormat,
+};
+use crate::attestation::AttestationBuilder;
+
+/// One row of the mapping table.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PropertyMapping {
+    /// Property name, with or without a `schema:` / `https://schema.org/` prefix
+    pub property: String,
+    pub predicate: String,
+}
+
+impl PropertyMapping {
+    pub fn new(property: impl Into<String>, predicate: impl Into<String>) -> Self {
+        Self {
+            property: property.into(),
+            predicate: predicate.into(),
+        }
+    }
+}
+
+/// Explicit property → predicate table. Properties not listed are ignored;
+/// nothing is inferred from `@context`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JsonLdMapping {
+    pub properties: Vec<PropertyMapping>,
+    /// Also attest each node's `@type` under this predicate (e.g. `"is_a"`)
+    #[serde(default)]
+    pub type_predicate: Option<String>,
+    #[serde(default)]
+    pub actor: Option<String>,
+    /// Node property holding an RFC 3339 timestamp or date (e.g. `"dateModified"`)
+    #[serde(default)]
+    pub timestamp_property: Option<String>,
+    /// Timestamp (Unix ms) for nodes without `timestamp_property`
+    #[serde(default)]
+    pub default_timestamp_ms: Option<i64>,
+    #[serde(default = "default_source")]
+    pub source: String,
+    /// Drop attestations identical to an earlier one (counted in `duplicates`)
+    #[serde(default = "default_true")]
+    pub dedupe: bool,
+}
+
+impl JsonLdMapping {
+    /// Mapping with the given table; everything else at its default. Set
+    /// `timestamp_property` or `default_timestamp_ms` before importing.
+    pub fn new(properties: Vec<PropertyMapping>) -> Self {
+        Self {
+            properties,
+            type_predicate: None,
+            actor: None,
+            timestamp_property: None,
+            default_timestamp_ms: None,
+            source: default_source(),
+            dedupe: true,
+        }
+    }
+}
+
+/// Import a JSON-LD document: a single node, an array of nodes, or an object
+/// with `@graph`. Only top-level nodes are subjects; nested nodes contribute
+/// their `@id` or `name` as a value. Errors report the 1-based node index.
+pub fn import_jsonld(document: &str, mapping: &JsonLdMapping) -> Result<ImportReport, ImportError> {
+    if mapping.timestamp_property.is_none() && mapping.default_timestamp_ms.is_none() {
+        return Err(ImportError::Spec(
+            "set either timestamp_property or default_timestamp_ms".to_string(),
+        ));
+    }
+
+    let root: Value =
+        serde_json::from_str(document).map_err(|e| ImportError::Input(e.to_string()))?;
+    let nodes: Vec<&Value> = match &root {
+        Value::Object(obj) => match obj.get("@graph") {
+            Some(Value::Array(graph)) => graph.iter().collect(),
+            Some(_) => return Err(ImportError::Input("@graph must be an array".to_string())),
+            None => vec![&root],
+        },
+        Value::Array(nodes) => nodes.iter().collect(),
+        _ => {
+            return Err(ImportError::Input(
+                "expected a JSON-LD node, array of nodes, or @graph".to_string(),
+            ))
+        }
+    };
+
+    let mut out = ReportBuilder::new(mapping.dedupe);
+    for (i, node) in nodes.into_iter().enumerate() {
+        let line = i + 1;
+        let Some(obj) = node.as_object() else {
+            out.error(line, None, RowErrorKind::Malformed, "node is not an object");
+            continue;
+        };
+        let props: HashMap<&str, &Value> = obj.iter().map(|(k, v)| (strip_vocab(k), v)).collect();
+
+        let Some(subject) = node_ref(obj) else {
+            out.error(
+                line,
+                Some("@id"),
+                RowErrorKind::MissingValue,
+                "node has no @id or name",
+            );
+            continue;
+        };
+
+        let timestamp = match mapping
+            .timestamp_property
+            .as_deref()
+            .and_then(|p| props.get(strip_vocab(p)).map(|v| (p, v)))
+        {
+            Some((property, value)) => {
+                let parsed = value
+                    .as_str()
+                    .ok_or_else(|| "timestamp is not a string".to_string())
+                    .and_then(|s| TimestampFormat::Rfc3339.parse(s));
+                match parsed {
+                    Ok(ms) => ms,
+                    Err(e) => {
+                        out.error(line, Some(property), RowErrorKind::Timestamp, e);
+                        continue;
+                    }
+                }
+            }
+            None => match mapping.default_timestamp_ms {
+                Some(ms) => ms,
+                None => {
+                    out.error(
+                        line,
+                        mapping.timestamp_property.as_deref(),
+                        RowErrorKind::Timestamp,
+                        "missing timestamp",
+                    );
+                    continue;
+                }
+            },
+        };
+
+        let claim = |predicate: &str, contexts: Vec<String>| {
+            let mut builder = AttestationBuilder::new()
+                .subject(subject.as_str())
+                .predicate(predicate)
+                .contexts(contexts)
+                .timestamp(timestamp)
+                .source(mapping.source.as_str());
+            if let Some(actor) = &mapping.actor {
+                builder = builder.actor(actor.as_str());
+            }
+            builder.build()
+        };
+
+        if let Some(predicate) = &mapping.type_predicate {
+            let types = obj.get("@type").map(values).unwrap_or_default();
+            if !types.is_empty() {
+                out.push(line, claim(predicate, types));
+            }
+        }
+        for m in &mapping.properties {
+            let found = props.get(strip_vocab(&m.property)).map(|v| values(v));
+            if let Some(contexts) = found.filter(|c| !c.is_empty()) {
+                out.push(line, claim(&m.predicate, contexts));
+            }
+        }
+    }
+
+    Ok(out.finish())
+}
+
+/// Drop a schema.org vocabulary prefix from a property name.
+fn strip_vocab(property: &str) -> &str {
+    ["schema:", "http://schema.org/", "https://schema.org/"]
+        .iter()
+        .find_map(|prefix| property.strip_prefix(prefix))
+        .unwrap_or(property)
+}
+
+/// A node's identity: `@id`, falling back to `name`.
+fn node_ref(obj: &serde_json::Map<String, Value>) -> Option<String> {
+    ["@id", "name", "schema:name"]
+        .iter()
+        .filter_map(|k| obj.get(*k).and_then(scalar))
+        .find(|s| !s.is_empty())
+}
+
+fn scalar(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(s.trim().to_string()),
+        Value::Number(n) => Some(n.to_string()),
+        Value::Bool(b) => Some(b.to_string()),
+        _ => None,
+    }
+}
+
+/// Flatten a property value into strings: scalars as-is, nested nodes by
+/// `@id` / `@value` / `name`, arrays element-wise.
+fn values(value: &Value) -> Vec<String> {
+    let mut out = Vec::new();
+    match value {
+        Value::Array(items) => out.extend(items.iter().flat_map(values)),
+        Value::Object(obj) => out.extend(
+            ["@value", "@id", "name", "schema:name"]
+                .iter()
+                .find_map(|k| obj.get(*k).and_then(scalar)),
+        ),
+        other => out.extend(scalar(other)),
+    }
+    out.retain(|s| !s.is_empty());
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::import::RowError;
+
+    fn mapping() -> JsonLdMapping {
+        JsonLdMapping {
+            type_predicate: Some("is_a".to_string()),
+            actor: Some("import:directory".to_string()),
+            timestamp_property: Some("dateModified".to_string()),
+            default_timestamp_ms: Some(0),
+            ..JsonLdMapping::new(vec![
+                PropertyMapping::new("jobTitle", "has_role"),
+                PropertyMapping::new("schema:worksFor", "member_of"),
+                PropertyMapping::new("knowsAbout", "knows_about"),
+            ])
+        }
+    }
+
+    #[test]
+    fn maps_graph_nodes() {
+        let doc = r#"{
+            "@context": "https://schema.org",
+            "@graph": [
+                {
+                    "@id": "ALICE",
+                    "@type": "Person",
+                    "jobTitle": "Engineer",
+                    "worksFor": {"@type": "Organization", "name": "ACME"},
+                    "knowsAbout": ["Rust", {"@value": "SQLite"}],
+                    "telephone": "ignored",
+                    "dateModified": "2024-01-01"
+                },
+                {"name": "BOB", "schema:jobTitle": "Designer"},
+                {"@type": "Person", "jobTitle": "Ghost"},
+                {"@id": "CAROL", "jobTitle": "PM", "dateModified": "last week"},
+                "not a node"
+            ]
+        }"#;
+
+        let report = import_jsonld(doc, &mapping()).unwrap();
+        let claims: Vec<(String, String, Vec<String>, i64)> = report
+            .attestations
+            .iter()
+            .map(|a| {
+                (
+                    a.subjects[0].clone(),
+                    a.predicates[0].clone(),
+                    a.contexts.clone(),
+                    a.timestamp,
+                )
+            })
+            .collect();
+        let s = |v: &[&str]| v.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        assert_eq!(
+            claims,
+            vec![
+                (
+                    "ALICE".into(),
+                    "is_a".into(),
+                    s(&["Person"]),
+                    1_704_067_200_000
+                ),
+                (
+                    "ALICE".into(),
+                    "has_role".into(),
+                    s(&["Engineer"]),
+                    1_704_067_200_000
+                ),
+                (
+                    "ALICE".into(),
+                    "member_of".into(),
+                    s(&["ACME"]),
+                    1_704_067_200_000
+                ),
+                (
+                    "ALICE".into(),
+                    "knows_about".into(),
+                    s(&["Rust", "SQLite"]),
+                    1_704_067_200_000
+                ),
+                ("BOB".into(), "has_role".into(), s(&["Designer"]), 0),
+            ]
+        );
+        assert!(report
+            .attestations
+            .iter()
+            .all(|a| a.actors == vec!["import:directory".to_string()] && a.source == "import"));
+
+        let errors: Vec<(usize, RowErrorKind)> =
+            report.errors.iter().map(|e| (e.line, e.kind)).collect();
+        assert_eq!(
+            errors,
+            vec![
+                (3, RowErrorKind::MissingValue),
+                (4, RowErrorKind::Timestamp),
+                (5, RowErrorKind::Malformed),
+            ]
+        );
+        assert_eq!(
+            report.errors[1],
+            RowError {
+                line: 4,
+                column: Some("dateModified".to_string()),
+                kind: RowErrorKind::Timestamp,
+                message: "'last week' is not an RFC 3339 timestamp or date".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn single_node_and_duplicates() {
+        let doc = r#"[{"@id": "ALICE", "jobTitle": "Engineer"}, {"@id": "ALICE", "jobTitle": "Engineer"}]"#;
+        let report = import_jsonld(doc, &mapping()).unwrap();
+        assert_eq!(report.attestations.len(), 1);
+        assert_eq!(report.duplicates, 1);
+
+        let report = import_jsonld(r#"{"@id": "BOB", "jobTitle": "PM"}"#, &mapping()).unwrap();
+        assert_eq!(report.attestations.len(), 1);
+    }
+
+    #[test]
+    fn unusable_documents_fail() {
+        assert!(matches!(
+            import_jsonld("{", &mapping()),
+            Err(ImportError::Input(_))
+        ));
+        assert!(matches!(
+            import_jsonld("42", &mapping()),
+            Err(ImportError::Input(_))
+        ));
+        let no_time = JsonLdMapping::new(vec![]);
+        assert!(matches!(
+            import_jsonld("[]", &no_time),
+            Err(ImportError::Spec(_))
+        ));
+    }
+}
@@ -0,0 +1,99 @@
+//! Timestamp parsing for import adapters.
+
+use chrono::{DateTime, NaiveDate, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+
+/// How a timestamp cell is written.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TimestampFormat {
+    /// RFC 3339 (`2024-03-01T12:00:00Z`) or a bare `2024-03-01` date (midnight UTC)
+    #[default]
+    Rfc3339,
+    /// Unix milliseconds
+    UnixMs,
+    /// Unix seconds
+    UnixSeconds,
+    /// chrono strftime pattern, e.g. `%d/%m/%Y %H:%M`. Values without an
+    /// offset are taken as UTC; date-only patterns mean midnight.
+    Pattern(String),
+}
+
+impl TimestampFormat {
+    /// Parse `value` into Unix milliseconds.
+    pub fn parse(&self, value: &str) -> Result<i64, String> {
+        let value = value.trim();
+        match self {
+            TimestampFormat::Rfc3339 => DateTime::parse_from_rfc3339(value)
+                .map(|dt| dt.timestamp_millis())
+                .or_else(|_| parse_date(value, "%Y-%m-%d"))
+                .map_err(|_| format!("'{}' is not an RFC 3339 timestamp or date", value)),
+            TimestampFormat::UnixMs => value
+                .parse::<i64>()
+                .map_err(|_| format!("'{}' is not Unix milliseconds", value)),
+            TimestampFormat::UnixSeconds => value
+                .parse::<i64>()
+                .ok()
+                .and_then(|s| s.checked_mul(1000))
+                .ok_or_else(|| format!("'{}' is not Unix seconds", value)),
+            TimestampFormat::Pattern(pattern) => DateTime::parse_from_str(value, pattern)
+                .map(|dt| dt.timestamp_millis())
+                .or_else(|_| {
+                    NaiveDateTime::parse_from_str(value, pattern)
+                        .map(|dt| dt.and_utc().timestamp_millis())
+                })
+                .or_else(|_| parse_date(value, pattern))
+                .map_err(|_| format!("'{}' does not match pattern '{}'", value, pattern)),
+        }
+    }
+}
+
+fn parse_date(value: &str, pattern: &str) -> Result<i64, chrono::ParseError> {
+    NaiveDate::parse_from_str(value, pattern).map(|d| {
+        d.and_hms_opt(0, 0, 0)
+            .expect("midnight is always valid")
+            .and_utc()
+            .timestamp_millis()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_each_format() {
+        assert_eq!(
+            TimestampFormat::Rfc3339.parse("2024-01-01T00:00:00Z"),
+            Ok(1_704_067_200_000)
+        );
+        assert_eq!(
+            TimestampFormat::Rfc3339.parse("2024-01-01"),
+            Ok(1_704_067_200_000)
+        );
+        assert_eq!(
+            TimestampFormat::UnixMs.parse("1704067200000"),
+            Ok(1_704_067_200_000)
+        );
+        assert_eq!(
+            TimestampFormat::UnixSeconds.parse(" 1704067200 "),
+            Ok(1_704_067_200_000)
+        );
+        let dmy = TimestampFormat::Pattern("%d/%m/%Y".to_string());
+        assert_eq!(dmy.parse("01/01/2024"), Ok(1_704_067_200_000));
+        let with_time = TimestampFormat::Pattern("%d/%m/%Y %H:%M".to_string());
+        assert_eq!(with_time.parse("01/01/2024 00:01"), Ok(1_704_067_260_000));
+    }
+
+    #[test]
+    fn reports_unparseable_values() {
+        let err = TimestampFormat::Rfc3339.parse("yesterday").unwrap_err();
+        assert!(err.contains("yesterday"));
+        assert!(TimestampFormat::UnixSeconds
+            .parse("99999999999999999")
+            .is_err());
+        assert!(TimestampFormat::Pattern("%d/%m/%Y".to_string())
+            .parse("2024-13-01")
+            .is_err());
+    }
+}
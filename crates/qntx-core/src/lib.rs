@@ -6,6 +6,8 @@
 //! # Features
 //!
 //! - `wasm` - WASM-compatible build (excludes native-only features)
+//! - `import` - CSV / JSON-LD import adapters (native only)
+//! - `bench` - seeded self-benchmark workloads (`run_benchmarks`)
 //!
 //! # Example
 //!
@@ -22,25 +24,89 @@
 //! Fuzzy search was removed. Rich text search will be provided by MeiliSearch
 //! via the qntx-meili plugin (ADR-015).
 
+pub mod actor_alias;
 pub mod attestation;
+#[cfg(any(feature = "bench", test))]
+pub mod benchmark;
+pub mod canonical;
 pub mod classify;
+pub mod config;
+pub mod duration;
 pub mod expand;
+pub mod graph;
+#[cfg(feature = "import")]
+pub mod import;
+pub mod lint;
 pub mod parser;
+pub mod pseudonymize;
 pub mod similarity;
+pub mod slow_ops;
 pub mod storage;
+#[cfg(feature = "templates")]
+pub mod template;
 pub mod temporal;
+pub mod type_registry;
+pub mod vocabulary;
 pub mod watcher;
 // Re-export main types at crate root
-pub use attestation::{Attestation, AttestationBuilder, AxFilter, AxResult, Conflict};
+pub use actor_alias::{normalize_actor, ActorAliasError, ActorAliasMap, ACTOR_ALIAS_PREDICATE};
+pub use attestation::{
+    apply_attestation_patch_json, diff_attestations_json, Attestation, AttestationBuilder,
+    AttestationDiff, AttrCondition, AttrOp, AttrValue, AxFilter, AxResult, Conflict,
+    MatchingSummary, OverFilter, TermCount,
+};
+#[cfg(any(feature = "bench", test))]
+pub use benchmark::{
+    run_benchmarks, run_benchmarks_json, BenchConfig, BenchEnvironment, BenchReport, WorkloadTiming,
+};
+pub use canonical::{canonical_hash, canonical_hash_hex, to_canonical_json};
 pub use classify::{
-    classify_claims, ActorCredibility, ClaimGroup, ClaimInput, ClaimTiming, ClaimWithTiming,
-    ClassificationResult, ClassifyInput, ClassifyOutput, ConfidenceCalculator, ConflictType,
-    SmartClassifier, TemporalAnalyzer, TemporalConfig, TemporalPattern,
+    claims_supported_by, classify_claims, classify_claims_with_defaults, resolve_provenance,
+    ActorCredibility, ClaimGroup, ClaimInput, ClaimProvenance, ClaimTiming, ClaimWithTiming,
+    ClassificationPolicy, ClassificationResult, ClassifyInput, ClassifyOutput,
+    ConfidenceCalculator, ConflictType, ResolvedProvenance, SmartClassifier, StaleClaim,
+    StaleClaimsConfig, SupportedClaim, TemporalAnalyzer, TemporalConfig, TemporalPattern,
+};
+pub use config::{ConfigError, ConfigViolation, QntxCoreConfig};
+pub use duration::{
+    parse_duration, CalendarDuration, CalendarPolicy, DurationError, CALENDAR_POLICY,
 };
 pub use expand::{
-    dedup_source_ids, dedup_source_ids_json, expand_cartesian, expand_claims_json, group_by_key,
-    group_claims_json, DedupInput, DedupOutput, ExpandAttestation, ExpandInput, ExpandOutput,
-    GroupInput, GroupOutput, IndividualClaim,
+    claim_key, dedup_source_ids, dedup_source_ids_by_role, dedup_source_ids_json, expand_cartesian,
+    expand_claims_json, group_by_key, group_by_key_with_format, group_claims_json, ClaimKeyFormat,
+    DedupInput, DedupOutput, ExpandAttestation, ExpandInput, ExpandOutput, GroupInput, GroupOutput,
+    IndividualClaim,
+};
+pub use graph::{
+    project, project_force_graph_json, project_graph_json, ActorMode, EdgeWeight, ForceGraph,
+    Graph, GraphEdge, GraphNode, GraphProjection,
+};
+pub use lint::{
+    lint_query, lint_query_json, lint_query_str, LintCode, LintConfig, LintContext, LintSeverity,
+    LintStats, LintWarning,
+};
+pub use parser::{
+    AxQuery, AxQueryBuilder, Lexer, ParseError, Parser, TemporalClause, Token, TokenKind,
+};
+pub use pseudonymize::{
+    pseudonymize_attestations_json, pseudonymize_store, PseudonymizeConfig, PseudonymizeError,
+    PseudonymizeReport, Pseudonymizer, TimestampPolicy, ValuePolicy,
+};
+pub use slow_ops::{
+    set_slow_op_clock, set_slow_op_config, set_slow_op_hook, set_slow_op_thresholds_json,
+    slow_ops_report, slow_ops_report_json, SlowOp, SlowOpCategory, SlowOpConfig, SlowOpDetail,
+};
+pub use storage::{AttestationStore, ChangeFeed, MemoryStore, QueryStore, StoreError};
+#[cfg(feature = "templates")]
+pub use template::{
+    AttestationTemplate, ParamKind, SlotKind, TemplateError, TemplateLibrary, TemplateLoadError,
+    TemplateParam, TemplateSlot, TEMPLATE_CONTEXT, TEMPLATE_PREDICATE,
+};
+pub use type_registry::{
+    type_registry_json, AttributeType, TypeChange, TypeDef, TypeRegistry, TypeRegistryDiff,
+    TypeWarning, TypeWarningKind,
+};
+pub use vocabulary::{
+    extract_vocabulary, extract_vocabulary_json, Vocabulary, VocabularyAttestation,
+    VocabularyExtractor,
 };
-pub use parser::{AxQuery, Lexer, ParseError, Parser, TemporalClause, Token, TokenKind};
-pub use storage::{AttestationStore, MemoryStore, QueryStore, StoreError};
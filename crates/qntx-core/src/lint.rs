@@ -0,0 +1,648 @@
+//! AX query linting.
+//!
+//! A query can parse fine and still never match: a subject nobody attested,
+//! `of ALICE` where ALICE only ever appears as an actor, `since next week`.
+//! [`lint_query`] checks a parsed query against what the store holds and
+//! returns [`LintWarning`]s the editor shows inline next to parse errors.
+//!
+//! | Code | Severity | Fires when |
+//! |------|----------|------------|
+//! | `unknown_subject` / `_predicate` / `_context` / `_actor` | warning | the term is not in that slot's vocabulary (with did-you-mean suggestions) |
+//! | `slot_value_looks_like_other_slot` | warning | the term is unknown in its slot but known in another, e.g. an actor used as a context |
+//! | `predicate_variant_mix` | hint | two predicates of the query are near-spellings of each other and only one is known |
+//! | `empty_time_range` | warning | a `between` range is empty, or the range misses every stored timestamp |
+//! | `future_date` | warning | `since`, `on` or the start of `between` lies in the future |
+//!
+//! Vocabulary rules only run for slots whose vocabulary is non-empty, so an
+//! empty or unknown store yields no false alarms. Rules can be switched off
+//! through [`LintConfig`], the `lint` section of
+//! [`QntxCoreConfig`](crate::config::QntxCoreConfig).
+
+use std::collections::BTreeSet;
+
+use serde::{Deserialize, Serialize};
+
+use crate::attestation::Attestation;
+use crate::parser::{AxQuery, ParseError, Parser, TemporalClause};
+use crate::storage::{QueryStore, StoreError};
+use crate::temporal::resolve_temporal;
+use crate::vocabulary::Vocabulary;
+
+/// Suggestions offered per unknown term
+const MAX_SUGGESTIONS: usize = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LintSeverity {
+    Hint,
+    Warning,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LintCode {
+    UnknownSubject,
+    UnknownPredicate,
+    UnknownContext,
+    UnknownActor,
+    SlotValueLooksLikeOtherSlot,
+    PredicateVariantMix,
+    EmptyTimeRange,
+    FutureDate,
+}
+
+impl LintCode {
+    pub fn severity(self) -> LintSeverity {
+        match self {
+            Self::PredicateVariantMix => LintSeverity::Hint,
+            _ => LintSeverity::Warning,
+        }
+    }
+}
+
+/// Query slots, named as in the AX grammar's meaning.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Slot {
+    Subject,
+    Predicate,
+    Context,
+    Actor,
+}
+
+impl Slot {
+    const ALL: [Slot; 4] = [Slot::Subject, Slot::Predicate, Slot::Context, Slot::Actor];
+
+    fn unknown_code(self) -> LintCode {
+        match self {
+            Slot::Subject => LintCode::UnknownSubject,
+            Slot::Predicate => LintCode::UnknownPredicate,
+            Slot::Context => LintCode::UnknownContext,
+            Slot::Actor => LintCode::UnknownActor,
+        }
+    }
+
+    /// How the slot is written in a query
+    fn clause(self) -> &'static str {
+        match self {
+            Slot::Subject => "as a subject",
+            Slot::Predicate => "after 'is'",
+            Slot::Context => "after 'of'",
+            Slot::Actor => "after 'by'",
+        }
+    }
+
+    fn noun(self) -> &'static str {
+        match self {
+            Slot::Subject => "subject",
+            Slot::Predicate => "predicate",
+            Slot::Context => "context",
+            Slot::Actor => "actor",
+        }
+    }
+}
+
+/// One lint finding.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LintWarning {
+    pub code: LintCode,
+    pub severity: LintSeverity,
+    pub message: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub slot: Option<Slot>,
+    /// The offending term as written
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub term: Option<String>,
+    /// Byte offset of `term` in the query text (set by [`lint_query_str`])
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub position: Option<usize>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub suggestions: Vec<String>,
+}
+
+impl LintWarning {
+    fn new(code: LintCode, message: String) -> Self {
+        Self {
+            code,
+            severity: code.severity(),
+            message,
+            slot: None,
+            term: None,
+            position: None,
+            suggestions: Vec::new(),
+        }
+    }
+
+    fn at(mut self, slot: Option<Slot>, term: &str) -> Self {
+        self.slot = slot;
+        self.term = Some(term.to_string());
+        self
+    }
+}
+
+/// Which rules run.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LintConfig {
+    /// Rules switched off
+    pub disabled: Vec<LintCode>,
+}
+
+impl LintConfig {
+    pub fn is_enabled(&self, code: LintCode) -> bool {
+        !self.disabled.contains(&code)
+    }
+}
+
+/// Timestamp bounds of the stored attestations.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LintStats {
+    pub earliest_ms: i64,
+    pub latest_ms: i64,
+}
+
+/// What the query is checked against.
+#[derive(Debug, Clone, Default)]
+pub struct LintContext {
+    pub subjects: BTreeSet<String>,
+    pub predicates: BTreeSet<String>,
+    pub contexts: BTreeSet<String>,
+    pub actors: BTreeSet<String>,
+    /// Stored timestamp bounds; without them only empty `between` ranges are caught
+    pub stats: Option<LintStats>,
+    /// Reference time for relative expressions and [`LintCode::FutureDate`]
+    pub now_ms: i64,
+    pub config: LintConfig,
+}
+
+impl LintContext {
+    /// No vocabulary: only the temporal rules run.
+    pub fn new(now_ms: i64) -> Self {
+        Self {
+            now_ms,
+            ..Default::default()
+        }
+    }
+
+    pub fn from_vocabulary(vocabulary: &Vocabulary, now_ms: i64) -> Self {
+        let set = |terms: &[String]| terms.iter().cloned().collect();
+        Self {
+            subjects: set(&vocabulary.subjects),
+            predicates: set(&vocabulary.predicates),
+            contexts: set(&vocabulary.contexts),
+            actors: set(&vocabulary.actors),
+            ..Self::new(now_ms)
+        }
+    }
+
+    /// Vocabulary and timestamp bounds of `attestations`.
+    pub fn from_attestations(attestations: &[Attestation], now_ms: i64) -> Self {
+        let mut context = Self::new(now_ms);
+        for a in attestations {
+            context.subjects.extend(a.subjects.iter().cloned());
+            context.predicates.extend(a.predicates.iter().cloned());
+            context.contexts.extend(a.contexts.iter().cloned());
+            context.actors.extend(a.actors.iter().cloned());
+        }
+        context.stats =
+            attestations
+                .iter()
+                .map(|a| a.timestamp)
+                .fold(None, |bounds: Option<LintStats>, t| {
+                    Some(match bounds {
+                        None => LintStats {
+                            earliest_ms: t,
+                            latest_ms: t,
+                        },
+                        Some(b) => LintStats {
+                            earliest_ms: b.earliest_ms.min(t),
+                            latest_ms: b.latest_ms.max(t),
+                        },
+                    })
+                });
+        context
+    }
+
+    /// Vocabulary from the store's distinct-term lists; no timestamp bounds.
+    pub fn from_store<S: QueryStore + ?Sized>(store: &S, now_ms: i64) -> Result<Self, StoreError> {
+        Ok(Self {
+            subjects: store.subjects()?.into_iter().collect(),
+            predicates: store.predicates()?.into_iter().collect(),
+            contexts: store.contexts()?.into_iter().collect(),
+            actors: store.actors()?.into_iter().collect(),
+            ..Self::new(now_ms)
+        })
+    }
+
+    pub fn with_stats(mut self, stats: LintStats) -> Self {
+        self.stats = Some(stats);
+        self
+    }
+
+    pub fn with_config(mut self, config: LintConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    pub(crate) fn vocabulary(&self, slot: Slot) -> &BTreeSet<String> {
+        match slot {
+            Slot::Subject => &self.subjects,
+            Slot::Predicate => &self.predicates,
+            Slot::Context => &self.contexts,
+            Slot::Actor => &self.actors,
+        }
+    }
+}
+
+/// Lint a parsed query. Warnings come in query order, temporal ones last.
+pub fn lint_query(query: &AxQuery, context: &LintContext) -> Vec<LintWarning> {
+    let mut warnings = Vec::new();
+    for slot in Slot::ALL {
+        lint_slot(query, slot, context, &mut warnings);
+    }
+    lint_temporal(query, context, &mut warnings);
+    warnings.retain(|w| context.config.is_enabled(w.code));
+    warnings
+}
+
+/// Parse and lint `input`, filling in each warning's `position`.
+pub fn lint_query_str(input: &str, context: &LintContext) -> Result<Vec<LintWarning>, ParseError> {
+    let query = Parser::parse(input)?;
+    let mut warnings = lint_query(&query, context);
+    // Terms borrow from `input`, so their offset is their address difference
+    let base = input.as_ptr() as usize;
+    let terms = query
+        .subjects
+        .iter()
+        .chain(&query.predicates)
+        .chain(&query.contexts)
+        .chain(&query.actors)
+        .chain(temporal_terms(&query));
+    for warning in &mut warnings {
+        let Some(term) = &warning.term else { continue };
+        warning.position = terms
+            .clone()
+            .find(|t| **t == term.as_str())
+            .map(|t| t.as_ptr() as usize)
+            .filter(|&p| p >= base && p <= base + input.len())
+            .map(|p| p - base);
+    }
+    Ok(warnings)
+}
+
+/// JSON entry point. Returns `{"warnings":[...]}` or `{"error":"..."}`.
+pub fn lint_query_json(input: &str, context: &LintContext) -> String {
+    match lint_query_str(input, context) {
+        Ok(warnings) => serde_json::json!({ "warnings": warnings }).to_string(),
+        Err(e) => serde_json::json!({ "error": e.to_string() }).to_string(),
+    }
+}
+
+fn temporal_terms<'q>(query: &'q AxQuery) -> Vec<&'q &'q str> {
+    match &query.temporal {
+        Some(TemporalClause::Since(e) | TemporalClause::Until(e) | TemporalClause::On(e)) => {
+            vec![e]
+        }
+        Some(TemporalClause::Between(start, end)) => vec![start, end],
+        Some(TemporalClause::Over(_)) | None => Vec::new(),
+    }
+}
+
+fn terms<'q>(query: &'q AxQuery, slot: Slot) -> &'q [&'q str] {
+    match slot {
+        Slot::Subject => &query.subjects,
+        Slot::Predicate => &query.predicates,
+        Slot::Context => &query.contexts,
+        Slot::Actor => &query.actors,
+    }
+}
+
+fn lint_slot(query: &AxQuery, slot: Slot, context: &LintContext, out: &mut Vec<LintWarning>) {
+    let vocabulary = context.vocabulary(slot);
+    if vocabulary.is_empty() {
+        return;
+    }
+    let written = terms(query, slot);
+    for &term in written {
+        if vocabulary.contains(term) {
+            continue;
+        }
+
+        // Known in another slot: almost certainly the wrong keyword
+        if let Some(other) = Slot::ALL
+            .into_iter()
+            .find(|&s| s != slot && context.vocabulary(s).contains(term))
+        {
+            out.push(
+                LintWarning::new(
+                    LintCode::SlotValueLooksLikeOtherSlot,
+                    format!(
+                        "'{}' is known as {} {}, not as {} {}",
+                        term,
+                        article(other.noun()),
+                        other.noun(),
+                        article(slot.noun()),
+                        slot.noun()
+                    ),
+                )
+                .at(Some(slot), term),
+            );
+            continue;
+        }
+
+        let suggestions = suggest(term, vocabulary);
+        // Its likely correct spelling is already in the query, which still matches
+        if slot == Slot::Predicate {
+            if let Some(known) = suggestions.iter().find(|s| written.contains(&s.as_str())) {
+                out.push(
+                    LintWarning::new(
+                        LintCode::PredicateVariantMix,
+                        format!(
+                            "'{}' looks like a misspelling of '{}', which the query also uses",
+                            term, known
+                        ),
+                    )
+                    .at(Some(slot), term),
+                );
+                continue;
+            }
+        }
+
+        let mut warning = LintWarning::new(
+            slot.unknown_code(),
+            format!(
+                "no attestation has '{}' {}; this matches nothing",
+                term,
+                slot.clause()
+            ),
+        )
+        .at(Some(slot), term);
+        warning.suggestions = suggestions;
+        out.push(warning);
+    }
+}
+
+fn article(noun: &str) -> &'static str {
+    if noun.starts_with(['a', 'e', 'i', 'o', 'u']) {
+        "an"
+    } else {
+        "a"
+    }
+}
+
+fn lint_temporal(query: &AxQuery, context: &LintContext, out: &mut Vec<LintWarning>) {
+    let now = context.now_ms;
+    // Unresolvable expressions are the parser diagnostics' business
+    let resolve = |expr: &str| resolve_temporal(expr, now);
+    let future = |expr: &str| {
+        LintWarning::new(
+            LintCode::FutureDate,
+            format!("'{}' is in the future; nothing has been attested yet", expr),
+        )
+        .at(None, expr)
+    };
+
+    let (start, end) = match &query.temporal {
+        Some(TemporalClause::Since(expr)) => {
+            let Some(start) = resolve(expr) else { return };
+            if start > now {
+                out.push(future(expr));
+            }
+            (Some(start), None)
+        }
+        Some(TemporalClause::On(expr)) => {
+            let Some(start) = resolve(expr) else { return };
+            if start > now {
+                out.push(future(expr));
+            }
+            (Some(start), Some(start + crate::duration::MS_PER_DAY))
+        }
+        Some(TemporalClause::Until(expr)) => (None, resolve(expr)),
+        Some(TemporalClause::Between(start_expr, end_expr)) => {
+            let (Some(start), Some(end)) = (resolve(start_expr), resolve(end_expr)) else {
+                return;
+            };
+            if start >= end {
+                out.push(
+                    LintWarning::new(
+                        LintCode::EmptyTimeRange,
+                        format!(
+                            "'{}' is not before '{}'; the range is empty",
+                            start_expr, end_expr
+                        ),
+                    )
+                    .at(None, start_expr),
+                );
+                return;
+            }
+            if start > now {
+                out.push(future(start_expr));
+            }
+            (Some(start), Some(end))
+        }
+        Some(TemporalClause::Over(_)) | None => return,
+    };
+
+    let Some(stats) = context.stats else { return };
+    let misses_before = end.is_some_and(|end| end < stats.earliest_ms);
+    let misses_after = start.is_some_and(|start| start > stats.latest_ms);
+    // A future start already got its own warning
+    let future_start = start.is_some_and(|s| s > now);
+    if misses_before || (misses_after && !future_start) {
+        out.push(LintWarning::new(
+            LintCode::EmptyTimeRange,
+            "the time range contains no stored attestation".to_string(),
+        ));
+    }
+}
+
+/// Vocabulary terms within a small edit distance of `term`, closest first.
+fn suggest(term: &str, vocabulary: &BTreeSet<String>) -> Vec<String> {
+    let lower = term.to_lowercase();
+    let max = match lower.chars().count() {
+        0..=4 => 1,
+        5..=8 => 2,
+        _ => 3,
+    };
+    let mut scored: Vec<(usize, &String)> = vocabulary
+        .iter()
+        .filter_map(|candidate| {
+            let d = edit_distance(&lower, &candidate.to_lowercase());
+            (d <= max).then_some((d, candidate))
+        })
+        .collect();
+    scored.sort();
+    scored
+        .into_iter()
+        .take(MAX_SUGGESTIONS)
+        .map(|(_, s)| s.clone())
+        .collect()
+}
+
+/// Levenshtein distance over chars.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, &cb) in b.iter().enumerate() {
+            let substitution = diagonal + usize::from(ca != cb);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(row[j + 1] + 1);
+        }
+    }
+    row[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAY: i64 = crate::duration::MS_PER_DAY;
+    /// 2024-06-01T00:00:00Z
+    const NOW: i64 = 1_717_200_000_000;
+
+    fn context() -> LintContext {
+        let set = |terms: &[&str]| terms.iter().map(|s| s.to_string()).collect();
+        LintContext {
+            subjects: set(&["ALICE", "BOB", "CAROL"]),
+            predicates: set(&["author_of", "engineer", "member"]),
+            contexts: set(&["GitHub", "ACME"]),
+            actors: set(&["human:dana", "system:ci"]),
+            stats: Some(LintStats {
+                earliest_ms: NOW - 365 * DAY,
+                latest_ms: NOW - DAY,
+            }),
+            now_ms: NOW,
+            config: LintConfig::default(),
+        }
+    }
+
+    fn lint(input: &str) -> Vec<LintWarning> {
+        lint_query_str(input, &context()).unwrap()
+    }
+
+    fn codes(warnings: &[LintWarning]) -> Vec<LintCode> {
+        warnings.iter().map(|w| w.code).collect()
+    }
+
+    #[test]
+    fn test_valid_query_has_no_warnings() {
+        let warnings =
+            lint("ALICE BOB is author_of member of GitHub by human:dana since 2024-01-01");
+        assert!(warnings.is_empty(), "{:?}", warnings);
+    }
+
+    #[test]
+    fn test_unknown_subject_suggests_close_terms() {
+        let warnings = lint("ALISE is engineer");
+        assert_eq!(codes(&warnings), vec![LintCode::UnknownSubject]);
+        let w = &warnings[0];
+        assert_eq!(w.severity, LintSeverity::Warning);
+        assert_eq!(w.term.as_deref(), Some("ALISE"));
+        assert_eq!(w.position, Some(0));
+        assert_eq!(w.suggestions, vec!["ALICE"]);
+
+        let warnings = lint("ALICE is enginer of Acme");
+        assert_eq!(
+            codes(&warnings),
+            vec![LintCode::UnknownPredicate, LintCode::UnknownContext]
+        );
+        assert_eq!(warnings[0].suggestions, vec!["engineer"]);
+        // Case differences count as close
+        assert_eq!(warnings[1].suggestions, vec!["ACME"]);
+        assert_eq!(warnings[1].position, Some(20));
+    }
+
+    #[test]
+    fn test_value_from_other_slot() {
+        let warnings = lint("ALICE is engineer of human:dana");
+        assert_eq!(
+            codes(&warnings),
+            vec![LintCode::SlotValueLooksLikeOtherSlot]
+        );
+        assert_eq!(warnings[0].slot, Some(Slot::Context));
+        assert!(warnings[0].message.contains("an actor"));
+    }
+
+    #[test]
+    fn test_predicate_variant_mix_is_a_hint() {
+        let warnings = lint("ALICE is author_of autor_of");
+        assert_eq!(codes(&warnings), vec![LintCode::PredicateVariantMix]);
+        assert_eq!(warnings[0].severity, LintSeverity::Hint);
+        assert_eq!(warnings[0].term.as_deref(), Some("autor_of"));
+    }
+
+    #[test]
+    fn test_empty_time_range() {
+        let warnings = lint("ALICE between 2024-03-01 and 2024-02-01");
+        assert_eq!(codes(&warnings), vec![LintCode::EmptyTimeRange]);
+
+        // Before anything was stored
+        let warnings = lint("ALICE until 2020-01-01");
+        assert_eq!(codes(&warnings), vec![LintCode::EmptyTimeRange]);
+
+        // Without stats only reversed ranges are caught
+        let mut ctx = context();
+        ctx.stats = None;
+        let warnings = lint_query_str("ALICE until 2020-01-01", &ctx).unwrap();
+        assert!(warnings.is_empty());
+    }
+
+    #[test]
+    fn test_future_date() {
+        let warnings = lint("ALICE since 2030-01-01");
+        assert_eq!(codes(&warnings), vec![LintCode::FutureDate]);
+        assert_eq!(warnings[0].term.as_deref(), Some("2030-01-01"));
+        assert_eq!(warnings[0].position, Some(12));
+
+        assert_eq!(
+            codes(&lint("ALICE on tomorrow")),
+            vec![LintCode::FutureDate]
+        );
+        // An open-ended `until` in the future still matches the past
+        assert!(lint("ALICE until 2030-01-01").is_empty());
+    }
+
+    #[test]
+    fn test_disabled_rules_and_empty_vocabulary() {
+        let ctx = context().with_config(LintConfig {
+            disabled: vec![LintCode::UnknownSubject, LintCode::FutureDate],
+        });
+        let warnings = lint_query_str("ALISE since 2030-01-01", &ctx).unwrap();
+        assert!(warnings.is_empty(), "{:?}", warnings);
+
+        // No vocabulary, no vocabulary warnings
+        let warnings = lint_query_str("ANYONE is anything", &LintContext::new(NOW)).unwrap();
+        assert!(warnings.is_empty());
+    }
+
+    #[test]
+    fn test_context_from_attestations_and_json() {
+        let a = crate::AttestationBuilder::new()
+            .id("AS-1")
+            .subject("ALICE")
+            .predicate("engineer")
+            .context("ACME")
+            .actor("human:dana")
+            .timestamp(NOW - DAY)
+            .build();
+        let ctx = LintContext::from_attestations(&[a], NOW);
+        assert_eq!(ctx.stats.unwrap().earliest_ms, NOW - DAY);
+        let json: serde_json::Value =
+            serde_json::from_str(&lint_query_json("ALICE is enginer", &ctx)).unwrap();
+        assert_eq!(json["warnings"][0]["code"], "unknown_predicate");
+        assert_eq!(json["warnings"][0]["suggestions"][0], "engineer");
+
+        let json: serde_json::Value = serde_json::from_str(&lint_query_json("is", &ctx)).unwrap();
+        assert!(json["error"].is_string());
+    }
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("", "abc"), 3);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("author_of", "autor_of"), 1);
+    }
+}
@@ -0,0 +1,577 @@
+//! Alternate interpretations of slot-ambiguous queries.
+//!
+//! The parser commits to one reading of every query. A few token patterns
+//! are routinely meant another way, and at those points
+//! [`Parser::parse_with_alternates`] forks:
+//!
+//! | Kind | Example | Alternate |
+//! |------|---------|-----------|
+//! | `keyword_case` | `R And D is team` | a keyword that isn't all lowercase read as a term: `R "And" D is team` |
+//! | `compound_predicate` | `NOTES is summary of Q3` | the predicate joined with the following `of`/`from`/`by`/`via`: `NOTES is summary_of of Q3` |
+//! | `implicit_predicate` | `ALICE engineer` | without an `is` clause, the last of several subjects as predicate: `ALICE is engineer` |
+//! | `slot_boundary` | `ALICE is engineer ACME of PROJECT` | the last of several terms moved past the keyword into the next slot: `ALICE is engineer of ACME PROJECT` |
+//!
+//! Each alternate is the rewritten query text, re-parsed with [`Parser::parse`];
+//! rewrites that don't parse or read the same as the primary are dropped. The
+//! primary is always exactly what `parse` returns.
+//!
+//! Interpretations are scored on how many slots they fill, whether a
+//! [`LintContext`] vocabulary knows their terms in the slot they land in, and
+//! a small penalty per rewrite. Confidences are the softmax of the scores
+//! over the primary and its alternates, so they sum to 1.
+//!
+//! Forking is bounded: only the first [`MAX_AMBIGUITIES`] points are used,
+//! each alone and in pairs, and at most [`MAX_FORKS`] rewrites are parsed.
+
+use serde::{Deserialize, Serialize};
+
+use super::{AxQuery, ParseError, Parser, TemporalClause, Token, TokenKind};
+use crate::lint::{LintContext, Slot};
+use crate::temporal::resolve_temporal;
+
+/// Ambiguity points considered per query
+pub const MAX_AMBIGUITIES: usize = 12;
+
+/// Rewrites parsed per query
+pub const MAX_FORKS: usize = 64;
+
+/// Score cost of each rewrite, so the written reading wins ties
+const REWRITE_PENALTY: f64 = 0.5;
+
+/// Which ambiguity an alternate resolves differently
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AmbiguityKind {
+    KeywordCase,
+    CompoundPredicate,
+    ImplicitPredicate,
+    SlotBoundary,
+}
+
+/// One ambiguity point, at the term it concerns
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Ambiguity {
+    pub kind: AmbiguityKind,
+    pub term: String,
+    /// Byte offset of `term` in the query text
+    pub position: usize,
+}
+
+/// Another way to read the query.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QueryAlternate {
+    /// The query rewritten to mean this reading, for "did you mean" chips
+    pub text: String,
+    pub confidence: f64,
+    /// The points resolved differently from the primary
+    pub ambiguities: Vec<Ambiguity>,
+}
+
+impl QueryAlternate {
+    /// The parsed alternate
+    pub fn query(&self) -> AxQuery<'_> {
+        Parser::parse(&self.text).expect("alternates are only kept when they parse")
+    }
+}
+
+/// A parse with its alternates, most confident first.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseAlternates<'a> {
+    /// Exactly what [`Parser::parse`] returns
+    pub primary: AxQuery<'a>,
+    pub primary_confidence: f64,
+    pub alternates: Vec<QueryAlternate>,
+    /// Rewrites parsed to find the alternates (at most [`MAX_FORKS`])
+    pub forks: usize,
+}
+
+/// A text edit resolving one ambiguity point: replace `start..end`
+struct Edit {
+    ambiguity: Ambiguity,
+    start: usize,
+    end: usize,
+    replacement: String,
+}
+
+impl<'a> Parser<'a> {
+    /// Parse `input` and up to `max_alternates` alternate readings, scored
+    /// without vocabulary.
+    pub fn parse_with_alternates(
+        input: &'a str,
+        max_alternates: usize,
+    ) -> Result<ParseAlternates<'a>, ParseError> {
+        Self::parse_with_alternates_in(input, max_alternates, None)
+    }
+
+    /// Like [`parse_with_alternates`](Self::parse_with_alternates), ranking
+    /// readings whose terms `vocabulary` knows in their slot higher.
+    pub fn parse_with_alternates_in(
+        input: &'a str,
+        max_alternates: usize,
+        vocabulary: Option<&LintContext>,
+    ) -> Result<ParseAlternates<'a>, ParseError> {
+        let primary = Parser::parse(input)?;
+        let tokens: Vec<Token<'a>> = super::Lexer::new(input)
+            .take_while(|t| t.kind != TokenKind::Eof)
+            .collect();
+        let edits = ambiguity_points(&tokens, &primary);
+
+        // Readings are compared by their canonical text, which parses back
+        // to an equal query
+        let mut seen = vec![primary.to_query_string()];
+        let mut readings: Vec<(String, Vec<usize>, f64)> = Vec::new();
+        let mut forks = 0;
+        for combo in combinations(edits.len()) {
+            if forks == MAX_FORKS {
+                break;
+            }
+            if overlapping(&edits, &combo) {
+                continue;
+            }
+            forks += 1;
+            let text = apply(input, &edits, &combo);
+            let Ok(query) = Parser::parse(&text) else {
+                continue;
+            };
+            let canonical = query.to_query_string();
+            if seen.contains(&canonical) {
+                continue;
+            }
+            seen.push(canonical);
+            let score = score(&query, combo.len(), vocabulary);
+            readings.push((text, combo, score));
+        }
+
+        let primary_score = score(&primary, 0, vocabulary);
+        let top = readings
+            .iter()
+            .map(|(_, _, s)| *s)
+            .fold(primary_score, f64::max);
+        let weight = |s: f64| (s - top).exp();
+        let total = weight(primary_score) + readings.iter().map(|r| weight(r.2)).sum::<f64>();
+        let confidence = |s: f64| (weight(s) / total * 1000.0).round() / 1000.0;
+
+        let mut alternates: Vec<QueryAlternate> = readings
+            .into_iter()
+            .map(|(text, combo, s)| QueryAlternate {
+                text,
+                confidence: confidence(s),
+                ambiguities: combo.iter().map(|&i| edits[i].ambiguity.clone()).collect(),
+            })
+            .collect();
+        alternates.sort_by(|a, b| {
+            b.confidence
+                .total_cmp(&a.confidence)
+                .then(a.ambiguities.len().cmp(&b.ambiguities.len()))
+                .then_with(|| a.text.cmp(&b.text))
+        });
+        alternates.truncate(max_alternates);
+
+        Ok(ParseAlternates {
+            primary,
+            primary_confidence: confidence(primary_score),
+            alternates,
+            forks,
+        })
+    }
+}
+
+/// JSON entry point. Returns
+/// `{"primary":{...},"confidence":0.6,"alternates":[{"text","query","confidence","ambiguities"}]}`
+/// or `{"error":"..."}`.
+pub fn parse_query_alternates_json(
+    input: &str,
+    max_alternates: usize,
+    vocabulary: Option<&LintContext>,
+) -> String {
+    match Parser::parse_with_alternates_in(input, max_alternates, vocabulary) {
+        Ok(parsed) => serde_json::json!({
+            "primary": parsed.primary,
+            "confidence": parsed.primary_confidence,
+            "alternates": parsed
+                .alternates
+                .iter()
+                .map(|a| serde_json::json!({
+                    "text": a.text,
+                    "query": a.query(),
+                    "confidence": a.confidence,
+                    "ambiguities": a.ambiguities,
+                }))
+                .collect::<Vec<_>>(),
+        })
+        .to_string(),
+        Err(e) => serde_json::json!({ "error": e.to_string() }).to_string(),
+    }
+}
+
+/// Slot each term token lands in, following the parser's clause keywords.
+/// Temporal and action terms get None.
+fn token_slots(tokens: &[Token]) -> Vec<Option<Slot>> {
+    let mut current = Some(Slot::Subject);
+    tokens
+        .iter()
+        .map(|t| match t.kind {
+            TokenKind::Identifier | TokenKind::QuotedString => current,
+            TokenKind::Is | TokenKind::Are => {
+                current = Some(Slot::Predicate);
+                None
+            }
+            TokenKind::Of | TokenKind::From => {
+                current = Some(Slot::Context);
+                None
+            }
+            TokenKind::By | TokenKind::Via => {
+                current = Some(Slot::Actor);
+                None
+            }
+            TokenKind::Since
+            | TokenKind::Until
+            | TokenKind::On
+            | TokenKind::Between
+            | TokenKind::Over
+            | TokenKind::So
+            | TokenKind::Therefore
+            | TokenKind::Where => {
+                current = None;
+                None
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+fn slot_terms<'q>(query: &'q AxQuery, slot: Slot) -> &'q [&'q str] {
+    match slot {
+        Slot::Subject => &query.subjects,
+        Slot::Predicate => &query.predicates,
+        Slot::Context => &query.contexts,
+        Slot::Actor => &query.actors,
+    }
+}
+
+fn ambiguity_points(tokens: &[Token], primary: &AxQuery) -> Vec<Edit> {
+    let slots = token_slots(tokens);
+    let is_term = |t: &Token| t.kind == TokenKind::Identifier;
+    let span_end = |t: &Token| t.offset + t.text.len();
+    let edit = |kind, t: &Token, start, end, replacement| Edit {
+        ambiguity: Ambiguity {
+            kind,
+            term: t.text.to_string(),
+            position: t.offset,
+        },
+        start,
+        end,
+        replacement,
+    };
+
+    let mut edits = Vec::new();
+    for (i, token) in tokens.iter().enumerate() {
+        let next = tokens.get(i + 1);
+
+        if token.kind != TokenKind::Identifier
+            && token.kind != TokenKind::QuotedString
+            && super::lexer::keyword_kind(token.text) == Some(token.kind)
+            && token.text != token.text.to_ascii_lowercase()
+        {
+            edits.push(edit(
+                AmbiguityKind::KeywordCase,
+                token,
+                token.offset,
+                span_end(token),
+                format!("\"{}\"", token.text),
+            ));
+        }
+
+        let Some(slot) = slots[i].filter(|_| is_term(token)) else {
+            continue;
+        };
+        let last_in_slot = next.is_none_or(|n| slots[i + 1].is_none() || !is_term(n));
+
+        if slot == Slot::Predicate {
+            if let Some(keyword) = next.filter(|n| {
+                matches!(
+                    n.kind,
+                    TokenKind::Of | TokenKind::From | TokenKind::By | TokenKind::Via
+                )
+            }) {
+                let followed = tokens.get(i + 2).is_some_and(|t| {
+                    matches!(t.kind, TokenKind::Identifier | TokenKind::QuotedString)
+                });
+                if followed {
+                    edits.push(edit(
+                        AmbiguityKind::CompoundPredicate,
+                        token,
+                        token.offset,
+                        span_end(token),
+                        format!("{}_{}", token.text, keyword.text.to_ascii_lowercase()),
+                    ));
+                }
+            }
+        }
+
+        if slot == Slot::Subject
+            && last_in_slot
+            && primary.subjects.len() >= 2
+            && primary.predicates.is_empty()
+        {
+            edits.push(edit(
+                AmbiguityKind::ImplicitPredicate,
+                token,
+                token.offset,
+                token.offset,
+                "is ".to_string(),
+            ));
+        }
+
+        if let Some(keyword) = next.filter(|n| n.kind.is_clause_keyword()) {
+            if slot_terms(primary, slot).len() >= 2 {
+                edits.push(edit(
+                    AmbiguityKind::SlotBoundary,
+                    token,
+                    token.offset,
+                    span_end(keyword),
+                    format!("{} {}", keyword.text, token.text),
+                ));
+            }
+        }
+    }
+    edits.truncate(MAX_AMBIGUITIES);
+    edits
+}
+
+/// Index sets of `n` points: each alone, then each pair
+fn combinations(n: usize) -> impl Iterator<Item = Vec<usize>> {
+    (0..n)
+        .map(|i| vec![i])
+        .chain((0..n).flat_map(move |i| (i + 1..n).map(move |j| vec![i, j])))
+}
+
+fn overlapping(edits: &[Edit], combo: &[usize]) -> bool {
+    combo.iter().enumerate().any(|(n, &i)| {
+        combo[n + 1..].iter().any(|&j| {
+            let (a, b) = (&edits[i], &edits[j]);
+            a.start <= b.end && b.start <= a.end
+        })
+    })
+}
+
+fn apply(input: &str, edits: &[Edit], combo: &[usize]) -> String {
+    let mut chosen: Vec<&Edit> = combo.iter().map(|&i| &edits[i]).collect();
+    chosen.sort_by_key(|e| std::cmp::Reverse(e.start));
+    let mut text = input.to_string();
+    for e in chosen {
+        text.replace_range(e.start..e.end, &e.replacement);
+    }
+    text
+}
+
+/// Whether a temporal clause's expressions mean a time at all
+fn temporal_resolves(temporal: &TemporalClause, now_ms: i64) -> bool {
+    let resolves = |expr: &str| resolve_temporal(expr, now_ms).is_some();
+    match temporal {
+        TemporalClause::Since(e) | TemporalClause::Until(e) | TemporalClause::On(e) => resolves(e),
+        TemporalClause::Between(start, end) => resolves(start) && resolves(end),
+        TemporalClause::Over(duration) => duration.duration().is_ok(),
+    }
+}
+
+/// Filled slots, plus vocabulary agreement, minus the rewrites it took. A
+/// temporal clause that resolves to no time (`On Sale`) counts against.
+fn score(query: &AxQuery, rewrites: usize, vocabulary: Option<&LintContext>) -> f64 {
+    let temporal = match &query.temporal {
+        Some(t) if temporal_resolves(t, vocabulary.map_or(0, |v| v.now_ms)) => 1.0,
+        Some(_) => -1.0,
+        None => 0.0,
+    };
+    let filled = [
+        query.has_subjects(),
+        query.has_predicates(),
+        query.has_contexts(),
+        query.has_actors(),
+        query.has_actions(),
+    ]
+    .iter()
+    .filter(|&&f| f)
+    .count() as f64
+        + temporal;
+
+    let mut agreement = 0.0;
+    if let Some(vocabulary) = vocabulary {
+        for slot in [Slot::Subject, Slot::Predicate, Slot::Context, Slot::Actor] {
+            let known = vocabulary.vocabulary(slot);
+            if known.is_empty() {
+                continue;
+            }
+            for term in slot_terms(query, slot) {
+                agreement += if known.contains(*term) {
+                    1.0
+                } else if [Slot::Subject, Slot::Predicate, Slot::Context, Slot::Actor]
+                    .iter()
+                    .any(|&other| vocabulary.vocabulary(other).contains(*term))
+                {
+                    -1.0
+                } else {
+                    -0.25
+                };
+            }
+        }
+    }
+    filled + agreement - REWRITE_PENALTY * rewrites as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vocabulary() -> LintContext {
+        let set = |terms: &[&str]| terms.iter().map(|s| s.to_string()).collect();
+        LintContext {
+            subjects: set(&["MEETING_NOTES", "ALICE", "RESEARCH", "DEVELOPMENT"]),
+            predicates: set(&["summary_of", "engineer", "team"]),
+            contexts: set(&["Q3", "ACME"]),
+            ..LintContext::new(0)
+        }
+    }
+
+    /// (text, confidence, kinds) of each alternate
+    fn alternates(
+        input: &str,
+        vocabulary: Option<&LintContext>,
+    ) -> (f64, Vec<(String, f64, Vec<AmbiguityKind>)>) {
+        let parsed = Parser::parse_with_alternates_in(input, 5, vocabulary).unwrap();
+        assert_eq!(parsed.primary, Parser::parse(input).unwrap());
+        let alternates = parsed
+            .alternates
+            .into_iter()
+            .map(|a| {
+                let kinds = a.ambiguities.iter().map(|x| x.kind).collect();
+                (a.text, a.confidence, kinds)
+            })
+            .collect();
+        (parsed.primary_confidence, alternates)
+    }
+
+    #[test]
+    fn compound_predicate_fixture() {
+        let input = "MEETING_NOTES is summary of Q3";
+        let (primary, alts) = alternates(input, None);
+        assert_eq!(alts.len(), 1);
+        assert_eq!(alts[0].0, "MEETING_NOTES is summary_of of Q3");
+        assert_eq!(alts[0].2, [AmbiguityKind::CompoundPredicate]);
+        // Same slots filled: the written reading wins on the rewrite penalty
+        assert!(primary > alts[0].1);
+
+        let parsed = Parser::parse_with_alternates(input, 1).unwrap();
+        let alternate = parsed.alternates[0].query();
+        assert_eq!(alternate.predicates, ["summary_of"]);
+        assert_eq!(alternate.contexts, ["Q3"]);
+
+        // A vocabulary that knows `summary_of` flips the ranking
+        let (primary, alts) = alternates(input, Some(&vocabulary()));
+        assert!(alts[0].1 > primary);
+        assert!((primary + alts[0].1 - 1.0).abs() < 0.002);
+    }
+
+    #[test]
+    fn implicit_predicate_fixture() {
+        let (primary, alts) = alternates("ALICE engineer", None);
+        assert_eq!(alts.len(), 1);
+        assert_eq!(alts[0].0, "ALICE is engineer");
+        assert_eq!(alts[0].2, [AmbiguityKind::ImplicitPredicate]);
+        // Two filled slots beat one even after the rewrite penalty
+        assert!(alts[0].1 > primary);
+
+        let (_, with_vocabulary) = alternates("ALICE engineer", Some(&vocabulary()));
+        assert!(with_vocabulary[0].1 > alts[0].1);
+
+        // An explicit `is` clause leaves nothing to route
+        let (_, alts) = alternates("ALICE BOB is engineer", None);
+        assert!(alts
+            .iter()
+            .all(|a| !a.2.contains(&AmbiguityKind::ImplicitPredicate)));
+    }
+
+    #[test]
+    fn keyword_case_and_slot_boundary_fixture() {
+        let input = "RESEARCH And DEVELOPMENT is team";
+        let (_, alts) = alternates(input, Some(&vocabulary()));
+        let texts: Vec<&str> = alts.iter().map(|a| a.0.as_str()).collect();
+        assert_eq!(
+            texts,
+            [
+                r#"RESEARCH "And" DEVELOPMENT is team"#,
+                "RESEARCH And is DEVELOPMENT team",
+                r#"RESEARCH "And" is DEVELOPMENT team"#,
+            ]
+        );
+        assert_eq!(alts[0].2, [AmbiguityKind::KeywordCase]);
+        assert_eq!(alts[1].2, [AmbiguityKind::SlotBoundary]);
+        assert_eq!(
+            alts[2].2,
+            [AmbiguityKind::KeywordCase, AmbiguityKind::SlotBoundary]
+        );
+        assert!(alts.windows(2).all(|w| w[0].1 >= w[1].1));
+
+        let parsed = Parser::parse_with_alternates(input, 1).unwrap();
+        assert_eq!(parsed.alternates.len(), 1);
+        assert_eq!(
+            parsed.alternates[0].ambiguities[0],
+            Ambiguity {
+                kind: AmbiguityKind::KeywordCase,
+                term: "And".into(),
+                position: 9,
+            }
+        );
+
+        // `On Sale` is no time, so reading `On` as a term ranks first
+        let (primary, alts) = alternates("Apple On Sale", None);
+        assert_eq!(alts[0].0, r#"Apple "On" Sale"#);
+        assert!(alts[0].1 > primary);
+        // Lowercase keywords are not ambiguous
+        assert!(alternates("ALICE is engineer since 2024-01-01", None)
+            .1
+            .is_empty());
+    }
+
+    #[test]
+    fn forks_are_bounded() {
+        let input = (0..50)
+            .map(|i| format!("T{} Is", i))
+            .collect::<Vec<_>>()
+            .join(" ")
+            + " END";
+        let parsed = Parser::parse_with_alternates(&input, 3).unwrap();
+        assert_eq!(parsed.primary, Parser::parse(&input).unwrap());
+        assert!(parsed.forks <= MAX_FORKS);
+        assert_eq!(parsed.alternates.len(), 3);
+
+        let subjects = (0..50).map(|i| format!("S{}", i)).collect::<Vec<_>>();
+        let input = subjects.join(" ");
+        let parsed = Parser::parse_with_alternates(&input, 3).unwrap();
+        assert_eq!(parsed.primary.subjects.len(), 50);
+        assert_eq!(parsed.alternates[0].text.matches(" is ").count(), 1);
+    }
+
+    #[test]
+    fn json_entry_point() {
+        let json: serde_json::Value =
+            serde_json::from_str(&parse_query_alternates_json("ALICE engineer", 3, None)).unwrap();
+        assert_eq!(
+            json["primary"]["subjects"],
+            serde_json::json!(["ALICE", "engineer"])
+        );
+        assert_eq!(json["alternates"][0]["text"], "ALICE is engineer");
+        assert_eq!(
+            json["alternates"][0]["query"]["predicates"],
+            serde_json::json!(["engineer"])
+        );
+        assert_eq!(
+            json["alternates"][0]["ambiguities"][0]["kind"],
+            "implicit_predicate"
+        );
+
+        let error: serde_json::Value =
+            serde_json::from_str(&parse_query_alternates_json("ALICE is", 3, None)).unwrap();
+        assert!(error["error"].is_string());
+    }
+}
@@ -1,22 +1,50 @@
 //! AST types for parsed AX queries
 
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::fmt;
 
+use crate::attestation::{AttrCondition, AttrOp, AttrValue, AxFilter, OverFilter};
+use crate::duration::{
+    parse_duration, CalendarDuration, CalendarPolicy, DurationError, CALENDAR_POLICY, MS_PER_DAY,
+};
+use crate::temporal::resolve_temporal;
+
+use super::lexer::{is_identifier_char, keyword_kind};
+
 /// A fully parsed AX query
 #[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
 pub struct AxQuery<'a> {
     #[serde(borrow)]
     pub subjects: Vec<&'a str>,
+    /// `subjects` split into `or` alternatives (`ALICE or BOB CAROL` is
+    /// `[[ALICE, BOB], [CAROL]]`). Empty unless the subjects use `or`.
+    #[serde(borrow, default, skip_serializing_if = "Vec::is_empty")]
+    pub subject_groups: Vec<Vec<&'a str>>,
+    /// Subjects after `not`/`except`
+    #[serde(borrow, default)]
+    pub excluded_subjects: Vec<&'a str>,
     #[serde(borrow)]
     pub predicates: Vec<&'a str>,
     #[serde(borrow)]
     pub contexts: Vec<&'a str>,
+    /// `contexts` split into `or` alternatives, like `subject_groups`
+    #[serde(borrow, default, skip_serializing_if = "Vec::is_empty")]
+    pub context_groups: Vec<Vec<&'a str>>,
+    /// Contexts of a negated `of` clause or after `not` within one
+    #[serde(borrow, default)]
+    pub excluded_contexts: Vec<&'a str>,
     #[serde(borrow)]
     pub actors: Vec<&'a str>,
+    /// Actors of a negated `by` clause or after `not` within one
+    #[serde(borrow, default)]
+    pub excluded_actors: Vec<&'a str>,
     pub temporal: Option<TemporalClause<'a>>,
     #[serde(borrow)]
     pub actions: Vec<&'a str>,
+    /// From the `where` clause; named as in [`AxFilter`] so the query JSON
+    /// works as a filter
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub attribute_conditions: Vec<AttrCondition>,
 }
 
 impl<'a> AxQuery<'a> {
@@ -48,6 +76,26 @@ impl<'a> AxQuery<'a> {
         !self.actions.is_empty()
     }
 
+    pub fn has_attribute_conditions(&self) -> bool {
+        !self.attribute_conditions.is_empty()
+    }
+
+    pub fn has_exclusions(&self) -> bool {
+        !self.excluded_subjects.is_empty()
+            || !self.excluded_contexts.is_empty()
+            || !self.excluded_actors.is_empty()
+    }
+
+    /// Subjects as `or` groups, one group per subject when none were written
+    pub fn subject_alternatives(&self) -> Vec<Vec<&'a str>> {
+        alternatives(&self.subjects, &self.subject_groups)
+    }
+
+    /// Contexts as `or` groups, one group per context when none were written
+    pub fn context_alternatives(&self) -> Vec<Vec<&'a str>> {
+        alternatives(&self.contexts, &self.context_groups)
+    }
+
     pub fn is_empty(&self) -> bool {
         self.subjects.is_empty()
             && self.predicates.is_empty()
@@ -55,6 +103,158 @@ impl<'a> AxQuery<'a> {
             && self.actors.is_empty()
             && self.temporal.is_none()
             && self.actions.is_empty()
+            && self.attribute_conditions.is_empty()
+            && !self.has_exclusions()
+    }
+
+    /// Convert into a store filter, resolving temporal expressions against `now_ms`.
+    ///
+    /// Filters match any listed subject and any listed context, so `or`
+    /// groups narrow nothing further and only the flat lists are used.
+    ///
+    /// `on X` becomes the 24h window starting at X; `over N<unit>` becomes an
+    /// `OverFilter` sized under [`CALENDAR_POLICY`]. Fails when the query has
+    /// `not` clauses (filters can't exclude, and dropping them would match
+    /// more than the query), when a temporal expression cannot be resolved,
+    /// or when an `over` duration doesn't parse.
+    pub fn to_filter(&self, now_ms: i64) -> Result<AxFilter, String> {
+        if self.has_exclusions() {
+            return Err("'not' clauses cannot be expressed as a filter".to_string());
+        }
+        let owned = |v: &[&str]| v.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        let resolve = |expr: &str| {
+            resolve_temporal(expr, now_ms)
+                .ok_or_else(|| format!("unable to parse temporal expression: {}", expr))
+        };
+
+        let mut filter = AxFilter {
+            subjects: owned(&self.subjects),
+            predicates: owned(&self.predicates),
+            contexts: owned(&self.contexts),
+            actors: owned(&self.actors),
+            attribute_conditions: self.attribute_conditions.clone(),
+            ..Default::default()
+        };
+
+        match &self.temporal {
+            Some(TemporalClause::Since(expr)) => filter.time_start = Some(resolve(expr)?),
+            Some(TemporalClause::Until(expr)) => filter.time_end = Some(resolve(expr)?),
+            Some(TemporalClause::On(expr)) => {
+                let start = resolve(expr)?;
+                filter.time_start = Some(start);
+                filter.time_end = Some(start + MS_PER_DAY);
+            }
+            Some(TemporalClause::Between(start, end)) => {
+                filter.time_start = Some(resolve(start)?);
+                filter.time_end = Some(resolve(end)?);
+            }
+            Some(TemporalClause::Over(dur)) => {
+                let min_span_ms = dur
+                    .to_millis(CALENDAR_POLICY)
+                    .ok_or_else(|| format!("invalid duration in 'over {}'", dur.raw))?;
+                filter.over = Some(OverFilter::new(min_span_ms));
+            }
+            None => {}
+        }
+
+        Ok(filter)
+    }
+
+    /// Render as AX query text that parses back to an equal query.
+    ///
+    /// Terms that wouldn't lex as a single identifier (spaces, punctuation,
+    /// keywords like `of`) are quoted. A term containing both quote
+    /// characters cannot be represented and is emitted single-quoted as-is.
+    pub fn to_query_string(&self) -> String {
+        fn clause(parts: &mut Vec<String>, keyword: Option<&str>, terms: &[&str]) {
+            grouped_clause(parts, keyword, terms, &[]);
+        }
+        fn grouped_clause(
+            parts: &mut Vec<String>,
+            keyword: Option<&str>,
+            terms: &[&str],
+            groups: &[Vec<&str>],
+        ) {
+            if terms.is_empty() {
+                return;
+            }
+            if let Some(keyword) = keyword {
+                parts.push(keyword.to_string());
+            }
+            for group in alternatives(terms, groups) {
+                let group: Vec<String> = group.iter().map(|t| quote_term(t)).collect();
+                parts.push(group.join(" or "));
+            }
+        }
+
+        let mut parts: Vec<String> = Vec::new();
+        grouped_clause(&mut parts, None, &self.subjects, &self.subject_groups);
+        clause(&mut parts, Some("not"), &self.excluded_subjects);
+        clause(&mut parts, Some("is"), &self.predicates);
+        grouped_clause(&mut parts, Some("of"), &self.contexts, &self.context_groups);
+        clause(&mut parts, Some("not of"), &self.excluded_contexts);
+        clause(&mut parts, Some("by"), &self.actors);
+        clause(&mut parts, Some("not by"), &self.excluded_actors);
+        if let Some(temporal) = &self.temporal {
+            parts.push(match temporal {
+                TemporalClause::Since(expr) => format!("since {}", quote_term(expr)),
+                TemporalClause::Until(expr) => format!("until {}", quote_term(expr)),
+                TemporalClause::On(expr) => format!("on {}", quote_term(expr)),
+                TemporalClause::Between(start, end) => {
+                    format!("between {} and {}", quote_term(start), quote_term(end))
+                }
+                TemporalClause::Over(dur) => format!("over {}", quote_term(dur.raw)),
+            });
+        }
+        for (i, condition) in self.attribute_conditions.iter().enumerate() {
+            parts.push(if i == 0 { "where" } else { "and" }.to_string());
+            parts.push(quote_term(&condition.key));
+            parts.push(condition.op.as_str().to_string());
+            match &condition.value {
+                // Bare `exists` means true
+                AttrValue::Bool(true) if condition.op == AttrOp::Exists => {}
+                AttrValue::Bool(b) => parts.push(b.to_string()),
+                AttrValue::Number(n) => parts.push(n.to_string()),
+                // Always quoted, so "3" and "true" stay strings
+                AttrValue::String(v) => parts.push(quote_literal(v)),
+            }
+        }
+        clause(&mut parts, Some("so"), &self.actions);
+
+        parts.join(" ")
+    }
+}
+
+/// `groups`, or each of `terms` alone when there are no groups
+fn alternatives<'a>(terms: &[&'a str], groups: &[Vec<&'a str>]) -> Vec<Vec<&'a str>> {
+    if groups.is_empty() {
+        terms.iter().map(|t| vec![*t]).collect()
+    } else {
+        groups.to_vec()
+    }
+}
+
+/// Quote `term` unless the lexer would read it back as one identifier.
+fn quote_term(term: &str) -> String {
+    let bare = term
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_alphanumeric() || c == '_' || !c.is_ascii())
+        && term.chars().all(is_identifier_char)
+        && keyword_kind(term).is_none();
+    if bare {
+        term.to_string()
+    } else {
+        quote_literal(term)
+    }
+}
+
+/// Quote `term`, with double quotes when it contains a single quote.
+fn quote_literal(term: &str) -> String {
+    if term.contains('\'') && !term.contains('"') {
+        format!("\"{}\"", term)
+    } else {
+        format!("'{}'", term)
     }
 }
 
@@ -80,39 +280,41 @@ impl fmt::Display for TemporalClause<'_> {
     }
 }
 
-/// Duration expression for "over" comparisons
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+/// Duration expression for "over" comparisons.
+///
+/// Keeps the text as written (`raw`) alongside its parse, so serde
+/// round-trips the original string and display never rewrites user input.
+#[derive(Debug, Clone, PartialEq)]
 pub struct DurationExpr<'a> {
     pub raw: &'a str,
-    pub value: Option<f64>,
-    pub unit: Option<DurationUnit>,
+    parsed: Result<CalendarDuration, DurationError>,
 }
 
 impl<'a> DurationExpr<'a> {
     pub fn parse(raw: &'a str) -> Self {
-        let trimmed = raw.trim();
-        if trimmed.is_empty() {
-            return Self {
-                raw,
-                value: None,
-                unit: None,
-            };
+        Self {
+            raw,
+            parsed: parse_duration(raw),
         }
+    }
 
-        let mut num_end = 0;
-        for (i, c) in trimmed.char_indices() {
-            if c.is_ascii_digit() || c == '.' {
-                num_end = i + c.len_utf8();
-            } else {
-                break;
-            }
-        }
+    /// The parsed duration, or why `raw` isn't one.
+    pub fn duration(&self) -> Result<&CalendarDuration, &DurationError> {
+        self.parsed.as_ref()
+    }
 
-        let value = trimmed[..num_end].parse::<f64>().ok();
-        let unit_str = &trimmed[num_end..];
-        let unit = DurationUnit::parse(unit_str);
+    /// Duration in milliseconds under `policy`. Returns None when `raw` is
+    /// not a valid duration.
+    pub fn to_millis(&self, policy: CalendarPolicy) -> Option<i64> {
+        self.parsed.as_ref().ok().map(|d| d.to_millis(policy))
+    }
 
-        Self { raw, value, unit }
+    /// Display form with whole units carried ("18m" → "1y6m").
+    pub fn normalized(&self) -> Option<String> {
+        self.parsed
+            .as_ref()
+            .ok()
+            .map(|d| d.normalized().to_string())
     }
 }
 
@@ -122,34 +324,14 @@ impl fmt::Display for DurationExpr<'_> {
     }
 }
 
-/// Duration unit types
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
-pub enum DurationUnit {
-    Years,
-    Months,
-    Weeks,
-    Days,
-}
-
-impl DurationUnit {
-    pub fn parse(s: &str) -> Option<Self> {
-        match s.to_ascii_lowercase().as_str() {
-            "y" | "yr" | "yrs" | "year" | "years" => Some(DurationUnit::Years),
-            "m" | "mo" | "mos" | "month" | "months" => Some(DurationUnit::Months),
-            "w" | "wk" | "wks" | "week" | "weeks" => Some(DurationUnit::Weeks),
-            "d" | "day" | "days" => Some(DurationUnit::Days),
-            _ => None,
-        }
+impl Serialize for DurationExpr<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.raw)
     }
 }
 
-impl fmt::Display for DurationUnit {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self {
-            DurationUnit::Years => write!(f, "y"),
-            DurationUnit::Months => write!(f, "m"),
-            DurationUnit::Weeks => write!(f, "w"),
-            DurationUnit::Days => write!(f, "d"),
-        }
+impl<'de: 'a, 'a> Deserialize<'de> for DurationExpr<'a> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        <&'de str>::deserialize(deserializer).map(DurationExpr::parse)
     }
 }
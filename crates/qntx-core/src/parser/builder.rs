@@ -0,0 +1,758 @@
+//! Structured construction of AX queries
+//!
+//! [`AxQueryBuilder`] edits a query slot by slot instead of through its
+//! text, so a UI never has to splice strings and re-parse. Every edit is
+//! validated when it's made; [`AxQueryBuilder::preview`] renders the query
+//! text (quoting terms that collide with keywords) and lists the values that
+//! needed quoting.
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::attestation::{AttrCondition, AttrValue};
+use crate::duration::parse_duration;
+
+use super::ast::{AxQuery, DurationExpr, TemporalClause};
+use super::lexer::keyword_kind;
+
+/// Term slot of a query
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QuerySlot {
+    Subjects,
+    Predicates,
+    Contexts,
+    Actors,
+    Actions,
+}
+
+impl QuerySlot {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            QuerySlot::Subjects => "subjects",
+            QuerySlot::Predicates => "predicates",
+            QuerySlot::Contexts => "contexts",
+            QuerySlot::Actors => "actors",
+            QuerySlot::Actions => "actions",
+        }
+    }
+}
+
+/// Owned [`TemporalClause`], serialized the same way
+/// (`{"Since":"2024-01-01"}`, `{"Between":["a","b"]}`, `{"Over":"5y"}`).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum TemporalSpec {
+    Since(String),
+    Until(String),
+    On(String),
+    Between(String, String),
+    Over(String),
+}
+
+impl TemporalSpec {
+    /// Borrow as the parser's clause type.
+    pub fn as_clause(&self) -> TemporalClause<'_> {
+        match self {
+            TemporalSpec::Since(expr) => TemporalClause::Since(expr),
+            TemporalSpec::Until(expr) => TemporalClause::Until(expr),
+            TemporalSpec::On(expr) => TemporalClause::On(expr),
+            TemporalSpec::Between(start, end) => TemporalClause::Between(start, end),
+            TemporalSpec::Over(raw) => TemporalClause::Over(DurationExpr::parse(raw)),
+        }
+    }
+
+    fn exprs(&self) -> Vec<&str> {
+        match self {
+            TemporalSpec::Since(expr)
+            | TemporalSpec::Until(expr)
+            | TemporalSpec::On(expr)
+            | TemporalSpec::Over(expr) => vec![expr],
+            TemporalSpec::Between(start, end) => vec![start, end],
+        }
+    }
+}
+
+impl From<&TemporalClause<'_>> for TemporalSpec {
+    fn from(clause: &TemporalClause<'_>) -> Self {
+        match clause {
+            TemporalClause::Since(expr) => TemporalSpec::Since(expr.to_string()),
+            TemporalClause::Until(expr) => TemporalSpec::Until(expr.to_string()),
+            TemporalClause::On(expr) => TemporalSpec::On(expr.to_string()),
+            TemporalClause::Between(start, end) => {
+                TemporalSpec::Between(start.to_string(), end.to_string())
+            }
+            TemporalClause::Over(dur) => TemporalSpec::Over(dur.raw.to_string()),
+        }
+    }
+}
+
+impl From<TemporalClause<'_>> for TemporalSpec {
+    fn from(clause: TemporalClause<'_>) -> Self {
+        TemporalSpec::from(&clause)
+    }
+}
+
+/// Why an edit was rejected. A rejected edit leaves the builder unchanged.
+#[derive(Debug, Error, Clone, PartialEq)]
+pub enum BuilderError {
+    #[error("empty value in {slot}")]
+    EmptyValue { slot: String },
+
+    /// The query language has no escapes, so a term can't hold both quotes
+    #[error("{value:?} in {slot} contains both quote characters and cannot be written as AX")]
+    Unquotable { slot: String, value: String },
+
+    #[error("invalid duration in 'over {0}'")]
+    InvalidDuration(String),
+
+    #[error("attribute condition on {key:?} has a non-finite number")]
+    NonFiniteNumber { key: String },
+
+    #[error("no attribute condition at index {index} ({len} present)")]
+    ConditionIndex { index: usize, len: usize },
+
+    #[error("empty query")]
+    EmptyQuery,
+}
+
+/// A value that is valid but reads as a keyword, so the query text quotes it
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BuilderWarning {
+    /// `subjects` … `actions`, `temporal` or `attribute_conditions`
+    pub slot: &'static str,
+    pub value: String,
+    /// Always `keyword` for now
+    pub code: &'static str,
+    pub message: String,
+}
+
+/// What a UI shows for the current state: the query, its text, and warnings
+#[derive(Debug, Clone, Serialize)]
+pub struct BuilderPreview<'a> {
+    pub query: AxQuery<'a>,
+    pub query_string: String,
+    pub warnings: Vec<BuilderWarning>,
+}
+
+/// One edit, as sent over the WASM boundary, tagged by `op`:
+///
+/// ```json
+/// {"op":"add","slot":"subjects","value":"ALICE"}
+/// {"op":"remove","slot":"subjects","value":"ALICE"}
+/// {"op":"set","slot":"predicates","values":["author_of"]}
+/// {"op":"set_temporal","temporal":{"Since":"2024-01-01"}}
+/// {"op":"add_condition","condition":{"key":"severity","op":"gte","value":3}}
+/// {"op":"remove_condition","index":0}
+/// {"op":"clear_conditions"}
+/// {"op":"clear"}
+/// ```
+///
+/// `set_temporal` with a `null` temporal clears it.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum BuilderOp {
+    Add {
+        slot: QuerySlot,
+        value: String,
+    },
+    Remove {
+        slot: QuerySlot,
+        value: String,
+    },
+    Set {
+        slot: QuerySlot,
+        values: Vec<String>,
+    },
+    SetTemporal {
+        temporal: Option<TemporalSpec>,
+    },
+    AddCondition {
+        condition: AttrCondition,
+    },
+    RemoveCondition {
+        index: usize,
+    },
+    ClearConditions,
+    Clear,
+}
+
+/// Builds an [`AxQuery`] one validated edit at a time.
+///
+/// Values are kept in insertion order and added at most once per slot.
+///
+/// ```rust
+/// use qntx_core::parser::{AxQueryBuilder, Parser};
+///
+/// let mut builder = AxQueryBuilder::new();
+/// builder.add_subject("ALICE")?.add_predicate("is")?;
+/// let text = builder.to_query_string();
+/// assert_eq!(text, "ALICE is 'is'");
+/// assert_eq!(Parser::parse(&text).unwrap(), builder.build()?);
+/// # Ok::<(), qntx_core::parser::BuilderError>(())
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AxQueryBuilder {
+    subjects: Vec<String>,
+    predicates: Vec<String>,
+    contexts: Vec<String>,
+    actors: Vec<String>,
+    actions: Vec<String>,
+    temporal: Option<TemporalSpec>,
+    conditions: Vec<AttrCondition>,
+}
+
+impl AxQueryBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start from a parsed query, e.g. to edit what the user typed.
+    pub fn from_query(query: &AxQuery<'_>) -> Self {
+        let owned = |v: &[&str]| v.iter().map(|s| s.to_string()).collect();
+        Self {
+            subjects: owned(&query.subjects),
+            predicates: owned(&query.predicates),
+            contexts: owned(&query.contexts),
+            actors: owned(&query.actors),
+            actions: owned(&query.actions),
+            temporal: query.temporal.as_ref().map(TemporalSpec::from),
+            conditions: query.attribute_conditions.clone(),
+        }
+    }
+
+    /// Values currently in `slot`
+    pub fn values(&self, slot: QuerySlot) -> &[String] {
+        match slot {
+            QuerySlot::Subjects => &self.subjects,
+            QuerySlot::Predicates => &self.predicates,
+            QuerySlot::Contexts => &self.contexts,
+            QuerySlot::Actors => &self.actors,
+            QuerySlot::Actions => &self.actions,
+        }
+    }
+
+    fn values_mut(&mut self, slot: QuerySlot) -> &mut Vec<String> {
+        match slot {
+            QuerySlot::Subjects => &mut self.subjects,
+            QuerySlot::Predicates => &mut self.predicates,
+            QuerySlot::Contexts => &mut self.contexts,
+            QuerySlot::Actors => &mut self.actors,
+            QuerySlot::Actions => &mut self.actions,
+        }
+    }
+
+    /// Append `value` to `slot`; a value already there is left in place.
+    pub fn add(&mut self, slot: QuerySlot, value: &str) -> Result<&mut Self, BuilderError> {
+        check_term(slot.as_str(), value)?;
+        let values = self.values_mut(slot);
+        if !values.iter().any(|v| v == value) {
+            values.push(value.to_string());
+        }
+        Ok(self)
+    }
+
+    /// Replace the values of `slot`. Nothing changes if any value is invalid.
+    pub fn set<I, S>(&mut self, slot: QuerySlot, values: I) -> Result<&mut Self, BuilderError>
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let mut next: Vec<String> = Vec::new();
+        for value in values {
+            let value = value.into();
+            check_term(slot.as_str(), &value)?;
+            if !next.contains(&value) {
+                next.push(value);
+            }
+        }
+        *self.values_mut(slot) = next;
+        Ok(self)
+    }
+
+    /// Remove `value` from `slot`. Returns whether it was there.
+    pub fn remove(&mut self, slot: QuerySlot, value: &str) -> bool {
+        let values = self.values_mut(slot);
+        let before = values.len();
+        values.retain(|v| v != value);
+        values.len() != before
+    }
+
+    pub fn add_subject(&mut self, value: &str) -> Result<&mut Self, BuilderError> {
+        self.add(QuerySlot::Subjects, value)
+    }
+
+    pub fn add_predicate(&mut self, value: &str) -> Result<&mut Self, BuilderError> {
+        self.add(QuerySlot::Predicates, value)
+    }
+
+    pub fn add_context(&mut self, value: &str) -> Result<&mut Self, BuilderError> {
+        self.add(QuerySlot::Contexts, value)
+    }
+
+    pub fn add_actor(&mut self, value: &str) -> Result<&mut Self, BuilderError> {
+        self.add(QuerySlot::Actors, value)
+    }
+
+    pub fn add_action(&mut self, value: &str) -> Result<&mut Self, BuilderError> {
+        self.add(QuerySlot::Actions, value)
+    }
+
+    pub fn set_subjects<I, S>(&mut self, values: I) -> Result<&mut Self, BuilderError>
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.set(QuerySlot::Subjects, values)
+    }
+
+    pub fn set_predicates<I, S>(&mut self, values: I) -> Result<&mut Self, BuilderError>
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.set(QuerySlot::Predicates, values)
+    }
+
+    pub fn set_contexts<I, S>(&mut self, values: I) -> Result<&mut Self, BuilderError>
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.set(QuerySlot::Contexts, values)
+    }
+
+    pub fn set_actors<I, S>(&mut self, values: I) -> Result<&mut Self, BuilderError>
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.set(QuerySlot::Actors, values)
+    }
+
+    pub fn set_actions<I, S>(&mut self, values: I) -> Result<&mut Self, BuilderError>
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.set(QuerySlot::Actions, values)
+    }
+
+    /// Set the temporal clause, from a parsed [`TemporalClause`] or a
+    /// [`TemporalSpec`]. An `over` duration must parse.
+    pub fn set_temporal(
+        &mut self,
+        temporal: impl Into<TemporalSpec>,
+    ) -> Result<&mut Self, BuilderError> {
+        let temporal = temporal.into();
+        for expr in temporal.exprs() {
+            check_term("temporal", expr)?;
+        }
+        if let TemporalSpec::Over(raw) = &temporal {
+            if parse_duration(raw).is_err() {
+                return Err(BuilderError::InvalidDuration(raw.clone()));
+            }
+        }
+        self.temporal = Some(temporal);
+        Ok(self)
+    }
+
+    pub fn clear_temporal(&mut self) -> &mut Self {
+        self.temporal = None;
+        self
+    }
+
+    /// Append a `where` condition. Its key must be non-empty, and a string
+    /// value representable in the query text.
+    pub fn add_condition(&mut self, condition: AttrCondition) -> Result<&mut Self, BuilderError> {
+        check_term("attribute_conditions", &condition.key)?;
+        match &condition.value {
+            AttrValue::String(v) if v.contains('\'') && v.contains('"') => {
+                return Err(BuilderError::Unquotable {
+                    slot: "attribute_conditions".into(),
+                    value: v.clone(),
+                });
+            }
+            AttrValue::Number(n) if !n.is_finite() => {
+                return Err(BuilderError::NonFiniteNumber {
+                    key: condition.key.clone(),
+                });
+            }
+            _ => {}
+        }
+        self.conditions.push(condition);
+        Ok(self)
+    }
+
+    /// Remove and return the condition at `index`.
+    pub fn remove_condition(&mut self, index: usize) -> Result<AttrCondition, BuilderError> {
+        if index >= self.conditions.len() {
+            return Err(BuilderError::ConditionIndex {
+                index,
+                len: self.conditions.len(),
+            });
+        }
+        Ok(self.conditions.remove(index))
+    }
+
+    pub fn clear_conditions(&mut self) -> &mut Self {
+        self.conditions.clear();
+        self
+    }
+
+    /// Back to an empty query.
+    pub fn clear(&mut self) -> &mut Self {
+        *self = Self::default();
+        self
+    }
+
+    /// Apply one edit from its [`BuilderOp`] form.
+    pub fn apply(&mut self, op: BuilderOp) -> Result<(), BuilderError> {
+        match op {
+            BuilderOp::Add { slot, value } => {
+                self.add(slot, &value)?;
+            }
+            BuilderOp::Remove { slot, value } => {
+                self.remove(slot, &value);
+            }
+            BuilderOp::Set { slot, values } => {
+                self.set(slot, values)?;
+            }
+            BuilderOp::SetTemporal { temporal } => match temporal {
+                Some(temporal) => {
+                    self.set_temporal(temporal)?;
+                }
+                None => {
+                    self.clear_temporal();
+                }
+            },
+            BuilderOp::AddCondition { condition } => {
+                self.add_condition(condition)?;
+            }
+            BuilderOp::RemoveCondition { index } => {
+                self.remove_condition(index)?;
+            }
+            BuilderOp::ClearConditions => {
+                self.clear_conditions();
+            }
+            BuilderOp::Clear => {
+                self.clear();
+            }
+        }
+        Ok(())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.query().is_empty()
+    }
+
+    /// The query built so far; fails only when it is empty, which the parser
+    /// would reject too.
+    pub fn build(&self) -> Result<AxQuery<'_>, BuilderError> {
+        let query = self.query();
+        if query.is_empty() {
+            return Err(BuilderError::EmptyQuery);
+        }
+        Ok(query)
+    }
+
+    /// Query text that parses back to [`build`](Self::build)'s result.
+    pub fn to_query_string(&self) -> String {
+        self.query().to_query_string()
+    }
+
+    /// Values that collide with AX keywords and are quoted in the text.
+    pub fn warnings(&self) -> Vec<BuilderWarning> {
+        let slots = [
+            QuerySlot::Subjects,
+            QuerySlot::Predicates,
+            QuerySlot::Contexts,
+            QuerySlot::Actors,
+            QuerySlot::Actions,
+        ];
+        let terms = slots
+            .iter()
+            .flat_map(|slot| {
+                self.values(*slot)
+                    .iter()
+                    .map(move |v| (slot.as_str(), v.as_str()))
+            })
+            .chain(
+                self.temporal
+                    .iter()
+                    .flat_map(|t| t.exprs())
+                    .map(|v| ("temporal", v)),
+            )
+            .chain(
+                self.conditions
+                    .iter()
+                    .map(|c| ("attribute_conditions", c.key.as_str())),
+            );
+        terms
+            .filter(|(_, value)| keyword_kind(value).is_some())
+            .map(|(slot, value)| BuilderWarning {
+                slot,
+                value: value.to_string(),
+                code: "keyword",
+                message: format!("'{}' is an AX keyword; the query text quotes it", value),
+            })
+            .collect()
+    }
+
+    /// Current state for display. Unlike [`build`](Self::build) this works
+    /// on an empty builder (empty query, empty text).
+    pub fn preview(&self) -> BuilderPreview<'_> {
+        let query = self.query();
+        BuilderPreview {
+            query_string: query.to_query_string(),
+            query,
+            warnings: self.warnings(),
+        }
+    }
+
+    /// [`preview`](Self::preview) as JSON:
+    /// `{"query":{...},"query_string":"...","warnings":[{"slot","value","code","message"}]}`
+    pub fn preview_json(&self) -> String {
+        serde_json::to_string(&self.preview()).unwrap_or_else(|e| {
+            serde_json::json!({ "error": format!("serialization failed: {}", e) }).to_string()
+        })
+    }
+
+    /// Apply a [`BuilderOp`] given as JSON. Returns the new
+    /// [`preview_json`](Self::preview_json), or `{"error":"..."}` with the
+    /// builder unchanged.
+    pub fn apply_json(&mut self, op_json: &str) -> String {
+        let op: BuilderOp = match serde_json::from_str(op_json) {
+            Ok(op) => op,
+            Err(e) => {
+                return serde_json::json!({ "error": format!("invalid builder op: {}", e) })
+                    .to_string()
+            }
+        };
+        match self.apply(op) {
+            Ok(()) => self.preview_json(),
+            Err(e) => serde_json::json!({ "error": e.to_string() }).to_string(),
+        }
+    }
+
+    fn query(&self) -> AxQuery<'_> {
+        fn borrowed(v: &[String]) -> Vec<&str> {
+            v.iter().map(String::as_str).collect()
+        }
+        AxQuery {
+            subjects: borrowed(&self.subjects),
+            predicates: borrowed(&self.predicates),
+            contexts: borrowed(&self.contexts),
+            actors: borrowed(&self.actors),
+            temporal: self.temporal.as_ref().map(TemporalSpec::as_clause),
+            actions: borrowed(&self.actions),
+            attribute_conditions: self.conditions.clone(),
+            ..Default::default()
+        }
+    }
+}
+
+/// Reject values the query text can't carry: blank, or holding both quotes.
+fn check_term(slot: &str, value: &str) -> Result<(), BuilderError> {
+    if value.trim().is_empty() {
+        return Err(BuilderError::EmptyValue { slot: slot.into() });
+    }
+    if value.contains('\'') && value.contains('"') {
+        return Err(BuilderError::Unquotable {
+            slot: slot.into(),
+            value: value.into(),
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::attestation::AttrOp;
+    use crate::parser::Parser;
+
+    fn round_trips(builder: &AxQueryBuilder) {
+        let text = builder.to_query_string();
+        let parsed = Parser::parse(&text).unwrap_or_else(|e| panic!("{}: {}", text, e));
+        assert_eq!(parsed, builder.build().unwrap(), "text: {}", text);
+    }
+
+    #[test]
+    fn test_keyword_values_are_quoted_and_flagged() {
+        let mut builder = AxQueryBuilder::new();
+        builder
+            .add_subject("since")
+            .unwrap()
+            .add_subject("ALICE")
+            .unwrap()
+            .add_predicate("is")
+            .unwrap()
+            .add_context("of")
+            .unwrap()
+            .add_actor("human:bob")
+            .unwrap()
+            .set_temporal(TemporalSpec::Since("2024-01-01".into()))
+            .unwrap();
+
+        assert_eq!(
+            builder.to_query_string(),
+            "'since' ALICE is 'is' of 'of' by human:bob since 2024-01-01"
+        );
+        round_trips(&builder);
+
+        let flagged: Vec<(&str, String)> = builder
+            .warnings()
+            .into_iter()
+            .map(|w| (w.slot, w.value))
+            .collect();
+        assert_eq!(
+            flagged,
+            vec![
+                ("subjects", "since".to_string()),
+                ("predicates", "is".to_string()),
+                ("contexts", "of".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_every_clause_round_trips() {
+        let mut builder = AxQueryBuilder::new();
+        builder
+            .set_subjects(["ALICE", "bob smith", "it's"])
+            .unwrap()
+            .set_predicates(["author_of", "And"])
+            .unwrap()
+            .set_contexts(["GitHub"])
+            .unwrap()
+            .set_actions(["notify", "where"])
+            .unwrap()
+            .set_temporal(TemporalSpec::Between(
+                "2024-01-01".into(),
+                "last week".into(),
+            ))
+            .unwrap()
+            .add_condition(AttrCondition {
+                key: "severity".into(),
+                op: AttrOp::Gte,
+                value: AttrValue::Number(3.0),
+            })
+            .unwrap()
+            .add_condition(AttrCondition {
+                key: "status".into(),
+                op: AttrOp::Eq,
+                value: AttrValue::String("true".into()),
+            })
+            .unwrap();
+        round_trips(&builder);
+
+        builder
+            .set_temporal(TemporalSpec::Over("1y6m".into()))
+            .unwrap();
+        round_trips(&builder);
+    }
+
+    #[test]
+    fn test_invalid_edits_leave_builder_unchanged() {
+        let mut builder = AxQueryBuilder::new();
+        builder.add_subject("ALICE").unwrap();
+        let before = builder.clone();
+
+        assert_eq!(
+            builder.add_predicate("  ").unwrap_err(),
+            BuilderError::EmptyValue {
+                slot: "predicates".into()
+            }
+        );
+        assert!(matches!(
+            builder.set_subjects(["BOB", r#"it's "x""#]),
+            Err(BuilderError::Unquotable { .. })
+        ));
+        assert_eq!(
+            builder
+                .set_temporal(TemporalSpec::Over("soon".into()))
+                .unwrap_err(),
+            BuilderError::InvalidDuration("soon".into())
+        );
+        assert!(matches!(
+            builder.add_condition(AttrCondition {
+                key: "score".into(),
+                op: AttrOp::Gt,
+                value: AttrValue::Number(f64::NAN),
+            }),
+            Err(BuilderError::NonFiniteNumber { .. })
+        ));
+        assert_eq!(
+            builder.remove_condition(0).unwrap_err(),
+            BuilderError::ConditionIndex { index: 0, len: 0 }
+        );
+        assert_eq!(builder, before);
+    }
+
+    #[test]
+    fn test_duplicates_and_removal() {
+        let mut builder = AxQueryBuilder::new();
+        builder
+            .add_subject("ALICE")
+            .unwrap()
+            .add_subject("ALICE")
+            .unwrap();
+        assert_eq!(builder.values(QuerySlot::Subjects), ["ALICE"]);
+
+        assert!(builder.remove(QuerySlot::Subjects, "ALICE"));
+        assert!(!builder.remove(QuerySlot::Subjects, "ALICE"));
+        assert!(builder.is_empty());
+        assert_eq!(builder.build().unwrap_err(), BuilderError::EmptyQuery);
+        assert_eq!(builder.preview().query_string, "");
+    }
+
+    #[test]
+    fn test_from_parsed_query() {
+        let query = Parser::parse("ALICE is author_of of GitHub over 5y so notify").unwrap();
+        let builder = AxQueryBuilder::from_query(&query);
+        assert_eq!(builder.build().unwrap(), query);
+    }
+
+    #[test]
+    fn test_apply_json_ops() {
+        let mut builder = AxQueryBuilder::new();
+        for op in [
+            r#"{"op":"add","slot":"subjects","value":"ALICE"}"#,
+            r#"{"op":"set","slot":"predicates","values":["is","knows"]}"#,
+            r#"{"op":"set_temporal","temporal":{"Between":["2024-01-01","2024-06-30"]}}"#,
+            r#"{"op":"add_condition","condition":{"key":"level","op":"exists","value":true}}"#,
+        ] {
+            let preview: serde_json::Value = serde_json::from_str(&builder.apply_json(op)).unwrap();
+            assert!(preview.get("error").is_none(), "{}: {}", op, preview);
+        }
+
+        let preview: serde_json::Value = serde_json::from_str(&builder.preview_json()).unwrap();
+        assert_eq!(
+            preview["query_string"],
+            "ALICE is 'is' knows between 2024-01-01 and 2024-06-30 where level exists"
+        );
+        assert_eq!(
+            preview["query"]["predicates"],
+            serde_json::json!(["is", "knows"])
+        );
+        assert_eq!(preview["warnings"][0]["code"], "keyword");
+        round_trips(&builder);
+
+        let error: serde_json::Value =
+            serde_json::from_str(&builder.apply_json(r#"{"op":"remove_condition","index":4}"#))
+                .unwrap();
+        assert!(error["error"].as_str().unwrap().contains("index 4"));
+        let error: serde_json::Value =
+            serde_json::from_str(&builder.apply_json(r#"{"op":"rename"}"#)).unwrap();
+        assert!(error["error"]
+            .as_str()
+            .unwrap()
+            .starts_with("invalid builder op"));
+
+        builder.apply_json(r#"{"op":"set_temporal","temporal":null}"#);
+        builder.apply_json(r#"{"op":"clear_conditions"}"#);
+        assert_eq!(builder.to_query_string(), "ALICE is 'is' knows");
+        builder.apply_json(r#"{"op":"clear"}"#);
+        assert!(builder.is_empty());
+    }
+}
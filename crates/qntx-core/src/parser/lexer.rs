@@ -3,7 +3,7 @@
 use super::token::{Token, TokenKind};
 
 /// Keywords lookup (case-insensitive)
-fn keyword_kind(s: &str) -> Option<TokenKind> {
+pub(crate) fn keyword_kind(s: &str) -> Option<TokenKind> {
     match s.to_ascii_lowercase().as_str() {
         "is" => Some(TokenKind::Is),
         "are" => Some(TokenKind::Are),
@@ -17,12 +17,28 @@ fn keyword_kind(s: &str) -> Option<TokenKind> {
         "between" => Some(TokenKind::Between),
         "over" => Some(TokenKind::Over),
         "and" => Some(TokenKind::And),
+        "or" => Some(TokenKind::Or),
+        "not" => Some(TokenKind::Not),
+        "except" => Some(TokenKind::Except),
         "so" => Some(TokenKind::So),
         "therefore" => Some(TokenKind::Therefore),
+        "where" => Some(TokenKind::Where),
         _ => None,
     }
 }
 
+/// Characters an unquoted identifier may contain. Non-ASCII characters are
+/// allowed (Unicode identifiers) unless they are whitespace.
+pub(crate) fn is_identifier_char(c: char) -> bool {
+    c.is_alphanumeric()
+        || c == '_'
+        || c == '-'
+        || c == '.'
+        || c == ':'
+        || c == '@'
+        || (!c.is_ascii() && !c.is_whitespace())
+}
+
 /// Zero-copy lexer for AX queries
 pub struct Lexer<'a> {
     input: &'a str,
@@ -87,10 +103,7 @@ impl<'a> Lexer<'a> {
         let start = self.position;
 
         while let Some(c) = self.peek_char() {
-            if c.is_alphanumeric() || c == '_' || c == '-' || c == '.' || c == ':' || c == '@' {
-                self.advance(c.len_utf8());
-            } else if !c.is_ascii() && !c.is_whitespace() {
-                // Allow non-ASCII characters (Unicode identifiers)
+            if is_identifier_char(c) {
                 self.advance(c.len_utf8());
             } else {
                 break;
@@ -107,6 +120,19 @@ impl<'a> Lexer<'a> {
         Token::new(TokenKind::Identifier, text, start)
     }
 
+    fn read_operator(&mut self) -> Token<'a> {
+        let start = self.position;
+        let c = self.peek_char().unwrap();
+        self.advance(c.len_utf8());
+        if self.peek_char() == Some('=') {
+            self.advance(1);
+        } else if c == '!' {
+            // A lone '!' is not an operator
+            return Token::new(TokenKind::Unknown, &self.input[start..self.position], start);
+        }
+        Token::new(TokenKind::Compare, &self.input[start..self.position], start)
+    }
+
     fn next_token(&mut self) -> Token<'a> {
         self.skip_whitespace();
 
@@ -135,6 +161,11 @@ impl<'a> Lexer<'a> {
                 self.advance(c.len_utf8());
                 Token::new(TokenKind::Pipe, &self.input[start..self.position], start)
             }
+            '=' | '!' | '<' | '>' => self.read_operator(),
+            // Negative number literal
+            '-' if self.remaining()[1..].starts_with(|d: char| d.is_ascii_digit()) => {
+                self.read_identifier()
+            }
             _ if c.is_alphanumeric() || c == '_' || !c.is_ascii() => self.read_identifier(),
             _ => {
                 // Unknown character - skip it
@@ -187,8 +218,9 @@ mod tests {
 
     #[test]
     fn test_keywords() {
-        let tokens =
-            collect_tokens("is are of from by via since until on between over and so therefore");
+        let tokens = collect_tokens(
+            "is are of from by via since until on between over and not except so therefore where",
+        );
         let kinds: Vec<_> = tokens.iter().map(|t| t.kind).collect();
         assert_eq!(
             kinds,
@@ -205,13 +237,30 @@ mod tests {
                 TokenKind::Between,
                 TokenKind::Over,
                 TokenKind::And,
+                TokenKind::Not,
+                TokenKind::Except,
                 TokenKind::So,
                 TokenKind::Therefore,
+                TokenKind::Where,
                 TokenKind::Eof,
             ]
         );
     }
 
+    #[test]
+    fn test_comparison_operators() {
+        let tokens = collect_tokens("a == 1 b != -2.5 c>=3 d<=4 e > f < g = h !i");
+        let operators: Vec<_> = tokens
+            .iter()
+            .filter(|t| t.kind == TokenKind::Compare)
+            .map(|t| t.text)
+            .collect();
+        assert_eq!(operators, vec!["==", "!=", ">=", "<=", ">", "<", "="]);
+        assert_eq!(tokens[5].kind, TokenKind::Identifier);
+        assert_eq!(tokens[5].text, "-2.5");
+        assert_eq!(tokens[tokens.len() - 3].kind, TokenKind::Unknown);
+    }
+
     #[test]
     fn test_quoted_string() {
         let tokens = collect_tokens("'hello world'");
@@ -5,16 +5,40 @@
 //! # Grammar
 //!
 //! ```text
-//! query ::= [subjects] [predicate_clause] [context_clause] [actor_clause] [temporal_clause] [action_clause]
+//! query ::= [subjects] [predicate_clause] [context_clause] [actor_clause] [temporal_clause] [where_clause] [action_clause]
 //!
-//! subjects         ::= IDENTIFIER+
+//! subjects         ::= alternatives+ (negation alternatives+)*
 //! predicate_clause ::= ("is" | "are") predicates
-//! context_clause   ::= ("of" | "from") contexts
-//! actor_clause     ::= ("by" | "via") actors
+//! context_clause   ::= [negation] ("of" | "from") alternatives+ (negation alternatives+)*
+//! actor_clause     ::= [negation] ("by" | "via") actors (negation actors)*
 //! temporal_clause  ::= temporal_keyword temporal_expr
+//! where_clause     ::= "where" condition ("and" condition)*
 //! action_clause    ::= ("so" | "therefore") actions
+//! alternatives     ::= IDENTIFIER ("or" IDENTIFIER)*
+//! negation         ::= "not" | "except"
+//!
+//! condition ::= key ("==" | "=" | "!=" | ">" | ">=" | "<" | "<=" | "contains") literal
+//!             | key "exists" ["true" | "false"]
+//! literal   ::= QUOTED_STRING | NUMBER | "true" | "false" | IDENTIFIER
 //! ```
 //!
+//! `ALICE or BOB CAROL` lists three subjects; `or` also records that ALICE and
+//! BOB are alternatives, in [`AxQuery::subject_groups`] (and likewise
+//! [`AxQuery::context_groups`]). `or` is a keyword, so a term spelled `or`
+//! must be quoted.
+//!
+//! `not` (or `except`) excludes what follows it: before `of`/`by` the whole
+//! clause (`ALICE is author of GitHub not by system:ci`), otherwise the
+//! terms up to the next keyword (`except BOB is member of TEAM`, `of ACME
+//! not LEGACY`). Excluded terms go to [`AxQuery::excluded_subjects`],
+//! [`AxQuery::excluded_contexts`] and [`AxQuery::excluded_actors`]. A
+//! negated clause can't start with `is`, `since` or the other keywords. Like
+//! `or`, a term spelled `not` must be quoted.
+//!
+//! A quoted literal is always a string; an unquoted one is a number or bool
+//! when it reads as one, else a string. The literal's type decides how an
+//! attribute compares (see [`AttrCondition`](crate::attestation::AttrCondition)).
+//!
 //! # Example
 //!
 //! ```rust
@@ -26,14 +50,26 @@
 //! assert_eq!(query.contexts, vec!["GitHub"]);
 //! ```
 
+mod alternates;
 mod ast;
+mod builder;
 mod lexer;
 mod token;
 
-pub use ast::{AxQuery, DurationExpr, DurationUnit, TemporalClause};
+pub use crate::duration::DurationUnit;
+pub use alternates::{
+    parse_query_alternates_json, Ambiguity, AmbiguityKind, ParseAlternates, QueryAlternate,
+    MAX_AMBIGUITIES, MAX_FORKS,
+};
+pub use ast::{AxQuery, DurationExpr, TemporalClause};
+pub use builder::{
+    AxQueryBuilder, BuilderError, BuilderOp, BuilderPreview, BuilderWarning, QuerySlot,
+    TemporalSpec,
+};
 pub use lexer::Lexer;
 pub use token::{Token, TokenKind};
 
+use crate::attestation::{AttrCondition, AttrOp, AttrValue};
 use thiserror::Error;
 
 /// Parser errors
@@ -68,6 +104,40 @@ pub enum ParseError {
     PipeNotSupported,
 }
 
+/// Typed value of an unquoted literal: a bool, a number, or else a string.
+fn literal(text: &str) -> AttrValue {
+    if text.eq_ignore_ascii_case("true") {
+        return AttrValue::Bool(true);
+    }
+    if text.eq_ignore_ascii_case("false") {
+        return AttrValue::Bool(false);
+    }
+    // Only digit-led text, so words like "inf" and "nan" stay strings
+    let numeric = text
+        .trim_start_matches('-')
+        .starts_with(|c: char| c.is_ascii_digit());
+    match text.parse::<f64>() {
+        Ok(n) if numeric && n.is_finite() => AttrValue::Number(n),
+        _ => AttrValue::String(text.to_string()),
+    }
+}
+
+/// A token as named in error messages
+fn describe(token: &Token) -> String {
+    match token.kind {
+        TokenKind::Eof => token.kind.to_string(),
+        _ => format!("'{}'", token.text),
+    }
+}
+
+/// What a `not`/`except` applies to
+enum Negated {
+    /// The `of`/`by` clause it precedes, parsed in this state
+    Clause(ParserState),
+    /// The terms after it in the current list
+    Terms,
+}
+
 /// Parser state machine states
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum ParserState {
@@ -77,7 +147,10 @@ enum ParserState {
     Contexts,
     Actors,
     Temporal,
+    Where,
     Actions,
+    /// A `not`/`except` that must be followed by a clause
+    Negation,
     Done,
 }
 
@@ -87,6 +160,8 @@ pub struct Parser<'a> {
     state: ParserState,
     query: AxQuery<'a>,
     current_position: usize,
+    /// Whether the clause about to be parsed follows a `not`/`except`
+    negated: bool,
 }
 
 impl<'a> Parser<'a> {
@@ -97,6 +172,7 @@ impl<'a> Parser<'a> {
             state: ParserState::Start,
             query: AxQuery::new(),
             current_position: 0,
+            negated: false,
         }
     }
 
@@ -111,6 +187,15 @@ impl<'a> Parser<'a> {
             self.step()?;
         }
         self.validate()?;
+        // Groups only carry information when some group has alternatives
+        for groups in [
+            &mut self.query.subject_groups,
+            &mut self.query.context_groups,
+        ] {
+            if groups.iter().all(|g| g.len() == 1) {
+                groups.clear();
+            }
+        }
         Ok(self.query)
     }
 
@@ -123,6 +208,10 @@ impl<'a> Parser<'a> {
             && self.query.actors.is_empty()
             && self.query.temporal.is_none()
             && self.query.actions.is_empty()
+            && self.query.attribute_conditions.is_empty()
+            && self.query.excluded_subjects.is_empty()
+            && self.query.excluded_contexts.is_empty()
+            && self.query.excluded_actors.is_empty()
         {
             return Err(ParseError::EmptyQuery);
         }
@@ -155,7 +244,9 @@ impl<'a> Parser<'a> {
             ParserState::Contexts => self.parse_contexts(),
             ParserState::Actors => self.parse_actors(),
             ParserState::Temporal => self.parse_temporal(),
+            ParserState::Where => self.parse_where(),
             ParserState::Actions => self.parse_actions(),
+            ParserState::Negation => self.parse_negated_clause(),
             ParserState::Done => Ok(()),
         }
     }
@@ -185,6 +276,7 @@ impl<'a> Parser<'a> {
             | TokenKind::Between
             | TokenKind::Over => self.state = ParserState::Temporal,
             TokenKind::So | TokenKind::Therefore => self.state = ParserState::Actions,
+            TokenKind::Where => self.state = ParserState::Where,
             TokenKind::Wildcard => {
                 // Reject wildcard immediately
                 return Err(ParseError::WildcardNotSupported {
@@ -195,14 +287,123 @@ impl<'a> Parser<'a> {
                 return Err(ParseError::PipeNotSupported);
             }
             TokenKind::Identifier | TokenKind::QuotedString => self.state = ParserState::Subjects,
-            TokenKind::Unknown | TokenKind::And => {
+            TokenKind::Or => return Err(self.misplaced_or("subject")),
+            TokenKind::Not | TokenKind::Except => {
+                self.state = match self.parse_negation(Some("subject"))? {
+                    Negated::Clause(state) => state,
+                    Negated::Terms => ParserState::Subjects,
+                };
+                self.negated = true;
+            }
+            TokenKind::Unknown | TokenKind::And | TokenKind::Compare => {
                 self.next();
             }
         }
         Ok(())
     }
 
+    /// Consume a `not`/`except` and work out what it negates: the `of`/`by`
+    /// clause after it, or, when `element` names the list being parsed, the
+    /// terms that follow.
+    fn parse_negation(&mut self, element: Option<&str>) -> Result<Negated, ParseError> {
+        let not = self.next().unwrap();
+        match self.peek() {
+            Some(t) if matches!(t.kind, TokenKind::Of | TokenKind::From) => {
+                Ok(Negated::Clause(ParserState::Contexts))
+            }
+            Some(t) if matches!(t.kind, TokenKind::By | TokenKind::Via) => {
+                Ok(Negated::Clause(ParserState::Actors))
+            }
+            Some(t)
+                if element.is_some()
+                    && matches!(t.kind, TokenKind::Identifier | TokenKind::QuotedString) =>
+            {
+                Ok(Negated::Terms)
+            }
+            Some(t) if t.kind == TokenKind::Wildcard => Err(ParseError::WildcardNotSupported {
+                field: element.unwrap_or("query").to_string(),
+            }),
+            Some(t) if t.kind == TokenKind::Pipe => Err(ParseError::PipeNotSupported),
+            Some(t) if t.kind != TokenKind::Eof => {
+                let expected = match element {
+                    Some(element) => format!("{}, 'of' or 'by' after '{}'", element, not.text),
+                    None => format!("'of' or 'by' after '{}'", not.text),
+                };
+                Err(ParseError::UnexpectedToken {
+                    expected,
+                    found: describe(t),
+                    position: t.offset,
+                })
+            }
+            _ => Err(ParseError::MissingElement {
+                keyword: not.text.to_string(),
+                element: element.unwrap_or("'of' or 'by' clause").to_string(),
+                position: not.offset,
+            }),
+        }
+    }
+
+    /// A `not`/`except` after a clause that can't itself be negated, so it
+    /// has to start a negated `of`/`by` clause
+    fn parse_negated_clause(&mut self) -> Result<(), ParseError> {
+        match self.parse_negation(None)? {
+            Negated::Clause(state) => {
+                self.state = state;
+                self.negated = true;
+                Ok(())
+            }
+            Negated::Terms => unreachable!("terms are only negated within a list"),
+        }
+    }
+
+    /// `or` and the term after it, an alternative to the term just before.
+    /// `after_term` says whether there was one.
+    fn parse_alternative(
+        &mut self,
+        element: &str,
+        after_term: bool,
+    ) -> Result<&'a str, ParseError> {
+        if !after_term {
+            return Err(self.misplaced_or(element));
+        }
+        let or = self.next().unwrap();
+        match self.peek() {
+            Some(t) if matches!(t.kind, TokenKind::Identifier | TokenKind::QuotedString) => {
+                Ok(self.next().unwrap().text)
+            }
+            Some(t) if t.kind == TokenKind::Wildcard => Err(ParseError::WildcardNotSupported {
+                field: element.to_string(),
+            }),
+            Some(t) if t.kind == TokenKind::Pipe => Err(ParseError::PipeNotSupported),
+            _ => Err(ParseError::MissingElement {
+                keyword: or.text.to_string(),
+                element: element.to_string(),
+                position: or.offset,
+            }),
+        }
+    }
+
+    /// Error for an `or` (the next token) that doesn't follow a subject or
+    /// context term
+    fn misplaced_or(&mut self, element: &str) -> ParseError {
+        let (found, position) = match self.peek() {
+            Some(t) => (describe(t), t.offset),
+            None => (TokenKind::Eof.to_string(), self.current_position),
+        };
+        let expected = match element {
+            "subject" | "context" => element.to_string(),
+            _ => format!("{} ('or' only joins subjects or contexts)", element),
+        };
+        ParseError::UnexpectedToken {
+            expected,
+            found,
+            position,
+        }
+    }
+
     fn parse_subjects(&mut self) -> Result<(), ParseError> {
+        let mut negated = std::mem::take(&mut self.negated);
+        let mut after_term = false;
         loop {
             if self.at_eof() {
                 self.state = ParserState::Done;
@@ -228,7 +429,37 @@ impl<'a> Parser<'a> {
                 }
                 TokenKind::Identifier | TokenKind::QuotedString => {
                     let t = self.next().unwrap();
-                    self.query.subjects.push(t.text);
+                    if negated {
+                        self.query.excluded_subjects.push(t.text);
+                    } else {
+                        self.query.subjects.push(t.text);
+                        self.query.subject_groups.push(vec![t.text]);
+                    }
+                    after_term = true;
+                }
+                TokenKind::Or => {
+                    let alternative = self.parse_alternative("subject", after_term)?;
+                    if negated {
+                        self.query.excluded_subjects.push(alternative);
+                    } else {
+                        self.query.subjects.push(alternative);
+                        if let Some(group) = self.query.subject_groups.last_mut() {
+                            group.push(alternative);
+                        }
+                    }
+                }
+                TokenKind::Not | TokenKind::Except => {
+                    match self.parse_negation(Some("subject"))? {
+                        Negated::Clause(state) => {
+                            self.state = state;
+                            self.negated = true;
+                            return Ok(());
+                        }
+                        Negated::Terms => {
+                            negated = true;
+                            after_term = false;
+                        }
+                    }
                 }
                 TokenKind::Is | TokenKind::Are => {
                     self.state = ParserState::Predicates;
@@ -254,12 +485,17 @@ impl<'a> Parser<'a> {
                     self.state = ParserState::Actions;
                     return Ok(());
                 }
+                TokenKind::Where => {
+                    self.state = ParserState::Where;
+                    return Ok(());
+                }
                 TokenKind::Eof => {
                     self.state = ParserState::Done;
                     return Ok(());
                 }
-                TokenKind::Unknown | TokenKind::And => {
+                TokenKind::Unknown | TokenKind::And | TokenKind::Compare => {
                     self.next();
+                    after_term = false;
                 }
             }
         }
@@ -306,6 +542,11 @@ impl<'a> Parser<'a> {
                     self.query.predicates.push(t.text);
                     found = true;
                 }
+                TokenKind::Or => return Err(self.misplaced_or("predicate")),
+                TokenKind::Not | TokenKind::Except => {
+                    self.state = ParserState::Negation;
+                    return Ok(());
+                }
                 TokenKind::Of | TokenKind::From => {
                     self.state = ParserState::Contexts;
                     return Ok(());
@@ -326,6 +567,10 @@ impl<'a> Parser<'a> {
                     self.state = ParserState::Actions;
                     return Ok(());
                 }
+                TokenKind::Where => {
+                    self.state = ParserState::Where;
+                    return Ok(());
+                }
                 TokenKind::Is | TokenKind::Are => {
                     self.state = ParserState::Predicates;
                     return Ok(());
@@ -334,7 +579,7 @@ impl<'a> Parser<'a> {
                     self.state = ParserState::Done;
                     return Ok(());
                 }
-                TokenKind::Unknown | TokenKind::And => {
+                TokenKind::Unknown | TokenKind::And | TokenKind::Compare => {
                     self.next();
                 }
             }
@@ -346,7 +591,9 @@ impl<'a> Parser<'a> {
         let keyword = keyword_token.as_ref().map(|t| t.text).unwrap_or("of");
         let keyword_pos = keyword_token.as_ref().map(|t| t.offset).unwrap_or(0);
 
+        let mut negated = std::mem::take(&mut self.negated);
         let mut found = false;
+        let mut after_term = false;
         loop {
             if self.at_eof() {
                 if !found {
@@ -379,8 +626,38 @@ impl<'a> Parser<'a> {
                 }
                 TokenKind::Identifier | TokenKind::QuotedString => {
                     let t = self.next().unwrap();
-                    self.query.contexts.push(t.text);
+                    if negated {
+                        self.query.excluded_contexts.push(t.text);
+                    } else {
+                        self.query.contexts.push(t.text);
+                        self.query.context_groups.push(vec![t.text]);
+                    }
                     found = true;
+                    after_term = true;
+                }
+                TokenKind::Or => {
+                    let alternative = self.parse_alternative("context", after_term)?;
+                    if negated {
+                        self.query.excluded_contexts.push(alternative);
+                    } else {
+                        self.query.contexts.push(alternative);
+                        if let Some(group) = self.query.context_groups.last_mut() {
+                            group.push(alternative);
+                        }
+                    }
+                }
+                TokenKind::Not | TokenKind::Except => {
+                    match self.parse_negation(Some("context"))? {
+                        Negated::Clause(state) => {
+                            self.state = state;
+                            self.negated = true;
+                            return Ok(());
+                        }
+                        Negated::Terms => {
+                            negated = true;
+                            after_term = false;
+                        }
+                    }
                 }
                 TokenKind::By | TokenKind::Via => {
                     self.state = ParserState::Actors;
@@ -398,8 +675,14 @@ impl<'a> Parser<'a> {
                     self.state = ParserState::Actions;
                     return Ok(());
                 }
+                TokenKind::Where => {
+                    self.state = ParserState::Where;
+                    return Ok(());
+                }
                 TokenKind::Of | TokenKind::From => {
                     self.next();
+                    negated = false;
+                    after_term = false;
                 }
                 TokenKind::Is | TokenKind::Are => {
                     self.state = ParserState::Predicates;
@@ -409,8 +692,9 @@ impl<'a> Parser<'a> {
                     self.state = ParserState::Done;
                     return Ok(());
                 }
-                TokenKind::Unknown | TokenKind::And => {
+                TokenKind::Unknown | TokenKind::And | TokenKind::Compare => {
                     self.next();
+                    after_term = false;
                 }
             }
         }
@@ -421,6 +705,7 @@ impl<'a> Parser<'a> {
         let keyword = keyword_token.as_ref().map(|t| t.text).unwrap_or("by");
         let keyword_pos = keyword_token.as_ref().map(|t| t.offset).unwrap_or(0);
 
+        let mut negated = std::mem::take(&mut self.negated);
         let mut found = false;
         loop {
             if self.at_eof() {
@@ -454,9 +739,22 @@ impl<'a> Parser<'a> {
                 }
                 TokenKind::Identifier | TokenKind::QuotedString => {
                     let t = self.next().unwrap();
-                    self.query.actors.push(t.text);
+                    if negated {
+                        self.query.excluded_actors.push(t.text);
+                    } else {
+                        self.query.actors.push(t.text);
+                    }
                     found = true;
                 }
+                TokenKind::Or => return Err(self.misplaced_or("actor")),
+                TokenKind::Not | TokenKind::Except => match self.parse_negation(Some("actor"))? {
+                    Negated::Clause(state) => {
+                        self.state = state;
+                        self.negated = true;
+                        return Ok(());
+                    }
+                    Negated::Terms => negated = true,
+                },
                 TokenKind::Since
                 | TokenKind::Until
                 | TokenKind::On
@@ -469,8 +767,13 @@ impl<'a> Parser<'a> {
                     self.state = ParserState::Actions;
                     return Ok(());
                 }
+                TokenKind::Where => {
+                    self.state = ParserState::Where;
+                    return Ok(());
+                }
                 TokenKind::By | TokenKind::Via => {
                     self.next();
+                    negated = false;
                 }
                 TokenKind::Eof => {
                     self.state = ParserState::Done;
@@ -484,7 +787,7 @@ impl<'a> Parser<'a> {
                     self.state = ParserState::Contexts;
                     return Ok(());
                 }
-                TokenKind::Unknown | TokenKind::And => {
+                TokenKind::Unknown | TokenKind::And | TokenKind::Compare => {
                     self.next();
                 }
             }
@@ -576,6 +879,8 @@ impl<'a> Parser<'a> {
 
         match token.kind {
             TokenKind::So | TokenKind::Therefore => self.state = ParserState::Actions,
+            TokenKind::Where => self.state = ParserState::Where,
+            TokenKind::Not | TokenKind::Except => self.state = ParserState::Negation,
             TokenKind::Eof => self.state = ParserState::Done,
             TokenKind::Since
             | TokenKind::Until
@@ -588,6 +893,147 @@ impl<'a> Parser<'a> {
         Ok(())
     }
 
+    fn parse_where(&mut self) -> Result<(), ParseError> {
+        let keyword_token = self.next();
+        let mut keyword = keyword_token.as_ref().map(|t| t.text).unwrap_or("where");
+        let mut keyword_pos = keyword_token.as_ref().map(|t| t.offset).unwrap_or(0);
+
+        // After the keyword or an 'and', a condition must follow
+        let mut expecting = true;
+        loop {
+            let token = match self.peek() {
+                Some(t) if t.kind != TokenKind::Eof => t,
+                _ => break,
+            };
+
+            match token.kind {
+                TokenKind::Identifier | TokenKind::QuotedString if expecting => {
+                    let condition = self.parse_condition()?;
+                    self.query.attribute_conditions.push(condition);
+                    expecting = false;
+                }
+                TokenKind::And if !expecting => {
+                    let t = self.next().unwrap();
+                    keyword = t.text;
+                    keyword_pos = t.offset;
+                    expecting = true;
+                }
+                TokenKind::Wildcard => {
+                    return Err(ParseError::WildcardNotSupported {
+                        field: "attribute".to_string(),
+                    });
+                }
+                TokenKind::Pipe => {
+                    return Err(ParseError::PipeNotSupported);
+                }
+                _ if expecting => break,
+                TokenKind::Is | TokenKind::Are => {
+                    self.state = ParserState::Predicates;
+                    return Ok(());
+                }
+                TokenKind::Of | TokenKind::From => {
+                    self.state = ParserState::Contexts;
+                    return Ok(());
+                }
+                TokenKind::By | TokenKind::Via => {
+                    self.state = ParserState::Actors;
+                    return Ok(());
+                }
+                TokenKind::Since
+                | TokenKind::Until
+                | TokenKind::On
+                | TokenKind::Between
+                | TokenKind::Over => {
+                    self.state = ParserState::Temporal;
+                    return Ok(());
+                }
+                TokenKind::So | TokenKind::Therefore => {
+                    self.state = ParserState::Actions;
+                    return Ok(());
+                }
+                TokenKind::Where => {
+                    self.state = ParserState::Where;
+                    return Ok(());
+                }
+                TokenKind::Not | TokenKind::Except => {
+                    self.state = ParserState::Negation;
+                    return Ok(());
+                }
+                _ => {
+                    return Err(ParseError::UnexpectedToken {
+                        expected: "'and'".to_string(),
+                        found: describe(token),
+                        position: token.offset,
+                    });
+                }
+            }
+        }
+
+        if expecting {
+            return Err(ParseError::MissingElement {
+                keyword: keyword.to_string(),
+                element: "condition".to_string(),
+                position: keyword_pos,
+            });
+        }
+        self.state = ParserState::Done;
+        Ok(())
+    }
+
+    /// `key operator literal`, or `key exists [true|false]`
+    fn parse_condition(&mut self) -> Result<AttrCondition, ParseError> {
+        let key = self.next().unwrap();
+        let operator = self.next().unwrap_or_else(|| Token::eof(key.offset));
+        let op = match (operator.kind, operator.text) {
+            (TokenKind::Compare, "==" | "=") => AttrOp::Eq,
+            (TokenKind::Compare, "!=") => AttrOp::Ne,
+            (TokenKind::Compare, ">") => AttrOp::Gt,
+            (TokenKind::Compare, ">=") => AttrOp::Gte,
+            (TokenKind::Compare, "<") => AttrOp::Lt,
+            (TokenKind::Compare, "<=") => AttrOp::Lte,
+            (TokenKind::Identifier, text) if text.eq_ignore_ascii_case("contains") => {
+                AttrOp::Contains
+            }
+            (TokenKind::Identifier, text) if text.eq_ignore_ascii_case("exists") => AttrOp::Exists,
+            _ => {
+                return Err(ParseError::UnexpectedToken {
+                    expected: format!("comparison operator after '{}'", key.text),
+                    found: describe(&operator),
+                    position: operator.offset,
+                })
+            }
+        };
+
+        let value = if op == AttrOp::Exists {
+            match self.peek() {
+                Some(t) if t.kind == TokenKind::Identifier => match literal(t.text) {
+                    AttrValue::Bool(b) => {
+                        self.next();
+                        AttrValue::Bool(b)
+                    }
+                    _ => AttrValue::Bool(true),
+                },
+                _ => AttrValue::Bool(true),
+            }
+        } else {
+            match self.peek().map(|t| t.kind) {
+                Some(TokenKind::QuotedString) => {
+                    AttrValue::String(self.next().unwrap().text.into())
+                }
+                Some(TokenKind::Identifier) => literal(self.next().unwrap().text),
+                _ => {
+                    return Err(ParseError::MissingElement {
+                        keyword: operator.text.to_string(),
+                        element: "value".to_string(),
+                        position: operator.offset,
+                    })
+                }
+            }
+        };
+
+        Ok(AttrCondition::new(key.text, op, value))
+    }
+
     fn parse_actions(&mut self) -> Result<(), ParseError> {
         let keyword_token = self.next();
         let keyword = keyword_token.as_ref().map(|t| t.text).unwrap_or("so");
@@ -621,9 +1067,14 @@ impl<'a> Parser<'a> {
                     self.query.actions.push(t.text);
                     found = true;
                 }
+                TokenKind::Or => return Err(self.misplaced_or("action")),
                 TokenKind::So | TokenKind::Therefore => {
                     self.next();
                 }
+                TokenKind::Where => {
+                    self.state = ParserState::Where;
+                    return Ok(());
+                }
                 TokenKind::Eof => {
                     self.state = ParserState::Done;
                     return Ok(());
@@ -640,6 +1091,7 @@ impl<'a> Parser<'a> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::duration::CalendarDuration;
 
     #[test]
     fn test_single_word_is_subject() {
@@ -704,8 +1156,199 @@ mod tests {
     fn test_temporal_over() {
         let query = Parser::parse("ALICE is experienced over 5y").unwrap();
         if let Some(TemporalClause::Over(dur)) = query.temporal {
-            assert_eq!(dur.value, Some(5.0));
-            assert_eq!(dur.unit, Some(DurationUnit::Years));
+            assert_eq!(
+                dur.duration(),
+                Ok(&CalendarDuration::of(5.0, DurationUnit::Years))
+            );
+        } else {
+            panic!("Expected Over clause");
+        }
+    }
+
+    #[test]
+    fn test_to_filter_over() {
+        let query = Parser::parse("ALICE is experienced over 5y").unwrap();
+        let filter = query.to_filter(0).unwrap();
+        assert_eq!(filter.subjects, vec!["ALICE"]);
+        assert_eq!(filter.predicates, vec!["experienced"]);
+        assert_eq!(
+            filter.over.map(|o| o.min_span_ms),
+            Some(5 * 365 * 86_400_000)
+        );
+        assert_eq!(filter.time_start, None);
+    }
+
+    #[test]
+    fn test_to_filter_rejects_exclusions() {
+        for input in [
+            "ALICE not BOB is member",
+            "ALICE is member not of LEGACY",
+            "ALICE is author not by system:ci",
+        ] {
+            let query = Parser::parse(input).unwrap();
+            assert!(query.to_filter(0).is_err(), "{}", input);
+        }
+    }
+
+    #[test]
+    fn test_to_filter_since() {
+        let query = Parser::parse("ALICE is author since 2024-01-01").unwrap();
+        let filter = query.to_filter(0).unwrap();
+        assert_eq!(filter.time_start, Some(1_704_067_200_000));
+        assert!(filter.over.is_none());
+    }
+
+    #[test]
+    fn test_to_filter_over_missing_unit() {
+        let query = Parser::parse("ALICE is experienced over 5").unwrap();
+        assert!(query.to_filter(0).is_err());
+    }
+
+    #[test]
+    fn test_to_filter_over_compound() {
+        let query = Parser::parse("ALICE is experienced over 1y6m").unwrap();
+        let filter = query.to_filter(0).unwrap();
+        assert_eq!(
+            filter.over.map(|o| o.min_span_ms),
+            Some((365 + 6 * 30) * 86_400_000)
+        );
+    }
+
+    #[test]
+    fn test_to_query_string_round_trips() {
+        let inputs = [
+            "ALICE BOB is author of GitHub by human:alice since 2024-01-01",
+            "'John Doe' is 'senior developer' of 'ACME Corp'",
+            "is 'of' of \"it's\" over 1y6m",
+            "ALICE between '3 days ago' and today so notify",
+            "Zoë is 'a/b' by 'x|y'",
+            "ALICE is author where severity >= 3 and status == 'open'",
+            "ALICE since 2024-01-01 where 'the score' < -2.5 and draft == false so notify",
+            "where tags contains \"it's\" and reviewer exists and archived exists false",
+            "BOB where level != '3' and 'where' <= 'true' and n > 1e3",
+        ];
+        for input in inputs {
+            let query = Parser::parse(input).unwrap();
+            let rendered = query.to_query_string();
+            let reparsed = Parser::parse(&rendered)
+                .unwrap_or_else(|e| panic!("{} → {}: {}", input, rendered, e));
+            assert_eq!(reparsed, query, "{} → {}", input, rendered);
+        }
+    }
+
+    #[test]
+    fn test_where_clause() {
+        let query =
+            Parser::parse("ALICE is author where severity >= 3 and status == 'open'").unwrap();
+        assert_eq!(query.subjects, vec!["ALICE"]);
+        assert_eq!(query.predicates, vec!["author"]);
+        assert_eq!(
+            query.attribute_conditions,
+            vec![
+                AttrCondition::new("severity", AttrOp::Gte, AttrValue::Number(3.0)),
+                AttrCondition::new("status", AttrOp::Eq, AttrValue::String("open".into())),
+            ]
+        );
+
+        let filter = query.to_filter(0).unwrap();
+        assert_eq!(filter.attribute_conditions, query.attribute_conditions);
+    }
+
+    #[test]
+    fn test_where_literal_types() {
+        let query = Parser::parse(
+            "where a = -2.5 and b == '3' and c != TRUE and d < open and e <= 2024-01-01 and f > inf",
+        )
+        .unwrap();
+        let values: Vec<_> = query
+            .attribute_conditions
+            .iter()
+            .map(|c| c.value.clone())
+            .collect();
+        assert_eq!(
+            values,
+            vec![
+                AttrValue::Number(-2.5),
+                AttrValue::String("3".into()),
+                AttrValue::Bool(true),
+                AttrValue::String("open".into()),
+                AttrValue::String("2024-01-01".into()),
+                AttrValue::String("inf".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_where_contains_and_exists() {
+        let query = Parser::parse(
+            "ALICE where tags contains urgent and reviewer exists and draft exists false so notify",
+        )
+        .unwrap();
+        assert_eq!(
+            query.attribute_conditions,
+            vec![
+                AttrCondition::new("tags", AttrOp::Contains, AttrValue::String("urgent".into())),
+                AttrCondition::new("reviewer", AttrOp::Exists, AttrValue::Bool(true)),
+                AttrCondition::new("draft", AttrOp::Exists, AttrValue::Bool(false)),
+            ]
+        );
+        assert_eq!(query.actions, vec!["notify"]);
+    }
+
+    #[test]
+    fn test_where_clause_position() {
+        let query = Parser::parse("ALICE where severity > 1 of GitHub since 2024-01-01").unwrap();
+        assert_eq!(query.contexts, vec!["GitHub"]);
+        assert_eq!(query.temporal, Some(TemporalClause::Since("2024-01-01")));
+        assert_eq!(query.attribute_conditions.len(), 1);
+    }
+
+    #[test]
+    fn test_where_errors() {
+        assert!(matches!(
+            Parser::parse("ALICE where"),
+            Err(ParseError::MissingElement { element, .. }) if element == "condition"
+        ));
+        assert!(matches!(
+            Parser::parse("ALICE where severity > 1 and"),
+            Err(ParseError::MissingElement { keyword, element, .. })
+                if keyword == "and" && element == "condition"
+        ));
+        assert!(matches!(
+            Parser::parse("ALICE where severity 3"),
+            Err(ParseError::UnexpectedToken { found, .. }) if found == "'3'"
+        ));
+        assert!(matches!(
+            Parser::parse("ALICE where severity >="),
+            Err(ParseError::MissingElement { keyword, element, .. })
+                if keyword == ">=" && element == "value"
+        ));
+        assert!(matches!(
+            Parser::parse("ALICE where a == 1 b == 2"),
+            Err(ParseError::UnexpectedToken { expected, .. }) if expected == "'and'"
+        ));
+        assert!(matches!(
+            Parser::parse("ALICE where * == 1"),
+            Err(ParseError::WildcardNotSupported { field }) if field == "attribute"
+        ));
+    }
+
+    #[test]
+    fn test_comparison_outside_where_is_skipped() {
+        let query = Parser::parse("ALICE = BOB").unwrap();
+        assert_eq!(query.subjects, vec!["ALICE", "BOB"]);
+        assert!(query.attribute_conditions.is_empty());
+    }
+
+    #[test]
+    fn test_over_serde_keeps_raw() {
+        let query = Parser::parse("ALICE is experienced over 18m").unwrap();
+        let json = serde_json::to_string(&query).unwrap();
+        assert!(json.contains(r#""Over":"18m""#));
+        let back: AxQuery = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, query);
+        if let Some(TemporalClause::Over(dur)) = back.temporal {
+            assert_eq!(dur.normalized().as_deref(), Some("1y6m"));
         } else {
             panic!("Expected Over clause");
         }
@@ -880,4 +1523,239 @@ mod tests {
         assert!(result.is_err());
         assert!(matches!(result.unwrap_err(), ParseError::PipeNotSupported));
     }
+
+    #[test]
+    fn test_or_groups_subjects() {
+        let query = Parser::parse("ALICE or BOB is author_of of GitHub").unwrap();
+        assert_eq!(query.subjects, vec!["ALICE", "BOB"]);
+        assert_eq!(query.subject_groups, vec![vec!["ALICE", "BOB"]]);
+        assert!(query.context_groups.is_empty());
+
+        let query = Parser::parse("ALICE OR BOB CAROL are authors").unwrap();
+        assert_eq!(query.subjects, vec!["ALICE", "BOB", "CAROL"]);
+        assert_eq!(
+            query.subject_groups,
+            vec![vec!["ALICE", "BOB"], vec!["CAROL"]]
+        );
+        assert_eq!(
+            query.subject_alternatives(),
+            vec![vec!["ALICE", "BOB"], vec!["CAROL"]]
+        );
+
+        // Without `or` there are no groups
+        let query = Parser::parse("ALICE BOB are authors").unwrap();
+        assert!(query.subject_groups.is_empty());
+        assert_eq!(
+            query.subject_alternatives(),
+            vec![vec!["ALICE"], vec!["BOB"]]
+        );
+    }
+
+    #[test]
+    fn test_or_groups_contexts() {
+        let query =
+            Parser::parse("ALICE is member of ACME or 'Globex Corp' LINUX or BSD by hr").unwrap();
+        assert_eq!(query.contexts, vec!["ACME", "Globex Corp", "LINUX", "BSD"]);
+        assert_eq!(
+            query.context_groups,
+            vec![vec!["ACME", "Globex Corp"], vec!["LINUX", "BSD"]]
+        );
+        assert_eq!(query.actors, vec!["hr"]);
+    }
+
+    #[test]
+    fn test_quoted_or_is_a_term() {
+        let query = Parser::parse("'or' is conjunction of \"this or that\"").unwrap();
+        assert_eq!(query.subjects, vec!["or"]);
+        assert_eq!(query.contexts, vec!["this or that"]);
+        assert!(query.subject_groups.is_empty());
+        assert!(query.context_groups.is_empty());
+    }
+
+    #[test]
+    fn test_or_without_alternative() {
+        let err = Parser::parse("ALICE or is author").unwrap_err();
+        assert!(matches!(
+            err,
+            ParseError::MissingElement { ref keyword, ref element, position: 6 }
+                if keyword == "or" && element == "subject"
+        ));
+        assert!(matches!(
+            Parser::parse("ALICE or").unwrap_err(),
+            ParseError::MissingElement { .. }
+        ));
+        let err = Parser::parse("ALICE is member of ACME or").unwrap_err();
+        assert!(matches!(
+            err,
+            ParseError::MissingElement { ref element, position: 24, .. } if element == "context"
+        ));
+        assert!(matches!(
+            Parser::parse("ALICE or * is author").unwrap_err(),
+            ParseError::WildcardNotSupported { .. }
+        ));
+    }
+
+    #[test]
+    fn test_misplaced_or() {
+        for (input, position) in [
+            ("or ALICE is author", 0),
+            ("ALICE is member of or ACME", 19),
+            ("ALICE is author or editor", 16),
+            ("ALICE by hr or ops", 12),
+            ("ALICE so notify or email", 16),
+        ] {
+            let err = Parser::parse(input).unwrap_err();
+            assert!(
+                matches!(err, ParseError::UnexpectedToken { ref found, position: p, .. }
+                    if found == "'or'" && p == position),
+                "{}: {:?}",
+                input,
+                err
+            );
+        }
+    }
+
+    #[test]
+    fn test_or_groups_round_trip() {
+        for input in [
+            "ALICE or BOB CAROL is author_of of GitHub or GitLab",
+            "'or' or \"either or\" is word",
+            "ALICE BOB is author",
+        ] {
+            let query = Parser::parse(input).unwrap();
+            let text = query.to_query_string();
+            assert_eq!(Parser::parse(&text).unwrap(), query, "{}", text);
+
+            let json = serde_json::to_string(&query).unwrap();
+            let back: AxQuery = serde_json::from_str(&json).unwrap();
+            assert_eq!(back, query);
+        }
+
+        let query = Parser::parse("ALICE or BOB CAROL is author").unwrap();
+        assert_eq!(query.to_query_string(), "ALICE or BOB CAROL is author");
+        let json: serde_json::Value = serde_json::to_value(&query).unwrap();
+        assert_eq!(
+            json["subject_groups"],
+            serde_json::json!([["ALICE", "BOB"], ["CAROL"]])
+        );
+        let flat = serde_json::to_value(Parser::parse("ALICE is author").unwrap()).unwrap();
+        assert!(flat.get("subject_groups").is_none());
+    }
+
+    #[test]
+    fn test_negated_clauses() {
+        let query = Parser::parse("ALICE is author of GitHub not by system:ci").unwrap();
+        assert_eq!(query.subjects, vec!["ALICE"]);
+        assert_eq!(query.contexts, vec!["GitHub"]);
+        assert!(query.actors.is_empty());
+        assert_eq!(query.excluded_actors, vec!["system:ci"]);
+
+        let query = Parser::parse("except BOB is member of TEAM").unwrap();
+        assert!(query.subjects.is_empty());
+        assert_eq!(query.excluded_subjects, vec!["BOB"]);
+        assert_eq!(query.predicates, vec!["member"]);
+        assert_eq!(query.contexts, vec!["TEAM"]);
+
+        let query = Parser::parse("ALICE not BOB or CAROL is member not of LEGACY").unwrap();
+        assert_eq!(query.subjects, vec!["ALICE"]);
+        assert_eq!(query.excluded_subjects, vec!["BOB", "CAROL"]);
+        assert!(query.subject_groups.is_empty());
+        assert_eq!(query.excluded_contexts, vec!["LEGACY"]);
+
+        // Within a list, `not` lasts until the next keyword
+        let query =
+            Parser::parse("is member of ACME not OLD of NEW by hr except bot ops since 2024-01-01")
+                .unwrap();
+        assert_eq!(query.contexts, vec!["ACME", "NEW"]);
+        assert_eq!(query.excluded_contexts, vec!["OLD"]);
+        assert_eq!(query.actors, vec!["hr"]);
+        assert_eq!(query.excluded_actors, vec!["bot", "ops"]);
+        assert!(query.has_temporal());
+
+        // Negated clauses after predicates, temporal and where clauses
+        let query = Parser::parse(
+            "ALICE is author since 2024-01-01 where draft == false not by bot not of ARCHIVE",
+        )
+        .unwrap();
+        assert_eq!(query.excluded_actors, vec!["bot"]);
+        assert_eq!(query.excluded_contexts, vec!["ARCHIVE"]);
+
+        // An exclusion alone is a query
+        let query = Parser::parse("not by system:ci").unwrap();
+        assert_eq!(query.excluded_actors, vec!["system:ci"]);
+        assert!(!query.is_empty());
+    }
+
+    #[test]
+    fn test_quoted_not_is_a_term() {
+        let query = Parser::parse("'not' is word of \"not this\" by 'except'").unwrap();
+        assert_eq!(query.subjects, vec!["not"]);
+        assert_eq!(query.contexts, vec!["not this"]);
+        assert_eq!(query.actors, vec!["except"]);
+        assert!(!query.has_exclusions());
+    }
+
+    #[test]
+    fn test_negation_errors() {
+        let err = Parser::parse("ALICE is author not").unwrap_err();
+        assert!(matches!(
+            err,
+            ParseError::MissingElement { ref keyword, position: 16, .. } if keyword == "not"
+        ));
+        let err = Parser::parse("ALICE except").unwrap_err();
+        assert!(matches!(
+            err,
+            ParseError::MissingElement { ref keyword, ref element, position: 6 }
+                if keyword == "except" && element == "subject"
+        ));
+        assert!(matches!(
+            Parser::parse("ALICE is member of ACME not of").unwrap_err(),
+            ParseError::MissingElement { ref element, .. } if element == "context"
+        ));
+
+        // Only lists and `of`/`by` clauses can be negated
+        for (input, position) in [
+            ("ALICE not is author", 10),
+            ("ALICE is not author", 13),
+            ("ALICE not since 2024-01-01", 10),
+            ("ALICE where draft == false not draft == true", 31),
+        ] {
+            let err = Parser::parse(input).unwrap_err();
+            assert!(
+                matches!(err, ParseError::UnexpectedToken { position: p, .. } if p == position),
+                "{}: {:?}",
+                input,
+                err
+            );
+        }
+        assert!(matches!(
+            Parser::parse("ALICE not *").unwrap_err(),
+            ParseError::WildcardNotSupported { .. }
+        ));
+    }
+
+    #[test]
+    fn test_negation_round_trip() {
+        for input in [
+            "ALICE is author of GitHub not by system:ci",
+            "except BOB is member of TEAM",
+            "ALICE not 'not' is member of ACME or GLOBEX not of LEGACY by hr not by bot so notify",
+            "not of ARCHIVE",
+        ] {
+            let query = Parser::parse(input).unwrap();
+            let text = query.to_query_string();
+            assert_eq!(Parser::parse(&text).unwrap(), query, "{}", text);
+
+            let json = serde_json::to_string(&query).unwrap();
+            let back: AxQuery = serde_json::from_str(&json).unwrap();
+            assert_eq!(back, query);
+        }
+
+        let query = Parser::parse("ALICE is author not by system:ci").unwrap();
+        assert_eq!(query.to_query_string(), "ALICE is author not by system:ci");
+        let json = serde_json::to_value(Parser::parse("ALICE is author").unwrap()).unwrap();
+        assert_eq!(json["excluded_subjects"], serde_json::json!([]));
+        assert_eq!(json["excluded_contexts"], serde_json::json!([]));
+        assert_eq!(json["excluded_actors"], serde_json::json!([]));
+    }
 }
@@ -26,11 +26,20 @@ pub enum TokenKind {
 
     // Connectors
     And,
+    Or,
+
+    // Negation
+    Not,
+    Except,
 
     // Action keywords
     So,
     Therefore,
 
+    // Attribute conditions
+    Where,
+    Compare, // Comparison operator: == = != > >= < <=
+
     // Special
     Eof,
     Unknown,
@@ -82,8 +91,13 @@ impl fmt::Display for TokenKind {
             TokenKind::Between => write!(f, "'between'"),
             TokenKind::Over => write!(f, "'over'"),
             TokenKind::And => write!(f, "'and'"),
+            TokenKind::Or => write!(f, "'or'"),
+            TokenKind::Not => write!(f, "'not'"),
+            TokenKind::Except => write!(f, "'except'"),
             TokenKind::So => write!(f, "'so'"),
             TokenKind::Therefore => write!(f, "'therefore'"),
+            TokenKind::Where => write!(f, "'where'"),
+            TokenKind::Compare => write!(f, "comparison operator"),
             TokenKind::Eof => write!(f, "end of input"),
             TokenKind::Unknown => write!(f, "unknown"),
             TokenKind::Wildcard => write!(f, "wildcard '*'"),
@@ -0,0 +1,629 @@
+//! Deterministic pseudonymization of attestations for sharing datasets.
+//!
+//! A [`Pseudonymizer`] replaces personal data with keyed tokens: every value
+//! is mapped through HMAC-SHA256 under a secret key, so the same `ALICE`
+//! becomes the same `SUBJ_…` token everywhere in a dataset (grouping and
+//! relationships survive), while nobody without the key can map tokens back
+//! or confirm a guess. The hex part of a token depends only on the value, so
+//! `ERIN` as a subject (`SUBJ_3c0f…`) and as a context (`CTX_3c0f…`) stays
+//! recognizably the same entity.
+//!
+//! What happens to each slot, attribute and timestamp is set by a
+//! [`PseudonymizeConfig`]. The defaults keep the dataset useful for
+//! debugging classification and queries:
+//!
+//! - subjects, contexts and actors are replaced by tokens; predicates and
+//!   sources are kept,
+//! - actors keep the prefix [`ActorCredibility`] is inferred from
+//!   (`human:`, `llm:`, `system:`), so conflict classification ranks them
+//!   exactly as before,
+//! - the `_` existence marker is always kept,
+//! - attribute strings longer than [`DEFAULT_MAX_ATTRIBUTE_LEN`] are redacted,
+//!   other attribute values kept unless a per-key policy says otherwise,
+//! - timestamps are kept (bucketing or jitter are opt-in, and change what
+//!   temporal classification sees).
+//!
+//! Attestation IDs are always replaced: ASUIDs embed fragments of the
+//! subject, predicate and context. Signatures and signer DIDs are dropped,
+//! since they cover the original content; [`Attestation::content_hash`] of
+//! the output describes the pseudonymized content.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+use crate::attestation::{Attestation, AxFilter};
+use crate::classify::ActorCredibility;
+use crate::storage::{AttestationStore, QueryStore, StoreError};
+
+/// Shortest accepted secret key, in bytes
+pub const MIN_KEY_LEN: usize = 16;
+
+/// Attribute strings longer than this are redacted by default
+pub const DEFAULT_MAX_ATTRIBUTE_LEN: usize = 32;
+
+/// Replacement for redacted values
+pub const REDACTED: &str = "[redacted]";
+
+/// Hex digits in a token. 48 bits keep collisions (which would merge
+/// distinct values into one group) unlikely well past millions of values.
+const TOKEN_HEX_LEN: usize = 12;
+
+/// Existence marker, kept as-is in every slot
+const EXISTENCE_MARKER: &str = "_";
+
+/// What to do with one kind of value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ValuePolicy {
+    /// Replace with a keyed token (`SUBJ_7f3a9c2e41b0`)
+    Hash,
+    /// Keep the original value
+    Preserve,
+    /// Replace with [`REDACTED`]
+    Redact,
+}
+
+/// What to do with `timestamp` and `created_at`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum TimestampPolicy {
+    #[default]
+    Preserve,
+    /// Round down to a multiple of `bucket_ms`
+    Bucket { bucket_ms: i64 },
+    /// Shift by up to ±`max_ms`, derived from the key and the attestation
+    /// ID so the same attestation always moves the same way
+    Jitter { max_ms: i64 },
+}
+
+/// Per-slot, per-attribute and timestamp policies.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PseudonymizeConfig {
+    pub subjects: ValuePolicy,
+    pub predicates: ValuePolicy,
+    pub contexts: ValuePolicy,
+    pub actors: ValuePolicy,
+    pub source: ValuePolicy,
+    /// Policies for specific attribute keys
+    pub attributes: BTreeMap<String, ValuePolicy>,
+    /// Attributes without a policy: strings longer than this are redacted,
+    /// everything else is kept
+    pub max_attribute_len: usize,
+    pub timestamps: TimestampPolicy,
+}
+
+impl Default for PseudonymizeConfig {
+    fn default() -> Self {
+        Self {
+            subjects: ValuePolicy::Hash,
+            predicates: ValuePolicy::Preserve,
+            contexts: ValuePolicy::Hash,
+            actors: ValuePolicy::Hash,
+            source: ValuePolicy::Preserve,
+            attributes: BTreeMap::new(),
+            max_attribute_len: DEFAULT_MAX_ATTRIBUTE_LEN,
+            timestamps: TimestampPolicy::Preserve,
+        }
+    }
+}
+
+/// Pseudonymization errors
+#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+pub enum PseudonymizeError {
+    #[error("pseudonymization key must be at least {MIN_KEY_LEN} bytes, got {0}")]
+    KeyTooShort(usize),
+}
+
+/// Applies a [`PseudonymizeConfig`] under a secret key.
+#[derive(Clone)]
+pub struct Pseudonymizer {
+    /// HMAC inner and outer hashes with the padded key already absorbed
+    inner: Sha256,
+    outer: Sha256,
+    config: PseudonymizeConfig,
+}
+
+impl std::fmt::Debug for Pseudonymizer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Pseudonymizer")
+            .field("config", &self.config)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Pseudonymizer {
+    pub fn new(key: &[u8], config: PseudonymizeConfig) -> Result<Self, PseudonymizeError> {
+        if key.len() < MIN_KEY_LEN {
+            return Err(PseudonymizeError::KeyTooShort(key.len()));
+        }
+        Ok(Self::keyed(key, config))
+    }
+
+    /// HMAC (RFC 2104) state for any key length, with a 64-byte block.
+    fn keyed(key: &[u8], config: PseudonymizeConfig) -> Self {
+        let mut block = [0u8; 64];
+        if key.len() > block.len() {
+            block[..32].copy_from_slice(&Sha256::digest(key));
+        } else {
+            block[..key.len()].copy_from_slice(key);
+        }
+        let pad = |byte: u8| {
+            let mut hasher = Sha256::new();
+            hasher.update(block.map(|b| b ^ byte));
+            hasher
+        };
+        Self {
+            inner: pad(0x36),
+            outer: pad(0x5c),
+            config,
+        }
+    }
+
+    pub fn config(&self) -> &PseudonymizeConfig {
+        &self.config
+    }
+
+    fn hmac(&self, message: &[&[u8]]) -> [u8; 32] {
+        let mut inner = self.inner.clone();
+        message.iter().for_each(|part| inner.update(part));
+        let mut outer = self.outer.clone();
+        outer.update(inner.finalize());
+        outer.finalize().into()
+    }
+
+    /// HMAC of `value`, separated by `domain` from other uses of the key.
+    fn mac(&self, domain: &str, value: &[u8]) -> [u8; 32] {
+        self.hmac(&[domain.as_bytes(), &[0], value])
+    }
+
+    /// Token for `value`: `prefix` plus the keyed hash of the value.
+    pub fn token(&self, prefix: &str, value: &str) -> String {
+        let mut token = format!("{}_", prefix);
+        token.push_str(&hex(&self.mac("value", value.as_bytes()))[..TOKEN_HEX_LEN]);
+        token
+    }
+
+    /// Pseudonymize one attestation.
+    pub fn pseudonymize(&self, attestation: &Attestation) -> Attestation {
+        let config = &self.config;
+        let slot = |values: &[String], policy: ValuePolicy, prefix: &str| -> Vec<String> {
+            values
+                .iter()
+                .map(|v| self.apply(v, policy, |v| self.token(prefix, v)))
+                .collect()
+        };
+        let attributes = attestation
+            .attributes
+            .iter()
+            .map(|(key, value)| (key.clone(), self.attribute(key, value)))
+            .collect();
+
+        Attestation {
+            id: self.id(&attestation.id),
+            subjects: slot(&attestation.subjects, config.subjects, "SUBJ"),
+            predicates: slot(&attestation.predicates, config.predicates, "PRED"),
+            contexts: slot(&attestation.contexts, config.contexts, "CTX"),
+            actors: attestation
+                .actors
+                .iter()
+                .map(|a| self.apply(a, config.actors, |a| self.actor_token(a)))
+                .collect(),
+            timestamp: self.timestamp(&attestation.id, attestation.timestamp),
+            source: self.apply(&attestation.source, config.source, |s| self.token("SRC", s)),
+            attributes,
+            created_at: self.timestamp(&attestation.id, attestation.created_at),
+            signature: None,
+            signer_did: None,
+            revision: 0,
+            confidence: attestation.confidence,
+        }
+    }
+
+    /// Pseudonymize a whole dataset.
+    pub fn pseudonymize_all(&self, attestations: &[Attestation]) -> Vec<Attestation> {
+        attestations.iter().map(|a| self.pseudonymize(a)).collect()
+    }
+
+    fn apply(&self, value: &str, policy: ValuePolicy, hash: impl Fn(&str) -> String) -> String {
+        if value == EXISTENCE_MARKER {
+            return value.to_string();
+        }
+        match policy {
+            ValuePolicy::Hash => hash(value),
+            ValuePolicy::Preserve => value.to_string(),
+            ValuePolicy::Redact => REDACTED.to_string(),
+        }
+    }
+
+    /// Actor token keeping the credibility kind of the original actor.
+    fn actor_token(&self, actor: &str) -> String {
+        let kind = match ActorCredibility::from_actor(actor) {
+            ActorCredibility::Human => "human:",
+            ActorCredibility::Llm => "llm:",
+            ActorCredibility::System => "system:",
+            ActorCredibility::External => "",
+        };
+        format!("{}{}", kind, self.token("ACTOR", actor))
+    }
+
+    /// ASUID-shaped replacement ID.
+    fn id(&self, id: &str) -> String {
+        let h = hex(&self.mac("id", id.as_bytes()));
+        format!(
+            "AS-{}-{}-{}-{}-{}",
+            &h[..8],
+            &h[8..12],
+            &h[12..16],
+            &h[16..20],
+            &h[20..32]
+        )
+    }
+
+    fn attribute(&self, key: &str, value: &Value) -> Value {
+        match self.config.attributes.get(key) {
+            Some(ValuePolicy::Preserve) => value.clone(),
+            Some(ValuePolicy::Redact) => Value::from(REDACTED),
+            Some(ValuePolicy::Hash) => self.hash_value(value),
+            None => self.redact_long_strings(value),
+        }
+    }
+
+    /// Strings (also inside arrays and objects) become tokens; other values
+    /// are hashed by their JSON form.
+    fn hash_value(&self, value: &Value) -> Value {
+        match value {
+            Value::String(s) => Value::from(self.token("ATTR", s)),
+            Value::Array(items) => items.iter().map(|v| self.hash_value(v)).collect(),
+            Value::Object(map) => map
+                .iter()
+                .map(|(k, v)| (k.clone(), self.hash_value(v)))
+                .collect(),
+            Value::Null => Value::Null,
+            other => Value::from(self.token("ATTR", &other.to_string())),
+        }
+    }
+
+    fn redact_long_strings(&self, value: &Value) -> Value {
+        match value {
+            Value::String(s) if s.chars().count() > self.config.max_attribute_len => {
+                Value::from(REDACTED)
+            }
+            Value::Array(items) => items.iter().map(|v| self.redact_long_strings(v)).collect(),
+            Value::Object(map) => map
+                .iter()
+                .map(|(k, v)| (k.clone(), self.redact_long_strings(v)))
+                .collect(),
+            other => other.clone(),
+        }
+    }
+
+    fn timestamp(&self, id: &str, ms: i64) -> i64 {
+        match self.config.timestamps {
+            TimestampPolicy::Preserve => ms,
+            TimestampPolicy::Bucket { bucket_ms } if bucket_ms > 0 => ms - ms.rem_euclid(bucket_ms),
+            TimestampPolicy::Jitter { max_ms } if max_ms > 0 => {
+                let mac = self.mac("timestamp", id.as_bytes());
+                let r = u64::from_le_bytes(mac[..8].try_into().expect("8 bytes"));
+                let span = 2 * max_ms as u64 + 1;
+                ms + (r % span) as i64 - max_ms
+            }
+            _ => ms,
+        }
+    }
+}
+
+/// Outcome of [`pseudonymize_store`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct PseudonymizeReport {
+    /// Attestations the filter matched
+    pub read: usize,
+    /// Pseudonymized attestations written to the destination
+    pub written: usize,
+}
+
+/// Pseudonymize the attestations of `source` matching `filter` into `dest`,
+/// e.g. a fresh SQLite file that is then shared as a snapshot.
+pub fn pseudonymize_store<S, D>(
+    source: &S,
+    filter: &AxFilter,
+    dest: &mut D,
+    pseudonymizer: &Pseudonymizer,
+) -> Result<PseudonymizeReport, StoreError>
+where
+    S: QueryStore + ?Sized,
+    D: AttestationStore + ?Sized,
+{
+    let result = source.query(filter)?;
+    let mut report = PseudonymizeReport {
+        read: result.attestations.len(),
+        written: 0,
+    };
+    for attestation in &result.attestations {
+        dest.put(pseudonymizer.pseudonymize(attestation))?;
+        report.written += 1;
+    }
+    Ok(report)
+}
+
+/// Config for the WASM pseudonymize_attestations function: the secret key
+/// next to the [`PseudonymizeConfig`] fields.
+#[derive(Debug, Deserialize)]
+pub struct PseudonymizeRequest {
+    pub key: String,
+    #[serde(flatten)]
+    pub config: PseudonymizeConfig,
+}
+
+/// Output of the WASM pseudonymize_attestations function.
+#[derive(Debug, Serialize)]
+pub struct PseudonymizeOutput {
+    pub attestations: Vec<Attestation>,
+    pub total: usize,
+}
+
+/// JSON entry point: a JSON array of attestations and a
+/// [`PseudonymizeRequest`] (`{"key": "...", "subjects": "hash", ...}`) in,
+/// `{"attestations": [...], "total": N}` or `{"error": "..."}` out.
+pub fn pseudonymize_attestations_json(attestations_json: &str, config_json: &str) -> String {
+    let error = |what: &str, e: &dyn std::fmt::Display| {
+        format!(
+            r#"{{"error":"{}: {}"}}"#,
+            what,
+            e.to_string().replace('"', "\\\"")
+        )
+    };
+    let attestations: Vec<Attestation> = match serde_json::from_str(attestations_json) {
+        Ok(v) => v,
+        Err(e) => return error("invalid attestations", &e),
+    };
+    let request: PseudonymizeRequest = match serde_json::from_str(config_json) {
+        Ok(v) => v,
+        Err(e) => return error("invalid pseudonymize config", &e),
+    };
+    let pseudonymizer = match Pseudonymizer::new(request.key.as_bytes(), request.config) {
+        Ok(p) => p,
+        Err(e) => return error("invalid pseudonymize config", &e),
+    };
+    let attestations = pseudonymizer.pseudonymize_all(&attestations);
+    let total = attestations.len();
+    match serde_json::to_string(&PseudonymizeOutput {
+        attestations,
+        total,
+    }) {
+        Ok(json) => json,
+        Err(e) => format!(r#"{{"error":"serialization failed: {}"}}"#, e),
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::expand::{expand_cartesian, group_by_key, ExpandAttestation};
+    use crate::storage::MemoryStore;
+    use crate::{classify_claims, AttestationBuilder};
+
+    const KEY: &[u8] = b"0123456789abcdef-test-key";
+
+    fn dataset() -> Vec<Attestation> {
+        serde_json::from_str(include_str!("../tests/fixtures/pseudonymize_dataset.json")).unwrap()
+    }
+
+    /// Groups and conflicts of the expand → group → classify pipeline, with
+    /// everything that names a value left out.
+    fn pipeline_shape(attestations: &[Attestation]) -> (Vec<usize>, Vec<Value>) {
+        let expand: Vec<ExpandAttestation> = attestations
+            .iter()
+            .map(|a| ExpandAttestation {
+                id: a.id.clone(),
+                subjects: a.subjects.clone(),
+                predicates: a.predicates.clone(),
+                contexts: a.contexts.clone(),
+                actors: a.actors.clone(),
+                timestamp_ms: a.timestamp,
+                confidence: a.confidence,
+            })
+            .collect();
+        let groups = group_by_key(&expand_cartesian(&expand));
+        let mut group_sizes: Vec<usize> = groups.iter().map(|g| g.claims.len()).collect();
+        group_sizes.sort_unstable();
+
+        let input = serde_json::json!({
+            "claim_groups": groups,
+            "now_ms": 1_760_000_000_000i64,
+        });
+        let output: Value = serde_json::from_str(&classify_claims(&input.to_string())).unwrap();
+        let mut conflicts: Vec<Value> = output["conflicts"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|c| {
+                let ranks: Vec<&Value> = c["actor_hierarchy"]
+                    .as_array()
+                    .unwrap()
+                    .iter()
+                    .map(|r| &r["credibility"])
+                    .collect();
+                serde_json::json!({
+                    "conflict_type": c["conflict_type"],
+                    "strategy": c["strategy"],
+                    "confidence": c["confidence"],
+                    "temporal_pattern": c["temporal_pattern"],
+                    "auto_resolved": c["auto_resolved"],
+                    "sources": c["source_ids"].as_array().unwrap().len(),
+                    "credibility": ranks,
+                })
+            })
+            .collect();
+        conflicts.sort_by_key(|c| c.to_string());
+        (group_sizes, conflicts)
+    }
+
+    #[test]
+    fn test_classification_survives_pseudonymization() {
+        let original = dataset();
+        let pseudonymizer = Pseudonymizer::new(KEY, PseudonymizeConfig::default()).unwrap();
+        let shared = pseudonymizer.pseudonymize_all(&original);
+
+        let (groups, conflicts) = pipeline_shape(&original);
+        assert_eq!(pipeline_shape(&shared), (groups, conflicts.clone()));
+
+        // The fixture must exercise more than one kind of conflict
+        let mut types: Vec<&str> = conflicts
+            .iter()
+            .map(|c| c["conflict_type"].as_str().unwrap())
+            .collect();
+        types.dedup();
+        assert!(types.len() >= 3, "fixture only produces {:?}", types);
+    }
+
+    #[test]
+    fn test_tokens_are_deterministic_and_keyed() {
+        let a = Pseudonymizer::new(KEY, PseudonymizeConfig::default()).unwrap();
+        let again = Pseudonymizer::new(KEY, PseudonymizeConfig::default()).unwrap();
+        let other =
+            Pseudonymizer::new(b"another-key-of-16+", PseudonymizeConfig::default()).unwrap();
+
+        let token = a.token("SUBJ", "ALICE");
+        assert_eq!(token, again.token("SUBJ", "ALICE"));
+        assert_ne!(token, other.token("SUBJ", "ALICE"));
+        assert_ne!(token, a.token("SUBJ", "BOB"));
+        assert!(token.starts_with("SUBJ_"));
+        assert_eq!(token.len(), "SUBJ_".len() + TOKEN_HEX_LEN);
+
+        assert_eq!(
+            Pseudonymizer::new(b"short", PseudonymizeConfig::default()).unwrap_err(),
+            PseudonymizeError::KeyTooShort(5)
+        );
+    }
+
+    #[test]
+    fn test_hmac_matches_rfc_4231() {
+        let config = PseudonymizeConfig::default;
+        // Test case 2 (the key is below MIN_KEY_LEN, so skip the check)
+        let mac =
+            Pseudonymizer::keyed(b"Jefe", config()).hmac(&[b"what do ya want ", b"for nothing?"]);
+        assert_eq!(
+            hex(&mac),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+        // Test case 6: a key longer than the block is hashed first
+        let mac = Pseudonymizer::new(&[0xaa; 131], config())
+            .unwrap()
+            .hmac(&[b"Test Using Larger Than Block-Size Key - Hash Key First"]);
+        assert_eq!(
+            hex(&mac),
+            "60e431591ee0b67f0d8a26aacbf5b77f8e0bc6213728c5140546040f0ee37f54"
+        );
+    }
+
+    #[test]
+    fn test_slot_attribute_and_timestamp_policies() {
+        let original = AttestationBuilder::new()
+            .id("AS-ALICE-author-GitHub-1")
+            .subject("ALICE")
+            .predicate("is_author_of")
+            .context("_")
+            .actor("human:bob")
+            .actor("crawler")
+            .timestamp(1_700_000_123_456)
+            .source("cli")
+            .attribute("note", serde_json::json!("short"))
+            .attribute(
+                "bio",
+                serde_json::json!("a very long free-text biography of alice"),
+            )
+            .attribute("email", serde_json::json!("alice@example.com"))
+            .attribute("stars", serde_json::json!(42))
+            .build();
+        let mut attributes = BTreeMap::new();
+        attributes.insert("email".to_string(), ValuePolicy::Hash);
+        attributes.insert("note".to_string(), ValuePolicy::Redact);
+        let config = PseudonymizeConfig {
+            contexts: ValuePolicy::Redact,
+            attributes,
+            timestamps: TimestampPolicy::Bucket {
+                bucket_ms: 3_600_000,
+            },
+            ..Default::default()
+        };
+        let p = Pseudonymizer::new(KEY, config).unwrap();
+        let shared = p.pseudonymize(&original);
+
+        assert_eq!(shared.subjects, vec![p.token("SUBJ", "ALICE")]);
+        assert_eq!(shared.predicates, vec!["is_author_of"]);
+        assert_eq!(shared.contexts, vec!["_"]);
+        assert!(shared.actors[0].starts_with("human:ACTOR_"));
+        assert!(shared.actors[1].starts_with("ACTOR_"));
+        assert!(shared.id.starts_with("AS-") && !shared.id.contains("ALICE"));
+        assert_eq!(shared.source, "cli");
+        assert_eq!(shared.timestamp, 1_699_999_200_000);
+        assert_eq!(shared.attributes["note"], REDACTED);
+        assert_eq!(shared.attributes["bio"], REDACTED);
+        assert_eq!(
+            shared.attributes["email"],
+            p.token("ATTR", "alice@example.com")
+        );
+        assert_eq!(shared.attributes["stars"], 42);
+        assert_ne!(shared.content_hash(), original.content_hash());
+
+        let jitter = Pseudonymizer::new(
+            KEY,
+            PseudonymizeConfig {
+                timestamps: TimestampPolicy::Jitter { max_ms: 60_000 },
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        let moved = jitter.pseudonymize(&original).timestamp;
+        assert!((moved - original.timestamp).abs() <= 60_000);
+        assert_eq!(moved, jitter.pseudonymize(&original).timestamp);
+    }
+
+    #[test]
+    fn test_pseudonymize_store_and_json() {
+        let mut source = MemoryStore::new();
+        for attestation in dataset() {
+            source.put(attestation).unwrap();
+        }
+        let p = Pseudonymizer::new(KEY, PseudonymizeConfig::default()).unwrap();
+        let mut dest = MemoryStore::new();
+        let report = pseudonymize_store(&source, &AxFilter::default(), &mut dest, &p).unwrap();
+        assert_eq!(report.read, dataset().len());
+        assert_eq!(report.written, report.read);
+        assert_eq!(
+            dest.subjects().unwrap().len(),
+            source.subjects().unwrap().len()
+        );
+        assert!(dest
+            .subjects()
+            .unwrap()
+            .iter()
+            .all(|s| s.starts_with("SUBJ_")));
+
+        let input = serde_json::to_string(&dataset()).unwrap();
+        let config = serde_json::json!({
+            "key": std::str::from_utf8(KEY).unwrap(),
+            "attributes": {"rating": "redact"},
+        });
+        let output: Value =
+            serde_json::from_str(&pseudonymize_attestations_json(&input, &config.to_string()))
+                .unwrap();
+        assert_eq!(output["total"], dataset().len());
+        let first: Attestation = serde_json::from_value(output["attestations"][0].clone()).unwrap();
+        assert_eq!(first, p.pseudonymize(&dataset()[0]));
+        assert_eq!(output["attestations"][8]["attributes"]["rating"], REDACTED);
+
+        let short_key: Value =
+            serde_json::from_str(&pseudonymize_attestations_json(&input, r#"{"key":"k"}"#))
+                .unwrap();
+        assert!(short_key["error"].as_str().unwrap().contains("at least"));
+    }
+}
@@ -1,3 +1,5 @@
+use serde::Serialize;
+
 /// Cosine similarity between two f32 slices.
 ///
 /// Returns 0.0 if either vector has zero magnitude.
@@ -29,6 +31,134 @@ pub fn cosine_similarity(a: &[f32], b: &[f32]) -> Result<f32, String> {
     Ok(dot / denom)
 }
 
+/// One item picked by [`mmr_rerank`].
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MmrPick<I> {
+    pub id: I,
+    /// The candidate's own relevance score, as passed in
+    pub relevance: f32,
+    /// λ·relevance − (1−λ)·max similarity to the items picked before it
+    pub marginal: f32,
+}
+
+/// Result of [`mmr_rerank`]: picks in selection order, plus candidates
+/// skipped because their vector is all zeros (no direction to compare).
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MmrResult<I> {
+    pub picks: Vec<MmrPick<I>>,
+    pub skipped: Vec<I>,
+}
+
+/// Re-rank `candidates` (id, vector, relevance) by maximal marginal relevance.
+///
+/// Picks up to `k` items one at a time, each maximizing
+/// `λ·relevance − (1−λ)·max cosine to the already picked items`, so a near
+/// duplicate of an earlier pick loses to a less relevant but different item.
+/// λ = 1 is plain relevance order; λ = 0 takes the most relevant item first
+/// and then always the one least like anything picked so far. Ties go to the
+/// higher relevance, then to the earlier candidate.
+///
+/// Meant for a candidate pool a few times larger than `k` (e.g. the top 50 by
+/// cosine to `query` re-ranked down to 10). `query` fixes the dimension every
+/// candidate must have; relevance is whatever score the caller ranked by.
+/// Returns Err on a dimension mismatch or a NaN `lambda`.
+pub fn mmr_rerank<I: Clone>(
+    query: &[f32],
+    candidates: &[(I, Vec<f32>, f32)],
+    lambda: f32,
+    k: usize,
+) -> Result<MmrResult<I>, String> {
+    if lambda.is_nan() {
+        return Err("lambda must be a number between 0 and 1".to_string());
+    }
+    let lambda = lambda.clamp(0.0, 1.0);
+
+    let mut skipped = Vec::new();
+    // (candidate index, norm, max similarity to picks so far)
+    let mut remaining: Vec<(usize, f32, Option<f32>)> = Vec::new();
+    for (i, (id, vector, _)) in candidates.iter().enumerate() {
+        if vector.len() != query.len() {
+            return Err(format!(
+                "vector dimension mismatch: {} vs {}",
+                query.len(),
+                vector.len()
+            ));
+        }
+        let norm = vector.iter().map(|x| x * x).sum::<f32>().sqrt();
+        if norm == 0.0 {
+            skipped.push(id.clone());
+        } else {
+            remaining.push((i, norm, None));
+        }
+    }
+
+    let marginal = |relevance: f32, max_sim: Option<f32>| {
+        lambda * relevance - (1.0 - lambda) * max_sim.unwrap_or(0.0)
+    };
+
+    let mut picks = Vec::new();
+    while picks.len() < k && !remaining.is_empty() {
+        let mut best = 0;
+        for slot in 1..remaining.len() {
+            let (i, _, sim) = remaining[slot];
+            let (b, _, best_sim) = remaining[best];
+            let (rel, best_rel) = (candidates[i].2, candidates[b].2);
+            let ord = marginal(rel, sim)
+                .total_cmp(&marginal(best_rel, best_sim))
+                .then(rel.total_cmp(&best_rel))
+                .then(b.cmp(&i));
+            if ord.is_gt() {
+                best = slot;
+            }
+        }
+
+        let (picked, picked_norm, sim) = remaining.swap_remove(best);
+        let (id, vector, relevance) = &candidates[picked];
+        picks.push(MmrPick {
+            id: id.clone(),
+            relevance: *relevance,
+            marginal: marginal(*relevance, sim),
+        });
+
+        for (i, norm, max_sim) in remaining.iter_mut() {
+            let dot: f32 = vector
+                .iter()
+                .zip(&candidates[*i].1)
+                .map(|(a, b)| a * b)
+                .sum();
+            let sim = dot / (picked_norm * *norm);
+            *max_sim = Some(max_sim.map_or(sim, |m| m.max(sim)));
+        }
+    }
+
+    Ok(MmrResult { picks, skipped })
+}
+
+/// [`mmr_rerank`] over packed rows: `rows` is `n * query.len()` floats laid
+/// out row by row, relevance is each row's cosine to `query`, and ids are row
+/// indexes. Shared by the browser and wazero exports.
+pub fn mmr_search(
+    query: &[f32],
+    rows: &[f32],
+    lambda: f32,
+    k: usize,
+) -> Result<MmrResult<usize>, String> {
+    let dim = query.len();
+    if dim == 0 || !rows.len().is_multiple_of(dim) {
+        return Err(format!(
+            "candidates length {} is not a multiple of query dimension {}",
+            rows.len(),
+            dim
+        ));
+    }
+    let candidates = rows
+        .chunks_exact(dim)
+        .enumerate()
+        .map(|(index, row)| Ok((index, row.to_vec(), cosine_similarity(query, row)?)))
+        .collect::<Result<Vec<_>, String>>()?;
+    mmr_rerank(query, &candidates, lambda, k)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -91,4 +221,69 @@ mod tests {
         // cos(45°) = 1/√2 ≈ 0.7071
         assert!((sim - std::f32::consts::FRAC_1_SQRT_2).abs() < 1e-6);
     }
+
+    /// Two near-duplicates of the query, one distinct but relevant item, and
+    /// one unrelated item.
+    fn pool() -> Vec<(&'static str, Vec<f32>, f32)> {
+        vec![
+            ("dup-a", vec![1.0, 0.05, 0.0], 0.95),
+            ("dup-b", vec![1.0, 0.0, 0.05], 0.94),
+            ("distinct", vec![0.6, 0.8, 0.0], 0.80),
+            ("unrelated", vec![0.0, 0.0, 1.0], 0.10),
+        ]
+    }
+
+    fn ids<I: Clone>(result: &MmrResult<I>) -> Vec<I> {
+        result.picks.iter().map(|p| p.id.clone()).collect()
+    }
+
+    #[test]
+    fn mmr_lambda_one_is_relevance_order() {
+        let result = mmr_rerank(&[1.0, 0.0, 0.0], &pool(), 1.0, 4).unwrap();
+        assert_eq!(
+            ids(&result),
+            vec!["dup-a", "dup-b", "distinct", "unrelated"]
+        );
+        for pick in &result.picks {
+            assert_eq!(pick.marginal, pick.relevance);
+        }
+    }
+
+    #[test]
+    fn mmr_lambda_zero_is_pure_diversity() {
+        let result = mmr_rerank(&[1.0, 0.0, 0.0], &pool(), 0.0, 3).unwrap();
+        // Most relevant first, then whatever is least like the picks so far
+        assert_eq!(ids(&result), vec!["dup-a", "unrelated", "distinct"]);
+        assert_eq!(result.picks[0].marginal, 0.0);
+    }
+
+    #[test]
+    fn mmr_demotes_near_duplicates() {
+        let result = mmr_rerank(&[1.0, 0.0, 0.0], &pool(), 0.5, 2).unwrap();
+        assert_eq!(ids(&result), vec!["dup-a", "distinct"]);
+        assert!(result.picks[1].marginal < result.picks[1].relevance);
+    }
+
+    #[test]
+    fn mmr_small_pool_and_zero_vectors() {
+        let mut candidates = pool();
+        candidates.truncate(2);
+        candidates.push(("empty", vec![0.0, 0.0, 0.0], 0.99));
+        let result = mmr_rerank(&[1.0, 0.0, 0.0], &candidates, 0.7, 10).unwrap();
+        assert_eq!(ids(&result), vec!["dup-a", "dup-b"]);
+        assert_eq!(result.skipped, vec!["empty"]);
+        assert!(result.picks.iter().all(|p| p.marginal.is_finite()));
+
+        assert!(mmr_rerank(&[1.0, 0.0], &candidates, 0.5, 2).is_err());
+        assert!(mmr_rerank(&[1.0, 0.0, 0.0], &candidates, f32::NAN, 2).is_err());
+    }
+
+    #[test]
+    fn mmr_search_packed_rows() {
+        let rows = [1.0, 0.0, 0.99, 0.1, 0.0, 1.0, 0.0, 0.0];
+        let result = mmr_search(&[1.0, 0.0], &rows, 0.3, 2).unwrap();
+        assert_eq!(ids(&result), vec![0, 2]);
+        assert_eq!(result.skipped, vec![3]);
+        assert!(mmr_search(&[1.0, 0.0], &rows[..3], 0.5, 2).is_err());
+    }
 }
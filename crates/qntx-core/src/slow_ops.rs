@@ -0,0 +1,511 @@
+//! Slow-operation log: which layer was slow, without a tracing dependency.
+//!
+//! Call sites wrap an operation in [`timed`] (or [`start`] and
+//! [`SlowOpTimer::finish`] around an `await`). When it takes at least its
+//! category's threshold, an entry goes into a ring buffer read by
+//! [`slow_ops_report`], and the registered hook is called with it: the
+//! browser build logs to `console.warn`, native hosts register their logger.
+//!
+//! A zero threshold disables the category; a disabled category costs one
+//! atomic load per call. Timing needs a clock: natively the system clock is
+//! used, while `wasm32` builds have none until the host calls
+//! [`set_slow_op_clock`], so nothing is recorded before that.
+
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+
+/// Layer an operation belongs to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SlowOpCategory {
+    /// Store queries and reads
+    StoreQuery,
+    /// Store writes
+    StoreWrite,
+    /// Claim classification
+    Classify,
+    /// Canonical JSON hashing
+    Hash,
+}
+
+impl SlowOpCategory {
+    pub const ALL: [SlowOpCategory; 4] = [
+        SlowOpCategory::StoreQuery,
+        SlowOpCategory::StoreWrite,
+        SlowOpCategory::Classify,
+        SlowOpCategory::Hash,
+    ];
+
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+impl std::fmt::Display for SlowOpCategory {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::StoreQuery => write!(f, "store_query"),
+            Self::StoreWrite => write!(f, "store_write"),
+            Self::Classify => write!(f, "classify"),
+            Self::Hash => write!(f, "hash"),
+        }
+    }
+}
+
+/// Per-category thresholds in milliseconds; 0 disables the category.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SlowOpConfig {
+    pub store_query_ms: u64,
+    pub store_write_ms: u64,
+    pub classify_ms: u64,
+    /// Off by default: hashing runs per attestation on hot paths
+    pub hash_ms: u64,
+    /// Entries kept for [`slow_ops_report`], oldest dropped first
+    pub ring_capacity: usize,
+}
+
+impl Default for SlowOpConfig {
+    fn default() -> Self {
+        Self {
+            store_query_ms: 200,
+            store_write_ms: 200,
+            classify_ms: 100,
+            hash_ms: 0,
+            ring_capacity: 100,
+        }
+    }
+}
+
+impl SlowOpConfig {
+    /// Threshold of `category` in milliseconds
+    pub fn threshold_ms(&self, category: SlowOpCategory) -> u64 {
+        match category {
+            SlowOpCategory::StoreQuery => self.store_query_ms,
+            SlowOpCategory::StoreWrite => self.store_write_ms,
+            SlowOpCategory::Classify => self.classify_ms,
+            SlowOpCategory::Hash => self.hash_ms,
+        }
+    }
+}
+
+/// What the slow operation was working on
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SlowOpDetail {
+    /// Size of the input, e.g. query or JSON length in bytes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub input_len: Option<usize>,
+    /// Number of results produced
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result_count: Option<usize>,
+}
+
+/// An operation that exceeded its threshold
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SlowOp {
+    pub category: SlowOpCategory,
+    pub duration_ms: f64,
+    pub detail: SlowOpDetail,
+    /// When the operation finished, in clock milliseconds (Unix epoch for the
+    /// built-in clocks)
+    pub at: f64,
+}
+
+/// Called with every recorded entry
+pub type SlowOpHook = fn(&SlowOp);
+
+/// Milliseconds from a fixed origin
+pub type SlowOpClock = fn() -> f64;
+
+#[cfg(not(target_arch = "wasm32"))]
+fn system_clock() -> f64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs_f64() * 1000.0)
+        .unwrap_or(0.0)
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+const DEFAULT_CLOCK: Option<SlowOpClock> = Some(system_clock);
+#[cfg(target_arch = "wasm32")]
+const DEFAULT_CLOCK: Option<SlowOpClock> = None;
+
+struct State {
+    capacity: usize,
+    entries: VecDeque<SlowOp>,
+    hook: Option<SlowOpHook>,
+    clock: Option<SlowOpClock>,
+}
+
+/// Thresholds, ring buffer, hook and clock. The process-wide log behind the
+/// free functions is one of these; tests can build their own.
+pub struct SlowOpLog {
+    /// Per category, indexed by [`SlowOpCategory::index`]
+    thresholds: [AtomicU64; 4],
+    state: Mutex<State>,
+}
+
+impl Default for SlowOpLog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SlowOpLog {
+    /// A log with the [`SlowOpConfig`] defaults and the built-in clock.
+    pub const fn new() -> Self {
+        Self {
+            thresholds: [
+                AtomicU64::new(200),
+                AtomicU64::new(200),
+                AtomicU64::new(100),
+                AtomicU64::new(0),
+            ],
+            state: Mutex::new(State {
+                capacity: 100,
+                entries: VecDeque::new(),
+                hook: None,
+                clock: DEFAULT_CLOCK,
+            }),
+        }
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Apply thresholds and ring capacity; excess entries are dropped.
+    pub fn configure(&self, config: &SlowOpConfig) {
+        for category in SlowOpCategory::ALL {
+            self.thresholds[category.index()]
+                .store(config.threshold_ms(category), Ordering::Relaxed);
+        }
+        let mut state = self.state();
+        state.capacity = config.ring_capacity;
+        while state.entries.len() > state.capacity {
+            state.entries.pop_front();
+        }
+    }
+
+    /// Current thresholds and ring capacity
+    pub fn config(&self) -> SlowOpConfig {
+        let threshold = |c: SlowOpCategory| self.thresholds[c.index()].load(Ordering::Relaxed);
+        SlowOpConfig {
+            store_query_ms: threshold(SlowOpCategory::StoreQuery),
+            store_write_ms: threshold(SlowOpCategory::StoreWrite),
+            classify_ms: threshold(SlowOpCategory::Classify),
+            hash_ms: threshold(SlowOpCategory::Hash),
+            ring_capacity: self.state().capacity,
+        }
+    }
+
+    pub fn set_hook(&self, hook: Option<SlowOpHook>) {
+        self.state().hook = hook;
+    }
+
+    pub fn set_clock(&self, clock: Option<SlowOpClock>) {
+        self.state().clock = clock;
+    }
+
+    /// Start timing an operation; None when its category is disabled or
+    /// there is no clock.
+    pub fn start(&self, category: SlowOpCategory) -> Option<SlowOpTimer<'_>> {
+        let threshold_ms = self.thresholds[category.index()].load(Ordering::Relaxed);
+        if threshold_ms == 0 {
+            return None;
+        }
+        let clock = self.state().clock?;
+        Some(SlowOpTimer {
+            log: self,
+            category,
+            threshold_ms,
+            clock,
+            started: clock(),
+        })
+    }
+
+    /// Run `op`, recording it if slow; `detail` describes its result.
+    pub fn timed<T>(
+        &self,
+        category: SlowOpCategory,
+        op: impl FnOnce() -> T,
+        detail: impl FnOnce(&T) -> SlowOpDetail,
+    ) -> T {
+        match self.start(category) {
+            None => op(),
+            Some(timer) => {
+                let result = op();
+                timer.finish(detail(&result));
+                result
+            }
+        }
+    }
+
+    fn record(&self, entry: SlowOp) {
+        let hook = {
+            let mut state = self.state();
+            if state.capacity > 0 {
+                if state.entries.len() == state.capacity {
+                    state.entries.pop_front();
+                }
+                state.entries.push_back(entry.clone());
+            }
+            state.hook
+        };
+        // Outside the lock, so the hook may read the report
+        if let Some(hook) = hook {
+            hook(&entry);
+        }
+    }
+
+    /// Recorded entries, oldest first
+    pub fn report(&self) -> Vec<SlowOp> {
+        self.state().entries.iter().cloned().collect()
+    }
+
+    pub fn clear(&self) {
+        self.state().entries.clear();
+    }
+}
+
+/// An operation being timed, see [`SlowOpLog::start`]
+pub struct SlowOpTimer<'a> {
+    log: &'a SlowOpLog,
+    category: SlowOpCategory,
+    threshold_ms: u64,
+    clock: SlowOpClock,
+    started: f64,
+}
+
+impl SlowOpTimer<'_> {
+    /// Stop timing; records the operation if it reached the threshold.
+    pub fn finish(self, detail: SlowOpDetail) {
+        let at = (self.clock)();
+        let duration_ms = at - self.started;
+        if duration_ms >= self.threshold_ms as f64 {
+            self.log.record(SlowOp {
+                category: self.category,
+                duration_ms,
+                detail,
+                at,
+            });
+        }
+    }
+}
+
+static SLOW_OPS: SlowOpLog = SlowOpLog::new();
+
+/// The process-wide log (per instance in WASM)
+pub fn slow_op_log() -> &'static SlowOpLog {
+    &SLOW_OPS
+}
+
+/// [`SlowOpLog::start`] on the process-wide log
+pub fn start(category: SlowOpCategory) -> Option<SlowOpTimer<'static>> {
+    SLOW_OPS.start(category)
+}
+
+/// [`SlowOpLog::timed`] on the process-wide log
+pub fn timed<T>(
+    category: SlowOpCategory,
+    op: impl FnOnce() -> T,
+    detail: impl FnOnce(&T) -> SlowOpDetail,
+) -> T {
+    SLOW_OPS.timed(category, op, detail)
+}
+
+/// Apply thresholds to the process-wide log
+pub fn set_slow_op_config(config: &SlowOpConfig) {
+    SLOW_OPS.configure(config)
+}
+
+/// Register the hook called with each recorded entry (None to remove)
+pub fn set_slow_op_hook(hook: Option<SlowOpHook>) {
+    SLOW_OPS.set_hook(hook)
+}
+
+/// Replace the clock, e.g. with `Date.now` in the browser
+pub fn set_slow_op_clock(clock: Option<SlowOpClock>) {
+    SLOW_OPS.set_clock(clock)
+}
+
+/// Recent slow operations, oldest first
+pub fn slow_ops_report() -> Vec<SlowOp> {
+    SLOW_OPS.report()
+}
+
+/// Update the process-wide thresholds from a partial [`SlowOpConfig`] JSON
+/// document; omitted fields keep their current value. Returns `{"ok":true}`
+/// or `{"error":"..."}`.
+pub fn set_slow_op_thresholds_json(patch: &str) -> String {
+    let updated = serde_json::from_str::<serde_json::Value>(patch).and_then(|patch| {
+        let mut config = serde_json::to_value(SLOW_OPS.config())?;
+        crate::config::merge_value(&mut config, &patch);
+        serde_json::from_value::<SlowOpConfig>(config)
+    });
+    match updated {
+        Ok(config) => {
+            SLOW_OPS.configure(&config);
+            r#"{"ok":true}"#.to_string()
+        }
+        Err(e) => {
+            serde_json::json!({ "error": format!("invalid slow op thresholds: {}", e) }).to_string()
+        }
+    }
+}
+
+/// [`slow_ops_report`] as a JSON array
+pub fn slow_ops_report_json() -> String {
+    serde_json::to_string(&slow_ops_report()).unwrap_or_else(|_| "[]".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicUsize;
+
+    // Each test owns its clock and hook counter, so they can run in parallel
+    // without touching the process-wide log.
+
+    fn log_with(clock: SlowOpClock, hook: SlowOpHook, config: SlowOpConfig) -> SlowOpLog {
+        let log = SlowOpLog::new();
+        log.configure(&config);
+        log.set_clock(Some(clock));
+        log.set_hook(Some(hook));
+        log
+    }
+
+    static THRESHOLD_NOW: AtomicU64 = AtomicU64::new(0);
+    static THRESHOLD_HOOKS: AtomicUsize = AtomicUsize::new(0);
+
+    #[test]
+    fn hook_fires_only_at_or_above_threshold() {
+        let log = log_with(
+            || THRESHOLD_NOW.load(Ordering::SeqCst) as f64,
+            |_| {
+                THRESHOLD_HOOKS.fetch_add(1, Ordering::SeqCst);
+            },
+            SlowOpConfig {
+                classify_ms: 50,
+                hash_ms: 0,
+                ..SlowOpConfig::default()
+            },
+        );
+        let run = |category, elapsed| {
+            log.timed(
+                category,
+                || THRESHOLD_NOW.fetch_add(elapsed, Ordering::SeqCst),
+                |_| SlowOpDetail {
+                    input_len: Some(elapsed as usize),
+                    result_count: None,
+                },
+            )
+        };
+
+        run(SlowOpCategory::Classify, 49);
+        assert_eq!(THRESHOLD_HOOKS.load(Ordering::SeqCst), 0);
+        run(SlowOpCategory::Classify, 50);
+        run(SlowOpCategory::Classify, 120);
+        assert_eq!(THRESHOLD_HOOKS.load(Ordering::SeqCst), 2);
+
+        // Disabled category: never timed, whatever it takes
+        assert!(log.start(SlowOpCategory::Hash).is_none());
+        run(SlowOpCategory::Hash, 10_000);
+        assert_eq!(THRESHOLD_HOOKS.load(Ordering::SeqCst), 2);
+
+        let report = log.report();
+        let durations: Vec<f64> = report.iter().map(|e| e.duration_ms).collect();
+        assert_eq!(durations, vec![50.0, 120.0]);
+        assert_eq!(report[1].category, SlowOpCategory::Classify);
+        assert_eq!(report[1].detail.input_len, Some(120));
+        assert_eq!(
+            report[1].at,
+            THRESHOLD_NOW.load(Ordering::SeqCst) as f64 - 10_000.0
+        );
+    }
+
+    static RING_NOW: AtomicU64 = AtomicU64::new(0);
+
+    #[test]
+    fn ring_buffer_keeps_newest_entries() {
+        let log = log_with(
+            || RING_NOW.load(Ordering::SeqCst) as f64,
+            |_| {},
+            SlowOpConfig {
+                store_query_ms: 1,
+                ring_capacity: 3,
+                ..SlowOpConfig::default()
+            },
+        );
+        for i in 0..5 {
+            let timer = log.start(SlowOpCategory::StoreQuery).unwrap();
+            RING_NOW.fetch_add(10, Ordering::SeqCst);
+            timer.finish(SlowOpDetail {
+                input_len: None,
+                result_count: Some(i),
+            });
+        }
+
+        let counts: Vec<_> = log
+            .report()
+            .iter()
+            .map(|e| e.detail.result_count.unwrap())
+            .collect();
+        assert_eq!(counts, vec![2, 3, 4]);
+
+        // Shrinking drops the oldest; zero capacity keeps nothing
+        log.configure(&SlowOpConfig {
+            store_query_ms: 1,
+            ring_capacity: 1,
+            ..SlowOpConfig::default()
+        });
+        assert_eq!(log.report()[0].detail.result_count, Some(4));
+        log.configure(&SlowOpConfig {
+            store_query_ms: 1,
+            ring_capacity: 0,
+            ..SlowOpConfig::default()
+        });
+        assert!(log.report().is_empty());
+    }
+
+    #[test]
+    fn thresholds_json_patches_current_config() {
+        let result = set_slow_op_thresholds_json(r#"{"classify_ms": 5}"#);
+        assert_eq!(result, r#"{"ok":true}"#);
+        let result = set_slow_op_thresholds_json(r#"{"hash_ms": 7}"#);
+        assert_eq!(result, r#"{"ok":true}"#);
+        let config = slow_op_log().config();
+        assert_eq!((config.classify_ms, config.hash_ms), (5, 7));
+        assert_eq!(
+            config.store_query_ms,
+            SlowOpConfig::default().store_query_ms
+        );
+
+        let error = set_slow_op_thresholds_json(r#"{"classify_ms": -1}"#);
+        assert!(error.contains("invalid slow op thresholds"), "{}", error);
+        assert_eq!(slow_op_log().config().classify_ms, 5);
+
+        set_slow_op_config(&SlowOpConfig::default());
+    }
+
+    #[test]
+    fn new_log_matches_default_config() {
+        assert_eq!(SlowOpLog::new().config(), SlowOpConfig::default());
+
+        let json = serde_json::to_value(SlowOp {
+            category: SlowOpCategory::StoreQuery,
+            duration_ms: 1.5,
+            detail: SlowOpDetail {
+                input_len: Some(12),
+                result_count: None,
+            },
+            at: 2.0,
+        })
+        .unwrap();
+        assert_eq!(json["category"], "store_query");
+        assert_eq!(json["detail"], serde_json::json!({"input_len": 12}));
+    }
+}
@@ -0,0 +1,374 @@
+//! Coordinated deletes.
+//!
+//! Deleting an attestation from the store leaves state derived from it
+//! elsewhere: index terms, sync hashes, attestations that point at it by ID.
+//! [`DeleteCoordinator`] runs the delete and those cleanups in a fixed order:
+//!
+//! 1. load the attestation (a missing ID ends the cascade, nothing touched)
+//! 2. find orphans: attestations whose subjects name the deleted ID (only
+//!    with [`DeleteOptions::find_orphans`]; they are reported, not deleted).
+//!    Subjects are looked up through the store's query; attribute values can
+//!    only be found by scanning every attestation, so that part is opt-in
+//!    separately with [`DeleteOptions::scan_attributes`]
+//! 3. remove it from the store
+//! 4. run the [`DeleteHook`]s in registration order
+//!
+//! Subsystems opt in by registering a hook, so minimal deployments pay for
+//! nothing. A hook failing after the store removal doesn't undo it: the error
+//! lands in the report and the remaining hooks still run.
+//!
+//! With [`DeleteOptions::dry_run`] the cascade is planned (steps 1, 2 and the
+//! hooks' dry runs) without changing anything.
+
+use serde::{Deserialize, Serialize};
+
+use crate::attestation::{Attestation, AxFilter};
+use crate::storage::error::StoreResult;
+use crate::storage::traits::QueryStore;
+
+/// Cleanup of one subsystem's state derived from a deleted attestation.
+pub trait DeleteHook {
+    /// Name shown in the [`DeleteReport`]
+    fn name(&self) -> &str;
+
+    /// Clean up after `deleted` was removed from the store, or with `dry_run`
+    /// only say what would be cleaned up. Returns descriptions of what was (or
+    /// would be) touched, e.g. index terms or hashes.
+    fn on_delete(&mut self, deleted: &Attestation, dry_run: bool) -> StoreResult<Vec<String>>;
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DeleteOptions {
+    /// Plan the cascade without changing anything
+    pub dry_run: bool,
+    /// Look for attestations naming the deleted ID as a subject
+    pub find_orphans: bool,
+    /// With `find_orphans`, also look for the ID in attribute values. This
+    /// reads every attestation in the store.
+    pub scan_attributes: bool,
+}
+
+/// What a hook touched.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HookReport {
+    pub name: String,
+    pub touched: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Outcome of a coordinated delete.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeleteReport {
+    pub id: String,
+    pub dry_run: bool,
+    /// The attestation existed (and, unless dry-run, was removed)
+    pub found: bool,
+    /// IDs of attestations referencing the deleted one, sorted
+    pub orphans: Vec<String>,
+    pub hooks: Vec<HookReport>,
+}
+
+/// Runs deletes and the cleanups registered for them.
+#[derive(Default)]
+pub struct DeleteCoordinator {
+    options: DeleteOptions,
+    hooks: Vec<Box<dyn DeleteHook>>,
+}
+
+impl DeleteCoordinator {
+    pub fn new(options: DeleteOptions) -> Self {
+        Self {
+            options,
+            hooks: Vec::new(),
+        }
+    }
+
+    /// Register a hook; hooks run in registration order.
+    pub fn with_hook(mut self, hook: impl DeleteHook + 'static) -> Self {
+        self.hooks.push(Box::new(hook));
+        self
+    }
+
+    pub fn options(&self) -> &DeleteOptions {
+        &self.options
+    }
+
+    /// Delete `id` from `store` and run the cascade.
+    pub fn delete<S: QueryStore + ?Sized>(
+        &mut self,
+        store: &mut S,
+        id: &str,
+    ) -> StoreResult<DeleteReport> {
+        let mut report = DeleteReport {
+            id: id.to_string(),
+            dry_run: self.options.dry_run,
+            ..Default::default()
+        };
+        let Some(deleted) = store.get(id)? else {
+            return Ok(report);
+        };
+        report.found = true;
+
+        if self.options.find_orphans {
+            report.orphans = find_orphans(&*store, id, self.options.scan_attributes)?;
+        }
+        if !self.options.dry_run {
+            store.delete(id)?;
+        }
+
+        for hook in &mut self.hooks {
+            let (touched, error) = match hook.on_delete(&deleted, self.options.dry_run) {
+                Ok(touched) => (touched, None),
+                Err(e) => (Vec::new(), Some(e.to_string())),
+            };
+            report.hooks.push(HookReport {
+                name: hook.name().to_string(),
+                touched,
+                error,
+            });
+        }
+        Ok(report)
+    }
+}
+
+/// IDs of attestations, other than `id` itself, that name `id` as a subject,
+/// or with `scan_attributes` anywhere [`references_id`] looks. Sorted.
+pub fn find_orphans<S: QueryStore + ?Sized>(
+    store: &S,
+    id: &str,
+    scan_attributes: bool,
+) -> StoreResult<Vec<String>> {
+    let candidates = if scan_attributes {
+        AxFilter::default()
+    } else {
+        AxFilter {
+            subjects: vec![id.to_string()],
+            ..Default::default()
+        }
+    };
+    let mut orphans: Vec<String> = store
+        .query(&candidates)?
+        .attestations
+        .into_iter()
+        .filter(|a| references_id(a, id))
+        .map(|a| a.id)
+        .collect();
+    orphans.sort();
+    Ok(orphans)
+}
+
+/// Whether `attestation` names `id` as a subject or in any attribute string
+/// (nested arrays and objects included). An attestation doesn't reference
+/// itself.
+pub fn references_id(attestation: &Attestation, id: &str) -> bool {
+    attestation.id != id
+        && (attestation.subjects.iter().any(|s| s == id)
+            || attestation
+                .attributes
+                .values()
+                .any(|v| value_mentions(v, id)))
+}
+
+fn value_mentions(value: &serde_json::Value, id: &str) -> bool {
+    match value {
+        serde_json::Value::String(s) => s == id,
+        serde_json::Value::Array(items) => items.iter().any(|v| value_mentions(v, id)),
+        serde_json::Value::Object(map) => map.values().any(|v| value_mentions(v, id)),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::attestation::AttestationBuilder;
+    use crate::storage::{AttestationStore, MemoryStore, StoreError};
+    use std::collections::BTreeSet;
+    use std::sync::{Arc, Mutex};
+
+    fn att(id: &str, subject: &str) -> Attestation {
+        AttestationBuilder::new()
+            .id(id)
+            .subject(subject)
+            .predicate("knows")
+            .context("work")
+            .actor("human:bob")
+            .build()
+    }
+
+    /// Stand-in for a sync subsystem: the content hashes it offers peers and
+    /// the hashes of deletes it must not fetch back.
+    #[derive(Clone, Default)]
+    struct SyncHashes(Arc<Mutex<(BTreeSet<String>, BTreeSet<String>)>>);
+
+    impl SyncHashes {
+        fn hash(a: &Attestation) -> String {
+            format!("h:{}", a.id)
+        }
+
+        fn offer(&self, a: &Attestation) {
+            self.0.lock().unwrap().0.insert(Self::hash(a));
+        }
+
+        fn offered(&self) -> BTreeSet<String> {
+            self.0.lock().unwrap().0.clone()
+        }
+
+        /// Hashes a peer holding `remote` would send us
+        fn wanted_from(&self, remote: &BTreeSet<String>) -> Vec<String> {
+            let (known, deleted) = &*self.0.lock().unwrap();
+            remote
+                .iter()
+                .filter(|h| !known.contains(*h) && !deleted.contains(*h))
+                .cloned()
+                .collect()
+        }
+    }
+
+    impl DeleteHook for SyncHashes {
+        fn name(&self) -> &str {
+            "sync"
+        }
+
+        fn on_delete(&mut self, deleted: &Attestation, dry_run: bool) -> StoreResult<Vec<String>> {
+            let hash = Self::hash(deleted);
+            let (known, gone) = &mut *self.0.lock().unwrap();
+            if !known.contains(&hash) {
+                return Ok(Vec::new());
+            }
+            if !dry_run {
+                known.remove(&hash);
+                gone.insert(hash.clone());
+            }
+            Ok(vec![hash])
+        }
+    }
+
+    struct Failing;
+
+    impl DeleteHook for Failing {
+        fn name(&self) -> &str {
+            "failing"
+        }
+
+        fn on_delete(&mut self, _: &Attestation, _: bool) -> StoreResult<Vec<String>> {
+            Err(StoreError::Backend("index offline".to_string()))
+        }
+    }
+
+    #[test]
+    fn test_deleted_attestation_does_not_come_back_through_sync() {
+        let mut store =
+            MemoryStore::with_attestations(vec![att("AS-1", "ALICE"), att("AS-2", "BOB")]);
+        let sync = SyncHashes::default();
+        for id in ["AS-1", "AS-2"] {
+            sync.offer(&store.get(id).unwrap().unwrap());
+        }
+        // A peer that synced before the delete still holds both
+        let remote = sync.offered();
+
+        let mut coordinator = DeleteCoordinator::default().with_hook(sync.clone());
+        let report = coordinator.delete(&mut store, "AS-1").unwrap();
+        assert!(report.found);
+        assert_eq!(report.hooks[0].touched, vec!["h:AS-1"]);
+        assert!(!store.exists("AS-1").unwrap());
+
+        assert!(!sync.offered().contains("h:AS-1"));
+        assert!(sync.wanted_from(&remote).is_empty());
+    }
+
+    #[test]
+    fn test_orphan_finder_flags_references() {
+        let embedding = AttestationBuilder::new()
+            .id("AS-emb")
+            .subject("AS-doc")
+            .predicate("embedding")
+            .build();
+        let citing = AttestationBuilder::new()
+            .id("AS-cite")
+            .subject("ALICE")
+            .predicate("cites")
+            .attribute("refs", serde_json::json!([{"source": "AS-doc"}]))
+            .build();
+        let mut store = MemoryStore::with_attestations(vec![
+            att("AS-doc", "DOC"),
+            embedding,
+            citing,
+            att("AS-other", "BOB"),
+        ]);
+
+        // Subject references only, unless attributes are scanned too
+        let mut coordinator = DeleteCoordinator::new(DeleteOptions {
+            dry_run: true,
+            find_orphans: true,
+            ..Default::default()
+        });
+        let report = coordinator.delete(&mut store, "AS-doc").unwrap();
+        assert_eq!(report.orphans, vec!["AS-emb"]);
+
+        let mut coordinator = DeleteCoordinator::new(DeleteOptions {
+            find_orphans: true,
+            scan_attributes: true,
+            ..Default::default()
+        });
+        let report = coordinator.delete(&mut store, "AS-doc").unwrap();
+        assert_eq!(report.orphans, vec!["AS-cite", "AS-emb"]);
+        // Flagged, not deleted
+        assert!(store.exists("AS-emb").unwrap());
+        assert!(!store.exists("AS-doc").unwrap());
+    }
+
+    #[test]
+    fn test_dry_run_changes_nothing() {
+        let mut store = MemoryStore::with_attestations(vec![att("AS-1", "ALICE")]);
+        let sync = SyncHashes::default();
+        sync.offer(&store.get("AS-1").unwrap().unwrap());
+
+        let mut coordinator = DeleteCoordinator::new(DeleteOptions {
+            dry_run: true,
+            find_orphans: true,
+            ..Default::default()
+        })
+        .with_hook(sync.clone());
+        let report = coordinator.delete(&mut store, "AS-1").unwrap();
+        assert!(report.dry_run && report.found);
+        assert_eq!(report.hooks[0].touched, vec!["h:AS-1"]);
+        assert!(store.exists("AS-1").unwrap());
+        assert!(sync.offered().contains("h:AS-1"));
+    }
+
+    #[test]
+    fn test_missing_id_and_failing_hook() {
+        let mut store = MemoryStore::with_attestations(vec![att("AS-1", "ALICE")]);
+        let sync = SyncHashes::default();
+        sync.offer(&store.get("AS-1").unwrap().unwrap());
+        let mut coordinator = DeleteCoordinator::default()
+            .with_hook(Failing)
+            .with_hook(sync.clone());
+
+        let missing = coordinator.delete(&mut store, "AS-nope").unwrap();
+        assert!(!missing.found);
+        assert!(missing.hooks.is_empty());
+
+        // The failing hook is reported; the store removal and later hooks stand
+        let report = coordinator.delete(&mut store, "AS-1").unwrap();
+        assert_eq!(report.hooks[0].name, "failing");
+        assert!(report.hooks[0]
+            .error
+            .as_deref()
+            .unwrap()
+            .contains("index offline"));
+        assert_eq!(report.hooks[1].touched, vec!["h:AS-1"]);
+        assert!(!store.exists("AS-1").unwrap());
+    }
+
+    #[test]
+    fn test_references_id() {
+        let a = att("AS-1", "AS-2");
+        assert!(references_id(&a, "AS-2"));
+        assert!(!references_id(&a, "AS-1"));
+        assert!(!references_id(&a, "AS-3"));
+    }
+}
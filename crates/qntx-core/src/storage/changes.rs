@@ -0,0 +1,252 @@
+//! Changefeed: an ordered log of writes for downstream consumers
+//!
+//! Every put, update and delete a store applies gets the next sequence number
+//! of that store, starting at 1. Consumers remember the last `seq` they
+//! handled and poll [`ChangeFeed::changes_since`] for what came after it.
+//! Sequence numbers are never reused: pruning old events leaves
+//! `latest_seq` where it was, and a consumer whose position was pruned sees
+//! [`ChangeBatch::missed`] and has to resync from a full scan.
+//!
+//! [`ChangeLog`] is the in-memory log behind `MemoryStore`; persistent
+//! backends keep the same contract in their own tables.
+
+use std::collections::VecDeque;
+
+use serde::{Deserialize, Serialize};
+
+use crate::storage::error::StoreResult;
+
+/// Kind of write an event records.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeOp {
+    Put,
+    Update,
+    Delete,
+}
+
+impl ChangeOp {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ChangeOp::Put => "put",
+            ChangeOp::Update => "update",
+            ChangeOp::Delete => "delete",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "put" => Some(ChangeOp::Put),
+            "update" => Some(ChangeOp::Update),
+            "delete" => Some(ChangeOp::Delete),
+            _ => None,
+        }
+    }
+}
+
+/// One write, in store order.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChangeEvent {
+    pub seq: u64,
+    pub op: ChangeOp,
+    pub attestation_id: String,
+    /// [`Attestation::content_hash`](crate::Attestation::content_hash) of
+    /// the written attestation; `None` for deletes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_hash: Option<String>,
+    /// When the write was applied, Unix milliseconds
+    pub at: i64,
+}
+
+/// Result of [`ChangeFeed::changes_since`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChangeBatch {
+    /// Events after the requested `seq`, oldest first
+    pub events: Vec<ChangeEvent>,
+    /// Highest sequence number the store has assigned, 0 before any write.
+    /// Caught up once the last event's `seq` equals it.
+    pub latest_seq: u64,
+    /// Events right after the requested `seq` were pruned; the consumer
+    /// lost track and must resync
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub missed: bool,
+}
+
+/// How many events to keep. Both limits may be set; the stricter wins.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChangeRetention {
+    /// Drop events older than this
+    #[serde(default)]
+    pub max_age_ms: Option<i64>,
+    /// Keep at most this many of the newest events
+    #[serde(default)]
+    pub max_events: Option<usize>,
+}
+
+/// Ordered change log of a store.
+pub trait ChangeFeed {
+    /// Up to `limit` events with a sequence number above `seq`, oldest
+    /// first, plus the store's latest sequence number. `seq` 0 starts from
+    /// the oldest retained event.
+    fn changes_since(&self, seq: u64, limit: usize) -> StoreResult<ChangeBatch>;
+
+    /// Highest sequence number assigned so far; 0 before any write.
+    fn latest_seq(&self) -> StoreResult<u64> {
+        Ok(self.changes_since(u64::MAX, 0)?.latest_seq)
+    }
+
+    /// Drop events `retention` no longer covers, measured from `now_ms`.
+    /// Returns how many were removed. `latest_seq` is unaffected.
+    fn prune_changes(&mut self, retention: &ChangeRetention, now_ms: i64) -> StoreResult<usize>;
+}
+
+/// Milliseconds since the Unix epoch, stamped on events as `at`
+pub type ChangeClock = fn() -> i64;
+
+/// The system clock natively; `wasm32` has no portable clock, so events are
+/// stamped 0 there unless the store is given one.
+pub fn system_clock() -> i64 {
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_millis() as i64)
+            .unwrap_or(0)
+    }
+    #[cfg(target_arch = "wasm32")]
+    {
+        0
+    }
+}
+
+/// In-memory change log with the [`ChangeFeed`] semantics.
+#[derive(Debug, Clone, Default)]
+pub struct ChangeLog {
+    events: VecDeque<ChangeEvent>,
+    latest_seq: u64,
+}
+
+impl ChangeLog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append an event under the next sequence number and return it.
+    pub fn record(
+        &mut self,
+        op: ChangeOp,
+        attestation_id: impl Into<String>,
+        content_hash: Option<String>,
+        at: i64,
+    ) -> u64 {
+        self.latest_seq += 1;
+        self.events.push_back(ChangeEvent {
+            seq: self.latest_seq,
+            op,
+            attestation_id: attestation_id.into(),
+            content_hash,
+            at,
+        });
+        self.latest_seq
+    }
+
+    pub fn latest_seq(&self) -> u64 {
+        self.latest_seq
+    }
+
+    /// See [`ChangeFeed::changes_since`].
+    pub fn since(&self, seq: u64, limit: usize) -> ChangeBatch {
+        // Sequence numbers are dense, so the position follows from the oldest
+        let oldest = self.events.front().map_or(self.latest_seq + 1, |e| e.seq);
+        let next = seq.saturating_add(1);
+        let skip = usize::try_from(next.saturating_sub(oldest)).unwrap_or(usize::MAX);
+        ChangeBatch {
+            events: self.events.iter().skip(skip).take(limit).cloned().collect(),
+            latest_seq: self.latest_seq,
+            missed: seq < self.latest_seq && next < oldest,
+        }
+    }
+
+    /// See [`ChangeFeed::prune_changes`].
+    pub fn prune(&mut self, retention: &ChangeRetention, now_ms: i64) -> usize {
+        let before = self.events.len();
+        if let Some(age) = retention.max_age_ms {
+            while self.events.front().is_some_and(|e| e.at < now_ms - age) {
+                self.events.pop_front();
+            }
+        }
+        if let Some(max) = retention.max_events {
+            while self.events.len() > max {
+                self.events.pop_front();
+            }
+        }
+        before - self.events.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn log_with(n: u64) -> ChangeLog {
+        let mut log = ChangeLog::new();
+        for i in 1..=n {
+            log.record(ChangeOp::Put, format!("AS-{}", i), None, i as i64 * 10);
+        }
+        log
+    }
+
+    #[test]
+    fn test_since_pages_in_order() {
+        let log = log_with(5);
+        let batch = log.since(0, 2);
+        assert_eq!(
+            batch.events.iter().map(|e| e.seq).collect::<Vec<_>>(),
+            vec![1, 2]
+        );
+        assert_eq!(batch.latest_seq, 5);
+        let rest = log.since(2, 10);
+        assert_eq!(
+            rest.events.iter().map(|e| e.seq).collect::<Vec<_>>(),
+            vec![3, 4, 5]
+        );
+        assert!(log.since(5, 10).events.is_empty());
+        assert!(log.since(99, 10).events.is_empty());
+    }
+
+    #[test]
+    fn test_prune_keeps_latest_seq_and_reports_missed() {
+        let mut log = log_with(5);
+        assert_eq!(
+            log.prune(
+                &ChangeRetention {
+                    max_events: Some(2),
+                    ..Default::default()
+                },
+                0
+            ),
+            3
+        );
+        assert_eq!(log.latest_seq(), 5);
+        assert!(log.since(1, 10).missed);
+        assert!(!log.since(3, 10).missed);
+        assert_eq!(log.since(3, 10).events.len(), 2);
+
+        // Age-based, then everything: the counter still holds
+        assert_eq!(
+            log.prune(
+                &ChangeRetention {
+                    max_age_ms: Some(5),
+                    ..Default::default()
+                },
+                100
+            ),
+            2
+        );
+        assert_eq!(log.since(0, 10).latest_seq, 5);
+        assert!(log.since(0, 10).missed);
+        assert!(!log.since(5, 10).missed);
+        assert_eq!(log.record(ChangeOp::Delete, "AS-1", None, 200), 6);
+        assert_eq!(log.since(5, 10).events[0].op, ChangeOp::Delete);
+    }
+}
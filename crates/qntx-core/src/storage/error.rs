@@ -1,7 +1,52 @@
 //! Storage error types
 
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
+/// Backend-independent classification of storage failures.
+///
+/// Every backend error (SQLite, IndexedDB, memory) maps to exactly one kind,
+/// so FFI and WASM callers can branch on `code()` instead of parsing messages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StorageErrorKind {
+    /// The referenced attestation does not exist
+    NotFound,
+    /// An attestation with the same ID (or unique key) already exists
+    Duplicate,
+    /// A storage quota or bound was hit
+    QuotaExceeded,
+    /// Stored data could not be decoded or failed an integrity check
+    Corruption,
+    /// Filesystem or other I/O failure
+    Io,
+    /// The caller supplied malformed data or an invalid query
+    InvalidInput,
+    /// Any other backend failure
+    Backend,
+}
+
+impl StorageErrorKind {
+    /// Stable machine-readable code, e.g. `"not_found"`.
+    pub fn code(&self) -> &'static str {
+        match self {
+            StorageErrorKind::NotFound => "not_found",
+            StorageErrorKind::Duplicate => "duplicate",
+            StorageErrorKind::QuotaExceeded => "quota_exceeded",
+            StorageErrorKind::Corruption => "corruption",
+            StorageErrorKind::Io => "io",
+            StorageErrorKind::InvalidInput => "invalid_input",
+            StorageErrorKind::Backend => "backend",
+        }
+    }
+}
+
+impl std::fmt::Display for StorageErrorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.code())
+    }
+}
+
 /// Errors that can occur during storage operations
 #[derive(Debug, Clone, Error)]
 pub enum StoreError {
@@ -29,6 +74,14 @@ pub enum StoreError {
     #[error("serialization error: {0}")]
     Serialization(String),
 
+    /// Stored data is corrupt (undecodable row, failed integrity check)
+    #[error("storage corruption: {0}")]
+    Corruption(String),
+
+    /// Filesystem or other I/O error
+    #[error("storage I/O error: {0}")]
+    Io(String),
+
     /// Storage quota exceeded
     #[error("quota exceeded for actor '{actor}' in context '{context}': {current} >= {limit}")]
     QuotaExceeded {
@@ -39,5 +92,69 @@ pub enum StoreError {
     },
 }
 
+impl StoreError {
+    /// Classify this error into its backend-independent kind.
+    pub fn kind(&self) -> StorageErrorKind {
+        match self {
+            StoreError::AlreadyExists(_) => StorageErrorKind::Duplicate,
+            StoreError::NotFound(_) => StorageErrorKind::NotFound,
+            StoreError::InvalidData(_) | StoreError::Query(_) | StoreError::Serialization(_) => {
+                StorageErrorKind::InvalidInput
+            }
+            StoreError::Backend(_) => StorageErrorKind::Backend,
+            StoreError::Corruption(_) => StorageErrorKind::Corruption,
+            StoreError::Io(_) => StorageErrorKind::Io,
+            StoreError::QuotaExceeded { .. } => StorageErrorKind::QuotaExceeded,
+        }
+    }
+
+    /// Shorthand for `self.kind().code()`.
+    pub fn code(&self) -> &'static str {
+        self.kind().code()
+    }
+
+    /// Render as `{"code":"...","message":"..."}` for FFI and WASM callers.
+    pub fn to_json(&self) -> String {
+        serde_json::json!({ "code": self.code(), "message": self.to_string() }).to_string()
+    }
+}
+
 /// Result type for storage operations
 pub type StoreResult<T> = Result<T, StoreError>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_kind_codes() {
+        assert_eq!(StoreError::NotFound("x".into()).code(), "not_found");
+        assert_eq!(StoreError::AlreadyExists("x".into()).code(), "duplicate");
+        assert_eq!(StoreError::Query("bad".into()).code(), "invalid_input");
+        assert_eq!(StoreError::Corruption("row 3".into()).code(), "corruption");
+        let quota = StoreError::QuotaExceeded {
+            actor: "a".into(),
+            context: "c".into(),
+            current: 2,
+            limit: 1,
+        };
+        assert_eq!(quota.kind(), StorageErrorKind::QuotaExceeded);
+    }
+
+    #[test]
+    fn test_to_json() {
+        let json: serde_json::Value =
+            serde_json::from_str(&StoreError::NotFound("AS-1".into()).to_json()).unwrap();
+        assert_eq!(json["code"], "not_found");
+        assert_eq!(json["message"], "attestation not found: AS-1");
+    }
+
+    #[test]
+    fn test_kind_serde_matches_code() {
+        let json = serde_json::to_string(&StorageErrorKind::QuotaExceeded).unwrap();
+        assert_eq!(
+            json,
+            format!("\"{}\"", StorageErrorKind::QuotaExceeded.code())
+        );
+    }
+}
@@ -5,7 +5,10 @@
 
 use std::collections::{HashMap, HashSet};
 
-use crate::attestation::{Attestation, AxFilter, AxResult, AxSummary};
+use crate::attestation::{Attestation, AxFilter, AxResult, AxSummary, MatchingSummary};
+use crate::storage::changes::{
+    system_clock, ChangeBatch, ChangeClock, ChangeFeed, ChangeLog, ChangeOp, ChangeRetention,
+};
 use crate::storage::error::{StoreError, StoreResult};
 use crate::storage::traits::{AttestationStore, QueryStore, StorageStats};
 
@@ -18,6 +21,12 @@ use crate::storage::traits::{AttestationStore, QueryStore, StorageStats};
 #[derive(Debug, Default)]
 pub struct MemoryStore {
     attestations: HashMap<String, Attestation>,
+    /// actor → IDs of the attestations naming it, for actor-filtered queries
+    actor_index: HashMap<String, HashSet<String>>,
+    /// Every write, for [`ChangeFeed`]; lives as long as the store
+    changes: ChangeLog,
+    /// Stamps change events; `None` uses [`system_clock`]
+    change_clock: Option<ChangeClock>,
 }
 
 impl MemoryStore {
@@ -25,6 +34,9 @@ impl MemoryStore {
     pub fn new() -> Self {
         Self {
             attestations: HashMap::new(),
+            actor_index: HashMap::new(),
+            changes: ChangeLog::new(),
+            change_clock: None,
         }
     }
 
@@ -41,6 +53,54 @@ impl MemoryStore {
     pub fn all(&self) -> &HashMap<String, Attestation> {
         &self.attestations
     }
+
+    /// Clock for the `at` of change events, e.g. the host clock on `wasm32`.
+    pub fn set_change_clock(&mut self, clock: ChangeClock) {
+        self.change_clock = Some(clock);
+    }
+
+    fn index_actors(&mut self, attestation: &Attestation) {
+        for actor in &attestation.actors {
+            self.actor_index
+                .entry(actor.clone())
+                .or_default()
+                .insert(attestation.id.clone());
+        }
+    }
+
+    fn unindex_actors(&mut self, attestation: &Attestation) {
+        for actor in &attestation.actors {
+            if let Some(ids) = self.actor_index.get_mut(actor) {
+                ids.remove(&attestation.id);
+                if ids.is_empty() {
+                    self.actor_index.remove(actor);
+                }
+            }
+        }
+    }
+
+    /// Attestations that may match `filter`: when it names actors but no
+    /// subjects, those the actor index lists for them, else all of them.
+    fn candidates<'s>(&'s self, filter: &AxFilter) -> Vec<&'s Attestation> {
+        if filter.actors.is_empty() || !filter.subjects.is_empty() {
+            return self.attestations.values().collect();
+        }
+        let ids: HashSet<&String> = filter
+            .actors
+            .iter()
+            .filter_map(|actor| self.actor_index.get(actor))
+            .flatten()
+            .collect();
+        ids.into_iter()
+            .filter_map(|id| self.attestations.get(id))
+            .collect()
+    }
+
+    fn record_change(&mut self, op: ChangeOp, attestation: Option<&Attestation>, id: &str) {
+        let at = self.change_clock.unwrap_or(system_clock)();
+        self.changes
+            .record(op, id, attestation.map(Attestation::content_hash), at);
+    }
 }
 
 impl AttestationStore for MemoryStore {
@@ -48,6 +108,15 @@ impl AttestationStore for MemoryStore {
         if self.attestations.contains_key(&attestation.id) {
             return Err(StoreError::AlreadyExists(attestation.id));
         }
+        attestation
+            .validate_confidence()
+            .map_err(StoreError::InvalidData)?;
+        let attestation = Attestation {
+            revision: attestation.revision.max(1),
+            ..attestation
+        };
+        self.record_change(ChangeOp::Put, Some(&attestation), &attestation.id);
+        self.index_actors(&attestation);
         self.attestations
             .insert(attestation.id.clone(), attestation);
         Ok(())
@@ -58,13 +127,30 @@ impl AttestationStore for MemoryStore {
     }
 
     fn delete(&mut self, id: &str) -> StoreResult<bool> {
-        Ok(self.attestations.remove(id).is_some())
+        let Some(removed) = self.attestations.remove(id) else {
+            return Ok(false);
+        };
+        self.unindex_actors(&removed);
+        self.record_change(ChangeOp::Delete, None, id);
+        Ok(true)
     }
 
     fn update(&mut self, attestation: Attestation) -> StoreResult<()> {
-        if !self.attestations.contains_key(&attestation.id) {
+        let Some(stored) = self.attestations.get(&attestation.id) else {
             return Err(StoreError::NotFound(attestation.id));
+        };
+        attestation
+            .validate_confidence()
+            .map_err(StoreError::InvalidData)?;
+        let attestation = Attestation {
+            revision: stored.revision + 1,
+            ..attestation
+        };
+        self.record_change(ChangeOp::Update, Some(&attestation), &attestation.id);
+        if let Some(replaced) = self.attestations.remove(&attestation.id) {
+            self.unindex_actors(&replaced);
         }
+        self.index_actors(&attestation);
         self.attestations
             .insert(attestation.id.clone(), attestation);
         Ok(())
@@ -75,27 +161,54 @@ impl AttestationStore for MemoryStore {
     }
 
     fn clear(&mut self) -> StoreResult<()> {
-        self.attestations.clear();
+        self.actor_index.clear();
+        let mut ids: Vec<String> = self.attestations.drain().map(|(id, _)| id).collect();
+        ids.sort();
+        for id in ids {
+            self.record_change(ChangeOp::Delete, None, &id);
+        }
         Ok(())
     }
 }
 
+impl ChangeFeed for MemoryStore {
+    fn changes_since(&self, seq: u64, limit: usize) -> StoreResult<ChangeBatch> {
+        Ok(self.changes.since(seq, limit))
+    }
+
+    fn prune_changes(&mut self, retention: &ChangeRetention, now_ms: i64) -> StoreResult<usize> {
+        Ok(self.changes.prune(retention, now_ms))
+    }
+}
+
 impl QueryStore for MemoryStore {
     fn query(&self, filter: &AxFilter) -> StoreResult<AxResult> {
+        filter
+            .validate_aliases_expanded()
+            .map_err(StoreError::Query)?;
         let mut matching: Vec<Attestation> = self
-            .attestations
-            .values()
+            .candidates(filter)
+            .into_iter()
             .filter(|a| matches_filter(a, filter))
             .cloned()
             .collect();
 
+        if let Some(over) = &filter.over {
+            matching = over.retain_spanning(matching);
+        }
+
+        let matching_summary = filter
+            .include_summary
+            .then(|| MatchingSummary::from_attestations(&matching));
+
         // Apply limit
         if let Some(limit) = filter.limit {
             matching.truncate(limit);
         }
 
         // Build summary
-        let summary = build_summary(&matching);
+        let mut summary = build_summary(&matching);
+        summary.matching = matching_summary;
 
         Ok(AxResult {
             attestations: matching,
@@ -218,7 +331,15 @@ fn matches_filter(attestation: &Attestation, filter: &AxFilter) -> bool {
         }
     }
 
-    true
+    // Check confidence
+    if let Some(min) = filter.min_confidence {
+        if !attestation.confidence.is_some_and(|c| c >= min) {
+            return false;
+        }
+    }
+
+    // Check attribute conditions
+    filter.matches_attributes(&attestation.attributes)
 }
 
 /// Build a summary from a list of attestations.
@@ -229,6 +350,7 @@ fn build_summary(attestations: &[Attestation]) -> AxSummary {
         unique_predicates: HashMap::new(),
         unique_contexts: HashMap::new(),
         unique_actors: HashMap::new(),
+        matching: None,
     };
 
     for attestation in attestations {
@@ -255,7 +377,8 @@ fn build_summary(attestations: &[Attestation]) -> AxSummary {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::attestation::AttestationBuilder;
+    use crate::attestation::{AttestationBuilder, AttrCondition, AttrOp, AttrValue, OverFilter};
+    use crate::storage::traits::PutOutcome;
 
     fn test_attestation(id: &str) -> Attestation {
         AttestationBuilder::new()
@@ -292,6 +415,41 @@ mod tests {
         assert!(matches!(result, Err(StoreError::AlreadyExists(_))));
     }
 
+    #[test]
+    fn test_put_if_revision() {
+        let mut store = MemoryStore::new();
+        let mut attestation = test_attestation("AS-test-1");
+
+        store.put(attestation.clone()).unwrap();
+        assert_eq!(store.get("AS-test-1").unwrap().unwrap().revision, 1);
+
+        attestation.subjects = vec!["BOB".to_string()];
+        store.update(attestation.clone()).unwrap();
+        assert_eq!(store.get("AS-test-1").unwrap().unwrap().revision, 2);
+
+        // A writer still holding revision 1 loses and sees the current value
+        attestation.subjects = vec!["CAROL".to_string()];
+        match store.put_if_revision(attestation.clone(), 1).unwrap() {
+            PutOutcome::Conflict { current } => {
+                let current = current.unwrap();
+                assert_eq!(current.subjects, vec!["BOB"]);
+                assert_eq!(current.revision, 2);
+            }
+            other => panic!("expected conflict, got {:?}", other),
+        }
+
+        assert_eq!(
+            store.put_if_revision(attestation, 2).unwrap(),
+            PutOutcome::Written { revision: 3 }
+        );
+        assert_eq!(
+            store
+                .put_if_revision(test_attestation("AS-test-2"), 0)
+                .unwrap(),
+            PutOutcome::Written { revision: 1 }
+        );
+    }
+
     #[test]
     fn test_delete() {
         let mut store = MemoryStore::new();
@@ -382,6 +540,67 @@ mod tests {
         assert_eq!(result.attestations[0].subjects, vec!["ALICE"]);
     }
 
+    #[test]
+    fn test_query_by_actor_uses_index() {
+        let at = |id: &str, subject: &str, actors: &[&str]| {
+            let mut builder = AttestationBuilder::new()
+                .id(id)
+                .subject(subject)
+                .predicate("knows");
+            for actor in actors {
+                builder = builder.actor(*actor);
+            }
+            builder.build()
+        };
+
+        let mut store = MemoryStore::new();
+        store.put(at("AS-1", "ALICE", &["human:bob"])).unwrap();
+        store
+            .put(at("AS-2", "BOB", &["human:bob", "llm:gpt"]))
+            .unwrap();
+        store.put(at("AS-3", "CAROL", &["llm:gpt"])).unwrap();
+
+        let ids = |store: &MemoryStore, subjects: &[&str], actors: &[&str]| {
+            let filter = AxFilter {
+                subjects: subjects.iter().map(|s| s.to_string()).collect(),
+                actors: actors.iter().map(|s| s.to_string()).collect(),
+                ..Default::default()
+            };
+            let mut ids: Vec<String> = store
+                .query(&filter)
+                .unwrap()
+                .attestations
+                .into_iter()
+                .map(|a| a.id)
+                .collect();
+            ids.sort();
+            ids
+        };
+
+        assert_eq!(ids(&store, &[], &["human:bob"]), vec!["AS-1", "AS-2"]);
+        // An attestation naming two filtered actors is listed once
+        assert_eq!(
+            ids(&store, &[], &["human:bob", "llm:gpt"]),
+            vec!["AS-1", "AS-2", "AS-3"]
+        );
+        assert_eq!(ids(&store, &["BOB"], &["llm:gpt"]), vec!["AS-2"]);
+        assert!(ids(&store, &[], &["human:nobody"]).is_empty());
+
+        // Updates and deletes keep the index current
+        store
+            .update(at("AS-2", "BOB", &["llm:gpt", "human:carol"]))
+            .unwrap();
+        assert_eq!(ids(&store, &[], &["human:bob"]), vec!["AS-1"]);
+        assert_eq!(ids(&store, &[], &["llm:gpt"]), vec!["AS-2", "AS-3"]);
+        store.delete("AS-1").unwrap();
+        assert!(ids(&store, &[], &["human:bob"]).is_empty());
+        assert!(!store.actor_index.contains_key("human:bob"));
+
+        store.clear().unwrap();
+        assert!(store.actor_index.is_empty());
+        assert!(ids(&store, &[], &["llm:gpt"]).is_empty());
+    }
+
     #[test]
     fn test_stats() {
         let mut store = MemoryStore::new();
@@ -416,4 +635,253 @@ mod tests {
         assert_eq!(stats.unique_contexts, 1); // Both have "work"
         assert_eq!(stats.unique_actors, 2);
     }
+
+    #[test]
+    fn test_query_over() {
+        const YEAR_MS: i64 = 365 * 86_400_000;
+        let at = |id: &str, subject: &str, ts: i64| {
+            AttestationBuilder::new()
+                .id(id)
+                .subject(subject)
+                .predicate("member")
+                .context("guild")
+                .timestamp(ts)
+                .build()
+        };
+
+        let mut store = MemoryStore::new();
+        store.put(at("AS-1", "ALICE", 0)).unwrap();
+        store.put(at("AS-2", "ALICE", 6 * YEAR_MS)).unwrap();
+        store.put(at("AS-3", "BOB", 0)).unwrap();
+        store.put(at("AS-4", "BOB", 4 * YEAR_MS)).unwrap();
+
+        let filter = AxFilter {
+            over: Some(OverFilter::new(5 * YEAR_MS)),
+            ..Default::default()
+        };
+        let mut ids: Vec<String> = store
+            .query(&filter)
+            .unwrap()
+            .attestations
+            .into_iter()
+            .map(|a| a.id)
+            .collect();
+        ids.sort();
+        assert_eq!(ids, vec!["AS-1", "AS-2"]);
+
+        // Span is computed within the time range: cutting off ALICE's first
+        // attestation leaves a single point, which spans nothing.
+        let filter = AxFilter {
+            time_start: Some(YEAR_MS),
+            over: Some(OverFilter::new(5 * YEAR_MS)),
+            ..Default::default()
+        };
+        assert!(store.query(&filter).unwrap().attestations.is_empty());
+    }
+
+    #[test]
+    fn test_query_matching_summary() {
+        let at = |id: &str, subject: &str, predicate: &str, ts: i64| {
+            AttestationBuilder::new()
+                .id(id)
+                .subject(subject)
+                .predicate(predicate)
+                .context("work")
+                .actor("human:bob")
+                .timestamp(ts)
+                .build()
+        };
+
+        let mut store = MemoryStore::new();
+        store.put(at("AS-1", "ALICE", "knows", 3000)).unwrap();
+        store.put(at("AS-2", "BOB", "knows", 1000)).unwrap();
+        store.put(at("AS-3", "CAROL", "works_at", 2000)).unwrap();
+
+        // Off by default
+        let filter = AxFilter {
+            limit: Some(1),
+            ..Default::default()
+        };
+        assert!(store.query(&filter).unwrap().summary.matching.is_none());
+
+        let filter = AxFilter {
+            limit: Some(1),
+            include_summary: true,
+            ..Default::default()
+        };
+        let summary = store.query(&filter).unwrap().summary;
+        assert_eq!(summary.total_attestations, 1);
+
+        let matching = summary.matching.unwrap();
+        assert_eq!(matching.total, 3);
+        assert_eq!(matching.distinct_subjects, 3);
+        assert_eq!(matching.distinct_predicates, 2);
+        assert_eq!(matching.distinct_contexts, 1);
+        assert_eq!(matching.distinct_actors, 1);
+        assert_eq!(matching.top_predicates[0].value, "knows");
+        assert_eq!(matching.top_predicates[0].count, 2);
+        assert_eq!(matching.earliest, Some(1000));
+        assert_eq!(matching.latest, Some(3000));
+    }
+
+    #[test]
+    fn test_query_attribute_conditions() {
+        let at = |id: &str, attributes: serde_json::Value| Attestation {
+            id: id.to_string(),
+            subjects: vec!["ALICE".to_string()],
+            attributes: serde_json::from_value(attributes).unwrap(),
+            ..Default::default()
+        };
+
+        let mut store = MemoryStore::new();
+        store
+            .put(at(
+                "AS-1",
+                serde_json::json!({"severity": 5, "status": "open"}),
+            ))
+            .unwrap();
+        store
+            .put(at(
+                "AS-2",
+                serde_json::json!({"severity": 1, "status": "open"}),
+            ))
+            .unwrap();
+        store
+            .put(at("AS-3", serde_json::json!({"severity": "5"})))
+            .unwrap();
+        store.put(at("AS-4", serde_json::json!({}))).unwrap();
+
+        let ids = |conditions: Vec<AttrCondition>| {
+            let filter = AxFilter {
+                attribute_conditions: conditions,
+                ..Default::default()
+            };
+            let mut ids: Vec<String> = store
+                .query(&filter)
+                .unwrap()
+                .attestations
+                .into_iter()
+                .map(|a| a.id)
+                .collect();
+            ids.sort();
+            ids
+        };
+
+        let severity = |op, n| AttrCondition::new("severity", op, AttrValue::Number(n));
+        let open = AttrCondition::new("status", AttrOp::Eq, AttrValue::String("open".into()));
+        assert_eq!(ids(vec![severity(AttrOp::Gte, 3.0)]), vec!["AS-1"]);
+        assert_eq!(ids(vec![severity(AttrOp::Lt, 3.0), open]), vec!["AS-2"]);
+        assert_eq!(ids(vec![severity(AttrOp::Ne, 5.0)]), vec!["AS-2", "AS-3"]);
+        assert_eq!(
+            ids(vec![AttrCondition::new(
+                "status",
+                AttrOp::Exists,
+                AttrValue::Bool(false)
+            )]),
+            vec!["AS-3", "AS-4"]
+        );
+    }
+
+    #[test]
+    fn test_query_min_confidence() {
+        let at = |id: &str, confidence: Option<f32>| Attestation {
+            id: id.to_string(),
+            subjects: vec!["ALICE".to_string()],
+            confidence,
+            ..Default::default()
+        };
+
+        let mut store = MemoryStore::new();
+        store.put(at("AS-1", Some(0.95))).unwrap();
+        store.put(at("AS-2", Some(0.4))).unwrap();
+        store.put(at("AS-3", None)).unwrap();
+
+        let filter = AxFilter {
+            min_confidence: Some(0.5),
+            ..Default::default()
+        };
+        let ids: Vec<String> = store
+            .query(&filter)
+            .unwrap()
+            .attestations
+            .into_iter()
+            .map(|a| a.id)
+            .collect();
+        assert_eq!(ids, vec!["AS-1"]);
+
+        let filter = AxFilter {
+            include_summary: true,
+            ..Default::default()
+        };
+        let matching = store.query(&filter).unwrap().summary.matching.unwrap();
+        let mut expected = vec![0; 10];
+        expected[4] = 1;
+        expected[9] = 1;
+        assert_eq!(matching.confidence_histogram, expected);
+
+        let err = store.put(at("AS-4", Some(1.5))).unwrap_err();
+        assert!(matches!(err, StoreError::InvalidData(_)));
+        let err = store.update(at("AS-1", Some(-0.1))).unwrap_err();
+        assert!(matches!(err, StoreError::InvalidData(_)));
+    }
+
+    #[test]
+    fn test_changefeed_orders_writes() {
+        let mut store = MemoryStore::new();
+        store.set_change_clock(|| 1_000);
+        store.put(test_attestation("AS-1")).unwrap();
+        store.put(test_attestation("AS-2")).unwrap();
+        store.delete("AS-1").unwrap();
+        assert!(!store.delete("AS-1").unwrap());
+        store.update(test_attestation("AS-2")).unwrap();
+        store.put(test_attestation("AS-3")).unwrap();
+
+        let batch = store.changes_since(0, 100).unwrap();
+        let ops: Vec<(u64, ChangeOp, &str)> = batch
+            .events
+            .iter()
+            .map(|e| (e.seq, e.op, e.attestation_id.as_str()))
+            .collect();
+        assert_eq!(
+            ops,
+            vec![
+                (1, ChangeOp::Put, "AS-1"),
+                (2, ChangeOp::Put, "AS-2"),
+                (3, ChangeOp::Delete, "AS-1"),
+                (4, ChangeOp::Update, "AS-2"),
+                (5, ChangeOp::Put, "AS-3"),
+            ]
+        );
+        assert_eq!(batch.latest_seq, 5);
+        assert_eq!(
+            batch.events[1].content_hash,
+            Some(store.get("AS-2").unwrap().unwrap().content_hash())
+        );
+        assert!(batch.events[2].content_hash.is_none());
+        assert_eq!(batch.events[0].at, 1_000);
+
+        // Resume mid-stream
+        let resumed = store.changes_since(3, 1).unwrap();
+        assert_eq!(resumed.events[0].seq, 4);
+        assert_eq!(resumed.events.len(), 1);
+
+        store.clear().unwrap();
+        let cleared = store.changes_since(5, 100).unwrap();
+        assert_eq!(cleared.events.len(), 2);
+        assert!(cleared.events.iter().all(|e| e.op == ChangeOp::Delete));
+        assert_eq!(store.latest_seq().unwrap(), 7);
+
+        let pruned = store
+            .prune_changes(
+                &ChangeRetention {
+                    max_events: Some(1),
+                    ..Default::default()
+                },
+                0,
+            )
+            .unwrap();
+        assert_eq!(pruned, 6);
+        assert_eq!(store.latest_seq().unwrap(), 7);
+        assert!(store.changes_since(2, 100).unwrap().missed);
+    }
 }
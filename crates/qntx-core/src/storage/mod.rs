@@ -33,13 +33,28 @@
 //! - `qntx-sqlite`: SQLite backend for native platforms (Tauri, server)
 //! - `qntx-indexeddb`: IndexedDB backend for browser WASM (async API matching
 //!   the same trait contract)
+//!
+//! # Changefeed
+//!
+//! Stores implementing [`ChangeFeed`] number every write so consumers can
+//! ask for what changed since the last sequence number they saw; see
+//! [`changes`].
 
+pub mod cascade;
+pub mod changes;
 pub mod enforcement;
 mod error;
 mod memory;
 mod traits;
 
+pub use cascade::{
+    find_orphans, references_id, DeleteCoordinator, DeleteHook, DeleteOptions, DeleteReport,
+    HookReport,
+};
+pub use changes::{
+    ChangeBatch, ChangeClock, ChangeEvent, ChangeFeed, ChangeLog, ChangeOp, ChangeRetention,
+};
 pub use enforcement::{EnforcementConfig, EnforcementEvent, EnforcementInput, EvictionDetails};
-pub use error::StoreError;
+pub use error::{StorageErrorKind, StoreError};
 pub use memory::MemoryStore;
-pub use traits::{AttestationStore, QueryStore, StorageStats};
+pub use traits::{AttestationStore, PutOutcome, QueryStore, StorageStats};
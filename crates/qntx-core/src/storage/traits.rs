@@ -2,6 +2,7 @@
 
 use crate::attestation::{Attestation, AxFilter, AxResult};
 use crate::storage::error::StoreResult;
+use serde::{Deserialize, Serialize};
 
 /// Core storage operations for attestations.
 ///
@@ -16,6 +17,15 @@ pub trait AttestationStore {
     /// If an attestation with the same ID already exists, returns `StoreError::AlreadyExists`.
     fn put(&mut self, attestation: Attestation) -> StoreResult<()>;
 
+    /// Store several attestations, returning one result per attestation, in order.
+    ///
+    /// Backends that can group writes override it to commit once for the lot
+    /// instead of once per attestation. The default calls [`put`](Self::put)
+    /// for each.
+    fn put_many(&mut self, attestations: Vec<Attestation>) -> Vec<StoreResult<()>> {
+        attestations.into_iter().map(|a| self.put(a)).collect()
+    }
+
     /// Retrieve an attestation by ID.
     ///
     /// Returns `None` if not found.
@@ -33,9 +43,44 @@ pub trait AttestationStore {
 
     /// Update an existing attestation.
     ///
+    /// The stored revision is incremented; the incoming `revision` is ignored.
     /// Returns `StoreError::NotFound` if the attestation doesn't exist.
     fn update(&mut self, attestation: Attestation) -> StoreResult<()>;
 
+    /// Write an attestation only if the stored revision equals `expected_revision`.
+    ///
+    /// `expected_revision` 0 means "must not exist yet" and creates the attestation
+    /// at revision 1; any other value updates it to `expected_revision + 1`. On a
+    /// mismatch nothing is written and `PutOutcome::Conflict` carries the current
+    /// stored attestation (`None` if it doesn't exist), so the caller can merge
+    /// and retry.
+    ///
+    /// The default implementation reads then writes, which is atomic for stores
+    /// only reachable through `&mut self`. Backends shared across connections
+    /// must override it.
+    fn put_if_revision(
+        &mut self,
+        attestation: Attestation,
+        expected_revision: u64,
+    ) -> StoreResult<PutOutcome> {
+        let current = self.get(&attestation.id)?;
+        if current.as_ref().map_or(0, |a| a.revision) != expected_revision {
+            return Ok(PutOutcome::Conflict {
+                current: current.map(Box::new),
+            });
+        }
+        match current {
+            Some(_) => self.update(attestation)?,
+            None => self.put(Attestation {
+                revision: 1,
+                ..attestation
+            })?,
+        }
+        Ok(PutOutcome::Written {
+            revision: expected_revision + 1,
+        })
+    }
+
     /// Get all attestation IDs.
     fn ids(&self) -> StoreResult<Vec<String>>;
 
@@ -48,6 +93,16 @@ pub trait AttestationStore {
     fn clear(&mut self) -> StoreResult<()>;
 }
 
+/// Result of [`AttestationStore::put_if_revision`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum PutOutcome {
+    /// The attestation was stored at this revision
+    Written { revision: u64 },
+    /// The expectation failed; nothing was written
+    Conflict { current: Option<Box<Attestation>> },
+}
+
 /// Extended query operations for attestation retrieval.
 ///
 /// This trait provides more advanced query capabilities beyond basic CRUD.
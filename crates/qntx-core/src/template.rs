@@ -0,0 +1,826 @@
+//! Attestation templates: parameterized attestations for repetitive entry.
+//!
+//! A template fixes some slots and leaves `{{param}}` placeholders for the
+//! rest, e.g. subject `{{person}}`, predicate `attended`, context
+//! `meeting-{{meeting}}`. [`AttestationTemplate::instantiate`] fills them in:
+//!
+//! - **Slots** are text. Placeholders are replaced by the param's value; a slot
+//!   that ends up empty (an unset optional param) is left out, and a slot kind
+//!   left with no value at all is an error.
+//! - **Attributes** are interpolated recursively. A string that is exactly one
+//!   placeholder becomes the param's typed value (number, boolean or string,
+//!   per [`ParamKind`]), or disappears when the param is unset. Placeholders
+//!   inside longer text are interpolated as text, unset ones as "".
+//! - **Params** are declared once. A missing required param without a default,
+//!   a value of the wrong kind, a value not fully matching `validation`, and a
+//!   param the template doesn't declare are all errors. Defaults go through
+//!   the same checks. Every problem is reported, not just the first.
+//!
+//! Templates are stored as attestations (`[name] template qntx`, the template
+//! body in the attributes), so they sync like any other data;
+//! [`TemplateLibrary`] reads them back.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use thiserror::Error;
+
+use crate::attestation::{Attestation, AttestationBuilder};
+
+/// Predicate marking an attestation as a template definition
+pub const TEMPLATE_PREDICATE: &str = "template";
+
+/// Context template definitions are attested in
+pub const TEMPLATE_CONTEXT: &str = "qntx";
+
+/// Slot of the attestation a template value fills
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SlotKind {
+    Subject,
+    Predicate,
+    Context,
+    Actor,
+}
+
+impl SlotKind {
+    pub const ALL: [SlotKind; 4] = [
+        SlotKind::Subject,
+        SlotKind::Predicate,
+        SlotKind::Context,
+        SlotKind::Actor,
+    ];
+}
+
+impl std::fmt::Display for SlotKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Subject => write!(f, "subject"),
+            Self::Predicate => write!(f, "predicate"),
+            Self::Context => write!(f, "context"),
+            Self::Actor => write!(f, "actor"),
+        }
+    }
+}
+
+/// Value type of a param, applied when a placeholder stands alone in an
+/// attribute
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ParamKind {
+    #[default]
+    String,
+    /// Integer or finite decimal
+    Number,
+    /// `true` or `false`
+    Boolean,
+}
+
+impl std::fmt::Display for ParamKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::String => write!(f, "string"),
+            Self::Number => write!(f, "number"),
+            Self::Boolean => write!(f, "boolean"),
+        }
+    }
+}
+
+fn default_required() -> bool {
+    true
+}
+
+/// A template parameter
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TemplateParam {
+    pub name: String,
+    #[serde(default)]
+    pub kind: ParamKind,
+    /// Required params need a value or a default (default: true)
+    #[serde(default = "default_required")]
+    pub required: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default: Option<String>,
+    /// Regex the whole value must match
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub validation: Option<String>,
+}
+
+/// One slot value, fixed text or containing `{{param}}` placeholders
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TemplateSlot {
+    pub slot: SlotKind,
+    pub value: String,
+}
+
+/// A parameterized attestation.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AttestationTemplate {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub params: Vec<TemplateParam>,
+    #[serde(default)]
+    pub slots: Vec<TemplateSlot>,
+    /// Attribute values, interpolated recursively
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub attributes: BTreeMap<String, Value>,
+}
+
+/// Why a template could not be read or instantiated.
+#[derive(Debug, Clone, PartialEq, Eq, Error, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum TemplateError {
+    #[error("missing required param '{param}'")]
+    MissingParam { param: String },
+
+    #[error("param '{param}' is not declared by the template")]
+    UnexpectedParam { param: String },
+
+    #[error("param '{param}' value '{value}' does not match {pattern}")]
+    ValidationFailed {
+        param: String,
+        value: String,
+        pattern: String,
+    },
+
+    #[error("param '{param}' value '{value}' is not a {expected}")]
+    WrongKind {
+        param: String,
+        value: String,
+        expected: ParamKind,
+    },
+
+    #[error("param '{param}' has an invalid validation pattern: {message}")]
+    InvalidPattern { param: String, message: String },
+
+    #[error("placeholder '{{{{{placeholder}}}}}' does not name a declared param")]
+    UnknownPlaceholder { placeholder: String },
+
+    #[error("no {slot} after filling in params")]
+    EmptySlot { slot: SlotKind },
+
+    #[error("invalid template: {message}")]
+    InvalidTemplate { message: String },
+}
+
+/// A param value after resolution: None when optional and unset
+type Resolved<'a> = HashMap<&'a str, Option<(&'a TemplateParam, String)>>;
+
+impl AttestationTemplate {
+    /// Structural problems independent of any params: duplicate or unnamed
+    /// params, bad patterns, placeholders naming undeclared params.
+    pub fn validate(&self) -> Vec<TemplateError> {
+        let mut errors = Vec::new();
+        if self.name.trim().is_empty() {
+            errors.push(TemplateError::InvalidTemplate {
+                message: "name is empty".to_string(),
+            });
+        }
+
+        let mut seen = HashSet::new();
+        for param in &self.params {
+            if param.name.trim().is_empty() {
+                errors.push(TemplateError::InvalidTemplate {
+                    message: "param with an empty name".to_string(),
+                });
+            } else if !seen.insert(param.name.as_str()) {
+                errors.push(TemplateError::InvalidTemplate {
+                    message: format!("param '{}' is declared twice", param.name),
+                });
+            }
+            if let Err(e) = param.pattern() {
+                errors.push(e);
+            }
+        }
+
+        let mut unknown = Vec::new();
+        let mut check = |text: &str| {
+            for name in placeholders(text) {
+                if !seen.contains(name) && !unknown.contains(&name.to_string()) {
+                    unknown.push(name.to_string());
+                }
+            }
+        };
+        for slot in &self.slots {
+            check(&slot.value);
+        }
+        for value in self.attributes.values() {
+            visit_strings(value, &mut check);
+        }
+        errors.extend(
+            unknown
+                .into_iter()
+                .map(|placeholder| TemplateError::UnknownPlaceholder { placeholder }),
+        );
+        errors
+    }
+
+    /// Fill in `params`, returning the attestation without ID or timestamp
+    /// (the caller assigns those), or every problem found.
+    pub fn instantiate(
+        &self,
+        params: &HashMap<String, String>,
+    ) -> Result<Attestation, Vec<TemplateError>> {
+        let mut errors = self.validate();
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+
+        let mut unexpected: Vec<&String> = params
+            .keys()
+            .filter(|name| !self.params.iter().any(|p| &p.name == *name))
+            .collect();
+        unexpected.sort();
+        errors.extend(
+            unexpected
+                .into_iter()
+                .map(|param| TemplateError::UnexpectedParam {
+                    param: param.clone(),
+                }),
+        );
+
+        let mut resolved: Resolved = HashMap::new();
+        for param in &self.params {
+            match param.resolve(params.get(&param.name)) {
+                Ok(value) => {
+                    resolved.insert(param.name.as_str(), value.map(|v| (param, v)));
+                }
+                Err(e) => errors.push(e),
+            }
+        }
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+
+        let mut slots: HashMap<SlotKind, Vec<String>> = HashMap::new();
+        for slot in &self.slots {
+            let value = interpolate(&slot.value, &resolved);
+            let value = value.trim();
+            if !value.is_empty() {
+                slots.entry(slot.slot).or_default().push(value.to_string());
+            }
+        }
+        for kind in SlotKind::ALL {
+            if !slots.contains_key(&kind) {
+                errors.push(TemplateError::EmptySlot { slot: kind });
+            }
+        }
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+        let mut take = |kind| slots.remove(&kind).unwrap_or_default();
+
+        let mut builder = AttestationBuilder::new()
+            .subjects(take(SlotKind::Subject))
+            .predicates(take(SlotKind::Predicate))
+            .contexts(take(SlotKind::Context))
+            .actors(take(SlotKind::Actor))
+            .source(format!("template:{}", self.name));
+        for (key, value) in &self.attributes {
+            if let Some(value) = fill_value(value, &resolved) {
+                builder = builder.attribute(key.clone(), value);
+            }
+        }
+        Ok(builder.build())
+    }
+
+    /// This template as a definition attestation: `[name] template qntx`,
+    /// with the rest of the template in the attributes.
+    pub fn to_attestation(
+        &self,
+        id: impl Into<String>,
+        actor: impl Into<String>,
+        timestamp: i64,
+    ) -> Attestation {
+        let mut builder = AttestationBuilder::new()
+            .id(id)
+            .subject(self.name.clone())
+            .predicate(TEMPLATE_PREDICATE)
+            .context(TEMPLATE_CONTEXT)
+            .actor(actor)
+            .timestamp(timestamp)
+            .source("template");
+        if let Ok(Value::Object(fields)) = serde_json::to_value(self) {
+            for (key, value) in fields {
+                if key != "name" {
+                    builder = builder.attribute(key, value);
+                }
+            }
+        }
+        builder.build()
+    }
+
+    /// Read a template back from its definition attestation.
+    pub fn from_attestation(attestation: &Attestation) -> Result<Self, TemplateError> {
+        let invalid = |message: String| TemplateError::InvalidTemplate { message };
+        if !is_template_definition(attestation) {
+            return Err(invalid(format!(
+                "{} is not a template definition",
+                attestation.id
+            )));
+        }
+        let name = attestation
+            .subjects
+            .first()
+            .ok_or_else(|| invalid(format!("{} has no subject", attestation.id)))?;
+
+        let mut fields: Map<String, Value> = attestation
+            .attributes
+            .iter()
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect();
+        fields.insert("name".to_string(), Value::String(name.clone()));
+        serde_json::from_value(Value::Object(fields))
+            .map_err(|e| invalid(format!("{}: {}", attestation.id, e)))
+    }
+}
+
+impl TemplateParam {
+    /// The validation pattern, anchored to the whole value
+    fn pattern(&self) -> Result<Option<Regex>, TemplateError> {
+        let Some(pattern) = &self.validation else {
+            return Ok(None);
+        };
+        Regex::new(&format!("^(?:{})$", pattern))
+            .map(Some)
+            .map_err(|e| TemplateError::InvalidPattern {
+                param: self.name.clone(),
+                message: e.to_string(),
+            })
+    }
+
+    /// The checked value of this param, None when optional and unset
+    fn resolve(&self, given: Option<&String>) -> Result<Option<String>, TemplateError> {
+        let Some(value) = given.or(self.default.as_ref()) else {
+            return if self.required {
+                Err(TemplateError::MissingParam {
+                    param: self.name.clone(),
+                })
+            } else {
+                Ok(None)
+            };
+        };
+
+        if typed_value(self.kind, value).is_none() {
+            return Err(TemplateError::WrongKind {
+                param: self.name.clone(),
+                value: value.clone(),
+                expected: self.kind,
+            });
+        }
+        if let Some(pattern) = self.pattern()? {
+            if !pattern.is_match(value) {
+                return Err(TemplateError::ValidationFailed {
+                    param: self.name.clone(),
+                    value: value.clone(),
+                    pattern: self.validation.clone().unwrap_or_default(),
+                });
+            }
+        }
+        Ok(Some(value.clone()))
+    }
+}
+
+/// `value` as JSON of `kind`, None if it doesn't parse as that kind
+fn typed_value(kind: ParamKind, value: &str) -> Option<Value> {
+    match kind {
+        ParamKind::String => Some(Value::String(value.to_string())),
+        ParamKind::Number => {
+            let value = value.trim();
+            if let Ok(i) = value.parse::<i64>() {
+                return Some(Value::from(i));
+            }
+            value
+                .parse::<f64>()
+                .ok()
+                .and_then(serde_json::Number::from_f64)
+                .map(Value::Number)
+        }
+        ParamKind::Boolean => match value.trim() {
+            "true" => Some(Value::Bool(true)),
+            "false" => Some(Value::Bool(false)),
+            _ => None,
+        },
+    }
+}
+
+/// Names of the `{{param}}` placeholders in `text`, in order
+fn placeholders(text: &str) -> Vec<&str> {
+    let mut names = Vec::new();
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        let after = &rest[start + 2..];
+        let Some(end) = after.find("}}") else {
+            break;
+        };
+        names.push(after[..end].trim());
+        rest = &after[end + 2..];
+    }
+    names
+}
+
+/// The param name if `text` is exactly one placeholder
+fn sole_placeholder(text: &str) -> Option<&str> {
+    let inner = text.trim().strip_prefix("{{")?.strip_suffix("}}")?;
+    (!inner.contains("{{") && !inner.contains("}}")).then(|| inner.trim())
+}
+
+/// Replace placeholders with their values as text, unset ones with ""
+fn interpolate(text: &str, resolved: &Resolved) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        let after = &rest[start + 2..];
+        let Some(end) = after.find("}}") else {
+            break;
+        };
+        out.push_str(&rest[..start]);
+        if let Some(Some((_, value))) = resolved.get(after[..end].trim()) {
+            out.push_str(value);
+        }
+        rest = &after[end + 2..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Interpolate an attribute value; None drops it (an unset sole placeholder)
+fn fill_value(value: &Value, resolved: &Resolved) -> Option<Value> {
+    match value {
+        Value::String(text) => match sole_placeholder(text) {
+            Some(name) => {
+                let (param, value) = resolved.get(name)?.as_ref()?;
+                typed_value(param.kind, value)
+            }
+            None => Some(Value::String(interpolate(text, resolved))),
+        },
+        Value::Array(items) => Some(Value::Array(
+            items
+                .iter()
+                .filter_map(|item| fill_value(item, resolved))
+                .collect(),
+        )),
+        Value::Object(fields) => Some(Value::Object(
+            fields
+                .iter()
+                .filter_map(|(k, v)| Some((k.clone(), fill_value(v, resolved)?)))
+                .collect(),
+        )),
+        other => Some(other.clone()),
+    }
+}
+
+fn visit_strings<'a>(value: &'a Value, f: &mut impl FnMut(&'a str)) {
+    match value {
+        Value::String(text) => f(text),
+        Value::Array(items) => items.iter().for_each(|item| visit_strings(item, f)),
+        Value::Object(fields) => fields.values().for_each(|v| visit_strings(v, f)),
+        _ => {}
+    }
+}
+
+fn is_template_definition(attestation: &Attestation) -> bool {
+    attestation
+        .predicates
+        .iter()
+        .any(|p| p == TEMPLATE_PREDICATE)
+}
+
+/// A template definition that could not be read
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TemplateLoadError {
+    pub attestation_id: String,
+    pub message: String,
+}
+
+/// Templates by name, read from definition attestations.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TemplateLibrary {
+    templates: Vec<AttestationTemplate>,
+    errors: Vec<TemplateLoadError>,
+}
+
+impl TemplateLibrary {
+    /// Build from attestations; anything that isn't a template definition is
+    /// ignored. When a name is defined more than once the newest definition
+    /// wins (on equal timestamps the larger ID).
+    pub fn from_attestations<'a>(attestations: impl IntoIterator<Item = &'a Attestation>) -> Self {
+        let mut newest: BTreeMap<String, (i64, &str, AttestationTemplate)> = BTreeMap::new();
+        let mut errors = Vec::new();
+        for attestation in attestations {
+            if !is_template_definition(attestation) {
+                continue;
+            }
+            let template = match AttestationTemplate::from_attestation(attestation) {
+                Ok(template) => template,
+                Err(e) => {
+                    errors.push(TemplateLoadError {
+                        attestation_id: attestation.id.clone(),
+                        message: e.to_string(),
+                    });
+                    continue;
+                }
+            };
+            let key = (attestation.timestamp, attestation.id.as_str());
+            match newest.get(&template.name) {
+                Some((ts, id, _)) if (*ts, *id) >= key => {}
+                _ => {
+                    newest.insert(template.name.clone(), (key.0, key.1, template));
+                }
+            }
+        }
+        Self {
+            templates: newest.into_values().map(|(_, _, t)| t).collect(),
+            errors,
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&AttestationTemplate> {
+        self.templates.iter().find(|t| t.name == name)
+    }
+
+    /// Templates sorted by name
+    pub fn templates(&self) -> &[AttestationTemplate] {
+        &self.templates
+    }
+
+    pub fn errors(&self) -> &[TemplateLoadError] {
+        &self.errors
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::{AttestationStore, MemoryStore, QueryStore};
+    use crate::AxFilter;
+    use serde_json::json;
+
+    fn meeting_template() -> AttestationTemplate {
+        serde_json::from_value(json!({
+            "name": "attended-meeting",
+            "description": "X attended meeting Y",
+            "params": [
+                {"name": "person", "validation": "[A-Z][A-Z0-9_]*"},
+                {"name": "meeting"},
+                {"name": "actor", "default": "human:me"},
+                {"name": "minutes", "kind": "number", "required": false},
+                {"name": "remote", "kind": "boolean", "default": "false"},
+                {"name": "cohost", "required": false}
+            ],
+            "slots": [
+                {"slot": "subject", "value": "{{person}}"},
+                {"slot": "subject", "value": "{{ cohost }}"},
+                {"slot": "predicate", "value": "attended"},
+                {"slot": "context", "value": "meeting-{{meeting}}"},
+                {"slot": "actor", "value": "{{actor}}"}
+            ],
+            "attributes": {
+                "duration_min": "{{minutes}}",
+                "remote": "{{remote}}",
+                "note": "{{person}} at {{meeting}} ({{minutes}} min)",
+                "tags": ["meeting", "{{cohost}}"]
+            }
+        }))
+        .unwrap()
+    }
+
+    fn params(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn instantiate_fills_slots_and_defaults() {
+        let template = meeting_template();
+        assert!(template.validate().is_empty());
+
+        let a = template
+            .instantiate(&params(&[("person", "ALICE"), ("meeting", "standup")]))
+            .unwrap();
+        assert_eq!(a.subjects, ["ALICE"]); // unset cohost slot is left out
+        assert_eq!(a.predicates, ["attended"]);
+        assert_eq!(a.contexts, ["meeting-standup"]);
+        assert_eq!(a.actors, ["human:me"]);
+        assert_eq!(a.source, "template:attended-meeting");
+        assert!(a.id.is_empty());
+        assert_eq!(a.timestamp, 0);
+
+        let b = template
+            .instantiate(&params(&[
+                ("person", "ALICE"),
+                ("meeting", "standup"),
+                ("cohost", "BOB"),
+                ("actor", "human:carol"),
+            ]))
+            .unwrap();
+        assert_eq!(b.subjects, ["ALICE", "BOB"]);
+        assert_eq!(b.actors, ["human:carol"]);
+    }
+
+    #[test]
+    fn attributes_interpolate_with_types() {
+        let template = meeting_template();
+
+        let a = template
+            .instantiate(&params(&[
+                ("person", "ALICE"),
+                ("meeting", "standup"),
+                ("minutes", "15"),
+                ("remote", "true"),
+                ("cohost", "BOB"),
+            ]))
+            .unwrap();
+        assert_eq!(a.attributes["duration_min"], json!(15));
+        assert_eq!(a.attributes["remote"], json!(true));
+        assert_eq!(a.attributes["note"], json!("ALICE at standup (15 min)"));
+        assert_eq!(a.attributes["tags"], json!(["meeting", "BOB"]));
+
+        // Unset optional params: sole placeholders disappear, embedded ones are ""
+        let b = template
+            .instantiate(&params(&[("person", "ALICE"), ("meeting", "standup")]))
+            .unwrap();
+        assert!(!b.attributes.contains_key("duration_min"));
+        assert_eq!(b.attributes["remote"], json!(false)); // default, typed
+        assert_eq!(b.attributes["note"], json!("ALICE at standup ( min)"));
+        assert_eq!(b.attributes["tags"], json!(["meeting"]));
+
+        let c = template
+            .instantiate(&params(&[
+                ("person", "ALICE"),
+                ("meeting", "standup"),
+                ("minutes", "7.5"),
+            ]))
+            .unwrap();
+        assert_eq!(c.attributes["duration_min"], json!(7.5));
+    }
+
+    #[test]
+    fn every_param_problem_is_reported() {
+        let template = meeting_template();
+
+        let errors = template
+            .instantiate(&params(&[
+                ("person", "alice"),
+                ("minutes", "a while"),
+                ("remote", "yes"),
+                ("room", "4"),
+            ]))
+            .unwrap_err();
+        assert_eq!(
+            errors,
+            vec![
+                TemplateError::UnexpectedParam {
+                    param: "room".into()
+                },
+                TemplateError::ValidationFailed {
+                    param: "person".into(),
+                    value: "alice".into(),
+                    pattern: "[A-Z][A-Z0-9_]*".into(),
+                },
+                TemplateError::MissingParam {
+                    param: "meeting".into()
+                },
+                TemplateError::WrongKind {
+                    param: "minutes".into(),
+                    value: "a while".into(),
+                    expected: ParamKind::Number,
+                },
+                TemplateError::WrongKind {
+                    param: "remote".into(),
+                    value: "yes".into(),
+                    expected: ParamKind::Boolean,
+                },
+            ]
+        );
+        assert_eq!(
+            errors[1].to_string(),
+            "param 'person' value 'alice' does not match [A-Z][A-Z0-9_]*"
+        );
+
+        // The pattern must match the whole value
+        let errors = template
+            .instantiate(&params(&[("person", "ALICE smith"), ("meeting", "x")]))
+            .unwrap_err();
+        assert!(matches!(
+            errors[..],
+            [TemplateError::ValidationFailed { .. }]
+        ));
+    }
+
+    #[test]
+    fn broken_templates_fail_before_params() {
+        let mut template = meeting_template();
+        template.params[0].validation = Some("[A-Z".to_string());
+        template.params.push(TemplateParam {
+            name: "meeting".into(),
+            kind: ParamKind::String,
+            required: true,
+            default: None,
+            validation: None,
+        });
+        template
+            .attributes
+            .insert("room".into(), json!("{{room}} / {{room}}"));
+
+        let errors = template.instantiate(&HashMap::new()).unwrap_err();
+        assert_eq!(errors.len(), 3, "{:?}", errors);
+        assert!(matches!(errors[0], TemplateError::InvalidPattern { .. }));
+        assert!(matches!(errors[1], TemplateError::InvalidTemplate { .. }));
+        assert_eq!(
+            errors[2],
+            TemplateError::UnknownPlaceholder {
+                placeholder: "room".into()
+            }
+        );
+        assert_eq!(
+            errors[2].to_string(),
+            "placeholder '{{room}}' does not name a declared param"
+        );
+
+        // A template that leaves a slot kind empty
+        let no_actor = AttestationTemplate {
+            name: "no-actor".into(),
+            slots: vec![
+                TemplateSlot {
+                    slot: SlotKind::Subject,
+                    value: "ALICE".into(),
+                },
+                TemplateSlot {
+                    slot: SlotKind::Predicate,
+                    value: "is".into(),
+                },
+                TemplateSlot {
+                    slot: SlotKind::Context,
+                    value: "_".into(),
+                },
+            ],
+            ..Default::default()
+        };
+        assert_eq!(
+            no_actor.instantiate(&HashMap::new()).unwrap_err(),
+            vec![TemplateError::EmptySlot {
+                slot: SlotKind::Actor
+            }]
+        );
+    }
+
+    #[test]
+    fn template_round_trips_through_a_store() {
+        let template = meeting_template();
+        let mut store = MemoryStore::new();
+        store
+            .put(template.to_attestation("AS-TPL-1", "human:me", 1_700_000_000_000))
+            .unwrap();
+        // An older definition of the same name and a broken one
+        let mut old = template.clone();
+        old.description = Some("old".into());
+        store
+            .put(old.to_attestation("AS-TPL-0", "human:me", 1_600_000_000_000))
+            .unwrap();
+        let mut broken = template.to_attestation("AS-TPL-2", "human:me", 0);
+        broken.subjects = vec!["broken".into()];
+        broken.attributes.insert("params".into(), json!("nope"));
+        store.put(broken).unwrap();
+
+        // "Reload": read the definitions back as serialized JSON
+        let filter = AxFilter {
+            predicates: vec![TEMPLATE_PREDICATE.to_string()],
+            contexts: vec![TEMPLATE_CONTEXT.to_string()],
+            ..Default::default()
+        };
+        let stored: Vec<Attestation> = store
+            .query(&filter)
+            .unwrap()
+            .attestations
+            .iter()
+            .map(|a| serde_json::from_str(&serde_json::to_string(a).unwrap()).unwrap())
+            .collect();
+        let library = TemplateLibrary::from_attestations(&stored);
+
+        assert_eq!(library.templates().len(), 1);
+        assert_eq!(library.get("attended-meeting"), Some(&template));
+        assert_eq!(library.errors().len(), 1);
+        assert_eq!(library.errors()[0].attestation_id, "AS-TPL-2");
+
+        let input = params(&[
+            ("person", "ALICE"),
+            ("meeting", "standup"),
+            ("minutes", "15"),
+        ]);
+        assert_eq!(
+            library
+                .get("attended-meeting")
+                .unwrap()
+                .instantiate(&input)
+                .unwrap(),
+            template.instantiate(&input).unwrap()
+        );
+    }
+}
@@ -5,6 +5,8 @@
 
 use serde::{Deserialize, Serialize};
 
+use crate::duration::{DurationUnit, CALENDAR_POLICY};
+
 /// Resolved temporal clause with epoch milliseconds.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum ResolvedTemporal {
@@ -18,10 +20,12 @@ pub enum ResolvedTemporal {
         start_ms: i64,
         end_ms: i64,
     },
+    /// `ms` is the duration under `CALENDAR_POLICY`, `normalized` its display
+    /// form ("18m" → "1y6m"); both are None when `raw` doesn't parse.
     Over {
         raw: String,
-        value: Option<f64>,
-        unit: Option<String>,
+        ms: Option<i64>,
+        normalized: Option<String>,
     },
 }
 
@@ -36,16 +40,17 @@ pub fn resolve_temporal(expr: &str, now_ms: i64) -> Option<i64> {
     let lower = expr.to_ascii_lowercase();
 
     // Exact matches
+    let unit = |u: DurationUnit| u.millis(CALENDAR_POLICY);
     match lower.as_str() {
         "now" | "today" => return Some(now_ms),
-        "yesterday" => return Some(now_ms - 86_400_000),
-        "tomorrow" => return Some(now_ms + 86_400_000),
-        "last week" => return Some(now_ms - 7 * 86_400_000),
-        "next week" => return Some(now_ms + 7 * 86_400_000),
-        "last month" => return Some(now_ms - 30 * 86_400_000),
-        "next month" => return Some(now_ms + 30 * 86_400_000),
-        "last year" => return Some(now_ms - 365 * 86_400_000),
-        "next year" => return Some(now_ms + 365 * 86_400_000),
+        "yesterday" => return Some(now_ms - unit(DurationUnit::Days)),
+        "tomorrow" => return Some(now_ms + unit(DurationUnit::Days)),
+        "last week" => return Some(now_ms - unit(DurationUnit::Weeks)),
+        "next week" => return Some(now_ms + unit(DurationUnit::Weeks)),
+        "last month" => return Some(now_ms - unit(DurationUnit::Months)),
+        "next month" => return Some(now_ms + unit(DurationUnit::Months)),
+        "last year" => return Some(now_ms - unit(DurationUnit::Years)),
+        "next year" => return Some(now_ms + unit(DurationUnit::Years)),
         _ => {}
     }
 
@@ -93,10 +98,10 @@ fn parse_relative_duration(expr: &str) -> Option<i64> {
         "second" | "seconds" | "sec" | "secs" => 1_000i64,
         "minute" | "minutes" | "min" | "mins" => 60_000,
         "hour" | "hours" | "hr" | "hrs" => 3_600_000,
-        "day" | "days" => 86_400_000,
-        "week" | "weeks" => 7 * 86_400_000,
-        "month" | "months" => 30 * 86_400_000,
-        "year" | "years" => 365 * 86_400_000,
+        "day" | "days" => DurationUnit::Days.millis(CALENDAR_POLICY),
+        "week" | "weeks" => DurationUnit::Weeks.millis(CALENDAR_POLICY),
+        "month" | "months" => DurationUnit::Months.millis(CALENDAR_POLICY),
+        "year" | "years" => DurationUnit::Years.millis(CALENDAR_POLICY),
         _ => return None,
     };
 
@@ -288,7 +293,7 @@ fn split_timezone(time_str: &str) -> (&str, i64) {
 }
 
 /// Days from Unix epoch (1970-01-01) to a given date.
-fn days_from_epoch(year: i32, month: u32, day: u32) -> Option<i64> {
+pub(crate) fn days_from_epoch(year: i32, month: u32, day: u32) -> Option<i64> {
     // Algorithm from http://howardhinnant.github.io/date_algorithms.html
     let y = if month <= 2 { year - 1 } else { year } as i64;
     let m = if month <= 2 { month + 9 } else { month - 3 } as i64;
@@ -300,8 +305,23 @@ fn days_from_epoch(year: i32, month: u32, day: u32) -> Option<i64> {
     Some(days)
 }
 
+/// Civil date (year, month, day) for a count of days from the Unix epoch.
+pub(crate) fn civil_from_days(days: i64) -> (i32, u32, u32) {
+    // Inverse of days_from_epoch, same source
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = (yoe + era * 400 + i64::from(month <= 2)) as i32;
+    (year, month, day)
+}
+
 /// Number of days in a given month.
-fn days_in_month(year: i32, month: u32) -> u32 {
+pub(crate) fn days_in_month(year: i32, month: u32) -> u32 {
     match month {
         1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
         4 | 6 | 9 | 11 => 30,
@@ -0,0 +1,650 @@
+//! Type definitions parsed from type-definition attestations.
+//!
+//! A type is defined by attesting `[name] is type`; the Go side writes it with
+//! the type name as its own actor and no context. The display metadata and the
+//! fields that matter to search live in the attributes:
+//!
+//! | attribute            | meaning                                          |
+//! |----------------------|--------------------------------------------------|
+//! | `display_label`      | human-readable label (defaults to the name)      |
+//! | `display_color`      | hex color for graph visualization                |
+//! | `display_icon`       | icon name for the UI                             |
+//! | `opacity`            | 0.0–1.0, absent means 1.0                        |
+//! | `deprecated`         | type is being phased out                         |
+//! | `rich_string_fields` | attribute names holding text for rich search     |
+//! | `array_fields`       | attribute names flattened into arrays            |
+//! | `attribute_types`    | `{field: type}` with a type from [`AttributeType`] |
+//!
+//! Broken definitions don't fail the registry: every problem becomes a
+//! [`TypeWarning`] and the type is kept with whatever could be read, so a typo
+//! shows up in the types panel instead of silently disabling rich search.
+
+use crate::attestation::Attestation;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Predicate marking an attestation as a type definition
+pub const TYPE_PREDICATE: &str = "type";
+
+/// Declared type of an attribute field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AttributeType {
+    String,
+    Number,
+    Boolean,
+    /// Unix milliseconds or an RFC 3339 string
+    Timestamp,
+    Array,
+    Object,
+}
+
+impl AttributeType {
+    /// Parse a declared type name; `None` for names the registry doesn't know.
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "string" => Some(Self::String),
+            "number" => Some(Self::Number),
+            "boolean" | "bool" => Some(Self::Boolean),
+            "timestamp" => Some(Self::Timestamp),
+            "array" => Some(Self::Array),
+            "object" => Some(Self::Object),
+            _ => None,
+        }
+    }
+}
+
+/// A node type, as parsed from its definition attestation.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TypeDef {
+    pub name: String,
+    pub label: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub icon: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub opacity: Option<f64>,
+    #[serde(default)]
+    pub deprecated: bool,
+    #[serde(default)]
+    pub rich_string_fields: Vec<String>,
+    #[serde(default)]
+    pub array_fields: Vec<String>,
+    #[serde(default)]
+    pub attribute_types: BTreeMap<String, AttributeType>,
+    /// ID of the attestation the definition was read from
+    pub attestation_id: String,
+    pub timestamp: i64,
+}
+
+/// What is wrong with a type definition.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TypeWarningKind {
+    /// Another attestation defines the same type; the newest one wins
+    DuplicateType,
+    /// `rich_string_fields` or `array_fields` isn't an array of strings
+    InvalidFieldList,
+    /// `attribute_types` names a type outside [`AttributeType`]
+    UnknownAttributeType,
+    /// A display attribute has the wrong JSON type or is out of range
+    InvalidAttribute,
+}
+
+/// A problem found while building the registry.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TypeWarning {
+    pub type_name: String,
+    pub attestation_id: String,
+    pub kind: TypeWarningKind,
+    pub message: String,
+}
+
+/// How one type changed between two registries.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TypeChange {
+    pub name: String,
+    /// Display properties that changed: `label`, `color`, `icon`, `opacity`,
+    /// `deprecated`
+    pub properties: Vec<String>,
+    pub rich_string_fields_added: Vec<String>,
+    pub rich_string_fields_removed: Vec<String>,
+    pub array_fields_added: Vec<String>,
+    pub array_fields_removed: Vec<String>,
+    /// Fields whose declared type was added, removed or changed
+    pub attribute_types_changed: Vec<String>,
+}
+
+impl TypeChange {
+    fn is_empty(&self) -> bool {
+        self.properties.is_empty()
+            && self.rich_string_fields_added.is_empty()
+            && self.rich_string_fields_removed.is_empty()
+            && self.array_fields_added.is_empty()
+            && self.array_fields_removed.is_empty()
+            && self.attribute_types_changed.is_empty()
+    }
+}
+
+/// Differences between two registries, names sorted.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TypeRegistryDiff {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub changed: Vec<TypeChange>,
+}
+
+impl TypeRegistryDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// Type definitions by name, plus the warnings found while reading them.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TypeRegistry {
+    types: BTreeMap<String, TypeDef>,
+    warnings: Vec<TypeWarning>,
+}
+
+impl TypeRegistry {
+    /// Build a registry from attestations; anything that isn't a type
+    /// definition is ignored.
+    pub fn from_attestations<'a>(attestations: impl IntoIterator<Item = &'a Attestation>) -> Self {
+        let mut registry = Self::default();
+        for attestation in attestations {
+            if !is_type_definition(attestation) {
+                continue;
+            }
+            for name in &attestation.subjects {
+                let name = name.trim();
+                if name.is_empty() {
+                    continue;
+                }
+                let def = parse_type_def(name, attestation, &mut registry.warnings);
+                registry.insert(def);
+            }
+        }
+        registry
+    }
+
+    fn insert(&mut self, def: TypeDef) {
+        let Some(existing) = self.types.get(&def.name) else {
+            self.types.insert(def.name.clone(), def);
+            return;
+        };
+        // Newest wins; on equal timestamps the larger ID, so the outcome
+        // doesn't depend on query order
+        let newer =
+            (def.timestamp, &def.attestation_id) > (existing.timestamp, &existing.attestation_id);
+        let (kept, dropped) = if newer {
+            (&def.attestation_id, &existing.attestation_id)
+        } else {
+            (&existing.attestation_id, &def.attestation_id)
+        };
+        self.warnings.push(TypeWarning {
+            type_name: def.name.clone(),
+            attestation_id: dropped.clone(),
+            kind: TypeWarningKind::DuplicateType,
+            message: format!(
+                "type '{}' is defined more than once; using {} over {}",
+                def.name, kept, dropped
+            ),
+        });
+        if newer {
+            self.types.insert(def.name.clone(), def);
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&TypeDef> {
+        self.types.get(name)
+    }
+
+    /// Types sorted by name
+    pub fn types(&self) -> impl Iterator<Item = &TypeDef> {
+        self.types.values()
+    }
+
+    pub fn len(&self) -> usize {
+        self.types.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.types.is_empty()
+    }
+
+    pub fn warnings(&self) -> &[TypeWarning] {
+        &self.warnings
+    }
+
+    /// Union of every type's rich string fields: the attributes a rich search
+    /// index should cover.
+    pub fn rich_string_fields(&self) -> BTreeSet<&str> {
+        self.types
+            .values()
+            .flat_map(|t| t.rich_string_fields.iter().map(String::as_str))
+            .collect()
+    }
+
+    /// What changed going from `self` to `newer`.
+    pub fn diff(&self, newer: &TypeRegistry) -> TypeRegistryDiff {
+        let mut diff = TypeRegistryDiff::default();
+        for (name, old) in &self.types {
+            match newer.types.get(name) {
+                None => diff.removed.push(name.clone()),
+                Some(new) => {
+                    let change = diff_type(old, new);
+                    if !change.is_empty() {
+                        diff.changed.push(change);
+                    }
+                }
+            }
+        }
+        diff.added = newer
+            .types
+            .keys()
+            .filter(|name| !self.types.contains_key(*name))
+            .cloned()
+            .collect();
+        diff
+    }
+}
+
+fn is_type_definition(attestation: &Attestation) -> bool {
+    attestation.predicates.iter().any(|p| p == TYPE_PREDICATE)
+}
+
+fn parse_type_def(
+    name: &str,
+    attestation: &Attestation,
+    warnings: &mut Vec<TypeWarning>,
+) -> TypeDef {
+    let mut warn = |kind, message: String| {
+        warnings.push(TypeWarning {
+            type_name: name.to_string(),
+            attestation_id: attestation.id.clone(),
+            kind,
+            message,
+        })
+    };
+    let attrs = &attestation.attributes;
+
+    let mut string_attr = |key: &str| match attrs.get(key) {
+        None | Some(serde_json::Value::Null) => None,
+        Some(serde_json::Value::String(s)) if !s.trim().is_empty() => Some(s.clone()),
+        Some(serde_json::Value::String(_)) => None,
+        Some(other) => {
+            warn(
+                TypeWarningKind::InvalidAttribute,
+                format!("{} must be a string, got {}", key, other),
+            );
+            None
+        }
+    };
+    let label = string_attr("display_label").unwrap_or_else(|| name.to_string());
+    let color = string_attr("display_color");
+    let icon = string_attr("display_icon");
+
+    let opacity = match attrs.get("opacity") {
+        None | Some(serde_json::Value::Null) => None,
+        Some(v) => match v.as_f64() {
+            Some(o) if (0.0..=1.0).contains(&o) => Some(o),
+            _ => {
+                warn(
+                    TypeWarningKind::InvalidAttribute,
+                    format!("opacity must be a number between 0 and 1, got {}", v),
+                );
+                None
+            }
+        },
+    };
+
+    let deprecated = match attrs.get("deprecated") {
+        None | Some(serde_json::Value::Null) => false,
+        Some(serde_json::Value::Bool(b)) => *b,
+        Some(other) => {
+            warn(
+                TypeWarningKind::InvalidAttribute,
+                format!("deprecated must be a boolean, got {}", other),
+            );
+            false
+        }
+    };
+
+    let mut field_list = |key: &str| {
+        let Some(value) = attrs.get(key) else {
+            return Vec::new();
+        };
+        let fields = value.as_array().and_then(|items| {
+            items
+                .iter()
+                .map(|item| item.as_str().map(str::to_string))
+                .collect::<Option<Vec<_>>>()
+        });
+        match fields {
+            Some(fields) => fields,
+            None if value.is_null() => Vec::new(),
+            None => {
+                warn(
+                    TypeWarningKind::InvalidFieldList,
+                    format!("{} must be an array of strings, got {}", key, value),
+                );
+                Vec::new()
+            }
+        }
+    };
+    let rich_string_fields = field_list("rich_string_fields");
+    let array_fields = field_list("array_fields");
+
+    let mut attribute_types = BTreeMap::new();
+    match attrs.get("attribute_types") {
+        None | Some(serde_json::Value::Null) => {}
+        Some(serde_json::Value::Object(declared)) => {
+            for (field, declared_type) in declared {
+                match declared_type.as_str().and_then(AttributeType::parse) {
+                    Some(t) => {
+                        attribute_types.insert(field.clone(), t);
+                    }
+                    None => warn(
+                        TypeWarningKind::UnknownAttributeType,
+                        format!("field '{}' has unknown type {}", field, declared_type),
+                    ),
+                }
+            }
+        }
+        Some(other) => warn(
+            TypeWarningKind::InvalidAttribute,
+            format!("attribute_types must be an object, got {}", other),
+        ),
+    }
+
+    TypeDef {
+        name: name.to_string(),
+        label,
+        color,
+        icon,
+        opacity,
+        deprecated,
+        rich_string_fields,
+        array_fields,
+        attribute_types,
+        attestation_id: attestation.id.clone(),
+        timestamp: attestation.timestamp,
+    }
+}
+
+fn diff_type(old: &TypeDef, new: &TypeDef) -> TypeChange {
+    let mut properties = Vec::new();
+    if old.label != new.label {
+        properties.push("label".to_string());
+    }
+    if old.color != new.color {
+        properties.push("color".to_string());
+    }
+    if old.icon != new.icon {
+        properties.push("icon".to_string());
+    }
+    if old.opacity != new.opacity {
+        properties.push("opacity".to_string());
+    }
+    if old.deprecated != new.deprecated {
+        properties.push("deprecated".to_string());
+    }
+
+    let (rich_string_fields_added, rich_string_fields_removed) =
+        diff_fields(&old.rich_string_fields, &new.rich_string_fields);
+    let (array_fields_added, array_fields_removed) =
+        diff_fields(&old.array_fields, &new.array_fields);
+
+    let fields: BTreeSet<&String> = old
+        .attribute_types
+        .keys()
+        .chain(new.attribute_types.keys())
+        .collect();
+    let attribute_types_changed = fields
+        .into_iter()
+        .filter(|f| old.attribute_types.get(*f) != new.attribute_types.get(*f))
+        .cloned()
+        .collect();
+
+    TypeChange {
+        name: old.name.clone(),
+        properties,
+        rich_string_fields_added,
+        rich_string_fields_removed,
+        array_fields_added,
+        array_fields_removed,
+        attribute_types_changed,
+    }
+}
+
+/// Fields only in `new`, and fields only in `old`, in their original order
+fn diff_fields(old: &[String], new: &[String]) -> (Vec<String>, Vec<String>) {
+    let added = new.iter().filter(|f| !old.contains(f)).cloned().collect();
+    let removed = old.iter().filter(|f| !new.contains(f)).cloned().collect();
+    (added, removed)
+}
+
+/// JSON entry point: build a registry from a JSON array of attestations.
+/// Returns `{"types": [...], "warnings": [...]}` or `{"error": "..."}`.
+pub fn type_registry_json(attestations_json: &str) -> String {
+    let attestations: Vec<Attestation> = match serde_json::from_str(attestations_json) {
+        Ok(a) => a,
+        Err(e) => {
+            return serde_json::json!({ "error": format!("invalid attestations: {}", e) })
+                .to_string()
+        }
+    };
+    let registry = TypeRegistry::from_attestations(&attestations);
+    serde_json::json!({
+        "types": registry.types().collect::<Vec<_>>(),
+        "warnings": registry.warnings(),
+    })
+    .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::attestation::AttestationBuilder;
+    use serde_json::json;
+
+    fn type_def(id: &str, name: &str, timestamp: i64, attrs: serde_json::Value) -> Attestation {
+        let mut builder = AttestationBuilder::new()
+            .id(id)
+            .subject(name)
+            .predicate(TYPE_PREDICATE)
+            .actor(name)
+            .timestamp(timestamp)
+            .source("test");
+        for (key, value) in attrs.as_object().unwrap() {
+            builder = builder.attribute(key, value.clone());
+        }
+        builder.build()
+    }
+
+    fn kinds(registry: &TypeRegistry) -> Vec<TypeWarningKind> {
+        registry.warnings().iter().map(|w| w.kind).collect()
+    }
+
+    #[test]
+    fn test_valid_definitions() {
+        let other = AttestationBuilder::new()
+            .id("AS-other")
+            .subject("ALICE")
+            .predicate("knows")
+            .build();
+        let atts = vec![
+            type_def(
+                "AS-t1",
+                "candidate",
+                1,
+                json!({
+                    "display_label": "Candidate",
+                    "display_color": "#e67e22",
+                    "display_icon": "person",
+                    "opacity": 0.5,
+                    "rich_string_fields": ["notes", "summary"],
+                    "array_fields": ["skills"],
+                    "attribute_types": {"notes": "string", "score": "number"},
+                }),
+            ),
+            type_def("AS-t2", "commit", 1, json!({"deprecated": true})),
+            other,
+        ];
+        let registry = TypeRegistry::from_attestations(&atts);
+        assert!(registry.warnings().is_empty(), "{:?}", registry.warnings());
+        assert_eq!(registry.len(), 2);
+
+        let candidate = registry.get("candidate").unwrap();
+        assert_eq!(candidate.label, "Candidate");
+        assert_eq!(candidate.color.as_deref(), Some("#e67e22"));
+        assert_eq!(candidate.icon.as_deref(), Some("person"));
+        assert_eq!(candidate.opacity, Some(0.5));
+        assert_eq!(candidate.rich_string_fields, vec!["notes", "summary"]);
+        assert_eq!(candidate.array_fields, vec!["skills"]);
+        assert_eq!(
+            candidate.attribute_types.get("score"),
+            Some(&AttributeType::Number)
+        );
+
+        let commit = registry.get("commit").unwrap();
+        assert_eq!(commit.label, "commit");
+        assert!(commit.deprecated);
+        assert!(registry.get("ALICE").is_none());
+        assert_eq!(
+            registry
+                .rich_string_fields()
+                .into_iter()
+                .collect::<Vec<_>>(),
+            vec!["notes", "summary"]
+        );
+    }
+
+    #[test]
+    fn test_duplicate_definitions_newest_wins() {
+        let old = type_def("AS-old", "jd", 1, json!({"rich_string_fields": ["a"]}));
+        let new = type_def("AS-new", "jd", 2, json!({"rich_string_fields": ["b"]}));
+
+        // Either order gives the same registry
+        for atts in [vec![old.clone(), new.clone()], vec![new, old]] {
+            let registry = TypeRegistry::from_attestations(&atts);
+            assert_eq!(registry.get("jd").unwrap().attestation_id, "AS-new");
+            assert_eq!(registry.get("jd").unwrap().rich_string_fields, vec!["b"]);
+            assert_eq!(kinds(&registry), vec![TypeWarningKind::DuplicateType]);
+            assert_eq!(registry.warnings()[0].attestation_id, "AS-old");
+        }
+    }
+
+    #[test]
+    fn test_malformed_definitions_warn_and_keep_the_type() {
+        let atts = vec![
+            type_def(
+                "AS-bad",
+                "document",
+                1,
+                json!({
+                    "display_color": 42,
+                    "opacity": 3,
+                    "rich_string_fields": "notes",
+                    "array_fields": ["tags", 7],
+                    "attribute_types": {"notes": "text", "pages": "number"},
+                }),
+            ),
+            type_def(
+                "AS-bad-types",
+                "author",
+                1,
+                json!({"attribute_types": ["string"]}),
+            ),
+        ];
+        let registry = TypeRegistry::from_attestations(&atts);
+        assert_eq!(
+            kinds(&registry),
+            vec![
+                TypeWarningKind::InvalidAttribute,
+                TypeWarningKind::InvalidAttribute,
+                TypeWarningKind::InvalidFieldList,
+                TypeWarningKind::InvalidFieldList,
+                TypeWarningKind::UnknownAttributeType,
+                TypeWarningKind::InvalidAttribute,
+            ]
+        );
+        assert!(registry.warnings()[2]
+            .message
+            .contains("rich_string_fields"));
+        assert!(registry.warnings()[4].message.contains("'notes'"));
+
+        let document = registry.get("document").unwrap();
+        assert!(document.color.is_none());
+        assert!(document.opacity.is_none());
+        assert!(document.rich_string_fields.is_empty());
+        assert!(document.array_fields.is_empty());
+        assert_eq!(
+            document.attribute_types.keys().collect::<Vec<_>>(),
+            vec!["pages"]
+        );
+        assert!(registry.get("author").unwrap().attribute_types.is_empty());
+    }
+
+    #[test]
+    fn test_diff_when_field_added() {
+        let before = TypeRegistry::from_attestations(&[
+            type_def(
+                "AS-1",
+                "candidate",
+                1,
+                json!({"rich_string_fields": ["notes"]}),
+            ),
+            type_def("AS-2", "commit", 1, json!({})),
+        ]);
+        let after = TypeRegistry::from_attestations(&[
+            type_def(
+                "AS-3",
+                "candidate",
+                2,
+                json!({
+                    "rich_string_fields": ["notes", "summary"],
+                    "attribute_types": {"summary": "string"},
+                }),
+            ),
+            type_def("AS-4", "author", 2, json!({})),
+        ]);
+
+        let diff = before.diff(&after);
+        assert_eq!(diff.added, vec!["author"]);
+        assert_eq!(diff.removed, vec!["commit"]);
+        assert_eq!(
+            diff.changed,
+            vec![TypeChange {
+                name: "candidate".to_string(),
+                rich_string_fields_added: vec!["summary".to_string()],
+                attribute_types_changed: vec!["summary".to_string()],
+                ..Default::default()
+            }]
+        );
+        assert!(after.diff(&after).is_empty());
+    }
+
+    #[test]
+    fn test_type_registry_json() {
+        let atts = vec![type_def(
+            "AS-1",
+            "candidate",
+            1,
+            json!({"rich_string_fields": ["notes", 1]}),
+        )];
+        let out: serde_json::Value =
+            serde_json::from_str(&type_registry_json(&serde_json::to_string(&atts).unwrap()))
+                .unwrap();
+        assert_eq!(out["types"][0]["name"], "candidate");
+        assert_eq!(out["warnings"][0]["kind"], "invalid_field_list");
+
+        let err: serde_json::Value = serde_json::from_str(&type_registry_json("not json")).unwrap();
+        assert!(err["error"]
+            .as_str()
+            .unwrap()
+            .starts_with("invalid attestations"));
+    }
+}
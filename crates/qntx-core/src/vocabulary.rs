@@ -0,0 +1,217 @@
+//! Vocabulary extraction from attestations.
+//!
+//! Collects the distinct subjects, predicates, contexts, and actors that appear
+//! in a set of attestations, so hosts feeding a search index use exactly the
+//! same notion of "vocabulary" as the Rust stores.
+//!
+//! Normalization: each value is trimmed, empty (or whitespace-only) values are
+//! dropped, case is preserved, and duplicates collapse to their first-seen
+//! position. Every term also carries its occurrence count.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// The slots of an attestation that contribute vocabulary.
+///
+/// Unknown fields are ignored, so proto-schema attestation JSON deserializes
+/// directly into this.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct VocabularyAttestation {
+    #[serde(default)]
+    pub subjects: Vec<String>,
+    #[serde(default)]
+    pub predicates: Vec<String>,
+    #[serde(default)]
+    pub contexts: Vec<String>,
+    #[serde(default)]
+    pub actors: Vec<String>,
+}
+
+/// Extracted vocabulary, one list per slot in first-seen order.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Vocabulary {
+    pub subjects: Vec<String>,
+    pub predicates: Vec<String>,
+    pub contexts: Vec<String>,
+    pub actors: Vec<String>,
+    pub counts: VocabularyCounts,
+    /// Number of attestations the vocabulary was extracted from
+    pub attestations: usize,
+}
+
+/// Occurrence counts aligned index-by-index with the `Vocabulary` lists.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct VocabularyCounts {
+    pub subjects: Vec<u64>,
+    pub predicates: Vec<u64>,
+    pub contexts: Vec<u64>,
+    pub actors: Vec<u64>,
+}
+
+/// Distinct terms of one slot with their counts, in first-seen order.
+#[derive(Debug, Clone, Default)]
+struct TermSet {
+    terms: Vec<String>,
+    counts: Vec<u64>,
+    index: HashMap<String, usize>,
+}
+
+impl TermSet {
+    fn add(&mut self, raw: &str) {
+        let term = raw.trim();
+        if term.is_empty() {
+            return;
+        }
+        match self.index.get(term) {
+            Some(&i) => self.counts[i] += 1,
+            None => {
+                self.index.insert(term.to_string(), self.terms.len());
+                self.terms.push(term.to_string());
+                self.counts.push(1);
+            }
+        }
+    }
+}
+
+/// Incremental vocabulary extractor. Feed attestations in any number of
+/// batches, then call `finish`; the result equals a single-pass extraction.
+#[derive(Debug, Clone, Default)]
+pub struct VocabularyExtractor {
+    subjects: TermSet,
+    predicates: TermSet,
+    contexts: TermSet,
+    actors: TermSet,
+    attestations: usize,
+}
+
+impl VocabularyExtractor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&mut self, attestation: &VocabularyAttestation) {
+        self.attestations += 1;
+        attestation
+            .subjects
+            .iter()
+            .for_each(|s| self.subjects.add(s));
+        attestation
+            .predicates
+            .iter()
+            .for_each(|p| self.predicates.add(p));
+        attestation
+            .contexts
+            .iter()
+            .for_each(|c| self.contexts.add(c));
+        attestation.actors.iter().for_each(|a| self.actors.add(a));
+    }
+
+    pub fn add_all<'a>(
+        &mut self,
+        attestations: impl IntoIterator<Item = &'a VocabularyAttestation>,
+    ) {
+        for a in attestations {
+            self.add(a);
+        }
+    }
+
+    pub fn finish(self) -> Vocabulary {
+        Vocabulary {
+            counts: VocabularyCounts {
+                subjects: self.subjects.counts,
+                predicates: self.predicates.counts,
+                contexts: self.contexts.counts,
+                actors: self.actors.counts,
+            },
+            subjects: self.subjects.terms,
+            predicates: self.predicates.terms,
+            contexts: self.contexts.terms,
+            actors: self.actors.terms,
+            attestations: self.attestations,
+        }
+    }
+}
+
+/// Extract vocabulary from attestations in a single pass.
+pub fn extract_vocabulary(attestations: &[VocabularyAttestation]) -> Vocabulary {
+    let mut extractor = VocabularyExtractor::new();
+    extractor.add_all(attestations);
+    extractor.finish()
+}
+
+/// JSON entry point. Input is a JSON array of attestations (proto schema);
+/// output is a serialized `Vocabulary` or `{"error":"..."}`.
+pub fn extract_vocabulary_json(input: &str) -> String {
+    let attestations: Vec<VocabularyAttestation> = match serde_json::from_str(input) {
+        Ok(v) => v,
+        Err(e) => {
+            return format!(r#"{{"error":"invalid vocabulary input: {}"}}"#, e);
+        }
+    };
+
+    match serde_json::to_string(&extract_vocabulary(&attestations)) {
+        Ok(json) => json,
+        Err(e) => format!(r#"{{"error":"serialization failed: {}"}}"#, e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn att(subjects: &[&str], predicates: &[&str]) -> VocabularyAttestation {
+        VocabularyAttestation {
+            subjects: subjects.iter().map(|s| s.to_string()).collect(),
+            predicates: predicates.iter().map(|s| s.to_string()).collect(),
+            contexts: vec!["TATOOINE".into()],
+            actors: vec!["imperial-records".into()],
+        }
+    }
+
+    #[test]
+    fn test_normalization() {
+        let vocab = extract_vocabulary(&[
+            att(&["LUKE", "  ", ""], &["pilots"]),
+            att(&[" LEIA ", "LUKE"], &["commands", "pilots"]),
+            att(&["luke"], &[]),
+        ]);
+
+        // Whitespace-only and empty entries dropped, trimmed, case preserved
+        assert_eq!(vocab.subjects, vec!["LUKE", "LEIA", "luke"]);
+        assert_eq!(vocab.counts.subjects, vec![2, 1, 1]);
+        assert_eq!(vocab.predicates, vec!["pilots", "commands"]);
+        assert_eq!(vocab.counts.predicates, vec![2, 1]);
+        assert_eq!(vocab.contexts, vec!["TATOOINE"]);
+        assert_eq!(vocab.counts.contexts, vec![3]);
+        assert_eq!(vocab.attestations, 3);
+    }
+
+    #[test]
+    fn test_incremental_matches_single_pass() {
+        let all = vec![
+            att(&["HAN"], &["smuggles"]),
+            att(&["CHEWIE", "HAN"], &["flies"]),
+            att(&["LANDO"], &["smuggles"]),
+        ];
+
+        let mut extractor = VocabularyExtractor::new();
+        extractor.add_all(&all[..1]);
+        extractor.add_all(&all[1..]);
+
+        assert_eq!(extractor.finish(), extract_vocabulary(&all));
+    }
+
+    #[test]
+    fn test_json_ignores_unknown_fields() {
+        let input = r#"[{"id":"SW001","subjects":["R2D2"],"predicates":["beeps"],"contexts":["_"],"actors":["c3po"],"timestamp":1000,"attributes":{}}]"#;
+        let parsed: serde_json::Value =
+            serde_json::from_str(&extract_vocabulary_json(input)).unwrap();
+        assert_eq!(parsed["subjects"][0], "R2D2");
+        assert_eq!(parsed["counts"]["actors"][0], 1);
+    }
+
+    #[test]
+    fn test_json_invalid() {
+        assert!(extract_vocabulary_json("not json").contains("invalid vocabulary input"));
+    }
+}
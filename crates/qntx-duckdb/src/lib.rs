@@ -370,6 +370,8 @@ impl DuckdbStore {
             created_at,
             signature,
             signer_did,
+            revision: 0,
+            confidence: None,
         })
     }
 }
@@ -561,6 +563,8 @@ mod tests {
             created_at: 1_700_000_000_000,
             signature: None,
             signer_did: None,
+            revision: 0,
+            confidence: None,
         }
     }
 
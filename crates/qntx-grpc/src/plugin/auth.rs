@@ -0,0 +1,469 @@
+//! Inbound authentication for plugin servers.
+//!
+//! Anyone who can reach a plugin port can otherwise run its jobs and
+//! handlers. With auth enabled, [`PluginServer`](super::PluginServer) refuses
+//! gRPC calls whose `authorization` metadata doesn't carry a valid
+//! `Bearer <token>`, answering `UNAUTHENTICATED`; handlers check the
+//! `Authorization` header of proxied HTTP requests with
+//! [`PluginAuth::check_http`], answering 401.
+//!
+//! - Several tokens can be valid at once, so the host can roll a new token
+//!   out before retiring the old one ([`PluginAuth::rotate`] keeps the newest
+//!   [`MAX_ACTIVE_TOKENS`])
+//! - Tokens are compared in constant time, and rejections never echo what
+//!   was presented
+//! - Localhost-exempt mode skips the check for loopback gRPC peers, for
+//!   development. It never applies to proxied HTTP: the peer of a
+//!   `HandleHTTP` call is the host's proxy, usually on loopback, whoever sent
+//!   the original request
+//! - Methods in [`AUTH_EXEMPT_METHODS`] stay open so orchestration can still
+//!   probe health
+//!
+//! Auth is off unless tokens are configured: through
+//! [`AUTH_TOKENS_ENV`] at startup, or the [`CONFIG_AUTH_TOKENS`] key of the
+//! `InitializeRequest` config via [`PluginAuth::update_from_config`].
+
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::future::Future;
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::sync::{Arc, RwLock};
+use std::task::{Context, Poll};
+
+use tonic::body::BoxBody;
+use tonic::server::NamedService;
+use tonic::transport::server::TcpConnectInfo;
+use tonic::Status;
+
+use super::proto::{ConfigFieldSchema, HttpHeader, HttpRequest, HttpResponse};
+
+/// Environment variable with the comma-separated tokens accepted at startup
+pub const AUTH_TOKENS_ENV: &str = "QNTX_PLUGIN_AUTH_TOKENS";
+
+/// Environment variable enabling localhost-exempt mode (`1` or `true`)
+pub const AUTH_LOCALHOST_EXEMPT_ENV: &str = "QNTX_PLUGIN_AUTH_LOCALHOST_EXEMPT";
+
+/// Config key replacing the accepted tokens (comma-separated)
+pub const CONFIG_AUTH_TOKENS: &str = "plugin_auth_tokens";
+
+/// Config key for localhost-exempt mode (`true`/`false`)
+pub const CONFIG_AUTH_LOCALHOST_EXEMPT: &str = "plugin_auth_localhost_exempt";
+
+/// How many tokens [`PluginAuth::rotate`] keeps valid
+pub const MAX_ACTIVE_TOKENS: usize = 3;
+
+/// Methods that never require a token, so orchestration keeps working.
+pub const AUTH_EXEMPT_METHODS: &[&str] = &["/protocol.DomainPluginService/Health"];
+
+/// Why a request was refused. The messages never include the presented token.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuthRejection {
+    /// No `Bearer` credentials were presented
+    Missing,
+    /// Credentials were presented but match no active token
+    Invalid,
+}
+
+impl AuthRejection {
+    pub fn message(self) -> &'static str {
+        match self {
+            Self::Missing => "missing bearer token",
+            Self::Invalid => "invalid bearer token",
+        }
+    }
+
+    pub fn to_status(self) -> Status {
+        Status::unauthenticated(self.message())
+    }
+
+    /// 401 response with a `WWW-Authenticate` challenge.
+    pub fn to_http(self) -> HttpResponse {
+        HttpResponse {
+            status_code: 401,
+            headers: vec![
+                HttpHeader {
+                    name: "Content-Type".to_string(),
+                    values: vec!["application/json".to_string()],
+                },
+                HttpHeader {
+                    name: "WWW-Authenticate".to_string(),
+                    values: vec!["Bearer".to_string()],
+                },
+            ],
+            body: serde_json::to_vec(&serde_json::json!({ "error": self.message() }))
+                .unwrap_or_default(),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct AuthState {
+    /// Newest first
+    tokens: Vec<String>,
+    localhost_exempt: bool,
+}
+
+/// Shared, rotatable set of accepted tokens.
+#[derive(Clone, Debug, Default)]
+pub struct PluginAuth {
+    state: Arc<RwLock<AuthState>>,
+}
+
+impl PluginAuth {
+    /// Auth disabled: every request is admitted until tokens are set.
+    pub fn disabled() -> Self {
+        Self::default()
+    }
+
+    /// Accept `tokens`; empty tokens are ignored.
+    pub fn with_tokens<I, T>(tokens: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<String>,
+    {
+        let auth = Self::default();
+        auth.set_tokens(tokens);
+        auth
+    }
+
+    /// Tokens from [`AUTH_TOKENS_ENV`] and the mode from
+    /// [`AUTH_LOCALHOST_EXEMPT_ENV`]; disabled when no tokens are set.
+    pub fn from_env() -> Self {
+        let auth = Self::with_tokens(split_tokens(
+            &std::env::var(AUTH_TOKENS_ENV).unwrap_or_default(),
+        ));
+        auth.set_localhost_exempt(
+            std::env::var(AUTH_LOCALHOST_EXEMPT_ENV)
+                .map(|v| parse_flag(&v))
+                .unwrap_or(false),
+        );
+        auth
+    }
+
+    /// Apply [`CONFIG_AUTH_TOKENS`] and [`CONFIG_AUTH_LOCALHOST_EXEMPT`] from
+    /// an `InitializeRequest` config; absent keys leave the current settings.
+    pub fn update_from_config(&self, config: &HashMap<String, String>) {
+        if let Some(tokens) = config.get(CONFIG_AUTH_TOKENS) {
+            self.set_tokens(split_tokens(tokens));
+        }
+        if let Some(exempt) = config.get(CONFIG_AUTH_LOCALHOST_EXEMPT) {
+            self.set_localhost_exempt(parse_flag(exempt));
+        }
+    }
+
+    /// Replace the accepted tokens; an empty set disables auth.
+    pub fn set_tokens<I, T>(&self, tokens: I)
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<String>,
+    {
+        self.state.write().unwrap().tokens = tokens
+            .into_iter()
+            .map(Into::into)
+            .filter(|t: &String| !t.is_empty())
+            .collect();
+    }
+
+    /// Start accepting `token`, keeping the newest [`MAX_ACTIVE_TOKENS`] in
+    /// total; the oldest beyond that stop working.
+    pub fn rotate(&self, token: impl Into<String>) {
+        let token = token.into();
+        if token.is_empty() {
+            return;
+        }
+        let mut state = self.state.write().unwrap();
+        state.tokens.retain(|t| *t != token);
+        state.tokens.insert(0, token);
+        state.tokens.truncate(MAX_ACTIVE_TOKENS);
+    }
+
+    pub fn set_localhost_exempt(&self, exempt: bool) {
+        self.state.write().unwrap().localhost_exempt = exempt;
+    }
+
+    /// Tokens are configured, so requests are checked.
+    pub fn is_enabled(&self) -> bool {
+        !self.state.read().unwrap().tokens.is_empty()
+    }
+
+    /// Check an `Authorization` value (`Bearer <token>`) from `peer`.
+    pub fn check(
+        &self,
+        authorization: Option<&str>,
+        peer: Option<SocketAddr>,
+    ) -> Result<(), AuthRejection> {
+        let state = self.state.read().unwrap();
+        if state.tokens.is_empty() {
+            return Ok(());
+        }
+        if state.localhost_exempt && peer.is_some_and(|p| p.ip().is_loopback()) {
+            return Ok(());
+        }
+        let presented = authorization
+            .and_then(bearer_token)
+            .ok_or(AuthRejection::Missing)?;
+        // Compare against every token so timing doesn't reveal which matched
+        let matched = state.tokens.iter().fold(false, |any, t| {
+            constant_time_eq(t.as_bytes(), presented.as_bytes()) | any
+        });
+        if matched {
+            Ok(())
+        } else {
+            Err(AuthRejection::Invalid)
+        }
+    }
+
+    /// Check the `Authorization` header of a proxied HTTP request. On
+    /// rejection the error is the 401 response to send back.
+    ///
+    /// Localhost-exempt mode doesn't apply: the gRPC peer is the host proxy,
+    /// not the client that sent the request.
+    pub fn check_http(&self, req: &HttpRequest) -> Result<(), HttpResponse> {
+        let authorization = req
+            .headers
+            .iter()
+            .find(|h| h.name.eq_ignore_ascii_case("authorization"))
+            .and_then(|h| h.values.first())
+            .map(String::as_str);
+        self.check(authorization, None)
+            .map_err(AuthRejection::to_http)
+    }
+
+    /// Config schema entries for the auth keys, for `ConfigSchema` responses.
+    pub fn schema_fields() -> HashMap<String, ConfigFieldSchema> {
+        HashMap::from([
+            (
+                CONFIG_AUTH_TOKENS.to_string(),
+                ConfigFieldSchema {
+                    r#type: "string".to_string(),
+                    description:
+                        "Comma-separated bearer tokens accepted by the plugin; empty disables auth"
+                            .to_string(),
+                    ..Default::default()
+                },
+            ),
+            (
+                CONFIG_AUTH_LOCALHOST_EXEMPT.to_string(),
+                ConfigFieldSchema {
+                    r#type: "boolean".to_string(),
+                    description: "Skip token checks for loopback gRPC peers, not proxied HTTP (development only)"
+                        .to_string(),
+                    default_value: "false".to_string(),
+                    ..Default::default()
+                },
+            ),
+        ])
+    }
+}
+
+fn split_tokens(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|t| !t.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+fn parse_flag(value: &str) -> bool {
+    matches!(
+        value.trim().to_ascii_lowercase().as_str(),
+        "1" | "true" | "yes"
+    )
+}
+
+fn bearer_token(authorization: &str) -> Option<&str> {
+    let (scheme, token) = authorization.trim().split_once(' ')?;
+    let token = token.trim();
+    (scheme.eq_ignore_ascii_case("bearer") && !token.is_empty()).then_some(token)
+}
+
+/// Equality whose running time depends only on the lengths.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    let len = a.len().max(b.len());
+    let mut diff = (a.len() ^ b.len()) as u8 | u8::from(a.len() != b.len());
+    for i in 0..len {
+        diff |= a.get(i).copied().unwrap_or(0) ^ b.get(i).copied().unwrap_or(0);
+    }
+    diff == 0
+}
+
+/// gRPC service wrapper that refuses calls without a valid token.
+#[derive(Clone)]
+pub(crate) struct Authenticated<S> {
+    inner: S,
+    auth: PluginAuth,
+}
+
+impl<S> Authenticated<S> {
+    pub(crate) fn new(inner: S, auth: PluginAuth) -> Self {
+        Self { inner, auth }
+    }
+}
+
+impl<S: NamedService> NamedService for Authenticated<S> {
+    const NAME: &'static str = S::NAME;
+}
+
+impl<S> tonic::codegen::Service<http::Request<BoxBody>> for Authenticated<S>
+where
+    S: tonic::codegen::Service<
+        http::Request<BoxBody>,
+        Response = http::Response<BoxBody>,
+        Error = Infallible,
+    >,
+    S::Future: Send + 'static,
+{
+    type Response = http::Response<BoxBody>;
+    type Error = Infallible;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Infallible>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Infallible>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: http::Request<BoxBody>) -> Self::Future {
+        if !AUTH_EXEMPT_METHODS.contains(&req.uri().path()) {
+            let authorization = req
+                .headers()
+                .get(http::header::AUTHORIZATION)
+                .and_then(|v| v.to_str().ok());
+            let peer = req
+                .extensions()
+                .get::<TcpConnectInfo>()
+                .and_then(|info| info.remote_addr());
+            if let Err(rejection) = self.auth.check(authorization, peer) {
+                let status = rejection.to_status();
+                return Box::pin(std::future::ready(Ok(status.into_http())));
+            }
+        }
+        Box::pin(self.inner.call(req))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn local() -> Option<SocketAddr> {
+        Some("127.0.0.1:40000".parse().unwrap())
+    }
+
+    fn remote() -> Option<SocketAddr> {
+        Some("10.0.0.7:40000".parse().unwrap())
+    }
+
+    fn http(authorization: Option<&str>) -> HttpRequest {
+        HttpRequest {
+            method: "POST".to_string(),
+            path: "/fit".to_string(),
+            headers: authorization
+                .map(|a| HttpHeader {
+                    name: "Authorization".to_string(),
+                    values: vec![a.to_string()],
+                })
+                .into_iter()
+                .collect(),
+            body: vec![],
+        }
+    }
+
+    #[test]
+    fn test_missing_wrong_and_valid_tokens() {
+        let auth = PluginAuth::with_tokens(["s3cret"]);
+        assert_eq!(auth.check(None, remote()), Err(AuthRejection::Missing));
+        assert_eq!(
+            auth.check(Some("Basic s3cret"), remote()),
+            Err(AuthRejection::Missing)
+        );
+        assert_eq!(
+            auth.check(Some("Bearer s3cre"), remote()),
+            Err(AuthRejection::Invalid)
+        );
+        assert_eq!(auth.check(Some("Bearer s3cret"), remote()), Ok(()));
+        assert_eq!(auth.check(Some("bearer  s3cret "), remote()), Ok(()));
+    }
+
+    #[test]
+    fn test_rotation_retires_oldest() {
+        let auth = PluginAuth::with_tokens(["t1"]);
+        auth.rotate("t2");
+        auth.rotate("t3");
+        // Three active: the host can still be mid-roll on any of them
+        for t in ["t1", "t2", "t3"] {
+            assert_eq!(auth.check(Some(&format!("Bearer {}", t)), remote()), Ok(()));
+        }
+        auth.rotate("t4");
+        assert_eq!(
+            auth.check(Some("Bearer t1"), remote()),
+            Err(AuthRejection::Invalid)
+        );
+        assert_eq!(auth.check(Some("Bearer t4"), remote()), Ok(()));
+    }
+
+    #[test]
+    fn test_disabled_and_localhost_exempt() {
+        let auth = PluginAuth::disabled();
+        assert!(!auth.is_enabled());
+        assert_eq!(auth.check(None, remote()), Ok(()));
+
+        let auth = PluginAuth::with_tokens(["s3cret"]);
+        auth.set_localhost_exempt(true);
+        assert_eq!(auth.check(None, local()), Ok(()));
+        assert_eq!(auth.check(None, remote()), Err(AuthRejection::Missing));
+        assert_eq!(auth.check(None, None), Err(AuthRejection::Missing));
+
+        // Proxied HTTP arrives from the host on loopback; it still needs a token
+        assert_eq!(auth.check_http(&http(None)).unwrap_err().status_code, 401);
+        assert!(auth.check_http(&http(Some("Bearer s3cret"))).is_ok());
+    }
+
+    #[test]
+    fn test_http_rejection_never_echoes_token() {
+        let auth = PluginAuth::with_tokens(["s3cret"]);
+        let resp = auth
+            .check_http(&http(Some("Bearer guessed-token")))
+            .unwrap_err();
+        assert_eq!(resp.status_code, 401);
+        assert!(resp
+            .headers
+            .iter()
+            .any(|h| h.name == "WWW-Authenticate" && h.values == ["Bearer"]));
+        let body = String::from_utf8(resp.body).unwrap();
+        assert!(!body.contains("guessed-token"), "{}", body);
+
+        assert_eq!(auth.check_http(&http(None)).unwrap_err().status_code, 401);
+        assert!(auth.check_http(&http(Some("Bearer s3cret"))).is_ok());
+    }
+
+    #[test]
+    fn test_update_from_config() {
+        let auth = PluginAuth::disabled();
+        auth.update_from_config(&HashMap::from([(
+            CONFIG_AUTH_TOKENS.to_string(),
+            "new, old".to_string(),
+        )]));
+        assert!(auth.is_enabled());
+        assert_eq!(auth.check(Some("Bearer old"), remote()), Ok(()));
+
+        // Keys left out keep the current settings
+        auth.update_from_config(&HashMap::new());
+        assert!(auth.is_enabled());
+        auth.update_from_config(&HashMap::from([(
+            CONFIG_AUTH_TOKENS.to_string(),
+            String::new(),
+        )]));
+        assert!(!auth.is_enabled());
+        assert_eq!(PluginAuth::schema_fields().len(), 2);
+    }
+
+    #[test]
+    fn test_constant_time_eq() {
+        assert!(constant_time_eq(b"abc", b"abc"));
+        assert!(!constant_time_eq(b"abc", b"abd"));
+        assert!(!constant_time_eq(b"abc", b"abc\0"));
+        assert!(!constant_time_eq(b"", b"a"));
+        assert!(constant_time_eq(b"", b""));
+    }
+}
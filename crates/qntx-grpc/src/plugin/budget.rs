@@ -0,0 +1,472 @@
+//! Cooperative resource budgets for plugins sharing a machine.
+//!
+//! Plugins size their thread pools and caches for the whole machine. Several
+//! of them on one small VM then oversubscribe it together. The host gives each
+//! plugin a share through environment variables:
+//!
+//! - [`MAX_MEMORY_ENV`]: resident memory ceiling in MiB
+//! - [`MAX_THREADS_ENV`]: worker threads (tokio workers, per-job thread caps)
+//! - [`NICE_ENV`]: scheduling niceness, applied at startup (unix only)
+//!
+//! [`PluginServer`](super::PluginServer) reads the budget and samples the
+//! process RSS every [`DEFAULT_CHECK_INTERVAL`].
+//! Above the ceiling the plugin is under pressure until RSS falls below
+//! [`RECOVERY_RATIO`] of it, so a plugin hovering at the limit doesn't flap.
+//! Pressure callbacks run on every transition. Services refuse new work with
+//! [`BudgetHandle::admit`] while under pressure.
+//!
+//! The tokio worker count is fixed when the runtime starts, so plugins that
+//! honour [`MAX_THREADS_ENV`] build their runtime with
+//! [`ResourceBudget::runtime`] instead of `#[tokio::main]`. On Linux niceness
+//! is per thread and only inherited by threads spawned afterwards, so `main`
+//! calls [`ResourceBudget::apply_nice`] before building the runtime; from
+//! inside a running runtime it would only renice the calling worker.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use tonic::Status;
+use tracing::{info, warn};
+
+/// Environment variable with the plugin's resident memory ceiling, in MiB
+pub const MAX_MEMORY_ENV: &str = "QNTX_PLUGIN_MAX_MEMORY_MB";
+
+/// Environment variable with the plugin's worker thread count
+pub const MAX_THREADS_ENV: &str = "QNTX_PLUGIN_MAX_THREADS";
+
+/// Environment variable with the plugin's niceness (-20..=19)
+pub const NICE_ENV: &str = "QNTX_PLUGIN_NICE";
+
+/// Pressure ends once RSS is below this fraction of the ceiling
+pub const RECOVERY_RATIO: f64 = 0.9;
+
+/// How often [`PluginServer`](super::PluginServer) samples RSS
+pub const DEFAULT_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+const MIB: u64 = 1024 * 1024;
+
+/// Limits the host assigned to this plugin; `None` means unconstrained.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ResourceBudget {
+    pub max_memory_bytes: Option<u64>,
+    pub max_threads: Option<usize>,
+    pub nice: Option<i32>,
+}
+
+impl ResourceBudget {
+    /// Read [`MAX_MEMORY_ENV`], [`MAX_THREADS_ENV`] and [`NICE_ENV`]. Values
+    /// that don't parse are logged and ignored.
+    pub fn from_env() -> Self {
+        Self::from_lookup(|name| std::env::var(name).ok())
+    }
+
+    /// Like [`from_env`](Self::from_env), reading variables through `lookup`.
+    pub fn from_lookup(lookup: impl Fn(&str) -> Option<String>) -> Self {
+        fn parse<T: std::str::FromStr>(name: &str, value: Option<String>) -> Option<T> {
+            let value = value?;
+            let parsed = value.trim().parse().ok();
+            if parsed.is_none() {
+                warn!("Ignoring {}={:?}: not a valid value", name, value);
+            }
+            parsed
+        }
+
+        Self {
+            max_memory_bytes: parse::<u64>(MAX_MEMORY_ENV, lookup(MAX_MEMORY_ENV))
+                .filter(|mb| *mb > 0)
+                .map(|mb| mb.saturating_mul(MIB)),
+            max_threads: parse::<usize>(MAX_THREADS_ENV, lookup(MAX_THREADS_ENV))
+                .filter(|n| *n > 0),
+            nice: parse::<i32>(NICE_ENV, lookup(NICE_ENV)).map(|n| n.clamp(-20, 19)),
+        }
+    }
+
+    /// Threads the plugin should use: the budget, or every core when unset.
+    pub fn threads(&self) -> usize {
+        self.max_threads
+            .unwrap_or_else(|| std::thread::available_parallelism().map_or(1, |n| n.get()))
+    }
+
+    /// Multi-threaded tokio runtime with [`threads`](Self::threads) workers.
+    pub fn runtime(&self) -> std::io::Result<tokio::runtime::Runtime> {
+        tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(self.threads())
+            .enable_all()
+            .build()
+    }
+
+    /// Set the calling thread's niceness to the budget's, if it has one.
+    ///
+    /// Threads spawned afterwards inherit it, so call this from `main` before
+    /// [`runtime`](Self::runtime) or any other pool starts its threads.
+    pub fn apply_nice(&self) -> std::io::Result<()> {
+        let Some(nice) = self.nice else {
+            return Ok(());
+        };
+        #[cfg(unix)]
+        {
+            // Safety: plain syscall; on Linux `who == 0` is the calling thread
+            if unsafe { libc::setpriority(libc::PRIO_PROCESS, 0, nice) } != 0 {
+                return Err(std::io::Error::last_os_error());
+            }
+        }
+        #[cfg(not(unix))]
+        warn!(
+            "Ignoring {}={}: not supported on this platform",
+            NICE_ENV, nice
+        );
+        Ok(())
+    }
+}
+
+/// A transition of the memory pressure state.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PressureEvent {
+    /// RSS went over the ceiling
+    Entered { rss_bytes: u64, limit_bytes: u64 },
+    /// RSS fell below [`RECOVERY_RATIO`] of the ceiling
+    Relieved { rss_bytes: u64, limit_bytes: u64 },
+}
+
+/// Budget next to what the process uses now.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ResourceUsage {
+    pub budget: ResourceBudget,
+    /// Last sampled RSS; `None` before the first sample or where unsupported
+    pub rss_bytes: Option<u64>,
+    pub under_pressure: bool,
+    /// Times pressure was entered since startup
+    pub pressure_events: u64,
+}
+
+type PressureCallback = Box<dyn Fn(&PressureEvent) + Send + Sync>;
+
+struct BudgetState {
+    budget: ResourceBudget,
+    under_pressure: AtomicBool,
+    pressure_events: AtomicU64,
+    /// Last sampled RSS plus one, 0 before the first sample
+    last_rss: AtomicU64,
+    callbacks: Mutex<Vec<(String, PressureCallback)>>,
+}
+
+/// Shared view of the plugin's budget and pressure state.
+#[derive(Clone)]
+pub struct BudgetHandle {
+    state: Arc<BudgetState>,
+}
+
+impl Default for BudgetHandle {
+    fn default() -> Self {
+        Self::new(ResourceBudget::default())
+    }
+}
+
+impl BudgetHandle {
+    pub fn new(budget: ResourceBudget) -> Self {
+        Self {
+            state: Arc::new(BudgetState {
+                budget,
+                under_pressure: AtomicBool::new(false),
+                pressure_events: AtomicU64::new(0),
+                last_rss: AtomicU64::new(0),
+                callbacks: Mutex::new(Vec::new()),
+            }),
+        }
+    }
+
+    pub fn budget(&self) -> ResourceBudget {
+        self.state.budget
+    }
+
+    pub fn is_under_pressure(&self) -> bool {
+        self.state.under_pressure.load(Ordering::Acquire)
+    }
+
+    /// Run `callback` on every pressure transition (shrink caches, log, ...).
+    /// Callbacks run on the sampling task, one at a time in registration order.
+    pub fn on_pressure(
+        &self,
+        name: impl Into<String>,
+        callback: impl Fn(&PressureEvent) + Send + Sync + 'static,
+    ) {
+        self.state
+            .callbacks
+            .lock()
+            .unwrap()
+            .push((name.into(), Box::new(callback)));
+    }
+
+    /// `RESOURCE_EXHAUSTED` while under pressure, for refusing new work.
+    #[allow(clippy::result_large_err)]
+    pub fn admit(&self) -> Result<(), Status> {
+        if !self.is_under_pressure() {
+            return Ok(());
+        }
+        Err(Status::resource_exhausted(format!(
+            "plugin is over its memory budget ({} MiB); retry later",
+            self.state.budget.max_memory_bytes.unwrap_or(0) / MIB
+        )))
+    }
+
+    pub fn usage(&self) -> ResourceUsage {
+        ResourceUsage {
+            budget: self.state.budget,
+            rss_bytes: self.state.last_rss.load(Ordering::Acquire).checked_sub(1),
+            under_pressure: self.is_under_pressure(),
+            pressure_events: self.state.pressure_events.load(Ordering::Acquire),
+        }
+    }
+
+    /// Budget and usage as `HealthResponse.details` / metadata extras.
+    pub fn details(&self) -> HashMap<String, String> {
+        let usage = self.usage();
+        let mut details = HashMap::from([
+            (
+                "budget_threads".to_string(),
+                usage.budget.threads().to_string(),
+            ),
+            (
+                "budget_under_pressure".to_string(),
+                usage.under_pressure.to_string(),
+            ),
+            (
+                "budget_pressure_events".to_string(),
+                usage.pressure_events.to_string(),
+            ),
+        ]);
+        if let Some(limit) = usage.budget.max_memory_bytes {
+            details.insert(
+                "budget_max_memory_mb".to_string(),
+                (limit / MIB).to_string(),
+            );
+        }
+        if let Some(rss) = usage.rss_bytes {
+            details.insert("rss_mb".to_string(), (rss / MIB).to_string());
+        }
+        details
+    }
+
+    /// Sample the process RSS now and apply it; see [`observe`](Self::observe).
+    pub fn check(&self) -> Option<PressureEvent> {
+        self.observe(current_rss()?)
+    }
+
+    /// Record an RSS sample, entering pressure above the ceiling and leaving
+    /// it below [`RECOVERY_RATIO`] of it. Returns the transition, if any,
+    /// after running the callbacks.
+    pub fn observe(&self, rss_bytes: u64) -> Option<PressureEvent> {
+        self.state
+            .last_rss
+            .store(rss_bytes.saturating_add(1), Ordering::Release);
+        let limit_bytes = self.state.budget.max_memory_bytes?;
+
+        let event = if self.is_under_pressure() {
+            let recovery = (limit_bytes as f64 * RECOVERY_RATIO) as u64;
+            (rss_bytes < recovery).then_some(PressureEvent::Relieved {
+                rss_bytes,
+                limit_bytes,
+            })
+        } else {
+            (rss_bytes > limit_bytes).then_some(PressureEvent::Entered {
+                rss_bytes,
+                limit_bytes,
+            })
+        }?;
+
+        match event {
+            PressureEvent::Entered { .. } => {
+                self.state.under_pressure.store(true, Ordering::Release);
+                self.state.pressure_events.fetch_add(1, Ordering::AcqRel);
+                warn!(
+                    "Memory budget exceeded: RSS {} MiB over {} MiB",
+                    rss_bytes / MIB,
+                    limit_bytes / MIB
+                );
+            }
+            PressureEvent::Relieved { .. } => {
+                self.state.under_pressure.store(false, Ordering::Release);
+                info!(
+                    "Memory pressure relieved: RSS {} MiB of {} MiB",
+                    rss_bytes / MIB,
+                    limit_bytes / MIB
+                );
+            }
+        }
+        for (name, callback) in self.state.callbacks.lock().unwrap().iter() {
+            info!("  Running pressure callback: {}", name);
+            callback(&event);
+        }
+        Some(event)
+    }
+
+    /// Sample RSS every `interval` until the returned task is aborted. Does
+    /// nothing without a memory ceiling or where RSS can't be read.
+    pub fn spawn_monitor(&self, interval: Duration) -> tokio::task::JoinHandle<()> {
+        let handle = self.clone();
+        tokio::spawn(async move {
+            if handle.budget().max_memory_bytes.is_none() || current_rss().is_none() {
+                return;
+            }
+            let mut ticks = tokio::time::interval(interval);
+            ticks.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+            loop {
+                ticks.tick().await;
+                handle.check();
+            }
+        })
+    }
+}
+
+/// Resident set size of this process, from `/proc/self/status` (Linux only).
+pub fn current_rss() -> Option<u64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    let kib: u64 = status
+        .lines()
+        .find_map(|line| line.strip_prefix("VmRSS:"))?
+        .trim()
+        .trim_end_matches("kB")
+        .trim()
+        .parse()
+        .ok()?;
+    Some(kib * 1024)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicUsize;
+
+    fn budget(vars: &[(&str, &str)]) -> ResourceBudget {
+        let vars: HashMap<String, String> = vars
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        ResourceBudget::from_lookup(|name| vars.get(name).cloned())
+    }
+
+    #[test]
+    fn test_from_env_parses_and_ignores_invalid() {
+        let parsed = budget(&[
+            (MAX_MEMORY_ENV, "256"),
+            (MAX_THREADS_ENV, " 2 "),
+            (NICE_ENV, "40"),
+        ]);
+        assert_eq!(
+            parsed,
+            ResourceBudget {
+                max_memory_bytes: Some(256 * MIB),
+                max_threads: Some(2),
+                nice: Some(19),
+            }
+        );
+
+        let invalid = budget(&[(MAX_MEMORY_ENV, "lots"), (MAX_THREADS_ENV, "0")]);
+        assert_eq!(invalid, ResourceBudget::default());
+        assert!(invalid.threads() >= 1);
+    }
+
+    #[test]
+    fn test_runtime_uses_budget_threads() {
+        let runtime = budget(&[(MAX_THREADS_ENV, "2")]).runtime().unwrap();
+        assert_eq!(runtime.metrics().num_workers(), 2);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_runtime_workers_inherit_niceness_applied_first() {
+        // In a thread of its own so the test runner's threads keep their niceness
+        std::thread::spawn(|| {
+            let budget = budget(&[(MAX_THREADS_ENV, "2"), (NICE_ENV, "19")]);
+            budget.apply_nice().unwrap();
+            let runtime = budget.runtime().unwrap();
+            // Safety: plain syscall on the calling thread
+            let worker_nice = runtime.block_on(async {
+                tokio::spawn(async { unsafe { libc::getpriority(libc::PRIO_PROCESS, 0) } })
+                    .await
+                    .unwrap()
+            });
+            assert_eq!(worker_nice, 19);
+        })
+        .join()
+        .unwrap();
+    }
+
+    #[test]
+    fn test_pressure_hysteresis_and_admission() {
+        let handle = BudgetHandle::new(ResourceBudget {
+            max_memory_bytes: Some(100 * MIB),
+            ..Default::default()
+        });
+        let fired = Arc::new(Mutex::new(Vec::new()));
+        let log = fired.clone();
+        handle.on_pressure("record", move |event| log.lock().unwrap().push(*event));
+
+        assert_eq!(handle.observe(80 * MIB), None);
+        assert!(handle.admit().is_ok());
+
+        assert!(matches!(
+            handle.observe(120 * MIB),
+            Some(PressureEvent::Entered { .. })
+        ));
+        let refused = handle.admit().unwrap_err();
+        assert_eq!(refused.code(), tonic::Code::ResourceExhausted);
+
+        // Below the ceiling but above the recovery threshold: still under pressure
+        assert_eq!(handle.observe(95 * MIB), None);
+        assert_eq!(handle.observe(120 * MIB), None);
+        assert!(handle.is_under_pressure());
+
+        assert!(matches!(
+            handle.observe(85 * MIB),
+            Some(PressureEvent::Relieved { .. })
+        ));
+        assert!(handle.admit().is_ok());
+        assert_eq!(fired.lock().unwrap().len(), 2);
+
+        let usage = handle.usage();
+        assert_eq!(usage.rss_bytes, Some(85 * MIB));
+        assert_eq!(usage.pressure_events, 1);
+        let details = handle.details();
+        assert_eq!(details["budget_max_memory_mb"], "100");
+        assert_eq!(details["rss_mb"], "85");
+        assert_eq!(details["budget_under_pressure"], "false");
+    }
+
+    #[test]
+    fn test_no_memory_ceiling_never_pressures() {
+        let handle = BudgetHandle::default();
+        assert_eq!(handle.observe(u64::MAX / 2), None);
+        assert!(handle.admit().is_ok());
+        assert!(!handle.details().contains_key("budget_max_memory_mb"));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_callback_fires_when_allocation_exceeds_budget() {
+        let baseline = current_rss().unwrap();
+        let handle = BudgetHandle::new(ResourceBudget {
+            max_memory_bytes: Some(baseline + 64 * MIB),
+            ..Default::default()
+        });
+        let entered = Arc::new(AtomicUsize::new(0));
+        let count = entered.clone();
+        handle.on_pressure("count", move |event| {
+            if matches!(event, PressureEvent::Entered { .. }) {
+                count.fetch_add(1, Ordering::SeqCst);
+            }
+        });
+
+        // Written so every page is resident
+        let ballast = vec![1u8; 160 * MIB as usize];
+        assert!(matches!(
+            handle.check(),
+            Some(PressureEvent::Entered { .. })
+        ));
+        assert_eq!(entered.load(Ordering::SeqCst), 1);
+        assert!(handle.admit().is_err());
+        drop(ballast);
+    }
+}
@@ -0,0 +1,320 @@
+//! Graceful drain for plugin servers.
+//!
+//! When the host upgrades a plugin it sends SIGTERM. Instead of dropping
+//! in-flight RPCs, [`PluginServer`](super::PluginServer):
+//!
+//! 1. stops accepting connections and answers new calls with `UNAVAILABLE`
+//!    (methods in [`DRAIN_EXEMPT_METHODS`] keep working, so the host can still
+//!    ask for health and see the plugin draining)
+//! 2. waits for in-flight calls, up to the drain deadline
+//!    (default 30s, or [`DRAIN_DEADLINE_ENV`] in seconds)
+//! 3. past the deadline, fires [`DrainHandle::cancelled`] and logs every call
+//!    still running
+//! 4. runs the `on_drain` hooks in registration order (flush write buffers,
+//!    close sessions, checkpoint SQLite)
+//!
+//! Calls are counted by a wrapper around the gRPC service, so plugins only
+//! need a [`DrainHandle`] to report draining in `Health` or to make long jobs
+//! stop early on cancellation.
+
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+
+use tokio::sync::Notify;
+use tonic::body::BoxBody;
+use tonic::server::NamedService;
+use tonic::Status;
+
+/// Environment variable overriding the drain deadline, in whole seconds
+pub const DRAIN_DEADLINE_ENV: &str = "QNTX_PLUGIN_DRAIN_DEADLINE_SECS";
+
+/// How long in-flight calls get to finish after a shutdown signal
+pub const DEFAULT_DRAIN_DEADLINE: Duration = Duration::from_secs(30);
+
+/// How long cancelled calls get to wind down before the server is dropped
+pub const DRAIN_CANCEL_GRACE: Duration = Duration::from_secs(1);
+
+/// Methods that are neither counted nor refused while draining. Health and
+/// metadata stay answerable; WebSocket streams live as long as the session
+/// and would otherwise always run into the deadline.
+pub const DRAIN_EXEMPT_METHODS: &[&str] = &[
+    "/protocol.DomainPluginService/Health",
+    "/protocol.DomainPluginService/Metadata",
+    "/protocol.DomainPluginService/Shutdown",
+    "/protocol.DomainPluginService/HandleWebSocket",
+];
+
+/// Drain deadline from [`DRAIN_DEADLINE_ENV`], or the default when unset or
+/// not a number.
+pub fn drain_deadline_from_env() -> Duration {
+    parse_drain_deadline(std::env::var(DRAIN_DEADLINE_ENV).ok().as_deref())
+}
+
+/// Drain deadline for a [`DRAIN_DEADLINE_ENV`] value in whole seconds, or
+/// the default when `None` or not a number.
+fn parse_drain_deadline(value: Option<&str>) -> Duration {
+    value
+        .and_then(|v| v.trim().parse::<u64>().ok())
+        .map(Duration::from_secs)
+        .unwrap_or(DEFAULT_DRAIN_DEADLINE)
+}
+
+/// A call that was still running when it was looked at.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InFlightCall {
+    /// gRPC method path, e.g. `/protocol.DomainPluginService/ExecuteJob`
+    pub method: String,
+    pub elapsed: Duration,
+}
+
+/// Outcome of a drain, kept on the [`DrainHandle`] once the server exits.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DrainReport {
+    /// Calls in flight at the signal that finished in time
+    pub completed: usize,
+    /// Calls still running at the deadline
+    pub cancelled: Vec<InFlightCall>,
+    /// Names of the `on_drain` hooks that ran
+    pub hooks: Vec<String>,
+    pub elapsed: Duration,
+}
+
+#[derive(Default)]
+struct DrainState {
+    draining: AtomicBool,
+    cancelled: AtomicBool,
+    next_call: AtomicU64,
+    calls: Mutex<HashMap<u64, (String, Instant)>>,
+    /// Woken on drain start, cancellation and when the last call ends
+    changed: Notify,
+    /// When the drain started and how many calls were in flight then
+    started: Mutex<Option<(Instant, usize)>>,
+    report: Mutex<Option<DrainReport>>,
+}
+
+/// Shared view of the server's drain state.
+#[derive(Clone, Default)]
+pub struct DrainHandle {
+    state: Arc<DrainState>,
+}
+
+impl DrainHandle {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A shutdown signal arrived; new calls are being refused.
+    pub fn is_draining(&self) -> bool {
+        self.state.draining.load(Ordering::Acquire)
+    }
+
+    /// The drain deadline passed with calls still running.
+    pub fn is_cancelled(&self) -> bool {
+        self.state.cancelled.load(Ordering::Acquire)
+    }
+
+    /// Resolves once the drain deadline has passed. Long-running handlers can
+    /// `select!` on this to clean up and return early.
+    pub async fn cancelled(&self) {
+        self.wait_until(|h| h.is_cancelled()).await
+    }
+
+    /// Resolves once a shutdown signal has arrived.
+    pub async fn draining(&self) {
+        self.wait_until(|h| h.is_draining()).await
+    }
+
+    /// Number of tracked calls running now.
+    pub fn active_calls(&self) -> usize {
+        self.state.calls.lock().unwrap().len()
+    }
+
+    /// Tracked calls running now, longest-running first.
+    pub fn in_flight(&self) -> Vec<InFlightCall> {
+        let mut calls: Vec<InFlightCall> = self
+            .state
+            .calls
+            .lock()
+            .unwrap()
+            .values()
+            .map(|(method, started)| InFlightCall {
+                method: method.clone(),
+                elapsed: started.elapsed(),
+            })
+            .collect();
+        calls.sort_by_key(|c| std::cmp::Reverse(c.elapsed));
+        calls
+    }
+
+    /// Report of the finished drain, if the server has drained.
+    pub fn report(&self) -> Option<DrainReport> {
+        self.state.report.lock().unwrap().clone()
+    }
+
+    pub(crate) fn start_drain(&self) {
+        *self.state.started.lock().unwrap() = Some((Instant::now(), self.active_calls()));
+        self.state.draining.store(true, Ordering::Release);
+        self.state.changed.notify_waiters();
+    }
+
+    pub(crate) fn cancel(&self) {
+        self.state.cancelled.store(true, Ordering::Release);
+        self.state.changed.notify_waiters();
+    }
+
+    pub(crate) fn drain_started(&self) -> Option<(Instant, usize)> {
+        *self.state.started.lock().unwrap()
+    }
+
+    pub(crate) fn set_report(&self, report: DrainReport) {
+        *self.state.report.lock().unwrap() = Some(report);
+    }
+
+    /// Resolves once no tracked call is running.
+    pub(crate) async fn idle(&self) {
+        self.wait_until(|h| h.active_calls() == 0).await
+    }
+
+    fn begin_call(&self, method: String) -> CallGuard {
+        let id = self.state.next_call.fetch_add(1, Ordering::Relaxed);
+        self.state
+            .calls
+            .lock()
+            .unwrap()
+            .insert(id, (method, Instant::now()));
+        CallGuard {
+            handle: self.clone(),
+            id,
+        }
+    }
+
+    async fn wait_until(&self, done: impl Fn(&Self) -> bool) {
+        loop {
+            // Registered before the check, so a notify in between isn't lost
+            let changed = self.state.changed.notified();
+            if done(self) {
+                return;
+            }
+            changed.await;
+        }
+    }
+}
+
+/// Removes its call from the in-flight set when the call ends or is dropped.
+struct CallGuard {
+    handle: DrainHandle,
+    id: u64,
+}
+
+impl Drop for CallGuard {
+    fn drop(&mut self) {
+        let mut calls = self.handle.state.calls.lock().unwrap();
+        calls.remove(&self.id);
+        if calls.is_empty() {
+            self.handle.state.changed.notify_waiters();
+        }
+    }
+}
+
+/// An `on_drain` hook: runs once, after in-flight calls have finished or been
+/// cancelled.
+pub type DrainHook = Box<dyn FnOnce() -> Pin<Box<dyn Future<Output = ()> + Send>> + Send>;
+
+/// gRPC service wrapper that counts in-flight calls and refuses new ones
+/// while draining.
+#[derive(Clone)]
+pub(crate) struct Tracked<S> {
+    inner: S,
+    drain: DrainHandle,
+}
+
+impl<S> Tracked<S> {
+    pub(crate) fn new(inner: S, drain: DrainHandle) -> Self {
+        Self { inner, drain }
+    }
+}
+
+impl<S: NamedService> NamedService for Tracked<S> {
+    const NAME: &'static str = S::NAME;
+}
+
+impl<S> tonic::codegen::Service<http::Request<BoxBody>> for Tracked<S>
+where
+    S: tonic::codegen::Service<
+        http::Request<BoxBody>,
+        Response = http::Response<BoxBody>,
+        Error = Infallible,
+    >,
+    S::Future: Send + 'static,
+{
+    type Response = http::Response<BoxBody>;
+    type Error = Infallible;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Infallible>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Infallible>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: http::Request<BoxBody>) -> Self::Future {
+        let method = req.uri().path().to_string();
+        if DRAIN_EXEMPT_METHODS.contains(&method.as_str()) {
+            return Box::pin(self.inner.call(req));
+        }
+        if self.drain.is_draining() {
+            let status = Status::unavailable("plugin is draining for shutdown");
+            return Box::pin(std::future::ready(Ok(status.into_http())));
+        }
+
+        let guard = self.drain.begin_call(method);
+        let call = self.inner.call(req);
+        Box::pin(async move {
+            let response = call.await;
+            drop(guard);
+            response
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_idle_and_cancel_wake_waiters() {
+        let drain = DrainHandle::new();
+        let guard = drain.begin_call("/test.Slow/Run".to_string());
+        assert_eq!(drain.active_calls(), 1);
+        assert_eq!(drain.in_flight()[0].method, "/test.Slow/Run");
+
+        let waiter = tokio::spawn({
+            let drain = drain.clone();
+            async move { drain.idle().await }
+        });
+        tokio::task::yield_now().await;
+        assert!(!waiter.is_finished());
+        drop(guard);
+        waiter.await.unwrap();
+
+        let cancelled = tokio::spawn({
+            let drain = drain.clone();
+            async move { drain.cancelled().await }
+        });
+        drain.cancel();
+        cancelled.await.unwrap();
+        assert!(drain.is_cancelled());
+    }
+
+    #[test]
+    fn test_parse_drain_deadline() {
+        assert_eq!(parse_drain_deadline(Some("5")), Duration::from_secs(5));
+        assert_eq!(parse_drain_deadline(Some(" 5 ")), Duration::from_secs(5));
+        assert_eq!(parse_drain_deadline(Some("soon")), DEFAULT_DRAIN_DEADLINE);
+        assert_eq!(parse_drain_deadline(None), DEFAULT_DRAIN_DEADLINE);
+    }
+}
@@ -0,0 +1,611 @@
+//! Request guards for plugin HTTP handlers.
+//!
+//! `HttpGuard` sits in front of a plugin's `handle_http` routing and rejects
+//! requests before they reach a handler:
+//! - bodies larger than the route's `max_body_bytes` → 413
+//! - requests beyond the route's token bucket → 429 with `Retry-After`
+//! - requests beyond the route's concurrency cap → 429 with `Retry-After`
+//!
+//! Limits are read from the plugin's `InitializeRequest.config` map (see
+//! [`HttpLimits::from_config`]) and advertised through [`HttpLimits::schema_fields`].
+//! Following the config convention, `0` means literal zero: a rate of `0` admits
+//! no requests, it does not disable limiting.
+//!
+//! Rate buckets keyed by a caller header are bounded by [`MAX_RATE_BUCKETS`]:
+//! the key is caller-controlled, so the guard forgets buckets that have
+//! refilled (they are the same as a new one) and, failing that, the least
+//! recently used ones.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! let guard = HttpGuard::new(HttpLimits::default());
+//! let _permit = match guard.admit(&req) {
+//!     Ok(permit) => permit,
+//!     Err(rejection) => return Ok(Response::new(rejection)),
+//! };
+//! // ... route the request; the concurrency slot is released when `_permit` drops
+//! ```
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use super::proto::{ConfigFieldSchema, HttpHeader, HttpRequest, HttpResponse};
+use crate::error::{Error, Result};
+
+/// Config key for the per-route body size limit in bytes.
+pub const CONFIG_MAX_BODY_BYTES: &str = "http_max_body_bytes";
+/// Config key for the sustained request rate per route, in requests per second.
+pub const CONFIG_RATE_PER_SECOND: &str = "http_rate_per_second";
+/// Config key for the token bucket size (requests allowed in a burst).
+pub const CONFIG_BURST: &str = "http_burst";
+/// Config key for the number of requests a route may serve at once.
+pub const CONFIG_MAX_CONCURRENT: &str = "http_max_concurrent";
+/// Config key naming a request header whose value splits rate buckets per caller.
+pub const CONFIG_RATE_KEY_HEADER: &str = "http_rate_key_header";
+
+/// Rate buckets kept at most, across routes and callers.
+pub const MAX_RATE_BUCKETS: usize = 10_000;
+
+/// Limits applied to a single route.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RouteLimits {
+    pub max_body_bytes: usize,
+    pub rate_per_second: f64,
+    pub burst: u32,
+    pub max_concurrent: usize,
+}
+
+impl Default for RouteLimits {
+    fn default() -> Self {
+        Self {
+            max_body_bytes: 16 * 1024 * 1024,
+            rate_per_second: 20.0,
+            burst: 40,
+            max_concurrent: 8,
+        }
+    }
+}
+
+/// Limits for all routes of a plugin: defaults plus per-route overrides.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct HttpLimits {
+    pub defaults: RouteLimits,
+    /// Overrides keyed by `"METHOD /path"`.
+    pub routes: HashMap<String, RouteLimits>,
+    /// Header whose value (e.g. an auth token or peer ID) keys rate buckets
+    /// in addition to the route. `None` shares one bucket per route.
+    pub rate_key_header: Option<String>,
+}
+
+impl HttpLimits {
+    /// Read defaults from a plugin config map. Missing keys keep their defaults;
+    /// unparseable or negative values are a configuration error.
+    pub fn from_config(config: &HashMap<String, String>) -> Result<Self> {
+        let mut limits = Self::default();
+        let d = &mut limits.defaults;
+        if let Some(v) = config.get(CONFIG_MAX_BODY_BYTES) {
+            d.max_body_bytes = parse_config(CONFIG_MAX_BODY_BYTES, v)?;
+        }
+        if let Some(v) = config.get(CONFIG_RATE_PER_SECOND) {
+            let rate: f64 = parse_config(CONFIG_RATE_PER_SECOND, v)?;
+            if !rate.is_finite() || rate < 0.0 {
+                return Err(Error::Config(format!(
+                    "{} must be a non-negative number, got {:?}",
+                    CONFIG_RATE_PER_SECOND, v
+                )));
+            }
+            d.rate_per_second = rate;
+        }
+        if let Some(v) = config.get(CONFIG_BURST) {
+            d.burst = parse_config(CONFIG_BURST, v)?;
+        }
+        if let Some(v) = config.get(CONFIG_MAX_CONCURRENT) {
+            d.max_concurrent = parse_config(CONFIG_MAX_CONCURRENT, v)?;
+        }
+        limits.rate_key_header = config
+            .get(CONFIG_RATE_KEY_HEADER)
+            .filter(|v| !v.is_empty())
+            .cloned();
+        Ok(limits)
+    }
+
+    /// Override the limits for one route.
+    pub fn route(mut self, method: &str, path: &str, limits: RouteLimits) -> Self {
+        self.routes.insert(route_key(method, path), limits);
+        self
+    }
+
+    /// Limits that apply to `method path`.
+    pub fn for_route(&self, method: &str, path: &str) -> &RouteLimits {
+        self.routes
+            .get(&route_key(method, path))
+            .unwrap_or(&self.defaults)
+    }
+
+    /// Config schema entries describing the limit keys, for `ConfigSchema` responses.
+    pub fn schema_fields() -> HashMap<String, ConfigFieldSchema> {
+        let d = RouteLimits::default();
+        let number = |description: &str, default: String| ConfigFieldSchema {
+            r#type: "number".to_string(),
+            description: description.to_string(),
+            default_value: default,
+            min_value: "0".to_string(),
+            ..Default::default()
+        };
+        HashMap::from([
+            (
+                CONFIG_MAX_BODY_BYTES.to_string(),
+                number(
+                    "Largest HTTP request body accepted per route, in bytes",
+                    d.max_body_bytes.to_string(),
+                ),
+            ),
+            (
+                CONFIG_RATE_PER_SECOND.to_string(),
+                number(
+                    "Sustained HTTP requests per second allowed per route",
+                    d.rate_per_second.to_string(),
+                ),
+            ),
+            (
+                CONFIG_BURST.to_string(),
+                number(
+                    "HTTP requests allowed in a burst per route",
+                    d.burst.to_string(),
+                ),
+            ),
+            (
+                CONFIG_MAX_CONCURRENT.to_string(),
+                number(
+                    "HTTP requests served at once per route",
+                    d.max_concurrent.to_string(),
+                ),
+            ),
+            (
+                CONFIG_RATE_KEY_HEADER.to_string(),
+                ConfigFieldSchema {
+                    r#type: "string".to_string(),
+                    description:
+                        "Request header (e.g. authorization) whose value splits rate limits per caller"
+                            .to_string(),
+                    ..Default::default()
+                },
+            ),
+        ])
+    }
+}
+
+fn parse_config<T: std::str::FromStr>(key: &str, value: &str) -> Result<T>
+where
+    T::Err: std::fmt::Display,
+{
+    value
+        .trim()
+        .parse()
+        .map_err(|e| Error::Config(format!("invalid {} {:?}: {}", key, value, e)))
+}
+
+fn route_key(method: &str, path: &str) -> String {
+    format!("{} {}", method.to_ascii_uppercase(), path)
+}
+
+/// Rejection counts since the guard was created.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RejectionCounts {
+    pub body_too_large: u64,
+    pub rate_limited: u64,
+    pub concurrency_limited: u64,
+}
+
+impl RejectionCounts {
+    pub fn total(&self) -> u64 {
+        self.body_too_large + self.rate_limited + self.concurrency_limited
+    }
+}
+
+struct Bucket {
+    tokens: f64,
+    refilled_at: Instant,
+    /// When the bucket is back to its burst size; `None` if it never refills
+    full_at: Option<Instant>,
+}
+
+/// Enforces [`HttpLimits`] for a plugin's HTTP routes.
+pub struct HttpGuard {
+    limits: Mutex<HttpLimits>,
+    buckets: Mutex<HashMap<String, Bucket>>,
+    in_flight: Mutex<HashMap<String, Arc<AtomicUsize>>>,
+    body_too_large: AtomicU64,
+    rate_limited: AtomicU64,
+    concurrency_limited: AtomicU64,
+}
+
+/// Concurrency slot held while a request is being served. Released on drop.
+pub struct HttpPermit {
+    in_flight: Arc<AtomicUsize>,
+}
+
+impl Drop for HttpPermit {
+    fn drop(&mut self) {
+        self.in_flight.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+impl HttpGuard {
+    pub fn new(limits: HttpLimits) -> Self {
+        Self {
+            limits: Mutex::new(limits),
+            buckets: Mutex::new(HashMap::new()),
+            in_flight: Mutex::new(HashMap::new()),
+            body_too_large: AtomicU64::new(0),
+            rate_limited: AtomicU64::new(0),
+            concurrency_limited: AtomicU64::new(0),
+        }
+    }
+
+    /// Replace the limits (e.g. after `Initialize`). Rate buckets start full again;
+    /// requests already in flight keep their slots.
+    pub fn set_limits(&self, limits: HttpLimits) {
+        *self.limits.lock().unwrap() = limits;
+        self.buckets.lock().unwrap().clear();
+    }
+
+    pub fn limits(&self) -> HttpLimits {
+        self.limits.lock().unwrap().clone()
+    }
+
+    pub fn rejections(&self) -> RejectionCounts {
+        RejectionCounts {
+            body_too_large: self.body_too_large.load(Ordering::Relaxed),
+            rate_limited: self.rate_limited.load(Ordering::Relaxed),
+            concurrency_limited: self.concurrency_limited.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Check a request against its route's limits. On success the returned permit
+    /// holds a concurrency slot until dropped; on rejection the error is the
+    /// response to send back.
+    pub fn admit(&self, req: &HttpRequest) -> std::result::Result<HttpPermit, HttpResponse> {
+        let key = route_key(&req.method, &req.path);
+        let (route, rate_key_header) = {
+            let limits = self.limits.lock().unwrap();
+            (
+                limits.for_route(&req.method, &req.path).clone(),
+                limits.rate_key_header.clone(),
+            )
+        };
+
+        if req.body.len() > route.max_body_bytes {
+            self.body_too_large.fetch_add(1, Ordering::Relaxed);
+            return Err(rejection(
+                413,
+                &format!(
+                    "request body of {} bytes exceeds the {} byte limit for {}",
+                    req.body.len(),
+                    route.max_body_bytes,
+                    key
+                ),
+                None,
+            ));
+        }
+
+        // Rate bucket: per route, optionally split by caller identity
+        let bucket_key = match &rate_key_header {
+            Some(header) => format!("{} {}", key, header_value(req, header).unwrap_or("")),
+            None => key.clone(),
+        };
+        if let Err(retry_after) = self.take_token(&bucket_key, &route) {
+            self.rate_limited.fetch_add(1, Ordering::Relaxed);
+            return Err(rejection(
+                429,
+                &format!(
+                    "rate limit of {}/s exceeded for {}",
+                    route.rate_per_second, key
+                ),
+                Some(retry_after),
+            ));
+        }
+
+        let in_flight = self
+            .in_flight
+            .lock()
+            .unwrap()
+            .entry(key.clone())
+            .or_default()
+            .clone();
+        let claimed = in_flight.fetch_update(Ordering::AcqRel, Ordering::Acquire, |n| {
+            (n < route.max_concurrent).then_some(n + 1)
+        });
+        if claimed.is_err() {
+            self.concurrency_limited.fetch_add(1, Ordering::Relaxed);
+            return Err(rejection(
+                429,
+                &format!(
+                    "{} concurrent requests already in progress for {}",
+                    route.max_concurrent, key
+                ),
+                Some(1),
+            ));
+        }
+
+        Ok(HttpPermit { in_flight })
+    }
+
+    /// Take one token, or return the whole seconds until one is available.
+    fn take_token(&self, bucket_key: &str, route: &RouteLimits) -> std::result::Result<(), u64> {
+        let capacity = f64::from(route.burst);
+        let now = Instant::now();
+        let mut buckets = self.buckets.lock().unwrap();
+        if !buckets.contains_key(bucket_key) && buckets.len() >= MAX_RATE_BUCKETS {
+            evict_buckets(&mut buckets, now);
+        }
+        let bucket = buckets.entry(bucket_key.to_string()).or_insert(Bucket {
+            tokens: capacity,
+            refilled_at: now,
+            full_at: Some(now),
+        });
+
+        let elapsed = now.duration_since(bucket.refilled_at).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * route.rate_per_second).min(capacity);
+        bucket.refilled_at = now;
+
+        let taken = bucket.tokens >= 1.0;
+        if taken {
+            bucket.tokens -= 1.0;
+        }
+        bucket.full_at = if bucket.tokens >= capacity {
+            Some(now)
+        } else if route.rate_per_second > 0.0 {
+            let secs = (capacity - bucket.tokens) / route.rate_per_second;
+            Duration::try_from_secs_f64(secs)
+                .ok()
+                .and_then(|d| now.checked_add(d))
+        } else {
+            None
+        };
+        if taken {
+            return Ok(());
+        }
+        if route.rate_per_second <= 0.0 || capacity < 1.0 {
+            // Never refills to a whole token; callers should back off for good
+            return Err(u64::from(u32::MAX));
+        }
+        let wait = (1.0 - bucket.tokens) / route.rate_per_second;
+        Err(wait.ceil().max(1.0) as u64)
+    }
+}
+
+/// Make room for a new bucket: drop the buckets that have refilled, and if
+/// that frees nothing, the least recently used tenth.
+fn evict_buckets(buckets: &mut HashMap<String, Bucket>, now: Instant) {
+    buckets.retain(|_, b| b.full_at.is_none_or(|full_at| full_at > now));
+    if buckets.len() < MAX_RATE_BUCKETS {
+        return;
+    }
+    let mut used: Vec<Instant> = buckets.values().map(|b| b.refilled_at).collect();
+    let keep = MAX_RATE_BUCKETS - MAX_RATE_BUCKETS / 10;
+    let (_, cutoff, _) = used.select_nth_unstable(buckets.len() - keep);
+    let cutoff = *cutoff;
+    buckets.retain(|_, b| b.refilled_at > cutoff);
+}
+
+fn header_value<'a>(req: &'a HttpRequest, name: &str) -> Option<&'a str> {
+    req.headers
+        .iter()
+        .find(|h| h.name.eq_ignore_ascii_case(name))
+        .and_then(|h| h.values.first())
+        .map(String::as_str)
+}
+
+fn rejection(status_code: i32, message: &str, retry_after: Option<u64>) -> HttpResponse {
+    let mut headers = vec![HttpHeader {
+        name: "Content-Type".to_string(),
+        values: vec!["application/json".to_string()],
+    }];
+    if let Some(secs) = retry_after {
+        headers.push(HttpHeader {
+            name: "Retry-After".to_string(),
+            values: vec![secs.to_string()],
+        });
+    }
+    HttpResponse {
+        status_code,
+        headers,
+        body: serde_json::to_vec(&serde_json::json!({ "error": message })).unwrap_or_default(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(method: &str, path: &str, body: &[u8]) -> HttpRequest {
+        HttpRequest {
+            method: method.to_string(),
+            path: path.to_string(),
+            headers: vec![],
+            body: body.to_vec(),
+        }
+    }
+
+    fn header<'a>(resp: &'a HttpResponse, name: &str) -> Option<&'a str> {
+        resp.headers
+            .iter()
+            .find(|h| h.name == name)
+            .map(|h| h.values[0].as_str())
+    }
+
+    fn limits(rate_per_second: f64, burst: u32) -> HttpLimits {
+        HttpLimits {
+            defaults: RouteLimits {
+                rate_per_second,
+                burst,
+                ..RouteLimits::default()
+            },
+            ..HttpLimits::default()
+        }
+    }
+
+    #[test]
+    fn test_oversized_body_rejected_with_413() {
+        let guard = HttpGuard::new(HttpLimits::default().route(
+            "POST",
+            "/fit",
+            RouteLimits {
+                max_body_bytes: 8,
+                ..RouteLimits::default()
+            },
+        ));
+
+        let resp = guard
+            .admit(&request("POST", "/fit", b"0123456789"))
+            .err()
+            .unwrap();
+        assert_eq!(resp.status_code, 413);
+        let body: serde_json::Value = serde_json::from_slice(&resp.body).unwrap();
+        assert!(body["error"].as_str().unwrap().contains("8 byte limit"));
+
+        assert!(guard.admit(&request("POST", "/fit", b"01234567")).is_ok());
+        // Other routes use the defaults
+        assert!(guard
+            .admit(&request("POST", "/other", b"0123456789"))
+            .is_ok());
+        assert_eq!(guard.rejections().body_too_large, 1);
+    }
+
+    #[test]
+    fn test_rate_limit_rejects_with_retry_after() {
+        let guard = HttpGuard::new(limits(0.5, 3));
+
+        for _ in 0..3 {
+            assert!(guard.admit(&request("GET", "/status", b"")).is_ok());
+        }
+        let resp = guard.admit(&request("GET", "/status", b"")).err().unwrap();
+        assert_eq!(resp.status_code, 429);
+        assert_eq!(header(&resp, "Retry-After"), Some("2"));
+
+        // Buckets are per route: other routes keep being served
+        assert!(guard.admit(&request("POST", "/fit", b"{}")).is_ok());
+        assert_eq!(guard.rejections().rate_limited, 1);
+    }
+
+    #[test]
+    fn test_rate_key_header_splits_buckets() {
+        let mut l = limits(0.001, 1);
+        l.rate_key_header = Some("x-peer".to_string());
+        let guard = HttpGuard::new(l);
+
+        let from = |peer: &str| HttpRequest {
+            headers: vec![HttpHeader {
+                name: "X-Peer".to_string(),
+                values: vec![peer.to_string()],
+            }],
+            ..request("GET", "/status", b"")
+        };
+        assert!(guard.admit(&from("luke")).is_ok());
+        assert!(guard.admit(&from("luke")).is_err());
+        assert!(guard.admit(&from("leia")).is_ok());
+    }
+
+    #[test]
+    fn test_rate_buckets_are_bounded() {
+        let mut l = limits(0.001, 1);
+        l.rate_key_header = Some("x-peer".to_string());
+        let guard = HttpGuard::new(l);
+        let from = |peer: usize| HttpRequest {
+            headers: vec![HttpHeader {
+                name: "X-Peer".to_string(),
+                values: vec![format!("peer-{}", peer)],
+            }],
+            ..request("GET", "/status", b"")
+        };
+
+        // Drained buckets never refill here, so only LRU eviction bounds them
+        for peer in 0..MAX_RATE_BUCKETS * 3 {
+            assert!(guard.admit(&from(peer)).is_ok());
+            assert!(guard.buckets.lock().unwrap().len() <= MAX_RATE_BUCKETS);
+        }
+        // Recent callers are still limited
+        assert!(guard.admit(&from(MAX_RATE_BUCKETS * 3 - 1)).is_err());
+    }
+
+    #[test]
+    fn test_refilled_buckets_are_evicted_first() {
+        let mut l = limits(1000.0, 1);
+        l.rate_key_header = Some("x-peer".to_string());
+        let guard = HttpGuard::new(l);
+        let from = |peer: &str| HttpRequest {
+            headers: vec![HttpHeader {
+                name: "X-Peer".to_string(),
+                values: vec![peer.to_string()],
+            }],
+            ..request("GET", "/status", b"")
+        };
+
+        for peer in 0..MAX_RATE_BUCKETS {
+            assert!(guard.admit(&from(&peer.to_string())).is_ok());
+        }
+        std::thread::sleep(Duration::from_millis(20));
+        assert!(guard.admit(&from("new")).is_ok());
+        assert_eq!(guard.buckets.lock().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_concurrency_cap_released_on_drop() {
+        let guard = HttpGuard::new(HttpLimits {
+            defaults: RouteLimits {
+                max_concurrent: 1,
+                ..RouteLimits::default()
+            },
+            ..HttpLimits::default()
+        });
+
+        let permit = guard.admit(&request("POST", "/fit", b"")).unwrap();
+        let resp = guard.admit(&request("POST", "/fit", b"")).err().unwrap();
+        assert_eq!(resp.status_code, 429);
+        assert_eq!(header(&resp, "Retry-After"), Some("1"));
+
+        drop(permit);
+        assert!(guard.admit(&request("POST", "/fit", b"")).is_ok());
+        assert_eq!(guard.rejections().concurrency_limited, 1);
+    }
+
+    #[test]
+    fn test_zero_rate_admits_nothing() {
+        let guard = HttpGuard::new(limits(0.0, 0));
+        assert_eq!(
+            guard
+                .admit(&request("GET", "/status", b""))
+                .err()
+                .unwrap()
+                .status_code,
+            429
+        );
+    }
+
+    #[test]
+    fn test_from_config() {
+        let config = HashMap::from([
+            (CONFIG_MAX_BODY_BYTES.to_string(), "1024".to_string()),
+            (CONFIG_RATE_PER_SECOND.to_string(), "2.5".to_string()),
+            (
+                CONFIG_RATE_KEY_HEADER.to_string(),
+                "authorization".to_string(),
+            ),
+        ]);
+        let limits = HttpLimits::from_config(&config).unwrap();
+        assert_eq!(limits.defaults.max_body_bytes, 1024);
+        assert_eq!(limits.defaults.rate_per_second, 2.5);
+        assert_eq!(limits.defaults.burst, RouteLimits::default().burst);
+        assert_eq!(limits.rate_key_header.as_deref(), Some("authorization"));
+
+        let bad = HashMap::from([(CONFIG_BURST.to_string(), "-1".to_string())]);
+        let err = HttpLimits::from_config(&bad).unwrap_err();
+        assert!(err.to_string().contains(CONFIG_BURST));
+
+        assert_eq!(HttpLimits::schema_fields().len(), 5);
+    }
+}
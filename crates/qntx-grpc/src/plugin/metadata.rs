@@ -0,0 +1,151 @@
+//! Standard `MetadataResponse` construction for plugins.
+//!
+//! Every plugin answers `Metadata` with the same core fields: name, version,
+//! the commit it was built from, the job types and HTTP routes it serves, and
+//! how long it has been running. `PluginMetadata` collects these once at
+//! startup and renders a fresh response per call, so uptime stays current and
+//! plugins don't hand-assemble the message.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! let metadata = PluginMetadata::new("reduce", env!("CARGO_PKG_VERSION"))
+//!     .description("Dimensionality reduction plugin")
+//!     .job_types(["reduce.umap", "reduce.pca"])
+//!     .http_route("POST", "/fit");
+//!
+//! async fn metadata(&self, _: Request<Empty>) -> Result<Response<MetadataResponse>, Status> {
+//!     Ok(Response::new(self.metadata.response()))
+//! }
+//! ```
+
+use std::collections::HashMap;
+use std::time::Instant;
+
+use super::proto::MetadataResponse;
+
+/// Commit hash baked in at build time via `QNTX_COMMIT_HASH`.
+pub const BUILD_COMMIT_HASH: &str = match option_env!("QNTX_COMMIT_HASH") {
+    Some(hash) => hash,
+    None => "unknown",
+};
+
+/// Builder for a plugin's `MetadataResponse`.
+///
+/// Clones share the original start time, so a copy handed to a service
+/// reports the same uptime as the one held by [`PluginServer`](super::PluginServer).
+#[derive(Clone, Debug)]
+pub struct PluginMetadata {
+    name: String,
+    version: String,
+    qntx_version: String,
+    description: String,
+    author: String,
+    license: String,
+    commit_hash: String,
+    job_types: Vec<String>,
+    http_routes: Vec<String>,
+    extras: HashMap<String, String>,
+    started_at: Instant,
+}
+
+impl PluginMetadata {
+    /// Start metadata for a plugin; the uptime clock starts now.
+    pub fn new(name: impl Into<String>, version: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            version: version.into(),
+            qntx_version: ">=0.1.0".to_string(),
+            description: String::new(),
+            author: "QNTX Contributors".to_string(),
+            license: "MIT".to_string(),
+            commit_hash: BUILD_COMMIT_HASH.to_string(),
+            job_types: Vec::new(),
+            http_routes: Vec::new(),
+            extras: HashMap::new(),
+            started_at: Instant::now(),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn version(&self) -> &str {
+        &self.version
+    }
+
+    /// Set the QNTX version requirement (default `>=0.1.0`).
+    pub fn qntx_version(mut self, requirement: impl Into<String>) -> Self {
+        self.qntx_version = requirement.into();
+        self
+    }
+
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.description = description.into();
+        self
+    }
+
+    pub fn author(mut self, author: impl Into<String>) -> Self {
+        self.author = author.into();
+        self
+    }
+
+    pub fn license(mut self, license: impl Into<String>) -> Self {
+        self.license = license.into();
+        self
+    }
+
+    /// Override the build commit (defaults to [`BUILD_COMMIT_HASH`]).
+    pub fn commit_hash(mut self, hash: impl Into<String>) -> Self {
+        self.commit_hash = hash.into();
+        self
+    }
+
+    /// Set the job handler names the plugin executes.
+    pub fn job_types<I, S>(mut self, job_types: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.job_types = job_types.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Add an HTTP route served through `HandleHTTP`.
+    pub fn http_route(mut self, method: &str, path: &str) -> Self {
+        self.http_routes.push(format!("{} {}", method, path));
+        self
+    }
+
+    /// Add a plugin-specific field. Later values replace earlier ones.
+    pub fn extra(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.extras.insert(key.into(), value.into());
+        self
+    }
+
+    /// Render the response, computing uptime at call time.
+    pub fn response(&self) -> MetadataResponse {
+        self.response_with(HashMap::new())
+    }
+
+    /// Render the response with runtime extras merged over the static ones.
+    pub fn response_with(&self, extras: HashMap<String, String>) -> MetadataResponse {
+        let mut merged = self.extras.clone();
+        merged.extend(extras);
+
+        MetadataResponse {
+            name: self.name.clone(),
+            version: self.version.clone(),
+            qntx_version: self.qntx_version.clone(),
+            description: self.description.clone(),
+            author: self.author.clone(),
+            license: self.license.clone(),
+            commit_hash: self.commit_hash.clone(),
+            job_types: self.job_types.clone(),
+            http_routes: self.http_routes.clone(),
+            uptime_seconds: self.started_at.elapsed().as_secs(),
+            extras: merged,
+        }
+    }
+}
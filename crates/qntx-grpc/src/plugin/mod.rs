@@ -2,12 +2,29 @@
 //!
 //! Provides common scaffolding for building QNTX plugins:
 //! - Server setup with graceful shutdown
+//! - Standard `MetadataResponse` construction
+//! - Graceful drain of in-flight calls on shutdown
+//! - Cooperative resource budgets (threads, memory pressure, niceness)
+//! - Bearer-token auth for gRPC calls and proxied HTTP requests
+//! - Request guards (body size, rate, concurrency) for HTTP handlers
+//! - Method-aware HTTP routing with 404/405 responses
+//! - Recording and replay of host↔plugin sessions for fixture tests
+//! - Batched, crash-safe attestation writes for high-frequency sources
 //! - Proto definitions (compiled from plugin/grpc/protocol/)
 //! - Common service patterns
 
+pub mod auth;
+pub mod budget;
+pub mod drain;
 mod ensure_type;
+pub mod limits;
+mod metadata;
+pub mod recording;
+pub mod replay;
+pub mod router;
 mod server;
 mod shutdown;
+pub mod write_buffer;
 
 pub mod proto {
     //! Protobuf definitions and gRPC services.
@@ -21,6 +38,18 @@ pub mod proto {
     tonic::include_proto!("protocol");
 }
 
+pub use auth::{AuthRejection, PluginAuth};
+pub use budget::{BudgetHandle, PressureEvent, ResourceBudget, ResourceUsage};
+pub use drain::{DrainHandle, DrainReport, InFlightCall};
 pub use ensure_type::{ensure_types, TypeDef};
+pub use limits::{HttpGuard, HttpLimits, HttpPermit, RejectionCounts, RouteLimits};
+pub use metadata::{PluginMetadata, BUILD_COMMIT_HASH};
+pub use recording::{Recording, Redactor, SessionRecorder};
+pub use replay::{replay, ReplayOptions, ReplayReport, Session};
+pub use router::{Held, Middleware, RouteMatch, Router};
 pub use server::PluginServer;
 pub use shutdown::shutdown_signal;
+pub use write_buffer::{
+    AtsStoreSink, AttestationSink, AttestationWriteBuffer, SinkError, WriteBufferConfig,
+    WriteBufferMetrics,
+};
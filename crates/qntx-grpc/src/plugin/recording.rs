@@ -0,0 +1,609 @@
+//! Recording of host↔plugin gRPC sessions for replay tests.
+//!
+//! Wrapping a service in [`Recording`] writes every `DomainPluginService`
+//! call (request metadata, request, response or status) to a session file
+//! when [`RECORD_ENV`] names one, and is a plain pass-through otherwise.
+//! [`replay`](super::replay) plays such a file back against a service and
+//! diffs the responses.
+//!
+//! Sessions are JSON lines so fixtures stay reviewable in PRs: a
+//! [`SessionHeader`] line, then one [`SessionEvent`] per call in completion
+//! order. Byte fields (`body`, `data`, `payload`, `result`) are written as
+//! text when they are UTF-8. Values of redacted fields, map keys, metadata
+//! and HTTP headers become [`REDACTED`]; see [`DEFAULT_REDACTED_FIELDS`]
+//! and [`RECORD_REDACT_ENV`].
+//!
+//! `HandleWebSocket` is recorded with its metadata and outgoing messages
+//! only, because the incoming stream belongs to the wrapped service.
+
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::future::Future;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio_stream::{Stream, StreamExt};
+use tonic::metadata::MetadataMap;
+use tonic::{Request, Response, Status, Streaming};
+use tracing::{info, warn};
+
+use super::proto::domain_plugin_service_server::DomainPluginService;
+use super::proto::{
+    ConfigSchemaResponse, Empty, ExecuteJobRequest, ExecuteJobResponse, GlyphDefResponse,
+    HealthResponse, HttpRequest, HttpResponse, InitializeRequest, InitializeResponse,
+    MetadataResponse, ParseAxQueryRequest, ParseAxQueryResponse, WebSocketMessage,
+};
+use crate::error::Result;
+
+/// Environment variable naming the session file to record into
+pub const RECORD_ENV: &str = "QNTX_PLUGIN_RECORD";
+
+/// Environment variable with extra field names to redact (comma-separated)
+pub const RECORD_REDACT_ENV: &str = "QNTX_PLUGIN_RECORD_REDACT";
+
+/// `format` of a session header line
+pub const SESSION_FORMAT: &str = "qntx-plugin-session";
+
+/// Session format version written by this recorder
+pub const SESSION_VERSION: u32 = 1;
+
+/// Value written in place of redacted data
+pub const REDACTED: &str = "[redacted]";
+
+/// Field, map key, metadata and header names redacted in every recording
+/// (compared case-insensitively)
+pub const DEFAULT_REDACTED_FIELDS: &[&str] = &[
+    "authorization",
+    "auth_token",
+    "plugin_auth_tokens",
+    "token",
+    "api_key",
+    "password",
+    "secret",
+];
+
+/// Proto `bytes` fields of the domain service, written as text when UTF-8
+pub const BYTES_FIELDS: &[&str] = &["body", "data", "payload", "result"];
+
+/// Transport headers left out of recorded metadata
+const TRANSPORT_HEADERS: &[&str] = &[
+    "content-type",
+    "te",
+    "user-agent",
+    "grpc-accept-encoding",
+    "grpc-encoding",
+    "grpc-timeout",
+];
+
+/// First line of a session file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SessionHeader {
+    pub format: String,
+    pub version: u32,
+    pub plugin: String,
+    /// Epoch milliseconds when recording started
+    pub recorded_at: u64,
+}
+
+/// gRPC status of a failed call.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecordedStatus {
+    /// `tonic::Code` name, e.g. `InvalidArgument`
+    pub code: String,
+    pub message: String,
+}
+
+impl From<&Status> for RecordedStatus {
+    fn from(status: &Status) -> Self {
+        Self {
+            code: format!("{:?}", status.code()),
+            message: status.message().to_string(),
+        }
+    }
+}
+
+/// One message of a recorded stream.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StreamMessage {
+    pub t_ms: u64,
+    pub message: Value,
+}
+
+/// One call, written when it completes.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SessionEvent {
+    pub seq: u64,
+    /// Milliseconds from the start of recording to the start of the call
+    pub t_ms: u64,
+    /// RPC name as in the proto, e.g. `HandleHTTP`
+    pub method: String,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub metadata: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub response: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<RecordedStatus>,
+    /// Outgoing messages of a streaming call
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stream: Option<Vec<StreamMessage>>,
+}
+
+/// Which names [`redact`] hides.
+#[derive(Debug, Clone)]
+pub struct Redactor {
+    fields: Vec<String>,
+}
+
+impl Default for Redactor {
+    fn default() -> Self {
+        Self::new(DEFAULT_REDACTED_FIELDS.iter().copied())
+    }
+}
+
+impl Redactor {
+    pub fn new<I, S>(fields: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        Self {
+            fields: fields
+                .into_iter()
+                .map(|f| f.as_ref().trim().to_ascii_lowercase())
+                .filter(|f| !f.is_empty())
+                .collect(),
+        }
+    }
+
+    /// The defaults plus the names in [`RECORD_REDACT_ENV`].
+    pub fn from_env() -> Self {
+        let extra = std::env::var(RECORD_REDACT_ENV).unwrap_or_default();
+        Self::new(
+            DEFAULT_REDACTED_FIELDS
+                .iter()
+                .copied()
+                .chain(extra.split(',')),
+        )
+    }
+
+    pub fn is_redacted(&self, name: &str) -> bool {
+        let name = name.to_ascii_lowercase();
+        self.fields.contains(&name)
+    }
+
+    /// Replace redacted values in `value`: non-empty object fields and map
+    /// entries by key, and `{name, values}` HTTP headers by header name.
+    pub fn redact(&self, value: &mut Value) {
+        match value {
+            Value::Object(map) => {
+                let header_name = map.get("name").and_then(Value::as_str).map(str::to_string);
+                if let (Some(name), Some(values)) = (header_name, map.get_mut("values")) {
+                    if self.is_redacted(&name) {
+                        *values = Value::from(vec![REDACTED]);
+                    }
+                }
+                for (key, field) in map.iter_mut() {
+                    // Objects under a redacted name (schema entries) are walked instead
+                    let secret = matches!(field, Value::String(s) if !s.is_empty())
+                        || matches!(field, Value::Array(_) | Value::Number(_));
+                    if secret && self.is_redacted(key) {
+                        *field = Value::from(REDACTED);
+                    } else {
+                        self.redact(field);
+                    }
+                }
+            }
+            Value::Array(items) => items.iter_mut().for_each(|item| self.redact(item)),
+            _ => {}
+        }
+    }
+}
+
+/// Write byte fields as text where they are UTF-8.
+pub fn bytes_to_text(value: &mut Value) {
+    walk_bytes_fields(value, &|field| {
+        let Value::Array(items) = field else { return };
+        let bytes: Option<Vec<u8>> = items
+            .iter()
+            .map(|b| b.as_u64().and_then(|b| u8::try_from(b).ok()))
+            .collect();
+        if let Some(text) = bytes.and_then(|b| String::from_utf8(b).ok()) {
+            *field = Value::String(text);
+        }
+    });
+}
+
+/// Inverse of [`bytes_to_text`], so a recorded message deserializes again.
+pub fn text_to_bytes(value: &mut Value) {
+    walk_bytes_fields(value, &|field| {
+        if let Value::String(text) = field {
+            *field = Value::from(text.as_bytes().to_vec());
+        }
+    });
+}
+
+/// A message as it appears in a session file: bytes as text, redacted.
+pub fn session_value<T: Serialize>(message: &T, redactor: &Redactor) -> Value {
+    let mut value = serde_json::to_value(message).unwrap_or(Value::Null);
+    bytes_to_text(&mut value);
+    redactor.redact(&mut value);
+    value
+}
+
+fn walk_bytes_fields(value: &mut Value, convert: &dyn Fn(&mut Value)) {
+    match value {
+        Value::Object(map) => {
+            for (key, field) in map.iter_mut() {
+                if BYTES_FIELDS.contains(&key.as_str()) {
+                    convert(field);
+                } else {
+                    walk_bytes_fields(field, convert);
+                }
+            }
+        }
+        Value::Array(items) => items
+            .iter_mut()
+            .for_each(|item| walk_bytes_fields(item, convert)),
+        _ => {}
+    }
+}
+
+struct RecorderState {
+    out: BufWriter<File>,
+    next_seq: u64,
+}
+
+/// Appends session events to a file, one JSON line each.
+pub struct SessionRecorder {
+    state: Mutex<RecorderState>,
+    started: Instant,
+    redactor: Redactor,
+}
+
+impl SessionRecorder {
+    /// Create `path` (truncating it) and write the session header.
+    pub fn create(path: impl AsRef<Path>, plugin: &str, redactor: Redactor) -> Result<Self> {
+        let mut out = BufWriter::new(File::create(path)?);
+        let header = SessionHeader {
+            format: SESSION_FORMAT.to_string(),
+            version: SESSION_VERSION,
+            plugin: plugin.to_string(),
+            recorded_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |d| d.as_millis() as u64),
+        };
+        serde_json::to_writer(&mut out, &header)?;
+        out.write_all(b"\n")?;
+        out.flush()?;
+        Ok(Self {
+            state: Mutex::new(RecorderState { out, next_seq: 0 }),
+            started: Instant::now(),
+            redactor,
+        })
+    }
+
+    /// Recorder for the file in [`RECORD_ENV`], if set. A file that can't be
+    /// created is logged and recording stays off.
+    pub fn from_env(plugin: &str) -> Option<Arc<Self>> {
+        let path = std::env::var(RECORD_ENV).ok().filter(|p| !p.is_empty())?;
+        match Self::create(&path, plugin, Redactor::from_env()) {
+            Ok(recorder) => {
+                info!("Recording gRPC session to {}", path);
+                Some(Arc::new(recorder))
+            }
+            Err(e) => {
+                warn!("Not recording: cannot create {}: {}", path, e);
+                None
+            }
+        }
+    }
+
+    fn elapsed_ms(&self) -> u64 {
+        self.started.elapsed().as_millis() as u64
+    }
+
+    fn message<T: Serialize>(&self, message: &T) -> Value {
+        session_value(message, &self.redactor)
+    }
+
+    fn metadata(&self, metadata: &MetadataMap) -> BTreeMap<String, String> {
+        metadata
+            .clone()
+            .into_headers()
+            .iter()
+            .filter(|(name, _)| !TRANSPORT_HEADERS.contains(&name.as_str()))
+            .filter_map(|(name, value)| {
+                let value = if self.redactor.is_redacted(name.as_str()) {
+                    REDACTED
+                } else {
+                    value.to_str().ok()?
+                };
+                Some((name.to_string(), value.to_string()))
+            })
+            .collect()
+    }
+
+    fn write(&self, mut event: SessionEvent) {
+        let mut state = self.state.lock().unwrap();
+        event.seq = state.next_seq;
+        state.next_seq += 1;
+        let written = serde_json::to_writer(&mut state.out, &event)
+            .map_err(std::io::Error::from)
+            .and_then(|()| state.out.write_all(b"\n"))
+            .and_then(|()| state.out.flush());
+        if let Err(e) = written {
+            warn!("Failed to record {} call: {}", event.method, e);
+        }
+    }
+}
+
+/// `DomainPluginService` wrapper recording every call into a session file.
+pub struct Recording<S> {
+    inner: S,
+    recorder: Option<Arc<SessionRecorder>>,
+}
+
+impl<S> Recording<S> {
+    /// Record into `recorder`, or pass calls straight through when `None`.
+    pub fn new(inner: S, recorder: Option<Arc<SessionRecorder>>) -> Self {
+        Self { inner, recorder }
+    }
+
+    /// Record when [`RECORD_ENV`] is set; see [`SessionRecorder::from_env`].
+    pub fn from_env(inner: S, plugin: &str) -> Self {
+        Self::new(inner, SessionRecorder::from_env(plugin))
+    }
+
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    async fn unary<Req, Resp, Fut>(
+        &self,
+        method: &str,
+        request: Request<Req>,
+        call: impl FnOnce(Request<Req>) -> Fut,
+    ) -> std::result::Result<Response<Resp>, Status>
+    where
+        Req: Serialize,
+        Resp: Serialize,
+        Fut: Future<Output = std::result::Result<Response<Resp>, Status>>,
+    {
+        let Some(recorder) = &self.recorder else {
+            return call(request).await;
+        };
+        let t_ms = recorder.elapsed_ms();
+        let metadata = recorder.metadata(request.metadata());
+        let recorded_request = recorder.message(request.get_ref());
+
+        let result = call(request).await;
+        let (response, error) = match &result {
+            Ok(response) => (Some(recorder.message(response.get_ref())), None),
+            Err(status) => (None, Some(RecordedStatus::from(status))),
+        };
+        recorder.write(SessionEvent {
+            seq: 0,
+            t_ms,
+            method: method.to_string(),
+            metadata,
+            request: Some(recorded_request),
+            response,
+            error,
+            stream: None,
+        });
+        result
+    }
+}
+
+type WebSocketStream =
+    Pin<Box<dyn Stream<Item = std::result::Result<WebSocketMessage, Status>> + Send>>;
+
+#[tonic::async_trait]
+impl<S: DomainPluginService> DomainPluginService for Recording<S> {
+    async fn metadata(
+        &self,
+        request: Request<Empty>,
+    ) -> std::result::Result<Response<MetadataResponse>, Status> {
+        self.unary("Metadata", request, |r| self.inner.metadata(r))
+            .await
+    }
+
+    async fn initialize(
+        &self,
+        request: Request<InitializeRequest>,
+    ) -> std::result::Result<Response<InitializeResponse>, Status> {
+        self.unary("Initialize", request, |r| self.inner.initialize(r))
+            .await
+    }
+
+    async fn shutdown(
+        &self,
+        request: Request<Empty>,
+    ) -> std::result::Result<Response<Empty>, Status> {
+        self.unary("Shutdown", request, |r| self.inner.shutdown(r))
+            .await
+    }
+
+    async fn handle_http(
+        &self,
+        request: Request<HttpRequest>,
+    ) -> std::result::Result<Response<HttpResponse>, Status> {
+        self.unary("HandleHTTP", request, |r| self.inner.handle_http(r))
+            .await
+    }
+
+    type HandleWebSocketStream = WebSocketStream;
+
+    async fn handle_web_socket(
+        &self,
+        request: Request<Streaming<WebSocketMessage>>,
+    ) -> std::result::Result<Response<Self::HandleWebSocketStream>, Status> {
+        let Some(recorder) = self.recorder.clone() else {
+            let response = self.inner.handle_web_socket(request).await?;
+            let stream: WebSocketStream = Box::pin(response.into_inner());
+            return Ok(Response::new(stream));
+        };
+        let t_ms = recorder.elapsed_ms();
+        let metadata = recorder.metadata(request.metadata());
+        let event = SessionEvent {
+            seq: 0,
+            t_ms,
+            method: "HandleWebSocket".to_string(),
+            metadata,
+            request: None,
+            response: None,
+            error: None,
+            stream: Some(Vec::new()),
+        };
+
+        let inner = match self.inner.handle_web_socket(request).await {
+            Ok(response) => response.into_inner(),
+            Err(status) => {
+                recorder.write(SessionEvent {
+                    error: Some(RecordedStatus::from(&status)),
+                    stream: None,
+                    ..event
+                });
+                return Err(status);
+            }
+        };
+
+        // Written once the outgoing stream ends or the client goes away
+        let log = Arc::new(Mutex::new(Some(event)));
+        let guard = StreamLog {
+            recorder: recorder.clone(),
+            log: log.clone(),
+        };
+        // Items are tonic's own Result<_, Status>
+        #[allow(clippy::result_large_err)]
+        let stream = inner.map(move |item| {
+            let _keep = &guard;
+            if let Some(event) = log.lock().unwrap().as_mut() {
+                match &item {
+                    Ok(message) => event
+                        .stream
+                        .get_or_insert_with(Vec::new)
+                        .push(StreamMessage {
+                            t_ms: recorder.elapsed_ms(),
+                            message: recorder.message(message),
+                        }),
+                    Err(status) => event.error = Some(RecordedStatus::from(status)),
+                }
+            }
+            item
+        });
+        let stream: WebSocketStream = Box::pin(stream);
+        Ok(Response::new(stream))
+    }
+
+    async fn health(
+        &self,
+        request: Request<Empty>,
+    ) -> std::result::Result<Response<HealthResponse>, Status> {
+        self.unary("Health", request, |r| self.inner.health(r))
+            .await
+    }
+
+    async fn config_schema(
+        &self,
+        request: Request<Empty>,
+    ) -> std::result::Result<Response<ConfigSchemaResponse>, Status> {
+        self.unary("ConfigSchema", request, |r| self.inner.config_schema(r))
+            .await
+    }
+
+    async fn register_glyphs(
+        &self,
+        request: Request<Empty>,
+    ) -> std::result::Result<Response<GlyphDefResponse>, Status> {
+        self.unary("RegisterGlyphs", request, |r| self.inner.register_glyphs(r))
+            .await
+    }
+
+    async fn execute_job(
+        &self,
+        request: Request<ExecuteJobRequest>,
+    ) -> std::result::Result<Response<ExecuteJobResponse>, Status> {
+        self.unary("ExecuteJob", request, |r| self.inner.execute_job(r))
+            .await
+    }
+
+    async fn parse_ax_query(
+        &self,
+        request: Request<ParseAxQueryRequest>,
+    ) -> std::result::Result<Response<ParseAxQueryResponse>, Status> {
+        self.unary("ParseAxQuery", request, |r| self.inner.parse_ax_query(r))
+            .await
+    }
+}
+
+/// Writes a stream's event when the stream is dropped.
+struct StreamLog {
+    recorder: Arc<SessionRecorder>,
+    log: Arc<Mutex<Option<SessionEvent>>>,
+}
+
+impl Drop for StreamLog {
+    fn drop(&mut self) {
+        if let Some(event) = self.log.lock().unwrap().take() {
+            self.recorder.write(event);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redacts_fields_map_keys_and_headers() {
+        let redactor = Redactor::new(DEFAULT_REDACTED_FIELDS.iter().copied().chain(["api_url"]));
+        let mut value = serde_json::json!({
+            "config": {"plugin_auth_tokens": "s3cret", "reduce_max_threads": "2", "API_URL": "x"},
+            "headers": [
+                {"name": "Authorization", "values": ["Bearer s3cret"]},
+                {"name": "Accept", "values": ["*/*"]},
+            ],
+        });
+        redactor.redact(&mut value);
+        assert_eq!(
+            value,
+            serde_json::json!({
+                "config": {"plugin_auth_tokens": REDACTED, "reduce_max_threads": "2", "API_URL": REDACTED},
+                "headers": [
+                    {"name": "Authorization", "values": [REDACTED]},
+                    {"name": "Accept", "values": ["*/*"]},
+                ],
+            })
+        );
+    }
+
+    #[test]
+    fn test_bytes_fields_round_trip_as_text() {
+        let request = HttpRequest {
+            method: "POST".into(),
+            path: "/fit".into(),
+            headers: vec![],
+            body: br#"{"method":"pca"}"#.to_vec(),
+        };
+        let mut value = serde_json::to_value(&request).unwrap();
+        bytes_to_text(&mut value);
+        assert_eq!(value["body"], r#"{"method":"pca"}"#);
+
+        text_to_bytes(&mut value);
+        let back: HttpRequest = serde_json::from_value(value).unwrap();
+        assert_eq!(back, request);
+
+        // Binary stays a byte array
+        let mut binary = serde_json::json!({"body": [0xff, 0x00]});
+        bytes_to_text(&mut binary);
+        assert_eq!(binary["body"], serde_json::json!([255, 0]));
+    }
+}
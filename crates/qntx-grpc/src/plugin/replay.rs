@@ -0,0 +1,389 @@
+//! Replay of recorded plugin sessions.
+//!
+//! [`replay`] serves a `DomainPluginService` on a loopback port, sends each
+//! recorded call through the generated gRPC client (metadata included) and
+//! diffs the response or status against the recording. Fields that differ
+//! between runs by design (uptimes, versions, RSS) are skipped through
+//! [`ReplayOptions::ignore`].
+//!
+//! ```ignore
+//! let session = Session::load("tests/fixtures/reduce_session.jsonl")?;
+//! let options = ReplayOptions::default().ignore("uptime_seconds");
+//! replay(ReducePluginService::new()?, &session, &options)
+//!     .await?
+//!     .assert_match();
+//! ```
+
+use std::fmt;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+use tokio::net::TcpListener;
+use tokio_stream::wrappers::TcpListenerStream;
+use tonic::metadata::{MetadataKey, MetadataValue};
+use tonic::transport::{Channel, Server};
+use tonic::{Request, Response, Status};
+
+use super::proto::domain_plugin_service_client::DomainPluginServiceClient;
+use super::proto::domain_plugin_service_server::{DomainPluginService, DomainPluginServiceServer};
+use super::proto::{Empty, ExecuteJobRequest, HttpRequest, InitializeRequest, ParseAxQueryRequest};
+use super::recording::{
+    session_value, text_to_bytes, RecordedStatus, Redactor, SessionEvent, SessionHeader, REDACTED,
+    SESSION_FORMAT, SESSION_VERSION,
+};
+use crate::error::{Error, Result};
+
+/// A parsed session file.
+#[derive(Debug, Clone)]
+pub struct Session {
+    pub header: SessionHeader,
+    pub events: Vec<SessionEvent>,
+}
+
+impl Session {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| Error::Io(e).wrap(format!("failed to read {}", path.display())))?;
+        Self::parse(&text)
+    }
+
+    pub fn parse(text: &str) -> Result<Self> {
+        let mut lines = text.lines().filter(|l| !l.trim().is_empty());
+        let header: SessionHeader = serde_json::from_str(
+            lines
+                .next()
+                .ok_or_else(|| Error::Config("empty session file".to_string()))?,
+        )?;
+        if header.format != SESSION_FORMAT {
+            return Err(Error::Config(format!(
+                "not a plugin session (format {:?})",
+                header.format
+            )));
+        }
+        if header.version > SESSION_VERSION {
+            return Err(Error::Config(format!(
+                "session version {} is newer than supported version {}",
+                header.version, SESSION_VERSION
+            )));
+        }
+        let events = lines
+            .map(serde_json::from_str)
+            .collect::<std::result::Result<Vec<SessionEvent>, _>>()?;
+        Ok(Self { header, events })
+    }
+}
+
+/// How [`replay`] plays a session back.
+#[derive(Debug, Clone, Default)]
+pub struct ReplayOptions {
+    /// Wait until each call's recorded offset before sending it
+    pub respect_timing: bool,
+    /// Response fields left out of the diff, at any depth
+    pub ignore_fields: Vec<String>,
+}
+
+impl ReplayOptions {
+    pub fn respect_timing(mut self, respect: bool) -> Self {
+        self.respect_timing = respect;
+        self
+    }
+
+    pub fn ignore(mut self, field: impl Into<String>) -> Self {
+        self.ignore_fields.push(field.into());
+        self
+    }
+}
+
+/// One difference between a recorded and a replayed call.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mismatch {
+    pub seq: u64,
+    pub method: String,
+    /// JSON path of the differing value, e.g. `$.body.error`
+    pub path: String,
+    pub expected: Value,
+    pub actual: Value,
+}
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "#{} {} {}: expected {}, got {}",
+            self.seq, self.method, self.path, self.expected, self.actual
+        )
+    }
+}
+
+/// Outcome of a [`replay`].
+#[derive(Debug, Clone, Default)]
+pub struct ReplayReport {
+    pub replayed: usize,
+    /// `(seq, method)` of calls that can't be replayed (streaming RPCs)
+    pub skipped: Vec<(u64, String)>,
+    pub mismatches: Vec<Mismatch>,
+}
+
+impl ReplayReport {
+    pub fn is_match(&self) -> bool {
+        self.mismatches.is_empty()
+    }
+
+    /// Panic with every mismatch listed unless the replay matched.
+    pub fn assert_match(&self) {
+        if self.is_match() {
+            return;
+        }
+        let lines: Vec<String> = self.mismatches.iter().map(|m| m.to_string()).collect();
+        panic!(
+            "replay diverged from recording in {} place(s):\n  {}",
+            lines.len(),
+            lines.join("\n  ")
+        );
+    }
+}
+
+/// Replay `session` against `service` and diff the responses.
+pub async fn replay<S: DomainPluginService>(
+    service: S,
+    session: &Session,
+    options: &ReplayOptions,
+) -> Result<ReplayReport> {
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let addr = listener.local_addr()?;
+    let server = tokio::spawn(
+        Server::builder()
+            .add_service(DomainPluginServiceServer::new(service))
+            .serve_with_incoming(TcpListenerStream::new(listener)),
+    );
+    let channel = Channel::from_shared(format!("http://{}", addr))
+        .map_err(|e| Error::context("invalid replay address", e))?
+        .connect()
+        .await;
+    let result = match channel {
+        Ok(channel) => run(DomainPluginServiceClient::new(channel), session, options).await,
+        Err(e) => Err(e.into()),
+    };
+    server.abort();
+    result
+}
+
+async fn run(
+    mut client: DomainPluginServiceClient<Channel>,
+    session: &Session,
+    options: &ReplayOptions,
+) -> Result<ReplayReport> {
+    let redactor = Redactor::default();
+    let started = Instant::now();
+    let mut report = ReplayReport::default();
+
+    for event in &session.events {
+        if options.respect_timing {
+            let due = Duration::from_millis(event.t_ms);
+            if let Some(wait) = due.checked_sub(started.elapsed()) {
+                tokio::time::sleep(wait).await;
+            }
+        }
+
+        macro_rules! call {
+            ($method:ident, $request:ty) => {{
+                let request: $request = decode(event)?;
+                outcome(
+                    client.$method(with_metadata(request, event)).await,
+                    &redactor,
+                )
+            }};
+        }
+        let (response, error) = match event.method.as_str() {
+            "Metadata" => call!(metadata, Empty),
+            "Initialize" => call!(initialize, InitializeRequest),
+            "Shutdown" => call!(shutdown, Empty),
+            "HandleHTTP" => call!(handle_http, HttpRequest),
+            "Health" => call!(health, Empty),
+            "ConfigSchema" => call!(config_schema, Empty),
+            "RegisterGlyphs" => call!(register_glyphs, Empty),
+            "ExecuteJob" => call!(execute_job, ExecuteJobRequest),
+            "ParseAxQuery" => call!(parse_ax_query, ParseAxQueryRequest),
+            _ => {
+                report.skipped.push((event.seq, event.method.clone()));
+                continue;
+            }
+        };
+        report.replayed += 1;
+
+        let mut diff = Diff {
+            seq: event.seq,
+            method: &event.method,
+            ignore: &options.ignore_fields,
+            out: &mut report.mismatches,
+        };
+        let to_value = |s: &Option<RecordedStatus>| {
+            s.as_ref()
+                .map_or(Value::Null, |s| serde_json::to_value(s).unwrap_or_default())
+        };
+        diff.compare("$.error", &to_value(&event.error), &to_value(&error));
+        if let (Some(expected), Some(actual)) = (&event.response, &response) {
+            diff.compare("$", expected, actual);
+        }
+    }
+    Ok(report)
+}
+
+fn decode<T: DeserializeOwned + Default>(event: &SessionEvent) -> Result<T> {
+    let Some(request) = &event.request else {
+        return Ok(T::default());
+    };
+    let mut request = request.clone();
+    text_to_bytes(&mut request);
+    serde_json::from_value(request)
+        .map_err(|e| Error::Serialization(e).wrap(format!("event #{} {}", event.seq, event.method)))
+}
+
+/// Attach recorded metadata. Redacted values are left off, so replayed
+/// services should run without auth.
+fn with_metadata<T>(message: T, event: &SessionEvent) -> Request<T> {
+    let mut request = Request::new(message);
+    for (name, value) in &event.metadata {
+        if value == REDACTED {
+            continue;
+        }
+        if let (Ok(key), Ok(value)) = (
+            MetadataKey::from_bytes(name.as_bytes()),
+            MetadataValue::try_from(value.as_str()),
+        ) {
+            request.metadata_mut().insert(key, value);
+        }
+    }
+    request
+}
+
+fn outcome<T: Serialize>(
+    result: std::result::Result<Response<T>, Status>,
+    redactor: &Redactor,
+) -> (Option<Value>, Option<RecordedStatus>) {
+    match result {
+        Ok(response) => (Some(session_value(response.get_ref(), redactor)), None),
+        Err(status) => (None, Some(RecordedStatus::from(&status))),
+    }
+}
+
+struct Diff<'a> {
+    seq: u64,
+    method: &'a str,
+    ignore: &'a [String],
+    out: &'a mut Vec<Mismatch>,
+}
+
+impl Diff<'_> {
+    /// Structural diff. Strings holding JSON objects or arrays (HTTP bodies)
+    /// are compared as JSON.
+    fn compare(&mut self, path: &str, expected: &Value, actual: &Value) {
+        match (expected, actual) {
+            (Value::Object(e), Value::Object(a)) => {
+                let keys: std::collections::BTreeSet<&String> = e.keys().chain(a.keys()).collect();
+                for key in keys {
+                    if self.ignore.iter().any(|i| i == key) {
+                        continue;
+                    }
+                    let null = Value::Null;
+                    self.compare(
+                        &format!("{}.{}", path, key),
+                        e.get(key).unwrap_or(&null),
+                        a.get(key).unwrap_or(&null),
+                    );
+                }
+            }
+            (Value::Array(e), Value::Array(a)) if e.len() == a.len() => {
+                for (i, (e, a)) in e.iter().zip(a).enumerate() {
+                    self.compare(&format!("{}[{}]", path, i), e, a);
+                }
+            }
+            (Value::String(e), Value::String(a)) if e != a => {
+                match (embedded_json(e), embedded_json(a)) {
+                    (Some(e), Some(a)) => self.compare(path, &e, &a),
+                    _ => self.push(path, expected, actual),
+                }
+            }
+            _ if expected != actual => self.push(path, expected, actual),
+            _ => {}
+        }
+    }
+
+    fn push(&mut self, path: &str, expected: &Value, actual: &Value) {
+        self.out.push(Mismatch {
+            seq: self.seq,
+            method: self.method.to_string(),
+            path: path.to_string(),
+            expected: expected.clone(),
+            actual: actual.clone(),
+        });
+    }
+}
+
+fn embedded_json(text: &str) -> Option<Value> {
+    serde_json::from_str(text)
+        .ok()
+        .filter(|v: &Value| v.is_object() || v.is_array())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn diff(expected: Value, actual: Value, ignore: &[&str]) -> Vec<String> {
+        let ignore: Vec<String> = ignore.iter().map(|s| s.to_string()).collect();
+        let mut out = Vec::new();
+        Diff {
+            seq: 0,
+            method: "HandleHTTP",
+            ignore: &ignore,
+            out: &mut out,
+        }
+        .compare("$", &expected, &actual);
+        out.into_iter().map(|m| m.path).collect()
+    }
+
+    #[test]
+    fn test_diff_ignores_fields_at_any_depth() {
+        let expected = serde_json::json!({"healthy": true, "details": {"uptime_seconds": "3"}});
+        let actual = serde_json::json!({"healthy": false, "details": {"uptime_seconds": "9"}});
+        assert_eq!(
+            diff(expected, actual, &["uptime_seconds"]),
+            vec!["$.healthy"]
+        );
+    }
+
+    #[test]
+    fn test_diff_compares_json_bodies_structurally() {
+        let expected = serde_json::json!({"body": r#"{"a":1,"b":[1,2]}"#});
+        let same = serde_json::json!({"body": r#"{"b": [1, 2], "a": 1}"#});
+        let changed = serde_json::json!({"body": r#"{"a":2,"b":[1,2]}"#});
+        assert!(diff(expected.clone(), same, &[]).is_empty());
+        assert_eq!(diff(expected, changed, &[]), vec!["$.body.a"]);
+    }
+
+    #[test]
+    fn test_parse_rejects_newer_versions() {
+        let header = |version: u32| {
+            format!(
+                r#"{{"format":"{}","version":{},"plugin":"p","recorded_at":0}}"#,
+                SESSION_FORMAT, version
+            )
+        };
+        let session = Session::parse(&format!(
+            "{}\n{}\n",
+            header(1),
+            r#"{"seq":0,"t_ms":0,"method":"Health","request":{},"response":{"healthy":true}}"#
+        ))
+        .unwrap();
+        assert_eq!(session.events.len(), 1);
+        assert_eq!(session.events[0].method, "Health");
+
+        assert!(Session::parse(&header(SESSION_VERSION + 1)).is_err());
+        assert!(Session::parse("").is_err());
+    }
+}
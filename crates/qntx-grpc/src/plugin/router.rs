@@ -0,0 +1,475 @@
+//! Method-aware routing for plugin HTTP handlers.
+//!
+//! `Router` matches an `HttpRequest` against registered `METHOD /pattern`
+//! routes and hands back the caller's route tag plus extracted path params;
+//! the plugin then dispatches on the tag. Unmatched requests get a ready-made
+//! response instead:
+//! - path matches no pattern → 404, with the list of routes in debug mode
+//! - path matches but the method doesn't → 405 with an `Allow` header
+//!
+//! Patterns are static segments plus `{name}` params, each matching exactly one
+//! non-empty segment (`/artifacts/{job_id}/{name}`). When several patterns match,
+//! the one with the fewest params wins, so `/session/new` beats `/session/{id}`.
+//! The query string is ignored for matching.
+//!
+//! Middleware runs on a matched request before dispatch, either for every route
+//! ([`Router::wrap_all`]) or for the route registered last ([`Router::with`]).
+//! [`HttpGuard`] is one: its permit is kept in the match until the response is built.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! #[derive(Clone, Copy)]
+//! enum Route { Status, Artifact }
+//!
+//! let router = Router::new()
+//!     .route("GET", "/status", Route::Status)
+//!     .route("GET", "/artifacts/{job_id}/{name}", Route::Artifact)
+//!     .with(guard.clone());
+//!
+//! let matched = match router.resolve(&req) {
+//!     Ok(matched) => matched,
+//!     Err(response) => return Ok(Response::new(response)),
+//! };
+//! match matched.route {
+//!     Route::Status => handle_status(),
+//!     Route::Artifact => handle_artifact(matched.param("job_id"), matched.param("name")),
+//! }
+//! ```
+
+use std::any::Any;
+use std::sync::Arc;
+
+use super::limits::HttpGuard;
+use super::proto::{HttpHeader, HttpRequest, HttpResponse};
+
+/// Value a middleware keeps alive for the duration of the request.
+pub type Held = Option<Box<dyn Any + Send>>;
+
+/// Path params in pattern order, as `(name, value)`.
+type Params = Vec<(String, String)>;
+
+/// Hook run on a matched request before its handler.
+pub trait Middleware: Send + Sync {
+    /// Return `Err(response)` to answer the request without reaching the handler.
+    fn before(&self, req: &HttpRequest, pattern: &str) -> Result<Held, HttpResponse>;
+}
+
+impl<F> Middleware for F
+where
+    F: Fn(&HttpRequest, &str) -> Result<Held, HttpResponse> + Send + Sync,
+{
+    fn before(&self, req: &HttpRequest, pattern: &str) -> Result<Held, HttpResponse> {
+        self(req, pattern)
+    }
+}
+
+impl Middleware for HttpGuard {
+    fn before(&self, req: &HttpRequest, _pattern: &str) -> Result<Held, HttpResponse> {
+        self.admit(req)
+            .map(|permit| Some(Box::new(permit) as Box<dyn Any + Send>))
+    }
+}
+
+impl<M: Middleware + ?Sized> Middleware for Arc<M> {
+    fn before(&self, req: &HttpRequest, pattern: &str) -> Result<Held, HttpResponse> {
+        (**self).before(req, pattern)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    Static(String),
+    Param(String),
+}
+
+struct RouteEntry<T> {
+    method: String,
+    pattern: String,
+    segments: Vec<Segment>,
+    tag: T,
+    middleware: Vec<Arc<dyn Middleware>>,
+}
+
+impl<T> RouteEntry<T> {
+    fn param_count(&self) -> usize {
+        self.segments
+            .iter()
+            .filter(|s| matches!(s, Segment::Param(_)))
+            .count()
+    }
+
+    /// Param values if `path` matches this pattern.
+    fn match_path(&self, path: &[&str]) -> Option<Params> {
+        if path.len() != self.segments.len() {
+            return None;
+        }
+        let mut params = Vec::new();
+        for (segment, part) in self.segments.iter().zip(path) {
+            match segment {
+                Segment::Static(s) if s == part => {}
+                Segment::Param(name) if !part.is_empty() => {
+                    params.push((name.clone(), part.to_string()));
+                }
+                _ => return None,
+            }
+        }
+        Some(params)
+    }
+}
+
+/// A request matched to a route.
+pub struct RouteMatch<T> {
+    pub route: T,
+    /// The pattern that matched, e.g. `/artifacts/{job_id}/{name}`
+    pub pattern: String,
+    pub params: Vec<(String, String)>,
+    /// Values kept alive by middleware (e.g. concurrency permits)
+    pub held: Vec<Box<dyn Any + Send>>,
+}
+
+impl<T> RouteMatch<T> {
+    /// Value of path param `name`.
+    pub fn param(&self, name: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(k, _)| k == name)
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+/// Route table for a plugin's `handle_http`. `T` is the caller's route tag.
+pub struct Router<T> {
+    routes: Vec<RouteEntry<T>>,
+    global: Vec<Arc<dyn Middleware>>,
+    debug: bool,
+}
+
+impl<T: Clone> Default for Router<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Clone> Router<T> {
+    pub fn new() -> Self {
+        Self {
+            routes: Vec::new(),
+            global: Vec::new(),
+            debug: false,
+        }
+    }
+
+    /// Register `method pattern` (method compared case-insensitively).
+    ///
+    /// # Panics
+    /// If `pattern` doesn't start with `/` or has an empty `{}` param.
+    pub fn route(mut self, method: &str, pattern: &str, tag: T) -> Self {
+        self.routes.push(RouteEntry {
+            method: method.to_ascii_uppercase(),
+            pattern: pattern.to_string(),
+            segments: parse_pattern(pattern),
+            tag,
+            middleware: Vec::new(),
+        });
+        self
+    }
+
+    /// Attach middleware to the route registered last.
+    ///
+    /// # Panics
+    /// If no route has been registered yet.
+    pub fn with(mut self, middleware: impl Middleware + 'static) -> Self {
+        self.routes
+            .last_mut()
+            .expect("Router::with called before any route")
+            .middleware
+            .push(Arc::new(middleware));
+        self
+    }
+
+    /// Attach middleware to every route; it runs before per-route middleware.
+    pub fn wrap_all(mut self, middleware: impl Middleware + 'static) -> Self {
+        self.global.push(Arc::new(middleware));
+        self
+    }
+
+    /// List the registered routes in 404 bodies.
+    pub fn debug(mut self, debug: bool) -> Self {
+        self.debug = debug;
+        self
+    }
+
+    /// Registered routes as `"METHOD /pattern"`, in registration order.
+    pub fn routes(&self) -> Vec<String> {
+        self.routes
+            .iter()
+            .map(|r| format!("{} {}", r.method, r.pattern))
+            .collect()
+    }
+
+    /// Match `req` and run its middleware. `Err` is the response to send back
+    /// (404, 405, or whatever a middleware rejected with).
+    pub fn resolve(&self, req: &HttpRequest) -> Result<RouteMatch<T>, HttpResponse> {
+        let path = req.path.split('?').next().unwrap_or("");
+        let parts: Vec<&str> = path.split('/').skip(1).collect();
+
+        let mut best: Option<(&RouteEntry<T>, Params)> = None;
+        let mut allowed: Vec<&str> = Vec::new();
+        for entry in &self.routes {
+            let Some(params) = entry.match_path(&parts) else {
+                continue;
+            };
+            if !entry.method.eq_ignore_ascii_case(&req.method) {
+                if !allowed.contains(&entry.method.as_str()) {
+                    allowed.push(&entry.method);
+                }
+                continue;
+            }
+            if best
+                .as_ref()
+                .is_none_or(|(b, _)| entry.param_count() < b.param_count())
+            {
+                best = Some((entry, params));
+            }
+        }
+
+        let Some((entry, params)) = best else {
+            return Err(if allowed.is_empty() {
+                self.not_found(req)
+            } else {
+                method_not_allowed(req, &allowed)
+            });
+        };
+
+        let mut held = Vec::new();
+        for middleware in self.global.iter().chain(&entry.middleware) {
+            if let Some(value) = middleware.before(req, &entry.pattern)? {
+                held.push(value);
+            }
+        }
+
+        Ok(RouteMatch {
+            route: entry.tag.clone(),
+            pattern: entry.pattern.clone(),
+            params,
+            held,
+        })
+    }
+
+    fn not_found(&self, req: &HttpRequest) -> HttpResponse {
+        let mut body = serde_json::json!({
+            "error": format!("no route for {} {}", req.method, req.path),
+        });
+        if self.debug {
+            body["routes"] = self.routes().into();
+        }
+        json_response(404, body, Vec::new())
+    }
+}
+
+fn method_not_allowed(req: &HttpRequest, allowed: &[&str]) -> HttpResponse {
+    let allow = allowed.join(", ");
+    json_response(
+        405,
+        serde_json::json!({
+            "error": format!("method {} not allowed for {}", req.method, req.path),
+            "allow": allowed,
+        }),
+        vec![HttpHeader {
+            name: "Allow".to_string(),
+            values: vec![allow],
+        }],
+    )
+}
+
+fn json_response(
+    status_code: i32,
+    body: serde_json::Value,
+    mut headers: Vec<HttpHeader>,
+) -> HttpResponse {
+    headers.insert(
+        0,
+        HttpHeader {
+            name: "Content-Type".to_string(),
+            values: vec!["application/json".to_string()],
+        },
+    );
+    HttpResponse {
+        status_code,
+        headers,
+        body: serde_json::to_vec(&body).unwrap_or_default(),
+    }
+}
+
+fn parse_pattern(pattern: &str) -> Vec<Segment> {
+    assert!(
+        pattern.starts_with('/'),
+        "route pattern must start with '/': {}",
+        pattern
+    );
+    pattern
+        .split('/')
+        .skip(1)
+        .map(
+            |part| match part.strip_prefix('{').and_then(|p| p.strip_suffix('}')) {
+                Some(name) => {
+                    assert!(
+                        !name.is_empty(),
+                        "empty param in route pattern: {}",
+                        pattern
+                    );
+                    Segment::Param(name.to_string())
+                }
+                None => Segment::Static(part.to_string()),
+            },
+        )
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugin::limits::{HttpLimits, RouteLimits};
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    enum Route {
+        Execute,
+        Artifact,
+        NewArtifact,
+        DeleteArtifact,
+    }
+
+    fn request(method: &str, path: &str) -> HttpRequest {
+        HttpRequest {
+            method: method.to_string(),
+            path: path.to_string(),
+            headers: vec![],
+            body: vec![],
+        }
+    }
+
+    fn router() -> Router<Route> {
+        Router::new()
+            .route("POST", "/execute", Route::Execute)
+            .route("GET", "/artifacts/{job_id}/{name}", Route::Artifact)
+            .route("GET", "/artifacts/{job_id}/new", Route::NewArtifact)
+            .route(
+                "DELETE",
+                "/artifacts/{job_id}/{name}",
+                Route::DeleteArtifact,
+            )
+    }
+
+    fn body(resp: &HttpResponse) -> serde_json::Value {
+        serde_json::from_slice(&resp.body).unwrap()
+    }
+
+    #[test]
+    fn test_path_params_extracted() {
+        let matched = router()
+            .resolve(&request("GET", "/artifacts/job-7/plot.png?download=1"))
+            .ok()
+            .unwrap();
+        assert_eq!(matched.route, Route::Artifact);
+        assert_eq!(matched.pattern, "/artifacts/{job_id}/{name}");
+        assert_eq!(matched.param("job_id"), Some("job-7"));
+        assert_eq!(matched.param("name"), Some("plot.png"));
+
+        // Static segments beat params regardless of registration order
+        let matched = router()
+            .resolve(&request("get", "/artifacts/job-7/new"))
+            .ok()
+            .unwrap();
+        assert_eq!(matched.route, Route::NewArtifact);
+
+        // Params never match an empty segment
+        let resp = router()
+            .resolve(&request("GET", "/artifacts//plot.png"))
+            .err()
+            .unwrap();
+        assert_eq!(resp.status_code, 404);
+    }
+
+    #[test]
+    fn test_method_mismatch_is_405_with_allow() {
+        let resp = router().resolve(&request("GET", "/execute")).err().unwrap();
+        assert_eq!(resp.status_code, 405);
+        let allow = resp.headers.iter().find(|h| h.name == "Allow").unwrap();
+        assert_eq!(allow.values, vec!["POST"]);
+
+        let resp = router()
+            .resolve(&request("PUT", "/artifacts/job-7/plot.png"))
+            .err()
+            .unwrap();
+        assert_eq!(body(&resp)["allow"], serde_json::json!(["GET", "DELETE"]));
+    }
+
+    #[test]
+    fn test_not_found_body_lists_routes_in_debug() {
+        let resp = router().resolve(&request("GET", "/nope")).err().unwrap();
+        assert_eq!(resp.status_code, 404);
+        assert_eq!(body(&resp)["error"], "no route for GET /nope");
+        assert!(body(&resp).get("routes").is_none());
+
+        let resp = router()
+            .debug(true)
+            .resolve(&request("GET", "/nope"))
+            .err()
+            .unwrap();
+        assert_eq!(
+            body(&resp)["routes"],
+            serde_json::json!([
+                "POST /execute",
+                "GET /artifacts/{job_id}/{name}",
+                "GET /artifacts/{job_id}/new",
+                "DELETE /artifacts/{job_id}/{name}",
+            ])
+        );
+    }
+
+    #[test]
+    fn test_route_middleware_runs_only_on_its_route() {
+        let guard = Arc::new(HttpGuard::new(HttpLimits {
+            defaults: RouteLimits {
+                max_concurrent: 1,
+                ..RouteLimits::default()
+            },
+            ..HttpLimits::default()
+        }));
+        let router = Router::new()
+            .route("POST", "/execute", Route::Execute)
+            .with(guard.clone())
+            .route("GET", "/artifacts/{job_id}/{name}", Route::Artifact)
+            .wrap_all(|req: &HttpRequest, _: &str| {
+                if req.headers.is_empty() {
+                    Ok(None)
+                } else {
+                    Err(json_response(400, serde_json::json!({}), Vec::new()))
+                }
+            });
+
+        // The guard's permit is held by the match: a second request is rejected
+        let first = router.resolve(&request("POST", "/execute")).ok().unwrap();
+        assert_eq!(first.held.len(), 1);
+        let resp = router.resolve(&request("POST", "/execute")).err().unwrap();
+        assert_eq!(resp.status_code, 429);
+        drop(first);
+        assert!(router.resolve(&request("POST", "/execute")).is_ok());
+
+        let matched = router
+            .resolve(&request("GET", "/artifacts/a/b"))
+            .ok()
+            .unwrap();
+        assert!(matched.held.is_empty());
+
+        let with_header = HttpRequest {
+            headers: vec![HttpHeader {
+                name: "X".to_string(),
+                values: vec![],
+            }],
+            ..request("GET", "/artifacts/a/b")
+        };
+        assert_eq!(router.resolve(&with_header).err().unwrap().status_code, 400);
+    }
+}
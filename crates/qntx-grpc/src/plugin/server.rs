@@ -1,9 +1,19 @@
 //! Plugin server utilities.
 
+use std::future::Future;
 use std::net::SocketAddr;
+use std::time::{Duration, Instant};
+use tokio::net::TcpListener;
+use tokio_stream::wrappers::TcpListenerStream;
 use tonic::transport::Server;
-use tracing::info;
+use tracing::{info, warn};
 
+use super::auth::{Authenticated, PluginAuth};
+use super::budget::{BudgetHandle, ResourceBudget, DEFAULT_CHECK_INTERVAL};
+use super::drain::{
+    drain_deadline_from_env, DrainHandle, DrainHook, DrainReport, Tracked, DRAIN_CANCEL_GRACE,
+};
+use super::metadata::PluginMetadata;
 use super::shutdown::shutdown_signal;
 use crate::error::Result;
 
@@ -14,8 +24,13 @@ const PULSE_CLOSE: &str = "❀";
 /// Builder for creating QNTX plugin servers.
 pub struct PluginServer {
     addr: SocketAddr,
-    name: String,
-    version: String,
+    metadata: PluginMetadata,
+    drain: DrainHandle,
+    drain_deadline: Duration,
+    drain_hooks: Vec<(String, DrainHook)>,
+    auth: PluginAuth,
+    budget: BudgetHandle,
+    budget_interval: Duration,
 }
 
 impl PluginServer {
@@ -23,8 +38,13 @@ impl PluginServer {
     pub fn new(name: impl Into<String>, version: impl Into<String>) -> Self {
         Self {
             addr: "0.0.0.0:9000".parse().unwrap(),
-            name: name.into(),
-            version: version.into(),
+            metadata: PluginMetadata::new(name, version),
+            drain: DrainHandle::new(),
+            drain_deadline: drain_deadline_from_env(),
+            drain_hooks: Vec::new(),
+            auth: PluginAuth::from_env(),
+            budget: BudgetHandle::new(ResourceBudget::from_env()),
+            budget_interval: DEFAULT_CHECK_INTERVAL,
         }
     }
 
@@ -40,11 +60,84 @@ impl PluginServer {
         self
     }
 
+    /// Replace the plugin metadata (name and version included).
+    pub fn with_metadata(mut self, metadata: PluginMetadata) -> Self {
+        self.metadata = metadata;
+        self
+    }
+
+    /// Metadata for the service's `Metadata` RPC.
+    ///
+    /// The uptime clock started when the server builder was created.
+    pub fn metadata(&self) -> PluginMetadata {
+        self.metadata.clone()
+    }
+
+    /// How long in-flight calls may run after a shutdown signal before they
+    /// are cancelled. Defaults to `QNTX_PLUGIN_DRAIN_DEADLINE_SECS` or 30s.
+    pub fn drain_deadline(mut self, deadline: Duration) -> Self {
+        self.drain_deadline = deadline;
+        self
+    }
+
+    /// Run `hook` during shutdown, after in-flight calls have finished or
+    /// been cancelled. Hooks run one at a time in registration order.
+    pub fn on_drain<F, Fut>(mut self, name: impl Into<String>, hook: F) -> Self
+    where
+        F: FnOnce() -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.drain_hooks
+            .push((name.into(), Box::new(move || Box::pin(hook()))));
+        self
+    }
+
+    /// Handle for the service to see whether the server is draining
+    /// (e.g. to report unhealthy) and when long calls should give up.
+    pub fn drain_handle(&self) -> DrainHandle {
+        self.drain.clone()
+    }
+
+    /// Tokens inbound calls must present. Defaults to
+    /// [`PluginAuth::from_env`], which is disabled when
+    /// `QNTX_PLUGIN_AUTH_TOKENS` is unset.
+    pub fn auth(mut self, auth: PluginAuth) -> Self {
+        self.auth = auth;
+        self
+    }
+
+    /// Handle for the service to rotate tokens (e.g. from `Initialize`
+    /// config) and to check proxied HTTP requests.
+    pub fn auth_handle(&self) -> PluginAuth {
+        self.auth.clone()
+    }
+
+    /// Resource budget to enforce. Defaults to [`ResourceBudget::from_env`],
+    /// which is unconstrained when the host sets no `QNTX_PLUGIN_MAX_*`.
+    ///
+    /// Replaces the handle, so call this before [`budget_handle`](Self::budget_handle).
+    pub fn budget(mut self, budget: ResourceBudget) -> Self {
+        self.budget = BudgetHandle::new(budget);
+        self
+    }
+
+    /// How often RSS is checked against the memory ceiling (default 5s).
+    pub fn budget_check_interval(mut self, interval: Duration) -> Self {
+        self.budget_interval = interval;
+        self
+    }
+
+    /// Handle for the service to register pressure callbacks, refuse work
+    /// while over budget and report usage in `Health` and `Metadata`.
+    pub fn budget_handle(&self) -> BudgetHandle {
+        self.budget.clone()
+    }
+
     /// Run the server with the provided gRPC service.
     ///
     /// This method handles:
     /// - Logging startup/shutdown
-    /// - Graceful shutdown on SIGTERM/Ctrl+C
+    /// - Graceful shutdown on SIGTERM/Ctrl+C, draining in-flight calls
     pub async fn serve<S>(self, service: S) -> Result<()>
     where
         S: tonic::codegen::Service<
@@ -57,15 +150,363 @@ impl PluginServer {
             + 'static,
         S::Future: Send + 'static,
     {
-        info!("{} Starting {} v{}", PULSE_OPEN, self.name, self.version);
-        info!("  Address: {}", self.addr);
+        let listener = TcpListener::bind(self.addr).await?;
+        self.serve_listener(listener, service).await
+    }
 
-        Server::builder()
-            .add_service(service)
-            .serve_with_shutdown(self.addr, shutdown_signal())
-            .await?;
+    /// Run the server on an already-bound listener.
+    ///
+    /// Use this when the plugin picks its own port (e.g. retrying past ports
+    /// held by another session); the configured address is ignored.
+    pub async fn serve_listener<S>(self, listener: TcpListener, service: S) -> Result<()>
+    where
+        S: tonic::codegen::Service<
+                http::Request<tonic::body::BoxBody>,
+                Response = http::Response<tonic::body::BoxBody>,
+                Error = std::convert::Infallible,
+            > + tonic::server::NamedService
+            + Clone
+            + Send
+            + 'static,
+        S::Future: Send + 'static,
+    {
+        self.serve_listener_until(listener, service, shutdown_signal())
+            .await
+    }
 
-        info!("{} {} shutdown complete", PULSE_CLOSE, self.name);
+    /// Like [`serve_listener`](Self::serve_listener), but drains and exits
+    /// when `signal` resolves instead of on SIGTERM/Ctrl+C.
+    pub async fn serve_listener_until<S, F>(
+        self,
+        listener: TcpListener,
+        service: S,
+        signal: F,
+    ) -> Result<()>
+    where
+        S: tonic::codegen::Service<
+                http::Request<tonic::body::BoxBody>,
+                Response = http::Response<tonic::body::BoxBody>,
+                Error = std::convert::Infallible,
+            > + tonic::server::NamedService
+            + Clone
+            + Send
+            + 'static,
+        S::Future: Send + 'static,
+        F: Future<Output = ()>,
+    {
+        let name = self.metadata.name();
+        info!(
+            "{} Starting {} v{}",
+            PULSE_OPEN,
+            name,
+            self.metadata.version()
+        );
+        info!("  Address: {}", listener.local_addr()?);
+        if self.auth.is_enabled() {
+            info!("  Auth: bearer token required");
+        }
+        let budget = self.budget.budget();
+        if budget != ResourceBudget::default() {
+            info!(
+                "  Budget: {} thread(s), memory {}, nice {}",
+                budget.threads(),
+                budget
+                    .max_memory_bytes
+                    .map_or("unlimited".to_string(), |b| format!("{} MiB", b >> 20)),
+                budget.nice.map_or("-".to_string(), |n| n.to_string())
+            );
+        }
+        let monitor = self.budget.spawn_monitor(self.budget_interval);
+
+        let drain = self.drain;
+        let deadline = self.drain_deadline;
+        let shutdown = {
+            let drain = drain.clone();
+            async move {
+                signal.await;
+                drain.start_drain();
+            }
+        };
+        let serve = Server::builder()
+            // Rejected calls never reach the drain's bookkeeping
+            .add_service(Authenticated::new(
+                Tracked::new(service, drain.clone()),
+                self.auth,
+            ))
+            .serve_with_incoming_shutdown(TcpListenerStream::new(listener), shutdown);
+        tokio::pin!(serve);
+
+        // Resolves only if calls are still running when the deadline passes
+        let overrun = async {
+            drain.draining().await;
+            info!(
+                "{} Draining {} in-flight call(s), deadline {:?}",
+                PULSE_CLOSE,
+                drain.active_calls(),
+                deadline
+            );
+            if tokio::time::timeout(deadline, drain.idle()).await.is_ok() {
+                std::future::pending::<()>().await;
+            }
+        };
+
+        let mut cancelled = Vec::new();
+        tokio::select! {
+            result = &mut serve => result?,
+            _ = overrun => {
+                cancelled = drain.in_flight();
+                drain.cancel();
+                for call in &cancelled {
+                    warn!(
+                        "{} Cancelling {} after {:?}: drain deadline {:?} exceeded",
+                        PULSE_CLOSE, call.method, call.elapsed, deadline
+                    );
+                }
+                // Cancelled calls get a moment to wind down before the server is dropped
+                match tokio::time::timeout(DRAIN_CANCEL_GRACE, &mut serve).await {
+                    Ok(result) => result?,
+                    Err(_) => warn!(
+                        "{} {} call(s) ignored cancellation, dropping them",
+                        PULSE_CLOSE,
+                        drain.active_calls()
+                    ),
+                }
+            }
+        }
+
+        monitor.abort();
+
+        let mut hooks = Vec::new();
+        for (hook_name, hook) in self.drain_hooks {
+            info!("  Running drain hook: {}", hook_name);
+            hook().await;
+            hooks.push(hook_name);
+        }
+
+        let (started, in_flight) = drain.drain_started().unwrap_or((Instant::now(), 0));
+        let report = DrainReport {
+            completed: in_flight.saturating_sub(cancelled.len()),
+            cancelled,
+            hooks,
+            elapsed: started.elapsed(),
+        };
+        info!(
+            "{} {} shutdown complete: drained in {:?}, {} call(s) completed, {} cancelled, {} hook(s) run",
+            PULSE_CLOSE,
+            name,
+            report.elapsed,
+            report.completed,
+            report.cancelled.len(),
+            report.hooks.len()
+        );
+        drain.set_report(report);
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugin::DrainHandle;
+    use std::convert::Infallible;
+    use std::pin::Pin;
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::task::{Context, Poll};
+    use tonic::body::BoxBody;
+    use tonic::codec::ProstCodec;
+    use tonic::{Request, Response, Status};
+
+    type BoxFuture<T> = Pin<Box<dyn Future<Output = T> + Send>>;
+
+    /// `/test.Slow/Run`: sleeps `delay`, or gives up when the drain cancels it.
+    #[derive(Clone)]
+    struct SlowService {
+        delay: Duration,
+        drain: DrainHandle,
+        finished: Arc<AtomicUsize>,
+    }
+
+    impl tonic::server::NamedService for SlowService {
+        const NAME: &'static str = "test.Slow";
+    }
+
+    impl tonic::server::UnaryService<()> for SlowService {
+        type Response = ();
+        type Future = BoxFuture<std::result::Result<Response<()>, Status>>;
+
+        fn call(&mut self, _request: Request<()>) -> Self::Future {
+            let this = self.clone();
+            Box::pin(async move {
+                tokio::select! {
+                    _ = tokio::time::sleep(this.delay) => {
+                        this.finished.fetch_add(1, Ordering::SeqCst);
+                        Ok(Response::new(()))
+                    }
+                    _ = this.drain.cancelled() => Err(Status::cancelled("drain deadline exceeded")),
+                }
+            })
+        }
+    }
+
+    impl tonic::codegen::Service<http::Request<BoxBody>> for SlowService {
+        type Response = http::Response<BoxBody>;
+        type Error = Infallible;
+        type Future = BoxFuture<std::result::Result<Self::Response, Infallible>>;
+
+        fn poll_ready(
+            &mut self,
+            _cx: &mut Context<'_>,
+        ) -> Poll<std::result::Result<(), Infallible>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn call(&mut self, req: http::Request<BoxBody>) -> Self::Future {
+            let this = self.clone();
+            Box::pin(async move {
+                let mut grpc = tonic::server::Grpc::new(ProstCodec::<(), ()>::default());
+                Ok(grpc.unary(this, req).await)
+            })
+        }
+    }
+
+    /// Start a server whose slow call takes `delay`, make one call, signal
+    /// shutdown while it runs and wait for both.
+    async fn drain_one_call(
+        delay: Duration,
+        deadline: Duration,
+    ) -> (std::result::Result<(), Status>, DrainReport, usize, bool) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let hook_ran = Arc::new(AtomicBool::new(false));
+        let flag = hook_ran.clone();
+        let server = PluginServer::new("slow", "0.0.0")
+            .drain_deadline(deadline)
+            .on_drain("flush", move || async move {
+                flag.store(true, Ordering::SeqCst);
+            });
+        let drain = server.drain_handle();
+        let finished = Arc::new(AtomicUsize::new(0));
+        let service = SlowService {
+            delay,
+            drain: drain.clone(),
+            finished: finished.clone(),
+        };
+
+        let (stop, stopped) = tokio::sync::oneshot::channel::<()>();
+        let server = tokio::spawn(server.serve_listener_until(listener, service, async {
+            let _ = stopped.await;
+        }));
+
+        let channel = tonic::transport::Channel::from_shared(format!("http://{}", addr))
+            .unwrap()
+            .connect()
+            .await
+            .unwrap();
+        let call = tokio::spawn(async move {
+            let mut grpc = tonic::client::Grpc::new(channel);
+            grpc.ready().await.unwrap();
+            grpc.unary(
+                Request::new(()),
+                http::uri::PathAndQuery::from_static("/test.Slow/Run"),
+                ProstCodec::<(), ()>::default(),
+            )
+            .await
+            .map(|_| ())
+        });
+
+        while drain.active_calls() == 0 {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+        stop.send(()).unwrap();
+
+        let outcome = call.await.unwrap();
+        server.await.unwrap().unwrap();
+        (
+            outcome,
+            drain.report().unwrap(),
+            finished.load(Ordering::SeqCst),
+            hook_ran.load(Ordering::SeqCst),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_drain_waits_for_call_under_deadline() {
+        let (outcome, report, finished, hook_ran) =
+            drain_one_call(Duration::from_millis(300), Duration::from_secs(5)).await;
+        assert!(outcome.is_ok(), "{:?}", outcome);
+        assert_eq!(finished, 1);
+        assert_eq!(report.completed, 1);
+        assert!(report.cancelled.is_empty());
+        assert_eq!(report.hooks, vec!["flush"]);
+        assert!(hook_ran);
+    }
+
+    #[tokio::test]
+    async fn test_drain_cancels_call_over_deadline() {
+        let (outcome, report, finished, hook_ran) =
+            drain_one_call(Duration::from_secs(30), Duration::from_millis(200)).await;
+        assert!(outcome.is_err());
+        assert_eq!(finished, 0);
+        assert_eq!(report.completed, 0);
+        assert_eq!(report.cancelled.len(), 1);
+        assert_eq!(report.cancelled[0].method, "/test.Slow/Run");
+        assert!(report.cancelled[0].elapsed >= Duration::from_millis(200));
+        assert!(hook_ran, "hooks run after cancelled calls too");
+    }
+    /// Call `/test.Slow/Run` with an optional `authorization` value.
+    async fn call_with(
+        addr: SocketAddr,
+        authorization: Option<&'static str>,
+    ) -> std::result::Result<(), Status> {
+        let channel = tonic::transport::Channel::from_shared(format!("http://{}", addr))
+            .unwrap()
+            .connect()
+            .await
+            .unwrap();
+        let mut grpc = tonic::client::Grpc::new(channel);
+        grpc.ready().await.unwrap();
+        let mut request = Request::new(());
+        if let Some(value) = authorization {
+            request
+                .metadata_mut()
+                .insert("authorization", value.parse().unwrap());
+        }
+        grpc.unary(
+            request,
+            http::uri::PathAndQuery::from_static("/test.Slow/Run"),
+            ProstCodec::<(), ()>::default(),
+        )
+        .await
+        .map(|_| ())
+    }
+
+    #[tokio::test]
+    async fn test_auth_rejects_calls_without_valid_token() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = PluginServer::new("slow", "0.0.0").auth(PluginAuth::with_tokens(["s3cret"]));
+        let drain = server.drain_handle();
+        let finished = Arc::new(AtomicUsize::new(0));
+        let service = SlowService {
+            delay: Duration::ZERO,
+            drain,
+            finished: finished.clone(),
+        };
+        let (stop, stopped) = tokio::sync::oneshot::channel::<()>();
+        let server = tokio::spawn(server.serve_listener_until(listener, service, async {
+            let _ = stopped.await;
+        }));
+
+        let missing = call_with(addr, None).await.unwrap_err();
+        assert_eq!(missing.code(), tonic::Code::Unauthenticated);
+        let wrong = call_with(addr, Some("Bearer guessed")).await.unwrap_err();
+        assert_eq!(wrong.code(), tonic::Code::Unauthenticated);
+        assert!(!wrong.message().contains("guessed"));
+        call_with(addr, Some("Bearer s3cret")).await.unwrap();
+        assert_eq!(finished.load(Ordering::SeqCst), 1);
+
+        stop.send(()).unwrap();
+        server.await.unwrap().unwrap();
+    }
+}
@@ -0,0 +1,882 @@
+//! Buffered attestation writes for high-frequency plugin sources.
+//!
+//! `AttestationWriteBuffer` collects `AttestationCommand`s and writes them in
+//! batches through an [`AttestationSink`] — usually [`AtsStoreSink`], which uses
+//! `BatchGenerateAndCreateAttestations` so a batch lands in one write transaction.
+//! A batch is sent when `max_batch` commands are waiting or the oldest has waited
+//! `max_age`, whichever comes first.
+//!
+//! Transient sink failures are retried with exponential backoff. A batch is always
+//! retried in place before anything queued behind it is sent, so commands reach the
+//! store in push order — in particular, in order per (actor, context). Commands
+//! stay queued when retries run out; permanent failures drop the batch and count
+//! it as failed.
+//!
+//! With `spill_path` set, every pushed command is appended to a JSONL file and
+//! acknowledged once written, so a plugin crash does not lose buffered commands:
+//! the next buffer opened on the same file replays whatever was never acknowledged.
+//! Replay deduplicates by content hash, which means byte-identical commands
+//! (same content and timestamp) are written once. The spill survives process
+//! crashes, not power loss (lines are not fsynced), and a crash between a
+//! successful write and its acknowledgement replays that batch again.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! use qntx_grpc::plugin::{AtsStoreSink, AttestationWriteBuffer, WriteBufferConfig};
+//!
+//! let sink = AtsStoreSink::new(channel.clone(), auth_token);
+//! let buffer = AttestationWriteBuffer::new(sink, WriteBufferConfig {
+//!     spill_path: Some(data_dir.join("pending-attestations.jsonl")),
+//!     ..WriteBufferConfig::default()
+//! })?;
+//!
+//! buffer.push(command)?;
+//! // ... on shutdown
+//! buffer.flush().await?;
+//! ```
+
+use std::collections::{HashSet, VecDeque};
+use std::fs::{File, OpenOptions};
+use std::future::Future;
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::Notify;
+use tracing::{debug, warn};
+
+use super::proto::ats_store_service_client::AtsStoreServiceClient;
+use super::proto::{AttestationCommand, BatchGenerateAttestationRequest};
+use crate::error::{Error, Result};
+
+/// Batching, retry and spill settings for an [`AttestationWriteBuffer`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct WriteBufferConfig {
+    /// Send a batch once this many commands are waiting. Also the largest batch sent.
+    pub max_batch: usize,
+    /// Send a batch once the oldest waiting command is this old.
+    pub max_age: Duration,
+    /// Retries of a transiently failing batch before `flush` gives up (the batch stays queued).
+    pub max_retries: u32,
+    /// Delay before the first retry; doubled on each further retry.
+    pub initial_backoff: Duration,
+    /// Upper bound for the retry delay.
+    pub max_backoff: Duration,
+    /// JSONL file holding unacknowledged commands across restarts. `None` keeps them in memory only.
+    pub spill_path: Option<PathBuf>,
+}
+
+impl Default for WriteBufferConfig {
+    fn default() -> Self {
+        Self {
+            max_batch: 100,
+            max_age: Duration::from_millis(500),
+            max_retries: 5,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(5),
+            spill_path: None,
+        }
+    }
+}
+
+/// A failed batch write.
+#[derive(Debug, thiserror::Error)]
+#[error("{message}")]
+pub struct SinkError {
+    pub message: String,
+    /// Whether retrying the same batch may succeed.
+    pub transient: bool,
+    /// Number of leading commands of the batch that were written before the failure.
+    pub written: usize,
+}
+
+impl SinkError {
+    /// A failure worth retrying (connection loss, timeouts, overload).
+    pub fn transient(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+            transient: true,
+            written: 0,
+        }
+    }
+
+    /// A failure that retrying will not fix (rejected command, bad auth token).
+    pub fn permanent(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+            transient: false,
+            written: 0,
+        }
+    }
+
+    /// Record that the first `written` commands of the batch were stored.
+    pub fn with_written(mut self, written: usize) -> Self {
+        self.written = written;
+        self
+    }
+}
+
+/// Destination for buffered attestation batches.
+pub trait AttestationSink: Send + Sync + 'static {
+    /// Write `batch` in order. On failure, report how many leading commands were
+    /// written so the buffer neither loses nor repeats them.
+    fn write_batch(
+        &self,
+        batch: &[AttestationCommand],
+    ) -> impl Future<Output = std::result::Result<(), SinkError>> + Send;
+}
+
+/// Writes batches to the host's ATS store over gRPC.
+#[derive(Clone)]
+pub struct AtsStoreSink {
+    client: AtsStoreServiceClient<tonic::transport::Channel>,
+    auth_token: String,
+}
+
+impl AtsStoreSink {
+    pub fn new(channel: tonic::transport::Channel, auth_token: impl Into<String>) -> Self {
+        Self {
+            client: AtsStoreServiceClient::new(channel),
+            auth_token: auth_token.into(),
+        }
+    }
+}
+
+impl AttestationSink for AtsStoreSink {
+    async fn write_batch(
+        &self,
+        batch: &[AttestationCommand],
+    ) -> std::result::Result<(), SinkError> {
+        let mut client = self.client.clone();
+        let req = BatchGenerateAttestationRequest {
+            auth_token: self.auth_token.clone(),
+            commands: batch.to_vec(),
+        };
+        match client.batch_generate_and_create_attestations(req).await {
+            Ok(resp) => {
+                let inner = resp.into_inner();
+                if inner.success {
+                    Ok(())
+                } else {
+                    // The server answered: retrying the same commands will not help.
+                    Err(SinkError::permanent(inner.error)
+                        .with_written(inner.created.max(0) as usize))
+                }
+            }
+            Err(status) => {
+                let message = format!("BatchGenerateAndCreateAttestations: {}", status);
+                if is_transient(status.code()) {
+                    Err(SinkError::transient(message))
+                } else {
+                    Err(SinkError::permanent(message))
+                }
+            }
+        }
+    }
+}
+
+fn is_transient(code: tonic::Code) -> bool {
+    matches!(
+        code,
+        tonic::Code::Unavailable
+            | tonic::Code::DeadlineExceeded
+            | tonic::Code::ResourceExhausted
+            | tonic::Code::Aborted
+            | tonic::Code::Cancelled
+            | tonic::Code::Unknown
+    )
+}
+
+/// Snapshot of a buffer's counters.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct WriteBufferMetrics {
+    /// Commands currently waiting to be written.
+    pub buffered: u64,
+    /// Commands written by the sink.
+    pub flushed: u64,
+    /// Commands dropped after a permanent sink failure.
+    pub failed: u64,
+    /// Commands re-sent after a transient sink failure (counted per attempt).
+    pub retried: u64,
+}
+
+/// Batches attestation commands in front of an [`AttestationSink`].
+///
+/// Dropping the buffer stops the background flusher without flushing; call
+/// [`flush`](Self::flush) on shutdown. Anything left behind is replayed from the
+/// spill file, if one is configured.
+pub struct AttestationWriteBuffer<S: AttestationSink> {
+    shared: Arc<Shared<S>>,
+    flusher: tokio::task::JoinHandle<()>,
+}
+
+impl<S: AttestationSink> AttestationWriteBuffer<S> {
+    /// Create a buffer and start its background flusher. Commands left unacknowledged
+    /// in the spill file are queued ahead of anything pushed later.
+    ///
+    /// Must be called within a Tokio runtime.
+    pub fn new(sink: S, config: WriteBufferConfig) -> Result<Self> {
+        if config.max_batch == 0 {
+            return Err(Error::Config(
+                "write buffer max_batch must be at least 1".into(),
+            ));
+        }
+
+        let mut pending = VecDeque::new();
+        let spill = match &config.spill_path {
+            Some(path) => {
+                let (spill, replayed) = Spill::open(path)?;
+                if !replayed.is_empty() {
+                    debug!(
+                        "replaying {} buffered attestations from {}",
+                        replayed.len(),
+                        path.display()
+                    );
+                }
+                let now = Instant::now();
+                pending.extend(replayed.into_iter().map(|(hash, command)| Pending {
+                    hash,
+                    command,
+                    queued_at: now,
+                }));
+                Some(spill)
+            }
+            None => None,
+        };
+
+        let shared = Arc::new(Shared {
+            sink,
+            config,
+            state: Mutex::new(State { pending, spill }),
+            flushing: tokio::sync::Mutex::new(()),
+            wake: Notify::new(),
+            flushed: AtomicU64::new(0),
+            failed: AtomicU64::new(0),
+            retried: AtomicU64::new(0),
+        });
+        let flusher = tokio::spawn(run_flusher(Arc::clone(&shared)));
+        Ok(Self { shared, flusher })
+    }
+
+    /// Queue a command. Commands without a timestamp are stamped now, so a late
+    /// batch keeps the time the event happened rather than the time it was written.
+    pub fn push(&self, mut command: AttestationCommand) -> Result<()> {
+        if command.timestamp.is_none() {
+            command.timestamp = Some(now_millis());
+        }
+        let hash = content_hash(&command)?;
+
+        let mut state = self.shared.state.lock().unwrap();
+        if let Some(spill) = state.spill.as_mut() {
+            spill.append(&SpillRecord::Put {
+                hash: hash.clone(),
+                command: command.clone(),
+            })?;
+        }
+        state.pending.push_back(Pending {
+            hash,
+            command,
+            queued_at: Instant::now(),
+        });
+        let len = state.pending.len();
+        drop(state);
+
+        // The first command starts the age timer; a full batch goes out right away.
+        if len == 1 || len >= self.shared.config.max_batch {
+            self.shared.wake.notify_one();
+        }
+        Ok(())
+    }
+
+    /// Write everything queued so far, batch by batch. Returns the number of
+    /// commands written. Use on graceful shutdown.
+    pub async fn flush(&self) -> Result<usize> {
+        let _guard = self.shared.flushing.lock().await;
+        let mut written = 0;
+        while !self.shared.is_empty() {
+            written += self.shared.flush_batch().await?;
+        }
+        Ok(written)
+    }
+
+    /// Current counters.
+    pub fn metrics(&self) -> WriteBufferMetrics {
+        WriteBufferMetrics {
+            buffered: self.shared.state.lock().unwrap().pending.len() as u64,
+            flushed: self.shared.flushed.load(Ordering::Relaxed),
+            failed: self.shared.failed.load(Ordering::Relaxed),
+            retried: self.shared.retried.load(Ordering::Relaxed),
+        }
+    }
+}
+
+impl<S: AttestationSink> Drop for AttestationWriteBuffer<S> {
+    fn drop(&mut self) {
+        self.flusher.abort();
+    }
+}
+
+struct Pending {
+    hash: String,
+    command: AttestationCommand,
+    queued_at: Instant,
+}
+
+struct State {
+    pending: VecDeque<Pending>,
+    spill: Option<Spill>,
+}
+
+struct Shared<S> {
+    sink: S,
+    config: WriteBufferConfig,
+    state: Mutex<State>,
+    /// Serializes batch writes; only the holder removes commands from the queue.
+    flushing: tokio::sync::Mutex<()>,
+    wake: Notify,
+    flushed: AtomicU64,
+    failed: AtomicU64,
+    retried: AtomicU64,
+}
+
+impl<S: AttestationSink> Shared<S> {
+    fn is_empty(&self) -> bool {
+        self.state.lock().unwrap().pending.is_empty()
+    }
+
+    /// Time until the next batch is due: zero if one is due now, `None` if the queue is empty.
+    fn due_in(&self) -> Option<Duration> {
+        let state = self.state.lock().unwrap();
+        let oldest = state.pending.front()?;
+        if state.pending.len() >= self.config.max_batch {
+            return Some(Duration::ZERO);
+        }
+        Some(
+            self.config
+                .max_age
+                .saturating_sub(oldest.queued_at.elapsed()),
+        )
+    }
+
+    /// Write the batch at the front of the queue, retrying transient failures.
+    /// The caller must hold `flushing`.
+    async fn flush_batch(&self) -> Result<usize> {
+        let batch: Vec<AttestationCommand> = {
+            let state = self.state.lock().unwrap();
+            state
+                .pending
+                .iter()
+                .take(self.config.max_batch)
+                .map(|p| p.command.clone())
+                .collect()
+        };
+        if batch.is_empty() {
+            return Ok(0);
+        }
+
+        let mut offset = 0;
+        let mut retries = 0;
+        let mut backoff = self.config.initial_backoff;
+        loop {
+            let err = match self.sink.write_batch(&batch[offset..]).await {
+                Ok(()) => {
+                    self.complete(batch.len() - offset, true)?;
+                    return Ok(batch.len());
+                }
+                Err(err) => err,
+            };
+
+            let written = err.written.min(batch.len() - offset);
+            if written > 0 {
+                self.complete(written, true)?;
+                offset += written;
+            }
+            if !err.transient {
+                let dropped = batch.len() - offset;
+                warn!(
+                    "dropping {} attestations after sink failure: {}",
+                    dropped, err
+                );
+                self.complete(dropped, false)?;
+                return Err(Error::context("attestation batch rejected", err));
+            }
+            if retries >= self.config.max_retries {
+                return Err(Error::context(
+                    format!(
+                        "attestation batch failed after {} retries, {} still buffered",
+                        retries,
+                        batch.len() - offset
+                    ),
+                    err,
+                ));
+            }
+
+            retries += 1;
+            debug!(
+                "retrying {} attestations in {:?} (attempt {}): {}",
+                batch.len() - offset,
+                backoff,
+                retries,
+                err
+            );
+            tokio::time::sleep(backoff).await;
+            backoff = (backoff * 2).min(self.config.max_backoff);
+            self.retried
+                .fetch_add((batch.len() - offset) as u64, Ordering::Relaxed);
+        }
+    }
+
+    /// Remove `n` commands from the front of the queue and acknowledge them in the spill.
+    fn complete(&self, n: usize, written: bool) -> Result<()> {
+        let counter = if written { &self.flushed } else { &self.failed };
+        counter.fetch_add(n as u64, Ordering::Relaxed);
+
+        let mut state = self.state.lock().unwrap();
+        let done: Vec<Pending> = state.pending.drain(..n).collect();
+        let State { pending, spill } = &mut *state;
+        if let Some(spill) = spill.as_mut() {
+            for p in &done {
+                spill.append(&SpillRecord::Ack {
+                    hash: p.hash.clone(),
+                })?;
+            }
+            spill.acked += n;
+            if pending.is_empty() || spill.acked >= COMPACT_AFTER_ACKS {
+                spill.rewrite(pending.iter().map(|p| (&p.hash, &p.command)))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+async fn run_flusher<S: AttestationSink>(shared: Arc<Shared<S>>) {
+    loop {
+        match shared.due_in() {
+            None => shared.wake.notified().await,
+            Some(wait) if !wait.is_zero() => {
+                tokio::select! {
+                    _ = shared.wake.notified() => {}
+                    _ = tokio::time::sleep(wait) => {}
+                }
+            }
+            Some(_) => {
+                let result = {
+                    let _guard = shared.flushing.lock().await;
+                    // A concurrent flush() may have emptied the queue meanwhile.
+                    if shared.due_in() == Some(Duration::ZERO) {
+                        shared.flush_batch().await
+                    } else {
+                        Ok(0)
+                    }
+                };
+                if let Err(e) = result {
+                    warn!("attestation write buffer: {}", e);
+                    tokio::time::sleep(shared.config.max_backoff).await;
+                }
+            }
+        }
+    }
+}
+
+/// Rewrite the spill file after this many acknowledgements even if the queue never drains.
+const COMPACT_AFTER_ACKS: usize = 10_000;
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+enum SpillRecord {
+    Put {
+        hash: String,
+        command: AttestationCommand,
+    },
+    Ack {
+        hash: String,
+    },
+}
+
+/// Append-only JSONL log of pushed and acknowledged commands.
+struct Spill {
+    path: PathBuf,
+    file: File,
+    /// Acknowledgements appended since the last rewrite.
+    acked: usize,
+}
+
+impl Spill {
+    /// Open `path`, returning the commands that were pushed but never acknowledged,
+    /// deduplicated by content hash, in push order. The file is rewritten to hold
+    /// just those.
+    fn open(path: &Path) -> Result<(Self, Vec<(String, AttestationCommand)>)> {
+        let mut puts = Vec::new();
+        let mut seen = HashSet::new();
+        let mut acked = HashSet::new();
+
+        match File::open(path) {
+            Ok(file) => {
+                for (lineno, line) in BufReader::new(file).lines().enumerate() {
+                    let line = line.map_err(|e| Error::Io(e).wrap("failed to read spill file"))?;
+                    if line.trim().is_empty() {
+                        continue;
+                    }
+                    // A crash mid-append leaves a torn last line; skip it rather than refuse to start.
+                    match serde_json::from_str::<SpillRecord>(&line) {
+                        Ok(SpillRecord::Put { hash, command }) => {
+                            if seen.insert(hash.clone()) {
+                                puts.push((hash, command));
+                            }
+                        }
+                        Ok(SpillRecord::Ack { hash }) => {
+                            acked.insert(hash);
+                        }
+                        Err(e) => warn!(
+                            "skipping unreadable line {} of {}: {}",
+                            lineno + 1,
+                            path.display(),
+                            e
+                        ),
+                    }
+                }
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => return Err(Error::Io(e).wrap("failed to open spill file")),
+        }
+
+        puts.retain(|(hash, _)| !acked.contains(hash));
+        let mut spill = Self {
+            path: path.to_path_buf(),
+            file: append_file(path)?,
+            acked: 0,
+        };
+        spill.rewrite(puts.iter().map(|(hash, command)| (hash, command)))?;
+        Ok((spill, puts))
+    }
+
+    fn append(&mut self, record: &SpillRecord) -> Result<()> {
+        let mut line = serde_json::to_vec(record)?;
+        line.push(b'\n');
+        self.file
+            .write_all(&line)
+            .map_err(|e| Error::Io(e).wrap("failed to append to spill file"))
+    }
+
+    /// Replace the file with `Put` records for `pending` only.
+    fn rewrite<'a>(
+        &mut self,
+        pending: impl Iterator<Item = (&'a String, &'a AttestationCommand)>,
+    ) -> Result<()> {
+        let tmp = self.path.with_extension("jsonl.tmp");
+        let mut out = Vec::new();
+        for (hash, command) in pending {
+            serde_json::to_writer(
+                &mut out,
+                &SpillRecord::Put {
+                    hash: hash.clone(),
+                    command: command.clone(),
+                },
+            )?;
+            out.push(b'\n');
+        }
+        std::fs::write(&tmp, &out).map_err(|e| Error::Io(e).wrap("failed to write spill file"))?;
+        std::fs::rename(&tmp, &self.path)
+            .map_err(|e| Error::Io(e).wrap("failed to replace spill file"))?;
+        self.file = append_file(&self.path)?;
+        self.acked = 0;
+        Ok(())
+    }
+}
+
+fn append_file(path: &Path) -> Result<File> {
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| Error::Io(e).wrap(format!("failed to open spill file {}", path.display())))
+}
+
+/// Content hash of a command: hex SHA-256 of its canonical JSON.
+fn content_hash(command: &AttestationCommand) -> Result<String> {
+    let value = serde_json::to_value(command)?;
+    Ok(qntx_core::canonical_hash_hex(&value))
+}
+
+fn now_millis() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as i64)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Records every successful batch; fails according to a script, one entry per call.
+    #[derive(Clone, Default)]
+    struct FlakySink {
+        written: Arc<Mutex<Vec<AttestationCommand>>>,
+        failures: Arc<Mutex<VecDeque<SinkError>>>,
+        calls: Arc<AtomicU64>,
+    }
+
+    impl FlakySink {
+        fn failing(failures: Vec<SinkError>) -> Self {
+            let sink = Self::default();
+            *sink.failures.lock().unwrap() = failures.into();
+            sink
+        }
+
+        fn written(&self) -> Vec<AttestationCommand> {
+            self.written.lock().unwrap().clone()
+        }
+    }
+
+    impl AttestationSink for FlakySink {
+        async fn write_batch(
+            &self,
+            batch: &[AttestationCommand],
+        ) -> std::result::Result<(), SinkError> {
+            self.calls.fetch_add(1, Ordering::Relaxed);
+            let failure = self.failures.lock().unwrap().pop_front();
+            match failure {
+                Some(err) => {
+                    let written = err.written.min(batch.len());
+                    self.written
+                        .lock()
+                        .unwrap()
+                        .extend_from_slice(&batch[..written]);
+                    Err(err)
+                }
+                None => {
+                    self.written.lock().unwrap().extend_from_slice(batch);
+                    Ok(())
+                }
+            }
+        }
+    }
+
+    fn command(actor: &str, context: &str, seq: i64) -> AttestationCommand {
+        AttestationCommand {
+            subjects: vec![format!("event-{}", seq)],
+            predicates: vec!["observed".to_string()],
+            contexts: vec![context.to_string()],
+            actors: vec![actor.to_string()],
+            timestamp: Some(1_700_000_000_000 + seq),
+            attributes: None,
+            source: "test".to_string(),
+            source_version: String::new(),
+        }
+    }
+
+    fn config() -> WriteBufferConfig {
+        WriteBufferConfig {
+            max_batch: 4,
+            max_age: Duration::from_secs(60),
+            max_retries: 3,
+            initial_backoff: Duration::from_millis(1),
+            max_backoff: Duration::from_millis(5),
+            spill_path: None,
+        }
+    }
+
+    fn spill_path(name: &str) -> PathBuf {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        std::env::temp_dir().join(format!(
+            "qntx-write-buffer-{}-{}-{}.jsonl",
+            name,
+            std::process::id(),
+            nanos
+        ))
+    }
+
+    fn subjects(commands: &[AttestationCommand]) -> Vec<String> {
+        commands.iter().map(|c| c.subjects[0].clone()).collect()
+    }
+
+    #[tokio::test]
+    async fn test_retries_preserve_order_per_actor_context() {
+        let sink = FlakySink::failing(vec![
+            SinkError::transient("unavailable"),
+            SinkError::transient("deadline exceeded").with_written(2),
+            SinkError::transient("unavailable"),
+        ]);
+        let buffer = AttestationWriteBuffer::new(sink.clone(), config()).unwrap();
+
+        let keys = [
+            ("claw", "workspace-a"),
+            ("claw", "workspace-b"),
+            ("vid", "cam-1"),
+        ];
+        let mut pushed = Vec::new();
+        for seq in 0..10 {
+            let (actor, context) = keys[seq as usize % keys.len()];
+            let cmd = command(actor, context, seq);
+            pushed.push(cmd.clone());
+            buffer.push(cmd).unwrap();
+        }
+        buffer.flush().await.unwrap();
+
+        let written = sink.written();
+        assert_eq!(
+            subjects(&written),
+            subjects(&pushed),
+            "no loss, no repeats, push order"
+        );
+        for (actor, context) in keys {
+            let per_key = |cmds: &[AttestationCommand]| -> Vec<String> {
+                cmds.iter()
+                    .filter(|c| c.actors[0] == actor && c.contexts[0] == context)
+                    .map(|c| c.subjects[0].clone())
+                    .collect()
+            };
+            assert_eq!(per_key(&written), per_key(&pushed));
+        }
+
+        let metrics = buffer.metrics();
+        assert_eq!(metrics.buffered, 0);
+        assert_eq!(metrics.flushed, 10);
+        assert_eq!(metrics.failed, 0);
+        // 4 after the first failure, 2 after the partial write, 2 after the third.
+        assert_eq!(metrics.retried, 8);
+    }
+
+    #[tokio::test]
+    async fn test_exhausted_retries_keep_batch_queued() {
+        let failures = (0..4)
+            .map(|_| SinkError::transient("unavailable"))
+            .collect();
+        let sink = FlakySink::failing(failures);
+        let buffer = AttestationWriteBuffer::new(sink.clone(), config()).unwrap();
+        buffer.push(command("claw", "ws", 1)).unwrap();
+        buffer.push(command("claw", "ws", 2)).unwrap();
+
+        assert!(buffer.flush().await.is_err());
+        assert_eq!(buffer.metrics().buffered, 2);
+        assert!(sink.written().is_empty());
+
+        assert_eq!(buffer.flush().await.unwrap(), 2);
+        assert_eq!(subjects(&sink.written()), vec!["event-1", "event-2"]);
+    }
+
+    #[tokio::test]
+    async fn test_permanent_failure_drops_batch() {
+        let sink = FlakySink::failing(vec![SinkError::permanent("invalid token").with_written(1)]);
+        let buffer = AttestationWriteBuffer::new(sink.clone(), config()).unwrap();
+        for seq in 0..3 {
+            buffer.push(command("claw", "ws", seq)).unwrap();
+        }
+
+        assert!(buffer.flush().await.is_err());
+        let metrics = buffer.metrics();
+        assert_eq!(metrics.buffered, 0);
+        assert_eq!(metrics.flushed, 1);
+        assert_eq!(metrics.failed, 2);
+        assert_eq!(metrics.retried, 0);
+    }
+
+    #[tokio::test]
+    async fn test_age_based_flush() {
+        let sink = FlakySink::default();
+        let buffer = AttestationWriteBuffer::new(
+            sink.clone(),
+            WriteBufferConfig {
+                max_batch: 100,
+                max_age: Duration::from_millis(50),
+                ..config()
+            },
+        )
+        .unwrap();
+
+        buffer.push(command("vid", "cam-1", 1)).unwrap();
+        buffer.push(command("vid", "cam-1", 2)).unwrap();
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        assert!(sink.written().is_empty(), "batch is not due yet");
+
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        assert_eq!(subjects(&sink.written()), vec!["event-1", "event-2"]);
+        assert_eq!(buffer.metrics().buffered, 0);
+    }
+
+    #[tokio::test]
+    async fn test_full_batch_flushes_without_waiting() {
+        let sink = FlakySink::default();
+        let buffer = AttestationWriteBuffer::new(sink.clone(), config()).unwrap();
+        for seq in 0..4 {
+            buffer.push(command("claw", "ws", seq)).unwrap();
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert_eq!(sink.written().len(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_spill_replays_unacknowledged_commands() {
+        let path = spill_path("replay");
+        let spill_config = WriteBufferConfig {
+            spill_path: Some(path.clone()),
+            ..config()
+        };
+
+        // First run: one command is written, then the plugin "crashes" with more queued.
+        let first = FlakySink::default();
+        let buffer = AttestationWriteBuffer::new(first.clone(), spill_config.clone()).unwrap();
+        buffer.push(command("claw", "ws", 1)).unwrap();
+        buffer.flush().await.unwrap();
+        buffer.push(command("claw", "ws", 2)).unwrap();
+        buffer.push(command("claw", "ws", 3)).unwrap();
+        buffer.push(command("claw", "ws", 3)).unwrap();
+        drop(buffer);
+        assert_eq!(subjects(&first.written()), vec!["event-1"]);
+
+        // A torn final line from the crash must not block startup.
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(b"{\"op\":\"put\",\"hash\":\"ab").unwrap();
+        drop(file);
+
+        // Second run: the unacknowledged commands come back once each, in order.
+        let second = FlakySink::default();
+        let buffer = AttestationWriteBuffer::new(second.clone(), spill_config.clone()).unwrap();
+        assert_eq!(buffer.metrics().buffered, 2);
+        buffer.push(command("claw", "ws", 4)).unwrap();
+        assert_eq!(buffer.flush().await.unwrap(), 3);
+        assert_eq!(
+            subjects(&second.written()),
+            vec!["event-2", "event-3", "event-4"]
+        );
+        drop(buffer);
+
+        // Everything was acknowledged: nothing left to replay.
+        let third = FlakySink::default();
+        let buffer = AttestationWriteBuffer::new(third.clone(), spill_config).unwrap();
+        assert_eq!(buffer.metrics().buffered, 0);
+        drop(buffer);
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_content_hash_is_stable_across_attribute_order() {
+        let mut a = command("claw", "ws", 1);
+        let mut b = a.clone();
+        let mut fields_a = std::collections::BTreeMap::new();
+        let mut fields_b = std::collections::BTreeMap::new();
+        for (k, v) in [("x", 1.0), ("y", 2.0)] {
+            let value = prost_types::Value {
+                kind: Some(prost_types::value::Kind::NumberValue(v)),
+            };
+            fields_a.insert(k.to_string(), value.clone());
+            fields_b.insert(k.to_string(), value);
+        }
+        a.attributes = Some(prost_types::Struct { fields: fields_a });
+        b.attributes = Some(prost_types::Struct { fields: fields_b });
+        assert_eq!(content_hash(&a).unwrap(), content_hash(&b).unwrap());
+        assert_eq!(content_hash(&a).unwrap().len(), 64);
+
+        b.timestamp = Some(0);
+        assert_ne!(content_hash(&a).unwrap(), content_hash(&b).unwrap());
+    }
+}
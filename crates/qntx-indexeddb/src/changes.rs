@@ -0,0 +1,152 @@
+//! Changefeed: async equivalent of `qntx_core::storage::ChangeFeed`
+//!
+//! Every write appends an event to the `"changes"` object store (schema v3)
+//! in the same transaction as the write, so an aborted write leaves no event
+//! and another tab's writes interleave in commit order. The store's
+//! auto-increment key is the sequence number.
+//!
+//! IndexedDB cannot read the key generator back, so `latest_seq` is the key
+//! of the newest event and pruning always keeps that one event. Apart from
+//! that, semantics match the other backends.
+
+use qntx_core::storage::{ChangeBatch, ChangeEvent, ChangeOp, ChangeRetention, StoreError};
+use wasm_bindgen::prelude::*;
+use web_sys::{IdbCursor, IdbCursorDirection, IdbKeyRange, IdbObjectStore, IdbTransactionMode};
+
+use crate::error::{IndexedDbError, Result};
+use crate::idb::{self, CHANGES_STORE_NAME};
+use crate::store::IndexedDbStore;
+
+/// Append an event inside the caller's transaction; IndexedDB assigns `seq`.
+pub(crate) async fn record(
+    changes: &IdbObjectStore,
+    op: ChangeOp,
+    id: &str,
+    content_hash: Option<String>,
+) -> Result<()> {
+    let event = serde_json::json!({
+        "op": op,
+        "attestation_id": id,
+        "content_hash": content_hash,
+        "at": js_sys::Date::now() as i64,
+    });
+    let val = js_sys::JSON::parse(&event.to_string())
+        .map_err(|_| StoreError::Serialization("change event is not valid JSON".into()))?;
+    let req = changes
+        .add(&val)
+        .map_err(|e| IndexedDbError::from_js(&e, IndexedDbError::Request))?;
+    idb::await_request(&req).await?;
+    Ok(())
+}
+
+impl IndexedDbStore {
+    /// Up to `limit` events with a sequence number above `seq`, oldest first.
+    /// Same contract as `ChangeFeed::changes_since`.
+    pub async fn changes_since(&self, seq: u64, limit: usize) -> Result<ChangeBatch> {
+        let (tx, changes) = self.changes_transaction(IdbTransactionMode::Readonly)?;
+
+        // getAll treats a limit of 0 as unlimited
+        let events = if limit == 0 {
+            Vec::new()
+        } else {
+            let range = IdbKeyRange::lower_bound_with_open(&JsValue::from_f64(seq as f64), true)
+                .map_err(|e| IndexedDbError::from_js(&e, IndexedDbError::Request))?;
+            let req = changes
+                .get_all_with_key_and_limit(&range, limit.min(u32::MAX as usize) as u32)
+                .map_err(|e| IndexedDbError::from_js(&e, IndexedDbError::Request))?;
+            let result = idb::await_request(&req).await?;
+            js_sys::Array::from(&result)
+                .iter()
+                .map(|val| js_to_event(&val))
+                .collect::<Result<Vec<_>>>()?
+        };
+        let oldest = edge_key(&changes, IdbCursorDirection::Next).await?;
+        let latest = edge_key(&changes, IdbCursorDirection::Prev)
+            .await?
+            .unwrap_or(0);
+        idb::await_transaction(&tx).await?;
+
+        let oldest = oldest.unwrap_or(latest.saturating_add(1));
+        Ok(ChangeBatch {
+            events,
+            latest_seq: latest,
+            missed: seq < latest && seq.saturating_add(1) < oldest,
+        })
+    }
+
+    /// Highest sequence number assigned so far; 0 before any write.
+    pub async fn latest_seq(&self) -> Result<u64> {
+        let (tx, changes) = self.changes_transaction(IdbTransactionMode::Readonly)?;
+        let latest = edge_key(&changes, IdbCursorDirection::Prev).await?;
+        idb::await_transaction(&tx).await?;
+        Ok(latest.unwrap_or(0))
+    }
+
+    /// Drop events `retention` no longer covers, measured from `now_ms`,
+    /// except the newest. Returns how many were removed.
+    pub async fn prune_changes(&self, retention: &ChangeRetention, now_ms: i64) -> Result<usize> {
+        let (tx, changes) = self.changes_transaction(IdbTransactionMode::Readwrite)?;
+        let req = changes
+            .get_all()
+            .map_err(|e| IndexedDbError::from_js(&e, IndexedDbError::Request))?;
+        let result = idb::await_request(&req).await?;
+        let events = js_sys::Array::from(&result)
+            .iter()
+            .map(|val| js_to_event(&val))
+            .collect::<Result<Vec<_>>>()?;
+
+        // Same rules as ChangeLog::prune, applied to a prefix of the log
+        let mut drop = 0;
+        if let Some(age) = retention.max_age_ms {
+            drop = events.iter().take_while(|e| e.at < now_ms - age).count();
+        }
+        if let Some(max) = retention.max_events {
+            drop = drop.max(events.len().saturating_sub(max));
+        }
+        let drop = drop.min(events.len().saturating_sub(1));
+
+        if drop > 0 {
+            let range = IdbKeyRange::upper_bound(&JsValue::from_f64(events[drop - 1].seq as f64))
+                .map_err(|e| IndexedDbError::from_js(&e, IndexedDbError::Request))?;
+            let req = changes
+                .delete(&range)
+                .map_err(|e| IndexedDbError::from_js(&e, IndexedDbError::Request))?;
+            idb::await_request(&req).await?;
+        }
+        idb::await_transaction(&tx).await?;
+        Ok(drop)
+    }
+
+    fn changes_transaction(
+        &self,
+        mode: IdbTransactionMode,
+    ) -> Result<(web_sys::IdbTransaction, IdbObjectStore)> {
+        let tx = idb::begin_multi_transaction(&self.db, &[CHANGES_STORE_NAME], mode)?;
+        let changes = idb::object_store(&tx, CHANGES_STORE_NAME)?;
+        Ok((tx, changes))
+    }
+}
+
+/// Key of the first event in `direction`, None when the log is empty.
+async fn edge_key(changes: &IdbObjectStore, direction: IdbCursorDirection) -> Result<Option<u64>> {
+    let req = changes
+        .open_key_cursor_with_range_and_direction(&JsValue::UNDEFINED, direction)
+        .map_err(|e| IndexedDbError::from_js(&e, IndexedDbError::Request))?;
+    let result = idb::await_request(&req).await?;
+    if result.is_null() || result.is_undefined() {
+        return Ok(None);
+    }
+    let cursor: IdbCursor = result.unchecked_into();
+    let key = cursor
+        .key()
+        .map_err(|e| IndexedDbError::from_js(&e, IndexedDbError::Request))?;
+    Ok(key.as_f64().map(|k| k as u64))
+}
+
+fn js_to_event(val: &JsValue) -> Result<ChangeEvent> {
+    let json: String = js_sys::JSON::stringify(val)
+        .map_err(|_| StoreError::Serialization("change event is not serializable".into()))?
+        .into();
+    serde_json::from_str(&json)
+        .map_err(|e| StoreError::Corruption(format!("change event: {}", e)).into())
+}
@@ -1,6 +1,7 @@
 //! Error types for IndexedDB storage backend
 
-use qntx_core::storage::StoreError;
+use qntx_core::storage::{StorageErrorKind, StoreError};
+use serde::Serialize;
 use thiserror::Error;
 
 /// Result type for IndexedDB operations
@@ -40,14 +41,154 @@ pub enum IndexedDbError {
     /// JavaScript value conversion error
     #[error("JS conversion error: {0}")]
     JsValue(String),
+
+    /// The origin ran out of storage (`QuotaExceededError`)
+    #[error("IndexedDB quota exceeded: {0}")]
+    QuotaExceeded(String),
+
+    /// An upgrade waited on other connections (usually other tabs) that never
+    /// closed; see `idb::OPEN_BLOCKED_TIMEOUT_MS`
+    #[error("IndexedDB upgrade blocked: {0}")]
+    Blocked(String),
+
+    /// The database on disk is newer than this build asked for (`VersionError`)
+    #[error("IndexedDB version conflict: {0}")]
+    VersionConflict(String),
+
+    /// The connection was closed or the object used out of order (`InvalidStateError`)
+    #[error("IndexedDB invalid state: {0}")]
+    InvalidState(String),
+
+    /// The transaction was aborted (`AbortError`)
+    #[error("IndexedDB transaction aborted: {0}")]
+    Aborted(String),
+
+    /// Storage error raised outside IndexedDB itself (bad input, outbox full)
+    #[error(transparent)]
+    Store(#[from] StoreError),
+}
+
+/// What the user can do about an error, for the UI to act on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RecoveryHint {
+    /// Free disk space or clear site data, then retry
+    FreeSpace,
+    /// Close other tabs of this app so the database can upgrade
+    CloseOtherTabs,
+    /// Reload to reconnect, or to pick up the build matching the database
+    ReloadPage,
+    /// Transient; retrying the same operation may succeed
+    RetryLater,
 }
 
 impl From<wasm_bindgen::JsValue> for IndexedDbError {
     fn from(val: wasm_bindgen::JsValue) -> Self {
-        let msg = js_sys::JSON::stringify(&val)
-            .map(String::from)
-            .unwrap_or_else(|_| format!("{:?}", val));
-        IndexedDbError::Request(msg)
+        IndexedDbError::from_js(&val, IndexedDbError::Request)
+    }
+}
+
+/// Describe a thrown JS value without touching `window`, so the same mapping
+/// works on the main thread and inside Web Workers.
+///
+/// `DOMException` and `Error` serialize to `{}` through `JSON.stringify`, so
+/// their `name` and `message` properties are read directly instead.
+pub fn js_error_message(val: &wasm_bindgen::JsValue) -> String {
+    if let Some(s) = val.as_string() {
+        return s;
+    }
+
+    let prop = |key: &str| {
+        js_sys::Reflect::get(val, &key.into())
+            .ok()
+            .and_then(|v| v.as_string())
+            .filter(|s| !s.is_empty())
+    };
+    match (prop("name"), prop("message")) {
+        (Some(name), Some(message)) => return format!("{}: {}", name, message),
+        (None, Some(message)) => return message,
+        (Some(name), None) => return name,
+        (None, None) => {}
+    }
+
+    js_sys::JSON::stringify(val)
+        .map(String::from)
+        .unwrap_or_else(|_| format!("{:?}", val))
+}
+
+impl IndexedDbError {
+    /// Map a `DOMException` name to its dedicated variant. Names not in the
+    /// table return `None`.
+    pub fn from_dom_name(name: &str, message: String) -> Option<Self> {
+        match name {
+            "QuotaExceededError" => Some(IndexedDbError::QuotaExceeded(message)),
+            "VersionError" => Some(IndexedDbError::VersionConflict(message)),
+            "InvalidStateError" => Some(IndexedDbError::InvalidState(message)),
+            "AbortError" => Some(IndexedDbError::Aborted(message)),
+            _ => None,
+        }
+    }
+
+    /// Classify a thrown JS value: a `DOMException` (or `Error`) whose name is
+    /// in [`from_dom_name`](Self::from_dom_name)'s table gets its variant,
+    /// anything else becomes `fallback` with [`js_error_message`].
+    pub fn from_js(val: &wasm_bindgen::JsValue, fallback: fn(String) -> Self) -> Self {
+        let prop = |key: &str| {
+            js_sys::Reflect::get(val, &key.into())
+                .ok()
+                .and_then(|v| v.as_string())
+        };
+        if let Some(name) = prop("name") {
+            let message = prop("message").unwrap_or_else(|| name.clone());
+            if let Some(err) = Self::from_dom_name(&name, message) {
+                return err;
+            }
+        }
+        fallback(js_error_message(val))
+    }
+
+    /// Classify this error into the backend-independent storage kind.
+    pub fn kind(&self) -> StorageErrorKind {
+        match self {
+            IndexedDbError::AlreadyExists(_) => StorageErrorKind::Duplicate,
+            IndexedDbError::NotFound(_) => StorageErrorKind::NotFound,
+            IndexedDbError::Json(_) => StorageErrorKind::InvalidInput,
+            IndexedDbError::QuotaExceeded(_) => StorageErrorKind::QuotaExceeded,
+            IndexedDbError::Store(e) => e.kind(),
+            IndexedDbError::NotAvailable(_)
+            | IndexedDbError::Open(_)
+            | IndexedDbError::Transaction(_)
+            | IndexedDbError::Request(_)
+            | IndexedDbError::JsValue(_)
+            | IndexedDbError::Blocked(_)
+            | IndexedDbError::VersionConflict(_)
+            | IndexedDbError::InvalidState(_)
+            | IndexedDbError::Aborted(_) => StorageErrorKind::Backend,
+        }
+    }
+
+    /// What the user can do about this error, if anything.
+    pub fn recovery_hint(&self) -> Option<RecoveryHint> {
+        match self {
+            IndexedDbError::QuotaExceeded(_) => Some(RecoveryHint::FreeSpace),
+            IndexedDbError::Blocked(_) => Some(RecoveryHint::CloseOtherTabs),
+            IndexedDbError::VersionConflict(_) | IndexedDbError::InvalidState(_) => {
+                Some(RecoveryHint::ReloadPage)
+            }
+            IndexedDbError::Aborted(_) => Some(RecoveryHint::RetryLater),
+            _ => None,
+        }
+    }
+
+    /// Render as `{"code":"...","message":"...","hint":"..."|null}` for WASM
+    /// callers; `code` is the storage kind as in `StoreError::to_json`.
+    pub fn to_json(&self) -> String {
+        serde_json::json!({
+            "code": self.kind().code(),
+            "message": self.to_string(),
+            "hint": self.recovery_hint(),
+        })
+        .to_string()
     }
 }
 
@@ -69,6 +210,68 @@ impl From<IndexedDbError> for StoreError {
                 StoreError::Backend(format!("IndexedDB request: {}", msg))
             }
             IndexedDbError::JsValue(msg) => StoreError::Backend(format!("IndexedDB JS: {}", msg)),
+            IndexedDbError::Store(e) => e,
+            // StoreError::QuotaExceeded is per actor/context; like SQLite's
+            // full-disk errors, a browser quota stays a backend error here
+            other @ (IndexedDbError::QuotaExceeded(_)
+            | IndexedDbError::Blocked(_)
+            | IndexedDbError::VersionConflict(_)
+            | IndexedDbError::InvalidState(_)
+            | IndexedDbError::Aborted(_)) => StoreError::Backend(other.to_string()),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_kind_survives_conversion() {
+        let errors = vec![
+            IndexedDbError::NotFound("AS-1".into()),
+            IndexedDbError::AlreadyExists("AS-1".into()),
+            IndexedDbError::Open("blocked".into()),
+        ];
+        for err in errors {
+            let kind = err.kind();
+            assert_eq!(StoreError::from(err).kind(), kind);
+        }
+    }
+
+    #[test]
+    fn test_dom_exception_names() {
+        let table = [
+            ("QuotaExceededError", Some(RecoveryHint::FreeSpace)),
+            ("VersionError", Some(RecoveryHint::ReloadPage)),
+            ("InvalidStateError", Some(RecoveryHint::ReloadPage)),
+            ("AbortError", Some(RecoveryHint::RetryLater)),
+        ];
+        for (name, hint) in table {
+            let err = IndexedDbError::from_dom_name(name, "boom".into()).unwrap();
+            assert_eq!(err.recovery_hint(), hint, "{}", name);
+            assert!(err.to_string().ends_with(": boom"), "{}", err);
+        }
+        assert!(IndexedDbError::from_dom_name("DataError", "boom".into()).is_none());
+        assert!(IndexedDbError::from_dom_name("", "boom".into()).is_none());
+
+        let quota = IndexedDbError::from_dom_name("QuotaExceededError", "full".into()).unwrap();
+        assert_eq!(quota.kind(), StorageErrorKind::QuotaExceeded);
+    }
+
+    #[test]
+    fn test_json_shape() {
+        let blocked: serde_json::Value =
+            serde_json::from_str(&IndexedDbError::Blocked("other tab".into()).to_json()).unwrap();
+        assert_eq!(blocked["code"], "backend");
+        assert_eq!(blocked["hint"], "close_other_tabs");
+        assert_eq!(blocked["message"], "IndexedDB upgrade blocked: other tab");
+
+        let plain: serde_json::Value = serde_json::from_str(
+            &IndexedDbError::from(StoreError::InvalidData("bad".into())).to_json(),
+        )
+        .unwrap();
+        assert_eq!(plain["code"], "invalid_input");
+        assert!(plain["hint"].is_null());
+    }
+}
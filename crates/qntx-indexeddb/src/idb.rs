@@ -4,7 +4,7 @@
 //! `wasm_bindgen_futures::JsFuture` and `js_sys::Promise`.
 
 use js_sys::Promise;
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 use std::rc::Rc;
 use wasm_bindgen::prelude::*;
 use wasm_bindgen::JsCast;
@@ -13,13 +13,22 @@ use web_sys::{
     IdbTransactionMode,
 };
 
-use crate::error::{IndexedDbError, Result};
+use crate::error::{js_error_message, IndexedDbError, Result};
 
-const DB_VERSION: u32 = 1;
+const DB_VERSION: u32 = 3;
+
+/// How long an open waits on other connections once its upgrade is blocked
+pub const OPEN_BLOCKED_TIMEOUT_MS: i32 = 3_000;
 
 /// Object store name for attestations.
 pub const STORE_NAME: &str = "attestations";
 
+/// Object store name for the outbox of writes not yet sent to the server (added in v2).
+pub const OUTBOX_STORE_NAME: &str = "outbox";
+
+/// Object store name for the changefeed (added in v3).
+pub const CHANGES_STORE_NAME: &str = "changes";
+
 /// Type alias for upgrade closure to reduce complexity
 type UpgradeClosure = Rc<RefCell<Option<Closure<dyn FnMut(web_sys::IdbVersionChangeEvent)>>>>;
 
@@ -65,14 +74,14 @@ fn request_to_promise(req: &IdbRequest) -> Promise {
         let req_e = req_error.clone();
         let closures_for_error = closures.clone();
         let on_error = Closure::wrap(Box::new(move |_event: web_sys::Event| {
-            let msg = req_e
+            // Reject with the DOMException itself so its name survives
+            let err = req_e
                 .error()
-                .map(|opt| {
-                    opt.map(|e| JsValue::from(e.message()))
-                        .unwrap_or_else(|| JsValue::from_str("unknown IDB error"))
-                })
-                .unwrap_or_else(|_| JsValue::from_str("unknown IDB error"));
-            let _ = reject.call1(&JsValue::UNDEFINED, &msg);
+                .ok()
+                .flatten()
+                .map(JsValue::from)
+                .unwrap_or_else(|| JsValue::from_str("unknown IDB error"));
+            let _ = reject.call1(&JsValue::UNDEFINED, &err);
             // Clean up both closures after error
             *closures_for_error.borrow_mut() = None;
         }) as Box<dyn FnMut(web_sys::Event)>);
@@ -87,55 +96,201 @@ fn request_to_promise(req: &IdbRequest) -> Promise {
     promise
 }
 
+/// The transaction's error, or an `AbortError` when it was aborted without one.
+fn transaction_error(tx: &IdbTransaction) -> JsValue {
+    match tx.error() {
+        Some(e) => e.into(),
+        None => {
+            let err = js_sys::Error::new("transaction aborted");
+            err.set_name("AbortError");
+            err.into()
+        }
+    }
+}
+
 /// Convert an IdbTransaction completion into a JS Promise.
+///
+/// Rejects on `error` and on `abort`: a transaction can abort without an
+/// error event (quota exhaustion in some browsers, `abort()`), and waiting on
+/// `complete` alone would then never settle.
 fn transaction_to_promise(tx: &IdbTransaction) -> Promise {
     let tx_complete = tx.clone();
     let tx_error = tx.clone();
 
     let promise = Promise::new(&mut move |resolve, reject| {
         // Store closures in Rc<RefCell> to manage their lifetime without leaking
-        type ClosurePair = (
+        type ClosureTriple = (
+            Closure<dyn FnMut(web_sys::Event)>,
             Closure<dyn FnMut(web_sys::Event)>,
             Closure<dyn FnMut(web_sys::Event)>,
         );
-        let closures: Rc<RefCell<Option<ClosurePair>>> = Rc::new(RefCell::new(None));
+        let closures: Rc<RefCell<Option<ClosureTriple>>> = Rc::new(RefCell::new(None));
 
         let closures_for_complete = closures.clone();
         let on_complete = Closure::wrap(Box::new(move |_event: web_sys::Event| {
             let _ = resolve.call0(&JsValue::UNDEFINED);
-            // Clean up both closures after completion
+            // Clean up all closures after completion
             *closures_for_complete.borrow_mut() = None;
         }) as Box<dyn FnMut(web_sys::Event)>);
 
         let tx_e = tx_error.clone();
+        let reject_e = reject.clone();
         let closures_for_error = closures.clone();
         let on_error = Closure::wrap(Box::new(move |_event: web_sys::Event| {
-            let msg = tx_e
-                .error()
-                .map(|e| JsValue::from(e.message()))
-                .unwrap_or_else(|| JsValue::from_str("transaction error"));
-            let _ = reject.call1(&JsValue::UNDEFINED, &msg);
-            // Clean up both closures after error
+            let _ = reject_e.call1(&JsValue::UNDEFINED, &transaction_error(&tx_e));
+            // Clean up all closures after error
             *closures_for_error.borrow_mut() = None;
         }) as Box<dyn FnMut(web_sys::Event)>);
 
+        let tx_a = tx_error.clone();
+        let closures_for_abort = closures.clone();
+        let on_abort = Closure::wrap(Box::new(move |_event: web_sys::Event| {
+            let _ = reject.call1(&JsValue::UNDEFINED, &transaction_error(&tx_a));
+            // Clean up all closures after abort
+            *closures_for_abort.borrow_mut() = None;
+        }) as Box<dyn FnMut(web_sys::Event)>);
+
         tx_complete.set_oncomplete(Some(on_complete.as_ref().unchecked_ref()));
         tx_error.set_onerror(Some(on_error.as_ref().unchecked_ref()));
+        tx_error.set_onabort(Some(on_abort.as_ref().unchecked_ref()));
 
-        // Store both closures to keep them alive until one fires
-        *closures.borrow_mut() = Some((on_complete, on_error));
+        // Store all closures to keep them alive until one fires
+        *closures.borrow_mut() = Some((on_complete, on_error, on_abort));
     });
 
     promise
 }
 
+/// Call `setTimeout` on the global object (window or worker).
+fn set_timeout(callback: &js_sys::Function, ms: i32) -> Option<JsValue> {
+    let global = js_sys::global();
+    let set: js_sys::Function = js_sys::Reflect::get(&global, &"setTimeout".into())
+        .ok()?
+        .dyn_into()
+        .ok()?;
+    set.call2(&global, callback, &JsValue::from(ms)).ok()
+}
+
+/// Call `clearTimeout` on the global object.
+fn clear_timeout(handle: &JsValue) {
+    let global = js_sys::global();
+    if let Some(clear) = js_sys::Reflect::get(&global, &"clearTimeout".into())
+        .ok()
+        .and_then(|f| f.dyn_into::<js_sys::Function>().ok())
+    {
+        let _ = clear.call1(&global, handle);
+    }
+}
+
+/// Closures of a [`BlockedWatch`]: the `blocked` listener and the timer callback.
+type BlockedClosures = (Closure<dyn FnMut(web_sys::Event)>, Closure<dyn FnMut()>);
+
+/// Rejects once an open request has been `blocked` for `timeout_ms`.
+///
+/// `blocked` fires when other connections (usually other tabs) still hold the
+/// database during an upgrade. They may close on their `versionchange` event,
+/// so the open gets `timeout_ms` before it is given up on.
+struct BlockedWatch {
+    promise: Promise,
+    timed_out: Rc<Cell<bool>>,
+    timer: Rc<RefCell<Option<JsValue>>>,
+    closures: Rc<RefCell<Option<BlockedClosures>>>,
+}
+
+impl BlockedWatch {
+    fn new(req: &IdbOpenDbRequest, timeout_ms: i32) -> Self {
+        let timed_out = Rc::new(Cell::new(false));
+        let timer: Rc<RefCell<Option<JsValue>>> = Rc::new(RefCell::new(None));
+        let closures: Rc<RefCell<Option<BlockedClosures>>> = Rc::new(RefCell::new(None));
+
+        let (flag, slot, req) = (timed_out.clone(), closures.clone(), req.clone());
+        let timer_for_blocked = timer.clone();
+        let promise = Promise::new(&mut move |_resolve, reject| {
+            let flag = flag.clone();
+            let on_timeout = Closure::wrap(Box::new(move || {
+                flag.set(true);
+                let _ = reject.call1(&JsValue::UNDEFINED, &JsValue::from_str("blocked"));
+            }) as Box<dyn FnMut()>);
+            let callback: js_sys::Function = on_timeout
+                .as_ref()
+                .unchecked_ref::<js_sys::Function>()
+                .clone();
+
+            let timer = timer_for_blocked.clone();
+            let on_blocked = Closure::wrap(Box::new(move |_event: web_sys::Event| {
+                if timer.borrow().is_none() {
+                    *timer.borrow_mut() = set_timeout(&callback, timeout_ms);
+                }
+            }) as Box<dyn FnMut(web_sys::Event)>);
+
+            req.set_onblocked(Some(on_blocked.as_ref().unchecked_ref()));
+            *slot.borrow_mut() = Some((on_blocked, on_timeout));
+        });
+
+        Self {
+            promise,
+            timed_out,
+            timer,
+            closures,
+        }
+    }
+
+    /// Stop watching: clear the timer and release the closures.
+    fn stop(&self, req: &IdbOpenDbRequest) {
+        if let Some(handle) = self.timer.borrow_mut().take() {
+            clear_timeout(&handle);
+        }
+        req.set_onblocked(None);
+        *self.closures.borrow_mut() = None;
+    }
+}
+
+/// After a blocked open was given up on, the request can still succeed once
+/// the other connections close. Close that late connection, so it doesn't
+/// block the next upgrade in turn, and keep `keep_alive` (the upgrade
+/// handler) until the request settles.
+fn close_when_opened<T: 'static>(req: &IdbOpenDbRequest, keep_alive: T) {
+    type Settled =
+        Rc<RefCell<Option<(Closure<dyn FnMut(web_sys::Event)>, Box<dyn std::any::Any>)>>>;
+    let slot: Settled = Rc::new(RefCell::new(None));
+
+    let (req_s, slot_s) = (req.clone(), slot.clone());
+    let on_settled = Closure::wrap(Box::new(move |_event: web_sys::Event| {
+        if let Ok(db) = req_s.result() {
+            if let Ok(db) = db.dyn_into::<IdbDatabase>() {
+                db.close();
+            }
+        }
+        *slot_s.borrow_mut() = None;
+    }) as Box<dyn FnMut(web_sys::Event)>);
+
+    let _ = req.add_event_listener_with_callback("success", on_settled.as_ref().unchecked_ref());
+    let _ = req.add_event_listener_with_callback("error", on_settled.as_ref().unchecked_ref());
+    *slot.borrow_mut() = Some((on_settled, Box::new(keep_alive)));
+}
+
 /// Open (or create) the QNTX IndexedDB database.
+///
+/// Fails with `IndexedDbError::Blocked` when another connection keeps the
+/// database from upgrading for [`OPEN_BLOCKED_TIMEOUT_MS`], and with
+/// `IndexedDbError::VersionConflict` when the stored database is newer than
+/// this build.
 pub async fn open_database(db_name: &str) -> Result<IdbDatabase> {
+    open_database_version(db_name, DB_VERSION, OPEN_BLOCKED_TIMEOUT_MS).await
+}
+
+/// [`open_database`] with an explicit schema version and blocked timeout.
+/// Versions above the current one run the same (idempotent) upgrade.
+pub async fn open_database_version(
+    db_name: &str,
+    version: u32,
+    blocked_timeout_ms: i32,
+) -> Result<IdbDatabase> {
     let factory = idb_factory()?;
 
     let open_req: IdbOpenDbRequest = factory
-        .open_with_u32(db_name, DB_VERSION)
-        .map_err(|e| IndexedDbError::Open(format!("{:?}", e)))?;
+        .open_with_u32(db_name, version)
+        .map_err(|e| IndexedDbError::from_js(&e, IndexedDbError::Open))?;
 
     // Store upgrade closure to manage its lifetime without leaking
     let upgrade_closure: UpgradeClosure = Rc::new(RefCell::new(None));
@@ -203,6 +358,29 @@ pub async fn open_database(db_name: &str) -> Result<IdbDatabase> {
                 )
                 .expect("create created_at index");
         }
+
+        // v2: outbox keyed by an auto-increment sequence, so key order is queue order
+        let outbox_name = String::from(OUTBOX_STORE_NAME);
+        if !db.object_store_names().contains(&outbox_name) {
+            let params = web_sys::IdbObjectStoreParameters::new();
+            js_sys::Reflect::set(&params, &"keyPath".into(), &"seq".into()).expect("set keyPath");
+            js_sys::Reflect::set(&params, &"autoIncrement".into(), &JsValue::TRUE)
+                .expect("set autoIncrement");
+            db.create_object_store_with_optional_parameters(OUTBOX_STORE_NAME, &params)
+                .expect("create outbox store");
+        }
+
+        // v3: changefeed, likewise keyed by an auto-increment sequence. The
+        // key generator never goes back, even after deletes, so seq is not reused.
+        let changes_name = String::from(CHANGES_STORE_NAME);
+        if !db.object_store_names().contains(&changes_name) {
+            let params = web_sys::IdbObjectStoreParameters::new();
+            js_sys::Reflect::set(&params, &"keyPath".into(), &"seq".into()).expect("set keyPath");
+            js_sys::Reflect::set(&params, &"autoIncrement".into(), &JsValue::TRUE)
+                .expect("set autoIncrement");
+            db.create_object_store_with_optional_parameters(CHANGES_STORE_NAME, &params)
+                .expect("create changes store");
+        }
     }) as Box<dyn FnMut(web_sys::IdbVersionChangeEvent)>);
 
     open_req.set_onupgradeneeded(Some(on_upgrade.as_ref().unchecked_ref()));
@@ -210,16 +388,26 @@ pub async fn open_database(db_name: &str) -> Result<IdbDatabase> {
     // Store closure to keep it alive during the open request
     *upgrade_closure.borrow_mut() = Some(on_upgrade);
 
-    // Await the open request via promise
+    // Await the open request via promise, raced against a blocked upgrade
     let open_promise = request_to_promise(open_req.unchecked_ref());
-    let result = wasm_bindgen_futures::JsFuture::from(open_promise)
-        .await
-        .map_err(|e| IndexedDbError::Open(format!("{:?}", e)))?;
+    let blocked = BlockedWatch::new(&open_req, blocked_timeout_ms);
+    let race = Promise::race(&js_sys::Array::of2(&open_promise, &blocked.promise));
+    let result = wasm_bindgen_futures::JsFuture::from(race).await;
+    blocked.stop(&open_req);
+
+    if blocked.timed_out.get() {
+        close_when_opened(&open_req, upgrade_closure_for_drop);
+        return Err(IndexedDbError::Blocked(format!(
+            "{} is held open by another connection (close other tabs of this app)",
+            db_name
+        )));
+    }
 
     // Clean up upgrade closure now that open is complete
     *upgrade_closure_for_drop.borrow_mut() = None;
 
     result
+        .map_err(|e| IndexedDbError::from_js(&e, IndexedDbError::Open))?
         .dyn_into::<IdbDatabase>()
         .map_err(|_| IndexedDbError::Open("result is not IdbDatabase".into()))
 }
@@ -231,19 +419,55 @@ pub fn begin_transaction(
 ) -> Result<(IdbTransaction, IdbObjectStore)> {
     let tx = db
         .transaction_with_str_and_mode(STORE_NAME, mode)
-        .map_err(|e| IndexedDbError::Transaction(format!("{:?}", e)))?;
+        .map_err(|e| IndexedDbError::from_js(&e, IndexedDbError::Transaction))?;
     let store = tx
         .object_store(STORE_NAME)
-        .map_err(|e| IndexedDbError::Request(format!("{:?}", e)))?;
+        .map_err(|e| IndexedDbError::from_js(&e, IndexedDbError::Request))?;
     Ok((tx, store))
 }
 
+/// Start a write transaction on the attestations store and the changefeed,
+/// so a write and its change event commit together.
+pub fn begin_write_transaction(
+    db: &IdbDatabase,
+) -> Result<(IdbTransaction, IdbObjectStore, IdbObjectStore)> {
+    let tx = begin_multi_transaction(
+        db,
+        &[STORE_NAME, CHANGES_STORE_NAME],
+        IdbTransactionMode::Readwrite,
+    )?;
+    let store = object_store(&tx, STORE_NAME)?;
+    let changes = object_store(&tx, CHANGES_STORE_NAME)?;
+    Ok((tx, store, changes))
+}
+
+/// Start one transaction spanning several object stores, so writes to all of
+/// them commit or abort together.
+pub fn begin_multi_transaction(
+    db: &IdbDatabase,
+    store_names: &[&str],
+    mode: IdbTransactionMode,
+) -> Result<IdbTransaction> {
+    let names = js_sys::Array::new();
+    for name in store_names {
+        names.push(&JsValue::from_str(name));
+    }
+    db.transaction_with_str_sequence_and_mode(&names, mode)
+        .map_err(|e| IndexedDbError::from_js(&e, IndexedDbError::Transaction))
+}
+
+/// Get an object store from a transaction.
+pub fn object_store(tx: &IdbTransaction, name: &str) -> Result<IdbObjectStore> {
+    tx.object_store(name)
+        .map_err(|e| IndexedDbError::from_js(&e, IndexedDbError::Request))
+}
+
 /// Await an IdbRequest, resolving to its result JsValue.
 pub async fn await_request(req: &IdbRequest) -> Result<JsValue> {
     let promise = request_to_promise(req);
     wasm_bindgen_futures::JsFuture::from(promise)
         .await
-        .map_err(|e| IndexedDbError::Request(format!("{:?}", e)))
+        .map_err(|e| IndexedDbError::from_js(&e, IndexedDbError::Request))
 }
 
 /// Await an IdbTransaction to complete.
@@ -251,7 +475,7 @@ pub async fn await_transaction(tx: &IdbTransaction) -> Result<()> {
     let promise = transaction_to_promise(tx);
     wasm_bindgen_futures::JsFuture::from(promise)
         .await
-        .map_err(|e| IndexedDbError::Transaction(format!("{:?}", e)))?;
+        .map_err(|e| IndexedDbError::from_js(&e, IndexedDbError::Transaction))?;
     Ok(())
 }
 
@@ -260,10 +484,10 @@ pub async fn delete_database(db_name: &str) -> Result<()> {
     let factory = idb_factory()?;
     let req = factory
         .delete_database(db_name)
-        .map_err(|e| IndexedDbError::Open(format!("delete db: {:?}", e)))?;
+        .map_err(|e| IndexedDbError::Open(format!("delete db: {}", js_error_message(&e))))?;
     let promise = request_to_promise(req.unchecked_ref());
     wasm_bindgen_futures::JsFuture::from(promise)
         .await
-        .map_err(|e| IndexedDbError::Open(format!("delete db: {:?}", e)))?;
+        .map_err(|e| IndexedDbError::Open(format!("delete db: {}", js_error_message(&e))))?;
     Ok(())
 }
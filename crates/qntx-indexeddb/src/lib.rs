@@ -14,6 +14,12 @@
 //! multiEntry indexes for efficient lookups. Timestamps are stored as numbers
 //! (milliseconds since epoch).
 //!
+//! An `"outbox"` object store (schema v2) queues local writes for upload; see
+//! [`outbox`].
+//!
+//! A `"changes"` object store (schema v3) records every write for consumers
+//! polling what changed; see [`changes`].
+//!
 //! # Example
 //!
 //! ```rust,ignore
@@ -37,12 +43,17 @@
 //! assert!(retrieved.is_some());
 //! ```
 
+pub mod changes;
 pub mod error;
 pub mod idb;
+pub mod outbox;
 pub mod store;
 
-pub use error::{IndexedDbError, Result};
-pub use store::IndexedDbStore;
+pub use error::{IndexedDbError, RecoveryHint, Result};
+pub use outbox::{
+    OutboxAdmission, OutboxEntry, OutboxFullAction, OutboxOp, OutboxPolicy, OutboxStatus,
+};
+pub use store::{ImportFailure, ImportMode, ImportReport, IndexedDbStore, QueryPage};
 
 // Re-export proto conversion utilities from qntx-proto
 pub use qntx_proto::proto_convert;
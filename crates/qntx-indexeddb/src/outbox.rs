@@ -0,0 +1,477 @@
+//! Outbox of local writes the server has not acknowledged yet.
+//!
+//! With the outbox in use, every local put/delete also appends an
+//! [`OutboxEntry`] to the `"outbox"` object store, in the same IndexedDB
+//! transaction as the write itself, so a reload or crash can never keep one
+//! without the other. The store's auto-increment `seq` key is the queue order.
+//!
+//! Draining is up to the caller: read [`entries`](IndexedDbStore::outbox_entries),
+//! upload the due ones, then [`remove`](IndexedDbStore::outbox_remove) the
+//! successes and [`update`](IndexedDbStore::outbox_update) the failures after
+//! [`OutboxEntry::record_failure`]. An entry is only removed after the upload
+//! succeeded, so a crash in between sends it again; `content_hash` lets the
+//! server recognise the duplicate. The hash covers the queue position and
+//! time as well as the write, so a put → delete → put of the same attestation
+//! queues three distinct keys and only true resends collapse.
+
+use qntx_core::{
+    attestation::Attestation,
+    storage::{ChangeOp, StoreError},
+};
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+use web_sys::{IdbObjectStore, IdbTransactionMode};
+
+use crate::changes;
+use crate::error::{IndexedDbError, Result};
+use crate::idb::{self, CHANGES_STORE_NAME, OUTBOX_STORE_NAME, STORE_NAME};
+use crate::store::{attestation_to_js, IndexedDbStore};
+
+/// Outbox size at which writes are blocked (or the oldest entries dropped)
+pub const DEFAULT_OUTBOX_MAX_ENTRIES: usize = 10_000;
+/// Outbox size at which [`OutboxStatus::warn`] is raised
+pub const DEFAULT_OUTBOX_WARN_ENTRIES: usize = 8_000;
+
+/// First retry delay; doubles with every failed attempt
+const RETRY_BASE_MS: i64 = 1_000;
+/// Cap on the retry delay
+const RETRY_MAX_MS: i64 = 15 * 60 * 1_000;
+
+/// Kind of local write an entry replays on the server.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OutboxOp {
+    Put,
+    Delete,
+}
+
+/// One queued local write.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OutboxEntry {
+    /// Queue position, assigned by IndexedDB on enqueue
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seq: Option<u64>,
+    pub op: OutboxOp,
+    /// Attestation ID the write applies to
+    pub id: String,
+    /// The attestation in proto JSON schema (puts only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub attestation: Option<serde_json::Value>,
+    /// SHA-256 of seq, queued_at, op, id and attestation, for server-side
+    /// dedup of resends; recomputed once `seq` is assigned
+    pub content_hash: String,
+    /// When the write happened locally (ms since epoch)
+    pub queued_at: i64,
+    /// Failed upload attempts so far
+    #[serde(default)]
+    pub attempts: u32,
+    /// Do not retry before this time (ms since epoch)
+    #[serde(default)]
+    pub next_attempt_at: i64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+}
+
+impl OutboxEntry {
+    /// Entry replaying a put of `attestation`.
+    pub fn put(attestation: &Attestation, now_ms: i64) -> Result<Self> {
+        let proto = qntx_proto::proto_convert::to_proto(attestation.clone());
+        let value =
+            serde_json::to_value(&proto).map_err(|e| StoreError::Serialization(e.to_string()))?;
+        Ok(Self::new(
+            OutboxOp::Put,
+            &attestation.id,
+            Some(value),
+            now_ms,
+        ))
+    }
+
+    /// Entry replaying a delete of `id`.
+    pub fn delete(id: &str, now_ms: i64) -> Self {
+        Self::new(OutboxOp::Delete, id, None, now_ms)
+    }
+
+    fn new(op: OutboxOp, id: &str, attestation: Option<serde_json::Value>, now_ms: i64) -> Self {
+        let mut entry = Self {
+            seq: None,
+            op,
+            id: id.to_string(),
+            attestation,
+            content_hash: String::new(),
+            queued_at: now_ms,
+            attempts: 0,
+            next_attempt_at: now_ms,
+            last_error: None,
+        };
+        entry.content_hash = entry.compute_hash();
+        entry
+    }
+
+    /// Record the queue position IndexedDB assigned and rehash with it.
+    fn assign_seq(&mut self, seq: u64) {
+        self.seq = Some(seq);
+        self.content_hash = self.compute_hash();
+    }
+
+    /// SHA-256 over the canonical JSON of the write and its queue slot.
+    fn compute_hash(&self) -> String {
+        let value = serde_json::json!({
+            "seq": self.seq,
+            "queued_at": self.queued_at,
+            "op": self.op,
+            "id": self.id,
+            "attestation": self.attestation,
+        });
+        qntx_core::canonical_hash_hex(&value)
+    }
+
+    /// Whether the entry's backoff has elapsed.
+    pub fn is_due(&self, now_ms: i64) -> bool {
+        self.next_attempt_at <= now_ms
+    }
+
+    /// Count a failed upload and push the next attempt back exponentially.
+    pub fn record_failure(&mut self, error: impl Into<String>, now_ms: i64) {
+        self.attempts += 1;
+        self.next_attempt_at = now_ms + retry_delay_ms(self.attempts);
+        self.last_error = Some(error.into());
+    }
+}
+
+/// Delay before retry number `attempts` (1s, 2s, 4s, ... capped at 15 minutes).
+pub fn retry_delay_ms(attempts: u32) -> i64 {
+    let doublings = attempts.saturating_sub(1).min(20);
+    (RETRY_BASE_MS << doublings).min(RETRY_MAX_MS)
+}
+
+/// What a write does when the outbox already holds `max_entries`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OutboxFullAction {
+    /// Reject the write with `StoreError::QuotaExceeded`; nothing is stored
+    #[default]
+    Block,
+    /// Drop the oldest entries to make room; the server never sees them
+    DropOldest,
+}
+
+/// Bounds on outbox growth while offline.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OutboxPolicy {
+    pub max_entries: usize,
+    pub warn_entries: usize,
+    pub on_full: OutboxFullAction,
+}
+
+impl Default for OutboxPolicy {
+    fn default() -> Self {
+        Self {
+            max_entries: DEFAULT_OUTBOX_MAX_ENTRIES,
+            warn_entries: DEFAULT_OUTBOX_WARN_ENTRIES,
+            on_full: OutboxFullAction::Block,
+        }
+    }
+}
+
+/// Result of queueing one write.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct OutboxAdmission {
+    /// Entries now in the outbox, including the new one
+    pub pending: usize,
+    /// Oldest entries dropped to make room (DropOldest only)
+    pub dropped: usize,
+    /// `pending` reached the policy's warn level
+    pub warn: bool,
+}
+
+/// Outbox summary for "N changes pending" UI.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct OutboxStatus {
+    pub pending: usize,
+    /// Age of the oldest entry in ms, None when empty
+    pub oldest_age_ms: Option<i64>,
+    /// Entries that failed at least once
+    pub failing: usize,
+    /// Earliest time any entry is due, None when empty
+    pub next_attempt_at: Option<i64>,
+    pub warn: bool,
+    pub full: bool,
+}
+
+impl IndexedDbStore {
+    /// [`put`](Self::put) that also queues the write in the outbox, atomically.
+    /// Fails with `StoreError::QuotaExceeded` when the outbox is full and the
+    /// policy blocks; the attestation is not stored then.
+    pub async fn put_queued(
+        &self,
+        attestation: Attestation,
+        now_ms: i64,
+        policy: &OutboxPolicy,
+    ) -> Result<OutboxAdmission> {
+        if self.exists(&attestation.id).await? {
+            return Err(IndexedDbError::AlreadyExists(attestation.id));
+        }
+
+        let attestation = Attestation {
+            revision: attestation.revision.max(1),
+            ..attestation
+        };
+        let entry = OutboxEntry::put(&attestation, now_ms)?;
+        let actor = attestation.actors.first().map_or("unknown", |a| a.as_str());
+        let js_val = attestation_to_js(&attestation)?;
+
+        let tx = idb::begin_multi_transaction(
+            &self.db,
+            &[STORE_NAME, OUTBOX_STORE_NAME, CHANGES_STORE_NAME],
+            IdbTransactionMode::Readwrite,
+        )?;
+        let store = idb::object_store(&tx, STORE_NAME)?;
+        let outbox = idb::object_store(&tx, OUTBOX_STORE_NAME)?;
+        let changes = idb::object_store(&tx, CHANGES_STORE_NAME)?;
+
+        let admission = match enqueue(&outbox, &entry, policy, actor).await {
+            Ok(admission) => admission,
+            Err(e) => {
+                idb::await_transaction(&tx).await?;
+                return Err(e);
+            }
+        };
+        let req = store
+            .add(&js_val)
+            .map_err(|e| IndexedDbError::from_js(&e, IndexedDbError::Request))?;
+        idb::await_request(&req).await?;
+        changes::record(
+            &changes,
+            ChangeOp::Put,
+            &attestation.id,
+            Some(attestation.content_hash()),
+        )
+        .await?;
+        idb::await_transaction(&tx).await?;
+
+        Ok(admission)
+    }
+
+    /// [`delete`](Self::delete) that also queues the delete in the outbox,
+    /// atomically. Returns None, queueing nothing, if the attestation didn't exist.
+    pub async fn delete_queued(
+        &self,
+        id: &str,
+        now_ms: i64,
+        policy: &OutboxPolicy,
+    ) -> Result<Option<OutboxAdmission>> {
+        if !self.exists(id).await? {
+            return Ok(None);
+        }
+
+        let entry = OutboxEntry::delete(id, now_ms);
+        let tx = idb::begin_multi_transaction(
+            &self.db,
+            &[STORE_NAME, OUTBOX_STORE_NAME, CHANGES_STORE_NAME],
+            IdbTransactionMode::Readwrite,
+        )?;
+        let store = idb::object_store(&tx, STORE_NAME)?;
+        let outbox = idb::object_store(&tx, OUTBOX_STORE_NAME)?;
+        let changes = idb::object_store(&tx, CHANGES_STORE_NAME)?;
+
+        let admission = match enqueue(&outbox, &entry, policy, "unknown").await {
+            Ok(admission) => admission,
+            Err(e) => {
+                idb::await_transaction(&tx).await?;
+                return Err(e);
+            }
+        };
+        let req = store
+            .delete(&JsValue::from_str(id))
+            .map_err(|e| IndexedDbError::from_js(&e, IndexedDbError::Request))?;
+        idb::await_request(&req).await?;
+        changes::record(&changes, ChangeOp::Delete, id, None).await?;
+        idb::await_transaction(&tx).await?;
+
+        Ok(Some(admission))
+    }
+
+    /// All queued entries, oldest first.
+    pub async fn outbox_entries(&self) -> Result<Vec<OutboxEntry>> {
+        let (tx, outbox) = self.outbox_transaction(IdbTransactionMode::Readonly)?;
+        let req = outbox
+            .get_all()
+            .map_err(|e| IndexedDbError::from_js(&e, IndexedDbError::Request))?;
+        let result = idb::await_request(&req).await?;
+        idb::await_transaction(&tx).await?;
+
+        js_sys::Array::from(&result)
+            .iter()
+            .map(|val| js_to_entry(&val))
+            .collect()
+    }
+
+    /// Remove an entry once the server has acknowledged it.
+    pub async fn outbox_remove(&self, seq: u64) -> Result<()> {
+        let (tx, outbox) = self.outbox_transaction(IdbTransactionMode::Readwrite)?;
+        let req = outbox
+            .delete(&JsValue::from_f64(seq as f64))
+            .map_err(|e| IndexedDbError::from_js(&e, IndexedDbError::Request))?;
+        idb::await_request(&req).await?;
+        idb::await_transaction(&tx).await?;
+        Ok(())
+    }
+
+    /// Write back an entry (e.g. after [`OutboxEntry::record_failure`]),
+    /// keeping its queue position.
+    pub async fn outbox_update(&self, entry: &OutboxEntry) -> Result<()> {
+        if entry.seq.is_none() {
+            return Err(StoreError::InvalidData(
+                "outbox entry has no seq; it was never enqueued".into(),
+            )
+            .into());
+        }
+        let (tx, outbox) = self.outbox_transaction(IdbTransactionMode::Readwrite)?;
+        let req = outbox
+            .put(&entry_to_js(entry)?)
+            .map_err(|e| IndexedDbError::from_js(&e, IndexedDbError::Request))?;
+        idb::await_request(&req).await?;
+        idb::await_transaction(&tx).await?;
+        Ok(())
+    }
+
+    /// Summarize the outbox against `policy`.
+    pub async fn outbox_status(&self, now_ms: i64, policy: &OutboxPolicy) -> Result<OutboxStatus> {
+        let entries = self.outbox_entries().await?;
+        let pending = entries.len();
+        Ok(OutboxStatus {
+            pending,
+            oldest_age_ms: entries.iter().map(|e| now_ms - e.queued_at).max(),
+            failing: entries.iter().filter(|e| e.attempts > 0).count(),
+            next_attempt_at: entries.iter().map(|e| e.next_attempt_at).min(),
+            warn: pending >= policy.warn_entries,
+            full: pending >= policy.max_entries,
+        })
+    }
+
+    /// Drop every queued entry without sending it.
+    pub async fn outbox_clear(&self) -> Result<()> {
+        let (tx, outbox) = self.outbox_transaction(IdbTransactionMode::Readwrite)?;
+        let req = outbox
+            .clear()
+            .map_err(|e| IndexedDbError::from_js(&e, IndexedDbError::Request))?;
+        idb::await_request(&req).await?;
+        idb::await_transaction(&tx).await?;
+        Ok(())
+    }
+
+    fn outbox_transaction(
+        &self,
+        mode: IdbTransactionMode,
+    ) -> Result<(web_sys::IdbTransaction, IdbObjectStore)> {
+        let tx = idb::begin_multi_transaction(&self.db, &[OUTBOX_STORE_NAME], mode)?;
+        let outbox = idb::object_store(&tx, OUTBOX_STORE_NAME)?;
+        Ok((tx, outbox))
+    }
+}
+
+/// Add `entry` inside the caller's transaction, applying the size policy first.
+async fn enqueue(
+    outbox: &IdbObjectStore,
+    entry: &OutboxEntry,
+    policy: &OutboxPolicy,
+    actor: &str,
+) -> Result<OutboxAdmission> {
+    let req = outbox
+        .count()
+        .map_err(|e| IndexedDbError::from_js(&e, IndexedDbError::Request))?;
+    let count = idb::await_request(&req).await?.as_f64().unwrap_or(0.0) as usize;
+
+    let mut dropped = 0;
+    if count >= policy.max_entries {
+        match policy.on_full {
+            OutboxFullAction::Block => {
+                return Err(StoreError::QuotaExceeded {
+                    actor: actor.to_string(),
+                    context: "outbox".to_string(),
+                    current: count,
+                    limit: policy.max_entries,
+                }
+                .into());
+            }
+            OutboxFullAction::DropOldest => {
+                dropped = count + 1 - policy.max_entries.max(1);
+                let req = outbox
+                    .get_all_keys_with_key_and_limit(&JsValue::UNDEFINED, dropped as u32)
+                    .map_err(|e| IndexedDbError::from_js(&e, IndexedDbError::Request))?;
+                let keys = idb::await_request(&req).await?;
+                for key in js_sys::Array::from(&keys).iter() {
+                    let req = outbox
+                        .delete(&key)
+                        .map_err(|e| IndexedDbError::from_js(&e, IndexedDbError::Request))?;
+                    idb::await_request(&req).await?;
+                }
+            }
+        }
+    }
+
+    let req = outbox
+        .add(&entry_to_js(entry)?)
+        .map_err(|e| IndexedDbError::from_js(&e, IndexedDbError::Request))?;
+    let key = idb::await_request(&req).await?;
+
+    // The hash includes the seq, which only exists once added
+    let mut entry = entry.clone();
+    entry.assign_seq(key.as_f64().unwrap_or(0.0) as u64);
+    let req = outbox
+        .put(&entry_to_js(&entry)?)
+        .map_err(|e| IndexedDbError::from_js(&e, IndexedDbError::Request))?;
+    idb::await_request(&req).await?;
+
+    let pending = count + 1 - dropped;
+    Ok(OutboxAdmission {
+        pending,
+        dropped,
+        warn: pending >= policy.warn_entries,
+    })
+}
+
+/// Entries round-trip through JSON: `JSON.parse` gives IndexedDB a plain
+/// object, and one without `seq` gets it assigned on add.
+fn entry_to_js(entry: &OutboxEntry) -> Result<JsValue> {
+    let json =
+        serde_json::to_string(entry).map_err(|e| StoreError::Serialization(e.to_string()))?;
+    js_sys::JSON::parse(&json)
+        .map_err(|_| StoreError::Serialization("outbox entry is not valid JSON".into()).into())
+}
+
+fn js_to_entry(val: &JsValue) -> Result<OutboxEntry> {
+    let json: String = js_sys::JSON::stringify(val)
+        .map_err(|_| StoreError::Serialization("outbox entry is not serializable".into()))?
+        .into();
+    serde_json::from_str(&json)
+        .map_err(|e| StoreError::Corruption(format!("outbox entry: {}", e)).into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn content_hash_is_per_queued_write() {
+        let a = OutboxEntry::delete("AS-1", 1_000);
+        assert_eq!(a.content_hash.len(), 64);
+        assert!(a.content_hash.bytes().all(|b| b.is_ascii_hexdigit()));
+
+        // Same write at another time, or in another slot, is a distinct key
+        let b = OutboxEntry::delete("AS-1", 2_000);
+        assert_ne!(a.content_hash, b.content_hash);
+        let (mut c, mut d) = (a.clone(), a.clone());
+        c.assign_seq(1);
+        d.assign_seq(3);
+        assert_ne!(c.content_hash, d.content_hash);
+        assert_ne!(c.content_hash, a.content_hash);
+
+        // A resend of the same entry keeps its key
+        let mut resend = c.clone();
+        resend.record_failure("offline", 5_000);
+        resend.assign_seq(1);
+        assert_eq!(resend.content_hash, c.content_hash);
+    }
+}
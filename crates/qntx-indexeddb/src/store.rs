@@ -2,20 +2,75 @@
 //!
 //! Because IndexedDB is inherently async, the methods here are async equivalents of the
 //! synchronous `AttestationStore` and `QueryStore` trait methods from `qntx-core`.
-//! The method signatures and semantics match exactly — same inputs, same outputs, same errors.
+//! The method signatures and semantics match exactly — same inputs, same outputs, and
+//! errors that convert into the same `StoreError`. Keeping them as `IndexedDbError` until
+//! then lets browser callers see browser-specific causes (quota, blocked upgrades).
 
 use std::collections::{HashMap, HashSet};
 
 use qntx_core::{
-    attestation::{Attestation, AxFilter, AxResult, AxSummary},
-    storage::{StorageStats, StoreError},
+    attestation::{Attestation, AxFilter, AxResult, AxSummary, MatchingSummary},
+    storage::{ChangeOp, PutOutcome, StorageStats, StoreError},
 };
+use serde::Serialize;
 use wasm_bindgen::prelude::*;
-use web_sys::{IdbDatabase, IdbTransactionMode};
+use web_sys::{IdbDatabase, IdbKeyRange, IdbTransactionMode};
 
+use crate::changes;
+use crate::error::{IndexedDbError, Result};
 use crate::idb;
 
-type StoreResult<T> = std::result::Result<T, StoreError>;
+/// Records read per request by [`IndexedDbStore::query_page`]
+pub const PAGE_SCAN_BATCH: u32 = 256;
+
+/// Version tag of [`QueryPage::next_cursor`]
+const PAGE_CURSOR_PREFIX: &str = "p1.";
+
+/// One page of [`IndexedDbStore::query_page`]
+#[derive(Debug, Clone)]
+pub struct QueryPage {
+    pub items: Vec<Attestation>,
+    /// Opaque token for the next page; None once the scan reached the end
+    pub next_cursor: Option<String>,
+}
+
+/// Attestations written per transaction by [`IndexedDbStore::import`]
+pub const IMPORT_BATCH: usize = 500;
+
+/// What [`IndexedDbStore::import`] does with an ID that is already stored
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportMode {
+    /// Keep the stored attestation and count the import as skipped
+    SkipExisting,
+    /// Replace it, as [`IndexedDbStore::update`] would
+    Overwrite,
+}
+
+impl ImportMode {
+    /// Parse `skip_existing` or `overwrite` (case-insensitive).
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "skip_existing" => Some(ImportMode::SkipExisting),
+            "overwrite" => Some(ImportMode::Overwrite),
+            _ => None,
+        }
+    }
+}
+
+/// A record [`IndexedDbStore::import`] left out, by its index in the input
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ImportFailure {
+    pub index: usize,
+    pub error: String,
+}
+
+/// Outcome of [`IndexedDbStore::import`]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ImportReport {
+    pub imported: usize,
+    pub skipped: usize,
+    pub errors: Vec<ImportFailure>,
+}
 
 /// IndexedDB-backed attestation store for browser WASM.
 ///
@@ -26,18 +81,18 @@ type StoreResult<T> = std::result::Result<T, StoreError>;
 ///
 /// All methods are async because IndexedDB is callback-based.
 pub struct IndexedDbStore {
-    db: IdbDatabase,
+    pub(crate) db: IdbDatabase,
 }
 
 impl IndexedDbStore {
     /// Open or create an IndexedDB store with the given database name.
-    pub async fn open(db_name: &str) -> crate::Result<Self> {
+    pub async fn open(db_name: &str) -> Result<Self> {
         let db = idb::open_database(db_name).await?;
         Ok(Self { db })
     }
 
     /// Open with the default database name "qntx".
-    pub async fn open_default() -> crate::Result<Self> {
+    pub async fn open_default() -> Result<Self> {
         Self::open("qntx").await
     }
 
@@ -47,7 +102,7 @@ impl IndexedDbStore {
     }
 
     /// Delete the database (for testing/cleanup).
-    pub async fn delete_database(db_name: &str) -> crate::Result<()> {
+    pub async fn delete_database(db_name: &str) -> Result<()> {
         idb::delete_database(db_name).await
     }
 
@@ -56,44 +111,49 @@ impl IndexedDbStore {
     // ========================================================================
 
     /// Store an attestation.
-    /// If an attestation with the same ID already exists, returns `StoreError::AlreadyExists`.
-    pub async fn put(&self, attestation: Attestation) -> StoreResult<()> {
+    /// If an attestation with the same ID already exists, returns `IndexedDbError::AlreadyExists`.
+    pub async fn put(&self, attestation: Attestation) -> Result<()> {
         // Check for duplicates
         if self.exists(&attestation.id).await? {
-            return Err(StoreError::AlreadyExists(attestation.id));
+            return Err(IndexedDbError::AlreadyExists(attestation.id));
         }
 
+        let attestation = Attestation {
+            revision: attestation.revision.max(1),
+            ..attestation
+        };
         let js_val = attestation_to_js(&attestation)?;
 
-        let (tx, store) = idb::begin_transaction(&self.db, IdbTransactionMode::Readwrite)
-            .map_err(StoreError::from)?;
+        let (tx, store, changes) = idb::begin_write_transaction(&self.db)?;
 
         let req = store
             .add(&js_val)
-            .map_err(|e| StoreError::Backend(format!("IDB add: {:?}", e)))?;
-        idb::await_request(&req).await.map_err(StoreError::from)?;
-        idb::await_transaction(&tx)
-            .await
-            .map_err(StoreError::from)?;
+            .map_err(|e| IndexedDbError::from_js(&e, IndexedDbError::Request))?;
+        idb::await_request(&req).await?;
+        changes::record(
+            &changes,
+            ChangeOp::Put,
+            &attestation.id,
+            Some(attestation.content_hash()),
+        )
+        .await?;
+        idb::await_transaction(&tx).await?;
 
         Ok(())
     }
 
     /// Retrieve an attestation by ID.
     /// Returns `None` if not found.
-    pub async fn get(&self, id: &str) -> StoreResult<Option<Attestation>> {
-        let (tx, store) = idb::begin_transaction(&self.db, IdbTransactionMode::Readonly)
-            .map_err(StoreError::from)?;
+    pub async fn get(&self, id: &str) -> Result<Option<Attestation>> {
+        let (tx, store) = idb::begin_transaction(&self.db, IdbTransactionMode::Readonly)?;
 
         let key = JsValue::from_str(id);
         let req = store
             .get(&key)
-            .map_err(|e| StoreError::Backend(format!("IDB get: {:?}", e)))?;
+            .map_err(|e| IndexedDbError::from_js(&e, IndexedDbError::Request))?;
 
-        let result = idb::await_request(&req).await.map_err(StoreError::from)?;
-        idb::await_transaction(&tx)
-            .await
-            .map_err(StoreError::from)?;
+        let result = idb::await_request(&req).await?;
+        idb::await_transaction(&tx).await?;
 
         if result.is_undefined() || result.is_null() {
             return Ok(None);
@@ -104,19 +164,16 @@ impl IndexedDbStore {
     }
 
     /// Check if an attestation exists.
-    pub async fn exists(&self, id: &str) -> StoreResult<bool> {
-        let (tx, store) = idb::begin_transaction(&self.db, IdbTransactionMode::Readonly)
-            .map_err(StoreError::from)?;
+    pub async fn exists(&self, id: &str) -> Result<bool> {
+        let (tx, store) = idb::begin_transaction(&self.db, IdbTransactionMode::Readonly)?;
 
         let key = JsValue::from_str(id);
         let req = store
             .count_with_key(&key)
-            .map_err(|e| StoreError::Backend(format!("IDB count: {:?}", e)))?;
+            .map_err(|e| IndexedDbError::from_js(&e, IndexedDbError::Request))?;
 
-        let result = idb::await_request(&req).await.map_err(StoreError::from)?;
-        idb::await_transaction(&tx)
-            .await
-            .map_err(StoreError::from)?;
+        let result = idb::await_request(&req).await?;
+        idb::await_transaction(&tx).await?;
 
         let count = result.as_f64().unwrap_or(0.0) as u32;
         Ok(count > 0)
@@ -124,64 +181,124 @@ impl IndexedDbStore {
 
     /// Delete an attestation by ID.
     /// Returns `true` if the attestation was deleted, `false` if it didn't exist.
-    pub async fn delete(&self, id: &str) -> StoreResult<bool> {
+    pub async fn delete(&self, id: &str) -> Result<bool> {
         let existed = self.exists(id).await?;
         if !existed {
             return Ok(false);
         }
 
-        let (tx, store) = idb::begin_transaction(&self.db, IdbTransactionMode::Readwrite)
-            .map_err(StoreError::from)?;
+        let (tx, store, changes) = idb::begin_write_transaction(&self.db)?;
 
         let key = JsValue::from_str(id);
         let req = store
             .delete(&key)
-            .map_err(|e| StoreError::Backend(format!("IDB delete: {:?}", e)))?;
+            .map_err(|e| IndexedDbError::from_js(&e, IndexedDbError::Request))?;
 
-        idb::await_request(&req).await.map_err(StoreError::from)?;
-        idb::await_transaction(&tx)
-            .await
-            .map_err(StoreError::from)?;
+        idb::await_request(&req).await?;
+        changes::record(&changes, ChangeOp::Delete, id, None).await?;
+        idb::await_transaction(&tx).await?;
 
         Ok(true)
     }
 
-    /// Update an existing attestation.
-    /// Returns `StoreError::NotFound` if the attestation doesn't exist.
-    pub async fn update(&self, attestation: Attestation) -> StoreResult<()> {
-        if !self.exists(&attestation.id).await? {
-            return Err(StoreError::NotFound(attestation.id));
-        }
-
+    /// Update an existing attestation, incrementing its stored revision.
+    /// Returns `IndexedDbError::NotFound` if the attestation doesn't exist.
+    pub async fn update(&self, attestation: Attestation) -> Result<()> {
+        let Some(stored) = self.get(&attestation.id).await? else {
+            return Err(IndexedDbError::NotFound(attestation.id));
+        };
+
+        let attestation = Attestation {
+            revision: stored.revision + 1,
+            ..attestation
+        };
         let js_val = attestation_to_js(&attestation)?;
 
-        let (tx, store) = idb::begin_transaction(&self.db, IdbTransactionMode::Readwrite)
-            .map_err(StoreError::from)?;
+        let (tx, store, changes) = idb::begin_write_transaction(&self.db)?;
 
         let req = store
             .put(&js_val)
-            .map_err(|e| StoreError::Backend(format!("IDB put: {:?}", e)))?;
-        idb::await_request(&req).await.map_err(StoreError::from)?;
-        idb::await_transaction(&tx)
-            .await
-            .map_err(StoreError::from)?;
+            .map_err(|e| IndexedDbError::from_js(&e, IndexedDbError::Request))?;
+        idb::await_request(&req).await?;
+        changes::record(
+            &changes,
+            ChangeOp::Update,
+            &attestation.id,
+            Some(attestation.content_hash()),
+        )
+        .await?;
+        idb::await_transaction(&tx).await?;
 
         Ok(())
     }
 
+    /// Write an attestation only if the stored revision equals `expected_revision`
+    /// (0 = must not exist). Same contract as `AttestationStore::put_if_revision`.
+    ///
+    /// The revision check and the write share one readwrite transaction, so
+    /// another tab writing the same ID in between cannot be overwritten.
+    pub async fn put_if_revision(
+        &self,
+        attestation: Attestation,
+        expected_revision: u64,
+    ) -> Result<PutOutcome> {
+        let (tx, store, changes) = idb::begin_write_transaction(&self.db)?;
+
+        let key = JsValue::from_str(&attestation.id);
+        let req = store
+            .get(&key)
+            .map_err(|e| IndexedDbError::from_js(&e, IndexedDbError::Request))?;
+        let result = idb::await_request(&req).await?;
+        let current = if result.is_undefined() || result.is_null() {
+            None
+        } else {
+            Some(js_to_attestation(&result)?)
+        };
+
+        if current.as_ref().map_or(0, |a| a.revision) != expected_revision {
+            idb::await_transaction(&tx).await?;
+            return Ok(PutOutcome::Conflict {
+                current: current.map(Box::new),
+            });
+        }
+
+        let revision = expected_revision + 1;
+        let attestation = Attestation {
+            revision,
+            ..attestation
+        };
+        let js_val = attestation_to_js(&attestation)?;
+        let req = store
+            .put(&js_val)
+            .map_err(|e| IndexedDbError::from_js(&e, IndexedDbError::Request))?;
+        idb::await_request(&req).await?;
+        let op = if current.is_some() {
+            ChangeOp::Update
+        } else {
+            ChangeOp::Put
+        };
+        changes::record(
+            &changes,
+            op,
+            &attestation.id,
+            Some(attestation.content_hash()),
+        )
+        .await?;
+        idb::await_transaction(&tx).await?;
+
+        Ok(PutOutcome::Written { revision })
+    }
+
     /// Get all attestation IDs.
-    pub async fn ids(&self) -> StoreResult<Vec<String>> {
-        let (tx, store) = idb::begin_transaction(&self.db, IdbTransactionMode::Readonly)
-            .map_err(StoreError::from)?;
+    pub async fn ids(&self) -> Result<Vec<String>> {
+        let (tx, store) = idb::begin_transaction(&self.db, IdbTransactionMode::Readonly)?;
 
         let req = store
             .get_all_keys()
-            .map_err(|e| StoreError::Backend(format!("IDB getAllKeys: {:?}", e)))?;
+            .map_err(|e| IndexedDbError::from_js(&e, IndexedDbError::Request))?;
 
-        let result = idb::await_request(&req).await.map_err(StoreError::from)?;
-        idb::await_transaction(&tx)
-            .await
-            .map_err(StoreError::from)?;
+        let result = idb::await_request(&req).await?;
+        idb::await_transaction(&tx).await?;
 
         let array = js_sys::Array::from(&result);
         let mut ids: Vec<String> = Vec::with_capacity(array.length() as usize);
@@ -194,35 +311,39 @@ impl IndexedDbStore {
     }
 
     /// Get the total count of attestations.
-    pub async fn count(&self) -> StoreResult<usize> {
-        let (tx, store) = idb::begin_transaction(&self.db, IdbTransactionMode::Readonly)
-            .map_err(StoreError::from)?;
+    pub async fn count(&self) -> Result<usize> {
+        let (tx, store) = idb::begin_transaction(&self.db, IdbTransactionMode::Readonly)?;
 
         let req = store
             .count()
-            .map_err(|e| StoreError::Backend(format!("IDB count: {:?}", e)))?;
+            .map_err(|e| IndexedDbError::from_js(&e, IndexedDbError::Request))?;
 
-        let result = idb::await_request(&req).await.map_err(StoreError::from)?;
-        idb::await_transaction(&tx)
-            .await
-            .map_err(StoreError::from)?;
+        let result = idb::await_request(&req).await?;
+        idb::await_transaction(&tx).await?;
 
         Ok(result.as_f64().unwrap_or(0.0) as usize)
     }
 
-    /// Clear all attestations.
-    pub async fn clear(&self) -> StoreResult<()> {
-        let (tx, store) = idb::begin_transaction(&self.db, IdbTransactionMode::Readwrite)
-            .map_err(StoreError::from)?;
+    /// Clear all attestations, recording a delete for each.
+    pub async fn clear(&self) -> Result<()> {
+        let (tx, store, changes) = idb::begin_write_transaction(&self.db)?;
+
+        let req = store
+            .get_all_keys()
+            .map_err(|e| IndexedDbError::from_js(&e, IndexedDbError::Request))?;
+        let keys = idb::await_request(&req).await?;
+        for key in js_sys::Array::from(&keys).iter() {
+            if let Some(id) = key.as_string() {
+                changes::record(&changes, ChangeOp::Delete, &id, None).await?;
+            }
+        }
 
         let req = store
             .clear()
-            .map_err(|e| StoreError::Backend(format!("IDB clear: {:?}", e)))?;
+            .map_err(|e| IndexedDbError::from_js(&e, IndexedDbError::Request))?;
 
-        idb::await_request(&req).await.map_err(StoreError::from)?;
-        idb::await_transaction(&tx)
-            .await
-            .map_err(StoreError::from)?;
+        idb::await_request(&req).await?;
+        idb::await_transaction(&tx).await?;
 
         Ok(())
     }
@@ -232,7 +353,10 @@ impl IndexedDbStore {
     // ========================================================================
 
     /// Execute an AX query filter and return matching attestations.
-    pub async fn query(&self, filter: &AxFilter) -> StoreResult<AxResult> {
+    pub async fn query(&self, filter: &AxFilter) -> Result<AxResult> {
+        filter
+            .validate_aliases_expanded()
+            .map_err(StoreError::Query)?;
         let all = self.get_all().await?;
 
         let mut matching: Vec<Attestation> = all
@@ -240,11 +364,20 @@ impl IndexedDbStore {
             .filter(|a| matches_filter(a, filter))
             .collect();
 
+        if let Some(over) = &filter.over {
+            matching = over.retain_spanning(matching);
+        }
+
+        let matching_summary = filter
+            .include_summary
+            .then(|| MatchingSummary::from_attestations(&matching));
+
         if let Some(limit) = filter.limit {
             matching.truncate(limit);
         }
 
-        let summary = build_summary(&matching);
+        let mut summary = build_summary(&matching);
+        summary.matching = matching_summary;
 
         Ok(AxResult {
             attestations: matching,
@@ -253,8 +386,67 @@ impl IndexedDbStore {
         })
     }
 
+    /// One page of the attestations matching `filter`, in ID order.
+    ///
+    /// Pass `None` for the first page, then each page's `next_cursor` until
+    /// it is `None`. Records are read in batches of [`PAGE_SCAN_BATCH`] and
+    /// filtered as they come, so memory stays bounded by the page size rather
+    /// than the store size.
+    ///
+    /// A cursor holds the last key scanned, and the scan resumes after it.
+    /// Writes between pages are tolerated: a deleted record is skipped, a
+    /// new one is returned only if its ID sorts after the cursor.
+    /// `filter.limit` and `filter.include_summary` are ignored; `over` needs
+    /// the whole result and is rejected.
+    pub async fn query_page(
+        &self,
+        filter: &AxFilter,
+        cursor: Option<String>,
+        page_size: usize,
+    ) -> Result<QueryPage> {
+        if page_size == 0 {
+            return Err(StoreError::Query("page_size must be at least 1".into()).into());
+        }
+        if filter.over.is_some() {
+            return Err(StoreError::Query("over is not supported by paged queries".into()).into());
+        }
+        filter
+            .validate_aliases_expanded()
+            .map_err(StoreError::Query)?;
+        let mut after = cursor.as_deref().map(decode_page_cursor).transpose()?;
+
+        let (tx, store) = idb::begin_transaction(&self.db, IdbTransactionMode::Readonly)?;
+        let mut items = Vec::new();
+        let next_cursor = 'scan: loop {
+            let range = ids_after(after.as_deref())?;
+            let req = store
+                .get_all_with_key_and_limit(&range, PAGE_SCAN_BATCH)
+                .map_err(|e| IndexedDbError::from_js(&e, IndexedDbError::Request))?;
+            let batch = js_sys::Array::from(&idb::await_request(&req).await?);
+            let exhausted = batch.length() < PAGE_SCAN_BATCH;
+
+            for i in 0..batch.length() {
+                let attestation = js_to_attestation(&batch.get(i))?;
+                after = Some(attestation.id.clone());
+                if matches_filter(&attestation, filter) {
+                    items.push(attestation);
+                    if items.len() == page_size {
+                        let last = exhausted && i + 1 == batch.length();
+                        break 'scan (!last).then(|| encode_page_cursor(&items[page_size - 1].id));
+                    }
+                }
+            }
+            if exhausted {
+                break None;
+            }
+        };
+        idb::await_transaction(&tx).await?;
+
+        Ok(QueryPage { items, next_cursor })
+    }
+
     /// Get all distinct predicates in the store.
-    pub async fn predicates(&self) -> StoreResult<Vec<String>> {
+    pub async fn predicates(&self) -> Result<Vec<String>> {
         let all = self.get_all().await?;
         let mut set = HashSet::new();
         for a in &all {
@@ -268,7 +460,7 @@ impl IndexedDbStore {
     }
 
     /// Get all distinct contexts in the store.
-    pub async fn contexts(&self) -> StoreResult<Vec<String>> {
+    pub async fn contexts(&self) -> Result<Vec<String>> {
         let all = self.get_all().await?;
         let mut set = HashSet::new();
         for a in &all {
@@ -282,7 +474,7 @@ impl IndexedDbStore {
     }
 
     /// Get all distinct subjects in the store.
-    pub async fn subjects(&self) -> StoreResult<Vec<String>> {
+    pub async fn subjects(&self) -> Result<Vec<String>> {
         let all = self.get_all().await?;
         let mut set = HashSet::new();
         for a in &all {
@@ -296,7 +488,7 @@ impl IndexedDbStore {
     }
 
     /// Get all distinct actors in the store.
-    pub async fn actors(&self) -> StoreResult<Vec<String>> {
+    pub async fn actors(&self) -> Result<Vec<String>> {
         let all = self.get_all().await?;
         let mut set = HashSet::new();
         for a in &all {
@@ -310,7 +502,7 @@ impl IndexedDbStore {
     }
 
     /// Get storage statistics.
-    pub async fn stats(&self) -> StoreResult<StorageStats> {
+    pub async fn stats(&self) -> Result<StorageStats> {
         Ok(StorageStats {
             total_attestations: self.count().await?,
             unique_subjects: self.subjects().await?.len(),
@@ -320,23 +512,130 @@ impl IndexedDbStore {
         })
     }
 
+    // ========================================================================
+    // Bulk import/export
+    // ========================================================================
+
+    /// Every attestation in ID order, handed to `each` [`PAGE_SCAN_BATCH`] at
+    /// a time from one readonly transaction, so an export can be serialized
+    /// as it is read. Returns how many attestations were read.
+    pub async fn export_batches(
+        &self,
+        mut each: impl FnMut(Vec<Attestation>) -> Result<()>,
+    ) -> Result<usize> {
+        let (tx, store) = idb::begin_transaction(&self.db, IdbTransactionMode::Readonly)?;
+        let mut after: Option<String> = None;
+        let mut total = 0;
+        loop {
+            let req = store
+                .get_all_with_key_and_limit(&ids_after(after.as_deref())?, PAGE_SCAN_BATCH)
+                .map_err(|e| IndexedDbError::from_js(&e, IndexedDbError::Request))?;
+            let batch = js_sys::Array::from(&idb::await_request(&req).await?);
+            let exhausted = batch.length() < PAGE_SCAN_BATCH;
+            let attestations = batch
+                .iter()
+                .map(|val| js_to_attestation(&val))
+                .collect::<Result<Vec<_>>>()?;
+            after = attestations.last().map(|a| a.id.clone());
+            total += attestations.len();
+            each(attestations)?;
+            if exhausted {
+                break;
+            }
+        }
+        idb::await_transaction(&tx).await?;
+        Ok(total)
+    }
+
+    /// Write many attestations, [`IMPORT_BATCH`] per transaction. Each comes
+    /// with its index in the caller's input, which `errors` reports.
+    ///
+    /// A new ID is stored as by [`put`](Self::put); an ID already stored, or
+    /// seen earlier in the input, is handled per `mode`. Attestations the
+    /// store rejects (an out-of-range confidence) go to `errors` and the rest
+    /// carry on. Imports are not queued in the outbox.
+    ///
+    /// Fails only when a transaction does; batches committed before it stay
+    /// written.
+    pub async fn import(
+        &self,
+        attestations: impl IntoIterator<Item = (usize, Attestation)>,
+        mode: ImportMode,
+    ) -> Result<ImportReport> {
+        let mut report = ImportReport::default();
+        let mut attestations = attestations.into_iter().peekable();
+        while attestations.peek().is_some() {
+            let (tx, store, changes) = idb::begin_write_transaction(&self.db)?;
+            for (index, attestation) in attestations.by_ref().take(IMPORT_BATCH) {
+                let req = store
+                    .get(&JsValue::from_str(&attestation.id))
+                    .map_err(|e| IndexedDbError::from_js(&e, IndexedDbError::Request))?;
+                let stored = idb::await_request(&req).await?;
+                let (attestation, op) = if stored.is_undefined() || stored.is_null() {
+                    let revision = attestation.revision.max(1);
+                    (
+                        Attestation {
+                            revision,
+                            ..attestation
+                        },
+                        ChangeOp::Put,
+                    )
+                } else if mode == ImportMode::SkipExisting {
+                    report.skipped += 1;
+                    continue;
+                } else {
+                    let revision = stored_revision(&stored) + 1;
+                    (
+                        Attestation {
+                            revision,
+                            ..attestation
+                        },
+                        ChangeOp::Update,
+                    )
+                };
+                let js_val = match attestation_to_js(&attestation) {
+                    Ok(js_val) => js_val,
+                    Err(e) => {
+                        report.errors.push(ImportFailure {
+                            index,
+                            error: e.to_string(),
+                        });
+                        continue;
+                    }
+                };
+
+                let req = store
+                    .put(&js_val)
+                    .map_err(|e| IndexedDbError::from_js(&e, IndexedDbError::Request))?;
+                idb::await_request(&req).await?;
+                changes::record(
+                    &changes,
+                    op,
+                    &attestation.id,
+                    Some(attestation.content_hash()),
+                )
+                .await?;
+                report.imported += 1;
+            }
+            idb::await_transaction(&tx).await?;
+        }
+        Ok(report)
+    }
+
     // ========================================================================
     // Internal helpers
     // ========================================================================
 
     /// Retrieve all attestations from the store.
-    pub async fn get_all(&self) -> StoreResult<Vec<Attestation>> {
-        let (tx, store) = idb::begin_transaction(&self.db, IdbTransactionMode::Readonly)
-            .map_err(StoreError::from)?;
+    pub async fn get_all(&self) -> Result<Vec<Attestation>> {
+        let (tx, store) = idb::begin_transaction(&self.db, IdbTransactionMode::Readonly)?;
 
         let req = store
             .get_all()
-            .map_err(|e| StoreError::Backend(format!("IDB getAll: {:?}", e)))?;
+            .map_err(|e| IndexedDbError::from_js(&e, IndexedDbError::Request))?;
 
-        let result = idb::await_request(&req).await.map_err(StoreError::from)?;
-        idb::await_transaction(&tx)
-            .await
-            .map_err(StoreError::from)?;
+        let result = idb::await_request(&req).await?;
+        idb::await_transaction(&tx).await?;
 
         let array = js_sys::Array::from(&result);
         let mut attestations = Vec::with_capacity(array.length() as usize);
@@ -357,7 +656,12 @@ impl IndexedDbStore {
 ///
 /// Array fields are stored as native JS arrays so IndexedDB multiEntry indexes work.
 /// Timestamps are stored as numbers (milliseconds) for efficient range queries.
-fn attestation_to_js(attestation: &Attestation) -> StoreResult<JsValue> {
+/// Every write goes through here, so this is where out-of-range confidences
+/// are rejected.
+pub(crate) fn attestation_to_js(attestation: &Attestation) -> Result<JsValue> {
+    attestation
+        .validate_confidence()
+        .map_err(StoreError::InvalidData)?;
     let obj = js_sys::Object::new();
 
     set_prop(&obj, "id", &JsValue::from_str(&attestation.id))?;
@@ -388,6 +692,14 @@ fn attestation_to_js(attestation: &Attestation) -> StoreResult<JsValue> {
         "created_at",
         &JsValue::from_f64(attestation.created_at as f64),
     )?;
+    set_prop(
+        &obj,
+        "revision",
+        &JsValue::from_f64(attestation.revision as f64),
+    )?;
+    if let Some(confidence) = attestation.confidence {
+        set_prop(&obj, "confidence", &JsValue::from_f64(confidence.into()))?;
+    }
 
     // Attributes: store as JSON string if non-empty, null otherwise
     if attestation.attributes.is_empty() {
@@ -402,7 +714,7 @@ fn attestation_to_js(attestation: &Attestation) -> StoreResult<JsValue> {
 }
 
 /// Convert a JS object from IndexedDB back to an Attestation.
-fn js_to_attestation(val: &JsValue) -> StoreResult<Attestation> {
+fn js_to_attestation(val: &JsValue) -> Result<Attestation> {
     let id = get_string_prop(val, "id")?;
     let subjects = get_string_array_prop(val, "subjects")?;
     let predicates = get_string_array_prop(val, "predicates")?;
@@ -411,6 +723,11 @@ fn js_to_attestation(val: &JsValue) -> StoreResult<Attestation> {
     let timestamp = get_number_prop(val, "timestamp")? as i64;
     let source = get_string_prop(val, "source")?;
     let created_at = get_number_prop(val, "created_at")? as i64;
+    let revision = stored_revision(val);
+    let confidence = js_sys::Reflect::get(val, &"confidence".into())
+        .ok()
+        .and_then(|v| v.as_f64())
+        .map(|c| c as f32);
 
     let attributes_val = js_sys::Reflect::get(val, &"attributes".into())
         .map_err(|_| StoreError::Serialization("missing attributes".into()))?;
@@ -436,34 +753,45 @@ fn js_to_attestation(val: &JsValue) -> StoreResult<Attestation> {
         created_at,
         signature: None,
         signer_did: None,
+        revision,
+        confidence,
     })
 }
 
+/// Revision of a stored record. Records written before revisions existed
+/// have no revision property and count as revision 1.
+fn stored_revision(val: &JsValue) -> u64 {
+    js_sys::Reflect::get(val, &"revision".into())
+        .ok()
+        .and_then(|v| v.as_f64())
+        .map_or(1, |r| r as u64)
+}
+
 /// Set a property on a JS object.
-fn set_prop(obj: &js_sys::Object, key: &str, val: &JsValue) -> StoreResult<()> {
+fn set_prop(obj: &js_sys::Object, key: &str, val: &JsValue) -> Result<()> {
     js_sys::Reflect::set(obj, &key.into(), val)
         .map_err(|_| StoreError::Backend(format!("failed to set property: {}", key)))?;
     Ok(())
 }
 
 /// Get a string property from a JS object.
-fn get_string_prop(val: &JsValue, key: &str) -> StoreResult<String> {
+fn get_string_prop(val: &JsValue, key: &str) -> Result<String> {
     let prop = js_sys::Reflect::get(val, &key.into())
         .map_err(|_| StoreError::Serialization(format!("missing property: {}", key)))?;
     prop.as_string()
-        .ok_or_else(|| StoreError::Serialization(format!("{} is not a string", key)))
+        .ok_or_else(|| StoreError::Serialization(format!("{} is not a string", key)).into())
 }
 
 /// Get a number property from a JS object.
-fn get_number_prop(val: &JsValue, key: &str) -> StoreResult<f64> {
+fn get_number_prop(val: &JsValue, key: &str) -> Result<f64> {
     let prop = js_sys::Reflect::get(val, &key.into())
         .map_err(|_| StoreError::Serialization(format!("missing property: {}", key)))?;
     prop.as_f64()
-        .ok_or_else(|| StoreError::Serialization(format!("{} is not a number", key)))
+        .ok_or_else(|| StoreError::Serialization(format!("{} is not a number", key)).into())
 }
 
 /// Get a string array property from a JS object.
-fn get_string_array_prop(val: &JsValue, key: &str) -> StoreResult<Vec<String>> {
+fn get_string_array_prop(val: &JsValue, key: &str) -> Result<Vec<String>> {
     let prop = js_sys::Reflect::get(val, &key.into())
         .map_err(|_| StoreError::Serialization(format!("missing property: {}", key)))?;
     let array = js_sys::Array::from(&prop);
@@ -485,6 +813,56 @@ fn string_vec_to_js_array(vec: &[String]) -> JsValue {
     array.into()
 }
 
+// ============================================================================
+// Page cursors
+// ============================================================================
+
+/// Key range of the IDs after `after`, or every ID when it is None
+fn ids_after(after: Option<&str>) -> Result<JsValue> {
+    match after {
+        Some(id) => Ok(
+            IdbKeyRange::lower_bound_with_open(&JsValue::from_str(id), true)
+                .map_err(|e| IndexedDbError::from_js(&e, IndexedDbError::Request))?
+                .into(),
+        ),
+        None => Ok(JsValue::UNDEFINED),
+    }
+}
+
+/// Cursor resuming a scan after `id`: the prefix, then the ID's UTF-8 bytes
+/// in hex so the token is safe in URLs and JSON.
+fn encode_page_cursor(id: &str) -> String {
+    let mut cursor = String::with_capacity(PAGE_CURSOR_PREFIX.len() + id.len() * 2);
+    cursor.push_str(PAGE_CURSOR_PREFIX);
+    for byte in id.bytes() {
+        cursor.push_str(&format!("{:02x}", byte));
+    }
+    cursor
+}
+
+/// The ID a cursor resumes after. Anything [`encode_page_cursor`] didn't
+/// produce is an error rather than a restart from the first page.
+fn decode_page_cursor(cursor: &str) -> Result<String> {
+    let invalid = || {
+        IndexedDbError::from(StoreError::Query(format!(
+            "invalid page cursor: {:?}",
+            cursor
+        )))
+    };
+    let hex = cursor
+        .strip_prefix(PAGE_CURSOR_PREFIX)
+        .ok_or_else(invalid)?;
+    if hex.is_empty() || hex.len() % 2 != 0 {
+        return Err(invalid());
+    }
+    let bytes = (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect::<Option<Vec<u8>>>()
+        .ok_or_else(invalid)?;
+    String::from_utf8(bytes).map_err(|_| invalid())
+}
+
 // ============================================================================
 // Query filtering (same logic as MemoryStore)
 // ============================================================================
@@ -539,7 +917,13 @@ fn matches_filter(attestation: &Attestation, filter: &AxFilter) -> bool {
         }
     }
 
-    true
+    if let Some(min) = filter.min_confidence {
+        if !attestation.confidence.is_some_and(|c| c >= min) {
+            return false;
+        }
+    }
+
+    filter.matches_attributes(&attestation.attributes)
 }
 
 /// Build a summary from a list of attestations.
@@ -550,6 +934,7 @@ fn build_summary(attestations: &[Attestation]) -> AxSummary {
         unique_predicates: HashMap::new(),
         unique_contexts: HashMap::new(),
         unique_actors: HashMap::new(),
+        matching: None,
     };
 
     for attestation in attestations {
@@ -572,3 +957,56 @@ fn build_summary(attestations: &[Attestation]) -> AxSummary {
 
     summary
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn page_cursor_round_trips() {
+        for id in ["AS-1", "as/with spaces?&=", "ÅLICE-✓"] {
+            let cursor = encode_page_cursor(id);
+            assert!(cursor.starts_with(PAGE_CURSOR_PREFIX));
+            assert!(cursor
+                .bytes()
+                .all(|b| b.is_ascii_alphanumeric() || b == b'.'));
+            assert_eq!(decode_page_cursor(&cursor).unwrap(), id);
+        }
+    }
+
+    #[test]
+    fn invalid_page_cursors_are_rejected() {
+        for cursor in ["", "AS-1", "p1.", "p1.4", "p1.zz", "p2.4153", "p1.ff"] {
+            let err = decode_page_cursor(cursor).unwrap_err();
+            assert_eq!(err.kind().code(), "invalid_input", "{}", cursor);
+            assert!(err.to_string().contains("invalid page cursor"));
+        }
+    }
+
+    #[test]
+    fn import_modes_and_report_json() {
+        assert_eq!(
+            ImportMode::parse("skip_existing"),
+            Some(ImportMode::SkipExisting)
+        );
+        assert_eq!(ImportMode::parse("Overwrite"), Some(ImportMode::Overwrite));
+        assert_eq!(ImportMode::parse("merge"), None);
+
+        let report = ImportReport {
+            imported: 2,
+            skipped: 1,
+            errors: vec![ImportFailure {
+                index: 3,
+                error: "bad".into(),
+            }],
+        };
+        assert_eq!(
+            serde_json::to_value(&report).unwrap(),
+            serde_json::json!({
+                "imported": 2,
+                "skipped": 1,
+                "errors": [{"index": 3, "error": "bad"}],
+            })
+        );
+    }
+}
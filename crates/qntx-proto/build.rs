@@ -67,6 +67,10 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         ),
     );
     config.field_attribute("protocol.Attestation.signer_did", "#[serde(default)]");
+    config.field_attribute(
+        "protocol.Attestation.confidence",
+        "#[serde(default, skip_serializing_if = \"Option::is_none\")]",
+    );
 
     config.compile_protos(&protos, &[&proto_dir])?;
 
@@ -3,10 +3,15 @@
 //! Converts between proto-generated types (prost + custom serde) and
 //! qntx_core internal types. The proto types use google.protobuf.Struct
 //! for attributes; core types use HashMap<String, serde_json::Value>.
+//!
+//! `revision` and `confidence` travel with the attestation but are metadata:
+//! content hashes such as the mirror's leave them out, so re-scoring a claim
+//! doesn't change its identity.
 
 use crate::serde_struct;
 use crate::Attestation as ProtoAttestation;
 use qntx_core::attestation::Attestation as CoreAttestation;
+use qntx_core::storage::PutOutcome;
 
 /// Convert a proto Attestation to core Attestation
 pub fn from_proto(proto: ProtoAttestation) -> CoreAttestation {
@@ -34,6 +39,8 @@ pub fn from_proto(proto: ProtoAttestation) -> CoreAttestation {
         } else {
             Some(proto.signer_did)
         },
+        revision: proto.revision,
+        confidence: proto.confidence,
     }
 }
 
@@ -57,9 +64,27 @@ pub fn to_proto(core: CoreAttestation) -> ProtoAttestation {
         created_at: core.created_at,
         signature: core.signature.unwrap_or_default(),
         signer_did: core.signer_did.unwrap_or_default(),
+        revision: core.revision,
+        confidence: core.confidence,
     }
 }
 
+/// Serialize a `put_if_revision` outcome for FFI/WASM callers, with the
+/// conflicting attestation in proto schema:
+/// `{"status":"written","revision":N}` or `{"status":"conflict","current":{...}|null}`.
+pub fn put_outcome_to_json(outcome: PutOutcome) -> String {
+    let value = match outcome {
+        PutOutcome::Written { revision } => {
+            serde_json::json!({ "status": "written", "revision": revision })
+        }
+        PutOutcome::Conflict { current } => serde_json::json!({
+            "status": "conflict",
+            "current": current.map(|a| to_proto(*a)),
+        }),
+    };
+    value.to_string()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -83,6 +108,8 @@ mod tests {
             created_at: 1234567890,
             signature: None,
             signer_did: None,
+            revision: 3,
+            confidence: Some(0.75),
         };
 
         let proto = to_proto(core.clone());
@@ -93,6 +120,8 @@ mod tests {
         assert_eq!(back.predicates, core.predicates);
         assert_eq!(back.timestamp, core.timestamp);
         assert_eq!(back.created_at, core.created_at);
+        assert_eq!(back.revision, 3);
+        assert_eq!(back.confidence, Some(0.75));
         assert_eq!(back.attributes["key"], "value");
         // f64 roundtrip: integer becomes float in protobuf Struct
         assert_eq!(back.attributes["count"], 42.0);
@@ -118,6 +147,8 @@ mod tests {
             created_at: 1704067200,
             signature: None,
             signer_did: None,
+            revision: 0,
+            confidence: None,
         };
 
         let proto = to_proto(core);
@@ -152,6 +183,8 @@ mod tests {
             created_at: 0,
             signature: None,
             signer_did: None,
+            revision: 0,
+            confidence: None,
         };
 
         let proto = to_proto(core.clone());
@@ -183,4 +216,29 @@ mod tests {
         assert_eq!(core.attributes["theme"], "dark");
         assert_eq!(core.attributes["version"], 2.0);
     }
+
+    #[test]
+    fn test_put_outcome_to_json() {
+        let written: serde_json::Value =
+            serde_json::from_str(&put_outcome_to_json(PutOutcome::Written { revision: 2 }))
+                .unwrap();
+        assert_eq!(
+            written,
+            serde_json::json!({"status": "written", "revision": 2})
+        );
+
+        let current = CoreAttestation {
+            id: "AS-1".to_string(),
+            revision: 4,
+            ..Default::default()
+        };
+        let conflict: serde_json::Value =
+            serde_json::from_str(&put_outcome_to_json(PutOutcome::Conflict {
+                current: Some(Box::new(current)),
+            }))
+            .unwrap();
+        assert_eq!(conflict["status"], "conflict");
+        assert_eq!(conflict["current"]["id"], "AS-1");
+        assert_eq!(conflict["current"]["revision"], 4);
+    }
 }
@@ -16,6 +16,8 @@ mod tests {
             created_at: 1234567890,
             signature: Vec::new(),
             signer_did: String::new(),
+            revision: 0,
+            confidence: None,
         };
 
         // Test JSON serialization works
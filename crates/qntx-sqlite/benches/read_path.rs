@@ -0,0 +1,90 @@
+//! Benchmark: hot read path of SqliteStore.
+//!
+//! Get-by-id and a filtered query over 10K attestations, with the statement
+//! cache disabled (`uncached`, every call re-prepares, as before caching) and
+//! enabled under each profile.
+//!
+//! Run with `cargo bench -p qntx-sqlite --bench read_path`.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use qntx_core::{
+    storage::{AttestationStore, QueryStore},
+    AttestationBuilder, AxFilter,
+};
+use qntx_sqlite::{OpenOptions, SqliteStore, StoreProfile};
+use tempfile::TempDir;
+
+const ATTESTATIONS: usize = 10_000;
+
+fn populate(dir: &TempDir) -> std::path::PathBuf {
+    let path = dir.path().join("bench.db");
+    let mut store =
+        SqliteStore::open_with(&path, &OpenOptions::profile(StoreProfile::Fast)).unwrap();
+    let batch = (0..ATTESTATIONS)
+        .map(|i| {
+            AttestationBuilder::new()
+                .id(format!("AS-{}", i))
+                .subject(format!("SUBJECT-{}", i % 500))
+                .predicate(["knows", "likes", "author_of", "member_of"][i % 4])
+                .context(format!("context-{}", i % 50))
+                .actor(format!("human:actor-{}", i % 20))
+                .timestamp(1704067200000 + i as i64 * 60_000)
+                .source("bench")
+                .attribute("score", serde_json::json!(i % 100))
+                .build()
+        })
+        .collect();
+    store.put_batched(batch, 1000).unwrap();
+    path
+}
+
+fn configurations() -> Vec<(&'static str, OpenOptions)> {
+    vec![
+        (
+            "uncached",
+            OpenOptions {
+                statement_cache: 0,
+                ..Default::default()
+            },
+        ),
+        ("durable", OpenOptions::profile(StoreProfile::Durable)),
+        ("balanced", OpenOptions::profile(StoreProfile::Balanced)),
+        ("fast", OpenOptions::profile(StoreProfile::Fast)),
+    ]
+}
+
+fn read_path(c: &mut Criterion) {
+    let dir = TempDir::new().unwrap();
+    let path = populate(&dir);
+    let filter = AxFilter {
+        predicates: vec!["author_of".into()],
+        contexts: vec!["context-7".into(), "context-9".into()],
+        limit: Some(50),
+        ..Default::default()
+    };
+
+    let mut get = c.benchmark_group("get_by_id");
+    for (name, options) in configurations() {
+        let store = SqliteStore::open_with(&path, &options).unwrap();
+        let mut i = 0;
+        get.bench_function(BenchmarkId::from_parameter(name), |b| {
+            b.iter(|| {
+                i = (i + 7919) % ATTESTATIONS;
+                black_box(store.get(&format!("AS-{}", i)).unwrap())
+            })
+        });
+    }
+    get.finish();
+
+    let mut query = c.benchmark_group("filtered_query");
+    for (name, options) in configurations() {
+        let store = SqliteStore::open_with(&path, &options).unwrap();
+        query.bench_function(BenchmarkId::from_parameter(name), |b| {
+            b.iter(|| black_box(store.query(&filter).unwrap()))
+        });
+    }
+    query.finish();
+}
+
+criterion_group!(benches, read_path);
+criterion_main!(benches);
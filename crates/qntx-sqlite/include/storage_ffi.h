@@ -23,16 +23,23 @@ extern "C" {
 // Opaque store handles
 typedef struct SqliteStore SqliteStore;
 typedef struct ReadConn ReadConn;
+typedef struct ReadOnlyStore ReadOnlyStore;
 // Result types
+// error_code is a static StorageErrorKind code ("not_found", "duplicate",
+// "quota_exceeded", "corruption", "io", "invalid_input", "backend").
+// NULL when the failure did not come from the store. Never free it.
 typedef struct {
     bool success;
     char *error_msg;
+    const char *error_code;
 } StorageResultC;
 
 typedef struct {
     bool success;
     char *error_msg;
     char *attestation_json; // NULL if not found
+    const char *error_code;
+    size_t corrupt_count;   // rows skipped by tolerant decoding (queries only)
 } AttestationResultC;
 
 typedef struct {
@@ -68,6 +75,37 @@ SqliteStore *storage_new_memory(void);
  */
 SqliteStore *storage_new_file(const char *path);
 
+/**
+ * Create a file-backed SQLite store with connection options.
+ * Returns NULL on failure, including invalid options.
+ * Must call storage_free() when done.
+ *
+ * Profiles (see StoreProfile): "durable" (default) syncs every commit;
+ * "balanced" may lose the last commits on power loss; "fast" never syncs
+ * and may corrupt the file on power loss or OS crash. No profile enables
+ * mmap; a non-zero "mmap_size" opts in and risks SIGBUS when a checkpoint
+ * changes the file under a mapping.
+ *
+ * @param path Filesystem path for database file
+ * @param options_json {"profile":"durable"|"balanced"|"fast","mmap_size":N,
+ *                     "cache_size_kib":N,"statement_cache":N}, all optional;
+ *                     NULL or "" for the defaults
+ */
+SqliteStore *storage_new_file_with_options(const char *path, const char *options_json);
+
+/**
+ * Create a file-backed SQLite store bound to a namespace (workspace).
+ * Every operation on the store, and on read connections opened from it,
+ * only sees attestations of that namespace. Attestation IDs are unique
+ * per namespace.
+ * Returns NULL on failure, including an empty namespace.
+ * Must call storage_free() when done.
+ *
+ * @param path Filesystem path for database file
+ * @param namespace_ Namespace name (non-empty, at most 128 bytes)
+ */
+SqliteStore *storage_new_file_scoped(const char *path, const char *namespace_);
+
 /**
  * Free a store and release all resources.
  * Safe to call with NULL pointer.
@@ -81,8 +119,8 @@ void storage_free(SqliteStore *store);
 // ============================================================================
 
 /**
- * Open a read-only connection from a file-backed store.
- * Returns NULL for in-memory stores or on failure.
+ * Open a read-only connection from a file-backed store, scoped to the
+ * store's namespace. Returns NULL for in-memory stores or on failure.
  */
 ReadConn *storage_open_read_conn(const SqliteStore *store);
 
@@ -102,7 +140,8 @@ AttestationResultC read_conn_get(const ReadConn *rc, const char *id);
 StorageResultC read_conn_exists(const ReadConn *rc, const char *id);
 
 /**
- * Query attestations through the read connection.
+ * Query attestations through the read connection. Same filter and result
+ * shapes as storage_query(); the rows and summary share one snapshot.
  */
 AttestationResultC read_conn_query(const ReadConn *rc, const char *filter_json);
 
@@ -141,6 +180,44 @@ AttestationResultC read_conn_query_raw(const ReadConn *rc, const char *sql, cons
  */
 StringArrayResultC read_conn_integrity_check(const ReadConn *rc);
 
+// ============================================================================
+// Read-only Snapshots (copied files with live refresh)
+// ============================================================================
+
+/**
+ * Open a copied database file as a validated read-only snapshot.
+ * Returns NULL when the file is truncated, corrupt or not a QNTX database.
+ * Must call read_only_free() when done.
+ */
+ReadOnlyStore *storage_open_read_only(const char *path);
+
+/**
+ * Free a read-only snapshot store.
+ */
+void read_only_free(ReadOnlyStore *store);
+
+/**
+ * Validate and swap to a newly copied file. NULL path reopens the current
+ * file. On failure the previous snapshot keeps serving.
+ * Output JSON: {"path":"...","rows":N,"schema_version":"...","file_len":N}
+ */
+AttestationResultC read_only_refresh(const ReadOnlyStore *store, const char *path);
+
+/**
+ * Get an attestation by ID from the current snapshot.
+ */
+AttestationResultC read_only_get(const ReadOnlyStore *store, const char *id);
+
+/**
+ * Query attestations from the current snapshot.
+ */
+AttestationResultC read_only_query(const ReadOnlyStore *store, const char *filter_json);
+
+/**
+ * Count attestations in the current snapshot.
+ */
+CountResultC read_only_count(const ReadOnlyStore *store);
+
 /**
  * Set enforcement config on the store.
  * When set, enforcement runs automatically after every storage_put().
@@ -191,6 +268,20 @@ StorageResultC storage_exists(const SqliteStore *store, const char *id);
  */
 StorageResultC storage_delete(SqliteStore *store, const char *id);
 
+/**
+ * Delete an attestation through the delete cascade, optionally listing the
+ * attestations that reference it (a full scan) or only planning it.
+ * A missing ID is not an error: the report has "found":false.
+ *
+ * @param store Store handle
+ * @param id Attestation ID
+ * @param options_json {"dry_run":false,"find_orphans":false}, or NULL
+ * @return Result with JSON {"id","dry_run","found","orphans":[...],
+ *         "hooks":[{"name","touched","error"}]}
+ */
+AttestationResultC storage_delete_cascade(SqliteStore *store, const char *id,
+                                          const char *options_json);
+
 /**
  * Update an existing attestation.
  *
@@ -200,6 +291,18 @@ StorageResultC storage_delete(SqliteStore *store, const char *id);
  */
 StorageResultC storage_update(SqliteStore *store, const char *attestation_json);
 
+/**
+ * Write an attestation only if its stored revision matches.
+ *
+ * @param store Store handle
+ * @param attestation_json JSON-encoded attestation
+ * @param expected_revision Revision the caller last read (0 = must not exist)
+ * @return Result whose attestation_json is {"status":"written","revision":N}
+ *         or {"status":"conflict","current":<attestation|null>}
+ */
+AttestationResultC storage_put_if_revision(SqliteStore *store, const char *attestation_json,
+                                           uint64_t expected_revision);
+
 /**
  * Get all attestation IDs.
  *
@@ -227,9 +330,16 @@ StorageResultC storage_clear(SqliteStore *store);
 /**
  * Query attestations with filters.
  *
+ * The filter is copied; the caller keeps ownership of filter_json. The
+ * result's strings belong to the caller: free it with attestation_result_free().
+ *
  * @param store Store handle
- * @param filter_json JSON-encoded AxFilter
- * @return Result with JSON array of matching attestations
+ * @param filter_json JSON-encoded AxFilter (subjects, predicates, contexts,
+ *        actors, time_start/time_end, limit, ...), or NULL for every
+ *        attestation up to 1000. Malformed JSON fails with "invalid_input".
+ * @return Result with JSON array of matching attestations, or
+ *         {"attestations": [...], "summary": {...}} when the filter sets
+ *         include_summary
  */
 AttestationResultC storage_query(const SqliteStore *store, const char *filter_json);
 
@@ -375,6 +485,74 @@ void query_result_free(QueryResultC result);
  */
 StringArrayResultC storage_integrity_check(const SqliteStore *store);
 
+// ============================================================================
+// Maintenance
+// ============================================================================
+
+/**
+ * Run a WAL checkpoint without touching read connections.
+ * Truncate only shrinks the WAL when no read connection holds it; use
+ * storage_wal_checkpoint to cycle read connections around it.
+ *
+ * @param store Store handle
+ * @param mode "passive", "full" or "truncate"
+ * @return Result with JSON {"mode","busy","wal_pages","checkpointed_pages"}
+ */
+AttestationResultC storage_checkpoint(const SqliteStore *store, const char *mode);
+
+/**
+ * Rebuild the database file (VACUUM). Blocks writers for the whole rewrite.
+ *
+ * @param store Store handle
+ * @return Result with JSON {"before":{page_size,page_count,freelist_count},"after":{...}}
+ */
+AttestationResultC storage_vacuum(const SqliteStore *store);
+
+/**
+ * Free up to `pages` free pages (0 = all). Requires auto_vacuum=INCREMENTAL.
+ *
+ * @param store Store handle
+ * @param pages Maximum pages to free
+ * @return Result with JSON {"freed_pages":N}
+ */
+AttestationResultC storage_incremental_vacuum(const SqliteStore *store, size_t pages);
+
+/**
+ * Refresh query planner statistics (ANALYZE).
+ *
+ * @param store Store handle
+ * @return Success or error
+ */
+StorageResultC storage_analyze(const SqliteStore *store);
+
+/**
+ * Structured integrity check.
+ *
+ * @param store Store handle
+ * @return Result with JSON {"ok":bool,"problems":[...]}
+ */
+AttestationResultC storage_integrity_report(const SqliteStore *store);
+
+/**
+ * Structured integrity check through a read connection. Unlike
+ * storage_integrity_report it does not hold up writes through the store.
+ *
+ * @param rc Read connection
+ * @return Result with JSON {"ok":bool,"problems":[...]}
+ */
+AttestationResultC read_conn_integrity_report(const ReadConn *rc);
+
+/**
+ * Attach an automatic maintenance policy (JSON
+ * {"wal_limit_bytes":N,"checkpoint_mode":"passive","check_every":N}).
+ * NULL or "" removes it. Caller must hold the write mutex.
+ *
+ * @param store Store handle
+ * @param policy_json Policy document, or NULL
+ * @return Success or error
+ */
+StorageResultC storage_set_maintenance_policy(SqliteStore *store, const char *policy_json);
+
 // ============================================================================
 // Age Distillation
 // ============================================================================
@@ -444,6 +622,51 @@ CheckpointResultC storage_wal_checkpoint(SqliteStore *store, ReadConn **read_con
  */
 StorageResultC storage_backup(const char *src_path, const char *dest_path);
 
+/**
+ * Mirror attestations matching an AxFilter into another database file,
+ * incrementally. Mirror state lives in this store; caller must hold the
+ * write mutex.
+ *
+ * @param store Source store handle
+ * @param dest_path Destination database path (created if missing)
+ * @param filter_json AxFilter JSON
+ * @param options_json MirrorOptions JSON
+ *        {"name","batch_size","propagate_deletes","dry_run","run_id"}, or NULL
+ * @return Result with JSON {"run_id","dry_run","copied","updated","skipped",
+ *         "deleted","errors":[{"id","error"}],"changes":[{"id","kind"}]}
+ */
+AttestationResultC storage_mirror(const SqliteStore *store, const char *dest_path,
+                                  const char *filter_json, const char *options_json);
+
+/**
+ * Export attestations matching an AxFilter to a CSV or Parquet file,
+ * streaming rows. Read-only; no write mutex needed.
+ *
+ * @param store Store handle
+ * @param path Output file path (replaced if it exists)
+ * @param filter_json AxFilter JSON
+ * @param format "csv" or "parquet" (parquet needs --features parquet)
+ * @param options_json ExportOptions JSON
+ *        {"arrays":{"mode":"json"|"explode"|"join","delimiter"},
+ *         "attributes":{"mode":"json"|"columns","columns":[{"key","type"}]},
+ *         "batch_rows"}, or NULL
+ * @return Result with JSON {"rows","bytes","duration_ms"}
+ */
+AttestationResultC storage_export_query(const SqliteStore *store, const char *path,
+                                        const char *filter_json, const char *format,
+                                        const char *options_json);
+
+/**
+ * Run the qntx-core self-benchmark (seeded workloads, ~1s budget).
+ * Development only; present when the library is built with `--features bench`.
+ *
+ * @param config_json BenchConfig JSON
+ *        {"seed","iterations","budget_ms","scale","workloads"}, or NULL
+ * @return Result with JSON {"environment","config","workloads":[{"name","size",
+ *         "iterations","median_ms","p95_ms","min_ms"}],"total_ms","budget_exhausted"}
+ */
+AttestationResultC storage_run_benchmarks(const char *config_json);
+
 /**
  * Deliberately trigger SIGBUS to verify flight recorder.
  * Development/testing only.
@@ -6,12 +6,28 @@
 //! - Maximum unique contexts
 //!
 //! Default quotas match standard tier: 16 attestations, 64 predicates, 64 contexts
+//!
+//! Quotas are per namespace: every count is taken through the wrapped store,
+//! which only sees its own namespace, so workspaces sharing a file don't eat
+//! into each other's limits.
+//!
+//! A full store rejects puts by default. An [`EvictionPolicy`] can instead
+//! make room by deleting the oldest attestations; predicate and context
+//! quotas always reject, since evicting attestations can't be relied on to
+//! shrink the vocabulary.
 
 use qntx_core::{
     attestation::{Attestation, AxFilter, AxResult},
-    storage::{AttestationStore, QueryStore, StorageStats, StoreError},
+    storage::{
+        AttestationStore, ChangeBatch, ChangeFeed, ChangeRetention, QueryStore, StorageStats,
+        StoreError,
+    },
 };
 
+use rusqlite::OptionalExtension;
+
+use crate::error::SqliteError;
+use crate::store::build_filter_sql;
 use crate::SqliteStore;
 
 type StoreResult<T> = Result<T, StoreError>;
@@ -62,24 +78,53 @@ impl StorageQuotas {
     }
 }
 
+/// What a put does when the attestation quota is reached
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum EvictionPolicy {
+    /// Fail with `QuotaExceeded`
+    #[default]
+    Reject,
+    /// Delete the oldest attestation (by timestamp) sharing an actor and a
+    /// context with the new one; if there is none, the oldest overall
+    EvictOldest,
+    /// Delete the oldest attestation sharing an actor and a context with the
+    /// new one; fail with `QuotaExceeded` if there is none
+    EvictOldestInGroup,
+}
+
+/// What [`BoundedStore::put_with_outcome`] did to make room
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BoundedPutOutcome {
+    /// IDs of the attestations deleted, in eviction order
+    pub evicted: Vec<String>,
+}
+
 /// Bounded storage wrapper enforcing quotas
 pub struct BoundedStore {
     store: SqliteStore,
     quotas: StorageQuotas,
+    policy: EvictionPolicy,
 }
 
 impl BoundedStore {
     /// Create a new bounded store with default quotas
     pub fn new(store: SqliteStore) -> Self {
-        Self {
-            store,
-            quotas: StorageQuotas::default(),
-        }
+        Self::with_quotas(store, StorageQuotas::default())
     }
 
     /// Create a bounded store with custom quotas
     pub fn with_quotas(store: SqliteStore, quotas: StorageQuotas) -> Self {
-        Self { store, quotas }
+        Self::with_policy(store, quotas, EvictionPolicy::default())
+    }
+
+    /// Create a bounded store with custom quotas that handles a full store
+    /// according to `policy`
+    pub fn with_policy(store: SqliteStore, quotas: StorageQuotas, policy: EvictionPolicy) -> Self {
+        Self {
+            store,
+            quotas,
+            policy,
+        }
     }
 
     /// Create an in-memory bounded store (for testing)
@@ -92,11 +137,26 @@ impl BoundedStore {
         Ok(Self::with_quotas(SqliteStore::in_memory()?, quotas))
     }
 
+    /// Bounded handle on `namespace` in the same database file, with the same
+    /// quotas applied to that namespace alone. Only works for file-backed stores.
+    pub fn scoped(&self, namespace: &str) -> crate::error::Result<Self> {
+        Ok(Self::with_policy(
+            self.store.scoped(namespace)?,
+            self.quotas,
+            self.policy,
+        ))
+    }
+
     /// Get a reference to the quotas
     pub fn quotas(&self) -> &StorageQuotas {
         &self.quotas
     }
 
+    /// What a put does once the attestation quota is reached
+    pub fn policy(&self) -> EvictionPolicy {
+        self.policy
+    }
+
     /// Get a reference to the underlying store
     pub fn store(&self) -> &SqliteStore {
         &self.store
@@ -104,24 +164,19 @@ impl BoundedStore {
 
     /// Check if adding an attestation would exceed quotas
     fn check_quotas(&self, attestation: &Attestation) -> StoreResult<()> {
-        // Get actor (first one, or "unknown")
-        let actor = attestation
-            .actors
-            .first()
-            .map(|s| s.as_str())
-            .unwrap_or("unknown");
-
         // Check attestation count
         let current_count = self.store.count()?;
         if current_count >= self.quotas.max_attestations {
-            return Err(StoreError::QuotaExceeded {
-                actor: actor.to_string(),
-                context: "attestations".to_string(),
-                current: current_count,
-                limit: self.quotas.max_attestations,
-            });
+            return Err(self.attestation_quota_error(attestation, current_count));
         }
 
+        self.check_vocabulary(attestation)
+    }
+
+    /// Check the predicate and context quotas against an attestation's values
+    fn check_vocabulary(&self, attestation: &Attestation) -> StoreResult<()> {
+        let actor = quota_actor(attestation);
+
         // Get current unique predicates and contexts
         let current_predicates = self.store.predicates()?;
         let current_contexts = self.store.contexts()?;
@@ -156,12 +211,122 @@ impl BoundedStore {
 
         Ok(())
     }
+
+    fn attestation_quota_error(&self, attestation: &Attestation, current: usize) -> StoreError {
+        StoreError::QuotaExceeded {
+            actor: quota_actor(attestation).to_string(),
+            context: "attestations".to_string(),
+            current,
+            limit: self.quotas.max_attestations,
+        }
+    }
+
+    /// Store an attestation, evicting older ones first if the policy allows
+    /// and the attestation quota is reached.
+    ///
+    /// Evictions and the write share a SAVEPOINT: if the put fails, nothing
+    /// is evicted. Duplicate IDs and vocabulary quotas are checked before
+    /// anything is deleted.
+    pub fn put_with_outcome(&mut self, attestation: Attestation) -> StoreResult<BoundedPutOutcome> {
+        if self.policy == EvictionPolicy::Reject {
+            self.check_quotas(&attestation)?;
+            self.store.put(attestation)?;
+            return Ok(BoundedPutOutcome::default());
+        }
+
+        if self.store.exists(&attestation.id)? {
+            return Err(StoreError::AlreadyExists(attestation.id));
+        }
+        self.check_vocabulary(&attestation)?;
+
+        self.store
+            .connection()
+            .execute_batch("SAVEPOINT bounded_put")
+            .map_err(SqliteError::from)?;
+        let mut outcome = BoundedPutOutcome::default();
+        if let Err(e) = self.evict_and_put(attestation, &mut outcome.evicted) {
+            let _ = self
+                .store
+                .connection()
+                .execute_batch("ROLLBACK TO SAVEPOINT bounded_put; RELEASE SAVEPOINT bounded_put");
+            return Err(e);
+        }
+        self.store
+            .connection()
+            .execute_batch("RELEASE SAVEPOINT bounded_put")
+            .map_err(SqliteError::from)?;
+        Ok(outcome)
+    }
+
+    fn evict_and_put(
+        &mut self,
+        attestation: Attestation,
+        evicted: &mut Vec<String>,
+    ) -> StoreResult<()> {
+        let mut current_count = self.store.count()?;
+        while current_count >= self.quotas.max_attestations {
+            let Some(victim) = self.eviction_victim(&attestation)? else {
+                return Err(self.attestation_quota_error(&attestation, current_count));
+            };
+            self.store.delete(&victim)?;
+            current_count -= 1;
+            evicted.push(victim);
+        }
+        self.store.put(attestation)
+    }
+
+    /// ID of the attestation the policy evicts to make room for `attestation`
+    fn eviction_victim(&self, attestation: &Attestation) -> StoreResult<Option<String>> {
+        if self.policy == EvictionPolicy::Reject {
+            return Ok(None);
+        }
+        let in_group = if attestation.actors.is_empty() || attestation.contexts.is_empty() {
+            None
+        } else {
+            self.oldest(&AxFilter {
+                actors: attestation.actors.clone(),
+                contexts: attestation.contexts.clone(),
+                ..Default::default()
+            })?
+        };
+
+        match (self.policy, in_group) {
+            (EvictionPolicy::EvictOldest, None) => self.oldest(&AxFilter::default()),
+            (_, in_group) => Ok(in_group),
+        }
+    }
+
+    /// ID of the oldest attestation matching `filter`'s junction and time
+    /// conditions, by timestamp with ties broken by ID. Selected in SQL so
+    /// each eviction reads one row rather than the whole group.
+    fn oldest(&self, filter: &AxFilter) -> StoreResult<Option<String>> {
+        let (filter_sql, params) = build_filter_sql(filter, self.store.namespace());
+        let sql = format!(
+            "SELECT att.id FROM attestations att{} ORDER BY att.timestamp, att.id LIMIT 1",
+            filter_sql
+        );
+        let id = self
+            .store
+            .connection()
+            .query_row(&sql, rusqlite::params_from_iter(params), |row| row.get(0))
+            .optional()
+            .map_err(SqliteError::from)?;
+        Ok(id)
+    }
+}
+
+/// Actor named in quota errors: the first one, or "unknown"
+fn quota_actor(attestation: &Attestation) -> &str {
+    attestation
+        .actors
+        .first()
+        .map(|s| s.as_str())
+        .unwrap_or("unknown")
 }
 
 impl AttestationStore for BoundedStore {
     fn put(&mut self, attestation: Attestation) -> StoreResult<()> {
-        self.check_quotas(&attestation)?;
-        self.store.put(attestation)
+        self.put_with_outcome(attestation).map(|_| ())
     }
 
     fn get(&self, id: &str) -> StoreResult<Option<Attestation>> {
@@ -174,44 +339,7 @@ impl AttestationStore for BoundedStore {
 
     fn update(&mut self, attestation: Attestation) -> StoreResult<()> {
         // For updates, only check new predicates/contexts (not total count)
-        let current_predicates = self.store.predicates()?;
-        let current_contexts = self.store.contexts()?;
-
-        // Get actor
-        let actor = attestation
-            .actors
-            .first()
-            .map(|s| s.as_str())
-            .unwrap_or("unknown");
-
-        // Check new predicates
-        for predicate in &attestation.predicates {
-            if !current_predicates.contains(predicate)
-                && current_predicates.len() >= self.quotas.max_predicates
-            {
-                return Err(StoreError::QuotaExceeded {
-                    actor: actor.to_string(),
-                    context: "predicates".to_string(),
-                    current: current_predicates.len(),
-                    limit: self.quotas.max_predicates,
-                });
-            }
-        }
-
-        // Check new contexts
-        for context in &attestation.contexts {
-            if !current_contexts.contains(context)
-                && current_contexts.len() >= self.quotas.max_contexts
-            {
-                return Err(StoreError::QuotaExceeded {
-                    actor: actor.to_string(),
-                    context: "contexts".to_string(),
-                    current: current_contexts.len(),
-                    limit: self.quotas.max_contexts,
-                });
-            }
-        }
-
+        self.check_vocabulary(&attestation)?;
         self.store.update(attestation)
     }
 
@@ -250,6 +378,20 @@ impl QueryStore for BoundedStore {
     }
 }
 
+impl ChangeFeed for BoundedStore {
+    fn changes_since(&self, seq: u64, limit: usize) -> StoreResult<ChangeBatch> {
+        self.store.changes_since(seq, limit)
+    }
+
+    fn latest_seq(&self) -> StoreResult<u64> {
+        self.store.latest_seq()
+    }
+
+    fn prune_changes(&mut self, retention: &ChangeRetention, now_ms: i64) -> StoreResult<usize> {
+        self.store.prune_changes(retention, now_ms)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -272,6 +414,32 @@ mod tests {
             .build()
     }
 
+    fn timed_attestation(id: &str, actor: &str, context: &str, timestamp: i64) -> Attestation {
+        AttestationBuilder::new()
+            .id(id)
+            .subject("ALICE")
+            .predicate("knows")
+            .context(context)
+            .actor(actor)
+            .timestamp(timestamp)
+            .source("test")
+            .build()
+    }
+
+    fn evicting_store(max_attestations: usize, policy: EvictionPolicy) -> BoundedStore {
+        BoundedStore::with_policy(
+            SqliteStore::in_memory().unwrap(),
+            StorageQuotas::new(max_attestations, 10, 10),
+            policy,
+        )
+    }
+
+    fn sorted_ids(store: &BoundedStore) -> Vec<String> {
+        let mut ids = store.ids().unwrap();
+        ids.sort();
+        ids
+    }
+
     #[test]
     fn test_bounded_put_within_quota() {
         let mut store = BoundedStore::in_memory().unwrap();
@@ -413,4 +581,113 @@ mod tests {
 
         assert_eq!(store.count().unwrap(), 100);
     }
+
+    #[test]
+    fn test_evict_oldest_prefers_group_then_global() {
+        let mut store = evicting_store(3, EvictionPolicy::EvictOldest);
+        store
+            .put(timed_attestation("AS-1", "human:alice", "work", 100))
+            .unwrap();
+        store
+            .put(timed_attestation("AS-2", "human:bob", "work", 50))
+            .unwrap();
+        store
+            .put(timed_attestation("AS-3", "human:alice", "work", 300))
+            .unwrap();
+
+        // AS-2 is older overall, but AS-1 shares the (actor, context) group
+        let outcome = store
+            .put_with_outcome(timed_attestation("AS-4", "human:alice", "work", 400))
+            .unwrap();
+        assert_eq!(outcome.evicted, ["AS-1"]);
+
+        // Nothing shares this group, so the oldest overall goes
+        let outcome = store
+            .put_with_outcome(timed_attestation("AS-5", "human:carol", "home", 500))
+            .unwrap();
+        assert_eq!(outcome.evicted, ["AS-2"]);
+        assert_eq!(sorted_ids(&store), ["AS-3", "AS-4", "AS-5"]);
+    }
+
+    #[test]
+    fn test_evict_oldest_in_group_rejects_without_group() {
+        let mut store = evicting_store(2, EvictionPolicy::EvictOldestInGroup);
+        store
+            .put(timed_attestation("AS-1", "human:alice", "work", 100))
+            .unwrap();
+        store
+            .put(timed_attestation("AS-2", "human:bob", "work", 200))
+            .unwrap();
+
+        let result = store.put(timed_attestation("AS-3", "human:carol", "work", 300));
+        assert!(matches!(result, Err(StoreError::QuotaExceeded { .. })));
+        assert_eq!(sorted_ids(&store), ["AS-1", "AS-2"]);
+
+        let outcome = store
+            .put_with_outcome(timed_attestation("AS-4", "human:bob", "work", 400))
+            .unwrap();
+        assert_eq!(outcome.evicted, ["AS-2"]);
+    }
+
+    #[test]
+    fn test_eviction_evicts_down_to_quota_oldest_first() {
+        let mut inner = SqliteStore::in_memory().unwrap();
+        for (id, ts) in [("AS-1", 300), ("AS-2", 100), ("AS-3", 200), ("AS-4", 400)] {
+            inner
+                .put(timed_attestation(id, "human:alice", "work", ts))
+                .unwrap();
+        }
+
+        // Quota lowered below what is already stored
+        let mut store = BoundedStore::with_policy(
+            inner,
+            StorageQuotas::new(2, 10, 10),
+            EvictionPolicy::EvictOldest,
+        );
+        let outcome = store
+            .put_with_outcome(timed_attestation("AS-5", "human:alice", "work", 500))
+            .unwrap();
+        assert_eq!(outcome.evicted, ["AS-2", "AS-3", "AS-1"]);
+        assert_eq!(sorted_ids(&store), ["AS-4", "AS-5"]);
+    }
+
+    #[test]
+    fn test_eviction_orders_by_timestamp_then_id() {
+        let mut store = evicting_store(3, EvictionPolicy::EvictOldest);
+        for (id, ts) in [("AS-3", 1_000), ("AS-2", 1_000), ("AS-1", 1_500)] {
+            store
+                .put(timed_attestation(id, "human:alice", "work", ts))
+                .unwrap();
+        }
+
+        let outcome = store
+            .put_with_outcome(timed_attestation("AS-4", "human:bob", "home", 2_000))
+            .unwrap();
+        assert_eq!(outcome.evicted, ["AS-2"]);
+        let outcome = store
+            .put_with_outcome(timed_attestation("AS-5", "human:carol", "home", 2_000))
+            .unwrap();
+        assert_eq!(outcome.evicted, ["AS-3"]);
+    }
+
+    #[test]
+    fn test_eviction_keeps_hard_failures() {
+        let quotas = StorageQuotas::new(1, 1, 10);
+        let mut store = BoundedStore::with_policy(
+            SqliteStore::in_memory().unwrap(),
+            quotas,
+            EvictionPolicy::EvictOldest,
+        );
+        store
+            .put(create_test_attestation("AS-1", "ALICE", "knows", "work"))
+            .unwrap();
+
+        // A new predicate still fails, and nothing is evicted for it
+        let result = store.put(create_test_attestation("AS-2", "BOB", "manages", "work"));
+        assert!(matches!(result, Err(StoreError::QuotaExceeded { .. })));
+        // So does a duplicate ID
+        let result = store.put(create_test_attestation("AS-1", "BOB", "knows", "work"));
+        assert!(matches!(result, Err(StoreError::AlreadyExists(_))));
+        assert_eq!(sorted_ids(&store), ["AS-1"]);
+    }
 }
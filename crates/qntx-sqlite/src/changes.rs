@@ -0,0 +1,218 @@
+//! Changefeed: the [`ChangeFeed`] of a SQLite store.
+//!
+//! Every write appends a row to `attestation_changes` under the next sequence
+//! number of the store's namespace, taken from `attestation_change_seq`.
+//! Both live in the file, so numbering continues where it left off after
+//! reopening, and several connections to one file share one order. The
+//! counter is kept apart from the events, so pruning never lowers
+//! `latest_seq`.
+//!
+//! Recording happens next to the history hooks (see [`history`](crate::history)),
+//! in the same functions every writer goes through. Each row write runs in a
+//! savepoint together with its events, so a failed write leaves none, and
+//! inside the writer's transaction where there is one: a batch import numbers
+//! its rows in order, and a rolled-back batch leaves no events. Raw SQL
+//! bypasses it.
+
+use rusqlite::{Connection, OptionalExtension};
+
+use qntx_core::attestation::Attestation;
+use qntx_core::storage::{
+    ChangeBatch, ChangeEvent, ChangeFeed, ChangeOp, ChangeRetention, StoreError,
+};
+
+use crate::error::{Result, SqliteError};
+use crate::store::SqliteStore;
+
+type StoreResult<T> = std::result::Result<T, StoreError>;
+
+fn now_ms() -> i64 {
+    chrono::Utc::now().timestamp_millis()
+}
+
+/// Append one event for `namespace` and return its sequence number.
+fn append(
+    conn: &Connection,
+    namespace: &str,
+    op: ChangeOp,
+    id: &str,
+    content_hash: Option<&str>,
+) -> Result<i64> {
+    let seq: i64 = conn
+        .prepare_cached(
+            "INSERT INTO attestation_change_seq (namespace, latest_seq) VALUES (?, 1)
+             ON CONFLICT (namespace) DO UPDATE SET latest_seq = latest_seq + 1
+             RETURNING latest_seq",
+        )?
+        .query_row([namespace], |row| row.get(0))?;
+    conn.prepare_cached(
+        "INSERT INTO attestation_changes (namespace, seq, operation, attestation_id, content_hash, at)
+         VALUES (?, ?, ?, ?, ?, ?)",
+    )?
+    .execute(rusqlite::params![
+        namespace,
+        seq,
+        op.as_str(),
+        id,
+        content_hash,
+        now_ms()
+    ])?;
+    Ok(seq)
+}
+
+/// Record a put or update of `attestation` as stored.
+pub(crate) fn record_write(
+    conn: &Connection,
+    namespace: &str,
+    op: ChangeOp,
+    attestation: &Attestation,
+) -> Result<()> {
+    append(
+        conn,
+        namespace,
+        op,
+        &attestation.id,
+        Some(&attestation.content_hash()),
+    )?;
+    Ok(())
+}
+
+/// Record deletions of `ids`.
+pub(crate) fn record_deletes<'a>(
+    conn: &Connection,
+    namespace: &str,
+    ids: impl IntoIterator<Item = &'a str>,
+) -> Result<()> {
+    for id in ids {
+        append(conn, namespace, ChangeOp::Delete, id, None)?;
+    }
+    Ok(())
+}
+
+/// Record deletions of every row in `namespace`, e.g. before `clear`.
+pub(crate) fn record_delete_all(conn: &Connection, namespace: &str) -> Result<()> {
+    let ids = conn
+        .prepare("SELECT id FROM attestations WHERE namespace = ? ORDER BY rowid")?
+        .query_map([namespace], |row| row.get::<_, String>(0))?
+        .collect::<std::result::Result<Vec<_>, rusqlite::Error>>()?;
+    record_deletes(conn, namespace, ids.iter().map(String::as_str))
+}
+
+fn latest_seq(conn: &Connection, namespace: &str) -> Result<u64> {
+    let seq: Option<i64> = conn
+        .query_row(
+            "SELECT latest_seq FROM attestation_change_seq WHERE namespace = ?",
+            [namespace],
+            |row| row.get(0),
+        )
+        .optional()?;
+    Ok(seq.unwrap_or(0) as u64)
+}
+
+fn decode_event(
+    seq: i64,
+    operation: String,
+    attestation_id: String,
+    content_hash: Option<String>,
+    at: i64,
+) -> StoreResult<ChangeEvent> {
+    let op = ChangeOp::parse(&operation).ok_or_else(|| {
+        StoreError::Corruption(format!(
+            "change event {}: unknown operation {}",
+            seq, operation
+        ))
+    })?;
+    Ok(ChangeEvent {
+        seq: seq as u64,
+        op,
+        attestation_id,
+        content_hash,
+        at,
+    })
+}
+
+impl ChangeFeed for SqliteStore {
+    fn changes_since(&self, seq: u64, limit: usize) -> StoreResult<ChangeBatch> {
+        let after = i64::try_from(seq).unwrap_or(i64::MAX);
+        let rows = self
+            .conn
+            .prepare_cached(
+                "SELECT seq, operation, attestation_id, content_hash, at FROM attestation_changes
+                 WHERE namespace = ? AND seq > ? ORDER BY seq LIMIT ?",
+            )
+            .map_err(SqliteError::from)?
+            .query_map(
+                rusqlite::params![
+                    self.namespace,
+                    after,
+                    i64::try_from(limit).unwrap_or(i64::MAX)
+                ],
+                |row| {
+                    Ok((
+                        row.get(0)?,
+                        row.get(1)?,
+                        row.get(2)?,
+                        row.get(3)?,
+                        row.get(4)?,
+                    ))
+                },
+            )
+            .map_err(SqliteError::from)?
+            .collect::<std::result::Result<Vec<_>, rusqlite::Error>>()
+            .map_err(SqliteError::from)?;
+        // Read after the events, so latest_seq covers all of them
+        let latest = latest_seq(&self.conn, &self.namespace)?;
+        let oldest: Option<i64> = self
+            .conn
+            .query_row(
+                "SELECT MIN(seq) FROM attestation_changes WHERE namespace = ?",
+                [&self.namespace],
+                |row| row.get(0),
+            )
+            .map_err(SqliteError::from)?;
+
+        let oldest = oldest.map_or(latest.saturating_add(1), |s| s as u64);
+        let events = rows
+            .into_iter()
+            .map(|(seq, op, id, hash, at)| decode_event(seq, op, id, hash, at))
+            .collect::<StoreResult<Vec<_>>>()?;
+        Ok(ChangeBatch {
+            events,
+            latest_seq: latest,
+            missed: seq < latest && seq.saturating_add(1) < oldest,
+        })
+    }
+
+    fn latest_seq(&self) -> StoreResult<u64> {
+        Ok(latest_seq(&self.conn, &self.namespace)?)
+    }
+
+    fn prune_changes(&mut self, retention: &ChangeRetention, now_ms: i64) -> StoreResult<usize> {
+        let tx = self
+            .conn
+            .unchecked_transaction()
+            .map_err(SqliteError::from)?;
+        let mut removed = 0;
+        if let Some(age) = retention.max_age_ms {
+            removed += tx
+                .execute(
+                    "DELETE FROM attestation_changes WHERE namespace = ? AND at < ?",
+                    rusqlite::params![self.namespace, now_ms - age],
+                )
+                .map_err(SqliteError::from)?;
+        }
+        if let Some(max_events) = retention.max_events {
+            removed += tx
+                .execute(
+                    "DELETE FROM attestation_changes WHERE namespace = ?1 AND seq <= (
+                         SELECT seq FROM attestation_changes WHERE namespace = ?1
+                         ORDER BY seq DESC LIMIT 1 OFFSET ?2
+                     )",
+                    rusqlite::params![self.namespace, max_events as i64],
+                )
+                .map_err(SqliteError::from)?;
+        }
+        tx.commit().map_err(SqliteError::from)?;
+        Ok(removed)
+    }
+}
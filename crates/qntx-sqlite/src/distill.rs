@@ -93,6 +93,8 @@ pub fn build_distill_attestation(evicted: &[Attestation], context: &str) -> Atte
         created_at: now_ms,
         signature: None,
         signer_did: None,
+        revision: 0,
+        confidence: None,
     }
 }
 
@@ -700,6 +702,8 @@ mod tests {
             created_at: 1746057600000,
             signature: None,
             signer_did: None,
+            revision: 0,
+            confidence: None,
         };
         let att2 = Attestation {
             id: "AS-distill-2".into(),
@@ -713,6 +717,8 @@ mod tests {
             created_at: 1746489600000,
             signature: None,
             signer_did: None,
+            revision: 0,
+            confidence: None,
         };
 
         let merged = merge_attributes(&[att1, att2]);
@@ -903,6 +909,8 @@ mod tests {
             created_at: 1747137000000,
             signature: None,
             signer_did: None,
+            revision: 0,
+            confidence: None,
         };
         let att2 = Attestation {
             id: "AS-distill-2".into(),
@@ -916,6 +924,8 @@ mod tests {
             created_at: 1747138200000,
             signature: None,
             signer_did: None,
+            revision: 0,
+            confidence: None,
         };
 
         let merged = merge_attributes(&[att1, att2]);
@@ -960,6 +970,8 @@ mod tests {
             created_at: 1747137000000,
             signature: None,
             signer_did: None,
+            revision: 0,
+            confidence: None,
         };
 
         // New raw attestation with timestamp
@@ -1053,6 +1065,8 @@ mod tests {
             created_at: 1747137000000,
             signature: None,
             signer_did: None,
+            revision: 0,
+            confidence: None,
         };
 
         // New raw attestation with a string value
@@ -1110,6 +1124,8 @@ mod tests {
             created_at: 1746057600000,
             signature: None,
             signer_did: None,
+            revision: 0,
+            confidence: None,
         };
 
         let mut attrs2 = HashMap::new();
@@ -1139,6 +1155,8 @@ mod tests {
             created_at: 1746489600000,
             signature: None,
             signer_did: None,
+            revision: 0,
+            confidence: None,
         };
 
         let merged = merge_attributes(&[att1, att2]);
@@ -1225,6 +1243,8 @@ mod tests {
             created_at: 1747137000000,
             signature: None,
             signer_did: None,
+            revision: 0,
+            confidence: None,
         };
 
         // New raw attestation
@@ -13,9 +13,11 @@ use qntx_core::storage::enforcement::{EnforcementEvent, EnforcementInput, Evicti
 
 use std::collections::HashSet;
 
+use crate::changes;
 use crate::distill;
 use crate::error::SqliteError;
-use crate::store::put_attestation;
+use crate::history;
+use crate::store::{atomically, put_attestation};
 use crate::SqliteStore;
 
 /// Extract distinct predicate strings from rows where each row has a JSON array
@@ -186,9 +188,9 @@ impl SqliteStore {
             .conn
             .query_row(
                 "SELECT COUNT(*) FROM attestation_actors a
-                 JOIN attestation_contexts c ON a.attestation_id = c.attestation_id
-                 WHERE a.actor = ?1 AND c.context = ?2",
-                rusqlite::params![actor, context],
+                 JOIN attestation_contexts c ON a.attestation_id = c.attestation_id AND a.namespace = c.namespace
+                 WHERE a.namespace = ?3 AND a.actor = ?1 AND c.context = ?2",
+                rusqlite::params![actor, context, self.namespace],
                 |row| row.get::<_, i64>(0),
             )
             .unwrap_or(0);
@@ -207,14 +209,14 @@ impl SqliteStore {
 
         // Load full attestation data for the eviction batch
         let eviction_batch = self.load_eviction_batch(
-            "SELECT att.id, att.subjects, att.predicates, att.contexts, att.actors, att.timestamp, att.source, att.attributes, att.created_at, att.signature, att.signer_did
+            "SELECT att.id, att.subjects, att.predicates, att.contexts, att.actors, att.timestamp, att.source, att.attributes, att.created_at, att.signature, att.signer_did, att.revision, att.confidence
              FROM attestations att
-             JOIN attestation_actors a ON att.id = a.attestation_id
-             JOIN attestation_contexts c ON att.id = c.attestation_id
-             WHERE a.actor = ?1 AND c.context = ?2
+             JOIN attestation_actors a ON att.id = a.attestation_id AND att.namespace = a.namespace
+             JOIN attestation_contexts c ON att.id = c.attestation_id AND att.namespace = c.namespace
+             WHERE att.namespace = ?4 AND a.actor = ?1 AND c.context = ?2
              ORDER BY att.timestamp ASC
              LIMIT ?3",
-            rusqlite::params![actor, context, delete_count],
+            rusqlite::params![actor, context, delete_count, self.namespace],
         )?;
 
         // Build eviction details for the event log
@@ -241,25 +243,39 @@ impl SqliteStore {
             }
         };
 
-        // Delete oldest attestations first (CASCADE deletes junction rows)
-        let _deleted = self.conn.execute(
-            "DELETE FROM attestations
-             WHERE id IN (
-                 SELECT att.id FROM attestations att
-                 JOIN attestation_actors a ON att.id = a.attestation_id
-                 JOIN attestation_contexts c ON att.id = c.attestation_id
-                 WHERE a.actor = ?1 AND c.context = ?2 COLLATE NOCASE
-                 ORDER BY att.timestamp ASC
-                 LIMIT ?3
-             )",
-            rusqlite::params![actor, context, delete_count],
-        )?;
+        let _deleted = atomically(&self.conn, || -> Result<usize, SqliteError> {
+            changes::record_deletes(
+                &self.conn,
+                &self.namespace,
+                eviction_batch.iter().map(|a| a.id.as_str()),
+            )?;
+            history::record_deletes(
+                &self.conn,
+                &self.namespace,
+                eviction_batch.iter().map(|a| a.id.as_str()),
+            )?;
+
+            // Delete oldest attestations first (CASCADE deletes junction rows)
+            let deleted = self.conn.execute(
+                "DELETE FROM attestations
+                 WHERE namespace = ?4 AND id IN (
+                     SELECT att.id FROM attestations att
+                     JOIN attestation_actors a ON att.id = a.attestation_id AND att.namespace = a.namespace
+                     JOIN attestation_contexts c ON att.id = c.attestation_id AND att.namespace = c.namespace
+                     WHERE att.namespace = ?4 AND a.actor = ?1 AND c.context = ?2 COLLATE NOCASE
+                     ORDER BY att.timestamp ASC
+                     LIMIT ?3
+                 )",
+                rusqlite::params![actor, context, delete_count, self.namespace],
+            )?;
+            Ok(deleted)
+        })?;
 
         // Σ Insert sigma after deleting originals.
         if !eviction_batch.is_empty() {
             let sigma = distill::build_distill_attestation(&eviction_batch, context);
             self.distilling = true;
-            let insert_result = put_attestation(&self.conn, &sigma);
+            let insert_result = put_attestation(&self.conn, &sigma, &self.namespace);
             self.distilling = false;
             if let Err(e) = insert_result {
                 eprintln!(
@@ -321,12 +337,12 @@ impl SqliteStore {
             let mut stmt = self.conn.prepare(
                 "SELECT c.context, COUNT(*) as cnt
                  FROM attestation_contexts c
-                 JOIN attestation_actors a ON c.attestation_id = a.attestation_id
-                 WHERE a.actor = ?1
+                 JOIN attestation_actors a ON c.attestation_id = a.attestation_id AND c.namespace = a.namespace
+                 WHERE a.namespace = ?2 AND a.actor = ?1
                  GROUP BY c.context
                  ORDER BY cnt ASC",
             )?;
-            let mut rows = stmt.query(rusqlite::params![actor])?;
+            let mut rows = stmt.query(rusqlite::params![actor, self.namespace])?;
             let mut result = Vec::new();
             while let Some(row) = rows.next()? {
                 result.push(ContextUsage {
@@ -351,6 +367,8 @@ impl SqliteStore {
             .collect();
         let placeholders: Vec<String> =
             (0..ctx_list.len()).map(|i| format!("?{}", i + 2)).collect();
+        // Namespace binds after the context list
+        let ns_param = format!("?{}", ctx_list.len() + 2);
 
         let mut details = EvictionDetails {
             evicted_actors: Vec::new(),
@@ -364,9 +382,10 @@ impl SqliteStore {
             let query = format!(
                 "SELECT MIN(att.timestamp)
                  FROM attestations att
-                 JOIN attestation_actors a ON att.id = a.attestation_id
-                 JOIN attestation_contexts c ON att.id = c.attestation_id
-                 WHERE a.actor = ?1 AND c.context IN ({})",
+                 JOIN attestation_actors a ON att.id = a.attestation_id AND att.namespace = a.namespace
+                 JOIN attestation_contexts c ON att.id = c.attestation_id AND att.namespace = c.namespace
+                 WHERE att.namespace = {} AND a.actor = ?1 AND c.context IN ({})",
+                ns_param,
                 placeholders.join(", ")
             );
             let mut stmt = self.conn.prepare(&query)?;
@@ -375,6 +394,7 @@ impl SqliteStore {
             for ctx in &ctx_list {
                 params.push(Box::new(ctx.to_string()));
             }
+            params.push(Box::new(self.namespace.clone()));
             let param_refs: Vec<&dyn rusqlite::types::ToSql> =
                 params.iter().map(|p| p.as_ref()).collect();
             details.last_seen = stmt
@@ -388,9 +408,10 @@ impl SqliteStore {
             let query = format!(
                 "SELECT DISTINCT att.predicates
                  FROM attestations att
-                 JOIN attestation_actors a ON att.id = a.attestation_id
-                 JOIN attestation_contexts c ON att.id = c.attestation_id
-                 WHERE a.actor = ?1 AND c.context IN ({})",
+                 JOIN attestation_actors a ON att.id = a.attestation_id AND att.namespace = a.namespace
+                 JOIN attestation_contexts c ON att.id = c.attestation_id AND att.namespace = c.namespace
+                 WHERE att.namespace = {} AND a.actor = ?1 AND c.context IN ({})",
+                ns_param,
                 placeholders.join(", ")
             );
             let mut stmt = self.conn.prepare(&query)?;
@@ -399,6 +420,7 @@ impl SqliteStore {
             for ctx in &ctx_list {
                 params.push(Box::new(ctx.to_string()));
             }
+            params.push(Box::new(self.namespace.clone()));
             let param_refs: Vec<&dyn rusqlite::types::ToSql> =
                 params.iter().map(|p| p.as_ref()).collect();
             let mut rows = stmt.query(param_refs.as_slice())?;
@@ -410,32 +432,46 @@ impl SqliteStore {
 
             // Load full attestation data for distillation
             let eviction_batch = self.load_eviction_batch(
-                "SELECT att.id, att.subjects, att.predicates, att.contexts, att.actors, att.timestamp, att.source, att.attributes, att.created_at, att.signature, att.signer_did
+                "SELECT att.id, att.subjects, att.predicates, att.contexts, att.actors, att.timestamp, att.source, att.attributes, att.created_at, att.signature, att.signer_did, att.revision, att.confidence
                  FROM attestations att
-                 JOIN attestation_actors a ON att.id = a.attestation_id
-                 JOIN attestation_contexts c ON att.id = c.attestation_id
-                 WHERE a.actor = ?1 AND c.context = ?2",
-                rusqlite::params![actor, cu.context],
+                 JOIN attestation_actors a ON att.id = a.attestation_id AND att.namespace = a.namespace
+                 JOIN attestation_contexts c ON att.id = c.attestation_id AND att.namespace = c.namespace
+                 WHERE att.namespace = ?3 AND a.actor = ?1 AND c.context = ?2",
+                rusqlite::params![actor, cu.context, self.namespace],
             )?;
 
-            // Delete all attestations with this context for this actor
-            let deleted = self.conn.execute(
-                "DELETE FROM attestations
-                 WHERE id IN (
-                     SELECT a.attestation_id
-                     FROM attestation_actors a
-                     JOIN attestation_contexts c ON a.attestation_id = c.attestation_id
-                     WHERE a.actor = ?1 AND c.context = ?2
-                 )",
-                rusqlite::params![actor, cu.context],
-            )?;
+            let deleted = atomically(&self.conn, || -> Result<usize, SqliteError> {
+                changes::record_deletes(
+                    &self.conn,
+                    &self.namespace,
+                    eviction_batch.iter().map(|a| a.id.as_str()),
+                )?;
+                history::record_deletes(
+                    &self.conn,
+                    &self.namespace,
+                    eviction_batch.iter().map(|a| a.id.as_str()),
+                )?;
+
+                // Delete all attestations with this context for this actor
+                let deleted = self.conn.execute(
+                    "DELETE FROM attestations
+                     WHERE namespace = ?3 AND id IN (
+                         SELECT a.attestation_id
+                         FROM attestation_actors a
+                         JOIN attestation_contexts c ON a.attestation_id = c.attestation_id AND a.namespace = c.namespace
+                         WHERE a.namespace = ?3 AND a.actor = ?1 AND c.context = ?2
+                     )",
+                    rusqlite::params![actor, cu.context, self.namespace],
+                )?;
+                Ok(deleted)
+            })?;
             total_deleted += deleted;
 
             // Σ Insert sigma after deleting originals
             if !eviction_batch.is_empty() {
                 let sigma = distill::build_distill_attestation(&eviction_batch, &cu.context);
                 self.distilling = true;
-                let insert_result = put_attestation(&self.conn, &sigma);
+                let insert_result = put_attestation(&self.conn, &sigma, &self.namespace);
                 self.distilling = false;
                 if let Err(e) = insert_result {
                     eprintln!(
@@ -498,13 +534,13 @@ impl SqliteStore {
             let mut stmt = self.conn.prepare(
                 "SELECT a.actor, MAX(att.timestamp) as last_seen
                  FROM attestation_actors a
-                 JOIN attestation_subjects s ON a.attestation_id = s.attestation_id
-                 JOIN attestations att ON att.id = a.attestation_id
-                 WHERE s.subject = ?1
+                 JOIN attestation_subjects s ON a.attestation_id = s.attestation_id AND a.namespace = s.namespace
+                 JOIN attestations att ON att.id = a.attestation_id AND att.namespace = a.namespace
+                 WHERE s.namespace = ?2 AND s.subject = ?1
                  GROUP BY a.actor
                  ORDER BY last_seen ASC",
             )?;
-            let mut rows = stmt.query(rusqlite::params![entity])?;
+            let mut rows = stmt.query(rusqlite::params![entity, self.namespace])?;
             let mut result = Vec::new();
             while let Some(row) = rows.next()? {
                 result.push(row.get::<_, String>(0)?);
@@ -524,6 +560,8 @@ impl SqliteStore {
         let placeholders: Vec<String> = (0..actor_list.len())
             .map(|i| format!("?{}", i + 2))
             .collect();
+        // Namespace binds after the actor list
+        let ns_param = format!("?{}", actor_list.len() + 2);
 
         let mut details = EvictionDetails {
             evicted_actors: Vec::new(),
@@ -537,9 +575,10 @@ impl SqliteStore {
             let query = format!(
                 "SELECT MIN(att.timestamp)
                  FROM attestations att
-                 JOIN attestation_actors a ON att.id = a.attestation_id
-                 JOIN attestation_subjects s ON att.id = s.attestation_id
-                 WHERE s.subject = ?1 AND a.actor IN ({})",
+                 JOIN attestation_actors a ON att.id = a.attestation_id AND att.namespace = a.namespace
+                 JOIN attestation_subjects s ON att.id = s.attestation_id AND att.namespace = s.namespace
+                 WHERE att.namespace = {} AND s.subject = ?1 AND a.actor IN ({})",
+                ns_param,
                 placeholders.join(", ")
             );
             let mut stmt = self.conn.prepare(&query)?;
@@ -548,6 +587,7 @@ impl SqliteStore {
             for actor in &actor_list {
                 params.push(Box::new(actor.to_string()));
             }
+            params.push(Box::new(self.namespace.clone()));
             let param_refs: Vec<&dyn rusqlite::types::ToSql> =
                 params.iter().map(|p| p.as_ref()).collect();
             details.last_seen = stmt
@@ -561,9 +601,10 @@ impl SqliteStore {
             let query = format!(
                 "SELECT DISTINCT att.predicates
                  FROM attestations att
-                 JOIN attestation_actors a ON att.id = a.attestation_id
-                 JOIN attestation_subjects s ON att.id = s.attestation_id
-                 WHERE s.subject = ?1 AND a.actor IN ({})",
+                 JOIN attestation_actors a ON att.id = a.attestation_id AND att.namespace = a.namespace
+                 JOIN attestation_subjects s ON att.id = s.attestation_id AND att.namespace = s.namespace
+                 WHERE att.namespace = {} AND s.subject = ?1 AND a.actor IN ({})",
+                ns_param,
                 placeholders.join(", ")
             );
             let mut stmt = self.conn.prepare(&query)?;
@@ -572,6 +613,7 @@ impl SqliteStore {
             for actor in &actor_list {
                 params.push(Box::new(actor.to_string()));
             }
+            params.push(Box::new(self.namespace.clone()));
             let param_refs: Vec<&dyn rusqlite::types::ToSql> =
                 params.iter().map(|p| p.as_ref()).collect();
             let mut rows = stmt.query(param_refs.as_slice())?;
@@ -583,32 +625,46 @@ impl SqliteStore {
 
             // Load full attestation data for distillation
             let eviction_batch = self.load_eviction_batch(
-                "SELECT att.id, att.subjects, att.predicates, att.contexts, att.actors, att.timestamp, att.source, att.attributes, att.created_at, att.signature, att.signer_did
+                "SELECT att.id, att.subjects, att.predicates, att.contexts, att.actors, att.timestamp, att.source, att.attributes, att.created_at, att.signature, att.signer_did, att.revision, att.confidence
                  FROM attestations att
-                 JOIN attestation_actors a ON att.id = a.attestation_id
-                 JOIN attestation_subjects s ON att.id = s.attestation_id
-                 WHERE a.actor = ?1 AND s.subject = ?2",
-                rusqlite::params![actor, entity],
+                 JOIN attestation_actors a ON att.id = a.attestation_id AND att.namespace = a.namespace
+                 JOIN attestation_subjects s ON att.id = s.attestation_id AND att.namespace = s.namespace
+                 WHERE att.namespace = ?3 AND a.actor = ?1 AND s.subject = ?2",
+                rusqlite::params![actor, entity, self.namespace],
             )?;
 
-            // Delete all attestations by this actor that mention this entity
-            let deleted = self.conn.execute(
-                "DELETE FROM attestations
-                 WHERE id IN (
-                     SELECT a.attestation_id
-                     FROM attestation_actors a
-                     JOIN attestation_subjects s ON a.attestation_id = s.attestation_id
-                     WHERE a.actor = ?1 AND s.subject = ?2
-                 )",
-                rusqlite::params![actor, entity],
-            )?;
+            let deleted = atomically(&self.conn, || -> Result<usize, SqliteError> {
+                changes::record_deletes(
+                    &self.conn,
+                    &self.namespace,
+                    eviction_batch.iter().map(|a| a.id.as_str()),
+                )?;
+                history::record_deletes(
+                    &self.conn,
+                    &self.namespace,
+                    eviction_batch.iter().map(|a| a.id.as_str()),
+                )?;
+
+                // Delete all attestations by this actor that mention this entity
+                let deleted = self.conn.execute(
+                    "DELETE FROM attestations
+                     WHERE namespace = ?3 AND id IN (
+                         SELECT a.attestation_id
+                         FROM attestation_actors a
+                         JOIN attestation_subjects s ON a.attestation_id = s.attestation_id AND a.namespace = s.namespace
+                         WHERE a.namespace = ?3 AND a.actor = ?1 AND s.subject = ?2
+                     )",
+                    rusqlite::params![actor, entity, self.namespace],
+                )?;
+                Ok(deleted)
+            })?;
             total_deleted += deleted;
 
             // Σ Insert sigma after deleting originals
             if !eviction_batch.is_empty() {
                 let sigma = distill::build_distill_attestation(&eviction_batch, "_distill");
                 self.distilling = true;
-                let insert_result = put_attestation(&self.conn, &sigma);
+                let insert_result = put_attestation(&self.conn, &sigma, &self.namespace);
                 self.distilling = false;
                 if let Err(e) = insert_result {
                     eprintln!(
@@ -650,21 +706,7 @@ impl SqliteStore {
         params: impl rusqlite::Params,
     ) -> Result<Vec<Attestation>, SqliteError> {
         let mut stmt = self.conn.prepare(sql)?;
-        let rows = stmt.query_map(params, |row| {
-            Ok((
-                row.get::<_, String>(0)?,
-                row.get::<_, String>(1)?,
-                row.get::<_, String>(2)?,
-                row.get::<_, String>(3)?,
-                row.get::<_, String>(4)?,
-                row.get::<_, String>(5)?,
-                row.get::<_, String>(6)?,
-                row.get::<_, Option<String>>(7)?,
-                row.get::<_, String>(8)?,
-                row.get::<_, Option<Vec<u8>>>(9)?,
-                row.get::<_, Option<String>>(10)?,
-            ))
-        })?;
+        let rows = stmt.query_map(params, crate::json::read_attestation_row)?;
 
         let mut attestations = Vec::new();
         for row_result in rows {
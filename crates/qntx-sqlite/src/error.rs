@@ -1,6 +1,6 @@
 //! Error types for SQLite storage backend
 
-use qntx_core::storage::StoreError;
+use qntx_core::storage::{StorageErrorKind, StoreError};
 use thiserror::Error;
 
 /// Result type for storage operations
@@ -32,18 +32,136 @@ pub enum SqliteError {
     /// IO error (for file operations)
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
+
+    /// Namespace name rejected (empty or too long)
+    #[error("Invalid namespace: {0}")]
+    InvalidNamespace(String),
+
+    /// Write attempted through a read-only snapshot
+    #[error("Read-only store: {0}")]
+    ReadOnly(String),
+
+    /// Snapshot file failed validation (truncated, corrupt or not a QNTX database)
+    #[error("Invalid snapshot: {0}")]
+    InvalidSnapshot(String),
+
+    /// Operation needs a file-backed database but the store is in memory
+    #[error("Requires a file-backed database: {0}")]
+    RequiresFile(String),
+}
+
+impl SqliteError {
+    /// Classify this error into the backend-independent storage kind.
+    pub fn kind(&self) -> StorageErrorKind {
+        match self {
+            SqliteError::AlreadyExists(_) => StorageErrorKind::Duplicate,
+            SqliteError::NotFound(_) => StorageErrorKind::NotFound,
+            SqliteError::Json(_) => StorageErrorKind::InvalidInput,
+            SqliteError::Migration(_) => StorageErrorKind::Backend,
+            SqliteError::Io(_) => StorageErrorKind::Io,
+            SqliteError::InvalidNamespace(_) => StorageErrorKind::InvalidInput,
+            SqliteError::ReadOnly(_) => StorageErrorKind::InvalidInput,
+            SqliteError::InvalidSnapshot(_) => StorageErrorKind::Corruption,
+            SqliteError::RequiresFile(_) => StorageErrorKind::InvalidInput,
+            SqliteError::Database(e) => database_error_kind(e),
+        }
+    }
+}
+
+/// Map a rusqlite error onto a storage kind using SQLite's primary result code.
+fn database_error_kind(err: &rusqlite::Error) -> StorageErrorKind {
+    use rusqlite::ErrorCode;
+
+    match err {
+        rusqlite::Error::QueryReturnedNoRows => StorageErrorKind::NotFound,
+        rusqlite::Error::SqliteFailure(e, _) => match e.code {
+            ErrorCode::ConstraintViolation => StorageErrorKind::Duplicate,
+            ErrorCode::DatabaseCorrupt | ErrorCode::NotADatabase => StorageErrorKind::Corruption,
+            ErrorCode::SystemIoFailure | ErrorCode::CannotOpen | ErrorCode::DiskFull => {
+                StorageErrorKind::Io
+            }
+            ErrorCode::TooBig => StorageErrorKind::InvalidInput,
+            _ => StorageErrorKind::Backend,
+        },
+        rusqlite::Error::InvalidParameterName(_)
+        | rusqlite::Error::InvalidColumnName(_)
+        | rusqlite::Error::SqlInputError { .. } => StorageErrorKind::InvalidInput,
+        rusqlite::Error::FromSqlConversionFailure(..)
+        | rusqlite::Error::InvalidColumnType(..)
+        | rusqlite::Error::Utf8Error(_) => StorageErrorKind::Corruption,
+        _ => StorageErrorKind::Backend,
+    }
 }
 
 /// Convert SqliteError to StoreError for the storage trait
+///
+/// The SQLite-specific detail stays in the message; the variant follows `kind()`.
 impl From<SqliteError> for StoreError {
     fn from(err: SqliteError) -> Self {
+        let kind = err.kind();
         match err {
             SqliteError::AlreadyExists(id) => StoreError::AlreadyExists(id),
             SqliteError::NotFound(id) => StoreError::NotFound(id),
             SqliteError::Json(e) => StoreError::Serialization(e.to_string()),
-            SqliteError::Database(e) => StoreError::Backend(format!("SQLite: {}", e)),
             SqliteError::Migration(msg) => StoreError::Backend(format!("Migration: {}", msg)),
-            SqliteError::Io(e) => StoreError::Backend(format!("IO: {}", e)),
+            SqliteError::Io(e) => StoreError::Io(format!("IO: {}", e)),
+            SqliteError::InvalidNamespace(msg) => {
+                StoreError::InvalidData(format!("namespace: {}", msg))
+            }
+            SqliteError::ReadOnly(msg) => StoreError::InvalidData(format!("read-only: {}", msg)),
+            SqliteError::InvalidSnapshot(msg) => {
+                StoreError::Corruption(format!("snapshot: {}", msg))
+            }
+            SqliteError::RequiresFile(msg) => {
+                StoreError::InvalidData(format!("requires a file-backed database: {}", msg))
+            }
+            SqliteError::Database(e) => {
+                let detail = format!("SQLite: {}", e);
+                match kind {
+                    StorageErrorKind::NotFound => StoreError::NotFound(detail),
+                    StorageErrorKind::Duplicate => StoreError::AlreadyExists(detail),
+                    StorageErrorKind::Corruption => StoreError::Corruption(detail),
+                    StorageErrorKind::Io => StoreError::Io(detail),
+                    StorageErrorKind::InvalidInput => StoreError::Query(detail),
+                    StorageErrorKind::QuotaExceeded | StorageErrorKind::Backend => {
+                        StoreError::Backend(detail)
+                    }
+                }
+            }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_kind_survives_conversion() {
+        let errors = vec![
+            SqliteError::NotFound("AS-1".into()),
+            SqliteError::AlreadyExists("AS-1".into()),
+            SqliteError::Database(rusqlite::Error::QueryReturnedNoRows),
+            SqliteError::Io(std::io::Error::other("disk gone")),
+            SqliteError::InvalidNamespace("".into()),
+            SqliteError::ReadOnly("put".into()),
+            SqliteError::InvalidSnapshot("truncated".into()),
+            SqliteError::RequiresFile("scoped handle".into()),
+        ];
+        for err in errors {
+            let kind = err.kind();
+            assert_eq!(StoreError::from(err).kind(), kind);
+        }
+    }
+
+    #[test]
+    fn test_constraint_violation_is_duplicate() {
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        conn.execute_batch("CREATE TABLE t (id TEXT PRIMARY KEY); INSERT INTO t VALUES ('a');")
+            .unwrap();
+        let err = conn.execute("INSERT INTO t VALUES ('a')", []).unwrap_err();
+        let err = StoreError::from(SqliteError::from(err));
+        assert_eq!(err.code(), "duplicate");
+        assert!(err.to_string().contains("UNIQUE"));
+    }
+}
@@ -0,0 +1,586 @@
+//! Export query results as CSV or Parquet.
+//!
+//! [`SqliteStore::export_query`] streams the rows of an AX query into a
+//! writer one attestation at a time, so exporting a large result never holds
+//! it in memory. (A filter whose attribute conditions can't run in SQL and
+//! that also has `limit` or `over` is the exception: it is finished in
+//! memory, as [`QueryStore::query`] does.)
+//!
+//! # Columns
+//!
+//! `id`, the four slots, `timestamp`, `source`, `confidence`, `revision`,
+//! `created_at`, then the attributes. Timestamps are UTC; CSV renders them
+//! as RFC 3339 with milliseconds, Parquet stores them as millisecond
+//! timestamps.
+//!
+//! The slots (subjects, predicates, contexts, actors) are flattened by
+//! [`ArrayEncoding`]:
+//!
+//! - `Json` (default): one row per attestation, each slot a JSON array
+//!   (`["ALICE","BOB"]`)
+//! - `Explode`: one row per individual claim, as
+//!   [`expand_cartesian`](qntx_core::expand::expand_cartesian) produces
+//!   them, in singular columns `subject`, `predicate`, `context`, `actor`.
+//!   An attestation with an empty slot yields no rows.
+//! - `Join`: one row per attestation, slot values joined with a delimiter.
+//!   Pick one that doesn't occur in the values; it is not escaped.
+//!
+//! Attributes are either one `attributes` JSON column ([`AttributeEncoding::Json`],
+//! default) or one `attributes.<key>` column per promoted key
+//! ([`AttributeEncoding::Columns`]), typed by [`AttributeType`]. Values that
+//! don't convert to the column's type are left empty.
+//!
+//! # CSV
+//!
+//! RFC 4180 quoting: fields containing a comma, quote, CR or LF are quoted,
+//! with quotes doubled. Rows end in `\n`; the first row is the header.
+//!
+//! # Parquet
+//!
+//! Behind the `parquet` feature, which pulls in the arrow and parquet
+//! crates. Files are Snappy-compressed, written in row groups of
+//! [`ExportOptions::batch_rows`].
+
+use std::io::{BufWriter, Write};
+use std::time::Instant;
+
+use chrono::{DateTime, SecondsFormat};
+use qntx_core::expand::{expand_cartesian, ExpandAttestation};
+use qntx_core::storage::{QueryStore, StoreError};
+use qntx_core::{Attestation, AxFilter};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::error::SqliteError;
+use crate::store::{build_query_sql, needs_post_filter, SqliteStore};
+
+type StoreResult<T> = Result<T, StoreError>;
+
+/// Rows per Parquet row group when the options don't say
+pub const DEFAULT_EXPORT_BATCH: usize = 8192;
+
+/// Output file format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportFormat {
+    Csv,
+    /// Requires the `parquet` feature
+    Parquet,
+}
+
+impl ExportFormat {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "csv" => Some(ExportFormat::Csv),
+            "parquet" => Some(ExportFormat::Parquet),
+            _ => None,
+        }
+    }
+}
+
+/// How the array slots become columns. See the [module docs](self).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum ArrayEncoding {
+    #[default]
+    Json,
+    Explode,
+    Join {
+        delimiter: String,
+    },
+}
+
+/// Type of a promoted attribute column.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AttributeType {
+    /// Strings as they are, other values as JSON
+    #[default]
+    String,
+    /// Numbers, or strings that parse as one
+    Number,
+    Boolean,
+    /// Unix milliseconds or an RFC 3339 string
+    Timestamp,
+}
+
+/// An attribute promoted to its own column.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AttributeColumn {
+    pub key: String,
+    #[serde(default, rename = "type")]
+    pub kind: AttributeType,
+}
+
+impl AttributeColumn {
+    pub fn new(key: impl Into<String>, kind: AttributeType) -> Self {
+        Self {
+            key: key.into(),
+            kind,
+        }
+    }
+}
+
+/// How attributes become columns. See the [module docs](self).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum AttributeEncoding {
+    #[default]
+    Json,
+    Columns {
+        columns: Vec<AttributeColumn>,
+    },
+}
+
+/// How an export lays out its rows.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ExportOptions {
+    pub arrays: ArrayEncoding,
+    pub attributes: AttributeEncoding,
+    /// Rows per Parquet row group (0 means [`DEFAULT_EXPORT_BATCH`])
+    pub batch_rows: usize,
+}
+
+/// What an export wrote.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExportSummary {
+    /// Data rows, excluding the CSV header
+    pub rows: u64,
+    pub bytes: u64,
+    pub duration_ms: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ColumnType {
+    Text,
+    Timestamp,
+    Float,
+    UInt,
+    Bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Cell {
+    Text(Option<String>),
+    Timestamp(Option<i64>),
+    Float(Option<f64>),
+    UInt(Option<u64>),
+    Bool(Option<bool>),
+}
+
+/// Column names and types for `options`, in row order.
+fn columns(options: &ExportOptions) -> Vec<(String, ColumnType)> {
+    let slots: [&str; 4] = match options.arrays {
+        ArrayEncoding::Explode => ["subject", "predicate", "context", "actor"],
+        _ => ["subjects", "predicates", "contexts", "actors"],
+    };
+    let mut columns = vec![("id".to_string(), ColumnType::Text)];
+    columns.extend(slots.iter().map(|s| (s.to_string(), ColumnType::Text)));
+    columns.extend([
+        ("timestamp".to_string(), ColumnType::Timestamp),
+        ("source".to_string(), ColumnType::Text),
+        ("confidence".to_string(), ColumnType::Float),
+        ("revision".to_string(), ColumnType::UInt),
+        ("created_at".to_string(), ColumnType::Timestamp),
+    ]);
+    match &options.attributes {
+        AttributeEncoding::Json => columns.push(("attributes".to_string(), ColumnType::Text)),
+        AttributeEncoding::Columns { columns: promoted } => {
+            columns.extend(promoted.iter().map(|c| {
+                let kind = match c.kind {
+                    AttributeType::String => ColumnType::Text,
+                    AttributeType::Number => ColumnType::Float,
+                    AttributeType::Boolean => ColumnType::Bool,
+                    AttributeType::Timestamp => ColumnType::Timestamp,
+                };
+                (format!("attributes.{}", c.key), kind)
+            }))
+        }
+    }
+    columns
+}
+
+fn attribute_cell(value: Option<&Value>, kind: AttributeType) -> Cell {
+    let value = value.filter(|v| !v.is_null());
+    match kind {
+        AttributeType::String => Cell::Text(value.map(|v| match v {
+            Value::String(s) => s.clone(),
+            other => other.to_string(),
+        })),
+        AttributeType::Number => Cell::Float(value.and_then(|v| match v {
+            Value::String(s) => s.trim().parse().ok(),
+            other => other.as_f64(),
+        })),
+        AttributeType::Boolean => Cell::Bool(value.and_then(Value::as_bool)),
+        AttributeType::Timestamp => Cell::Timestamp(value.and_then(|v| {
+            match v {
+                Value::String(s) => DateTime::parse_from_rfc3339(s)
+                    .ok()
+                    .map(|d| d.timestamp_millis()),
+                other => other.as_i64(),
+            }
+        })),
+    }
+}
+
+/// The rows `attestation` contributes, cells in [`columns`] order.
+fn rows(attestation: &Attestation, options: &ExportOptions) -> Vec<Vec<Cell>> {
+    let slot = |values: &[String]| -> Cell {
+        Cell::Text(Some(match &options.arrays {
+            ArrayEncoding::Join { delimiter } => values.join(delimiter),
+            _ => serde_json::to_string(values).expect("string array always serializes"),
+        }))
+    };
+    let mut tail = vec![
+        Cell::Timestamp(Some(attestation.timestamp)),
+        Cell::Text(Some(attestation.source.clone())),
+        Cell::Float(attestation.confidence.map(f64::from)),
+        Cell::UInt(Some(attestation.revision)),
+        Cell::Timestamp(Some(attestation.created_at)),
+    ];
+    match &options.attributes {
+        AttributeEncoding::Json => tail.push(Cell::Text(
+            (!attestation.attributes.is_empty()).then(|| {
+                let sorted: std::collections::BTreeMap<_, _> =
+                    attestation.attributes.iter().collect();
+                serde_json::to_string(&sorted).expect("JSON values always serialize")
+            }),
+        )),
+        AttributeEncoding::Columns { columns } => tail.extend(
+            columns
+                .iter()
+                .map(|c| attribute_cell(attestation.attributes.get(&c.key), c.kind)),
+        ),
+    }
+
+    let id = Cell::Text(Some(attestation.id.clone()));
+    if options.arrays != ArrayEncoding::Explode {
+        let mut row = vec![
+            id,
+            slot(&attestation.subjects),
+            slot(&attestation.predicates),
+            slot(&attestation.contexts),
+            slot(&attestation.actors),
+        ];
+        row.extend(tail);
+        return vec![row];
+    }
+
+    let claims = expand_cartesian(&[ExpandAttestation {
+        id: attestation.id.clone(),
+        subjects: attestation.subjects.clone(),
+        predicates: attestation.predicates.clone(),
+        contexts: attestation.contexts.clone(),
+        actors: attestation.actors.clone(),
+        timestamp_ms: attestation.timestamp,
+        confidence: attestation.confidence,
+    }]);
+    claims
+        .into_iter()
+        .map(|claim| {
+            let mut row = vec![
+                id.clone(),
+                Cell::Text(Some(claim.subject)),
+                Cell::Text(Some(claim.predicate)),
+                Cell::Text(Some(claim.context)),
+                Cell::Text(Some(claim.actor)),
+            ];
+            row.extend(tail.iter().cloned());
+            row
+        })
+        .collect()
+}
+
+/// Counts what reaches the underlying writer.
+struct CountingWriter<W> {
+    inner: W,
+    bytes: u64,
+}
+
+impl<W: Write> Write for CountingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let n = self.inner.write(buf)?;
+        self.bytes += n as u64;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+fn io_error(e: std::io::Error) -> StoreError {
+    SqliteError::from(e).into()
+}
+
+/// Write `field` with RFC 4180 quoting.
+fn write_csv_field(out: &mut impl Write, field: &str) -> std::io::Result<()> {
+    if field.contains([',', '"', '\r', '\n']) {
+        write!(out, "\"{}\"", field.replace('"', "\"\""))
+    } else {
+        out.write_all(field.as_bytes())
+    }
+}
+
+fn write_csv_row<'a>(
+    out: &mut impl Write,
+    fields: impl IntoIterator<Item = &'a str>,
+) -> std::io::Result<()> {
+    for (i, field) in fields.into_iter().enumerate() {
+        if i > 0 {
+            out.write_all(b",")?;
+        }
+        write_csv_field(out, field)?;
+    }
+    out.write_all(b"\n")
+}
+
+fn csv_text(cell: &Cell) -> String {
+    match cell {
+        Cell::Text(v) => v.clone().unwrap_or_default(),
+        Cell::Timestamp(v) => v
+            .and_then(DateTime::from_timestamp_millis)
+            .map(|d| d.to_rfc3339_opts(SecondsFormat::Millis, true))
+            .unwrap_or_default(),
+        Cell::Float(v) => v.map(|f| f.to_string()).unwrap_or_default(),
+        Cell::UInt(v) => v.map(|n| n.to_string()).unwrap_or_default(),
+        Cell::Bool(v) => v.map(|b| b.to_string()).unwrap_or_default(),
+    }
+}
+
+impl SqliteStore {
+    /// Export the attestations matching `filter` to `writer` in `format`,
+    /// laid out by `options`. Rows come in query order (newest first).
+    pub fn export_query<W: Write + Send>(
+        &self,
+        filter: &AxFilter,
+        format: ExportFormat,
+        writer: W,
+        options: &ExportOptions,
+    ) -> StoreResult<ExportSummary> {
+        let started = Instant::now();
+        let columns = columns(options);
+        let mut out = CountingWriter {
+            inner: writer,
+            bytes: 0,
+        };
+
+        let rows = match format {
+            ExportFormat::Csv => {
+                let mut buffered = BufWriter::new(&mut out);
+                write_csv_row(&mut buffered, columns.iter().map(|(name, _)| name.as_str()))
+                    .map_err(io_error)?;
+                let mut count = 0;
+                self.for_each_match(filter, |attestation| {
+                    for row in rows(attestation, options) {
+                        let fields: Vec<String> = row.iter().map(csv_text).collect();
+                        write_csv_row(&mut buffered, fields.iter().map(String::as_str))
+                            .map_err(io_error)?;
+                        count += 1;
+                    }
+                    Ok(())
+                })?;
+                buffered.flush().map_err(io_error)?;
+                count
+            }
+            #[cfg(feature = "parquet")]
+            ExportFormat::Parquet => {
+                parquet_export::write(self, filter, &columns, options, &mut out)?
+            }
+            #[cfg(not(feature = "parquet"))]
+            ExportFormat::Parquet => {
+                return Err(StoreError::Backend(
+                    "parquet export requires qntx-sqlite's `parquet` feature".into(),
+                ))
+            }
+        };
+        out.flush().map_err(io_error)?;
+
+        Ok(ExportSummary {
+            rows,
+            bytes: out.bytes,
+            duration_ms: started.elapsed().as_millis() as u64,
+        })
+    }
+
+    /// [`export_query`](Self::export_query) into a new file at `path`,
+    /// replacing any file there.
+    pub fn export_query_to_file(
+        &self,
+        filter: &AxFilter,
+        format: ExportFormat,
+        path: impl AsRef<std::path::Path>,
+        options: &ExportOptions,
+    ) -> StoreResult<ExportSummary> {
+        let file = std::fs::File::create(path).map_err(io_error)?;
+        self.export_query(filter, format, file, options)
+    }
+
+    /// Call `f` with each attestation matching `filter`, in query order,
+    /// decoding rows as they are read.
+    fn for_each_match(
+        &self,
+        filter: &AxFilter,
+        mut f: impl FnMut(&Attestation) -> StoreResult<()>,
+    ) -> StoreResult<()> {
+        let post_filter = needs_post_filter(filter);
+        if post_filter && (filter.limit.is_some() || filter.over.is_some()) {
+            return self.query(filter)?.attestations.iter().try_for_each(f);
+        }
+
+        let (sql, params) = build_query_sql(filter, &self.namespace);
+        let mut stmt = self.conn.prepare(&sql).map_err(SqliteError::from)?;
+        let param_refs: Vec<&dyn rusqlite::ToSql> =
+            params.iter().map(|p| p as &dyn rusqlite::ToSql).collect();
+        let rows = stmt
+            .query_map(&param_refs[..], crate::json::read_attestation_row)
+            .map_err(SqliteError::from)?;
+        for row in rows {
+            let Some(attestation) = self.decode_row(row.map_err(SqliteError::from)?)? else {
+                continue;
+            };
+            if post_filter && !filter.matches_attributes(&attestation.attributes) {
+                continue;
+            }
+            f(&attestation)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "parquet")]
+mod parquet_export {
+    use std::io::Write;
+    use std::sync::Arc;
+
+    use arrow_array::builder::{
+        BooleanBuilder, Float64Builder, StringBuilder, TimestampMillisecondBuilder, UInt64Builder,
+    };
+    use arrow_array::{ArrayRef, RecordBatch};
+    use arrow_schema::{DataType, Field, Schema, TimeUnit};
+    use parquet::arrow::ArrowWriter;
+    use parquet::basic::Compression;
+    use parquet::file::properties::WriterProperties;
+
+    use super::{
+        rows, Cell, ColumnType, ExportOptions, StoreError, StoreResult, DEFAULT_EXPORT_BATCH,
+    };
+    use crate::store::SqliteStore;
+    use qntx_core::AxFilter;
+
+    enum ColumnBuilder {
+        Text(StringBuilder),
+        Timestamp(TimestampMillisecondBuilder),
+        Float(Float64Builder),
+        UInt(UInt64Builder),
+        Bool(BooleanBuilder),
+    }
+
+    impl ColumnBuilder {
+        fn new(kind: ColumnType) -> Self {
+            match kind {
+                ColumnType::Text => ColumnBuilder::Text(StringBuilder::new()),
+                ColumnType::Timestamp => ColumnBuilder::Timestamp(
+                    TimestampMillisecondBuilder::new().with_timezone("UTC"),
+                ),
+                ColumnType::Float => ColumnBuilder::Float(Float64Builder::new()),
+                ColumnType::UInt => ColumnBuilder::UInt(UInt64Builder::new()),
+                ColumnType::Bool => ColumnBuilder::Bool(BooleanBuilder::new()),
+            }
+        }
+
+        fn append(&mut self, cell: Cell) {
+            match (self, cell) {
+                (ColumnBuilder::Text(b), Cell::Text(v)) => b.append_option(v),
+                (ColumnBuilder::Timestamp(b), Cell::Timestamp(v)) => b.append_option(v),
+                (ColumnBuilder::Float(b), Cell::Float(v)) => b.append_option(v),
+                (ColumnBuilder::UInt(b), Cell::UInt(v)) => b.append_option(v),
+                (ColumnBuilder::Bool(b), Cell::Bool(v)) => b.append_option(v),
+                _ => unreachable!("cells follow the column types"),
+            }
+        }
+
+        fn finish(&mut self) -> ArrayRef {
+            match self {
+                ColumnBuilder::Text(b) => Arc::new(b.finish()),
+                ColumnBuilder::Timestamp(b) => Arc::new(b.finish()),
+                ColumnBuilder::Float(b) => Arc::new(b.finish()),
+                ColumnBuilder::UInt(b) => Arc::new(b.finish()),
+                ColumnBuilder::Bool(b) => Arc::new(b.finish()),
+            }
+        }
+    }
+
+    fn data_type(kind: ColumnType) -> DataType {
+        match kind {
+            ColumnType::Text => DataType::Utf8,
+            ColumnType::Timestamp => DataType::Timestamp(TimeUnit::Millisecond, Some("UTC".into())),
+            ColumnType::Float => DataType::Float64,
+            ColumnType::UInt => DataType::UInt64,
+            ColumnType::Bool => DataType::Boolean,
+        }
+    }
+
+    fn parquet_error(e: impl std::fmt::Display) -> StoreError {
+        StoreError::Backend(format!("parquet export: {}", e))
+    }
+
+    pub(super) fn write<W: Write + Send>(
+        store: &SqliteStore,
+        filter: &AxFilter,
+        columns: &[(String, ColumnType)],
+        options: &ExportOptions,
+        out: W,
+    ) -> StoreResult<u64> {
+        let schema = Arc::new(Schema::new(
+            columns
+                .iter()
+                .map(|(name, kind)| Field::new(name, data_type(*kind), true))
+                .collect::<Vec<_>>(),
+        ));
+        let batch_rows = match options.batch_rows {
+            0 => DEFAULT_EXPORT_BATCH,
+            n => n,
+        };
+        let properties = WriterProperties::builder()
+            .set_compression(Compression::SNAPPY)
+            .set_max_row_group_row_count(Some(batch_rows))
+            .build();
+        let mut writer =
+            ArrowWriter::try_new(out, schema.clone(), Some(properties)).map_err(parquet_error)?;
+
+        let mut builders: Vec<ColumnBuilder> = columns
+            .iter()
+            .map(|(_, kind)| ColumnBuilder::new(*kind))
+            .collect();
+        let mut pending = 0;
+        let mut total = 0;
+        let flush =
+            |builders: &mut Vec<ColumnBuilder>, writer: &mut ArrowWriter<W>| -> StoreResult<()> {
+                let arrays = builders.iter_mut().map(ColumnBuilder::finish).collect();
+                let batch = RecordBatch::try_new(schema.clone(), arrays).map_err(parquet_error)?;
+                writer.write(&batch).map_err(parquet_error)
+            };
+
+        store.for_each_match(filter, |attestation| {
+            for row in rows(attestation, options) {
+                for (builder, cell) in builders.iter_mut().zip(row) {
+                    builder.append(cell);
+                }
+                pending += 1;
+                total += 1;
+                if pending == batch_rows {
+                    flush(&mut builders, &mut writer)?;
+                    pending = 0;
+                }
+            }
+            Ok(())
+        })?;
+        if pending > 0 {
+            flush(&mut builders, &mut writer)?;
+        }
+        writer.close().map_err(parquet_error)?;
+        Ok(total)
+    }
+}
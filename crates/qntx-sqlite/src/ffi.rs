@@ -9,17 +9,22 @@
 //! - String results are owned by caller and must be freed with `storage_string_free()`
 //! - JSON strings passed to functions are copied, caller retains ownership
 
+use std::ffi::CStr;
 use std::os::raw::c_char;
 use std::path::Path;
 use std::ptr;
 
-use qntx_core::storage::AttestationStore;
+use qntx_core::storage::{
+    AttestationStore, DeleteCoordinator, DeleteOptions, StorageErrorKind, StoreError,
+};
 use qntx_ffi_common::{
     cstr_to_str, cstring_new_or_empty, free_boxed, free_cstring, vec_into_raw, FfiResult,
 };
 use qntx_proto::proto_convert;
 use rusqlite::OptionalExtension;
 
+use crate::profile::OpenOptions;
+use crate::snapshot::{ReadOnlyStore, SnapshotInfo};
 use crate::store::ReadConn;
 use crate::SqliteStore;
 
@@ -27,11 +32,15 @@ use crate::SqliteStore;
 const MAX_ID_LENGTH: usize = 256;
 const MAX_JSON_LENGTH: usize = 1_000_000; // 1MB
 
+/// Limit of a query whose filter pointer is NULL
+const DEFAULT_QUERY_LIMIT: usize = 1000;
+
 /// C-compatible result wrapper
 #[repr(C)]
 pub struct StorageResultC {
     pub success: bool,
     pub error_msg: *mut c_char,
+    pub error_code: *const c_char, // static StorageErrorKind code, NULL if not a store error
 }
 
 /// C-compatible attestation result (for get operations)
@@ -40,6 +49,47 @@ pub struct AttestationResultC {
     pub success: bool,
     pub error_msg: *mut c_char,
     pub attestation_json: *mut c_char, // NULL if not found
+    pub error_code: *const c_char,     // static StorageErrorKind code, NULL if not a store error
+    pub corrupt_count: usize,          // rows skipped by tolerant decoding (queries only)
+}
+
+/// Static NUL-terminated code for a storage error kind. Never freed by the caller.
+fn error_code_ptr(kind: StorageErrorKind) -> *const c_char {
+    let code: &'static CStr = match kind {
+        StorageErrorKind::NotFound => c"not_found",
+        StorageErrorKind::Duplicate => c"duplicate",
+        StorageErrorKind::QuotaExceeded => c"quota_exceeded",
+        StorageErrorKind::Corruption => c"corruption",
+        StorageErrorKind::Io => c"io",
+        StorageErrorKind::InvalidInput => c"invalid_input",
+        StorageErrorKind::Backend => c"backend",
+    };
+    code.as_ptr()
+}
+
+/// Parse the `filter_json` argument of the query functions. NULL is an empty
+/// filter capped at [`DEFAULT_QUERY_LIMIT`]; malformed JSON is an
+/// `invalid_input` error.
+fn parse_filter(filter_json: *const c_char) -> Result<qntx_core::AxFilter, AttestationResultC> {
+    if filter_json.is_null() {
+        return Ok(qntx_core::AxFilter {
+            limit: Some(DEFAULT_QUERY_LIMIT),
+            ..Default::default()
+        });
+    }
+    let filter_str = unsafe { cstr_to_str(filter_json) }.map_err(AttestationResultC::error)?;
+    if filter_str.len() > MAX_JSON_LENGTH {
+        return Err(AttestationResultC::error(
+            "filter JSON exceeds maximum length",
+        ));
+    }
+    let filter: qntx_core::AxFilter = serde_json::from_str(filter_str).map_err(|e| {
+        AttestationResultC::store_error(&StoreError::Query(format!("invalid filter JSON: {}", e)))
+    })?;
+    filter
+        .validate_aliases_expanded()
+        .map_err(|e| AttestationResultC::store_error(&StoreError::Query(e)))?;
+    Ok(filter)
 }
 
 /// C-compatible string array result (for ids operation)
@@ -64,6 +114,14 @@ impl StorageResultC {
         Self {
             success: true,
             error_msg: ptr::null_mut(),
+            error_code: ptr::null(),
+        }
+    }
+
+    fn store_error(err: &StoreError) -> Self {
+        Self {
+            error_code: error_code_ptr(err.kind()),
+            ..Self::error(&err.to_string())
         }
     }
 }
@@ -75,6 +133,7 @@ impl FfiResult for StorageResultC {
         Self {
             success: false,
             error_msg,
+            error_code: ptr::null(),
         }
     }
 }
@@ -85,6 +144,15 @@ impl AttestationResultC {
             success: true,
             error_msg: ptr::null_mut(),
             attestation_json: cstring_new_or_empty(&json),
+            error_code: ptr::null(),
+            corrupt_count: 0,
+        }
+    }
+
+    fn with_corrupt_count(self, corrupt_count: usize) -> Self {
+        Self {
+            corrupt_count,
+            ..self
         }
     }
 
@@ -93,6 +161,15 @@ impl AttestationResultC {
             success: true,
             error_msg: ptr::null_mut(),
             attestation_json: ptr::null_mut(),
+            error_code: ptr::null(),
+            corrupt_count: 0,
+        }
+    }
+
+    fn store_error(err: &StoreError) -> Self {
+        Self {
+            error_code: error_code_ptr(err.kind()),
+            ..Self::error(&err.to_string())
         }
     }
 }
@@ -105,6 +182,8 @@ impl FfiResult for AttestationResultC {
             success: false,
             error_msg,
             attestation_json: ptr::null_mut(),
+            error_code: ptr::null(),
+            corrupt_count: 0,
         }
     }
 }
@@ -197,6 +276,89 @@ pub extern "C" fn storage_new_file(path: *const c_char) -> *mut SqliteStore {
     }
 }
 
+/// Open a file-backed store tuned by `options_json`, an `OpenOptions` object:
+/// `{"profile":"durable"|"balanced"|"fast","mmap_size":N,"cache_size_kib":N,"statement_cache":N}`,
+/// every field optional. NULL or empty options mean the defaults (durable, no
+/// mmap), as `storage_new_file`. Returns NULL on failure, including invalid options.
+#[no_mangle]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn storage_new_file_with_options(
+    path: *const c_char,
+    options_json: *const c_char,
+) -> *mut SqliteStore {
+    let path_str = match unsafe { cstr_to_str(path) } {
+        Ok(s) => s,
+        Err(e) => {
+            eprintln!("qntx-sqlite: invalid path string: {}", e);
+            return ptr::null_mut();
+        }
+    };
+    let options = if options_json.is_null() {
+        OpenOptions::default()
+    } else {
+        let parsed = unsafe { cstr_to_str(options_json) }
+            .map_err(String::from)
+            .and_then(|json| {
+                if json.is_empty() {
+                    Ok(OpenOptions::default())
+                } else {
+                    serde_json::from_str(json).map_err(|e| format!("invalid open options: {}", e))
+                }
+            });
+        match parsed {
+            Ok(options) => options,
+            Err(e) => {
+                eprintln!("qntx-sqlite: {}", e);
+                return ptr::null_mut();
+            }
+        }
+    };
+
+    match SqliteStore::open_with(Path::new(path_str), &options) {
+        Ok(store) => Box::into_raw(Box::new(store)),
+        Err(e) => {
+            eprintln!("qntx-sqlite: failed to open {}: {}", path_str, e);
+            ptr::null_mut()
+        }
+    }
+}
+
+/// Open a file-backed store bound to `namespace`. Every operation through the
+/// returned pointer (and read connections opened from it) only sees that
+/// namespace. Returns NULL on failure, including an empty namespace.
+#[no_mangle]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn storage_new_file_scoped(
+    path: *const c_char,
+    namespace: *const c_char,
+) -> *mut SqliteStore {
+    let path_str = match unsafe { cstr_to_str(path) } {
+        Ok(s) => s,
+        Err(e) => {
+            eprintln!("qntx-sqlite: invalid path string: {}", e);
+            return ptr::null_mut();
+        }
+    };
+    let namespace_str = match unsafe { cstr_to_str(namespace) } {
+        Ok(s) => s,
+        Err(e) => {
+            eprintln!("qntx-sqlite: invalid namespace string: {}", e);
+            return ptr::null_mut();
+        }
+    };
+
+    match SqliteStore::open_scoped(Path::new(path_str), namespace_str) {
+        Ok(store) => Box::into_raw(Box::new(store)),
+        Err(e) => {
+            eprintln!(
+                "qntx-sqlite: failed to open {} (namespace {}): {}",
+                path_str, namespace_str, e
+            );
+            ptr::null_mut()
+        }
+    }
+}
+
 #[no_mangle]
 #[allow(clippy::not_unsafe_ptr_arg_deref)]
 pub extern "C" fn storage_free(store: *mut SqliteStore) {
@@ -207,8 +369,8 @@ pub extern "C" fn storage_free(store: *mut SqliteStore) {
 // Read Connection (separate pointer, independent of SqliteStore)
 // ============================================================================
 
-/// Open a read-only connection from a file-backed store.
-/// Returns NULL for in-memory stores or on failure.
+/// Open a read-only connection from a file-backed store, scoped to the
+/// store's namespace. Returns NULL for in-memory stores or on failure.
 /// The returned pointer is independent of the store — Go can access it
 /// without creating overlapping Rust references.
 #[no_mangle]
@@ -234,6 +396,157 @@ pub extern "C" fn read_conn_free(rc: *mut ReadConn) {
     unsafe { free_boxed(rc) };
 }
 
+// ============================================================================
+// Read-only Snapshots (copied files with live refresh)
+// ============================================================================
+
+/// JSON for a snapshot swap: `{"path","rows","schema_version","file_len"}`.
+fn snapshot_info_json(info: &SnapshotInfo) -> String {
+    serde_json::json!({
+        "path": info.path.to_string_lossy(),
+        "rows": info.rows,
+        "schema_version": info.schema_version,
+        "file_len": info.file_len,
+    })
+    .to_string()
+}
+
+/// Open a copied database file as a validated read-only snapshot.
+/// Returns NULL when the file is truncated, corrupt or not a QNTX database.
+/// The handle has no write entry points; free it with `read_only_free()`.
+#[no_mangle]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn storage_open_read_only(path: *const c_char) -> *mut ReadOnlyStore {
+    let path_str = match unsafe { cstr_to_str(path) } {
+        Ok(s) => s,
+        Err(e) => {
+            eprintln!("qntx-sqlite: invalid path string: {}", e);
+            return ptr::null_mut();
+        }
+    };
+
+    match SqliteStore::open_read_only(Path::new(path_str)) {
+        Ok(store) => Box::into_raw(Box::new(store)),
+        Err(e) => {
+            eprintln!("qntx-sqlite: failed to open snapshot {}: {}", path_str, e);
+            ptr::null_mut()
+        }
+    }
+}
+
+/// Free a read-only snapshot store.
+#[no_mangle]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn read_only_free(store: *mut ReadOnlyStore) {
+    unsafe { free_boxed(store) };
+}
+
+/// Validate and swap to a newly copied file. `path` NULL reopens the current
+/// file; otherwise the store serves and refreshes from `path` from now on.
+/// On failure the previous snapshot keeps serving.
+///
+/// Output JSON: `{"path":"...","rows":N,"schema_version":"...","file_len":N}`
+#[no_mangle]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn read_only_refresh(
+    store: *const ReadOnlyStore,
+    path: *const c_char,
+) -> AttestationResultC {
+    if store.is_null() {
+        return AttestationResultC::error("null store pointer");
+    }
+    let store = unsafe { &*store };
+    let result = if path.is_null() {
+        store.refresh()
+    } else {
+        match unsafe { cstr_to_str(path) } {
+            Ok(p) => store.refresh_from(Path::new(p)),
+            Err(e) => return AttestationResultC::error(e),
+        }
+    };
+    match result {
+        Ok(info) => AttestationResultC::ok(snapshot_info_json(&info)),
+        Err(e) => AttestationResultC::store_error(&StoreError::from(e)),
+    }
+}
+
+/// Get an attestation by ID from the current snapshot.
+#[no_mangle]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn read_only_get(
+    store: *const ReadOnlyStore,
+    id: *const c_char,
+) -> AttestationResultC {
+    if store.is_null() {
+        return AttestationResultC::error("null store pointer");
+    }
+    let id_str = match unsafe { cstr_to_str(id) } {
+        Ok(s) => s,
+        Err(e) => return AttestationResultC::error(e),
+    };
+    if id_str.len() > MAX_ID_LENGTH {
+        return AttestationResultC::error("ID exceeds maximum length");
+    }
+    let store = unsafe { &*store };
+    match store.get(id_str) {
+        Ok(Some(attestation)) => {
+            let proto = proto_convert::to_proto(attestation);
+            match serde_json::to_string(&proto) {
+                Ok(json) => AttestationResultC::ok(json),
+                Err(e) => AttestationResultC::error(&format!("failed to serialize: {}", e)),
+            }
+        }
+        Ok(None) => AttestationResultC::not_found(),
+        Err(e) => AttestationResultC::store_error(&e),
+    }
+}
+
+/// Query attestations from the current snapshot.
+#[no_mangle]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn read_only_query(
+    store: *const ReadOnlyStore,
+    filter_json: *const c_char,
+) -> AttestationResultC {
+    if store.is_null() {
+        return AttestationResultC::error("null store pointer");
+    }
+    let filter = match parse_filter(filter_json) {
+        Ok(f) => f,
+        Err(e) => return e,
+    };
+    let store = unsafe { &*store };
+
+    use qntx_core::storage::QueryStore;
+    let result = match store.query(&filter) {
+        Ok(r) => r,
+        Err(e) => return AttestationResultC::store_error(&e),
+    };
+    let proto_attestations: Vec<qntx_proto::Attestation> = result
+        .attestations
+        .into_iter()
+        .map(proto_convert::to_proto)
+        .collect();
+    match serde_json::to_string(&proto_attestations) {
+        Ok(json) => AttestationResultC::ok(json),
+        Err(e) => AttestationResultC::error(&format!("failed to serialize results: {}", e)),
+    }
+}
+
+/// Count attestations in the current snapshot.
+#[no_mangle]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn read_only_count(store: *const ReadOnlyStore) -> CountResultC {
+    if store.is_null() {
+        return CountResultC::error("null store pointer");
+    }
+    let store = unsafe { &*store };
+    match store.count() {
+        Ok(count) => CountResultC::ok(count),
+        Err(e) => CountResultC::error(&format!("{}", e)),
+    }
+}
+
 /// WAL checkpoint result
 #[repr(C)]
 pub struct CheckpointResultC {
@@ -403,28 +716,17 @@ pub extern "C" fn read_conn_get(rc: *const ReadConn, id: *const c_char) -> Attes
         return AttestationResultC::error("ID exceeds maximum length");
     }
     let rc = unsafe { &*rc };
-    let mut stmt = match rc.conn.prepare(
-        "SELECT id, subjects, predicates, contexts, actors, timestamp, source, attributes, created_at, signature, signer_did FROM attestations WHERE id = ?",
+    let mut stmt = match rc.conn.prepare_cached(
+        "SELECT id, subjects, predicates, contexts, actors, timestamp, source, attributes, created_at, signature, signer_did, revision, confidence FROM attestations WHERE namespace = ? AND id = ?",
     ) {
         Ok(s) => s,
         Err(e) => return AttestationResultC::error(&format!("{}", e)),
     };
     let result = match stmt
-        .query_row([id_str], |row| {
-            Ok((
-                row.get::<_, String>(0)?,
-                row.get::<_, String>(1)?,
-                row.get::<_, String>(2)?,
-                row.get::<_, String>(3)?,
-                row.get::<_, String>(4)?,
-                row.get::<_, String>(5)?,
-                row.get::<_, String>(6)?,
-                row.get::<_, Option<String>>(7)?,
-                row.get::<_, String>(8)?,
-                row.get::<_, Option<Vec<u8>>>(9)?,
-                row.get::<_, Option<String>>(10)?,
-            ))
-        })
+        .query_row(
+            [rc.namespace.as_str(), id_str],
+            crate::json::read_attestation_row,
+        )
         .optional()
     {
         Ok(r) => r,
@@ -457,21 +759,23 @@ pub extern "C" fn read_conn_exists(rc: *const ReadConn, id: *const c_char) -> St
         Err(e) => return StorageResultC::error(e),
     };
     let rc = unsafe { &*rc };
-    match rc
+    let found = rc
         .conn
-        .query_row("SELECT 1 FROM attestations WHERE id = ?", [id_str], |_| {
-            Ok(())
-        }) {
-        Ok(()) => StorageResultC::ok(),
-        Err(rusqlite::Error::QueryReturnedNoRows) => StorageResultC {
+        .prepare_cached("SELECT 1 FROM attestations WHERE namespace = ? AND id = ?")
+        .and_then(|mut stmt| stmt.exists([rc.namespace.as_str(), id_str]));
+    match found {
+        Ok(true) => StorageResultC::ok(),
+        Ok(false) => StorageResultC {
             success: false,
             error_msg: ptr::null_mut(),
+            error_code: ptr::null(),
         },
         Err(e) => StorageResultC::error(&format!("{}", e)),
     }
 }
 
-/// Query attestations through the read connection.
+/// Query attestations through the read connection. Same filter and result
+/// shapes as [`storage_query`]; the rows and summary share one snapshot.
 #[no_mangle]
 #[allow(clippy::not_unsafe_ptr_arg_deref)]
 pub extern "C" fn read_conn_query(
@@ -481,24 +785,30 @@ pub extern "C" fn read_conn_query(
     if rc.is_null() {
         return AttestationResultC::error("null read connection");
     }
-    let filter_str = match unsafe { cstr_to_str(filter_json) } {
-        Ok(s) => s,
-        Err(e) => return AttestationResultC::error(e),
-    };
-    if filter_str.len() > MAX_JSON_LENGTH {
-        return AttestationResultC::error("filter JSON exceeds maximum length");
-    }
-    let rc = unsafe { &*rc };
-    let filter: qntx_core::AxFilter = match serde_json::from_str(filter_str) {
+    let filter = match parse_filter(filter_json) {
         Ok(f) => f,
-        Err(e) => return AttestationResultC::error(&format!("invalid filter JSON: {}", e)),
+        Err(e) => return e,
     };
+    let rc = unsafe { &*rc };
 
     // Build the same query as QueryStore::query but using rc.conn
-    use crate::store::build_query_sql;
-    let (sql, params) = build_query_sql(&filter);
+    use crate::store::{
+        build_query_sql, build_summary, needs_post_filter, post_filter, query_matching_summary,
+    };
+    let (sql, params) = build_query_sql(&filter, &rc.namespace);
+
+    // The rows and the SQL-side matching summary must see one snapshot
+    let sql_summary = filter.include_summary && !needs_post_filter(&filter);
+    let tx = if sql_summary && rc.conn.is_autocommit() {
+        match rc.conn.unchecked_transaction() {
+            Ok(tx) => Some(tx),
+            Err(e) => return AttestationResultC::error(&format!("{}", e)),
+        }
+    } else {
+        None
+    };
 
-    let mut stmt = match rc.conn.prepare(&sql) {
+    let mut stmt = match rc.conn.prepare_cached(&sql) {
         Ok(s) => s,
         Err(e) => return AttestationResultC::error(&format!("{}", e)),
     };
@@ -506,26 +816,14 @@ pub extern "C" fn read_conn_query(
     let param_refs: Vec<&dyn rusqlite::ToSql> =
         params.iter().map(|p| p as &dyn rusqlite::ToSql).collect();
 
-    let rows = match stmt.query_map(&param_refs[..], |row| {
-        Ok((
-            row.get::<_, String>(0)?,
-            row.get::<_, String>(1)?,
-            row.get::<_, String>(2)?,
-            row.get::<_, String>(3)?,
-            row.get::<_, String>(4)?,
-            row.get::<_, String>(5)?,
-            row.get::<_, String>(6)?,
-            row.get::<_, Option<String>>(7)?,
-            row.get::<_, String>(8)?,
-            row.get::<_, Option<Vec<u8>>>(9)?,
-            row.get::<_, Option<String>>(10)?,
-        ))
-    }) {
+    let rows = match stmt.query_map(&param_refs[..], crate::json::read_attestation_row) {
         Ok(r) => r,
         Err(e) => return AttestationResultC::error(&format!("{}", e)),
     };
 
+    // Read connections have no quarantine; undecodable rows are skipped and counted.
     let mut attestations = Vec::new();
+    let mut corrupt_count = 0;
     for row_result in rows {
         let row_data = match row_result {
             Ok(r) => r,
@@ -533,17 +831,51 @@ pub extern "C" fn read_conn_query(
         };
         match SqliteStore::row_to_attestation(row_data) {
             Ok(a) => attestations.push(a),
+            Err(_) => corrupt_count += 1,
+        }
+    }
+    drop(stmt);
+    let mut matching = None;
+    if needs_post_filter(&filter) {
+        (attestations, matching) = post_filter(&filter, attestations);
+    } else if sql_summary {
+        match query_matching_summary(&rc.conn, &filter, &rc.namespace) {
+            Ok(m) => matching = Some(m),
             Err(e) => return AttestationResultC::error(&format!("{}", e)),
         }
     }
+    if let Some(tx) = tx {
+        if let Err(e) = tx.finish() {
+            return AttestationResultC::error(&format!("{}", e));
+        }
+    }
+
+    let mut summary = build_summary(&attestations);
+    summary.matching = matching;
+    match query_result_json(&filter, attestations, &summary) {
+        Ok(json) => AttestationResultC::ok(json).with_corrupt_count(corrupt_count),
+        Err(e) => AttestationResultC::error(&format!("failed to serialize results: {}", e)),
+    }
+}
 
+/// JSON array of proto attestations, or `{"attestations": [...], "summary": {...}}`
+/// when the filter sets `include_summary`.
+fn query_result_json(
+    filter: &qntx_core::AxFilter,
+    attestations: Vec<qntx_core::Attestation>,
+    summary: &qntx_core::AxSummary,
+) -> serde_json::Result<String> {
     let proto_attestations: Vec<qntx_proto::Attestation> = attestations
         .into_iter()
         .map(proto_convert::to_proto)
         .collect();
-    match serde_json::to_string(&proto_attestations) {
-        Ok(json) => AttestationResultC::ok(json),
-        Err(e) => AttestationResultC::error(&format!("failed to serialize results: {}", e)),
+    if filter.include_summary {
+        serde_json::to_string(&serde_json::json!({
+            "attestations": proto_attestations,
+            "summary": summary,
+        }))
+    } else {
+        serde_json::to_string(&proto_attestations)
     }
 }
 
@@ -557,7 +889,8 @@ pub extern "C" fn read_conn_ids(rc: *const ReadConn) -> StringArrayResultC {
     let rc = unsafe { &*rc };
     match query_distinct(
         &rc.conn,
-        "SELECT id FROM attestations ORDER BY created_at DESC",
+        "SELECT id FROM attestations WHERE namespace = ?1 ORDER BY created_at DESC",
+        [&rc.namespace],
     ) {
         Ok(ids) => StringArrayResultC::ok(ids),
         Err(e) => StringArrayResultC::error(&e),
@@ -572,11 +905,11 @@ pub extern "C" fn read_conn_count(rc: *const ReadConn) -> CountResultC {
         return CountResultC::error("null read connection");
     }
     let rc = unsafe { &*rc };
-    match rc
-        .conn
-        .query_row("SELECT COUNT(*) FROM attestations", [], |row| {
-            row.get::<_, usize>(0)
-        }) {
+    match rc.conn.query_row(
+        "SELECT COUNT(*) FROM attestations WHERE namespace = ?",
+        [&rc.namespace],
+        |row| row.get::<_, usize>(0),
+    ) {
         Ok(count) => CountResultC::ok(count),
         Err(e) => CountResultC::error(&format!("{}", e)),
     }
@@ -592,7 +925,8 @@ pub extern "C" fn read_conn_predicates(rc: *const ReadConn) -> StringArrayResult
     let rc = unsafe { &*rc };
     match query_distinct(
         &rc.conn,
-        "SELECT DISTINCT predicate FROM attestation_predicates ORDER BY predicate",
+        "SELECT DISTINCT predicate FROM attestation_predicates WHERE namespace = ?1 ORDER BY predicate",
+        [&rc.namespace],
     ) {
         Ok(values) => StringArrayResultC::ok(values),
         Err(e) => StringArrayResultC::error(&e.to_string()),
@@ -609,7 +943,8 @@ pub extern "C" fn read_conn_contexts(rc: *const ReadConn) -> StringArrayResultC
     let rc = unsafe { &*rc };
     match query_distinct(
         &rc.conn,
-        "SELECT DISTINCT context FROM attestation_contexts ORDER BY context",
+        "SELECT DISTINCT context FROM attestation_contexts WHERE namespace = ?1 ORDER BY context",
+        [&rc.namespace],
     ) {
         Ok(values) => StringArrayResultC::ok(values),
         Err(e) => StringArrayResultC::error(&e.to_string()),
@@ -626,29 +961,36 @@ pub extern "C" fn read_conn_stats(rc: *const ReadConn) -> AttestationResultC {
     let rc = unsafe { &*rc };
     let count = |sql: &str| -> Result<usize, String> {
         rc.conn
-            .query_row(sql, [], |row| row.get::<_, usize>(0))
+            .query_row(sql, [&rc.namespace], |row| row.get::<_, usize>(0))
             .map_err(|e| format!("{}", e))
     };
-    let total = match count("SELECT COUNT(*) FROM attestations") {
-        Ok(v) => v,
-        Err(e) => return AttestationResultC::error(&e),
-    };
-    let subjects = match count("SELECT COUNT(DISTINCT subject) FROM attestation_subjects") {
-        Ok(v) => v,
-        Err(e) => return AttestationResultC::error(&e),
-    };
-    let predicates = match count("SELECT COUNT(DISTINCT predicate) FROM attestation_predicates") {
-        Ok(v) => v,
-        Err(e) => return AttestationResultC::error(&e),
-    };
-    let contexts = match count("SELECT COUNT(DISTINCT context) FROM attestation_contexts") {
+    let total = match count("SELECT COUNT(*) FROM attestations WHERE namespace = ?") {
         Ok(v) => v,
         Err(e) => return AttestationResultC::error(&e),
     };
-    let actors = match count("SELECT COUNT(DISTINCT actor) FROM attestation_actors") {
+    let subjects =
+        match count("SELECT COUNT(DISTINCT subject) FROM attestation_subjects WHERE namespace = ?")
+        {
+            Ok(v) => v,
+            Err(e) => return AttestationResultC::error(&e),
+        };
+    let predicates = match count(
+        "SELECT COUNT(DISTINCT predicate) FROM attestation_predicates WHERE namespace = ?",
+    ) {
         Ok(v) => v,
         Err(e) => return AttestationResultC::error(&e),
     };
+    let contexts =
+        match count("SELECT COUNT(DISTINCT context) FROM attestation_contexts WHERE namespace = ?")
+        {
+            Ok(v) => v,
+            Err(e) => return AttestationResultC::error(&e),
+        };
+    let actors =
+        match count("SELECT COUNT(DISTINCT actor) FROM attestation_actors WHERE namespace = ?") {
+            Ok(v) => v,
+            Err(e) => return AttestationResultC::error(&e),
+        };
     let json = format!(
         r#"{{"total_attestations":{},"unique_subjects":{},"unique_predicates":{},"unique_contexts":{},"unique_actors":{}}}"#,
         total, subjects, predicates, contexts, actors,
@@ -657,6 +999,7 @@ pub extern "C" fn read_conn_stats(rc: *const ReadConn) -> AttestationResultC {
 }
 
 /// Execute a raw SQL query through the read connection.
+/// Not restricted to the connection's namespace.
 #[no_mangle]
 #[allow(clippy::not_unsafe_ptr_arg_deref)]
 pub extern "C" fn read_conn_query_raw(
@@ -716,25 +1059,13 @@ pub extern "C" fn read_conn_query_raw(
     let param_slice: Vec<&dyn rusqlite::types::ToSql> =
         param_refs.iter().map(|p| p.as_ref()).collect();
 
-    let rows = match stmt.query_map(param_slice.as_slice(), |row| {
-        Ok((
-            row.get::<_, String>(0)?,
-            row.get::<_, String>(1)?,
-            row.get::<_, String>(2)?,
-            row.get::<_, String>(3)?,
-            row.get::<_, String>(4)?,
-            row.get::<_, String>(5)?,
-            row.get::<_, String>(6)?,
-            row.get::<_, Option<String>>(7)?,
-            row.get::<_, String>(8)?,
-            row.get::<_, Option<Vec<u8>>>(9)?,
-            row.get::<_, Option<String>>(10)?,
-        ))
-    }) {
+    let rows = match stmt.query_map(param_slice.as_slice(), crate::json::read_attestation_row) {
         Ok(r) => r,
         Err(e) => return AttestationResultC::error(&format!("{}", e)),
     };
+    // Read connections have no quarantine; undecodable rows are skipped and counted.
     let mut attestations = Vec::new();
+    let mut corrupt_count = 0;
     for row_result in rows {
         let row_data = match row_result {
             Ok(r) => r,
@@ -742,7 +1073,7 @@ pub extern "C" fn read_conn_query_raw(
         };
         match SqliteStore::row_to_attestation(row_data) {
             Ok(a) => attestations.push(a),
-            Err(e) => return AttestationResultC::error(&format!("{}", e)),
+            Err(_) => corrupt_count += 1,
         }
     }
     let protos: Vec<qntx_proto::Attestation> = attestations
@@ -750,7 +1081,7 @@ pub extern "C" fn read_conn_query_raw(
         .map(proto_convert::to_proto)
         .collect();
     match serde_json::to_string(&protos) {
-        Ok(json) => AttestationResultC::ok(json),
+        Ok(json) => AttestationResultC::ok(json).with_corrupt_count(corrupt_count),
         Err(e) => AttestationResultC::error(&format!("failed to serialize results: {}", e)),
     }
 }
@@ -763,17 +1094,21 @@ pub extern "C" fn read_conn_integrity_check(rc: *const ReadConn) -> StringArrayR
         return StringArrayResultC::error("null read connection");
     }
     let rc = unsafe { &*rc };
-    match query_distinct(&rc.conn, "PRAGMA integrity_check") {
+    match query_distinct(&rc.conn, "PRAGMA integrity_check", []) {
         Ok(lines) => StringArrayResultC::ok(lines),
         Err(e) => StringArrayResultC::error(&format!("integrity check failed: {}", e)),
     }
 }
 
 /// Helper: query a single-column result set.
-fn query_distinct(conn: &rusqlite::Connection, sql: &str) -> Result<Vec<String>, String> {
-    let mut stmt = conn.prepare(sql).map_err(|e| format!("{}", e))?;
+fn query_distinct(
+    conn: &rusqlite::Connection,
+    sql: &str,
+    params: impl rusqlite::Params,
+) -> Result<Vec<String>, String> {
+    let mut stmt = conn.prepare_cached(sql).map_err(|e| format!("{}", e))?;
     let rows = stmt
-        .query_map([], |row| row.get::<_, String>(0))
+        .query_map(params, |row| row.get::<_, String>(0))
         .map_err(|e| format!("{}", e))?;
     let mut results = Vec::new();
     for row in rows {
@@ -840,7 +1175,12 @@ pub extern "C" fn storage_put(
 
     let proto: qntx_proto::Attestation = match serde_json::from_str(json_str) {
         Ok(a) => a,
-        Err(e) => return StorageResultC::error(&format!("failed to parse JSON: {}", e)),
+        Err(e) => {
+            return StorageResultC::store_error(&StoreError::InvalidData(format!(
+                "failed to parse JSON: {}",
+                e
+            )))
+        }
     };
     let attestation = proto_convert::from_proto(proto);
 
@@ -848,7 +1188,7 @@ pub extern "C" fn storage_put(
 
     match store.put(attestation) {
         Ok(()) => StorageResultC::ok(),
-        Err(e) => StorageResultC::error(&format!("{}", e)),
+        Err(e) => StorageResultC::store_error(&e),
     }
 }
 
@@ -879,7 +1219,7 @@ pub extern "C" fn storage_get(store: *const SqliteStore, id: *const c_char) -> A
             }
         }
         Ok(None) => AttestationResultC::not_found(),
-        Err(e) => AttestationResultC::error(&format!("{}", e)),
+        Err(e) => AttestationResultC::store_error(&e),
     }
 }
 
@@ -902,8 +1242,9 @@ pub extern "C" fn storage_exists(store: *const SqliteStore, id: *const c_char) -
         Ok(false) => StorageResultC {
             success: false,
             error_msg: ptr::null_mut(),
+            error_code: ptr::null(),
         },
-        Err(e) => StorageResultC::error(&format!("{}", e)),
+        Err(e) => StorageResultC::store_error(&e),
     }
 }
 
@@ -923,9 +1264,56 @@ pub extern "C" fn storage_delete(store: *mut SqliteStore, id: *const c_char) ->
 
     match store.delete(id_str) {
         Ok(true) => StorageResultC::ok(),
-        Ok(false) => StorageResultC::error("not found"),
-        Err(e) => StorageResultC::error(&format!("{}", e)),
+        Ok(false) => StorageResultC {
+            error_code: error_code_ptr(StorageErrorKind::NotFound),
+            ..StorageResultC::error("not found")
+        },
+        Err(e) => StorageResultC::store_error(&e),
+    }
+}
+
+/// Delete an attestation through the delete cascade. `options_json` is
+/// `{"dry_run":false,"find_orphans":false,"scan_attributes":false}` or NULL for plain deletion with
+/// a report. A missing ID is not an error: the report has `"found":false`.
+///
+/// Output JSON: `{"id","dry_run","found","orphans":[...],"hooks":[...]}`
+#[no_mangle]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn storage_delete_cascade(
+    store: *mut SqliteStore,
+    id: *const c_char,
+    options_json: *const c_char,
+) -> AttestationResultC {
+    if store.is_null() {
+        return AttestationResultC::error("null store pointer");
     }
+    let id_str = match unsafe { cstr_to_str(id) } {
+        Ok(s) => s,
+        Err(e) => return AttestationResultC::error(e),
+    };
+    let options: DeleteOptions = if options_json.is_null() {
+        DeleteOptions::default()
+    } else {
+        let json = match unsafe { cstr_to_str(options_json) } {
+            Ok(s) => s,
+            Err(e) => return AttestationResultC::error(e),
+        };
+        match serde_json::from_str(json) {
+            Ok(o) => o,
+            Err(e) => {
+                return AttestationResultC::store_error(&StoreError::InvalidData(format!(
+                    "invalid delete options: {}",
+                    e
+                )))
+            }
+        }
+    };
+
+    let store = unsafe { &mut *store };
+    maintenance_json(
+        "delete_cascade",
+        DeleteCoordinator::new(options).delete(store, id_str),
+    )
 }
 
 #[no_mangle]
@@ -947,13 +1335,62 @@ pub extern "C" fn storage_update(
 
     let proto: qntx_proto::Attestation = match serde_json::from_str(json_str) {
         Ok(a) => a,
-        Err(e) => return StorageResultC::error(&format!("failed to parse JSON: {}", e)),
+        Err(e) => {
+            return StorageResultC::store_error(&StoreError::InvalidData(format!(
+                "failed to parse JSON: {}",
+                e
+            )))
+        }
     };
     let attestation = proto_convert::from_proto(proto);
 
     match store.update(attestation) {
         Ok(()) => StorageResultC::ok(),
-        Err(e) => StorageResultC::error(&format!("{}", e)),
+        Err(e) => StorageResultC::store_error(&e),
+    }
+}
+
+/// Write an attestation only if its stored revision matches `expected_revision`
+/// (0 = must not exist yet). Returns `{"status":"written","revision":N}` or
+/// `{"status":"conflict","current":...}` in `attestation_json`.
+#[no_mangle]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn storage_put_if_revision(
+    store: *mut SqliteStore,
+    attestation_json: *const c_char,
+    expected_revision: u64,
+) -> AttestationResultC {
+    if store.is_null() {
+        return AttestationResultC::error("null store pointer");
+    }
+
+    let json_str = match unsafe { cstr_to_str(attestation_json) } {
+        Ok(s) => s,
+        Err(e) => return AttestationResultC::error(e),
+    };
+
+    if json_str.len() > MAX_JSON_LENGTH {
+        return AttestationResultC::error("attestation JSON exceeds maximum length");
+    }
+
+    let store = unsafe { &mut *store };
+
+    let proto: qntx_proto::Attestation = match serde_json::from_str(json_str) {
+        Ok(a) => a,
+        Err(e) => {
+            return AttestationResultC::store_error(&StoreError::InvalidData(format!(
+                "failed to parse JSON: {}",
+                e
+            )))
+        }
+    };
+    let attestation = proto_convert::from_proto(proto);
+
+    crate::flight_recorder::record_fmt("storage_put_if_revision", &attestation.id);
+
+    match store.put_if_revision(attestation, expected_revision) {
+        Ok(outcome) => AttestationResultC::ok(proto_convert::put_outcome_to_json(outcome)),
+        Err(e) => AttestationResultC::store_error(&e),
     }
 }
 
@@ -998,11 +1435,16 @@ pub extern "C" fn storage_clear(store: *mut SqliteStore) -> StorageResultC {
 
     match store.clear() {
         Ok(()) => StorageResultC::ok(),
-        Err(e) => StorageResultC::error(&format!("{}", e)),
+        Err(e) => StorageResultC::store_error(&e),
     }
 }
 
 /// Query attestations with filters (returns JSON array of matching attestations)
+///
+/// `filter_json` is a JSON `AxFilter` (subjects, predicates, contexts, actors,
+/// time_start/time_end, limit, ...), or NULL for every attestation up to
+/// [`DEFAULT_QUERY_LIMIT`]. Malformed JSON fails with `invalid_input`. With
+/// `include_summary` set the result is `{"attestations": [...], "summary": {...}}`.
 #[no_mangle]
 #[allow(clippy::not_unsafe_ptr_arg_deref)]
 pub extern "C" fn storage_query(
@@ -1013,38 +1455,26 @@ pub extern "C" fn storage_query(
         return AttestationResultC::error("null store pointer");
     }
 
-    let filter_str = match unsafe { cstr_to_str(filter_json) } {
-        Ok(s) => s,
-        Err(e) => return AttestationResultC::error(e),
-    };
-
-    if filter_str.len() > MAX_JSON_LENGTH {
-        return AttestationResultC::error("filter JSON exceeds maximum length");
-    }
-
-    let store = unsafe { &*store };
-
-    // Parse filter JSON
-    let filter: qntx_core::AxFilter = match serde_json::from_str(filter_str) {
+    let filter = match parse_filter(filter_json) {
         Ok(f) => f,
-        Err(e) => return AttestationResultC::error(&format!("invalid filter JSON: {}", e)),
+        Err(e) => return e,
     };
+    let store = unsafe { &*store };
 
     // Query attestations
     use qntx_core::storage::QueryStore;
     let result = match store.query(&filter) {
         Ok(r) => r,
-        Err(e) => return AttestationResultC::error(&format!("query failed: {}", e)),
+        Err(e) => {
+            return AttestationResultC {
+                error_code: error_code_ptr(e.kind()),
+                ..AttestationResultC::error(&format!("query failed: {}", e))
+            }
+        }
     };
 
-    // Convert attestations to proto types and serialize
-    let proto_attestations: Vec<qntx_proto::Attestation> = result
-        .attestations
-        .into_iter()
-        .map(proto_convert::to_proto)
-        .collect();
-    match serde_json::to_string(&proto_attestations) {
-        Ok(json) => AttestationResultC::ok(json),
+    match query_result_json(&filter, result.attestations, &result.summary) {
+        Ok(json) => AttestationResultC::ok(json).with_corrupt_count(store.last_corrupt_count()),
         Err(e) => AttestationResultC::error(&format!("failed to serialize results: {}", e)),
     }
 }
@@ -1262,11 +1692,338 @@ pub extern "C" fn storage_integrity_check(store: *const SqliteStore) -> StringAr
         }
     };
     match store.integrity_check() {
-        Ok(lines) => StringArrayResultC::ok(lines),
+        Ok(report) if report.ok => StringArrayResultC::ok(vec!["ok".to_string()]),
+        Ok(report) => StringArrayResultC::ok(report.problems),
         Err(e) => StringArrayResultC::error(&format!("integrity check failed: {}", e)),
     }
 }
 
+// ============================================================================
+// Maintenance
+// ============================================================================
+
+/// Serialize a maintenance report for the admin endpoint.
+fn maintenance_json<T: serde::Serialize>(
+    op: &str,
+    result: Result<T, StoreError>,
+) -> AttestationResultC {
+    match result {
+        Ok(report) => match serde_json::to_string(&report) {
+            Ok(json) => AttestationResultC::ok(json),
+            Err(e) => AttestationResultC::error(&format!("{}: serialization failed: {}", op, e)),
+        },
+        Err(e) => AttestationResultC::store_error(&e),
+    }
+}
+
+/// Run a WAL checkpoint. `mode` is "passive", "full" or "truncate".
+/// Truncate only shrinks the WAL when no read connection holds it; use
+/// `storage_wal_checkpoint` to cycle read connections around it.
+///
+/// Output JSON: `{"mode":"full","busy":false,"wal_pages":N,"checkpointed_pages":N}`
+#[no_mangle]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn storage_checkpoint(
+    store: *const SqliteStore,
+    mode: *const c_char,
+) -> AttestationResultC {
+    let store = unsafe {
+        match store.as_ref() {
+            Some(s) => s,
+            None => return AttestationResultC::error("null store pointer"),
+        }
+    };
+    let mode_str = match unsafe { cstr_to_str(mode) } {
+        Ok(s) => s,
+        Err(e) => return AttestationResultC::error(e),
+    };
+    let Some(mode) = crate::maintenance::CheckpointMode::parse(mode_str) else {
+        return AttestationResultC::error(&format!("unknown checkpoint mode: {}", mode_str));
+    };
+    crate::flight_recorder::record_fmt("storage_checkpoint", mode_str);
+    maintenance_json("checkpoint", store.checkpoint(mode))
+}
+
+/// Rebuild the database file. Blocks writers for the whole rewrite.
+///
+/// Output JSON: `{"before":{"page_size":N,"page_count":N,"freelist_count":N},"after":{...}}`
+#[no_mangle]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn storage_vacuum(store: *const SqliteStore) -> AttestationResultC {
+    let store = unsafe {
+        match store.as_ref() {
+            Some(s) => s,
+            None => return AttestationResultC::error("null store pointer"),
+        }
+    };
+    crate::flight_recorder::record("storage_vacuum");
+    maintenance_json("vacuum", store.vacuum())
+}
+
+/// Free up to `pages` pages (0 = all). Requires incremental auto-vacuum.
+///
+/// Output JSON: `{"freed_pages":N}`
+#[no_mangle]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn storage_incremental_vacuum(
+    store: *const SqliteStore,
+    pages: usize,
+) -> AttestationResultC {
+    let store = unsafe {
+        match store.as_ref() {
+            Some(s) => s,
+            None => return AttestationResultC::error("null store pointer"),
+        }
+    };
+    maintenance_json(
+        "incremental_vacuum",
+        store
+            .incremental_vacuum(pages)
+            .map(|freed| serde_json::json!({ "freed_pages": freed })),
+    )
+}
+
+/// Refresh query planner statistics (ANALYZE).
+#[no_mangle]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn storage_analyze(store: *const SqliteStore) -> StorageResultC {
+    let store = unsafe {
+        match store.as_ref() {
+            Some(s) => s,
+            None => return StorageResultC::error("null store pointer"),
+        }
+    };
+    match store.analyze() {
+        Ok(()) => StorageResultC::ok(),
+        Err(e) => StorageResultC::store_error(&e),
+    }
+}
+
+/// Structured integrity check.
+///
+/// Output JSON: `{"ok":false,"problems":["..."]}`
+#[no_mangle]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn storage_integrity_report(store: *const SqliteStore) -> AttestationResultC {
+    let store = unsafe {
+        match store.as_ref() {
+            Some(s) => s,
+            None => return AttestationResultC::error("null store pointer"),
+        }
+    };
+    maintenance_json("integrity_check", store.integrity_check())
+}
+
+/// Structured integrity check through a read connection, so writes through
+/// the store are not held up. Output JSON as for `storage_integrity_report`.
+#[no_mangle]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn read_conn_integrity_report(rc: *const ReadConn) -> AttestationResultC {
+    if rc.is_null() {
+        return AttestationResultC::error("null read connection");
+    }
+    let rc = unsafe { &*rc };
+    maintenance_json("integrity_check", rc.integrity_check())
+}
+
+/// Attach an automatic maintenance policy, e.g.
+/// `{"wal_limit_bytes":67108864,"checkpoint_mode":"passive","check_every":100}`.
+/// NULL or an empty string removes the policy.
+///
+/// Caller must hold the write mutex (muWrite) before calling.
+#[no_mangle]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn storage_set_maintenance_policy(
+    store: *mut SqliteStore,
+    policy_json: *const c_char,
+) -> StorageResultC {
+    let store = unsafe {
+        match store.as_mut() {
+            Some(s) => s,
+            None => return StorageResultC::error("null store pointer"),
+        }
+    };
+    let policy = if policy_json.is_null() {
+        None
+    } else {
+        match unsafe { cstr_to_str(policy_json) } {
+            Ok("") => None,
+            Ok(json) => match serde_json::from_str(json) {
+                Ok(policy) => Some(policy),
+                Err(e) => {
+                    return StorageResultC::error(&format!("invalid maintenance policy: {}", e))
+                }
+            },
+            Err(e) => return StorageResultC::error(e),
+        }
+    };
+    match store.set_maintenance_policy(policy) {
+        Ok(()) => StorageResultC::ok(),
+        Err(e) => StorageResultC::store_error(&e),
+    }
+}
+
+// ============================================================================
+// Mirror
+// ============================================================================
+
+/// Mirror attestations matching `filter_json` (an AxFilter) into the
+/// database at `dest_path`, which is created if missing. `options_json` is a
+/// MirrorOptions document; NULL or "" uses the defaults. Mirror state is kept
+/// in this store, so the caller must hold the write mutex.
+///
+/// Output JSON: `{"run_id":"..","dry_run":false,"copied":N,"updated":N,"skipped":N,
+/// "deleted":N,"errors":[{"id","error"}],"changes":[{"id","kind"}]}`
+#[no_mangle]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn storage_mirror(
+    store: *const SqliteStore,
+    dest_path: *const c_char,
+    filter_json: *const c_char,
+    options_json: *const c_char,
+) -> AttestationResultC {
+    let store = unsafe {
+        match store.as_ref() {
+            Some(s) => s,
+            None => return AttestationResultC::error("null store pointer"),
+        }
+    };
+    let dest_path = match unsafe { cstr_to_str(dest_path) } {
+        Ok(s) => s,
+        Err(e) => return AttestationResultC::error(e),
+    };
+    let filter_str = match unsafe { cstr_to_str(filter_json) } {
+        Ok(s) => s,
+        Err(e) => return AttestationResultC::error(e),
+    };
+    if filter_str.len() > MAX_JSON_LENGTH {
+        return AttestationResultC::error("filter JSON exceeds maximum length");
+    }
+    let filter: qntx_core::AxFilter = match serde_json::from_str(filter_str) {
+        Ok(f) => f,
+        Err(e) => {
+            return AttestationResultC::store_error(&StoreError::Query(format!(
+                "invalid filter JSON: {}",
+                e
+            )))
+        }
+    };
+    let options_str = if options_json.is_null() {
+        ""
+    } else {
+        match unsafe { cstr_to_str(options_json) } {
+            Ok(s) => s,
+            Err(e) => return AttestationResultC::error(e),
+        }
+    };
+    let options: crate::mirror::MirrorOptions = if options_str.is_empty() {
+        Default::default()
+    } else {
+        match serde_json::from_str(options_str) {
+            Ok(o) => o,
+            Err(e) => {
+                return AttestationResultC::store_error(&StoreError::InvalidData(format!(
+                    "invalid mirror options JSON: {}",
+                    e
+                )))
+            }
+        }
+    };
+
+    crate::flight_recorder::record_fmt("storage_mirror", dest_path);
+    let mut dest = match SqliteStore::open(dest_path) {
+        Ok(d) => d,
+        Err(e) => return AttestationResultC::store_error(&e.into()),
+    };
+    maintenance_json("mirror", store.mirror_to(&mut dest, &filter, &options))
+}
+
+// ============================================================================
+// Export
+// ============================================================================
+
+/// Export attestations matching `filter_json` (an AxFilter) to a new file at
+/// `path`. `format` is "csv" or "parquet" (the latter needs the library built
+/// with `--features parquet`). `options_json` is an ExportOptions document;
+/// NULL or "" uses the defaults. Reads only, so no write mutex is needed.
+///
+/// Output JSON: `{"rows":N,"bytes":N,"duration_ms":N}`
+#[no_mangle]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn storage_export_query(
+    store: *const SqliteStore,
+    path: *const c_char,
+    filter_json: *const c_char,
+    format: *const c_char,
+    options_json: *const c_char,
+) -> AttestationResultC {
+    let store = unsafe {
+        match store.as_ref() {
+            Some(s) => s,
+            None => return AttestationResultC::error("null store pointer"),
+        }
+    };
+    let path = match unsafe { cstr_to_str(path) } {
+        Ok(s) => s,
+        Err(e) => return AttestationResultC::error(e),
+    };
+    let filter_str = match unsafe { cstr_to_str(filter_json) } {
+        Ok(s) => s,
+        Err(e) => return AttestationResultC::error(e),
+    };
+    if filter_str.len() > MAX_JSON_LENGTH {
+        return AttestationResultC::error("filter JSON exceeds maximum length");
+    }
+    let filter: qntx_core::AxFilter = match serde_json::from_str(filter_str) {
+        Ok(f) => f,
+        Err(e) => {
+            return AttestationResultC::store_error(&StoreError::Query(format!(
+                "invalid filter JSON: {}",
+                e
+            )))
+        }
+    };
+    let format = match unsafe { cstr_to_str(format) } {
+        Ok(s) => match crate::export::ExportFormat::parse(s) {
+            Some(f) => f,
+            None => {
+                return AttestationResultC::store_error(&StoreError::InvalidData(format!(
+                    "unknown export format {:?} (want \"csv\" or \"parquet\")",
+                    s
+                )))
+            }
+        },
+        Err(e) => return AttestationResultC::error(e),
+    };
+    let options_str = if options_json.is_null() {
+        ""
+    } else {
+        match unsafe { cstr_to_str(options_json) } {
+            Ok(s) => s,
+            Err(e) => return AttestationResultC::error(e),
+        }
+    };
+    let options: crate::export::ExportOptions = if options_str.is_empty() {
+        Default::default()
+    } else {
+        match serde_json::from_str(options_str) {
+            Ok(o) => o,
+            Err(e) => {
+                return AttestationResultC::store_error(&StoreError::InvalidData(format!(
+                    "invalid export options JSON: {}",
+                    e
+                )))
+            }
+        }
+    };
+
+    crate::flight_recorder::record_fmt("storage_export_query", path);
+    maintenance_json(
+        "export",
+        store.export_query_to_file(&filter, format, path, &options),
+    )
+}
+
 // ============================================================================
 // Backup
 // ============================================================================
@@ -1333,6 +2090,33 @@ pub extern "C" fn storage_crash_test() {
     std::process::abort();
 }
 
+/// Run the qntx-core self-benchmark (seeded workloads, ~1s budget) on this
+/// machine. `config_json` is a `BenchConfig`, or NULL for defaults.
+/// Development only; present when built with the `bench` feature.
+///
+/// Output JSON: `{"environment":{...},"config":{...},"workloads":[{"name",
+/// "size","iterations","median_ms","p95_ms","min_ms"}],"total_ms":N,
+/// "budget_exhausted":false}`
+#[cfg(feature = "bench")]
+#[no_mangle]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn storage_run_benchmarks(config_json: *const c_char) -> AttestationResultC {
+    let config = if config_json.is_null() {
+        qntx_core::BenchConfig::default()
+    } else {
+        let json = match unsafe { cstr_to_str(config_json) } {
+            Ok(s) => s,
+            Err(e) => return AttestationResultC::error(e),
+        };
+        match serde_json::from_str(json) {
+            Ok(c) => c,
+            Err(e) => return AttestationResultC::error(&format!("invalid bench config: {}", e)),
+        }
+    };
+    let report = qntx_core::benchmark::run_benchmarks_native(&config);
+    maintenance_json("run_benchmarks", Ok(report))
+}
+
 // ============================================================================
 // Utilities
 // ============================================================================
@@ -1344,6 +2128,208 @@ mod tests {
     use super::*;
     use std::ffi::CString;
 
+    fn error_code(ptr: *const c_char) -> &'static str {
+        assert!(!ptr.is_null(), "expected an error code");
+        unsafe { CStr::from_ptr(ptr) }.to_str().unwrap()
+    }
+
+    #[test]
+    fn test_error_codes() {
+        let store = storage_new_memory();
+        let json = r#"{"id":"AS-1","subjects":["ALICE"],"predicates":["knows"],"contexts":["work"],"actors":["human:bob"],"timestamp":1000,"source":"test","attributes":{},"created_at":1000}"#;
+        let json_cstr = CString::new(json).unwrap();
+
+        let first = storage_put(store, json_cstr.as_ptr());
+        assert!(first.success);
+        assert!(first.error_code.is_null());
+        storage_result_free(first);
+
+        let dup = storage_put(store, json_cstr.as_ptr());
+        assert!(!dup.success);
+        assert_eq!(error_code(dup.error_code), "duplicate");
+        storage_result_free(dup);
+
+        let missing = CString::new(json.replace("AS-1", "AS-missing")).unwrap();
+        let update = storage_update(store, missing.as_ptr());
+        assert_eq!(
+            error_code(update.error_code),
+            StorageErrorKind::NotFound.code()
+        );
+        storage_result_free(update);
+
+        let bad = CString::new("{not json").unwrap();
+        let parse = storage_put(store, bad.as_ptr());
+        assert_eq!(error_code(parse.error_code), "invalid_input");
+        storage_result_free(parse);
+
+        let unexpanded = CString::new(r#"{"expand_actor_aliases":true}"#).unwrap();
+        let query = storage_query(store, unexpanded.as_ptr());
+        assert!(!query.success);
+        assert_eq!(error_code(query.error_code), "invalid_input");
+        attestation_result_free(query);
+
+        storage_free(store);
+    }
+
+    #[test]
+    fn test_query_reports_corrupt_count() {
+        let store = storage_new_memory();
+        for id in ["AS-1", "AS-2"] {
+            let json = format!(
+                r#"{{"id":"{id}","subjects":["ALICE"],"predicates":["knows"],"contexts":["work"],"actors":["human:bob"],"timestamp":1000,"source":"test","attributes":{{}},"created_at":1000}}"#
+            );
+            let json_cstr = CString::new(json).unwrap();
+            storage_result_free(storage_put(store, json_cstr.as_ptr()));
+        }
+        unsafe { &*store }
+            .connection()
+            .execute(
+                "UPDATE attestations SET attributes = '{broken' WHERE id = 'AS-2'",
+                [],
+            )
+            .unwrap();
+
+        let filter = CString::new("{}").unwrap();
+        let result = storage_query(store, filter.as_ptr());
+        assert!(result.success);
+        assert_eq!(result.corrupt_count, 1);
+        attestation_result_free(result);
+
+        storage_free(store);
+    }
+
+    #[test]
+    fn test_query_filter_round_trip() {
+        let store = storage_new_memory();
+        for (id, predicate, actor, ts) in [
+            ("AS-1", "knows", "human:bob", 1000),
+            ("AS-2", "knows", "human:carol", 2000),
+            ("AS-3", "likes", "human:bob", 3000),
+            ("AS-4", "knows", "human:bob", 4000),
+        ] {
+            let json = format!(
+                r#"{{"id":"{id}","subjects":["ALICE"],"predicates":["{predicate}"],"contexts":["work"],"actors":["{actor}"],"timestamp":{ts},"source":"test","attributes":{{}},"created_at":{ts}}}"#
+            );
+            let json_cstr = CString::new(json).unwrap();
+            storage_result_free(storage_put(store, json_cstr.as_ptr()));
+        }
+        let query_ids = |filter: *const c_char| {
+            let result = storage_query(store, filter);
+            assert!(result.success);
+            let body = unsafe { CStr::from_ptr(result.attestation_json) }
+                .to_str()
+                .unwrap()
+                .to_string();
+            attestation_result_free(result);
+            let items: Vec<serde_json::Value> = serde_json::from_str(&body).unwrap();
+            let mut ids: Vec<String> = items
+                .iter()
+                .map(|a| a["id"].as_str().unwrap().to_string())
+                .collect();
+            ids.sort();
+            ids
+        };
+
+        let filter = CString::new(
+            r#"{"subjects":["ALICE"],"predicates":["knows"],"contexts":["work"],"actors":["human:bob"],"time_start":500,"time_end":3500}"#,
+        )
+        .unwrap();
+        assert_eq!(query_ids(filter.as_ptr()), ["AS-1"]);
+        let limited = CString::new(r#"{"predicates":["knows"],"limit":2}"#).unwrap();
+        assert_eq!(query_ids(limited.as_ptr()).len(), 2);
+
+        // NULL filter: everything, up to the default limit
+        assert_eq!(query_ids(ptr::null()), ["AS-1", "AS-2", "AS-3", "AS-4"]);
+
+        let bad = CString::new("{not json").unwrap();
+        let result = storage_query(store, bad.as_ptr());
+        assert!(!result.success);
+        assert_eq!(error_code(result.error_code), "invalid_input");
+        attestation_result_free(result);
+
+        storage_free(store);
+    }
+
+    #[test]
+    fn test_query_include_summary() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = CString::new(dir.path().join("summary.db").to_str().unwrap()).unwrap();
+        let store = storage_new_file(path.as_ptr());
+        assert!(!store.is_null());
+        for (id, context) in [("AS-1", "work"), ("AS-2", "work"), ("AS-3", "home")] {
+            let json = format!(
+                r#"{{"id":"{id}","subjects":["ALICE"],"predicates":["knows"],"contexts":["{context}"],"actors":["human:bob"],"timestamp":1000,"source":"test","attributes":{{}},"created_at":1000}}"#
+            );
+            let json_cstr = CString::new(json).unwrap();
+            storage_result_free(storage_put(store, json_cstr.as_ptr()));
+        }
+        let rc = storage_open_read_conn(store);
+        assert!(!rc.is_null());
+
+        let body = |result: AttestationResultC| {
+            assert!(result.success);
+            let body = unsafe { CStr::from_ptr(result.attestation_json) }
+                .to_str()
+                .unwrap()
+                .to_string();
+            attestation_result_free(result);
+            serde_json::from_str::<serde_json::Value>(&body).unwrap()
+        };
+        let filter = CString::new(r#"{"limit":1,"include_summary":true}"#).unwrap();
+        for result in [
+            storage_query(store, filter.as_ptr()),
+            read_conn_query(rc, filter.as_ptr()),
+        ] {
+            let value = body(result);
+            assert_eq!(value["attestations"].as_array().unwrap().len(), 1);
+            assert_eq!(value["summary"]["total_attestations"], 1);
+            assert_eq!(value["summary"]["matching"]["total"], 3);
+            assert_eq!(value["summary"]["matching"]["distinct_contexts"], 2);
+        }
+
+        // Without the flag the result stays a plain array
+        let plain = CString::new(r#"{"limit":1}"#).unwrap();
+        assert!(body(read_conn_query(rc, plain.as_ptr())).is_array());
+
+        read_conn_free(rc);
+        storage_free(store);
+    }
+
+    #[test]
+    fn test_scoped_file_stores_are_isolated() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = CString::new(dir.path().join("ws.db").to_str().unwrap()).unwrap();
+        let alpha_ns = CString::new("alpha").unwrap();
+        let beta_ns = CString::new("beta").unwrap();
+        let alpha = storage_new_file_scoped(path.as_ptr(), alpha_ns.as_ptr());
+        let beta = storage_new_file_scoped(path.as_ptr(), beta_ns.as_ptr());
+        assert!(!alpha.is_null() && !beta.is_null());
+
+        let json = r#"{"id":"AS-1","subjects":["ALICE"],"predicates":["knows"],"contexts":["work"],"actors":["human:bob"],"timestamp":1000,"source":"test","attributes":{},"created_at":1000}"#;
+        let json_cstr = CString::new(json).unwrap();
+        storage_result_free(storage_put(alpha, json_cstr.as_ptr()));
+
+        let id_cstr = CString::new("AS-1").unwrap();
+        let from_beta = storage_get(beta, id_cstr.as_ptr());
+        assert!(from_beta.success);
+        assert!(from_beta.attestation_json.is_null());
+        attestation_result_free(from_beta);
+
+        // Read connections inherit the namespace
+        let rc = storage_open_read_conn(beta);
+        let count = read_conn_count(rc);
+        assert!(count.success);
+        assert_eq!(count.count, 0);
+        count_result_free(count);
+        read_conn_free(rc);
+
+        let empty = CString::new("").unwrap();
+        assert!(storage_new_file_scoped(path.as_ptr(), empty.as_ptr()).is_null());
+
+        storage_free(alpha);
+        storage_free(beta);
+    }
+
     #[test]
     fn test_lifecycle() {
         let store = storage_new_memory();
@@ -1371,4 +2357,77 @@ mod tests {
 
         storage_free(store);
     }
+
+    #[test]
+    fn test_put_if_revision() {
+        let store = storage_new_memory();
+
+        let json = r#"{"id":"AS-1","subjects":["LUKE"],"predicates":["pilots"],"contexts":["x-wing"],"actors":["human:leia"],"timestamp":1000,"source":"test","attributes":{},"created_at":1000}"#;
+        let json_cstr = CString::new(json).unwrap();
+
+        let created = storage_put_if_revision(store, json_cstr.as_ptr(), 0);
+        assert!(created.success);
+        let body = unsafe { CStr::from_ptr(created.attestation_json) }
+            .to_str()
+            .unwrap()
+            .to_string();
+        assert_eq!(body, r#"{"status":"written","revision":1}"#);
+        attestation_result_free(created);
+
+        let stale = storage_put_if_revision(store, json_cstr.as_ptr(), 0);
+        assert!(stale.success);
+        let body = unsafe { CStr::from_ptr(stale.attestation_json) }
+            .to_str()
+            .unwrap()
+            .to_string();
+        let value: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(value["status"], "conflict");
+        assert_eq!(value["current"]["revision"], 1);
+        attestation_result_free(stale);
+
+        let updated = storage_put_if_revision(store, json_cstr.as_ptr(), 1);
+        assert!(updated.success);
+        let body = unsafe { CStr::from_ptr(updated.attestation_json) }
+            .to_str()
+            .unwrap()
+            .to_string();
+        assert_eq!(body, r#"{"status":"written","revision":2}"#);
+        attestation_result_free(updated);
+
+        storage_free(store);
+    }
+
+    #[test]
+    fn test_delete_cascade_reports_orphans() {
+        let store = storage_new_memory();
+        let doc = r#"{"id":"AS-doc","subjects":["DOC"],"predicates":["is"],"contexts":["work"],"actors":["human:bob"],"timestamp":1000,"source":"test","attributes":{},"created_at":1000}"#;
+        let emb = r#"{"id":"AS-emb","subjects":["AS-doc"],"predicates":["embedding"],"contexts":["work"],"actors":["human:bob"],"timestamp":1000,"source":"test","attributes":{},"created_at":1000}"#;
+        for json in [doc, emb] {
+            let json_cstr = CString::new(json).unwrap();
+            storage_result_free(storage_put(store, json_cstr.as_ptr()));
+        }
+
+        let id = CString::new("AS-doc").unwrap();
+        let options = CString::new(r#"{"find_orphans":true}"#).unwrap();
+        let result = storage_delete_cascade(store, id.as_ptr(), options.as_ptr());
+        assert!(result.success);
+        let body = unsafe { CStr::from_ptr(result.attestation_json) }
+            .to_str()
+            .unwrap()
+            .to_string();
+        attestation_result_free(result);
+        let report: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(report["found"], true);
+        assert_eq!(report["orphans"], serde_json::json!(["AS-emb"]));
+
+        let again = storage_delete_cascade(store, id.as_ptr(), ptr::null());
+        let body = unsafe { CStr::from_ptr(again.attestation_json) }
+            .to_str()
+            .unwrap()
+            .to_string();
+        attestation_result_free(again);
+        assert!(body.contains(r#""found":false"#), "{}", body);
+
+        storage_free(store);
+    }
 }
@@ -0,0 +1,425 @@
+//! Attestation history: an append-only change log for time-travel queries.
+//!
+//! Puts overwrite and deletes erase, so the live tables can't say what the
+//! store held on March 1st. With history enabled for a namespace
+//! ([`SqliteStore::enable_history`]), every write also appends the stored
+//! attestation (or the deletion) to `attestation_history`, with a wall-clock
+//! `applied_at` and a global sequence number. Then:
+//!
+//! - [`SqliteStore::query_as_of`] rebuilds the set visible at an instant and
+//!   runs an `AxFilter` over it
+//! - [`SqliteStore::history_of`] lists every change to one attestation
+//! - [`SqliteStore::prune_history`] applies a [`HistoryRetention`]
+//!
+//! Recording happens in the Rust write path, in the same functions every
+//! writer goes through: `put`, `update`, `put_if_revision`, `delete`,
+//! `clear`, imports, bounded-storage enforcement, distillation and
+//! `repair_row`. Raw SQL run through `query_attestations_raw` or the SQL FFI
+//! bypasses it.
+//!
+//! Enabling is per namespace and stored in the file, so it survives reopening.
+//! On enabling, the namespace's current rows become `baseline` entries; the
+//! history starts there, and instants before it can't be reconstructed.
+
+use rusqlite::{Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+
+use qntx_core::attestation::{Attestation, AxFilter, AxResult};
+use qntx_core::storage::{MemoryStore, QueryStore, StoreError};
+
+use crate::error::{Result, SqliteError};
+use crate::store::SqliteStore;
+
+type StoreResult<T> = std::result::Result<T, StoreError>;
+
+/// Kind of change a history entry records.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HistoryOperation {
+    /// Present when history was enabled
+    Baseline,
+    Put,
+    Update,
+    Delete,
+}
+
+impl HistoryOperation {
+    fn as_str(self) -> &'static str {
+        match self {
+            HistoryOperation::Baseline => "baseline",
+            HistoryOperation::Put => "put",
+            HistoryOperation::Update => "update",
+            HistoryOperation::Delete => "delete",
+        }
+    }
+
+    fn parse(s: &str) -> Option<Self> {
+        match s {
+            "baseline" => Some(HistoryOperation::Baseline),
+            "put" => Some(HistoryOperation::Put),
+            "update" => Some(HistoryOperation::Update),
+            "delete" => Some(HistoryOperation::Delete),
+            _ => None,
+        }
+    }
+}
+
+/// One recorded change.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct HistoryEntry {
+    /// Position in the file-wide change order
+    pub seq: i64,
+    pub operation: HistoryOperation,
+    /// The attestation as stored after the change; `None` for deletes
+    pub attestation: Option<Attestation>,
+    /// Wall-clock time of the change, Unix milliseconds
+    pub applied_at: i64,
+}
+
+/// How much history to keep. Both limits may be set; the stricter wins.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HistoryRetention {
+    /// Drop changes older than this
+    #[serde(default)]
+    pub max_age_ms: Option<i64>,
+    /// Keep roughly this many of the newest entries
+    #[serde(default)]
+    pub max_rows: Option<usize>,
+}
+
+/// Outcome of [`SqliteStore::prune_history`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct HistoryPruneReport {
+    pub removed: usize,
+    /// Earliest instant `query_as_of` accepts after pruning
+    pub horizon_ms: i64,
+}
+
+fn now_ms() -> i64 {
+    chrono::Utc::now().timestamp_millis()
+}
+
+/// Whether `namespace` records history.
+pub(crate) fn history_enabled(conn: &Connection, namespace: &str) -> Result<bool> {
+    Ok(conn
+        .prepare_cached("SELECT 1 FROM attestation_history_namespaces WHERE namespace = ?")?
+        .exists([namespace])?)
+}
+
+/// Append a put/update of `attestation` if `namespace` records history.
+pub(crate) fn record_write(
+    conn: &Connection,
+    namespace: &str,
+    operation: HistoryOperation,
+    attestation: &Attestation,
+) -> Result<()> {
+    if !history_enabled(conn, namespace)? {
+        return Ok(());
+    }
+    insert_entry(
+        conn,
+        namespace,
+        operation,
+        &attestation.id,
+        Some(attestation),
+    )
+}
+
+/// Append deletions of `ids` if `namespace` records history. Call before the
+/// rows are deleted.
+pub(crate) fn record_deletes<'a>(
+    conn: &Connection,
+    namespace: &str,
+    ids: impl IntoIterator<Item = &'a str>,
+) -> Result<()> {
+    if !history_enabled(conn, namespace)? {
+        return Ok(());
+    }
+    for id in ids {
+        insert_entry(conn, namespace, HistoryOperation::Delete, id, None)?;
+    }
+    Ok(())
+}
+
+/// Append deletions of every row in `namespace`, e.g. before `clear`.
+pub(crate) fn record_delete_all(conn: &Connection, namespace: &str) -> Result<()> {
+    if !history_enabled(conn, namespace)? {
+        return Ok(());
+    }
+    conn.execute(
+        "INSERT INTO attestation_history (namespace, attestation_id, operation, attestation, applied_at)
+         SELECT namespace, id, 'delete', NULL, ?2 FROM attestations WHERE namespace = ?1
+         ORDER BY rowid",
+        rusqlite::params![namespace, now_ms()],
+    )?;
+    Ok(())
+}
+
+fn insert_entry(
+    conn: &Connection,
+    namespace: &str,
+    operation: HistoryOperation,
+    id: &str,
+    attestation: Option<&Attestation>,
+) -> Result<()> {
+    let json = attestation.map(serde_json::to_string).transpose()?;
+    conn.prepare_cached(
+        "INSERT INTO attestation_history (namespace, attestation_id, operation, attestation, applied_at)
+         VALUES (?, ?, ?, ?, ?)",
+    )?
+    .execute(rusqlite::params![
+        namespace,
+        id,
+        operation.as_str(),
+        json,
+        now_ms()
+    ])?;
+    Ok(())
+}
+
+fn decode_entry(
+    seq: i64,
+    operation: String,
+    json: Option<String>,
+    applied_at: i64,
+) -> StoreResult<HistoryEntry> {
+    let operation = HistoryOperation::parse(&operation).ok_or_else(|| {
+        StoreError::Corruption(format!(
+            "history entry {}: unknown operation {}",
+            seq, operation
+        ))
+    })?;
+    let attestation = json
+        .map(|j| serde_json::from_str(&j))
+        .transpose()
+        .map_err(|e| StoreError::Corruption(format!("history entry {}: {}", seq, e)))?;
+    Ok(HistoryEntry {
+        seq,
+        operation,
+        attestation,
+        applied_at,
+    })
+}
+
+impl SqliteStore {
+    /// Start recording history for this store's namespace. The rows it holds
+    /// now are recorded as `baseline` entries, so enabling on an existing
+    /// database makes the current state the first version of each
+    /// attestation. Returns the number of baseline entries; 0 when history
+    /// was already enabled.
+    pub fn enable_history(&mut self) -> StoreResult<usize> {
+        if history_enabled(&self.conn, &self.namespace)? {
+            return Ok(0);
+        }
+        let current = self.query(&AxFilter::default())?.attestations;
+        let tx = self
+            .conn
+            .unchecked_transaction()
+            .map_err(SqliteError::from)?;
+        let now = now_ms();
+        tx.execute(
+            "INSERT INTO attestation_history_namespaces (namespace, enabled_at, horizon_ms)
+             VALUES (?1, ?2, ?2)",
+            rusqlite::params![self.namespace, now],
+        )
+        .map_err(SqliteError::from)?;
+        for attestation in &current {
+            insert_entry(
+                &tx,
+                &self.namespace,
+                HistoryOperation::Baseline,
+                &attestation.id,
+                Some(attestation),
+            )?;
+        }
+        tx.commit().map_err(SqliteError::from)?;
+        Ok(current.len())
+    }
+
+    /// Stop recording history for this namespace and discard what was
+    /// recorded: a log with a gap would answer `query_as_of` wrongly.
+    /// Returns the number of entries removed.
+    pub fn disable_history(&mut self) -> StoreResult<usize> {
+        let tx = self
+            .conn
+            .unchecked_transaction()
+            .map_err(SqliteError::from)?;
+        tx.execute(
+            "DELETE FROM attestation_history_namespaces WHERE namespace = ?",
+            [&self.namespace],
+        )
+        .map_err(SqliteError::from)?;
+        let removed = tx
+            .execute(
+                "DELETE FROM attestation_history WHERE namespace = ?",
+                [&self.namespace],
+            )
+            .map_err(SqliteError::from)?;
+        tx.commit().map_err(SqliteError::from)?;
+        Ok(removed)
+    }
+
+    /// Whether this namespace records history.
+    pub fn history_enabled(&self) -> StoreResult<bool> {
+        Ok(history_enabled(&self.conn, &self.namespace)?)
+    }
+
+    /// Earliest instant [`query_as_of`](Self::query_as_of) can reconstruct,
+    /// or `None` when history is disabled.
+    pub fn history_horizon(&self) -> StoreResult<Option<i64>> {
+        Ok(self
+            .conn
+            .query_row(
+                "SELECT horizon_ms FROM attestation_history_namespaces WHERE namespace = ?",
+                [&self.namespace],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(SqliteError::from)?)
+    }
+
+    /// Every recorded change to `id`, oldest first. Empty when the
+    /// attestation was never recorded (or history is disabled).
+    pub fn history_of(&self, id: &str) -> StoreResult<Vec<HistoryEntry>> {
+        let mut stmt = self
+            .conn
+            .prepare(
+                "SELECT seq, operation, attestation, applied_at FROM attestation_history
+                 WHERE namespace = ? AND attestation_id = ? ORDER BY seq",
+            )
+            .map_err(SqliteError::from)?;
+        let rows = stmt
+            .query_map([self.namespace.as_str(), id], |row| {
+                Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
+            })
+            .map_err(SqliteError::from)?
+            .collect::<std::result::Result<Vec<_>, rusqlite::Error>>()
+            .map_err(SqliteError::from)?;
+        rows.into_iter()
+            .map(|(seq, op, json, at)| decode_entry(seq, op, json, at))
+            .collect()
+    }
+
+    /// Run `filter` over the attestations visible at `as_of_ms`: for each ID,
+    /// its latest version written at or before that instant, unless that
+    /// change was a delete. Filtering uses the in-memory store's matching
+    /// rules. Fails when history is disabled or `as_of_ms` is before the
+    /// horizon.
+    pub fn query_as_of(&self, filter: &AxFilter, as_of_ms: i64) -> StoreResult<AxResult> {
+        let horizon = self.history_horizon()?.ok_or_else(|| {
+            StoreError::InvalidData(format!(
+                "history is not enabled for namespace {}",
+                self.namespace
+            ))
+        })?;
+        if as_of_ms < horizon {
+            return Err(StoreError::InvalidData(format!(
+                "{} is before the history horizon {}",
+                as_of_ms, horizon
+            )));
+        }
+
+        let mut stmt = self
+            .conn
+            .prepare(
+                "SELECT h.seq, h.attestation FROM attestation_history h
+                 JOIN (
+                     SELECT MAX(seq) AS seq FROM attestation_history
+                     WHERE namespace = ?1 AND applied_at <= ?2
+                     GROUP BY attestation_id
+                 ) latest ON h.seq = latest.seq
+                 WHERE h.operation != 'delete'
+                 ORDER BY h.seq",
+            )
+            .map_err(SqliteError::from)?;
+        let rows = stmt
+            .query_map(rusqlite::params![self.namespace, as_of_ms], |row| {
+                Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
+            })
+            .map_err(SqliteError::from)?
+            .collect::<std::result::Result<Vec<_>, rusqlite::Error>>()
+            .map_err(SqliteError::from)?;
+        let visible = rows
+            .into_iter()
+            .map(|(seq, json)| {
+                serde_json::from_str(&json)
+                    .map_err(|e| StoreError::Corruption(format!("history entry {}: {}", seq, e)))
+            })
+            .collect::<StoreResult<Vec<Attestation>>>()?;
+        MemoryStore::with_attestations(visible).query(filter)
+    }
+
+    /// Drop history older than `retention` allows, measured from `now_ms`.
+    ///
+    /// For each attestation the newest change before the cutoff is kept
+    /// (unless it was a delete), so `query_as_of` stays exact for every
+    /// instant from the new horizon on; `max_rows` can therefore be exceeded
+    /// by up to one entry per live attestation.
+    pub fn prune_history(
+        &mut self,
+        retention: &HistoryRetention,
+        now_ms: i64,
+    ) -> StoreResult<HistoryPruneReport> {
+        let horizon = self.history_horizon()?.ok_or_else(|| {
+            StoreError::InvalidData(format!(
+                "history is not enabled for namespace {}",
+                self.namespace
+            ))
+        })?;
+
+        let mut cutoff = retention.max_age_ms.map(|age| now_ms - age);
+        if let Some(max_rows) = retention.max_rows {
+            // applied_at of the oldest entry among the newest `max_rows`
+            let oldest_kept: Option<i64> = self
+                .conn
+                .query_row(
+                    "SELECT applied_at FROM attestation_history WHERE namespace = ?
+                     ORDER BY seq DESC LIMIT 1 OFFSET ?",
+                    rusqlite::params![self.namespace, max_rows.saturating_sub(1) as i64],
+                    |row| row.get(0),
+                )
+                .optional()
+                .map_err(SqliteError::from)?;
+            if max_rows == 0 {
+                cutoff = Some(now_ms);
+            } else if let Some(at) = oldest_kept {
+                cutoff = Some(cutoff.map_or(at, |c| c.max(at)));
+            }
+        }
+        let Some(cutoff) = cutoff.filter(|&c| c > horizon) else {
+            return Ok(HistoryPruneReport {
+                removed: 0,
+                horizon_ms: horizon,
+            });
+        };
+
+        let tx = self
+            .conn
+            .unchecked_transaction()
+            .map_err(SqliteError::from)?;
+        // Superseded before the cutoff, or the last word before it was a delete
+        let removed = tx
+            .execute(
+                "DELETE FROM attestation_history
+                 WHERE namespace = ?1 AND applied_at < ?2 AND (
+                     operation = 'delete' OR seq NOT IN (
+                         SELECT MAX(seq) FROM attestation_history
+                         WHERE namespace = ?1 AND applied_at < ?2
+                         GROUP BY attestation_id
+                     )
+                 )",
+                rusqlite::params![self.namespace, cutoff],
+            )
+            .map_err(SqliteError::from)?;
+        tx.execute(
+            "UPDATE attestation_history_namespaces SET horizon_ms = ? WHERE namespace = ?",
+            rusqlite::params![cutoff, self.namespace],
+        )
+        .map_err(SqliteError::from)?;
+        tx.commit().map_err(SqliteError::from)?;
+        Ok(HistoryPruneReport {
+            removed,
+            horizon_ms: cutoff,
+        })
+    }
+}
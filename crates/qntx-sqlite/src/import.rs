@@ -0,0 +1,128 @@
+//! Bulk import of external data (CSV, JSON-LD) into a store.
+//!
+//! Thin CLI-facing layer over the `qntx_core::import` adapters: read a file,
+//! build attestations, and write them in batches. Each batch runs inside a
+//! SAVEPOINT, so a failing batch rolls back on its own and a large file
+//! doesn't pay one commit per row.
+//!
+//! Imported IDs are content-derived, so re-running an import is idempotent:
+//! attestations already in the store are skipped and counted.
+
+use std::path::Path;
+
+use qntx_core::import::{
+    import_csv, import_jsonld, CsvImportSpec, ImportReport, JsonLdMapping, RowError,
+};
+use qntx_core::storage::{AttestationStore, StoreError};
+use qntx_core::Attestation;
+
+use crate::error::SqliteError;
+use crate::store::SqliteStore;
+
+type StoreResult<T> = Result<T, StoreError>;
+
+/// Attestations written per SAVEPOINT when the caller has no preference
+pub const DEFAULT_IMPORT_BATCH: usize = 500;
+
+/// What an import did to the store.
+#[derive(Debug, Clone, Default)]
+pub struct ImportSummary {
+    /// Newly stored attestations
+    pub imported: usize,
+    /// Attestations skipped because their ID was already stored
+    pub already_present: usize,
+    /// Rows dropped as duplicates within the input
+    pub duplicates: usize,
+    /// Rows that could not be imported, with line numbers
+    pub errors: Vec<RowError>,
+}
+
+impl SqliteStore {
+    /// Store attestations in SAVEPOINT batches of `batch_size` (0 means
+    /// [`DEFAULT_IMPORT_BATCH`]). Attestations whose ID exists are skipped.
+    /// Returns `(stored, already_present)`.
+    pub fn put_batched(
+        &mut self,
+        attestations: Vec<Attestation>,
+        batch_size: usize,
+    ) -> StoreResult<(usize, usize)> {
+        let batch_size = if batch_size == 0 {
+            DEFAULT_IMPORT_BATCH
+        } else {
+            batch_size
+        };
+
+        let mut stored = 0;
+        let mut existing = 0;
+        let mut batches = attestations.into_iter().peekable();
+        while batches.peek().is_some() {
+            self.connection()
+                .execute_batch("SAVEPOINT import_batch")
+                .map_err(SqliteError::from)?;
+
+            let mut result = Ok(());
+            for attestation in batches.by_ref().take(batch_size) {
+                match self.put(attestation) {
+                    Ok(()) => stored += 1,
+                    Err(StoreError::AlreadyExists(_)) => existing += 1,
+                    Err(e) => {
+                        result = Err(e);
+                        break;
+                    }
+                }
+            }
+
+            if let Err(e) = result {
+                let _ = self.connection().execute_batch(
+                    "ROLLBACK TO SAVEPOINT import_batch; RELEASE SAVEPOINT import_batch",
+                );
+                return Err(e);
+            }
+            self.connection()
+                .execute_batch("RELEASE SAVEPOINT import_batch")
+                .map_err(SqliteError::from)?;
+        }
+
+        Ok((stored, existing))
+    }
+
+    fn store_report(
+        &mut self,
+        report: ImportReport,
+        batch_size: usize,
+    ) -> StoreResult<ImportSummary> {
+        let (imported, already_present) = self.put_batched(report.attestations, batch_size)?;
+        Ok(ImportSummary {
+            imported,
+            already_present,
+            duplicates: report.duplicates,
+            errors: report.errors,
+        })
+    }
+
+    /// Import a CSV file mapped by `spec`. Bad rows are returned in
+    /// `errors`; only unreadable files or spec mismatches fail the call.
+    pub fn import_csv_file(
+        &mut self,
+        path: impl AsRef<Path>,
+        spec: &CsvImportSpec,
+        batch_size: usize,
+    ) -> StoreResult<ImportSummary> {
+        let file = std::fs::File::open(path).map_err(SqliteError::from)?;
+        let report = import_csv(std::io::BufReader::new(file), spec)?;
+        self.store_report(report, batch_size)
+    }
+
+    /// Import a JSON-LD file mapped by `mapping`. Bad nodes are returned in
+    /// `errors`; only unreadable or non-JSON files fail the call.
+    pub fn import_jsonld_file(
+        &mut self,
+        path: impl AsRef<Path>,
+        mapping: &JsonLdMapping,
+        batch_size: usize,
+    ) -> StoreResult<ImportSummary> {
+        let document = std::fs::read_to_string(path).map_err(SqliteError::from)?;
+        let report = import_jsonld(&document, mapping)?;
+        self.store_report(report, batch_size)
+    }
+}
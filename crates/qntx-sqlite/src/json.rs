@@ -3,11 +3,138 @@
 //! Handles conversion between Rust types and SQLite JSON columns,
 //! matching the format used by the Go implementation.
 
+use qntx_core::Attestation;
+use serde::Serialize;
 use serde_json::Value;
 use std::collections::HashMap;
 
 use crate::error::Result;
 
+/// Raw row tuple from the attestations table, before conversion to Attestation.
+///
+/// Column order: id, subjects, predicates, contexts, actors, timestamp, source,
+/// attributes, created_at, signature, signer_did, revision, confidence.
+pub type AttestationRow = (
+    String,
+    String,
+    String,
+    String,
+    String,
+    String,
+    String,
+    Option<String>,
+    String,
+    Option<Vec<u8>>,
+    Option<String>,
+    u64,
+    Option<f32>,
+);
+
+/// Read a result row into an [`AttestationRow`].
+///
+/// `revision` and `confidence` are read only when the row has a twelfth and
+/// thirteenth column, so raw queries that select the original eleven columns
+/// still decode (with revision 0 and no confidence).
+pub fn read_attestation_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<AttestationRow> {
+    let columns = row.as_ref().column_count();
+    let revision = if columns > 11 {
+        row.get::<_, i64>(11)? as u64
+    } else {
+        0
+    };
+    let confidence = if columns > 12 {
+        row.get::<_, Option<f64>>(12)?.map(|c| c as f32)
+    } else {
+        None
+    };
+    Ok((
+        row.get::<_, String>(0)?,
+        row.get::<_, String>(1)?,
+        row.get::<_, String>(2)?,
+        row.get::<_, String>(3)?,
+        row.get::<_, String>(4)?,
+        row.get::<_, String>(5)?,
+        row.get::<_, String>(6)?,
+        row.get::<_, Option<String>>(7)?,
+        row.get::<_, String>(8)?,
+        row.get::<_, Option<Vec<u8>>>(9)?,
+        row.get::<_, Option<String>>(10)?,
+        revision,
+        confidence,
+    ))
+}
+
+/// A stored row that could not be decoded into an Attestation.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CorruptRow {
+    /// Attestation ID of the row
+    pub id: String,
+    /// Column that failed to decode (e.g. "attributes")
+    pub column: &'static str,
+    /// Decoder error message
+    pub error: String,
+}
+
+impl std::fmt::Display for CorruptRow {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "row {} column {}: {}", self.id, self.column, self.error)
+    }
+}
+
+/// Decode one attestations row, reporting which column failed.
+///
+/// Errors are per-row so callers can skip a bad row instead of failing a
+/// whole result set.
+pub fn decode_attestation_row(row: AttestationRow) -> std::result::Result<Attestation, CorruptRow> {
+    let (
+        id,
+        subjects_json,
+        predicates_json,
+        contexts_json,
+        actors_json,
+        timestamp_str,
+        source,
+        attributes_json,
+        created_at_str,
+        signature,
+        signer_did,
+        revision,
+        confidence,
+    ) = row;
+
+    let corrupt = |column: &'static str, e: crate::error::SqliteError| CorruptRow {
+        id: id.clone(),
+        column,
+        error: e.to_string(),
+    };
+
+    let subjects = deserialize_string_vec(&subjects_json).map_err(|e| corrupt("subjects", e))?;
+    let predicates =
+        deserialize_string_vec(&predicates_json).map_err(|e| corrupt("predicates", e))?;
+    let contexts = deserialize_string_vec(&contexts_json).map_err(|e| corrupt("contexts", e))?;
+    let actors = deserialize_string_vec(&actors_json).map_err(|e| corrupt("actors", e))?;
+    let attributes =
+        deserialize_attributes(attributes_json).map_err(|e| corrupt("attributes", e))?;
+    let timestamp = sql_to_timestamp(&timestamp_str).map_err(|e| corrupt("timestamp", e))?;
+    let created_at = sql_to_timestamp(&created_at_str).map_err(|e| corrupt("created_at", e))?;
+
+    Ok(Attestation {
+        id,
+        subjects,
+        predicates,
+        contexts,
+        actors,
+        timestamp,
+        source,
+        attributes,
+        created_at,
+        signature,
+        signer_did,
+        revision,
+        confidence,
+    })
+}
+
 /// Serialize a Vec<String> to JSON string for SQLite storage
 pub fn serialize_string_vec(vec: &[String]) -> Result<String> {
     Ok(serde_json::to_string(vec)?)
@@ -15,13 +142,63 @@ pub fn serialize_string_vec(vec: &[String]) -> Result<String> {
 
 /// Deserialize a JSON string from SQLite to Vec<String>.
 /// Handles JSON "null" and empty strings as empty vec.
+///
+/// Arrays of strings without escapes (what `serialize_string_vec` writes for
+/// ordinary terms) take a direct scan; anything else goes through serde_json.
 pub fn deserialize_string_vec(json: &str) -> Result<Vec<String>> {
     if json == "null" || json.is_empty() {
         return Ok(Vec::new());
     }
+    if let Some(values) = parse_plain_string_array(json) {
+        return Ok(values);
+    }
     Ok(serde_json::from_str(json)?)
 }
 
+/// Parse `["a","b",...]` whose strings hold no escapes or control
+/// characters. None for any other input, including valid JSON the scan
+/// doesn't handle; the caller falls back to serde_json.
+fn parse_plain_string_array(json: &str) -> Option<Vec<String>> {
+    let bytes = json.as_bytes();
+    let skip_ws = |mut i: usize| {
+        while i < bytes.len() && matches!(bytes[i], b' ' | b'\t' | b'\n' | b'\r') {
+            i += 1;
+        }
+        i
+    };
+
+    let mut i = skip_ws(0);
+    if bytes.get(i) != Some(&b'[') {
+        return None;
+    }
+    i = skip_ws(i + 1);
+    let mut values = Vec::new();
+    if bytes.get(i) == Some(&b']') {
+        return (skip_ws(i + 1) == bytes.len()).then_some(values);
+    }
+    loop {
+        if bytes.get(i) != Some(&b'"') {
+            return None;
+        }
+        let start = i + 1;
+        let len = bytes[start..]
+            .iter()
+            .position(|&b| b == b'"' || b == b'\\' || b < 0x20)?;
+        let end = start + len;
+        if bytes[end] != b'"' {
+            return None;
+        }
+        // Quotes are ASCII, so both ends fall on char boundaries
+        values.push(json[start..end].to_string());
+        i = skip_ws(end + 1);
+        match bytes.get(i) {
+            Some(b',') => i = skip_ws(i + 1),
+            Some(b']') => return (skip_ws(i + 1) == bytes.len()).then_some(values),
+            _ => return None,
+        }
+    }
+}
+
 /// Serialize attributes HashMap to JSON string for SQLite storage
 pub fn serialize_attributes(attrs: &HashMap<String, Value>) -> Result<Option<String>> {
     if attrs.is_empty() {
@@ -95,6 +272,25 @@ mod tests {
         assert!(vec.is_empty());
     }
 
+    #[test]
+    fn test_plain_string_array_matches_serde() {
+        for json in [
+            r#"[]"#,
+            r#" [ "ALICE" , "BOB" ] "#,
+            r#"["","ünïcode","a b"]"#,
+            r#"["esc\"aped","tab\t"]"#,
+            r#"["\u0041"]"#,
+        ] {
+            let expected: Vec<String> = serde_json::from_str(json).unwrap();
+            assert_eq!(deserialize_string_vec(json).unwrap(), expected, "{}", json);
+        }
+        assert_eq!(parse_plain_string_array(r#"["a\"b"]"#), None);
+
+        for bad in [r#"["a""#, r#"["a",]"#, r#"["a"] x"#, r#"[1]"#, r#"{"a":1}"#] {
+            assert!(deserialize_string_vec(bad).is_err(), "{}", bad);
+        }
+    }
+
     #[test]
     fn test_serialize_empty_attributes() {
         let attrs = HashMap::new();
@@ -124,4 +320,30 @@ mod tests {
         let restored = sql_to_timestamp(&sql_str).unwrap();
         assert_eq!(original, restored);
     }
+
+    #[test]
+    fn test_decode_row_reports_corrupt_column() {
+        let row: AttestationRow = (
+            "AS-1".to_string(),
+            r#"["LUKE"]"#.to_string(),
+            r#"["trained_by"]"#.to_string(),
+            r#"["dagobah"]"#.to_string(),
+            r#"["human:yoda"]"#.to_string(),
+            "2024-01-01T00:00:00+00:00".to_string(),
+            "test".to_string(),
+            Some("{not json".to_string()),
+            "2024-01-01T00:00:00+00:00".to_string(),
+            None,
+            None,
+            1,
+            None,
+        );
+
+        let corrupt = decode_attestation_row(row).unwrap_err();
+        assert_eq!(corrupt.id, "AS-1");
+        assert_eq!(corrupt.column, "attributes");
+        assert!(corrupt
+            .to_string()
+            .starts_with("row AS-1 column attributes:"));
+    }
 }
@@ -10,6 +10,24 @@
 //! - Supports in-memory databases for testing
 //! - Thread-safe with proper connection handling
 //! - Optional quota enforcement via `BoundedStore`
+//! - Workspace namespaces: several workspaces share one file, isolated in SQL
+//!   (`SqliteStore::scoped`, `SqliteStore::open_scoped`)
+//! - Batched CSV / JSON-LD import (`SqliteStore::import_csv_file`,
+//!   `SqliteStore::import_jsonld_file`)
+//! - Online maintenance: checkpoints, vacuum, `ANALYZE`, integrity reports
+//!   and an auto-checkpoint `MaintenancePolicy` (see `maintenance`)
+//! - Incremental, filtered mirroring into another store
+//!   (`SqliteStore::mirror_to`, see `mirror`)
+//! - Opt-in attestation history with time-travel queries
+//!   (`SqliteStore::enable_history`, `SqliteStore::query_as_of`, see `history`)
+//! - A changefeed numbering every write per namespace, for consumers that
+//!   poll for what changed (`ChangeFeed` on `SqliteStore`, see `changes`)
+//! - Streaming CSV / Parquet export of query results
+//!   (`SqliteStore::export_query`, see `export`; Parquet behind the `parquet` feature)
+//! - Durability profiles and a prepared-statement cache for busy servers
+//!   (`SqliteStore::open_with`, see `profile`)
+//! - Validated read-only snapshots of copied files with live refresh
+//!   (`SqliteStore::open_read_only`, see `snapshot`)
 //!
 //! # Example: Basic Usage
 //!
@@ -63,12 +81,20 @@
 //! ```
 
 pub mod bounded;
+pub mod changes;
 pub mod distill;
 pub mod enforcement;
 pub mod error;
+pub mod export;
 pub mod flight_recorder;
+pub mod history;
+pub mod import;
 pub mod json;
+pub mod maintenance;
 pub mod migrate;
+pub mod mirror;
+pub mod profile;
+pub mod snapshot;
 pub mod store;
 pub mod vec;
 
@@ -84,6 +110,21 @@ pub mod ffi;
 pub mod sql_ffi;
 
 // Re-export main types
-pub use bounded::{BoundedStore, StorageQuotas};
+pub use bounded::{BoundedPutOutcome, BoundedStore, EvictionPolicy, StorageQuotas};
 pub use error::{Result, SqliteError};
-pub use store::SqliteStore;
+pub use export::{
+    ArrayEncoding, AttributeColumn, AttributeEncoding, AttributeType, ExportFormat, ExportOptions,
+    ExportSummary,
+};
+pub use history::{HistoryEntry, HistoryOperation, HistoryPruneReport, HistoryRetention};
+pub use import::{ImportSummary, DEFAULT_IMPORT_BATCH};
+pub use json::CorruptRow;
+pub use maintenance::{
+    CheckpointMode, CheckpointReport, IntegrityReport, MaintenancePolicy, PageStats, VacuumReport,
+};
+pub use mirror::{
+    mirror, MirrorChange, MirrorChangeKind, MirrorError, MirrorOptions, MirrorReport,
+};
+pub use profile::{OpenOptions, StoreProfile, DEFAULT_STATEMENT_CACHE};
+pub use snapshot::{ReadOnlyStore, SnapshotInfo, SnapshotWatcher, SwapCallback};
+pub use store::{drop_namespace_token, RepairAction, SqliteStore, DEFAULT_NAMESPACE};
@@ -0,0 +1,316 @@
+//! Online database maintenance: WAL checkpoints, vacuum, planner statistics
+//! and integrity checks, without taking the process down.
+//!
+//! File-backed stores run in WAL mode with automatic checkpoints disabled
+//! (see [`SqliteStore::open`]), so a long-running server needs these to keep
+//! the WAL and free pages in check. What each operation locks:
+//!
+//! | Operation | Readers | Writers |
+//! |---|---|---|
+//! | `checkpoint(Passive)` | never blocked | never blocked; frames still in use are skipped |
+//! | `checkpoint(Full)` | never blocked | blocked until every WAL frame is copied (busy timeout applies) |
+//! | `checkpoint(Truncate)` | waits for readers to leave the WAL | blocked for the copy plus truncation |
+//! | `vacuum` | keep their snapshot | blocked for the full rewrite, O(database size) |
+//! | `incremental_vacuum` | keep their snapshot | blocked while `pages` pages are freed |
+//! | `analyze` | never blocked | blocked while indexes are scanned, O(index size) |
+//! | `integrity_check` | never blocked | writes through the same store wait, O(database size) |
+//! | `ReadConn::integrity_check` | never blocked | never blocked (read transaction on its own connection) |
+//!
+//! Reader connections opened with [`SqliteStore::open_read_conn`] keep the
+//! WAL in use, so a Truncate checkpoint only shrinks the file once they are
+//! closed (the FFI `storage_wal_checkpoint` does that dance for Go).
+
+use serde::{Deserialize, Serialize};
+
+use qntx_core::storage::{StorageErrorKind, StoreError};
+
+use crate::error::SqliteError;
+use crate::store::{ReadConn, SqliteStore};
+
+type StoreResult<T> = Result<T, StoreError>;
+
+/// Writes between WAL size checks when the policy doesn't say
+pub const DEFAULT_MAINTENANCE_CHECK_EVERY: u64 = 100;
+
+/// `PRAGMA wal_checkpoint` mode.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckpointMode {
+    /// Copy what can be copied without waiting on anyone
+    #[default]
+    Passive,
+    /// Wait for writers, then copy every frame
+    Full,
+    /// Full, then wait for readers and truncate the WAL file to zero bytes
+    Truncate,
+}
+
+impl CheckpointMode {
+    fn pragma(self) -> &'static str {
+        match self {
+            CheckpointMode::Passive => "PRAGMA wal_checkpoint(PASSIVE)",
+            CheckpointMode::Full => "PRAGMA wal_checkpoint(FULL)",
+            CheckpointMode::Truncate => "PRAGMA wal_checkpoint(TRUNCATE)",
+        }
+    }
+
+    /// Parse `passive`, `full` or `truncate` (case-insensitive).
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "passive" => Some(CheckpointMode::Passive),
+            "full" => Some(CheckpointMode::Full),
+            "truncate" => Some(CheckpointMode::Truncate),
+            _ => None,
+        }
+    }
+}
+
+/// Outcome of a WAL checkpoint.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct CheckpointReport {
+    pub mode: CheckpointMode,
+    /// A Full/Truncate checkpoint could not finish because of readers or writers
+    pub busy: bool,
+    /// Frames in the WAL (-1 when the database is not in WAL mode)
+    pub wal_pages: i64,
+    /// Frames copied back into the database (-1 when not in WAL mode)
+    pub checkpointed_pages: i64,
+}
+
+/// Page usage of the main database file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct PageStats {
+    pub page_size: i64,
+    pub page_count: i64,
+    /// Unused pages that vacuum can reclaim
+    pub freelist_count: i64,
+}
+
+impl PageStats {
+    pub fn size_bytes(&self) -> i64 {
+        self.page_size * self.page_count
+    }
+}
+
+/// Page usage around a vacuum.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct VacuumReport {
+    pub before: PageStats,
+    pub after: PageStats,
+}
+
+/// Structured `PRAGMA integrity_check` result.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct IntegrityReport {
+    pub ok: bool,
+    /// One entry per problem SQLite reported; empty when `ok`
+    pub problems: Vec<String>,
+}
+
+/// Automatic upkeep attached with [`SqliteStore::set_maintenance_policy`].
+///
+/// Checked opportunistically: every `check_every` writes the store looks at
+/// the WAL file size and checkpoints with `checkpoint_mode` once it exceeds
+/// `wal_limit_bytes`. The limit is also installed as `journal_size_limit`,
+/// so a WAL that has been fully checkpointed is cut back to it on reuse.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MaintenancePolicy {
+    pub wal_limit_bytes: u64,
+    #[serde(default)]
+    pub checkpoint_mode: CheckpointMode,
+    #[serde(default = "default_check_every")]
+    pub check_every: u64,
+}
+
+fn default_check_every() -> u64 {
+    DEFAULT_MAINTENANCE_CHECK_EVERY
+}
+
+impl MaintenancePolicy {
+    /// Passive checkpoint whenever the WAL grows past `mb` megabytes.
+    pub fn wal_limit_mb(mb: u64) -> Self {
+        Self {
+            wal_limit_bytes: mb * 1024 * 1024,
+            checkpoint_mode: CheckpointMode::Passive,
+            check_every: DEFAULT_MAINTENANCE_CHECK_EVERY,
+        }
+    }
+}
+
+impl SqliteStore {
+    /// Run a WAL checkpoint in `mode`. See the module docs for what each mode
+    /// blocks.
+    pub fn checkpoint(&self, mode: CheckpointMode) -> StoreResult<CheckpointReport> {
+        let (busy, wal_pages, checkpointed_pages) = self
+            .conn
+            .query_row(mode.pragma(), [], |row| {
+                Ok((row.get::<_, i64>(0)?, row.get(1)?, row.get(2)?))
+            })
+            .map_err(SqliteError::from)?;
+        Ok(CheckpointReport {
+            mode,
+            busy: busy != 0,
+            wal_pages,
+            checkpointed_pages,
+        })
+    }
+
+    /// Size of the `-wal` file in bytes, or None for in-memory stores.
+    pub fn wal_size_bytes(&self) -> Option<u64> {
+        let path = self.db_path.as_deref()?;
+        Some(
+            std::fs::metadata(format!("{}-wal", path))
+                .map(|m| m.len())
+                .unwrap_or(0),
+        )
+    }
+
+    /// Current page usage of the main database.
+    pub fn page_stats(&self) -> StoreResult<PageStats> {
+        let pragma = |name: &str| {
+            self.conn
+                .query_row(&format!("PRAGMA {}", name), [], |row| row.get::<_, i64>(0))
+                .map_err(SqliteError::from)
+        };
+        Ok(PageStats {
+            page_size: pragma("page_size")?,
+            page_count: pragma("page_count")?,
+            freelist_count: pragma("freelist_count")?,
+        })
+    }
+
+    /// Rebuild the database file, dropping free pages. Blocks writers for the
+    /// whole rewrite and needs free disk space of about the database size.
+    /// In WAL mode the rewritten pages land in the WAL; follow with a
+    /// Truncate checkpoint to shrink the files on disk.
+    pub fn vacuum(&self) -> StoreResult<VacuumReport> {
+        let before = self.page_stats()?;
+        self.conn
+            .execute_batch("VACUUM")
+            .map_err(SqliteError::from)?;
+        let after = self.page_stats()?;
+        Ok(VacuumReport { before, after })
+    }
+
+    /// Switch the database to `auto_vacuum = INCREMENTAL` (required by
+    /// [`incremental_vacuum`](Self::incremental_vacuum)). Runs a full vacuum
+    /// once to apply it.
+    pub fn enable_incremental_vacuum(&self) -> StoreResult<VacuumReport> {
+        self.conn
+            .pragma_update(None, "auto_vacuum", "INCREMENTAL")
+            .map_err(SqliteError::from)?;
+        self.vacuum()
+    }
+
+    /// Return up to `pages` free pages to the filesystem (0 = all of them).
+    /// Returns the number of pages freed. Fails unless incremental
+    /// auto-vacuum is enabled, since SQLite would silently do nothing.
+    pub fn incremental_vacuum(&self, pages: usize) -> StoreResult<i64> {
+        let auto_vacuum: i64 = self
+            .conn
+            .query_row("PRAGMA auto_vacuum", [], |row| row.get(0))
+            .map_err(SqliteError::from)?;
+        if auto_vacuum != 2 {
+            return Err(StoreError::Backend(
+                "incremental_vacuum requires auto_vacuum=INCREMENTAL (see enable_incremental_vacuum)"
+                    .into(),
+            ));
+        }
+
+        let before = self.page_stats()?.freelist_count;
+        // The pragma frees one page per step, so it has to be drained
+        (|| {
+            let mut stmt = self
+                .conn
+                .prepare(&format!("PRAGMA incremental_vacuum({})", pages))?;
+            let mut rows = stmt.query([])?;
+            while rows.next()?.is_some() {}
+            Ok(())
+        })()
+        .map_err(|e: rusqlite::Error| SqliteError::from(e))?;
+        Ok(before - self.page_stats()?.freelist_count)
+    }
+
+    /// Refresh the query planner's statistics (`ANALYZE`).
+    pub fn analyze(&self) -> StoreResult<()> {
+        self.conn
+            .execute_batch("ANALYZE")
+            .map_err(SqliteError::from)?;
+        Ok(())
+    }
+
+    /// Run `PRAGMA integrity_check` on the write connection, so this store's
+    /// writes wait until it finishes; use [`ReadConn::integrity_check`] to
+    /// check beside them. Damage SQLite reports, or that stops it from
+    /// running the check at all, ends up in the report; only other failures
+    /// are errors.
+    pub fn integrity_check(&self) -> StoreResult<IntegrityReport> {
+        integrity_report(&self.conn)
+    }
+
+    /// Attach (or with None, remove) automatic maintenance.
+    pub fn set_maintenance_policy(&mut self, policy: Option<MaintenancePolicy>) -> StoreResult<()> {
+        let limit = policy
+            .as_ref()
+            .map_or(-1, |p| i64::try_from(p.wal_limit_bytes).unwrap_or(i64::MAX));
+        self.conn
+            .pragma_update(None, "journal_size_limit", limit)
+            .map_err(SqliteError::from)?;
+        self.maintenance_policy = policy;
+        Ok(())
+    }
+
+    pub fn maintenance_policy(&self) -> Option<&MaintenancePolicy> {
+        self.maintenance_policy.as_ref()
+    }
+
+    /// Called after each write: checkpoint if the policy's WAL limit is
+    /// exceeded. Returns the checkpoint report when one ran.
+    pub(crate) fn run_maintenance_policy(&mut self) -> StoreResult<Option<CheckpointReport>> {
+        let Some(policy) = &self.maintenance_policy else {
+            return Ok(None);
+        };
+        if !self.put_count.is_multiple_of(policy.check_every.max(1)) {
+            return Ok(None);
+        }
+        match self.wal_size_bytes() {
+            Some(size) if size > policy.wal_limit_bytes => {
+                self.checkpoint(policy.checkpoint_mode).map(Some)
+            }
+            _ => Ok(None),
+        }
+    }
+}
+
+impl ReadConn {
+    /// Same as [`SqliteStore::integrity_check`], on this read connection:
+    /// neither readers nor writers wait for it.
+    pub fn integrity_check(&self) -> StoreResult<IntegrityReport> {
+        integrity_report(&self.conn)
+    }
+}
+
+fn integrity_report(conn: &rusqlite::Connection) -> StoreResult<IntegrityReport> {
+    let lines = (|| {
+        let mut stmt = conn.prepare("PRAGMA integrity_check")?;
+        let rows = stmt.query_map([], |row| row.get::<_, String>(0))?;
+        rows.collect::<Result<Vec<_>, _>>()
+    })()
+    .map_err(SqliteError::from);
+
+    match lines {
+        Ok(lines) if lines.len() == 1 && lines[0] == "ok" => Ok(IntegrityReport {
+            ok: true,
+            problems: Vec::new(),
+        }),
+        Ok(problems) => Ok(IntegrityReport {
+            ok: false,
+            problems,
+        }),
+        Err(e) if e.kind() == StorageErrorKind::Corruption => Ok(IntegrityReport {
+            ok: false,
+            problems: vec![e.to_string()],
+        }),
+        Err(e) => Err(e.into()),
+    }
+}
@@ -205,6 +205,30 @@ const MIGRATIONS: &[(&str, &str)] = &[
         "050",
         include_str!("../../../db/sqlite/migrations/050_junction_tables_nocase.sql"),
     ),
+    (
+        "052",
+        include_str!("../../../db/sqlite/migrations/052_add_revision_to_attestations.sql"),
+    ),
+    (
+        "053",
+        include_str!("../../../db/sqlite/migrations/053_add_namespace_to_attestations.sql"),
+    ),
+    (
+        "054",
+        include_str!("../../../db/sqlite/migrations/054_create_mirror_state.sql"),
+    ),
+    (
+        "055",
+        include_str!("../../../db/sqlite/migrations/055_create_attestation_history.sql"),
+    ),
+    (
+        "056",
+        include_str!("../../../db/sqlite/migrations/056_add_confidence_to_attestations.sql"),
+    ),
+    (
+        "057",
+        include_str!("../../../db/sqlite/migrations/057_create_attestation_changes.sql"),
+    ),
 ];
 
 /// Versions whose migrations are allowed to fail (they depend on sqlite-vec).
@@ -362,6 +386,44 @@ mod tests {
         // Should not fail
     }
 
+    #[test]
+    fn test_namespace_migration_backfills_default() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute("PRAGMA foreign_keys = ON", []).unwrap();
+        for (version, sql) in MIGRATIONS.iter().filter(|(v, _)| *v != "053") {
+            if apply_migration(&conn, version, sql).is_err() {
+                assert!(OPTIONAL_VERSIONS.contains(version));
+            }
+        }
+        conn.execute_batch(
+            "INSERT INTO attestations (id, subjects, predicates, contexts, actors, timestamp)
+             VALUES ('AS-1', '[\"ALICE\"]', '[\"knows\"]', '[\"work\"]', '[\"bob\"]', '2024-01-01');
+             INSERT INTO attestation_predicates (attestation_id, predicate) VALUES ('AS-1', 'knows');",
+        )
+        .unwrap();
+
+        migrate(&conn).unwrap();
+
+        let namespace: String = conn
+            .query_row(
+                "SELECT namespace FROM attestations WHERE id = 'AS-1'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(namespace, "default");
+
+        // Junction rows survive and still cascade through the composite key
+        conn.execute("DELETE FROM attestations WHERE id = 'AS-1'", [])
+            .unwrap();
+        let remaining: i64 = conn
+            .query_row("SELECT COUNT(*) FROM attestation_predicates", [], |row| {
+                row.get(0)
+            })
+            .unwrap();
+        assert_eq!(remaining, 0);
+    }
+
     #[test]
     fn test_migration_records_in_schema_migrations() {
         let conn = Connection::open_in_memory().unwrap();
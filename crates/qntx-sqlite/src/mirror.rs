@@ -0,0 +1,377 @@
+//! Mirror a filtered subset of a store into another store.
+//!
+//! The "publish" workflow: attestations matching an [`AxFilter`] are copied
+//! from a private workspace into a shared store, repeatedly. Each run only
+//! writes what changed since the last one:
+//!
+//! - an attestation the destination already holds with the same content hash
+//!   is skipped; one it holds with different content is updated
+//! - what a mirror has written is remembered in the `mirror_state` table of
+//!   the store doing the bookkeeping (normally the source), keyed by mirror
+//!   name and attestation ID, along with the content hash at copy time
+//! - with `propagate_deletes`, attestations that were mirrored before but no
+//!   longer match the filter are deleted from the destination, unless the
+//!   destination copy changed since it was mirrored
+//!
+//! New copies reach the destination through one
+//! [`put_many`](AttestationStore::put_many) per batch, and state is committed
+//! once per batch, so an interrupted run leaves every finished batch recorded
+//! and the next run picks up where it stopped. The content hash is [`Attestation::content_hash`]: the canonical hash of the
+//! attestation without its storage bookkeeping (`created_at`, `revision`).
+
+use std::collections::{BTreeMap, HashSet};
+
+use qntx_core::storage::{AttestationStore, QueryStore, StoreError};
+use qntx_core::{Attestation, AxFilter};
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+
+use crate::error::SqliteError;
+use crate::import::DEFAULT_IMPORT_BATCH;
+use crate::store::SqliteStore;
+
+type StoreResult<T> = Result<T, StoreError>;
+
+/// Mirror name used when the options don't give one
+pub const DEFAULT_MIRROR_NAME: &str = "default";
+
+/// How a mirror run behaves.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MirrorOptions {
+    /// Identifies the mirror in the state table; runs sharing a name share
+    /// their record of what was copied
+    pub name: String,
+    /// Attestations per state commit (0 means [`DEFAULT_IMPORT_BATCH`])
+    pub batch_size: usize,
+    /// Delete previously mirrored attestations that no longer match
+    pub propagate_deletes: bool,
+    /// Report what would change without writing to either store
+    pub dry_run: bool,
+    /// Recorded with every state row; a UUID is generated when unset
+    pub run_id: Option<String>,
+}
+
+impl Default for MirrorOptions {
+    fn default() -> Self {
+        Self {
+            name: DEFAULT_MIRROR_NAME.to_string(),
+            batch_size: 0,
+            propagate_deletes: false,
+            dry_run: false,
+            run_id: None,
+        }
+    }
+}
+
+/// What a run did (or, in a dry run, would do) to one attestation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MirrorChangeKind {
+    /// Not in the destination yet
+    Copy,
+    /// In the destination with different content
+    Update,
+    /// Mirrored before, no longer matches the filter
+    Delete,
+}
+
+/// A change made to the destination.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MirrorChange {
+    pub id: String,
+    pub kind: MirrorChangeKind,
+}
+
+/// An attestation that could not be mirrored. The run carries on without it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MirrorError {
+    pub id: String,
+    pub error: String,
+}
+
+/// Outcome of a mirror run.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MirrorReport {
+    pub run_id: String,
+    pub dry_run: bool,
+    /// Attestations newly written to the destination
+    pub copied: usize,
+    /// Destination attestations overwritten with changed content
+    pub updated: usize,
+    /// Matching attestations the destination already had unchanged
+    pub skipped: usize,
+    /// Previously mirrored attestations removed from the destination
+    pub deleted: usize,
+    pub errors: Vec<MirrorError>,
+    /// Every copy, update and delete, in the order they were made
+    pub changes: Vec<MirrorChange>,
+}
+
+impl MirrorReport {
+    fn record(&mut self, id: &str, kind: MirrorChangeKind) {
+        match kind {
+            MirrorChangeKind::Copy => self.copied += 1,
+            MirrorChangeKind::Update => self.updated += 1,
+            MirrorChangeKind::Delete => self.deleted += 1,
+        }
+        self.changes.push(MirrorChange {
+            id: id.to_string(),
+            kind,
+        });
+    }
+
+    fn error(&mut self, id: &str, error: impl ToString) {
+        self.errors.push(MirrorError {
+            id: id.to_string(),
+            error: error.to_string(),
+        });
+    }
+}
+
+/// Hex content hash used to compare source and destination copies.
+pub fn mirror_content_hash(attestation: &Attestation) -> String {
+    attestation.content_hash()
+}
+
+/// Mirror the attestations of `source` matching `filter` into `dest`,
+/// keeping the mirror's bookkeeping in `state`.
+///
+/// Per-attestation failures end up in [`MirrorReport::errors`]; only failing
+/// to query the source or to write the state table fails the call.
+/// `propagate_deletes` refuses a filter with a `limit`, since attestations cut
+/// off by the limit would look deleted.
+pub fn mirror<S, D>(
+    source: &S,
+    dest: &mut D,
+    state: &SqliteStore,
+    filter: &AxFilter,
+    options: &MirrorOptions,
+) -> StoreResult<MirrorReport>
+where
+    S: QueryStore + ?Sized,
+    D: AttestationStore + ?Sized,
+{
+    if options.propagate_deletes && filter.limit.is_some() {
+        return Err(StoreError::InvalidData(
+            "propagate_deletes needs a filter without limit".to_string(),
+        ));
+    }
+    let batch_size = if options.batch_size == 0 {
+        DEFAULT_IMPORT_BATCH
+    } else {
+        options.batch_size
+    };
+    let mut report = MirrorReport {
+        run_id: options
+            .run_id
+            .clone()
+            .unwrap_or_else(|| uuid::Uuid::new_v4().to_string()),
+        dry_run: options.dry_run,
+        ..Default::default()
+    };
+
+    let recorded = load_state(state, &options.name)?;
+    let matched = source.query(filter)?.attestations;
+    let mut seen = HashSet::with_capacity(matched.len());
+
+    for batch in matched.chunks(batch_size) {
+        let mut confirmed = Vec::with_capacity(batch.len());
+        let mut copies = Vec::new();
+        for attestation in batch {
+            seen.insert(attestation.id.as_str());
+            let hash = mirror_content_hash(attestation);
+            let known = recorded.get(&attestation.id) == Some(&hash);
+            match mirror_one(dest, attestation, &hash, known, options.dry_run) {
+                Ok(None) => report.skipped += 1,
+                Ok(Some(MirrorChangeKind::Copy)) if !options.dry_run => {
+                    // Written together below, so the chunk commits once
+                    copies.push((attestation, hash));
+                    continue;
+                }
+                Ok(Some(kind)) => report.record(&attestation.id, kind),
+                Err(e) => {
+                    report.error(&attestation.id, e);
+                    continue;
+                }
+            }
+            confirmed.push((attestation.id.as_str(), hash));
+        }
+        if !copies.is_empty() {
+            let written = dest.put_many(copies.iter().map(|(a, _)| (*a).clone()).collect());
+            for ((attestation, hash), result) in copies.into_iter().zip(written) {
+                match result {
+                    Ok(()) => {
+                        report.record(&attestation.id, MirrorChangeKind::Copy);
+                        confirmed.push((attestation.id.as_str(), hash));
+                    }
+                    Err(e) => report.error(&attestation.id, e),
+                }
+            }
+        }
+        if !options.dry_run {
+            save_state(state, &options.name, &report.run_id, &confirmed)?;
+        }
+    }
+
+    if options.propagate_deletes {
+        let mut forgotten = Vec::new();
+        for (id, hash) in recorded
+            .iter()
+            .filter(|(id, _)| !seen.contains(id.as_str()))
+        {
+            match dest.get(id) {
+                Ok(None) => forgotten.push(id.as_str()),
+                Ok(Some(copy)) if mirror_content_hash(&copy) == *hash => {
+                    if !options.dry_run {
+                        if let Err(e) = dest.delete(id) {
+                            report.error(id, e);
+                            continue;
+                        }
+                    }
+                    report.record(id, MirrorChangeKind::Delete);
+                    forgotten.push(id.as_str());
+                }
+                Ok(Some(_)) => report.error(
+                    id,
+                    "changed in the destination since it was mirrored; not deleted",
+                ),
+                Err(e) => report.error(id, e),
+            }
+        }
+        if !options.dry_run {
+            forget_state(state, &options.name, &forgotten)?;
+        }
+    }
+
+    Ok(report)
+}
+
+/// Bring one attestation up to date in `dest`. `None` means it already was.
+/// Copies are only classified; the caller writes them in batches.
+fn mirror_one<D: AttestationStore + ?Sized>(
+    dest: &mut D,
+    attestation: &Attestation,
+    hash: &str,
+    known: bool,
+    dry_run: bool,
+) -> StoreResult<Option<MirrorChangeKind>> {
+    // Unchanged since the last run: existence is enough, no need to fetch
+    if known && dest.exists(&attestation.id)? {
+        return Ok(None);
+    }
+    let kind = match dest.get(&attestation.id)? {
+        Some(copy) if mirror_content_hash(&copy) == hash => return Ok(None),
+        Some(_) => MirrorChangeKind::Update,
+        None => MirrorChangeKind::Copy,
+    };
+    if kind == MirrorChangeKind::Update && !dry_run {
+        dest.update(attestation.clone())?;
+    }
+    Ok(Some(kind))
+}
+
+fn load_state(state: &SqliteStore, mirror: &str) -> StoreResult<BTreeMap<String, String>> {
+    let mut stmt = state
+        .connection()
+        .prepare(
+            "SELECT attestation_id, content_hash FROM mirror_state
+             WHERE namespace = ?1 AND mirror = ?2",
+        )
+        .map_err(SqliteError::from)?;
+    let rows = stmt
+        .query_map(params![state.namespace(), mirror], |row| {
+            Ok((row.get(0)?, row.get(1)?))
+        })
+        .map_err(SqliteError::from)?;
+    let mut recorded = BTreeMap::new();
+    for row in rows {
+        let (id, hash) = row.map_err(SqliteError::from)?;
+        recorded.insert(id, hash);
+    }
+    Ok(recorded)
+}
+
+/// Run `write` inside a SAVEPOINT on the state connection.
+fn in_savepoint(
+    state: &SqliteStore,
+    write: impl FnOnce() -> rusqlite::Result<()>,
+) -> StoreResult<()> {
+    let conn = state.connection();
+    conn.execute_batch("SAVEPOINT mirror_state")
+        .map_err(SqliteError::from)?;
+    if let Err(e) = write() {
+        let _ = conn
+            .execute_batch("ROLLBACK TO SAVEPOINT mirror_state; RELEASE SAVEPOINT mirror_state");
+        return Err(SqliteError::from(e).into());
+    }
+    conn.execute_batch("RELEASE SAVEPOINT mirror_state")
+        .map_err(SqliteError::from)?;
+    Ok(())
+}
+
+fn save_state(
+    state: &SqliteStore,
+    mirror: &str,
+    run_id: &str,
+    rows: &[(&str, String)],
+) -> StoreResult<()> {
+    if rows.is_empty() {
+        return Ok(());
+    }
+    in_savepoint(state, || {
+        let mut stmt = state.connection().prepare_cached(
+            "INSERT INTO mirror_state (namespace, mirror, attestation_id, content_hash, run_id)
+             VALUES (?1, ?2, ?3, ?4, ?5)
+             ON CONFLICT (namespace, mirror, attestation_id)
+             DO UPDATE SET content_hash = excluded.content_hash, run_id = excluded.run_id",
+        )?;
+        for (id, hash) in rows {
+            stmt.execute(params![state.namespace(), mirror, id, hash, run_id])?;
+        }
+        Ok(())
+    })
+}
+
+fn forget_state(state: &SqliteStore, mirror: &str, ids: &[&str]) -> StoreResult<()> {
+    if ids.is_empty() {
+        return Ok(());
+    }
+    in_savepoint(state, || {
+        let mut stmt = state.connection().prepare_cached(
+            "DELETE FROM mirror_state
+             WHERE namespace = ?1 AND mirror = ?2 AND attestation_id = ?3",
+        )?;
+        for id in ids {
+            stmt.execute(params![state.namespace(), mirror, id])?;
+        }
+        Ok(())
+    })
+}
+
+impl SqliteStore {
+    /// Mirror this store's attestations matching `filter` into `dest`,
+    /// keeping the bookkeeping in this store. See [`mirror`].
+    pub fn mirror_to<D: AttestationStore + ?Sized>(
+        &self,
+        dest: &mut D,
+        filter: &AxFilter,
+        options: &MirrorOptions,
+    ) -> StoreResult<MirrorReport> {
+        mirror(self, dest, self, filter, options)
+    }
+
+    /// Drop the record of what mirror `name` copied, so its next run compares
+    /// every attestation afresh and propagates no deletions. Returns the
+    /// number of rows removed.
+    pub fn forget_mirror(&self, name: &str) -> StoreResult<usize> {
+        let removed = self
+            .connection()
+            .execute(
+                "DELETE FROM mirror_state WHERE namespace = ?1 AND mirror = ?2",
+                params![self.namespace(), name],
+            )
+            .map_err(SqliteError::from)?;
+        Ok(removed)
+    }
+}
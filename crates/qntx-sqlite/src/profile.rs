@@ -0,0 +1,148 @@
+//! Connection tuning: durability profiles and the prepared-statement cache.
+//!
+//! [`SqliteStore::open_with`](crate::SqliteStore::open_with) takes
+//! [`OpenOptions`]; [`SqliteStore::open`](crate::SqliteStore::open) uses the
+//! defaults ([`StoreProfile::Durable`]). Read connections opened from a
+//! store get the same read-side settings (mmap, page cache, statement cache).
+//!
+//! Every profile runs in WAL mode with `wal_autocheckpoint` off (see
+//! `SqliteStore::open`), so they differ only in `synchronous`, the page cache
+//! and temporary storage. None of them memory-maps the database; see
+//! [`OpenOptions::mmap_size`].
+
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+
+/// Prepared statements kept per connection when the options don't say
+pub const DEFAULT_STATEMENT_CACHE: usize = 64;
+
+/// Pragma bundle applied on open, trading durability for write latency.
+///
+/// No profile corrupts the database when the process exits or crashes:
+/// WAL commits are atomic and the log is replayed on the next open. They
+/// differ in what an operating-system crash or power loss can cost.
+///
+/// No profile turns on memory-mapped I/O (`mmap_size` stays 0). With many
+/// connections sharing one mapping, a checkpoint can shrink or rewrite the
+/// file under another connection's map, which faults with SIGBUS instead of
+/// returning an error. Callers that want mmap set
+/// [`OpenOptions::mmap_size`] themselves.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StoreProfile {
+    /// `synchronous = FULL`, default page cache.
+    ///
+    /// The WAL is synced on every commit: a commit that returned survives
+    /// power loss. Slowest writes. SQLite's own default, and ours.
+    #[default]
+    Durable,
+    /// `synchronous = NORMAL`, 16 MiB page cache.
+    ///
+    /// The WAL is synced at checkpoints only. Power loss can roll back the
+    /// most recent commits, but never corrupts the file.
+    Balanced,
+    /// `synchronous = OFF`, 64 MiB page cache, temporary tables in memory.
+    ///
+    /// SQLite never syncs. Power loss or an OS crash can lose recent
+    /// commits and may corrupt the database, so use it only for data that
+    /// can be rebuilt (caches, replicas, benchmarks).
+    Fast,
+}
+
+impl StoreProfile {
+    /// Value of `PRAGMA synchronous`
+    pub fn synchronous(&self) -> &'static str {
+        match self {
+            StoreProfile::Durable => "FULL",
+            StoreProfile::Balanced => "NORMAL",
+            StoreProfile::Fast => "OFF",
+        }
+    }
+
+    /// Page cache size in KiB; None leaves SQLite's default (2 MiB)
+    pub fn cache_size_kib(&self) -> Option<u64> {
+        match self {
+            StoreProfile::Durable => None,
+            StoreProfile::Balanced => Some(16 * 1024),
+            StoreProfile::Fast => Some(64 * 1024),
+        }
+    }
+
+    /// Parse a profile name (`durable`, `balanced`, `fast`), case-insensitively.
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "durable" => Some(StoreProfile::Durable),
+            "balanced" => Some(StoreProfile::Balanced),
+            "fast" => Some(StoreProfile::Fast),
+            _ => None,
+        }
+    }
+}
+
+/// How [`SqliteStore::open_with`](crate::SqliteStore::open_with) configures
+/// its connections. Unset fields take the profile's value.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OpenOptions {
+    pub profile: StoreProfile,
+    /// Bytes of the database file to memory-map, on the write connection
+    /// and every read connection. Unset or 0 keeps mmap off, whatever the
+    /// profile. Opting in risks SIGBUS when a checkpoint changes the file
+    /// under a connection's mapping (see [`StoreProfile`]).
+    pub mmap_size: Option<u64>,
+    /// Override the profile's page cache size, in KiB
+    pub cache_size_kib: Option<u64>,
+    /// Prepared statements cached per connection, least recently used
+    /// evicted first. 0 re-prepares every statement.
+    pub statement_cache: usize,
+}
+
+impl Default for OpenOptions {
+    fn default() -> Self {
+        Self {
+            profile: StoreProfile::default(),
+            mmap_size: None,
+            cache_size_kib: None,
+            statement_cache: DEFAULT_STATEMENT_CACHE,
+        }
+    }
+}
+
+impl OpenOptions {
+    /// Defaults of `profile`
+    pub fn profile(profile: StoreProfile) -> Self {
+        Self {
+            profile,
+            ..Default::default()
+        }
+    }
+
+    pub(crate) fn mmap_size(&self) -> u64 {
+        self.mmap_size.unwrap_or(0)
+    }
+
+    pub(crate) fn cache_size_kib(&self) -> Option<u64> {
+        self.cache_size_kib
+            .or_else(|| self.profile.cache_size_kib())
+    }
+
+    /// Settings shared by write and read connections.
+    pub(crate) fn apply_read_settings(&self, conn: &Connection) -> rusqlite::Result<()> {
+        conn.pragma_update(None, "mmap_size", self.mmap_size() as i64)?;
+        if let Some(kib) = self.cache_size_kib() {
+            // Negative cache_size is in KiB rather than pages
+            conn.pragma_update(None, "cache_size", -(kib as i64))?;
+        }
+        if self.profile == StoreProfile::Fast {
+            conn.pragma_update(None, "temp_store", "MEMORY")?;
+        }
+        conn.set_prepared_statement_cache_capacity(self.statement_cache);
+        Ok(())
+    }
+
+    /// Settings of the write connection.
+    pub(crate) fn apply_write_settings(&self, conn: &Connection) -> rusqlite::Result<()> {
+        conn.pragma_update(None, "synchronous", self.profile.synchronous())?;
+        self.apply_read_settings(conn)
+    }
+}
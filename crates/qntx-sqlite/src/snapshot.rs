@@ -0,0 +1,416 @@
+//! Read-only snapshot stores
+//!
+//! Analytics hosts receive periodic copies of a database file. A
+//! [`ReadOnlyStore`] serves queries from such a copy and can swap to a newer
+//! copy without a restart. Every file is validated before it is served: the
+//! header must describe a complete SQLite file, `PRAGMA quick_check` must pass
+//! and the attestation schema must be present, so a half-copied file is
+//! rejected instead of answering queries with garbage.
+//!
+//! Swaps are atomic from the caller's point of view. A query clones the
+//! current snapshot handle before it runs, so a refresh that lands mid-query
+//! leaves that query on the connection it started with; the old connection
+//! closes when its last query finishes.
+
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::{Arc, Mutex, MutexGuard, Weak};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, SystemTime};
+
+use qntx_core::{
+    attestation::{Attestation, AxFilter, AxResult},
+    storage::{AttestationStore, PutOutcome, QueryStore, StorageStats, StoreError},
+};
+use rusqlite::{Connection, OpenFlags, OptionalExtension};
+
+use crate::error::{Result, SqliteError};
+use crate::store::{validate_namespace, SqliteStore, DEFAULT_NAMESPACE};
+
+type StoreResult<T> = std::result::Result<T, StoreError>;
+
+/// Size of the fixed SQLite database header.
+const HEADER_LEN: usize = 100;
+
+/// Magic string every SQLite 3 database starts with.
+const HEADER_MAGIC: &[u8; 16] = b"SQLite format 3\0";
+
+/// Tables a file must contain to be served as an attestation snapshot.
+const REQUIRED_TABLES: [&str; 2] = ["attestations", "schema_migrations"];
+
+/// What a validated snapshot file contained when it was opened.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SnapshotInfo {
+    /// File the snapshot was opened from
+    pub path: PathBuf,
+    /// Attestations visible in the store's namespace
+    pub rows: usize,
+    /// Highest applied migration version, if any were recorded
+    pub schema_version: Option<String>,
+    /// File size in bytes at open time
+    pub file_len: u64,
+    /// File modification time at open time
+    pub modified: Option<SystemTime>,
+}
+
+/// Called by [`ReadOnlyStore::watch_for_changes`] after each successful swap.
+pub type SwapCallback = Box<dyn Fn(&SnapshotInfo) + Send>;
+
+/// One opened, validated copy of the database.
+struct Snapshot {
+    store: Mutex<SqliteStore>,
+    info: SnapshotInfo,
+}
+
+impl Snapshot {
+    fn store(&self) -> MutexGuard<'_, SqliteStore> {
+        // A panicking query cannot leave a read-only connection half-written.
+        self.store.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Read-only view of a copied database file with live refresh.
+///
+/// Reads delegate to the current snapshot; every write returns
+/// [`SqliteError::ReadOnly`]. Open with [`SqliteStore::open_read_only`].
+pub struct ReadOnlyStore {
+    namespace: String,
+    path: Mutex<PathBuf>,
+    current: Mutex<Arc<Snapshot>>,
+}
+
+impl SqliteStore {
+    /// Open `path` as a validated, read-only snapshot.
+    ///
+    /// Fails with [`SqliteError::InvalidSnapshot`] when the file is truncated,
+    /// fails `PRAGMA quick_check` or lacks the attestation schema.
+    pub fn open_read_only(path: impl AsRef<Path>) -> Result<ReadOnlyStore> {
+        ReadOnlyStore::open(path, DEFAULT_NAMESPACE)
+    }
+
+    /// Like [`SqliteStore::open_read_only`], restricted to `namespace`.
+    pub fn open_read_only_scoped(path: impl AsRef<Path>, namespace: &str) -> Result<ReadOnlyStore> {
+        validate_namespace(namespace)?;
+        ReadOnlyStore::open(path, namespace)
+    }
+}
+
+impl ReadOnlyStore {
+    fn open(path: impl AsRef<Path>, namespace: &str) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let snapshot = open_snapshot(&path, namespace)?;
+        Ok(Self {
+            namespace: namespace.to_string(),
+            path: Mutex::new(path),
+            current: Mutex::new(Arc::new(snapshot)),
+        })
+    }
+
+    /// Namespace every query is restricted to.
+    pub fn namespace(&self) -> &str {
+        &self.namespace
+    }
+
+    /// File the store refreshes from.
+    pub fn path(&self) -> PathBuf {
+        lock(&self.path).clone()
+    }
+
+    /// Details of the snapshot currently being served.
+    pub fn info(&self) -> SnapshotInfo {
+        self.snapshot().info.clone()
+    }
+
+    /// Reopen the store's file and swap to it once it validates.
+    ///
+    /// On failure the current snapshot keeps serving and the error is returned.
+    pub fn refresh(&self) -> Result<SnapshotInfo> {
+        let path = self.path();
+        self.swap_to(&path)
+    }
+
+    /// Validate `path` and, if it passes, serve it and refresh from it from now on.
+    pub fn refresh_from(&self, path: impl AsRef<Path>) -> Result<SnapshotInfo> {
+        let path = path.as_ref().to_path_buf();
+        let info = self.swap_to(&path)?;
+        *lock(&self.path) = path;
+        Ok(info)
+    }
+
+    /// Poll the store's file every `interval` and refresh when its size or
+    /// modification time changes.
+    ///
+    /// `on_swap` runs after each successful swap; without it each swap is
+    /// logged to stderr with the new row count and schema version. A file
+    /// that fails validation is logged and skipped until it changes again.
+    /// Polling stops when the returned watcher or the store is dropped.
+    pub fn watch_for_changes(
+        self: &Arc<Self>,
+        interval: Duration,
+        on_swap: Option<SwapCallback>,
+    ) -> SnapshotWatcher {
+        let store: Weak<Self> = Arc::downgrade(self);
+        let mut seen = file_fingerprint(&self.path());
+        let (stop, stopped) = mpsc::channel::<()>();
+
+        let handle = thread::spawn(move || loop {
+            match stopped.recv_timeout(interval) {
+                Err(RecvTimeoutError::Timeout) => {}
+                _ => return,
+            }
+            let Some(store) = store.upgrade() else {
+                return;
+            };
+            let path = store.path();
+            let fingerprint = file_fingerprint(&path);
+            if fingerprint == seen {
+                continue;
+            }
+            seen = fingerprint;
+            match store.refresh() {
+                Ok(info) => match &on_swap {
+                    Some(callback) => callback(&info),
+                    None => eprintln!(
+                        "qntx-sqlite: snapshot {} refreshed: {} rows, schema {}",
+                        info.path.display(),
+                        info.rows,
+                        info.schema_version.as_deref().unwrap_or("none")
+                    ),
+                },
+                Err(e) => eprintln!(
+                    "qntx-sqlite: snapshot {} not refreshed: {}",
+                    path.display(),
+                    e
+                ),
+            }
+        });
+
+        SnapshotWatcher {
+            stop: Some(stop),
+            handle: Some(handle),
+        }
+    }
+
+    fn swap_to(&self, path: &Path) -> Result<SnapshotInfo> {
+        let snapshot = open_snapshot(path, &self.namespace)?;
+        let info = snapshot.info.clone();
+        // The previous snapshot closes once in-flight queries release it.
+        *lock(&self.current) = Arc::new(snapshot);
+        Ok(info)
+    }
+
+    fn snapshot(&self) -> Arc<Snapshot> {
+        Arc::clone(&lock(&self.current))
+    }
+
+    fn read_only(&self, operation: &str) -> SqliteError {
+        SqliteError::ReadOnly(format!(
+            "{} on snapshot {}",
+            operation,
+            self.path().display()
+        ))
+    }
+}
+
+/// Background poller started by [`ReadOnlyStore::watch_for_changes`].
+///
+/// Dropping it stops the poller and waits for the thread to exit.
+pub struct SnapshotWatcher {
+    stop: Option<mpsc::Sender<()>>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl Drop for SnapshotWatcher {
+    fn drop(&mut self) {
+        drop(self.stop.take());
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+impl AttestationStore for ReadOnlyStore {
+    fn put(&mut self, _attestation: Attestation) -> StoreResult<()> {
+        Err(self.read_only("put").into())
+    }
+
+    fn get(&self, id: &str) -> StoreResult<Option<Attestation>> {
+        self.snapshot().store().get(id)
+    }
+
+    fn exists(&self, id: &str) -> StoreResult<bool> {
+        self.snapshot().store().exists(id)
+    }
+
+    fn delete(&mut self, _id: &str) -> StoreResult<bool> {
+        Err(self.read_only("delete").into())
+    }
+
+    fn update(&mut self, _attestation: Attestation) -> StoreResult<()> {
+        Err(self.read_only("update").into())
+    }
+
+    fn put_if_revision(
+        &mut self,
+        _attestation: Attestation,
+        _expected_revision: u64,
+    ) -> StoreResult<PutOutcome> {
+        Err(self.read_only("put_if_revision").into())
+    }
+
+    fn ids(&self) -> StoreResult<Vec<String>> {
+        self.snapshot().store().ids()
+    }
+
+    fn count(&self) -> StoreResult<usize> {
+        self.snapshot().store().count()
+    }
+
+    fn clear(&mut self) -> StoreResult<()> {
+        Err(self.read_only("clear").into())
+    }
+}
+
+impl QueryStore for ReadOnlyStore {
+    fn query(&self, filter: &AxFilter) -> StoreResult<AxResult> {
+        self.snapshot().store().query(filter)
+    }
+
+    fn predicates(&self) -> StoreResult<Vec<String>> {
+        self.snapshot().store().predicates()
+    }
+
+    fn contexts(&self) -> StoreResult<Vec<String>> {
+        self.snapshot().store().contexts()
+    }
+
+    fn subjects(&self) -> StoreResult<Vec<String>> {
+        self.snapshot().store().subjects()
+    }
+
+    fn actors(&self) -> StoreResult<Vec<String>> {
+        self.snapshot().store().actors()
+    }
+
+    fn stats(&self) -> StoreResult<StorageStats> {
+        self.snapshot().store().stats()
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Size and modification time, or `None` while the file is missing.
+fn file_fingerprint(path: &Path) -> Option<(u64, Option<SystemTime>)> {
+    let meta = fs::metadata(path).ok()?;
+    Some((meta.len(), meta.modified().ok()))
+}
+
+/// Open `path` read-only and validate it before it may be served.
+fn open_snapshot(path: &Path, namespace: &str) -> Result<Snapshot> {
+    let meta = fs::metadata(path)?;
+    check_header(path, meta.len())?;
+
+    let conn = Connection::open_with_flags(
+        path,
+        OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+    )?;
+    conn.pragma_update(None, "busy_timeout", "5000")?;
+    conn.pragma_update(None, "mmap_size", "0")?;
+
+    let check: String = conn
+        .query_row("PRAGMA quick_check", [], |row| row.get(0))
+        .map_err(|e| invalid(path, format!("quick_check failed: {}", e)))?;
+    if check != "ok" {
+        return Err(invalid(path, format!("quick_check reported: {}", check)));
+    }
+    for table in REQUIRED_TABLES {
+        let found: Option<String> = conn
+            .query_row(
+                "SELECT name FROM sqlite_master WHERE type = 'table' AND name = ?1",
+                [table],
+                |row| row.get(0),
+            )
+            .optional()?;
+        if found.is_none() {
+            return Err(invalid(path, format!("missing table {}", table)));
+        }
+    }
+    let schema_version: Option<String> =
+        conn.query_row("SELECT MAX(version) FROM schema_migrations", [], |row| {
+            row.get(0)
+        })?;
+
+    let rows: usize = conn.query_row(
+        "SELECT COUNT(*) FROM attestations WHERE namespace = ?1",
+        [namespace],
+        |row| row.get(0),
+    )?;
+
+    let mut store = SqliteStore::new(conn);
+    store.namespace = namespace.to_string();
+
+    Ok(Snapshot {
+        store: Mutex::new(store),
+        info: SnapshotInfo {
+            path: path.to_path_buf(),
+            rows,
+            schema_version,
+            file_len: meta.len(),
+            modified: meta.modified().ok(),
+        },
+    })
+}
+
+/// Reject files whose header is missing or declares more pages than were copied.
+fn check_header(path: &Path, file_len: u64) -> Result<()> {
+    let mut header = [0u8; HEADER_LEN];
+    let mut file = fs::File::open(path)?;
+    if file_len < HEADER_LEN as u64 {
+        return Err(invalid(
+            path,
+            format!("file is {} bytes, too short", file_len),
+        ));
+    }
+    file.read_exact(&mut header)?;
+    if &header[..16] != HEADER_MAGIC {
+        return Err(invalid(path, "not a SQLite database".to_string()));
+    }
+
+    // Page size 1 encodes 65536.
+    let page_size = match u16::from_be_bytes([header[16], header[17]]) {
+        1 => 65536,
+        n => n as u64,
+    };
+    let page_count = u32::from_be_bytes([header[28], header[29], header[30], header[31]]) as u64;
+    let change_counter = &header[24..28];
+    let valid_for = &header[92..96];
+    // The in-header page count is only trustworthy when written by the same
+    // change that last touched the file.
+    if change_counter == valid_for && page_count * page_size > file_len {
+        return Err(invalid(
+            path,
+            format!(
+                "truncated: header declares {} pages of {} bytes, file has {} bytes",
+                page_count, page_size, file_len
+            ),
+        ));
+    }
+    if !file_len.is_multiple_of(page_size) {
+        return Err(invalid(
+            path,
+            format!(
+                "truncated: {} bytes is not a whole number of {}-byte pages",
+                file_len, page_size
+            ),
+        ));
+    }
+    Ok(())
+}
+
+fn invalid(path: &Path, reason: String) -> SqliteError {
+    SqliteError::InvalidSnapshot(format!("{}: {}", path.display(), reason))
+}
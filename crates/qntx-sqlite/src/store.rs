@@ -1,37 +1,40 @@
 //! SQLite storage backend implementing AttestationStore trait
 
 use qntx_core::{
-    attestation::{Attestation, AxFilter, AxResult, AxSummary},
-    storage::{AttestationStore, QueryStore, StorageStats, StoreError},
+    attestation::{
+        confidence_bucket, Attestation, AttrCondition, AttrOp, AttrValue, AxFilter, AxResult,
+        AxSummary, MatchingSummary, TermCount, CONFIDENCE_BUCKETS, SUMMARY_TOP_N,
+    },
+    storage::{
+        AttestationStore, ChangeOp, PutOutcome, QueryStore, StorageErrorKind, StorageStats,
+        StoreError,
+    },
 };
 use rusqlite::{backup, Connection, OptionalExtension};
-use std::collections::{HashMap, HashSet};
+use std::cell::{Cell, RefCell};
+use std::collections::{BTreeMap, HashMap, HashSet};
 
+use crate::changes;
 use crate::error::SqliteError;
+use crate::history::{self, HistoryOperation};
+use crate::maintenance::{CheckpointMode, MaintenancePolicy};
+use crate::profile::{OpenOptions, DEFAULT_STATEMENT_CACHE};
 
-/// Raw row tuple from the attestations table, before conversion to Attestation.
-type AttestationRow = (
-    String,
-    String,
-    String,
-    String,
-    String,
-    String,
-    String,
-    Option<String>,
-    String,
-    Option<Vec<u8>>,
-    Option<String>,
-);
 use crate::json::{
-    deserialize_attributes, deserialize_string_vec, serialize_attributes, serialize_string_vec,
-    sql_to_timestamp, timestamp_to_sql,
+    decode_attestation_row, serialize_attributes, serialize_string_vec, timestamp_to_sql,
+    AttestationRow, CorruptRow,
 };
 
 use qntx_core::storage::enforcement::{EnforcementConfig, EnforcementInput};
 
 type StoreResult<T> = Result<T, StoreError>;
 
+/// Namespace of unscoped stores, and of rows written before namespaces existed.
+pub const DEFAULT_NAMESPACE: &str = "default";
+
+/// Longest accepted namespace name, in bytes.
+const MAX_NAMESPACE_LENGTH: usize = 128;
+
 /// SQLite-backed attestation store (write connection).
 ///
 /// File-backed stores also create a separate `ReadConn` for queries.
@@ -39,8 +42,15 @@ type StoreResult<T> = Result<T, StoreError>;
 /// so reads never block writes. Each connection is single-threaded
 /// (rusqlite uses RefCell), but Go serializes access per-connection
 /// with separate mutexes.
+///
+/// Every store is bound to one namespace (workspace). Attestation IDs are
+/// unique per namespace, so the same ID can exist in several namespaces of
+/// one file; `get`, `put`, `delete`, queries, vocabulary and stats only ever
+/// see rows of the store's own namespace. See [`SqliteStore::scoped`].
 pub struct SqliteStore {
     pub(crate) conn: Connection,
+    /// Namespace every read and write is restricted to.
+    pub(crate) namespace: String,
     /// Database file path, if file-backed. Used by backup to open a separate read connection.
     pub(crate) db_path: Option<String>,
     /// Enforcement config for bounded storage limits (16/64/64 default).
@@ -49,10 +59,29 @@ pub struct SqliteStore {
     /// When true, put() skips enforcement to prevent infinite loops during distillation.
     pub(crate) distilling: bool,
     /// Counter for amortized enforcement: only run enforcement every N puts.
-    put_count: u64,
+    pub(crate) put_count: u64,
+    /// Automatic WAL upkeep, checked after writes (see `maintenance`).
+    pub(crate) maintenance_policy: Option<MaintenancePolicy>,
     /// In-memory enforcement counters for O(1) threshold checks.
     /// Populated lazily from DB on first access, then maintained on put/delete.
     pub(crate) enforcement_counters: EnforcementCounters,
+    /// When true, an undecodable row fails the whole query instead of being skipped.
+    strict_decoding: bool,
+    /// Rows skipped because they could not be decoded, keyed by attestation ID.
+    quarantine: RefCell<BTreeMap<String, CorruptRow>>,
+    /// Rows skipped by the most recent query.
+    last_corrupt_count: Cell<usize>,
+    /// Connection settings, reused for read connections and scoped handles.
+    pub(crate) open_options: OpenOptions,
+}
+
+/// Fix applied by [`SqliteStore::repair_row`] to a quarantined row.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RepairAction {
+    /// Clear the attributes column, keeping the rest of the attestation
+    DropAttributes,
+    /// Delete the attestation entirely
+    DeleteRow,
 }
 
 /// In-memory counters for O(1) enforcement threshold checks.
@@ -118,6 +147,8 @@ impl EnforcementCounters {
 #[allow(dead_code)]
 pub struct ReadConn {
     pub(crate) conn: Connection,
+    /// Namespace of the store this connection was opened from.
+    pub(crate) namespace: String,
 }
 
 impl SqliteStore {
@@ -128,11 +159,17 @@ impl SqliteStore {
     pub fn new(conn: Connection) -> Self {
         Self {
             conn,
+            namespace: DEFAULT_NAMESPACE.to_string(),
             db_path: None,
             enforcement_config: None,
             distilling: false,
             put_count: 0,
+            maintenance_policy: None,
             enforcement_counters: EnforcementCounters::default(),
+            strict_decoding: false,
+            quarantine: RefCell::new(BTreeMap::new()),
+            last_corrupt_count: Cell::new(0),
+            open_options: OpenOptions::default(),
         }
     }
 
@@ -142,6 +179,7 @@ impl SqliteStore {
         crate::vec::init_vec_extension();
 
         let conn = Connection::open_in_memory()?;
+        conn.set_prepared_statement_cache_capacity(DEFAULT_STATEMENT_CACHE);
         crate::migrate::migrate(&conn)?;
         Ok(Self::new(conn))
     }
@@ -151,6 +189,16 @@ impl SqliteStore {
     /// Opens a read-write connection. Use [`open_read_conn`] to create a
     /// separate read-only connection for concurrent queries.
     pub fn open(path: impl AsRef<std::path::Path>) -> crate::error::Result<Self> {
+        Self::open_with(path, &OpenOptions::default())
+    }
+
+    /// Create a new file-backed SQLite store tuned by `options`: its
+    /// [`StoreProfile`](crate::StoreProfile), mmap and page cache sizes, and
+    /// the prepared-statement cache.
+    pub fn open_with(
+        path: impl AsRef<std::path::Path>,
+        options: &OpenOptions,
+    ) -> crate::error::Result<Self> {
         // Initialize sqlite-vec extension BEFORE creating connection
         crate::vec::init_vec_extension();
 
@@ -164,7 +212,7 @@ impl SqliteStore {
         conn.pragma_update(None, "journal_mode", "WAL")?;
         conn.pragma_update(None, "foreign_keys", "ON")?;
         conn.pragma_update(None, "busy_timeout", "5000")?;
-        conn.pragma_update(None, "mmap_size", "0")?;
+        options.apply_write_settings(&conn)?;
         // WAL always memory-maps the -shm index file for reader/writer coordination.
         // With many connections sharing the same mmap, auto-checkpoints during writes
         // can invalidate the mapping → SIGBUS at _platform_memmove with page-aligned
@@ -175,47 +223,147 @@ impl SqliteStore {
 
         Ok(Self {
             conn,
+            namespace: DEFAULT_NAMESPACE.to_string(),
             db_path: Some(path_str),
             enforcement_config: None,
             distilling: false,
             put_count: 0,
+            maintenance_policy: None,
             enforcement_counters: EnforcementCounters::default(),
+            strict_decoding: false,
+            quarantine: RefCell::new(BTreeMap::new()),
+            last_corrupt_count: Cell::new(0),
+            open_options: options.clone(),
         })
     }
 
-    /// Open a separate read-only connection for concurrent queries.
+    /// Open a separate read-only connection for concurrent queries, with the
+    /// store's mmap, page cache and statement cache settings.
     /// Only works for file-backed stores.
     pub fn open_read_conn(&self) -> crate::error::Result<ReadConn> {
-        let path = self.db_path.as_deref().ok_or_else(|| {
-            crate::error::SqliteError::Migration(
-                "read connection requires a file-backed database".into(),
-            )
-        })?;
+        let path = self
+            .db_path
+            .as_deref()
+            .ok_or_else(|| crate::error::SqliteError::RequiresFile("read connection".into()))?;
         let conn = Connection::open_with_flags(
             path,
             rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY | rusqlite::OpenFlags::SQLITE_OPEN_NO_MUTEX,
         )?;
         conn.pragma_update(None, "busy_timeout", "5000")?;
-        conn.pragma_update(None, "mmap_size", "0")?;
-        Ok(ReadConn { conn })
+        self.open_options.apply_read_settings(&conn)?;
+        Ok(ReadConn {
+            conn,
+            namespace: self.namespace.clone(),
+        })
+    }
+
+    /// Open a file-backed store bound to `namespace`.
+    ///
+    /// Equivalent to [`SqliteStore::open`] followed by scoping; rows of other
+    /// namespaces in the same file are invisible to the returned store.
+    pub fn open_scoped(
+        path: impl AsRef<std::path::Path>,
+        namespace: &str,
+    ) -> crate::error::Result<Self> {
+        validate_namespace(namespace)?;
+        let mut store = Self::open(path)?;
+        store.namespace = namespace.to_string();
+        Ok(store)
+    }
+
+    /// Open another handle on the same database file, bound to `namespace`.
+    ///
+    /// The handle has its own write connection and enforcement counters and
+    /// inherits this store's enforcement config, decoding mode and open
+    /// options. Isolation is
+    /// enforced in SQL: every statement it runs filters on the namespace column.
+    /// Only works for file-backed stores.
+    pub fn scoped(&self, namespace: &str) -> crate::error::Result<SqliteStore> {
+        let path = self
+            .db_path
+            .as_deref()
+            .ok_or_else(|| SqliteError::RequiresFile("scoped handle".into()))?;
+        validate_namespace(namespace)?;
+        let mut store = Self::open_with(path, &self.open_options)?;
+        store.namespace = namespace.to_string();
+        store.enforcement_config = self.enforcement_config.clone();
+        store.strict_decoding = self.strict_decoding;
+        Ok(store)
+    }
+
+    /// Namespace this store reads and writes.
+    pub fn namespace(&self) -> &str {
+        &self.namespace
+    }
+
+    /// All namespaces holding at least one attestation, across the whole file.
+    pub fn list_namespaces(&self) -> StoreResult<Vec<String>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT DISTINCT namespace FROM attestations ORDER BY namespace")
+            .map_err(SqliteError::from)?;
+        let namespaces = stmt
+            .query_map([], |row| row.get::<_, String>(0))
+            .map_err(SqliteError::from)?
+            .collect::<Result<Vec<String>, rusqlite::Error>>()
+            .map_err(SqliteError::from)?;
+        Ok(namespaces)
+    }
+
+    /// Attestation count of every namespace in the file.
+    pub fn count_by_namespace(&self) -> StoreResult<BTreeMap<String, usize>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT namespace, COUNT(*) FROM attestations GROUP BY namespace")
+            .map_err(SqliteError::from)?;
+        let counts = stmt
+            .query_map([], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)? as usize))
+            })
+            .map_err(SqliteError::from)?
+            .collect::<Result<BTreeMap<String, usize>, rusqlite::Error>>()
+            .map_err(SqliteError::from)?;
+        Ok(counts)
+    }
+
+    /// Delete every attestation in `namespace`, whichever namespace this store
+    /// is bound to. `confirmation` must equal [`drop_namespace_token`] for that
+    /// namespace, so a namespace can't be wiped by passing the wrong variable.
+    /// Returns the number of attestations deleted.
+    pub fn drop_namespace(&mut self, namespace: &str, confirmation: &str) -> StoreResult<usize> {
+        if confirmation != drop_namespace_token(namespace) {
+            return Err(StoreError::InvalidData(format!(
+                "confirmation token does not match namespace {}",
+                namespace
+            )));
+        }
+        let deleted = atomically(&self.conn, || -> StoreResult<usize> {
+            changes::record_delete_all(&self.conn, namespace)?;
+            history::record_delete_all(&self.conn, namespace)?;
+            // CASCADE removes the junction rows
+            let deleted = self
+                .conn
+                .execute("DELETE FROM attestations WHERE namespace = ?", [namespace])
+                .map_err(SqliteError::from)?;
+            Ok(deleted)
+        })?;
+        if namespace == self.namespace {
+            self.enforcement_counters = EnforcementCounters::default();
+            self.quarantine.borrow_mut().clear();
+        }
+        Ok(deleted)
     }
 
     /// Run a TRUNCATE checkpoint on the write connection.
     /// Caller MUST ensure no read connections are open — TRUNCATE requires
     /// exclusive access to the WAL. Returns (busy, wal_pages, checkpointed_pages).
     pub fn wal_checkpoint_truncate(&self) -> StoreResult<(i32, i32, i32)> {
-        let mut busy = 0i32;
-        let mut log = 0i32;
-        let mut checkpointed = 0i32;
-        self.conn
-            .query_row("PRAGMA wal_checkpoint(TRUNCATE)", [], |row| {
-                busy = row.get(0)?;
-                log = row.get(1)?;
-                checkpointed = row.get(2)?;
-                Ok(())
-            })
-            .map_err(SqliteError::from)?;
-        Ok((busy, log, checkpointed))
+        let report = self.checkpoint(CheckpointMode::Truncate)?;
+        Ok((
+            report.busy as i32,
+            report.wal_pages as i32,
+            report.checkpointed_pages as i32,
+        ))
     }
 
     /// Age-triggered distillation: fold old attestations into sigmas.
@@ -234,31 +382,17 @@ impl SqliteStore {
 
         // Load candidates
         let mut stmt = self.conn.prepare(
-            "SELECT id, subjects, predicates, contexts, actors, timestamp, source, attributes, created_at, signature, signer_did
+            "SELECT id, subjects, predicates, contexts, actors, timestamp, source, attributes, created_at, signature, signer_did, revision, confidence
              FROM attestations
-             WHERE (timestamp < ?1 OR source = 'distill')
+             WHERE namespace = ?3 AND (timestamp < ?1 OR source = 'distill')
              ORDER BY timestamp ASC
              LIMIT ?2"
         ).map_err(SqliteError::from)?;
 
         let rows = stmt
             .query_map(
-                rusqlite::params![cutoff_rfc3339, batch_size as i64],
-                |row| {
-                    Ok((
-                        row.get::<_, String>(0)?,
-                        row.get::<_, String>(1)?,
-                        row.get::<_, String>(2)?,
-                        row.get::<_, String>(3)?,
-                        row.get::<_, String>(4)?,
-                        row.get::<_, String>(5)?,
-                        row.get::<_, String>(6)?,
-                        row.get::<_, Option<String>>(7)?,
-                        row.get::<_, String>(8)?,
-                        row.get::<_, Option<Vec<u8>>>(9)?,
-                        row.get::<_, Option<String>>(10)?,
-                    ))
-                },
+                rusqlite::params![cutoff_rfc3339, batch_size as i64, self.namespace],
+                crate::json::read_attestation_row,
             )
             .map_err(SqliteError::from)?;
 
@@ -338,16 +472,18 @@ impl SqliteStore {
                     "[distill] DELETE id={} pred={} subj={} actor={} ts={}",
                     att.id, predicate, subj, actor, att.timestamp
                 );
+                changes::record_deletes(&tx, &self.namespace, [att.id.as_str()])?;
+                history::record_deletes(&tx, &self.namespace, [att.id.as_str()])?;
                 tx.execute(
-                    "DELETE FROM attestations WHERE id = ?1",
-                    rusqlite::params![att.id],
+                    "DELETE FROM attestations WHERE namespace = ?1 AND id = ?2",
+                    rusqlite::params![self.namespace, att.id],
                 )
                 .map_err(SqliteError::from)?;
                 total_distilled += 1;
             }
 
             // Insert sigma
-            put_attestation(&tx, &sigma)?;
+            put_attestation(&tx, &sigma, &self.namespace)?;
             total_created += 1;
         }
 
@@ -356,23 +492,6 @@ impl SqliteStore {
         Ok((total_distilled, total_created, total_skipped))
     }
 
-    /// Run PRAGMA integrity_check and return the result lines.
-    /// A healthy database returns a single line: "ok".
-    pub fn integrity_check(&self) -> StoreResult<Vec<String>> {
-        let mut stmt = self
-            .conn
-            .prepare("PRAGMA integrity_check")
-            .map_err(SqliteError::from)?;
-        let rows = stmt
-            .query_map([], |row| row.get::<_, String>(0))
-            .map_err(SqliteError::from)?;
-        let mut results = Vec::new();
-        for row in rows {
-            results.push(row.map_err(SqliteError::from)?);
-        }
-        Ok(results)
-    }
-
     /// Create a hot backup of the database to the given path.
     /// Flushes WAL to the main DB first via TRUNCATE checkpoint, then opens a
     /// separate read-only source connection — callers do NOT need to hold the mutex.
@@ -416,52 +535,203 @@ impl SqliteStore {
         &self.conn
     }
 
-    /// Extract a row tuple into an Attestation, converting errors through SqliteError.
-    pub fn row_to_attestation(row_data: AttestationRow) -> StoreResult<Attestation> {
-        let (
-            id,
-            subjects_json,
-            predicates_json,
-            contexts_json,
-            actors_json,
-            timestamp_str,
-            source,
-            attributes_json,
-            created_at_str,
-            signature,
-            signer_did,
-        ) = row_data;
-
-        let subjects = deserialize_string_vec(&subjects_json)?;
-        let predicates = deserialize_string_vec(&predicates_json)?;
-        let contexts = deserialize_string_vec(&contexts_json)?;
-        let actors = deserialize_string_vec(&actors_json)?;
-        let attributes = deserialize_attributes(attributes_json)?;
-        let timestamp = sql_to_timestamp(&timestamp_str)?;
-        let created_at = sql_to_timestamp(&created_at_str)?;
-
-        Ok(Attestation {
-            id,
-            subjects,
-            predicates,
-            contexts,
-            actors,
-            timestamp,
-            source,
-            attributes,
-            created_at,
-            signature,
-            signer_did,
+    /// Overwrite a stored attestation and bump its revision. With `expected_revision`,
+    /// only a row at that revision is touched. Returns the number of rows updated.
+    fn update_row(
+        &self,
+        attestation: &Attestation,
+        expected_revision: Option<u64>,
+    ) -> StoreResult<usize> {
+        attestation
+            .validate_confidence()
+            .map_err(StoreError::InvalidData)?;
+        let subjects_json = serialize_string_vec(&attestation.subjects)?;
+        let predicates_json = serialize_string_vec(&attestation.predicates)?;
+        let contexts_json = serialize_string_vec(&attestation.contexts)?;
+        let actors_json = serialize_string_vec(&attestation.actors)?;
+        let attributes_json = serialize_attributes(&attestation.attributes)?;
+
+        let timestamp_sql = timestamp_to_sql(attestation.timestamp);
+
+        atomically(&self.conn, || -> StoreResult<usize> {
+            let rows_affected = self
+                .conn
+                .execute(
+                    "UPDATE attestations
+             SET subjects = ?, predicates = ?, contexts = ?, actors = ?,
+                 timestamp = ?, source = ?, attributes = ?, signature = ?, signer_did = ?,
+                 confidence = ?, revision = revision + 1
+             WHERE namespace = ? AND id = ? AND (? IS NULL OR revision = ?)",
+                    rusqlite::params![
+                        subjects_json,
+                        predicates_json,
+                        contexts_json,
+                        actors_json,
+                        timestamp_sql,
+                        attestation.source,
+                        attributes_json,
+                        attestation.signature,
+                        attestation.signer_did,
+                        attestation.confidence.map(f64::from),
+                        self.namespace,
+                        attestation.id,
+                        expected_revision.map(|r| r as i64),
+                        expected_revision.map(|r| r as i64),
+                    ],
+                )
+                .map_err(SqliteError::from)?;
+
+            if rows_affected > 0 {
+                rewrite_junction_rows(&self.conn, attestation, &self.namespace)?;
+                changes::record_write(&self.conn, &self.namespace, ChangeOp::Update, attestation)?;
+            }
+            if rows_affected > 0 && history::history_enabled(&self.conn, &self.namespace)? {
+                if let Some(stored) = self.get(&attestation.id)? {
+                    history::record_write(
+                        &self.conn,
+                        &self.namespace,
+                        HistoryOperation::Update,
+                        &stored,
+                    )?;
+                }
+            }
+
+            Ok(rows_affected)
         })
     }
 
+    /// Delete `id` with its changefeed and history rows. Returns rows deleted.
+    fn delete_row(&self, id: &str) -> StoreResult<usize> {
+        atomically(&self.conn, || {
+            if self.exists(id)? {
+                changes::record_deletes(&self.conn, &self.namespace, [id])?;
+                history::record_deletes(&self.conn, &self.namespace, [id])?;
+            }
+            let rows_affected = self
+                .conn
+                .execute(
+                    "DELETE FROM attestations WHERE namespace = ? AND id = ?",
+                    [self.namespace.as_str(), id],
+                )
+                .map_err(SqliteError::from)?;
+            Ok(rows_affected)
+        })
+    }
+
+    /// The undecoded row of `id` in this namespace.
+    fn get_row(&self, id: &str) -> StoreResult<Option<AttestationRow>> {
+        let mut stmt = self
+            .conn
+            .prepare_cached(
+                "SELECT id, subjects, predicates, contexts, actors, timestamp, source, attributes, created_at, signature, signer_did, revision, confidence
+                 FROM attestations
+                 WHERE namespace = ? AND id = ?",
+            )
+            .map_err(SqliteError::from)?;
+        let row = stmt
+            .query_row(
+                [self.namespace.as_str(), id],
+                crate::json::read_attestation_row,
+            )
+            .optional()
+            .map_err(SqliteError::from)?;
+        Ok(row)
+    }
+
+    /// Extract a row tuple into an Attestation. Decode failures become
+    /// `StoreError::Corruption` naming the row and column.
+    pub fn row_to_attestation(row_data: AttestationRow) -> StoreResult<Attestation> {
+        decode_attestation_row(row_data).map_err(|c| StoreError::Corruption(c.to_string()))
+    }
+
+    /// Fail queries on the first undecodable row instead of skipping it.
+    /// Off by default; tests use it to keep fail-fast behavior.
+    pub fn set_strict_decoding(&mut self, strict: bool) {
+        self.strict_decoding = strict;
+    }
+
+    /// Rows skipped by tolerant decoding since this store was opened, ordered by ID.
+    pub fn corrupt_rows(&self) -> Vec<CorruptRow> {
+        self.quarantine.borrow().values().cloned().collect()
+    }
+
+    /// Number of rows skipped by the most recent query, including rows
+    /// already in the quarantine.
+    pub fn last_corrupt_count(&self) -> usize {
+        self.last_corrupt_count.get()
+    }
+
+    /// Decode query rows. Undecodable rows are quarantined (and logged once per
+    /// row ID) unless strict decoding is enabled, in which case they fail the query.
+    fn decode_rows(
+        &self,
+        rows: impl Iterator<Item = rusqlite::Result<AttestationRow>>,
+    ) -> StoreResult<Vec<Attestation>> {
+        let mut attestations = Vec::new();
+        let mut skipped = 0;
+        for row_result in rows {
+            match self.decode_row(row_result.map_err(SqliteError::from)?)? {
+                Some(a) => attestations.push(a),
+                None => skipped += 1,
+            }
+        }
+        self.last_corrupt_count.set(skipped);
+        Ok(attestations)
+    }
+
+    /// Decode one query row; `None` if it was quarantined (see [`Self::decode_rows`]).
+    pub(crate) fn decode_row(&self, row_data: AttestationRow) -> StoreResult<Option<Attestation>> {
+        match decode_attestation_row(row_data) {
+            Ok(a) => Ok(Some(a)),
+            Err(c) if self.strict_decoding => Err(StoreError::Corruption(c.to_string())),
+            Err(c) => {
+                let mut quarantine = self.quarantine.borrow_mut();
+                if !quarantine.contains_key(&c.id) {
+                    eprintln!("qntx-sqlite: skipping corrupt attestation {}", c);
+                    quarantine.insert(c.id.clone(), c);
+                }
+                Ok(None)
+            }
+        }
+    }
+
+    /// Repair a row that failed to decode, and drop it from the quarantine.
+    /// Returns `StoreError::NotFound` if no attestation has this ID.
+    ///
+    /// Dropping attributes is written as an ordinary update: it bumps the
+    /// revision and reaches the changefeed and history. It fails with
+    /// `StoreError::Corruption` if another column is undecodable too.
+    pub fn repair_row(&mut self, id: &str, fix: RepairAction) -> StoreResult<()> {
+        let rows_affected = match fix {
+            RepairAction::DropAttributes => match self.get_row(id)? {
+                None => 0,
+                Some(mut row) => {
+                    row.7 = None;
+                    let repaired = Self::row_to_attestation(row)?;
+                    self.update_row(&repaired, None)?
+                }
+            },
+            RepairAction::DeleteRow => self.delete_row(id)?,
+        };
+        if rows_affected == 0 {
+            return Err(StoreError::NotFound(id.to_string()));
+        }
+        self.quarantine.borrow_mut().remove(id);
+        Ok(())
+    }
+
     /// Execute a raw SQL query with parameters, returning attestation rows as JSON.
     ///
     /// The query MUST select the standard attestation columns in order:
     ///   id, subjects, predicates, contexts, actors, timestamp, source, attributes, created_at, signature, signer_did
+    /// optionally followed by `revision` (attestations read without it have revision 0)
+    /// and `confidence`.
     ///
     /// Parameters are passed as a JSON array of values (strings, numbers, nulls).
     /// This allows Go to keep its query builder while Rust owns the connection.
+    ///
+    /// The SQL runs as given and is NOT restricted to this store's namespace;
+    /// callers that need isolation must filter on the `namespace` column themselves.
     pub fn query_attestations_raw(
         &self,
         sql: &str,
@@ -502,38 +772,20 @@ impl SqliteStore {
             param_refs.iter().map(|p| p.as_ref()).collect();
 
         let rows = stmt
-            .query_map(param_slice.as_slice(), |row| {
-                Ok((
-                    row.get::<_, String>(0)?,
-                    row.get::<_, String>(1)?,
-                    row.get::<_, String>(2)?,
-                    row.get::<_, String>(3)?,
-                    row.get::<_, String>(4)?,
-                    row.get::<_, String>(5)?,
-                    row.get::<_, String>(6)?,
-                    row.get::<_, Option<String>>(7)?,
-                    row.get::<_, String>(8)?,
-                    row.get::<_, Option<Vec<u8>>>(9)?,
-                    row.get::<_, Option<String>>(10)?,
-                ))
-            })
+            .query_map(param_slice.as_slice(), crate::json::read_attestation_row)
             .map_err(SqliteError::from)?;
 
-        let mut attestations = Vec::new();
-        for row_result in rows {
-            let row_data = row_result.map_err(SqliteError::from)?;
-            attestations.push(Self::row_to_attestation(row_data)?);
-        }
+        let attestations = self.decode_rows(rows)?;
 
         Ok(attestations)
     }
 
-    /// Helper to query rows from a prepared statement.
+    /// Helper to query a single column, binding this store's namespace to `?1`.
     fn query_distinct_values(&self, sql: &str) -> StoreResult<Vec<String>> {
-        let mut stmt = self.conn.prepare(sql).map_err(SqliteError::from)?;
+        let mut stmt = self.conn.prepare_cached(sql).map_err(SqliteError::from)?;
 
         let values = stmt
-            .query_map([], |row| row.get::<_, String>(0))
+            .query_map([&self.namespace], |row| row.get::<_, String>(0))
             .map_err(SqliteError::from)?
             .collect::<Result<Vec<String>, rusqlite::Error>>()
             .map_err(SqliteError::from)?;
@@ -542,9 +794,128 @@ impl SqliteStore {
     }
 }
 
+/// Confirmation token [`SqliteStore::drop_namespace`] expects for `namespace`.
+pub fn drop_namespace_token(namespace: &str) -> String {
+    format!("drop-namespace:{}", namespace)
+}
+
+/// Reject namespace names that can't be stored or would be ambiguous.
+pub(crate) fn validate_namespace(namespace: &str) -> crate::error::Result<()> {
+    if namespace.is_empty() {
+        return Err(SqliteError::InvalidNamespace("empty name".into()));
+    }
+    if namespace.len() > MAX_NAMESPACE_LENGTH {
+        return Err(SqliteError::InvalidNamespace(format!(
+            "name exceeds {} bytes",
+            MAX_NAMESPACE_LENGTH
+        )));
+    }
+    Ok(())
+}
+
+/// Junction tables indexing the multi-value fields
+const JUNCTION_TABLES: &[&str] = &[
+    "attestation_actors",
+    "attestation_contexts",
+    "attestation_subjects",
+    "attestation_predicates",
+];
+
+/// Insert the junction rows that index `attestation`'s actors, contexts,
+/// subjects and predicates for filtered queries.
+fn write_junction_rows(
+    conn: &Connection,
+    attestation: &Attestation,
+    namespace: &str,
+) -> StoreResult<()> {
+    crate::flight_recorder::record_fmt("put:junction_actors", &attestation.id);
+    for actor in &attestation.actors {
+        conn.execute(
+            "INSERT INTO attestation_actors (attestation_id, actor, namespace) VALUES (?, ?, ?)",
+            rusqlite::params![attestation.id, actor, namespace],
+        )
+        .map_err(SqliteError::from)?;
+    }
+    crate::flight_recorder::record_fmt("put:junction_contexts", &attestation.id);
+    for context in &attestation.contexts {
+        conn.execute(
+            "INSERT INTO attestation_contexts (attestation_id, context, namespace) VALUES (?, ?, ?)",
+            rusqlite::params![attestation.id, context, namespace],
+        )
+        .map_err(SqliteError::from)?;
+    }
+    crate::flight_recorder::record_fmt("put:junction_subjects", &attestation.id);
+    for subject in &attestation.subjects {
+        conn.execute(
+            "INSERT INTO attestation_subjects (attestation_id, subject, namespace) VALUES (?, ?, ?)",
+            rusqlite::params![attestation.id, subject, namespace],
+        )
+        .map_err(SqliteError::from)?;
+    }
+    crate::flight_recorder::record_fmt("put:junction_predicates", &attestation.id);
+    for predicate in &attestation.predicates {
+        conn.execute(
+            "INSERT INTO attestation_predicates (attestation_id, predicate, namespace) VALUES (?, ?, ?)",
+            rusqlite::params![attestation.id, predicate, namespace],
+        )
+        .map_err(SqliteError::from)?;
+    }
+    Ok(())
+}
+
+/// Replace `attestation`'s junction rows after its fields changed in place.
+fn rewrite_junction_rows(
+    conn: &Connection,
+    attestation: &Attestation,
+    namespace: &str,
+) -> StoreResult<()> {
+    for table in JUNCTION_TABLES {
+        conn.execute(
+            &format!(
+                "DELETE FROM {} WHERE attestation_id = ? AND namespace = ?",
+                table
+            ),
+            rusqlite::params![attestation.id, namespace],
+        )
+        .map_err(SqliteError::from)?;
+    }
+    write_junction_rows(conn, attestation, namespace)
+}
+
+/// Run `write` inside a SAVEPOINT on `conn`, so a row write and the
+/// changefeed and history rows recorded with it commit or roll back together.
+/// Nests inside an open transaction or savepoint.
+pub(crate) fn atomically<T, E: From<SqliteError>>(
+    conn: &Connection,
+    write: impl FnOnce() -> std::result::Result<T, E>,
+) -> std::result::Result<T, E> {
+    conn.execute_batch("SAVEPOINT atomic_write")
+        .map_err(SqliteError::from)?;
+    match write() {
+        Ok(value) => {
+            conn.execute_batch("RELEASE SAVEPOINT atomic_write")
+                .map_err(SqliteError::from)?;
+            Ok(value)
+        }
+        Err(e) => {
+            let _ = conn.execute_batch(
+                "ROLLBACK TO SAVEPOINT atomic_write; RELEASE SAVEPOINT atomic_write",
+            );
+            Err(e)
+        }
+    }
+}
+
 /// Insert an attestation through any Connection (shared by SqliteStore and WriteConn).
 /// Handles the main INSERT, junction tables, and enforcement counter updates.
-pub(crate) fn put_attestation(conn: &Connection, attestation: &Attestation) -> StoreResult<()> {
+pub(crate) fn put_attestation(
+    conn: &Connection,
+    attestation: &Attestation,
+    namespace: &str,
+) -> StoreResult<()> {
+    attestation
+        .validate_confidence()
+        .map_err(StoreError::InvalidData)?;
     let subjects_json = serialize_string_vec(&attestation.subjects)?;
     let predicates_json = serialize_string_vec(&attestation.predicates)?;
     let contexts_json = serialize_string_vec(&attestation.contexts)?;
@@ -555,9 +926,10 @@ pub(crate) fn put_attestation(conn: &Connection, attestation: &Attestation) -> S
     let created_at_sql = timestamp_to_sql(attestation.created_at);
 
     crate::flight_recorder::record_fmt("put:insert_main", &attestation.id);
-    conn.execute(
-        "INSERT INTO attestations (id, subjects, predicates, contexts, actors, timestamp, source, attributes, created_at, signature, signer_did)
-         VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+    atomically(conn, || -> StoreResult<()> {
+        conn.execute(
+        "INSERT INTO attestations (id, subjects, predicates, contexts, actors, timestamp, source, attributes, created_at, signature, signer_did, revision, confidence, namespace)
+         VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
         rusqlite::params![
             attestation.id,
             subjects_json,
@@ -570,43 +942,26 @@ pub(crate) fn put_attestation(conn: &Connection, attestation: &Attestation) -> S
             created_at_sql,
             attestation.signature,
             attestation.signer_did,
+            // Stored revisions start at 1; 0 is reserved for "never stored"
+            attestation.revision.max(1) as i64,
+            attestation.confidence.map(f64::from),
+            namespace,
         ],
     )
     .map_err(SqliteError::from)?;
 
-    // Populate junction tables for indexed lookups
-    crate::flight_recorder::record_fmt("put:junction_actors", &attestation.id);
-    for actor in &attestation.actors {
-        conn.execute(
-            "INSERT INTO attestation_actors (attestation_id, actor) VALUES (?, ?)",
-            rusqlite::params![attestation.id, actor],
-        )
-        .map_err(SqliteError::from)?;
-    }
-    crate::flight_recorder::record_fmt("put:junction_contexts", &attestation.id);
-    for context in &attestation.contexts {
-        conn.execute(
-            "INSERT INTO attestation_contexts (attestation_id, context) VALUES (?, ?)",
-            rusqlite::params![attestation.id, context],
-        )
-        .map_err(SqliteError::from)?;
-    }
-    crate::flight_recorder::record_fmt("put:junction_subjects", &attestation.id);
-    for subject in &attestation.subjects {
-        conn.execute(
-            "INSERT INTO attestation_subjects (attestation_id, subject) VALUES (?, ?)",
-            rusqlite::params![attestation.id, subject],
-        )
-        .map_err(SqliteError::from)?;
-    }
-    crate::flight_recorder::record_fmt("put:junction_predicates", &attestation.id);
-    for predicate in &attestation.predicates {
-        conn.execute(
-            "INSERT INTO attestation_predicates (attestation_id, predicate) VALUES (?, ?)",
-            rusqlite::params![attestation.id, predicate],
-        )
-        .map_err(SqliteError::from)?;
-    }
+        write_junction_rows(conn, attestation, namespace)?;
+
+        changes::record_write(conn, namespace, ChangeOp::Put, attestation)?;
+        if history::history_enabled(conn, namespace)? {
+            let stored = Attestation {
+                revision: attestation.revision.max(1),
+                ..attestation.clone()
+            };
+            history::record_write(conn, namespace, HistoryOperation::Put, &stored)?;
+        }
+        Ok(())
+    })?;
 
     crate::flight_recorder::record_fmt("put:done", &attestation.id);
     Ok(())
@@ -622,7 +977,7 @@ impl AttestationStore for SqliteStore {
         let contexts = attestation.contexts.clone();
         let subjects = attestation.subjects.clone();
 
-        put_attestation(&self.conn, &attestation)?;
+        put_attestation(&self.conn, &attestation, &self.namespace)?;
 
         // Update in-memory counters and check thresholds.
         // Skip enforcement when distilling to prevent infinite loops (distill insert → enforce → distill).
@@ -676,139 +1031,172 @@ impl AttestationStore for SqliteStore {
         if self.put_count.is_multiple_of(5000) {
             let _ = self.conn.execute_batch("PRAGMA wal_checkpoint(PASSIVE)");
         }
+        if let Err(e) = self.run_maintenance_policy() {
+            eprintln!("qntx-sqlite: policy checkpoint failed: {}", e);
+        }
 
         Ok(())
     }
 
-    #[allow(clippy::type_complexity)]
+    /// Look up `id` in this store's namespace only. IDs are unique per
+    /// namespace, so another namespace may hold a different attestation with
+    /// the same ID; it is never returned here.
     fn get(&self, id: &str) -> StoreResult<Option<Attestation>> {
-        let mut stmt = self
-            .conn
-            .prepare(
-                "SELECT id, subjects, predicates, contexts, actors, timestamp, source, attributes, created_at, signature, signer_did
-                 FROM attestations
-                 WHERE id = ?",
-            )
-            .map_err(SqliteError::from)?;
-
-        let result: Option<(
-            String,
-            String,
-            String,
-            String,
-            String,
-            String,
-            String,
-            Option<String>,
-            String,
-            Option<Vec<u8>>,
-            Option<String>,
-        )> = stmt
-            .query_row([id], |row| {
-                Ok((
-                    row.get::<_, String>(0)?,
-                    row.get::<_, String>(1)?,
-                    row.get::<_, String>(2)?,
-                    row.get::<_, String>(3)?,
-                    row.get::<_, String>(4)?,
-                    row.get::<_, String>(5)?,
-                    row.get::<_, String>(6)?,
-                    row.get::<_, Option<String>>(7)?,
-                    row.get::<_, String>(8)?,
-                    row.get::<_, Option<Vec<u8>>>(9)?,
-                    row.get::<_, Option<String>>(10)?,
-                ))
-            })
-            .optional()
-            .map_err(SqliteError::from)?;
-
-        match result {
+        match self.get_row(id)? {
             None => Ok(None),
             Some(row_data) => Self::row_to_attestation(row_data).map(Some),
         }
     }
 
-    fn delete(&mut self, id: &str) -> StoreResult<bool> {
-        let rows_affected = self
+    fn exists(&self, id: &str) -> StoreResult<bool> {
+        let mut stmt = self
             .conn
-            .execute("DELETE FROM attestations WHERE id = ?", [id])
+            .prepare_cached("SELECT 1 FROM attestations WHERE namespace = ? AND id = ?")
             .map_err(SqliteError::from)?;
+        Ok(stmt
+            .exists([self.namespace.as_str(), id])
+            .map_err(SqliteError::from)?)
+    }
 
-        Ok(rows_affected > 0)
+    fn delete(&mut self, id: &str) -> StoreResult<bool> {
+        Ok(self.delete_row(id)? > 0)
     }
 
     fn update(&mut self, attestation: Attestation) -> StoreResult<()> {
         if !self.exists(&attestation.id)? {
             return Err(StoreError::NotFound(attestation.id.clone()));
         }
+        self.update_row(&attestation, None)?;
+        Ok(())
+    }
 
-        let subjects_json = serialize_string_vec(&attestation.subjects)?;
-        let predicates_json = serialize_string_vec(&attestation.predicates)?;
-        let contexts_json = serialize_string_vec(&attestation.contexts)?;
-        let actors_json = serialize_string_vec(&attestation.actors)?;
-        let attributes_json = serialize_attributes(&attestation.attributes)?;
-
-        let timestamp_sql = timestamp_to_sql(attestation.timestamp);
+    /// Compare-and-swap in one statement: the UPDATE only matches when the
+    /// stored revision equals `expected_revision`. Creation (`expected_revision`
+    /// 0) relies on the primary key, so a concurrent insert of the same ID loses.
+    fn put_many(&mut self, attestations: Vec<Attestation>) -> Vec<StoreResult<()>> {
+        // One savepoint for the lot, so the batch commits once
+        if self.conn.execute_batch("SAVEPOINT put_many").is_err() {
+            return attestations.into_iter().map(|a| self.put(a)).collect();
+        }
+        let results: Vec<_> = attestations.into_iter().map(|a| self.put(a)).collect();
+        if let Err(e) = self.conn.execute_batch("RELEASE SAVEPOINT put_many") {
+            let _ = self
+                .conn
+                .execute_batch("ROLLBACK TO SAVEPOINT put_many; RELEASE SAVEPOINT put_many");
+            let err: StoreError = SqliteError::from(e).into();
+            return results.into_iter().map(|_| Err(err.clone())).collect();
+        }
+        results
+    }
 
-        self.conn
-            .execute(
-                "UPDATE attestations
-             SET subjects = ?, predicates = ?, contexts = ?, actors = ?,
-                 timestamp = ?, source = ?, attributes = ?, signature = ?, signer_did = ?
-             WHERE id = ?",
-                rusqlite::params![
-                    subjects_json,
-                    predicates_json,
-                    contexts_json,
-                    actors_json,
-                    timestamp_sql,
-                    attestation.source,
-                    attributes_json,
-                    attestation.signature,
-                    attestation.signer_did,
-                    attestation.id,
-                ],
-            )
-            .map_err(SqliteError::from)?;
+    fn put_if_revision(
+        &mut self,
+        attestation: Attestation,
+        expected_revision: u64,
+    ) -> StoreResult<PutOutcome> {
+        let id = attestation.id.clone();
+        let written = if expected_revision == 0 {
+            let attestation = Attestation {
+                revision: 1,
+                ..attestation
+            };
+            match self.put(attestation) {
+                Ok(()) => true,
+                Err(e) if e.kind() == StorageErrorKind::Duplicate => false,
+                Err(e) => return Err(e),
+            }
+        } else {
+            self.update_row(&attestation, Some(expected_revision))? > 0
+        };
 
-        Ok(())
+        if written {
+            Ok(PutOutcome::Written {
+                revision: expected_revision + 1,
+            })
+        } else {
+            Ok(PutOutcome::Conflict {
+                current: self.get(&id)?.map(Box::new),
+            })
+        }
     }
 
     fn ids(&self) -> StoreResult<Vec<String>> {
-        self.query_distinct_values("SELECT id FROM attestations ORDER BY created_at DESC")
+        self.query_distinct_values(
+            "SELECT id FROM attestations WHERE namespace = ?1 ORDER BY created_at DESC",
+        )
     }
 
     fn clear(&mut self) -> StoreResult<()> {
-        self.conn
-            .execute("DELETE FROM attestations", [])
-            .map_err(SqliteError::from)?;
-
-        Ok(())
+        atomically(&self.conn, || -> StoreResult<()> {
+            changes::record_delete_all(&self.conn, &self.namespace)?;
+            history::record_delete_all(&self.conn, &self.namespace)?;
+            self.conn
+                .execute(
+                    "DELETE FROM attestations WHERE namespace = ?",
+                    [&self.namespace],
+                )
+                .map_err(SqliteError::from)?;
+            Ok(())
+        })
     }
 }
 
-/// Build SQL and params for an AxFilter query. Used by both SqliteStore and ReadConn.
-pub fn build_query_sql(filter: &AxFilter) -> (String, Vec<String>) {
+/// Build SQL and params for an AxFilter query restricted to `namespace`.
+/// Used by both SqliteStore and ReadConn.
+///
+/// When [`needs_post_filter`] holds, the SQL leaves out the attribute
+/// conditions it can't express along with `limit` and `over`; finish the
+/// rows with [`post_filter`].
+pub fn build_query_sql(filter: &AxFilter, namespace: &str) -> (String, Vec<String>) {
+    let prefilter = sql_prefilter(filter);
+    let filter = prefilter.as_ref().unwrap_or(filter);
+
     // DISTINCT is only needed when JOINs are present (multi-value junction
     // tables can produce duplicate attestation rows). Without JOINs,
-    // attestations.id is already unique and DISTINCT forces a full-table
-    // dedup scan that blocks LIMIT short-circuit (876K rows → ~14 min).
+    // attestations.id is already unique within the namespace and DISTINCT
+    // forces a full-table dedup scan that blocks LIMIT short-circuit
+    // (876K rows → ~14 min).
     let has_joins = !filter.subjects.is_empty()
         || !filter.predicates.is_empty()
         || !filter.contexts.is_empty()
         || !filter.actors.is_empty();
     let distinct = if has_joins { "DISTINCT " } else { "" };
     let mut sql = format!(
-        "SELECT {}att.id, att.subjects, att.predicates, att.contexts, att.actors, att.timestamp, att.source, att.attributes, att.created_at, att.signature, att.signer_did \
+        "SELECT {}att.id, att.subjects, att.predicates, att.contexts, att.actors, att.timestamp, att.source, att.attributes, att.created_at, att.signature, att.signer_did, att.revision, att.confidence \
          FROM attestations att",
         distinct
     );
+    let (filter_sql, params) = build_filter_sql(filter, namespace);
+
+    if let Some(over) = filter.over {
+        sql = format!(
+            "WITH {ctes} \
+             SELECT att.id, att.subjects, att.predicates, att.contexts, att.actors, att.timestamp, att.source, att.attributes, att.created_at, att.signature, att.signer_did, att.revision, att.confidence \
+             FROM attestations att WHERE (att.namespace, att.id) IN (SELECT t.ns, t.id FROM triples t \
+             JOIN spans g ON g.subject = t.subject AND g.predicate = t.predicate AND g.context = t.context)",
+            ctes = over_ctes(&filter_sql, over.min_span_ms),
+        );
+    } else {
+        sql.push_str(&filter_sql);
+    }
+
+    sql.push_str(" ORDER BY att.created_at DESC, att.rowid DESC");
+    if let Some(limit) = filter.limit {
+        sql.push_str(&format!(" LIMIT {}", limit));
+    }
+
+    (sql, params)
+}
+
+/// JOIN and WHERE clauses (against `attestations att`) shared by the row
+/// query and the matching-set aggregates.
+pub(crate) fn build_filter_sql(filter: &AxFilter, namespace: &str) -> (String, Vec<String>) {
     let mut joins = Vec::new();
-    let mut conditions = Vec::new();
-    let mut params: Vec<String> = Vec::new();
+    let mut conditions = vec!["att.namespace = ?".to_string()];
+    let mut params: Vec<String> = vec![namespace.to_string()];
 
     if !filter.subjects.is_empty() {
-        joins.push("JOIN attestation_subjects js ON att.id = js.attestation_id");
+        joins.push("JOIN attestation_subjects js ON att.id = js.attestation_id AND att.namespace = js.namespace");
         conditions.push(format!(
             "js.subject IN ({})",
             filter
@@ -821,7 +1209,7 @@ pub fn build_query_sql(filter: &AxFilter) -> (String, Vec<String>) {
         params.extend(filter.subjects.iter().cloned());
     }
     if !filter.predicates.is_empty() {
-        joins.push("JOIN attestation_predicates jp ON att.id = jp.attestation_id");
+        joins.push("JOIN attestation_predicates jp ON att.id = jp.attestation_id AND att.namespace = jp.namespace");
         conditions.push(format!(
             "jp.predicate IN ({})",
             filter
@@ -834,7 +1222,7 @@ pub fn build_query_sql(filter: &AxFilter) -> (String, Vec<String>) {
         params.extend(filter.predicates.iter().cloned());
     }
     if !filter.contexts.is_empty() {
-        joins.push("JOIN attestation_contexts jc ON att.id = jc.attestation_id");
+        joins.push("JOIN attestation_contexts jc ON att.id = jc.attestation_id AND att.namespace = jc.namespace");
         conditions.push(format!(
             "jc.context IN ({})",
             filter
@@ -847,7 +1235,7 @@ pub fn build_query_sql(filter: &AxFilter) -> (String, Vec<String>) {
         params.extend(filter.contexts.iter().cloned());
     }
     if !filter.actors.is_empty() {
-        joins.push("JOIN attestation_actors ja ON att.id = ja.attestation_id");
+        joins.push("JOIN attestation_actors ja ON att.id = ja.attestation_id AND att.namespace = ja.namespace");
         conditions.push(format!(
             "ja.actor IN ({})",
             filter
@@ -871,58 +1259,341 @@ pub fn build_query_sql(filter: &AxFilter) -> (String, Vec<String>) {
         conditions.push("att.timestamp <= ?".to_string());
         params.push(crate::json::timestamp_to_sql(end));
     }
+    if let Some(min) = filter.min_confidence {
+        // Widened exactly as stored values are, so a bound equal to a
+        // stored f32 matches it
+        conditions.push("att.confidence >= ?".to_string());
+        params.push(f64::from(min).to_string());
+    }
+    // Conditions SQL can't express are checked by post_filter instead
+    for (sql, condition_params) in filter
+        .attribute_conditions
+        .iter()
+        .filter_map(attribute_condition_sql)
+    {
+        conditions.push(sql);
+        params.extend(condition_params);
+    }
 
+    let mut filter_sql = String::new();
     for join in &joins {
-        sql.push(' ');
-        sql.push_str(join);
+        filter_sql.push(' ');
+        filter_sql.push_str(join);
     }
-    if !conditions.is_empty() {
-        sql.push_str(" WHERE ");
-        sql.push_str(&conditions.join(" AND "));
+    filter_sql.push_str(" WHERE ");
+    filter_sql.push_str(&conditions.join(" AND "));
+
+    (filter_sql, params)
+}
+
+/// Attributes as JSON, or NULL when the column doesn't hold valid JSON, so a
+/// corrupt row fails the condition instead of the whole query.
+const ATTRIBUTES_JSON: &str = "(CASE WHEN json_valid(att.attributes) THEN att.attributes END)";
+
+/// SQL for one attribute condition, with its params, or None when it can't
+/// be pushed down: keys that don't fit a quoted JSON path label, and
+/// non-finite numbers. Bound params are all TEXT, so numbers and bools are
+/// cast back before comparing with `json_extract`, which keeps the JSON type.
+fn attribute_condition_sql(condition: &AttrCondition) -> Option<(String, Vec<String>)> {
+    let key = &condition.key;
+    if key.is_empty() || key.contains(['"', '\\']) || key.chars().any(char::is_control) {
+        return None;
     }
-    sql.push_str(" ORDER BY att.created_at DESC, att.rowid DESC");
+    let path = format!("$.\"{}\"", key);
+    let json_type = format!("json_type({ATTRIBUTES_JSON}, ?)");
+    let extract = format!("json_extract({ATTRIBUTES_JSON}, ?)");
+
+    let (types, operand, param) = match &condition.value {
+        AttrValue::Number(n) if !n.is_finite() => return None,
+        AttrValue::Number(n) => ("'integer', 'real'", "CAST(? AS REAL)", n.to_string()),
+        AttrValue::String(v) => ("'text'", "?", v.clone()),
+        AttrValue::Bool(b) => (
+            "'true', 'false'",
+            "CAST(? AS INTEGER)",
+            u8::from(*b).to_string(),
+        ),
+    };
+    // Present with the operand's type, compared with `cmp`
+    let typed = |cmp: &str| {
+        (
+            format!("({json_type} IN ({types}) AND {extract} {cmp} {operand})"),
+            vec![path.clone(), path.clone(), param.clone()],
+        )
+    };
+
+    Some(match condition.op {
+        AttrOp::Exists if condition.value == AttrValue::Bool(false) => {
+            (format!("{json_type} IS NULL"), vec![path])
+        }
+        AttrOp::Exists => (format!("{json_type} IS NOT NULL"), vec![path]),
+        AttrOp::Eq => typed("="),
+        AttrOp::Ne => {
+            let (eq, eq_params) = typed("=");
+            let mut params = vec![path];
+            params.extend(eq_params);
+            (format!("({json_type} IS NOT NULL AND NOT {eq})"), params)
+        }
+        AttrOp::Gt => typed(">"),
+        AttrOp::Gte => typed(">="),
+        AttrOp::Lt => typed("<"),
+        AttrOp::Lte => typed("<="),
+        AttrOp::Contains => {
+            let element = format!(
+                "({json_type} = 'array' AND EXISTS (SELECT 1 FROM json_each({ATTRIBUTES_JSON}, ?) je \
+                 WHERE je.type IN ({types}) AND je.value = {operand}))"
+            );
+            let element_params = vec![path.clone(), path.clone(), param.clone()];
+            match &condition.value {
+                AttrValue::String(_) => {
+                    let mut params = vec![path.clone(), path, param];
+                    params.extend(element_params);
+                    (
+                        format!(
+                            "(({json_type} = 'text' AND instr({extract}, ?) > 0) OR {element})"
+                        ),
+                        params,
+                    )
+                }
+                _ => (element, element_params),
+            }
+        }
+    })
+}
+
+/// Whether some attribute condition of `filter` can't be evaluated in SQL.
+pub fn needs_post_filter(filter: &AxFilter) -> bool {
+    filter
+        .attribute_conditions
+        .iter()
+        .any(|c| attribute_condition_sql(c).is_none())
+}
+
+/// The filter to run in SQL when [`needs_post_filter`] holds: only the
+/// attribute conditions SQL can express, and no `limit` or `over`, which
+/// must wait until every condition has been checked.
+fn sql_prefilter(filter: &AxFilter) -> Option<AxFilter> {
+    if !needs_post_filter(filter) {
+        return None;
+    }
+    Some(AxFilter {
+        attribute_conditions: filter
+            .attribute_conditions
+            .iter()
+            .filter(|c| attribute_condition_sql(c).is_some())
+            .cloned()
+            .collect(),
+        limit: None,
+        over: None,
+        ..filter.clone()
+    })
+}
+
+/// Finish the rows of a [`needs_post_filter`] query: apply the attribute
+/// conditions, then `over`, then `limit`, as the in-memory store does. The
+/// matching summary, when the filter asks for one, covers the set before
+/// `limit`.
+pub fn post_filter(
+    filter: &AxFilter,
+    mut attestations: Vec<Attestation>,
+) -> (Vec<Attestation>, Option<MatchingSummary>) {
+    attestations.retain(|a| filter.matches_attributes(&a.attributes));
+    if let Some(over) = &filter.over {
+        attestations = over.retain_spanning(attestations);
+    }
+    let matching = filter
+        .include_summary
+        .then(|| MatchingSummary::from_attestations(&attestations));
     if let Some(limit) = filter.limit {
-        sql.push_str(&format!(" LIMIT {}", limit));
+        attestations.truncate(limit);
     }
+    (attestations, matching)
+}
 
-    (sql, params)
+/// `matched`, `triples` and `spans` CTEs for an "over" filter.
+fn over_ctes(filter_sql: &str, min_span_ms: i64) -> String {
+    // Span is measured over the rows that pass every other condition,
+    // grouped by each (subject, predicate, context) they assert.
+    // min_span_ms is an i64, so inlining it is injection-safe and keeps
+    // the comparison numeric (bound params here are all TEXT). The span is
+    // rounded to whole milliseconds first: julianday is a float, and a span
+    // exactly at the bound can otherwise land a fraction below it.
+    format!(
+        "matched AS (SELECT DISTINCT att.id AS id, att.namespace AS ns, att.timestamp AS ts FROM attestations att{filter_sql}), \
+         triples AS (SELECT m.id AS id, m.ns AS ns, m.ts AS ts, s.subject AS subject, p.predicate AS predicate, c.context AS context FROM matched m \
+         JOIN attestation_subjects s ON s.attestation_id = m.id AND s.namespace = m.ns \
+         JOIN attestation_predicates p ON p.attestation_id = m.id AND p.namespace = m.ns \
+         JOIN attestation_contexts c ON c.attestation_id = m.id AND c.namespace = m.ns), \
+         spans AS (SELECT subject, predicate, context FROM triples GROUP BY subject, predicate, context \
+         HAVING CAST(ROUND((MAX(julianday(ts)) - MIN(julianday(ts))) * 86400000.0) AS INTEGER) >= {min_span_ms})",
+    )
+}
+
+/// CTE list ending in `m(id, ns, ts)`: every attestation the filter
+/// matches, ignoring `limit`.
+fn build_matching_ctes(filter: &AxFilter, namespace: &str) -> (String, Vec<String>) {
+    let (filter_sql, params) = build_filter_sql(filter, namespace);
+    let ctes = match filter.over {
+        Some(over) => format!(
+            "{}, m AS (SELECT DISTINCT t.id AS id, t.ns AS ns, t.ts AS ts FROM triples t \
+             JOIN spans g ON g.subject = t.subject AND g.predicate = t.predicate AND g.context = t.context)",
+            over_ctes(&filter_sql, over.min_span_ms)
+        ),
+        None => format!(
+            "m AS (SELECT DISTINCT att.id AS id, att.namespace AS ns, att.timestamp AS ts FROM attestations att{})",
+            filter_sql
+        ),
+    };
+    (ctes, params)
+}
+
+/// Aggregate the full matching set of `filter` with SQL, without loading rows.
+pub(crate) fn query_matching_summary(
+    conn: &Connection,
+    filter: &AxFilter,
+    namespace: &str,
+) -> Result<MatchingSummary, SqliteError> {
+    let (ctes, params) = build_matching_ctes(filter, namespace);
+    let param_refs: Vec<&dyn rusqlite::ToSql> =
+        params.iter().map(|p| p as &dyn rusqlite::ToSql).collect();
+
+    // Several statements read the matching set; a read transaction keeps
+    // them on one snapshot when the caller isn't already inside one.
+    let tx = if conn.is_autocommit() {
+        Some(conn.unchecked_transaction()?)
+    } else {
+        None
+    };
+
+    let distinct = |table: &str, column: &str| {
+        format!(
+            "(SELECT COUNT(DISTINCT j.{column}) FROM m JOIN {table} j ON j.attestation_id = m.id AND j.namespace = m.ns)"
+        )
+    };
+    let sql = format!(
+        "WITH {ctes} SELECT COUNT(*), {}, {}, {}, {}, \
+         (SELECT ts FROM m ORDER BY julianday(ts) ASC LIMIT 1), \
+         (SELECT ts FROM m ORDER BY julianday(ts) DESC LIMIT 1) FROM m",
+        distinct("attestation_subjects", "subject"),
+        distinct("attestation_predicates", "predicate"),
+        distinct("attestation_contexts", "context"),
+        distinct("attestation_actors", "actor"),
+    );
+    let (total, subjects, predicates, contexts, actors, earliest, latest) =
+        conn.query_row(&sql, &param_refs[..], |row| {
+            Ok((
+                row.get::<_, usize>(0)?,
+                row.get::<_, usize>(1)?,
+                row.get::<_, usize>(2)?,
+                row.get::<_, usize>(3)?,
+                row.get::<_, usize>(4)?,
+                row.get::<_, Option<String>>(5)?,
+                row.get::<_, Option<String>>(6)?,
+            ))
+        })?;
+
+    let top_terms = |table: &str, column: &str| -> rusqlite::Result<Vec<TermCount>> {
+        let sql = format!(
+            "WITH {ctes} SELECT j.{column}, COUNT(DISTINCT m.id) AS n FROM m \
+             JOIN {table} j ON j.attestation_id = m.id AND j.namespace = m.ns \
+             GROUP BY j.{column} ORDER BY n DESC, j.{column} ASC LIMIT {SUMMARY_TOP_N}"
+        );
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt.query_map(&param_refs[..], |row| {
+            Ok(TermCount {
+                value: row.get(0)?,
+                count: row.get(1)?,
+            })
+        })?;
+        rows.collect()
+    };
+    let top_predicates = top_terms("attestation_predicates", "predicate")?;
+    let top_contexts = top_terms("attestation_contexts", "context")?;
+
+    // Bucketed in Rust so deciles match MatchingSummary::from_attestations
+    let mut confidence_histogram = Vec::new();
+    let mut stmt = conn.prepare(&format!(
+        "WITH {ctes} SELECT att.confidence, COUNT(*) FROM m \
+         JOIN attestations att ON att.namespace = m.ns AND att.id = m.id \
+         WHERE att.confidence IS NOT NULL GROUP BY att.confidence"
+    ))?;
+    let mut rows = stmt.query(&param_refs[..])?;
+    while let Some(row) = rows.next()? {
+        if confidence_histogram.is_empty() {
+            confidence_histogram = vec![0; CONFIDENCE_BUCKETS];
+        }
+        let confidence = row.get::<_, f64>(0)? as f32;
+        confidence_histogram[confidence_bucket(confidence)] += row.get::<_, usize>(1)?;
+    }
+    drop(rows);
+    drop(stmt);
+
+    if let Some(tx) = tx {
+        tx.finish()?;
+    }
+
+    Ok(MatchingSummary {
+        total,
+        distinct_subjects: subjects,
+        distinct_predicates: predicates,
+        distinct_contexts: contexts,
+        distinct_actors: actors,
+        top_predicates,
+        top_contexts,
+        earliest: earliest
+            .map(|ts| crate::json::sql_to_timestamp(&ts))
+            .transpose()?,
+        latest: latest
+            .map(|ts| crate::json::sql_to_timestamp(&ts))
+            .transpose()?,
+        confidence_histogram,
+    })
 }
 
 impl QueryStore for SqliteStore {
     fn query(&self, filter: &AxFilter) -> StoreResult<AxResult> {
-        let (sql, params) = build_query_sql(filter);
+        filter
+            .validate_aliases_expanded()
+            .map_err(StoreError::Query)?;
+        let (sql, params) = build_query_sql(filter, &self.namespace);
+
+        // The rows and the SQL-side matching summary must see one snapshot
+        let sql_summary = filter.include_summary && !needs_post_filter(filter);
+        let tx = if sql_summary && self.conn.is_autocommit() {
+            Some(
+                self.conn
+                    .unchecked_transaction()
+                    .map_err(SqliteError::from)?,
+            )
+        } else {
+            None
+        };
 
-        let mut stmt = self.conn.prepare(&sql).map_err(SqliteError::from)?;
+        // Filters of the same shape produce the same SQL, so the cache hits
+        // across differing values
+        let mut stmt = self.conn.prepare_cached(&sql).map_err(SqliteError::from)?;
 
         let param_refs: Vec<&dyn rusqlite::ToSql> =
             params.iter().map(|p| p as &dyn rusqlite::ToSql).collect();
 
         let rows = stmt
-            .query_map(&param_refs[..], |row| {
-                Ok((
-                    row.get::<_, String>(0)?,
-                    row.get::<_, String>(1)?,
-                    row.get::<_, String>(2)?,
-                    row.get::<_, String>(3)?,
-                    row.get::<_, String>(4)?,
-                    row.get::<_, String>(5)?,
-                    row.get::<_, String>(6)?,
-                    row.get::<_, Option<String>>(7)?,
-                    row.get::<_, String>(8)?,
-                    row.get::<_, Option<Vec<u8>>>(9)?,
-                    row.get::<_, Option<String>>(10)?,
-                ))
-            })
+            .query_map(&param_refs[..], crate::json::read_attestation_row)
             .map_err(SqliteError::from)?;
 
-        let mut attestations = Vec::new();
-        for row_result in rows {
-            let row_data = row_result.map_err(SqliteError::from)?;
-            attestations.push(Self::row_to_attestation(row_data)?);
+        let mut attestations = self.decode_rows(rows)?;
+        drop(stmt);
+        let mut matching = None;
+        if needs_post_filter(filter) {
+            (attestations, matching) = post_filter(filter, attestations);
+        } else if sql_summary {
+            matching = Some(query_matching_summary(&self.conn, filter, &self.namespace)?);
+        }
+        if let Some(tx) = tx {
+            tx.finish().map_err(SqliteError::from)?;
         }
 
         // Build summary
-        let summary = build_summary(&attestations);
+        let mut summary = build_summary(&attestations);
+        summary.matching = matching;
 
         Ok(AxResult {
             attestations,
@@ -933,24 +1604,26 @@ impl QueryStore for SqliteStore {
 
     fn predicates(&self) -> StoreResult<Vec<String>> {
         self.query_distinct_values(
-            "SELECT DISTINCT predicate FROM attestation_predicates ORDER BY predicate",
+            "SELECT DISTINCT predicate FROM attestation_predicates WHERE namespace = ?1 ORDER BY predicate",
         )
     }
 
     fn contexts(&self) -> StoreResult<Vec<String>> {
         self.query_distinct_values(
-            "SELECT DISTINCT context FROM attestation_contexts ORDER BY context",
+            "SELECT DISTINCT context FROM attestation_contexts WHERE namespace = ?1 ORDER BY context",
         )
     }
 
     fn subjects(&self) -> StoreResult<Vec<String>> {
         self.query_distinct_values(
-            "SELECT DISTINCT subject FROM attestation_subjects ORDER BY subject",
+            "SELECT DISTINCT subject FROM attestation_subjects WHERE namespace = ?1 ORDER BY subject",
         )
     }
 
     fn actors(&self) -> StoreResult<Vec<String>> {
-        self.query_distinct_values("SELECT DISTINCT actor FROM attestation_actors ORDER BY actor")
+        self.query_distinct_values(
+            "SELECT DISTINCT actor FROM attestation_actors WHERE namespace = ?1 ORDER BY actor",
+        )
     }
 
     fn stats(&self) -> StoreResult<StorageStats> {
@@ -965,13 +1638,14 @@ impl QueryStore for SqliteStore {
 }
 
 /// Build a summary from a list of attestations.
-fn build_summary(attestations: &[Attestation]) -> AxSummary {
+pub(crate) fn build_summary(attestations: &[Attestation]) -> AxSummary {
     let mut summary = AxSummary {
         total_attestations: attestations.len(),
         unique_subjects: HashMap::new(),
         unique_predicates: HashMap::new(),
         unique_contexts: HashMap::new(),
         unique_actors: HashMap::new(),
+        matching: None,
     };
 
     for attestation in attestations {
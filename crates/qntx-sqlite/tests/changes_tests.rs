@@ -0,0 +1,246 @@
+//! Changefeed tests: ordering, resumption, pruning and persistence
+
+use qntx_core::{
+    storage::{AttestationStore, ChangeFeed, ChangeOp, ChangeRetention},
+    Attestation, AttestationBuilder,
+};
+use qntx_sqlite::SqliteStore;
+
+fn attestation(id: &str, predicate: &str) -> Attestation {
+    AttestationBuilder::new()
+        .id(id)
+        .subject("ALICE")
+        .predicate(predicate)
+        .context("work")
+        .actor("human:bob")
+        .timestamp(1704067200000)
+        .source("test")
+        .build()
+}
+
+fn ops(store: &SqliteStore, since: u64) -> Vec<(u64, ChangeOp, String)> {
+    store
+        .changes_since(since, 100)
+        .unwrap()
+        .events
+        .into_iter()
+        .map(|e| (e.seq, e.op, e.attestation_id))
+        .collect()
+}
+
+#[test]
+fn test_interleaved_writes_are_gap_free_and_ordered() {
+    let mut store = SqliteStore::in_memory().unwrap();
+    assert_eq!(store.latest_seq().unwrap(), 0);
+
+    store.put(attestation("AS-1", "knows")).unwrap();
+    store.put(attestation("AS-2", "knows")).unwrap();
+    store.delete("AS-1").unwrap();
+    assert!(!store.delete("AS-1").unwrap());
+    store.update(attestation("AS-2", "likes")).unwrap();
+    store.put(attestation("AS-3", "knows")).unwrap();
+    store.clear().unwrap();
+
+    let events = ops(&store, 0);
+    let expected = vec![
+        (1, ChangeOp::Put, "AS-1"),
+        (2, ChangeOp::Put, "AS-2"),
+        (3, ChangeOp::Delete, "AS-1"),
+        (4, ChangeOp::Update, "AS-2"),
+        (5, ChangeOp::Put, "AS-3"),
+        (6, ChangeOp::Delete, "AS-2"),
+        (7, ChangeOp::Delete, "AS-3"),
+    ];
+    assert_eq!(
+        events,
+        expected
+            .into_iter()
+            .map(|(seq, op, id)| (seq, op, id.to_string()))
+            .collect::<Vec<_>>()
+    );
+
+    let batch = store.changes_since(0, 100).unwrap();
+    assert_eq!(batch.latest_seq, 7);
+    assert!(!batch.missed);
+    assert_eq!(
+        batch.events[3].content_hash,
+        Some(attestation("AS-2", "likes").content_hash())
+    );
+    assert!(batch.events[2].content_hash.is_none());
+}
+
+#[test]
+fn test_resume_from_mid_stream() {
+    let mut store = SqliteStore::in_memory().unwrap();
+    for i in 1..=5 {
+        store
+            .put(attestation(&format!("AS-{}", i), "knows"))
+            .unwrap();
+    }
+
+    let first = store.changes_since(0, 2).unwrap();
+    assert_eq!(first.events.len(), 2);
+    assert_eq!(first.latest_seq, 5);
+
+    let cursor = first.events.last().unwrap().seq;
+    store.delete("AS-1").unwrap();
+    let rest: Vec<u64> = ops(&store, cursor).into_iter().map(|e| e.0).collect();
+    assert_eq!(rest, vec![3, 4, 5, 6]);
+    assert!(ops(&store, 6).is_empty());
+}
+
+#[test]
+fn test_writes_and_events_commit_together() {
+    let mut store = SqliteStore::in_memory().unwrap();
+    store.put(attestation("AS-1", "knows")).unwrap();
+    let revision = store.get("AS-1").unwrap().unwrap().revision;
+
+    // Recording the event fails: the row write must not land either
+    store
+        .connection()
+        .execute_batch(
+            "CREATE TEMP TRIGGER fail_events BEFORE INSERT ON attestation_changes
+             BEGIN SELECT RAISE(ABORT, 'changefeed unavailable'); END;",
+        )
+        .unwrap();
+    assert!(store.put(attestation("AS-2", "knows")).is_err());
+    assert!(store.update(attestation("AS-1", "likes")).is_err());
+    assert!(!store.exists("AS-2").unwrap());
+    let stored = store.get("AS-1").unwrap().unwrap();
+    assert_eq!(stored.predicates, vec!["knows"]);
+    assert_eq!(stored.revision, revision);
+
+    // The row delete fails after its event was recorded: the event goes too
+    store
+        .connection()
+        .execute_batch(
+            "DROP TRIGGER fail_events;
+             CREATE TEMP TRIGGER fail_deletes BEFORE DELETE ON attestations
+             BEGIN SELECT RAISE(ABORT, 'delete refused'); END;",
+        )
+        .unwrap();
+    assert!(store.delete("AS-1").is_err());
+    assert!(store.clear().is_err());
+    assert_eq!(store.latest_seq().unwrap(), 1);
+    assert!(store.exists("AS-1").unwrap());
+    assert!(store.connection().is_autocommit());
+}
+
+#[test]
+fn test_batch_import_numbers_rows_and_rollback_leaves_no_events() {
+    let mut store = SqliteStore::in_memory().unwrap();
+    store.put(attestation("AS-0", "knows")).unwrap();
+
+    let batch = (1..=4)
+        .map(|i| attestation(&format!("AS-{}", i), "knows"))
+        .collect();
+    let (stored, existing) = store.put_batched(batch, 2).unwrap();
+    assert_eq!((stored, existing), (4, 0));
+    let ids: Vec<String> = ops(&store, 1).into_iter().map(|e| e.2).collect();
+    assert_eq!(ids, vec!["AS-1", "AS-2", "AS-3", "AS-4"]);
+
+    // Invalid confidence fails the batch; its puts and events roll back
+    let mut bad = attestation("AS-6", "knows");
+    bad.confidence = Some(2.0);
+    assert!(store
+        .put_batched(vec![attestation("AS-5", "knows"), bad], 10)
+        .is_err());
+    assert_eq!(store.latest_seq().unwrap(), 5);
+    store.put(attestation("AS-7", "knows")).unwrap();
+    assert_eq!(ops(&store, 5), vec![(6, ChangeOp::Put, "AS-7".to_string())]);
+}
+
+#[test]
+fn test_prune_preserves_latest_seq() {
+    let mut store = SqliteStore::in_memory().unwrap();
+    for i in 1..=5 {
+        store
+            .put(attestation(&format!("AS-{}", i), "knows"))
+            .unwrap();
+    }
+
+    let removed = store
+        .prune_changes(
+            &ChangeRetention {
+                max_events: Some(2),
+                ..Default::default()
+            },
+            0,
+        )
+        .unwrap();
+    assert_eq!(removed, 3);
+    assert_eq!(store.latest_seq().unwrap(), 5);
+
+    // A consumer at 1 lost events 2 and 3; one at 3 did not
+    assert!(store.changes_since(1, 100).unwrap().missed);
+    let resumed = store.changes_since(3, 100).unwrap();
+    assert!(!resumed.missed);
+    assert_eq!(resumed.events.len(), 2);
+
+    // Age-based pruning of everything keeps the counter
+    let now = chrono::Utc::now().timestamp_millis() + 60_000;
+    store
+        .prune_changes(
+            &ChangeRetention {
+                max_age_ms: Some(1_000),
+                ..Default::default()
+            },
+            now,
+        )
+        .unwrap();
+    let batch = store.changes_since(5, 100).unwrap();
+    assert!(batch.events.is_empty());
+    assert_eq!(batch.latest_seq, 5);
+    assert!(!batch.missed);
+
+    store.delete("AS-1").unwrap();
+    assert_eq!(
+        ops(&store, 5),
+        vec![(6, ChangeOp::Delete, "AS-1".to_string())]
+    );
+}
+
+#[test]
+fn test_sequence_survives_reopen() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("changes.db");
+    {
+        let mut store = SqliteStore::open(&path).unwrap();
+        store.put(attestation("AS-1", "knows")).unwrap();
+        store.put(attestation("AS-2", "knows")).unwrap();
+        store
+            .prune_changes(
+                &ChangeRetention {
+                    max_events: Some(0),
+                    ..Default::default()
+                },
+                0,
+            )
+            .unwrap();
+    }
+
+    let mut store = SqliteStore::open(&path).unwrap();
+    assert_eq!(store.latest_seq().unwrap(), 2);
+    store.delete("AS-2").unwrap();
+    assert_eq!(
+        ops(&store, 2),
+        vec![(3, ChangeOp::Delete, "AS-2".to_string())]
+    );
+}
+
+#[test]
+fn test_namespaces_number_independently() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut alpha = SqliteStore::open(dir.path().join("ns.db")).unwrap();
+    let mut beta = alpha.scoped("beta").unwrap();
+
+    alpha.put(attestation("AS-1", "knows")).unwrap();
+    beta.put(attestation("AS-1", "knows")).unwrap();
+    beta.put(attestation("AS-2", "knows")).unwrap();
+
+    assert_eq!(alpha.latest_seq().unwrap(), 1);
+    assert_eq!(
+        ops(&beta, 0).into_iter().map(|e| e.0).collect::<Vec<_>>(),
+        vec![1, 2]
+    );
+}
@@ -1,7 +1,13 @@
 //! CRUD operation tests for SqliteStore
 
-use qntx_core::{storage::AttestationStore, AttestationBuilder};
-use qntx_sqlite::SqliteStore;
+use qntx_core::{
+    storage::{
+        AttestationStore, ChangeFeed, ChangeOp, MemoryStore, PutOutcome, QueryStore,
+        StorageErrorKind,
+    },
+    AttestationBuilder, AxFilter,
+};
+use qntx_sqlite::{HistoryOperation, RepairAction, SqliteStore};
 
 /// Helper to create a test attestation
 fn create_test_attestation(id: &str) -> qntx_core::Attestation {
@@ -49,6 +55,26 @@ fn test_put_duplicate_fails() {
     assert!(result.is_err());
 }
 
+#[test]
+fn test_put_many_reports_each_result() {
+    let mut store = SqliteStore::in_memory().unwrap();
+    store.put(create_test_attestation("AS-2")).unwrap();
+
+    let results = store.put_many(vec![
+        create_test_attestation("AS-1"),
+        create_test_attestation("AS-2"),
+        create_test_attestation("AS-3"),
+    ]);
+    assert!(results[0].is_ok());
+    assert_eq!(
+        results[1].as_ref().unwrap_err().kind(),
+        StorageErrorKind::Duplicate
+    );
+    assert!(results[2].is_ok());
+    assert_eq!(store.count().unwrap(), 3);
+    assert!(store.connection().is_autocommit());
+}
+
 #[test]
 fn test_get_nonexistent() {
     let store = SqliteStore::in_memory().unwrap();
@@ -97,6 +123,39 @@ fn test_update() {
     assert_eq!(retrieved.subjects, vec!["BOB"]);
 }
 
+#[test]
+fn test_update_reindexes_actors() {
+    let mut store = SqliteStore::in_memory().unwrap();
+    let mut attestation = create_test_attestation("AS-test-1");
+    store.put(attestation.clone()).unwrap();
+
+    attestation.actors = vec!["human:carol".to_string()];
+    attestation.contexts = vec!["home".to_string()];
+    store.update(attestation).unwrap();
+
+    let ids = |filter: AxFilter| -> Vec<String> {
+        store
+            .query(&filter)
+            .unwrap()
+            .attestations
+            .into_iter()
+            .map(|a| a.id)
+            .collect()
+    };
+    let by_actor = |actor: &str| AxFilter {
+        actors: vec![actor.to_string()],
+        ..Default::default()
+    };
+    assert_eq!(ids(by_actor("human:carol")), vec!["AS-test-1"]);
+    assert!(ids(by_actor("human:bob")).is_empty());
+    assert!(ids(AxFilter {
+        contexts: vec!["work".to_string()],
+        ..Default::default()
+    })
+    .is_empty());
+    assert_eq!(store.actors().unwrap(), vec!["human:carol"]);
+}
+
 #[test]
 fn test_update_nonexistent() {
     let mut store = SqliteStore::in_memory().unwrap();
@@ -107,6 +166,176 @@ fn test_update_nonexistent() {
     assert!(result.is_err());
 }
 
+#[test]
+fn test_put_if_revision_between_connections() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("revisions.db");
+    let mut writer_a = SqliteStore::open(&path).unwrap();
+    let mut writer_b = SqliteStore::open(&path).unwrap();
+
+    writer_a.put(create_test_attestation("AS-test-1")).unwrap();
+
+    // Both writers read revision 1
+    let mut seen_a = writer_a.get("AS-test-1").unwrap().unwrap();
+    let mut seen_b = writer_b.get("AS-test-1").unwrap().unwrap();
+    assert_eq!(seen_a.revision, 1);
+    assert_eq!(seen_b.revision, 1);
+
+    seen_a.subjects = vec!["BOB".to_string()];
+    assert_eq!(
+        writer_a.put_if_revision(seen_a, 1).unwrap(),
+        PutOutcome::Written { revision: 2 }
+    );
+
+    seen_b.subjects = vec!["CAROL".to_string()];
+    match writer_b.put_if_revision(seen_b, 1).unwrap() {
+        PutOutcome::Conflict { current } => {
+            let current = current.unwrap();
+            assert_eq!(current.subjects, vec!["BOB"]);
+            assert_eq!(current.revision, 2);
+        }
+        other => panic!("expected conflict, got {:?}", other),
+    }
+
+    // Expecting "does not exist" for an existing row is also a conflict
+    assert!(matches!(
+        writer_b
+            .put_if_revision(create_test_attestation("AS-test-1"), 0)
+            .unwrap(),
+        PutOutcome::Conflict { .. }
+    ));
+
+    // Plain writes keep working alongside conditional ones
+    writer_b.put(create_test_attestation("AS-test-2")).unwrap();
+    assert_eq!(writer_a.get("AS-test-2").unwrap().unwrap().revision, 1);
+}
+
+#[test]
+fn test_missing_id_error_code_matches_memory_store() {
+    let mut sqlite = SqliteStore::in_memory().unwrap();
+    let mut memory = MemoryStore::new();
+
+    let sqlite_err = sqlite
+        .update(create_test_attestation("AS-missing"))
+        .unwrap_err();
+    let memory_err = memory
+        .update(create_test_attestation("AS-missing"))
+        .unwrap_err();
+
+    assert_eq!(sqlite_err.kind(), StorageErrorKind::NotFound);
+    assert_eq!(sqlite_err.code(), memory_err.code());
+}
+
+/// Store with AS-good intact and AS-bad carrying an unparseable attributes column.
+fn store_with_corrupt_attributes() -> SqliteStore {
+    let mut store = SqliteStore::in_memory().unwrap();
+    store.put(create_test_attestation("AS-good")).unwrap();
+    store.put(create_test_attestation("AS-bad")).unwrap();
+    store
+        .connection()
+        .execute(
+            "UPDATE attestations SET attributes = '{broken' WHERE id = 'AS-bad'",
+            [],
+        )
+        .unwrap();
+    store
+}
+
+#[test]
+fn test_query_skips_and_quarantines_corrupt_rows() {
+    let store = store_with_corrupt_attributes();
+
+    let result = store.query(&AxFilter::default()).unwrap();
+    assert_eq!(result.attestations.len(), 1);
+    assert_eq!(result.attestations[0].id, "AS-good");
+    assert_eq!(store.last_corrupt_count(), 1);
+
+    // Querying again skips the row again but does not duplicate the quarantine entry
+    store.query(&AxFilter::default()).unwrap();
+    let corrupt = store.corrupt_rows();
+    assert_eq!(corrupt.len(), 1);
+    assert_eq!(corrupt[0].id, "AS-bad");
+    assert_eq!(corrupt[0].column, "attributes");
+}
+
+#[test]
+fn test_strict_decoding_fails_on_corrupt_row() {
+    let mut store = store_with_corrupt_attributes();
+    store.set_strict_decoding(true);
+
+    let err = store.query(&AxFilter::default()).unwrap_err();
+    assert_eq!(err.kind(), StorageErrorKind::Corruption);
+    assert!(err.to_string().contains("AS-bad"));
+}
+
+#[test]
+fn test_repair_row() {
+    let mut store = store_with_corrupt_attributes();
+    store.query(&AxFilter::default()).unwrap();
+
+    store
+        .repair_row("AS-bad", RepairAction::DropAttributes)
+        .unwrap();
+    assert!(store.corrupt_rows().is_empty());
+
+    let result = store.query(&AxFilter::default()).unwrap();
+    assert_eq!(result.attestations.len(), 2);
+    assert!(store.get("AS-bad").unwrap().unwrap().attributes.is_empty());
+
+    store.repair_row("AS-bad", RepairAction::DeleteRow).unwrap();
+    assert!(!store.exists("AS-bad").unwrap());
+
+    let err = store
+        .repair_row("AS-absent", RepairAction::DeleteRow)
+        .unwrap_err();
+    assert_eq!(err.kind(), StorageErrorKind::NotFound);
+    let err = store
+        .repair_row("AS-absent", RepairAction::DropAttributes)
+        .unwrap_err();
+    assert_eq!(err.kind(), StorageErrorKind::NotFound);
+}
+
+#[test]
+fn test_drop_attributes_is_a_recorded_update() {
+    let mut store = store_with_corrupt_attributes();
+    store.enable_history().unwrap();
+    let revision = |store: &SqliteStore| -> i64 {
+        store
+            .connection()
+            .query_row(
+                "SELECT revision FROM attestations WHERE id = 'AS-bad'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap()
+    };
+    let before = revision(&store);
+    let latest = store.changes_since(0, 100).unwrap().latest_seq;
+
+    store
+        .repair_row("AS-bad", RepairAction::DropAttributes)
+        .unwrap();
+
+    // Revision bump
+    assert_eq!(revision(&store), before + 1);
+    assert_eq!(
+        store.get("AS-bad").unwrap().unwrap().revision,
+        before as u64 + 1
+    );
+
+    // Changefeed event
+    let events = store.changes_since(latest, 100).unwrap().events;
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].op, ChangeOp::Update);
+    assert_eq!(events[0].attestation_id, "AS-bad");
+
+    // History entry
+    let history = store.history_of("AS-bad").unwrap();
+    let last = history.last().unwrap();
+    assert_eq!(last.operation, HistoryOperation::Update);
+    assert!(last.attestation.as_ref().unwrap().attributes.is_empty());
+}
+
 #[test]
 fn test_ids() {
     let mut store = SqliteStore::in_memory().unwrap();
@@ -249,3 +478,29 @@ fn test_timestamp_preservation() {
     let retrieved = store.get("AS-time").unwrap().unwrap();
     assert_eq!(retrieved.timestamp, timestamp);
 }
+
+#[test]
+fn test_confidence_round_trip() {
+    let mut store = SqliteStore::in_memory().unwrap();
+    let mut attestation = create_test_attestation("AS-conf");
+    attestation.confidence = Some(0.82);
+    store.put(attestation.clone()).unwrap();
+    assert_eq!(
+        store.get("AS-conf").unwrap().unwrap().confidence,
+        Some(0.82)
+    );
+
+    attestation.confidence = None;
+    store.update(attestation.clone()).unwrap();
+    assert_eq!(store.get("AS-conf").unwrap().unwrap().confidence, None);
+
+    // Out of range is rejected on both write paths
+    attestation.confidence = Some(1.2);
+    let err = store.update(attestation.clone()).unwrap_err();
+    assert_eq!(err.kind(), StorageErrorKind::InvalidInput);
+    attestation.id = "AS-conf-2".to_string();
+    attestation.confidence = Some(-0.5);
+    let err = store.put(attestation).unwrap_err();
+    assert_eq!(err.kind(), StorageErrorKind::InvalidInput);
+    assert!(!store.exists("AS-conf-2").unwrap());
+}
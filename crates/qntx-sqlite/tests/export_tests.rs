@@ -0,0 +1,390 @@
+//! Query export tests: CSV quoting, array and attribute flattening, Parquet
+
+use qntx_core::expand::{expand_cartesian, ExpandAttestation};
+use qntx_core::{storage::AttestationStore, Attestation, AttestationBuilder, AxFilter};
+use qntx_sqlite::{
+    ArrayEncoding, AttributeColumn, AttributeEncoding, AttributeType, ExportFormat, ExportOptions,
+    SqliteStore,
+};
+
+fn store_with(attestations: Vec<Attestation>) -> SqliteStore {
+    let mut store = SqliteStore::in_memory().unwrap();
+    for a in attestations {
+        store.put(a).unwrap();
+    }
+    store
+}
+
+fn tricky() -> Attestation {
+    AttestationBuilder::new()
+        .id("AS-tricky")
+        .subject("Smith, \"Bob\"\nJr")
+        .subject("ALICE")
+        .predicate("knows")
+        .context("work, home")
+        .actor("human:bob")
+        .timestamp(1704067200000)
+        .source("csv,\"import\"")
+        .attribute("note", serde_json::json!("line one\r\nline two"))
+        .attribute("score", serde_json::json!(0.75))
+        .build()
+}
+
+fn export_csv(store: &SqliteStore, options: &ExportOptions) -> (String, u64) {
+    let mut out = Vec::new();
+    let summary = store
+        .export_query(&AxFilter::default(), ExportFormat::Csv, &mut out, options)
+        .unwrap();
+    assert_eq!(summary.bytes, out.len() as u64);
+    (String::from_utf8(out).unwrap(), summary.rows)
+}
+
+/// Minimal RFC 4180 reader, enough to check the writer round-trips.
+fn parse_csv(text: &str) -> Vec<Vec<String>> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut chars = text.chars().peekable();
+    let mut quoted = false;
+    while let Some(c) = chars.next() {
+        match (quoted, c) {
+            (true, '"') if chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            (true, '"') => quoted = false,
+            (true, c) => field.push(c),
+            (false, '"') => quoted = true,
+            (false, ',') => row.push(std::mem::take(&mut field)),
+            (false, '\n') => {
+                row.push(std::mem::take(&mut field));
+                rows.push(std::mem::take(&mut row));
+            }
+            (false, c) => field.push(c),
+        }
+    }
+    rows
+}
+
+fn column<'a>(rows: &'a [Vec<String>], name: &str) -> Vec<&'a str> {
+    let i = rows[0].iter().position(|h| h == name).unwrap();
+    rows[1..].iter().map(|r| r[i].as_str()).collect()
+}
+
+#[test]
+fn test_csv_quotes_delimiters_quotes_and_newlines() {
+    let store = store_with(vec![tricky()]);
+    let (text, rows) = export_csv(&store, &ExportOptions::default());
+    assert_eq!(rows, 1);
+    assert!(text.contains(r#""csv,""import""""#));
+
+    let parsed = parse_csv(&text);
+    assert_eq!(parsed.len(), 2);
+    assert_eq!(parsed[0].len(), parsed[1].len());
+    assert_eq!(column(&parsed, "source"), vec!["csv,\"import\""]);
+    let subjects: Vec<String> = serde_json::from_str(column(&parsed, "subjects")[0]).unwrap();
+    assert_eq!(subjects, vec!["Smith, \"Bob\"\nJr", "ALICE"]);
+    assert_eq!(column(&parsed, "contexts"), vec![r#"["work, home"]"#]);
+    assert_eq!(
+        column(&parsed, "timestamp"),
+        vec!["2024-01-01T00:00:00.000Z"]
+    );
+    let attributes: serde_json::Value =
+        serde_json::from_str(column(&parsed, "attributes")[0]).unwrap();
+    assert_eq!(attributes["note"], "line one\r\nline two");
+}
+
+#[test]
+fn test_join_mode_quotes_joined_values() {
+    let store = store_with(vec![tricky()]);
+    let options = ExportOptions {
+        arrays: ArrayEncoding::Join {
+            delimiter: "|".into(),
+        },
+        ..Default::default()
+    };
+    let parsed = parse_csv(&export_csv(&store, &options).0);
+    assert_eq!(
+        column(&parsed, "subjects"),
+        vec!["Smith, \"Bob\"\nJr|ALICE"]
+    );
+    assert_eq!(column(&parsed, "contexts"), vec!["work, home"]);
+}
+
+#[test]
+fn test_explode_matches_cartesian_expansion() {
+    let attestations = vec![
+        tricky(),
+        AttestationBuilder::new()
+            .id("AS-wide")
+            .subjects(["A", "B", "C"])
+            .predicates(["p", "q"])
+            .contexts(["c1", "c2"])
+            .actors(["x", "y"])
+            .timestamp(1704067200000)
+            .build(),
+        AttestationBuilder::new()
+            .id("AS-one")
+            .subject("D")
+            .predicate("p")
+            .context("c")
+            .actor("x")
+            .timestamp(1704067200000)
+            .build(),
+    ];
+    let expected = expand_cartesian(
+        &attestations
+            .iter()
+            .map(|a| ExpandAttestation {
+                id: a.id.clone(),
+                subjects: a.subjects.clone(),
+                predicates: a.predicates.clone(),
+                contexts: a.contexts.clone(),
+                actors: a.actors.clone(),
+                timestamp_ms: a.timestamp,
+                confidence: a.confidence,
+            })
+            .collect::<Vec<_>>(),
+    )
+    .len();
+    assert_eq!(expected, 2 + 24 + 1);
+
+    let store = store_with(attestations);
+    let options = ExportOptions {
+        arrays: ArrayEncoding::Explode,
+        ..Default::default()
+    };
+    let (text, rows) = export_csv(&store, &options);
+    assert_eq!(rows as usize, expected);
+
+    let parsed = parse_csv(&text);
+    assert_eq!(parsed.len(), expected + 1);
+    assert_eq!(
+        &parsed[0][..5],
+        ["id", "subject", "predicate", "context", "actor"]
+    );
+    let tricky_subjects: Vec<&str> = parsed[1..]
+        .iter()
+        .filter(|r| r[0] == "AS-tricky")
+        .map(|r| r[1].as_str())
+        .collect();
+    assert_eq!(tricky_subjects.len(), 2);
+    assert!(tricky_subjects.contains(&"Smith, \"Bob\"\nJr"));
+}
+
+#[test]
+fn test_promoted_attribute_columns_are_typed() {
+    let mut second = tricky();
+    second.id = "AS-second".into();
+    second.attributes = [
+        ("score".to_string(), serde_json::json!("not a number")),
+        (
+            "seen".to_string(),
+            serde_json::json!("2024-02-01T12:00:00Z"),
+        ),
+    ]
+    .into_iter()
+    .collect();
+    let store = store_with(vec![tricky(), second]);
+
+    let options = ExportOptions {
+        attributes: AttributeEncoding::Columns {
+            columns: vec![
+                AttributeColumn::new("score", AttributeType::Number),
+                AttributeColumn::new("note", AttributeType::String),
+                AttributeColumn::new("seen", AttributeType::Timestamp),
+            ],
+        },
+        ..Default::default()
+    };
+    let parsed = parse_csv(&export_csv(&store, &options).0);
+    assert!(!parsed[0].contains(&"attributes".to_string()));
+
+    let ids = column(&parsed, "id");
+    let by_id = |name: &str, id: &str| {
+        column(&parsed, name)[ids.iter().position(|i| *i == id).unwrap()].to_string()
+    };
+    assert_eq!(by_id("attributes.score", "AS-tricky"), "0.75");
+    assert_eq!(by_id("attributes.score", "AS-second"), "");
+    assert_eq!(
+        by_id("attributes.note", "AS-tricky"),
+        "line one\r\nline two"
+    );
+    assert_eq!(
+        by_id("attributes.seen", "AS-second"),
+        "2024-02-01T12:00:00.000Z"
+    );
+    assert_eq!(by_id("attributes.seen", "AS-tricky"), "");
+}
+
+#[test]
+fn test_export_applies_filter() {
+    let mut other = tricky();
+    other.id = "AS-other".into();
+    other.subjects = vec!["BOB".into()];
+    other.attributes.clear();
+    let store = store_with(vec![tricky(), other]);
+
+    let mut out = Vec::new();
+    let filter = AxFilter {
+        subjects: vec!["BOB".into()],
+        ..Default::default()
+    };
+    let summary = store
+        .export_query(
+            &filter,
+            ExportFormat::Csv,
+            &mut out,
+            &ExportOptions::default(),
+        )
+        .unwrap();
+    assert_eq!(summary.rows, 1);
+    assert_eq!(
+        column(&parse_csv(&String::from_utf8(out).unwrap()), "id"),
+        vec!["AS-other"]
+    );
+}
+
+#[cfg(not(feature = "parquet"))]
+#[test]
+fn test_parquet_needs_feature() {
+    let store = store_with(vec![tricky()]);
+    let err = store
+        .export_query(
+            &AxFilter::default(),
+            ExportFormat::Parquet,
+            Vec::new(),
+            &ExportOptions::default(),
+        )
+        .unwrap_err();
+    assert!(err.to_string().contains("parquet"));
+}
+
+#[cfg(feature = "parquet")]
+#[test]
+fn test_parquet_round_trip() {
+    use arrow_array::{Array, Float64Array, StringArray, TimestampMillisecondArray};
+    use arrow_schema::{DataType, TimeUnit};
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+    let mut attestations = vec![tricky()];
+    for i in 0..25 {
+        attestations.push(
+            AttestationBuilder::new()
+                .id(format!("AS-{}", i))
+                .subject(format!("S{}", i))
+                .predicate("p")
+                .context("c")
+                .actor("x")
+                .timestamp(1704067200000 + i)
+                .attribute("score", serde_json::json!(i))
+                .build(),
+        );
+    }
+    let store = store_with(attestations);
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("out.parquet");
+    let options = ExportOptions {
+        attributes: AttributeEncoding::Columns {
+            columns: vec![
+                AttributeColumn::new("score", AttributeType::Number),
+                AttributeColumn::new("note", AttributeType::String),
+            ],
+        },
+        batch_rows: 10,
+        ..Default::default()
+    };
+    let summary = store
+        .export_query_to_file(&AxFilter::default(), ExportFormat::Parquet, &path, &options)
+        .unwrap();
+    assert_eq!(summary.rows, 26);
+    assert_eq!(summary.bytes, std::fs::metadata(&path).unwrap().len());
+
+    let reader =
+        ParquetRecordBatchReaderBuilder::try_new(std::fs::File::open(&path).unwrap()).unwrap();
+    assert_eq!(reader.metadata().num_row_groups(), 3);
+    let schema = reader.schema().clone();
+    let names: Vec<&str> = schema.fields().iter().map(|f| f.name().as_str()).collect();
+    assert_eq!(
+        names,
+        vec![
+            "id",
+            "subjects",
+            "predicates",
+            "contexts",
+            "actors",
+            "timestamp",
+            "source",
+            "confidence",
+            "revision",
+            "created_at",
+            "attributes.score",
+            "attributes.note",
+        ]
+    );
+    let utc = DataType::Timestamp(TimeUnit::Millisecond, Some("UTC".into()));
+    assert_eq!(
+        schema.field_with_name("timestamp").unwrap().data_type(),
+        &utc
+    );
+    assert_eq!(
+        schema.field_with_name("created_at").unwrap().data_type(),
+        &utc
+    );
+    assert_eq!(
+        schema
+            .field_with_name("attributes.score")
+            .unwrap()
+            .data_type(),
+        &DataType::Float64
+    );
+    assert_eq!(
+        schema.field_with_name("revision").unwrap().data_type(),
+        &DataType::UInt64
+    );
+
+    let batches: Vec<_> = reader.build().unwrap().map(|b| b.unwrap()).collect();
+    assert_eq!(batches.iter().map(|b| b.num_rows()).sum::<usize>(), 26);
+
+    let mut found = false;
+    for batch in &batches {
+        let ids = batch
+            .column(0)
+            .as_any()
+            .downcast_ref::<StringArray>()
+            .unwrap();
+        let subjects = batch
+            .column(1)
+            .as_any()
+            .downcast_ref::<StringArray>()
+            .unwrap();
+        let timestamps = batch
+            .column(5)
+            .as_any()
+            .downcast_ref::<TimestampMillisecondArray>()
+            .unwrap();
+        let scores = batch
+            .column(10)
+            .as_any()
+            .downcast_ref::<Float64Array>()
+            .unwrap();
+        let notes = batch
+            .column(11)
+            .as_any()
+            .downcast_ref::<StringArray>()
+            .unwrap();
+        for row in 0..batch.num_rows() {
+            if ids.value(row) == "AS-tricky" {
+                found = true;
+                assert_eq!(subjects.value(row), r#"["Smith, \"Bob\"\nJr","ALICE"]"#);
+                assert_eq!(timestamps.value(row), 1704067200000);
+                assert_eq!(scores.value(row), 0.75);
+                assert_eq!(notes.value(row), "line one\r\nline two");
+            } else {
+                assert!(notes.is_null(row));
+            }
+        }
+    }
+    assert!(found);
+}
@@ -0,0 +1,228 @@
+//! Attestation history and time-travel query tests
+
+use std::thread::sleep;
+use std::time::Duration;
+
+use qntx_core::{
+    storage::{AttestationStore, StorageErrorKind},
+    Attestation, AttestationBuilder, AxFilter,
+};
+use qntx_sqlite::{HistoryOperation, HistoryRetention, SqliteStore};
+
+fn attestation(id: &str, predicate: &str) -> Attestation {
+    AttestationBuilder::new()
+        .id(id)
+        .subject("ALICE")
+        .predicate(predicate)
+        .context("work")
+        .actor("human:bob")
+        .timestamp(1704067200000)
+        .source("test")
+        .build()
+}
+
+/// Wall-clock instant strictly between two writes.
+fn tick() -> i64 {
+    sleep(Duration::from_millis(5));
+    let now = chrono::Utc::now().timestamp_millis();
+    sleep(Duration::from_millis(5));
+    now
+}
+
+fn ids_as_of(store: &SqliteStore, as_of: i64) -> Vec<(String, String)> {
+    let mut result: Vec<_> = store
+        .query_as_of(&AxFilter::default(), as_of)
+        .unwrap()
+        .attestations
+        .into_iter()
+        .map(|a| (a.id, a.predicates[0].clone()))
+        .collect();
+    result.sort();
+    result
+}
+
+#[test]
+fn test_query_as_of_follows_put_update_delete() {
+    let mut store = SqliteStore::in_memory().unwrap();
+    assert_eq!(store.enable_history().unwrap(), 0);
+
+    store.put(attestation("AS-1", "knows")).unwrap();
+    store.put(attestation("AS-2", "knows")).unwrap();
+    let after_put = tick();
+    store.update(attestation("AS-1", "likes")).unwrap();
+    let after_update = tick();
+    store.delete("AS-2").unwrap();
+    let after_delete = tick();
+
+    assert_eq!(
+        ids_as_of(&store, after_put),
+        vec![
+            ("AS-1".to_string(), "knows".to_string()),
+            ("AS-2".to_string(), "knows".to_string())
+        ]
+    );
+    assert_eq!(
+        ids_as_of(&store, after_update),
+        vec![
+            ("AS-1".to_string(), "likes".to_string()),
+            ("AS-2".to_string(), "knows".to_string())
+        ]
+    );
+    assert_eq!(
+        ids_as_of(&store, after_delete),
+        vec![("AS-1".to_string(), "likes".to_string())]
+    );
+
+    // Filters apply to the reconstructed set
+    let filter = AxFilter {
+        predicates: vec!["knows".to_string()],
+        ..Default::default()
+    };
+    let ids: Vec<_> = store
+        .query_as_of(&filter, after_update)
+        .unwrap()
+        .attestations
+        .into_iter()
+        .map(|a| a.id)
+        .collect();
+    assert_eq!(ids, vec!["AS-2".to_string()]);
+}
+
+#[test]
+fn test_history_of_lists_changes_in_order() {
+    let mut store = SqliteStore::in_memory().unwrap();
+    store.enable_history().unwrap();
+
+    store.put(attestation("AS-1", "knows")).unwrap();
+    store.update(attestation("AS-1", "likes")).unwrap();
+    store.delete("AS-1").unwrap();
+
+    let entries = store.history_of("AS-1").unwrap();
+    let ops: Vec<_> = entries.iter().map(|e| e.operation).collect();
+    assert_eq!(
+        ops,
+        vec![
+            HistoryOperation::Put,
+            HistoryOperation::Update,
+            HistoryOperation::Delete
+        ]
+    );
+    assert_eq!(entries[0].attestation.as_ref().unwrap().revision, 1);
+    assert_eq!(entries[1].attestation.as_ref().unwrap().revision, 2);
+    assert_eq!(
+        entries[1].attestation.as_ref().unwrap().predicates,
+        ["likes"]
+    );
+    assert!(entries[2].attestation.is_none());
+    assert!(entries.windows(2).all(|w| w[0].seq < w[1].seq));
+
+    assert!(store.history_of("AS-missing").unwrap().is_empty());
+}
+
+#[test]
+fn test_enable_on_existing_database_records_baseline() {
+    let mut store = SqliteStore::in_memory().unwrap();
+    store.put(attestation("AS-1", "knows")).unwrap();
+    store.put(attestation("AS-2", "knows")).unwrap();
+
+    assert!(!store.history_enabled().unwrap());
+    assert_eq!(store.enable_history().unwrap(), 2);
+    assert!(store.history_enabled().unwrap());
+    // Idempotent
+    assert_eq!(store.enable_history().unwrap(), 0);
+
+    let baseline = tick();
+    store.clear().unwrap();
+    let cleared = tick();
+
+    assert_eq!(ids_as_of(&store, baseline).len(), 2);
+    assert!(ids_as_of(&store, cleared).is_empty());
+    assert_eq!(
+        store.history_of("AS-1").unwrap()[0].operation,
+        HistoryOperation::Baseline
+    );
+
+    // Before history started there is nothing to reconstruct
+    let horizon = store.history_horizon().unwrap().unwrap();
+    let err = store
+        .query_as_of(&AxFilter::default(), horizon - 1)
+        .unwrap_err();
+    assert_eq!(err.kind(), StorageErrorKind::InvalidInput);
+}
+
+#[test]
+fn test_prune_keeps_queries_after_horizon_exact() {
+    let mut store = SqliteStore::in_memory().unwrap();
+    store.enable_history().unwrap();
+
+    store.put(attestation("AS-1", "knows")).unwrap();
+    store.put(attestation("AS-2", "knows")).unwrap();
+    store.update(attestation("AS-1", "likes")).unwrap();
+    store.delete("AS-2").unwrap();
+    let before_cutoff = tick();
+    store.put(attestation("AS-3", "knows")).unwrap();
+    let after_put = tick();
+
+    let expected = ids_as_of(&store, after_put);
+    let report = store
+        .prune_history(
+            &HistoryRetention {
+                max_age_ms: Some(0),
+                max_rows: None,
+            },
+            before_cutoff,
+        )
+        .unwrap();
+
+    // AS-1's put is superseded; AS-2's put and delete go entirely
+    assert_eq!(report.removed, 3);
+    assert_eq!(report.horizon_ms, before_cutoff);
+    assert_eq!(store.history_horizon().unwrap(), Some(before_cutoff));
+    assert_eq!(ids_as_of(&store, after_put), expected);
+    assert_eq!(
+        ids_as_of(&store, before_cutoff),
+        vec![("AS-1".to_string(), "likes".to_string())]
+    );
+    assert!(store
+        .query_as_of(&AxFilter::default(), before_cutoff - 1)
+        .is_err());
+
+    // Row-count retention keeps at least the newest entries
+    let report = store
+        .prune_history(
+            &HistoryRetention {
+                max_age_ms: None,
+                max_rows: Some(1),
+            },
+            after_put,
+        )
+        .unwrap();
+    assert_eq!(report.removed, 0);
+    assert_eq!(ids_as_of(&store, after_put), expected);
+}
+
+#[test]
+fn test_namespace_without_history_is_unaffected() {
+    let mut store = SqliteStore::in_memory().unwrap();
+    store.put(attestation("AS-1", "knows")).unwrap();
+    store.delete("AS-1").unwrap();
+
+    assert!(store.history_of("AS-1").unwrap().is_empty());
+    assert!(store.history_horizon().unwrap().is_none());
+    assert!(store.query_as_of(&AxFilter::default(), 0).is_err());
+    assert!(store
+        .prune_history(&HistoryRetention::default(), 0)
+        .is_err());
+}
+
+#[test]
+fn test_disable_history_discards_entries() {
+    let mut store = SqliteStore::in_memory().unwrap();
+    store.enable_history().unwrap();
+    store.put(attestation("AS-1", "knows")).unwrap();
+
+    assert_eq!(store.disable_history().unwrap(), 1);
+    assert!(!store.history_enabled().unwrap());
+    store.update(attestation("AS-1", "likes")).unwrap();
+    assert!(store.history_of("AS-1").unwrap().is_empty());
+}
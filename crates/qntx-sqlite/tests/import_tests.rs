@@ -0,0 +1,119 @@
+//! Import tests for SqliteStore
+
+use std::path::PathBuf;
+
+use qntx_core::{
+    import::{
+        CsvImportSpec, JsonLdMapping, PropertyMapping, RowErrorKind, SlotSource, TimestampFormat,
+        TimestampSpec,
+    },
+    storage::{AttestationStore, QueryStore},
+    AxFilter,
+};
+use qntx_sqlite::SqliteStore;
+
+/// The messy fixture shared with qntx-core's golden test
+fn fixture(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("../qntx-core/tests/fixtures")
+        .join(name)
+}
+
+fn people_spec() -> CsvImportSpec {
+    CsvImportSpec {
+        context: Some(SlotSource::column("team")),
+        timestamp: Some(TimestampSpec {
+            column: "joined".to_string(),
+            format: TimestampFormat::Pattern("%d/%m/%Y".to_string()),
+        }),
+        ignore_columns: vec!["internal_id".to_string()],
+        ..CsvImportSpec::new("name", SlotSource::fixed("member_of"))
+    }
+}
+
+#[test]
+fn test_import_csv_file() {
+    let mut store = SqliteStore::in_memory().unwrap();
+
+    let summary = store
+        .import_csv_file(fixture("import_people.csv"), &people_spec(), 2)
+        .unwrap();
+    assert_eq!(summary.imported, 3);
+    assert_eq!(summary.already_present, 0);
+    assert_eq!(summary.duplicates, 1);
+    let lines: Vec<usize> = summary.errors.iter().map(|e| e.line).collect();
+    assert_eq!(lines, vec![4, 5, 10, 11]);
+    assert_eq!(summary.errors[0].kind, RowErrorKind::Timestamp);
+
+    let result = store
+        .query(&AxFilter {
+            contexts: vec!["ops, infra".to_string()],
+            ..Default::default()
+        })
+        .unwrap();
+    assert_eq!(result.attestations.len(), 1);
+    assert_eq!(result.attestations[0].subjects, vec!["BOB"]);
+    assert_eq!(
+        result.attestations[0].attributes.get("notes"),
+        Some(&serde_json::json!("Says \"hi\" a lot"))
+    );
+
+    // Content IDs make a second run a no-op
+    let again = store
+        .import_csv_file(fixture("import_people.csv"), &people_spec(), 0)
+        .unwrap();
+    assert_eq!(again.imported, 0);
+    assert_eq!(again.already_present, 3);
+    assert_eq!(store.count().unwrap(), 3);
+}
+
+#[test]
+fn test_import_csv_file_errors() {
+    let mut store = SqliteStore::in_memory().unwrap();
+
+    let missing = store.import_csv_file(fixture("no_such_file.csv"), &people_spec(), 0);
+    assert_eq!(missing.unwrap_err().code(), "io");
+
+    let spec = CsvImportSpec {
+        default_timestamp_ms: Some(0),
+        ..CsvImportSpec::new("person", SlotSource::fixed("knows"))
+    };
+    let mismatch = store.import_csv_file(fixture("import_people.csv"), &spec, 0);
+    assert_eq!(mismatch.unwrap_err().code(), "invalid_input");
+    assert_eq!(store.count().unwrap(), 0);
+}
+
+#[test]
+fn test_import_jsonld_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("people.jsonld");
+    std::fs::write(
+        &path,
+        r#"{"@graph": [
+            {"@id": "ALICE", "jobTitle": "Engineer", "worksFor": {"name": "ACME"}},
+            {"@id": "BOB", "jobTitle": "Designer"}
+        ]}"#,
+    )
+    .unwrap();
+
+    let mapping = JsonLdMapping {
+        default_timestamp_ms: Some(1_000),
+        ..JsonLdMapping::new(vec![
+            PropertyMapping::new("jobTitle", "has_role"),
+            PropertyMapping::new("worksFor", "member_of"),
+        ])
+    };
+
+    let mut store = SqliteStore::in_memory().unwrap();
+    let summary = store.import_jsonld_file(&path, &mapping, 0).unwrap();
+    assert_eq!(summary.imported, 3);
+    assert!(summary.errors.is_empty());
+
+    let roles = store
+        .query(&AxFilter {
+            predicates: vec!["has_role".to_string()],
+            ..Default::default()
+        })
+        .unwrap();
+    assert_eq!(roles.attestations.len(), 2);
+}
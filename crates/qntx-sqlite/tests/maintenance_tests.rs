@@ -0,0 +1,130 @@
+//! Maintenance tests for SqliteStore (checkpoints, vacuum, integrity)
+
+use std::io::{Seek, SeekFrom, Write};
+
+use qntx_core::{storage::AttestationStore, AttestationBuilder};
+use qntx_sqlite::{CheckpointMode, MaintenancePolicy, SqliteStore};
+use rusqlite::Connection;
+use tempfile::TempDir;
+
+fn fill(store: &mut SqliteStore, count: usize) {
+    for i in 0..count {
+        store
+            .put(
+                AttestationBuilder::new()
+                    .id(format!("AS-{}", i))
+                    .subject(format!("SUBJECT-{}", i))
+                    .predicate("knows")
+                    .context(format!("context-{}", i % 17))
+                    .attribute("note", serde_json::json!("x".repeat(200)))
+                    .build(),
+            )
+            .unwrap();
+    }
+}
+
+#[test]
+fn test_truncate_checkpoint_shrinks_wal() {
+    let dir = TempDir::new().unwrap();
+    let mut store = SqliteStore::open(dir.path().join("wal.db")).unwrap();
+    fill(&mut store, 200);
+
+    let before = store.wal_size_bytes().unwrap();
+    assert!(before > 0, "writes should land in the WAL");
+
+    let passive = store.checkpoint(CheckpointMode::Passive).unwrap();
+    assert!(!passive.busy);
+    assert_eq!(passive.wal_pages, passive.checkpointed_pages);
+    assert_eq!(store.wal_size_bytes().unwrap(), before);
+
+    let report = store.checkpoint(CheckpointMode::Truncate).unwrap();
+    assert!(!report.busy);
+    assert_eq!(report.mode, CheckpointMode::Truncate);
+    assert_eq!(store.wal_size_bytes().unwrap(), 0);
+}
+
+#[test]
+fn test_maintenance_policy_checkpoints_after_writes() {
+    let dir = TempDir::new().unwrap();
+    let mut store = SqliteStore::open(dir.path().join("policy.db")).unwrap();
+    store
+        .set_maintenance_policy(Some(MaintenancePolicy {
+            wal_limit_bytes: 64 * 1024,
+            checkpoint_mode: CheckpointMode::Truncate,
+            check_every: 10,
+        }))
+        .unwrap();
+
+    fill(&mut store, 500);
+    // The last check ran at write 500 and truncated anything over the limit
+    assert!(store.wal_size_bytes().unwrap() <= 64 * 1024);
+
+    store.set_maintenance_policy(None).unwrap();
+    assert!(store.maintenance_policy().is_none());
+}
+
+#[test]
+fn test_vacuum_analyze_and_incremental_vacuum() {
+    let mut store = SqliteStore::in_memory().unwrap();
+    fill(&mut store, 200);
+    store.clear().unwrap();
+
+    // Not enabled yet: refuse rather than silently do nothing
+    assert!(store.incremental_vacuum(0).is_err());
+
+    let report = store.vacuum().unwrap();
+    assert!(report.before.freelist_count > 0);
+    assert_eq!(report.after.freelist_count, 0);
+    assert!(report.after.page_count < report.before.page_count);
+
+    store.enable_incremental_vacuum().unwrap();
+    fill(&mut store, 200);
+    store.clear().unwrap();
+    let free = store.page_stats().unwrap().freelist_count;
+    assert!(free > 1);
+    assert_eq!(store.incremental_vacuum(1).unwrap(), 1);
+    assert_eq!(store.incremental_vacuum(0).unwrap(), free - 1);
+
+    store.analyze().unwrap();
+}
+
+#[test]
+fn test_integrity_check_flags_corrupted_copy() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("healthy.db");
+    let mut store = SqliteStore::open(&path).unwrap();
+    fill(&mut store, 300);
+    store.checkpoint(CheckpointMode::Truncate).unwrap();
+
+    let report = store.integrity_check().unwrap();
+    assert!(report.ok);
+    assert!(report.problems.is_empty());
+    assert_eq!(
+        store.open_read_conn().unwrap().integrity_check().unwrap(),
+        report
+    );
+
+    let page_size = store.page_stats().unwrap().page_size as u64;
+    let root: u64 = store
+        .connection()
+        .query_row(
+            "SELECT rootpage FROM sqlite_master WHERE type = 'index' AND tbl_name = 'attestations' LIMIT 1",
+            [],
+            |row| row.get(0),
+        )
+        .unwrap();
+    drop(store);
+
+    // Overwrite one index page of a copy with garbage
+    let copy = dir.path().join("corrupt.db");
+    std::fs::copy(&path, &copy).unwrap();
+    let mut file = std::fs::OpenOptions::new().write(true).open(&copy).unwrap();
+    file.seek(SeekFrom::Start((root - 1) * page_size)).unwrap();
+    file.write_all(&vec![0xA5; page_size as usize]).unwrap();
+    drop(file);
+
+    let corrupt = SqliteStore::new(Connection::open(&copy).unwrap());
+    let report = corrupt.integrity_check().unwrap();
+    assert!(!report.ok);
+    assert!(!report.problems.is_empty());
+}
@@ -0,0 +1,356 @@
+//! Mirror tests: incremental runs as the source gains, changes and loses
+//! attestations
+
+use qntx_core::{
+    storage::{AttestationStore, MemoryStore, StoreError},
+    Attestation, AttestationBuilder, AxFilter,
+};
+use qntx_sqlite::{mirror, MirrorChangeKind, MirrorOptions, SqliteStore};
+
+type StoreResult<T> = Result<T, StoreError>;
+
+fn attestation(id: &str, context: &str, note: &str) -> Attestation {
+    AttestationBuilder::new()
+        .id(id)
+        .subject("ALICE")
+        .predicate("knows")
+        .context(context)
+        .actor("human:alice")
+        .timestamp(1704067200000)
+        .source("test")
+        .attribute("note", serde_json::json!(note))
+        .build()
+}
+
+fn public() -> AxFilter {
+    AxFilter {
+        contexts: vec!["public".to_string()],
+        ..Default::default()
+    }
+}
+
+fn deleting() -> MirrorOptions {
+    MirrorOptions {
+        propagate_deletes: true,
+        ..Default::default()
+    }
+}
+
+/// Changes of a run sorted by ID; the order within a run follows the query
+fn changes(report: &qntx_sqlite::MirrorReport) -> Vec<(&str, MirrorChangeKind)> {
+    let mut changes: Vec<_> = report
+        .changes
+        .iter()
+        .map(|c| (c.id.as_str(), c.kind))
+        .collect();
+    changes.sort_by_key(|(id, _)| *id);
+    changes
+}
+
+#[test]
+fn test_incremental_runs_follow_the_source() {
+    let mut source = SqliteStore::in_memory().unwrap();
+    let mut dest = SqliteStore::in_memory().unwrap();
+    source.put(attestation("AS-1", "public", "a")).unwrap();
+    source.put(attestation("AS-2", "public", "b")).unwrap();
+    source
+        .put(attestation("AS-private", "private", "x"))
+        .unwrap();
+
+    let first = source.mirror_to(&mut dest, &public(), &deleting()).unwrap();
+    assert_eq!((first.copied, first.skipped, first.deleted), (2, 0, 0));
+    assert!(first.errors.is_empty());
+    assert_eq!(dest.count().unwrap(), 2);
+    assert!(!dest.exists("AS-private").unwrap());
+
+    // Nothing changed: everything is skipped
+    let second = source.mirror_to(&mut dest, &public(), &deleting()).unwrap();
+    assert_eq!((second.copied, second.updated, second.skipped), (0, 0, 2));
+    assert!(second.changes.is_empty());
+
+    // Gains one, changes one, loses one
+    source.put(attestation("AS-3", "public", "c")).unwrap();
+    source.update(attestation("AS-1", "public", "a2")).unwrap();
+    source.delete("AS-2").unwrap();
+
+    let third = source.mirror_to(&mut dest, &public(), &deleting()).unwrap();
+    assert_eq!(
+        changes(&third),
+        vec![
+            ("AS-1", MirrorChangeKind::Update),
+            ("AS-2", MirrorChangeKind::Delete),
+            ("AS-3", MirrorChangeKind::Copy),
+        ]
+    );
+    assert_eq!((third.copied, third.updated, third.deleted), (1, 1, 1));
+    assert_eq!(
+        dest.get("AS-1").unwrap().unwrap().attributes["note"],
+        serde_json::json!("a2")
+    );
+    assert!(!dest.exists("AS-2").unwrap());
+
+    // Leaving the filter counts as a deletion too
+    source.delete("AS-3").unwrap();
+    source.put(attestation("AS-3", "private", "c")).unwrap();
+    let fourth = source.mirror_to(&mut dest, &public(), &deleting()).unwrap();
+    assert_eq!(changes(&fourth), vec![("AS-3", MirrorChangeKind::Delete)]);
+    assert_eq!(dest.ids().unwrap(), vec!["AS-1".to_string()]);
+}
+
+#[test]
+fn test_deletes_only_propagate_when_asked() {
+    let mut source = SqliteStore::in_memory().unwrap();
+    let mut dest = SqliteStore::in_memory().unwrap();
+    source.put(attestation("AS-1", "public", "a")).unwrap();
+    source
+        .mirror_to(&mut dest, &public(), &MirrorOptions::default())
+        .unwrap();
+
+    source.delete("AS-1").unwrap();
+    let kept = source
+        .mirror_to(&mut dest, &public(), &MirrorOptions::default())
+        .unwrap();
+    assert_eq!(kept.deleted, 0);
+    assert!(dest.exists("AS-1").unwrap());
+
+    // The state still remembers AS-1, so a later deleting run catches up
+    let caught_up = source.mirror_to(&mut dest, &public(), &deleting()).unwrap();
+    assert_eq!(caught_up.deleted, 1);
+    assert!(!dest.exists("AS-1").unwrap());
+}
+
+#[test]
+fn test_locally_changed_copies_are_not_deleted() {
+    let mut source = SqliteStore::in_memory().unwrap();
+    let mut dest = SqliteStore::in_memory().unwrap();
+    source.put(attestation("AS-1", "public", "a")).unwrap();
+    source.mirror_to(&mut dest, &public(), &deleting()).unwrap();
+
+    dest.update(attestation("AS-1", "public", "edited downstream"))
+        .unwrap();
+    source.delete("AS-1").unwrap();
+
+    let report = source.mirror_to(&mut dest, &public(), &deleting()).unwrap();
+    assert_eq!(report.deleted, 0);
+    assert_eq!(report.errors.len(), 1);
+    assert_eq!(report.errors[0].id, "AS-1");
+    assert!(dest.exists("AS-1").unwrap());
+}
+
+#[test]
+fn test_dry_run_writes_nothing() {
+    let mut source = SqliteStore::in_memory().unwrap();
+    let mut dest = SqliteStore::in_memory().unwrap();
+    source.put(attestation("AS-1", "public", "a")).unwrap();
+    source.put(attestation("AS-2", "public", "b")).unwrap();
+    source.mirror_to(&mut dest, &public(), &deleting()).unwrap();
+
+    source.put(attestation("AS-3", "public", "c")).unwrap();
+    source.delete("AS-2").unwrap();
+    let options = MirrorOptions {
+        dry_run: true,
+        ..deleting()
+    };
+    let preview = source.mirror_to(&mut dest, &public(), &options).unwrap();
+    assert!(preview.dry_run);
+    assert_eq!(
+        changes(&preview),
+        vec![
+            ("AS-2", MirrorChangeKind::Delete),
+            ("AS-3", MirrorChangeKind::Copy),
+        ]
+    );
+    assert!(!dest.exists("AS-3").unwrap());
+    assert!(dest.exists("AS-2").unwrap());
+
+    // The preview left the state alone: the real run sees the same changes
+    let real = source.mirror_to(&mut dest, &public(), &deleting()).unwrap();
+    assert_eq!(changes(&real), changes(&preview));
+}
+
+#[test]
+fn test_interrupted_run_resumes_from_state() {
+    let mut source = SqliteStore::in_memory().unwrap();
+    for i in 0..5 {
+        source
+            .put(attestation(&format!("AS-{}", i), "public", "a"))
+            .unwrap();
+    }
+    // A destination that fails after two writes stands in for a crash
+    let mut dest = MemoryStore::new();
+    let mut flaky = FailAfter {
+        inner: &mut dest,
+        writes_left: 2,
+    };
+    let options = MirrorOptions {
+        batch_size: 2,
+        ..deleting()
+    };
+    let partial = source.mirror_to(&mut flaky, &public(), &options).unwrap();
+    assert_eq!(partial.copied, 2);
+    assert_eq!(partial.errors.len(), 3);
+
+    let resumed = source.mirror_to(&mut dest, &public(), &options).unwrap();
+    assert_eq!((resumed.copied, resumed.skipped), (3, 2));
+    assert!(resumed.errors.is_empty());
+    assert_eq!(dest.count().unwrap(), 5);
+}
+
+#[test]
+fn test_copies_are_written_once_per_batch() {
+    let mut source = SqliteStore::in_memory().unwrap();
+    for i in 0..5 {
+        source
+            .put(attestation(&format!("AS-{}", i), "public", "a"))
+            .unwrap();
+    }
+    let mut inner = MemoryStore::new();
+    let mut dest = Batches {
+        inner: &mut inner,
+        sizes: Vec::new(),
+    };
+    let options = MirrorOptions {
+        batch_size: 2,
+        ..Default::default()
+    };
+
+    let report = mirror(&source, &mut dest, &source, &public(), &options).unwrap();
+    assert_eq!(report.copied, 5);
+    assert_eq!(dest.sizes, vec![2, 2, 1]);
+    assert_eq!(inner.count().unwrap(), 5);
+
+    // A rerun has nothing to copy
+    let mut dest = Batches {
+        inner: &mut inner,
+        sizes: Vec::new(),
+    };
+    let rerun = mirror(&source, &mut dest, &source, &public(), &options).unwrap();
+    assert_eq!(rerun.skipped, 5);
+    assert!(dest.sizes.is_empty());
+}
+
+#[test]
+fn test_named_mirrors_keep_separate_state() {
+    let mut source = SqliteStore::in_memory().unwrap();
+    let mut team = SqliteStore::in_memory().unwrap();
+    let mut world = MemoryStore::new();
+    source.put(attestation("AS-1", "public", "a")).unwrap();
+
+    let named = |name: &str| MirrorOptions {
+        name: name.to_string(),
+        run_id: Some(format!("run-{}", name)),
+        ..deleting()
+    };
+    let to_team = source
+        .mirror_to(&mut team, &public(), &named("team"))
+        .unwrap();
+    assert_eq!(to_team.run_id, "run-team");
+    mirror(&source, &mut world, &source, &public(), &named("world")).unwrap();
+
+    source.delete("AS-1").unwrap();
+    assert_eq!(source.forget_mirror("world").unwrap(), 1);
+    let world_run = source
+        .mirror_to(&mut world, &public(), &named("world"))
+        .unwrap();
+    assert_eq!(world_run.deleted, 0);
+    let team_run = source
+        .mirror_to(&mut team, &public(), &named("team"))
+        .unwrap();
+    assert_eq!(team_run.deleted, 1);
+}
+
+#[test]
+fn test_propagate_deletes_rejects_limited_filter() {
+    let source = SqliteStore::in_memory().unwrap();
+    let mut dest = MemoryStore::new();
+    let filter = AxFilter {
+        limit: Some(10),
+        ..public()
+    };
+    assert!(source.mirror_to(&mut dest, &filter, &deleting()).is_err());
+}
+
+/// Passes writes through until `writes_left` runs out, then fails them.
+struct FailAfter<'a> {
+    inner: &'a mut MemoryStore,
+    writes_left: usize,
+}
+
+impl FailAfter<'_> {
+    fn spend(&mut self) -> StoreResult<()> {
+        if self.writes_left == 0 {
+            return Err(StoreError::Backend("connection lost".to_string()));
+        }
+        self.writes_left -= 1;
+        Ok(())
+    }
+}
+
+impl AttestationStore for FailAfter<'_> {
+    fn put(&mut self, attestation: Attestation) -> StoreResult<()> {
+        self.spend()?;
+        self.inner.put(attestation)
+    }
+
+    fn get(&self, id: &str) -> StoreResult<Option<Attestation>> {
+        self.inner.get(id)
+    }
+
+    fn delete(&mut self, id: &str) -> StoreResult<bool> {
+        self.spend()?;
+        self.inner.delete(id)
+    }
+
+    fn update(&mut self, attestation: Attestation) -> StoreResult<()> {
+        self.spend()?;
+        self.inner.update(attestation)
+    }
+
+    fn ids(&self) -> StoreResult<Vec<String>> {
+        self.inner.ids()
+    }
+
+    fn clear(&mut self) -> StoreResult<()> {
+        self.inner.clear()
+    }
+}
+
+/// Records the size of every `put_many`; single puts are refused.
+struct Batches<'a> {
+    inner: &'a mut MemoryStore,
+    sizes: Vec<usize>,
+}
+
+impl AttestationStore for Batches<'_> {
+    fn put(&mut self, _attestation: Attestation) -> StoreResult<()> {
+        Err(StoreError::Backend("expected a batched write".to_string()))
+    }
+
+    fn put_many(&mut self, attestations: Vec<Attestation>) -> Vec<StoreResult<()>> {
+        self.sizes.push(attestations.len());
+        attestations
+            .into_iter()
+            .map(|a| self.inner.put(a))
+            .collect()
+    }
+
+    fn get(&self, id: &str) -> StoreResult<Option<Attestation>> {
+        self.inner.get(id)
+    }
+
+    fn delete(&mut self, id: &str) -> StoreResult<bool> {
+        self.inner.delete(id)
+    }
+
+    fn update(&mut self, attestation: Attestation) -> StoreResult<()> {
+        self.inner.update(attestation)
+    }
+
+    fn ids(&self) -> StoreResult<Vec<String>> {
+        self.inner.ids()
+    }
+
+    fn clear(&mut self) -> StoreResult<()> {
+        self.inner.clear()
+    }
+}
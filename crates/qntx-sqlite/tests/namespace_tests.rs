@@ -0,0 +1,206 @@
+//! Namespace isolation tests for SqliteStore
+//!
+//! Two scoped handles on the same database file must never see each other's
+//! attestations, vocabulary or stats.
+
+use qntx_core::{
+    storage::{AttestationStore, QueryStore, StorageErrorKind},
+    AttestationBuilder, AxFilter,
+};
+use qntx_sqlite::{
+    drop_namespace_token, BoundedStore, SqliteError, SqliteStore, StorageQuotas, DEFAULT_NAMESPACE,
+};
+use tempfile::TempDir;
+
+fn attestation(id: &str, subject: &str, predicate: &str, context: &str) -> qntx_core::Attestation {
+    AttestationBuilder::new()
+        .id(id)
+        .subject(subject)
+        .predicate(predicate)
+        .context(context)
+        .actor("human:test")
+        .timestamp(1704067200000)
+        .source("test")
+        .build()
+}
+
+/// Open two handles on one file, scoped to `alpha` and `beta`.
+fn two_workspaces() -> (TempDir, SqliteStore, SqliteStore) {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("workspaces.db");
+    let alpha = SqliteStore::open_scoped(&path, "alpha").unwrap();
+    let beta = alpha.scoped("beta").unwrap();
+    (dir, alpha, beta)
+}
+
+#[test]
+fn test_same_id_in_two_namespaces() {
+    let (_dir, mut alpha, mut beta) = two_workspaces();
+
+    alpha
+        .put(attestation("AS-1", "ALICE", "knows", "work"))
+        .unwrap();
+    beta.put(attestation("AS-1", "BOB", "likes", "home"))
+        .unwrap();
+
+    assert_eq!(alpha.get("AS-1").unwrap().unwrap().subjects, vec!["ALICE"]);
+    assert_eq!(beta.get("AS-1").unwrap().unwrap().subjects, vec!["BOB"]);
+
+    // Still unique within a namespace
+    let dup = alpha.put(attestation("AS-1", "CAROL", "knows", "work"));
+    assert_eq!(dup.unwrap_err().kind(), StorageErrorKind::Duplicate);
+}
+
+#[test]
+fn test_crud_does_not_cross_namespaces() {
+    let (_dir, mut alpha, mut beta) = two_workspaces();
+
+    alpha
+        .put(attestation("AS-1", "ALICE", "knows", "work"))
+        .unwrap();
+
+    assert!(beta.get("AS-1").unwrap().is_none());
+    assert!(!beta.exists("AS-1").unwrap());
+    assert!(!beta.delete("AS-1").unwrap());
+    assert_eq!(
+        beta.update(attestation("AS-1", "EVE", "knows", "work"))
+            .unwrap_err()
+            .kind(),
+        StorageErrorKind::NotFound
+    );
+    assert!(beta.ids().unwrap().is_empty());
+
+    beta.clear().unwrap();
+    assert_eq!(alpha.get("AS-1").unwrap().unwrap().subjects, vec!["ALICE"]);
+}
+
+#[test]
+fn test_queries_are_scoped() {
+    let (_dir, mut alpha, mut beta) = two_workspaces();
+
+    alpha
+        .put(attestation("AS-1", "ALICE", "knows", "work"))
+        .unwrap();
+    beta.put(attestation("AS-2", "ALICE", "knows", "work"))
+        .unwrap();
+
+    for filter in [
+        AxFilter::default(),
+        AxFilter {
+            subjects: vec!["ALICE".to_string()],
+            predicates: vec!["knows".to_string()],
+            ..Default::default()
+        },
+    ] {
+        let result = alpha.query(&filter).unwrap();
+        let ids: Vec<&str> = result.attestations.iter().map(|a| a.id.as_str()).collect();
+        assert_eq!(ids, vec!["AS-1"]);
+    }
+}
+
+#[test]
+fn test_vocabulary_and_stats_are_scoped() {
+    let (_dir, mut alpha, mut beta) = two_workspaces();
+
+    alpha
+        .put(attestation("AS-1", "ALICE", "knows", "work"))
+        .unwrap();
+    alpha
+        .put(attestation("AS-2", "BOB", "knows", "work"))
+        .unwrap();
+    beta.put(attestation("AS-3", "CAROL", "likes", "home"))
+        .unwrap();
+
+    assert_eq!(alpha.predicates().unwrap(), vec!["knows"]);
+    assert_eq!(alpha.contexts().unwrap(), vec!["work"]);
+    assert_eq!(alpha.subjects().unwrap(), vec!["ALICE", "BOB"]);
+    assert_eq!(beta.predicates().unwrap(), vec!["likes"]);
+    assert_eq!(beta.contexts().unwrap(), vec!["home"]);
+    assert_eq!(beta.subjects().unwrap(), vec!["CAROL"]);
+
+    let alpha_stats = alpha.stats().unwrap();
+    assert_eq!(alpha_stats.total_attestations, 2);
+    assert_eq!(alpha_stats.unique_subjects, 2);
+    assert_eq!(alpha_stats.unique_predicates, 1);
+
+    let beta_stats = beta.stats().unwrap();
+    assert_eq!(beta_stats.total_attestations, 1);
+    assert_eq!(beta_stats.unique_subjects, 1);
+    assert_eq!(beta_stats.unique_actors, 1);
+}
+
+#[test]
+fn test_admin_operations() {
+    let (dir, mut alpha, mut beta) = two_workspaces();
+    let mut default = SqliteStore::open(dir.path().join("workspaces.db")).unwrap();
+    assert_eq!(default.namespace(), DEFAULT_NAMESPACE);
+
+    alpha
+        .put(attestation("AS-1", "ALICE", "knows", "work"))
+        .unwrap();
+    beta.put(attestation("AS-1", "ALICE", "knows", "work"))
+        .unwrap();
+    beta.put(attestation("AS-2", "ALICE", "knows", "work"))
+        .unwrap();
+    default
+        .put(attestation("AS-1", "ALICE", "knows", "work"))
+        .unwrap();
+
+    assert_eq!(
+        alpha.list_namespaces().unwrap(),
+        vec!["alpha", "beta", "default"]
+    );
+    let counts = alpha.count_by_namespace().unwrap();
+    assert_eq!(counts["alpha"], 1);
+    assert_eq!(counts["beta"], 2);
+    assert_eq!(counts["default"], 1);
+
+    // Wrong token leaves the namespace alone
+    let refused = default.drop_namespace("beta", &drop_namespace_token("alpha"));
+    assert_eq!(refused.unwrap_err().kind(), StorageErrorKind::InvalidInput);
+    assert_eq!(beta.count().unwrap(), 2);
+
+    let dropped = default
+        .drop_namespace("beta", &drop_namespace_token("beta"))
+        .unwrap();
+    assert_eq!(dropped, 2);
+    assert!(beta.ids().unwrap().is_empty());
+    assert!(beta.predicates().unwrap().is_empty());
+    assert_eq!(alpha.count().unwrap(), 1);
+    assert_eq!(default.count().unwrap(), 1);
+}
+
+#[test]
+fn test_bounded_quotas_are_per_namespace() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = SqliteStore::open_scoped(dir.path().join("bounded.db"), "alpha").unwrap();
+    let mut alpha = BoundedStore::with_quotas(store, StorageQuotas::new(1, 10, 10));
+    let mut beta = alpha.scoped("beta").unwrap();
+
+    alpha
+        .put(attestation("AS-1", "ALICE", "knows", "work"))
+        .unwrap();
+    // alpha is full, beta's quota is untouched
+    assert_eq!(
+        alpha
+            .put(attestation("AS-2", "ALICE", "knows", "work"))
+            .unwrap_err()
+            .kind(),
+        StorageErrorKind::QuotaExceeded
+    );
+    beta.put(attestation("AS-2", "ALICE", "knows", "work"))
+        .unwrap();
+}
+
+#[test]
+fn test_invalid_namespaces() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("invalid.db");
+    assert!(SqliteStore::open_scoped(&path, "").is_err());
+    assert!(SqliteStore::open_scoped(&path, &"x".repeat(129)).is_err());
+    // In-memory stores have no file to share
+    assert!(matches!(
+        SqliteStore::in_memory().unwrap().scoped("alpha"),
+        Err(SqliteError::RequiresFile(_))
+    ));
+}
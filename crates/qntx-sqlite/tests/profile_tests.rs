@@ -0,0 +1,168 @@
+//! Store profile tests: pragmas, statement caching and clean reopen
+
+use qntx_core::{
+    storage::{AttestationStore, QueryStore},
+    Attestation, AttestationBuilder, AxFilter,
+};
+use qntx_sqlite::{OpenOptions, SqliteStore, StoreProfile, DEFAULT_STATEMENT_CACHE};
+use tempfile::TempDir;
+
+fn attestation(i: usize) -> Attestation {
+    AttestationBuilder::new()
+        .id(format!("AS-{}", i))
+        .subject(format!("SUBJECT-{}", i % 10))
+        .subject("ALL")
+        .predicate(["knows", "likes"][i % 2])
+        .context(format!("context-{}", i % 7))
+        .actor("human:bob")
+        .timestamp(1704067200000 + i as i64)
+        .source("test")
+        .attribute("note", serde_json::json!(format!("say \"hi\" #{}", i)))
+        .build()
+}
+
+fn pragma(store: &SqliteStore, name: &str) -> i64 {
+    store
+        .connection()
+        .pragma_query_value(None, name, |row| row.get(0))
+        .unwrap()
+}
+
+fn predicates(store: &SqliteStore, predicate: &str) -> Vec<String> {
+    let filter = AxFilter {
+        predicates: vec![predicate.to_string()],
+        ..Default::default()
+    };
+    let mut ids: Vec<String> = store
+        .query(&filter)
+        .unwrap()
+        .attestations
+        .into_iter()
+        .map(|a| a.id)
+        .collect();
+    ids.sort();
+    ids
+}
+
+#[test]
+fn test_profiles_set_pragmas() {
+    let dir = TempDir::new().unwrap();
+
+    // Plain open keeps SQLite's FULL durability
+    let durable = SqliteStore::open(dir.path().join("durable.db")).unwrap();
+    assert_eq!(pragma(&durable, "synchronous"), 2);
+    assert_eq!(pragma(&durable, "mmap_size"), 0);
+
+    let balanced = SqliteStore::open_with(
+        dir.path().join("balanced.db"),
+        &OpenOptions::profile(StoreProfile::Balanced),
+    )
+    .unwrap();
+    assert_eq!(pragma(&balanced, "synchronous"), 1);
+    assert_eq!(pragma(&balanced, "cache_size"), -16 * 1024);
+    assert_eq!(pragma(&balanced, "mmap_size"), 0);
+
+    let fast = SqliteStore::open_with(
+        dir.path().join("fast.db"),
+        &OpenOptions::profile(StoreProfile::Fast),
+    )
+    .unwrap();
+    assert_eq!(pragma(&fast, "synchronous"), 0);
+    assert_eq!(pragma(&fast, "temp_store"), 2);
+    // Not even the fast profile maps the file
+    assert_eq!(pragma(&fast, "mmap_size"), 0);
+
+    // Scoped handles keep the profile
+    let scoped = fast.scoped("beta").unwrap();
+    assert_eq!(pragma(&scoped, "synchronous"), 0);
+
+    // mmap only when asked for; explicit sizes override the profile
+    let tuned = SqliteStore::open_with(
+        dir.path().join("tuned.db"),
+        &OpenOptions {
+            profile: StoreProfile::Fast,
+            mmap_size: Some(64 * 1024 * 1024),
+            cache_size_kib: Some(1024),
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    assert_eq!(pragma(&tuned, "mmap_size"), 64 * 1024 * 1024);
+    assert_eq!(pragma(&tuned, "cache_size"), -1024);
+}
+
+#[test]
+fn test_options_json() {
+    let options: OpenOptions = serde_json::from_str(r#"{"profile":"fast"}"#).unwrap();
+    assert_eq!(options.profile, StoreProfile::Fast);
+    assert_eq!(options.statement_cache, DEFAULT_STATEMENT_CACHE);
+    assert_eq!(OpenOptions::default().profile, StoreProfile::Durable);
+    assert_eq!(
+        serde_json::from_str::<OpenOptions>("{}").unwrap(),
+        OpenOptions::default()
+    );
+    assert!(serde_json::from_str::<OpenOptions>(r#"{"profile":"reckless"}"#).is_err());
+    assert_eq!(StoreProfile::parse("FAST"), Some(StoreProfile::Fast));
+}
+
+#[test]
+fn test_fast_profile_survives_clean_reopen() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("fast.db");
+    let options = OpenOptions::profile(StoreProfile::Fast);
+    {
+        let mut store = SqliteStore::open_with(&path, &options).unwrap();
+        for i in 0..300 {
+            store.put(attestation(i)).unwrap();
+        }
+        store.delete("AS-7").unwrap();
+        store.update(attestation(8)).unwrap();
+    }
+
+    let store = SqliteStore::open_with(&path, &options).unwrap();
+    assert!(store.integrity_check().unwrap().ok);
+    assert_eq!(store.ids().unwrap().len(), 299);
+    assert!(!store.exists("AS-7").unwrap());
+    assert_eq!(store.get("AS-8").unwrap().unwrap().revision, 2);
+    assert_eq!(predicates(&store, "knows").len(), 150);
+
+    // Readable under another profile too
+    drop(store);
+    let durable =
+        SqliteStore::open_with(&path, &OpenOptions::profile(StoreProfile::Durable)).unwrap();
+    assert_eq!(
+        durable.get("AS-42").unwrap().unwrap().attributes,
+        attestation(42).attributes
+    );
+}
+
+#[test]
+fn test_small_statement_cache_keeps_results_correct() {
+    let dir = TempDir::new().unwrap();
+    for statement_cache in [0, 1, DEFAULT_STATEMENT_CACHE] {
+        let path = dir.path().join(format!("cache-{}.db", statement_cache));
+        let mut store = SqliteStore::open_with(
+            &path,
+            &OpenOptions {
+                statement_cache,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        for i in 0..40 {
+            store.put(attestation(i)).unwrap();
+        }
+
+        // Alternate statements so a one-entry cache evicts on every call
+        for _ in 0..3 {
+            assert_eq!(predicates(&store, "knows").len(), 20);
+            assert!(store.exists("AS-3").unwrap());
+            let stored = store.get("AS-3").unwrap().unwrap();
+            assert_eq!(stored.subjects, attestation(3).subjects);
+            assert_eq!(stored.attributes, attestation(3).attributes);
+            assert_eq!(predicates(&store, "likes").len(), 20);
+            assert!(!store.exists("AS-99").unwrap());
+            assert_eq!(store.ids().unwrap().len(), 40);
+        }
+    }
+}
@@ -1,9 +1,10 @@
 //! Query tests for SqliteStore
 
 use qntx_core::{
-    storage::{AttestationStore, QueryStore},
-    AttestationBuilder, AxFilter,
+    storage::{AttestationStore, MemoryStore, QueryStore},
+    AttestationBuilder, AttrCondition, AttrOp, AttrValue, AxFilter, OverFilter,
 };
+use qntx_sqlite::store::needs_post_filter;
 use qntx_sqlite::SqliteStore;
 
 /// Helper to create a test attestation
@@ -701,3 +702,461 @@ fn test_query_with_multiple_values_in_filter() {
         .iter()
         .any(|a| a.subjects.contains(&"BOB".to_string())));
 }
+
+#[test]
+fn test_query_over() {
+    const YEAR_MS: i64 = 365 * 86_400_000;
+    let mut store = SqliteStore::in_memory().unwrap();
+
+    for (id, subject, ts) in [
+        ("AS-1", "ALICE", 0),
+        ("AS-2", "ALICE", 6 * YEAR_MS),
+        ("AS-3", "BOB", 0),
+        ("AS-4", "BOB", 4 * YEAR_MS),
+        ("AS-5", "CAROL", YEAR_MS),
+        ("AS-6", "CAROL", 7 * YEAR_MS),
+    ] {
+        store
+            .put(create_attestation(
+                id,
+                subject,
+                "member",
+                "guild",
+                "human:bob",
+                ts,
+            ))
+            .unwrap();
+    }
+
+    let filter = AxFilter {
+        over: Some(OverFilter::new(5 * YEAR_MS)),
+        ..Default::default()
+    };
+    let mut ids: Vec<String> = store
+        .query(&filter)
+        .unwrap()
+        .attestations
+        .into_iter()
+        .map(|a| a.id)
+        .collect();
+    ids.sort();
+    assert_eq!(ids, vec!["AS-1", "AS-2", "AS-5", "AS-6"]);
+
+    // The span is measured within the time range, so cutting off the early
+    // attestations leaves nothing spanning five years.
+    let filter = AxFilter {
+        time_start: Some(YEAR_MS / 2),
+        over: Some(OverFilter::new(5 * YEAR_MS)),
+        ..Default::default()
+    };
+    let ids: Vec<String> = store
+        .query(&filter)
+        .unwrap()
+        .attestations
+        .into_iter()
+        .map(|a| a.id)
+        .collect();
+    assert_eq!(ids.len(), 2);
+    assert!(ids.contains(&"AS-5".to_string()) && ids.contains(&"AS-6".to_string()));
+
+    // Over combines with slot filters and limit
+    let filter = AxFilter {
+        subjects: vec!["ALICE".to_string()],
+        over: Some(OverFilter::new(5 * YEAR_MS)),
+        limit: Some(1),
+        ..Default::default()
+    };
+    assert_eq!(store.query(&filter).unwrap().attestations.len(), 1);
+}
+
+#[test]
+fn test_query_over_span_exactly_at_bound() {
+    // An hour apart in 2026: in julianday floats this span comes out a
+    // fraction of a millisecond short of 3_600_000.
+    const HOUR_MS: i64 = 3_600_000;
+    const START: i64 = 1_784_982_559_000;
+    let mut store = SqliteStore::in_memory().unwrap();
+    let mut memory = MemoryStore::new();
+    for (id, ts) in [("AS-1", START), ("AS-2", START + HOUR_MS)] {
+        let a = create_attestation(id, "ALICE", "member", "guild", "human:bob", ts);
+        store.put(a.clone()).unwrap();
+        memory.put(a).unwrap();
+    }
+
+    let filter = AxFilter {
+        over: Some(OverFilter::new(HOUR_MS)),
+        ..Default::default()
+    };
+    assert_eq!(
+        sorted_ids(store.query(&filter).unwrap()),
+        vec!["AS-1", "AS-2"]
+    );
+    assert_eq!(
+        sorted_ids(store.query(&filter).unwrap()),
+        sorted_ids(memory.query(&filter).unwrap())
+    );
+}
+
+#[test]
+fn test_query_matching_summary() {
+    let mut store = SqliteStore::in_memory().unwrap();
+
+    for (id, subject, predicate, context, actor, ts) in [
+        ("AS-1", "ALICE", "knows", "work", "human:bob", 3000),
+        ("AS-2", "BOB", "knows", "work", "human:alice", 1000),
+        ("AS-3", "CAROL", "knows", "social", "human:bob", 2000),
+        ("AS-4", "ALICE", "works_at", "ACME", "human:bob", 4000),
+    ] {
+        store
+            .put(create_attestation(
+                id, subject, predicate, context, actor, ts,
+            ))
+            .unwrap();
+    }
+
+    // Off by default
+    let filter = AxFilter {
+        limit: Some(1),
+        ..Default::default()
+    };
+    assert!(store.query(&filter).unwrap().summary.matching.is_none());
+
+    // Aggregates cover the whole matching set, not just the returned page
+    let filter = AxFilter {
+        predicates: vec!["knows".to_string()],
+        limit: Some(1),
+        include_summary: true,
+        ..Default::default()
+    };
+    let summary = store.query(&filter).unwrap().summary;
+    assert_eq!(summary.total_attestations, 1);
+
+    let matching = summary.matching.unwrap();
+    assert_eq!(matching.total, 3);
+    assert_eq!(matching.distinct_subjects, 3);
+    assert_eq!(matching.distinct_predicates, 1);
+    assert_eq!(matching.distinct_contexts, 2);
+    assert_eq!(matching.distinct_actors, 2);
+    assert_eq!(matching.top_contexts[0].value, "work");
+    assert_eq!(matching.top_contexts[0].count, 2);
+    assert_eq!(matching.top_contexts[1].value, "social");
+    assert_eq!(matching.earliest, Some(1000));
+    assert_eq!(matching.latest, Some(3000));
+
+    // Nothing matched
+    let filter = AxFilter {
+        subjects: vec!["NOBODY".to_string()],
+        include_summary: true,
+        ..Default::default()
+    };
+    let matching = store.query(&filter).unwrap().summary.matching.unwrap();
+    assert_eq!(matching.total, 0);
+    assert!(matching.top_predicates.is_empty());
+    assert_eq!(matching.earliest, None);
+}
+
+#[test]
+fn test_query_matching_summary_over() {
+    const YEAR_MS: i64 = 365 * 86_400_000;
+    let mut store = SqliteStore::in_memory().unwrap();
+
+    for (id, subject, ts) in [
+        ("AS-1", "ALICE", 0),
+        ("AS-2", "ALICE", 6 * YEAR_MS),
+        ("AS-3", "BOB", 0),
+        ("AS-4", "BOB", 4 * YEAR_MS),
+    ] {
+        store
+            .put(create_attestation(
+                id,
+                subject,
+                "member",
+                "guild",
+                "human:bob",
+                ts,
+            ))
+            .unwrap();
+    }
+
+    let filter = AxFilter {
+        over: Some(OverFilter::new(5 * YEAR_MS)),
+        limit: Some(1),
+        include_summary: true,
+        ..Default::default()
+    };
+    let matching = store.query(&filter).unwrap().summary.matching.unwrap();
+    assert_eq!(matching.total, 2);
+    assert_eq!(matching.distinct_subjects, 1);
+    assert_eq!(matching.earliest, Some(0));
+    assert_eq!(matching.latest, Some(6 * YEAR_MS));
+}
+
+#[test]
+fn test_query_min_confidence() {
+    let mut store = SqliteStore::in_memory().unwrap();
+    let scored = |id: &str, confidence: f32| {
+        AttestationBuilder::new()
+            .id(id)
+            .subject("ALICE")
+            .predicate("spotted_at")
+            .context("cam-7")
+            .actor("detector")
+            .timestamp(1000)
+            .confidence(confidence)
+            .build()
+    };
+    store.put(scored("AS-1", 0.95)).unwrap();
+    store.put(scored("AS-2", 0.4)).unwrap();
+    store.put(scored("AS-3", 0.7)).unwrap();
+    store
+        .put(create_attestation(
+            "AS-4",
+            "ALICE",
+            "spotted_at",
+            "cam-7",
+            "human:bob",
+            1000,
+        ))
+        .unwrap();
+
+    let filter = AxFilter {
+        predicates: vec!["spotted_at".to_string()],
+        min_confidence: Some(0.7),
+        include_summary: true,
+        ..Default::default()
+    };
+    let result = store.query(&filter).unwrap();
+    let mut ids: Vec<String> = result.attestations.into_iter().map(|a| a.id).collect();
+    ids.sort();
+    // A bound equal to a stored value matches it; unscored rows never do
+    assert_eq!(ids, vec!["AS-1", "AS-3"]);
+
+    let mut expected = vec![0; 10];
+    expected[7] = 1;
+    expected[9] = 1;
+    assert_eq!(
+        result.summary.matching.unwrap().confidence_histogram,
+        expected
+    );
+
+    // Without the bound, the histogram covers only rows that carry a confidence
+    let filter = AxFilter {
+        include_summary: true,
+        ..Default::default()
+    };
+    let matching = store.query(&filter).unwrap().summary.matching.unwrap();
+    assert_eq!(matching.total, 4);
+    assert_eq!(matching.confidence_histogram.iter().sum::<usize>(), 3);
+    assert_eq!(matching.confidence_histogram[4], 1);
+}
+
+/// Key that can't go into a JSON path label, forcing the Rust post-filter
+const UNPUSHABLE: &str = "level\"";
+
+const ATTRIBUTE_STEP_MS: i64 = 365 * 86_400_000;
+
+/// Attestations whose attributes carry the same value under `level` (pushed
+/// down to SQL) and `level"` (post-filtered), covering every JSON type, plus
+/// one attestation without attributes.
+fn attribute_dataset() -> Vec<qntx_core::Attestation> {
+    let values = [
+        serde_json::json!(3),
+        serde_json::json!(7.5),
+        serde_json::json!(-2),
+        serde_json::json!("3"),
+        serde_json::json!("open"),
+        serde_json::json!("reopened"),
+        serde_json::json!(true),
+        serde_json::json!(false),
+        serde_json::json!(null),
+        serde_json::json!(["urgent", 3, true]),
+        serde_json::json!({"nested": "open"}),
+    ];
+    let mut attestations: Vec<_> = values
+        .iter()
+        .enumerate()
+        .map(|(i, value)| {
+            let mut a = create_attestation(
+                &format!("AS-{}", i),
+                "ALICE",
+                "flagged",
+                "tracker",
+                "human:bob",
+                1000 + i as i64 * ATTRIBUTE_STEP_MS,
+            );
+            a.attributes.insert("level".to_string(), value.clone());
+            a.attributes.insert(UNPUSHABLE.to_string(), value.clone());
+            a
+        })
+        .collect();
+    attestations.push(create_attestation(
+        "AS-bare",
+        "ALICE",
+        "flagged",
+        "tracker",
+        "human:bob",
+        500,
+    ));
+    attestations
+}
+
+fn attribute_conditions(key: &str) -> Vec<AttrCondition> {
+    use AttrValue::{Bool, Number, String as Text};
+    let ops = [
+        AttrOp::Eq,
+        AttrOp::Ne,
+        AttrOp::Gt,
+        AttrOp::Gte,
+        AttrOp::Lt,
+        AttrOp::Lte,
+        AttrOp::Contains,
+    ];
+    let values = [
+        Number(3.0),
+        Number(-2.0),
+        Number(7.5),
+        Text("3".into()),
+        Text("open".into()),
+        Text("pen".into()),
+        Text("urgent".into()),
+        Bool(true),
+        Bool(false),
+    ];
+    let mut conditions: Vec<_> = ops
+        .iter()
+        .flat_map(|op| {
+            values
+                .iter()
+                .map(move |v| AttrCondition::new(key, *op, v.clone()))
+        })
+        .collect();
+    conditions.push(AttrCondition::new(key, AttrOp::Exists, Bool(true)));
+    conditions.push(AttrCondition::new(key, AttrOp::Exists, Bool(false)));
+    conditions
+}
+
+fn sorted_ids(result: qntx_core::AxResult) -> Vec<String> {
+    let mut ids: Vec<String> = result.attestations.into_iter().map(|a| a.id).collect();
+    ids.sort();
+    ids
+}
+
+#[test]
+fn test_query_attribute_conditions_match_memory_store() {
+    let mut store = SqliteStore::in_memory().unwrap();
+    let mut memory = MemoryStore::new();
+    for a in attribute_dataset() {
+        store.put(a.clone()).unwrap();
+        memory.put(a).unwrap();
+    }
+
+    for key in ["level", UNPUSHABLE] {
+        for condition in attribute_conditions(key) {
+            let filter = AxFilter {
+                attribute_conditions: vec![condition.clone()],
+                ..Default::default()
+            };
+            assert_eq!(
+                needs_post_filter(&filter),
+                key == UNPUSHABLE,
+                "{:?}",
+                condition
+            );
+            assert_eq!(
+                sorted_ids(store.query(&filter).unwrap()),
+                sorted_ids(memory.query(&filter).unwrap()),
+                "{:?}",
+                condition
+            );
+        }
+    }
+}
+
+#[test]
+fn test_query_attribute_conditions_semantics() {
+    let mut store = SqliteStore::in_memory().unwrap();
+    for a in attribute_dataset() {
+        store.put(a).unwrap();
+    }
+    let ids = |op, value| {
+        let filter = AxFilter {
+            attribute_conditions: vec![AttrCondition::new("level", op, value)],
+            ..Default::default()
+        };
+        sorted_ids(store.query(&filter).unwrap())
+    };
+
+    // The typed value decides: number 3 doesn't match the string "3"
+    assert_eq!(ids(AttrOp::Eq, AttrValue::Number(3.0)), vec!["AS-0"]);
+    assert_eq!(ids(AttrOp::Eq, AttrValue::String("3".into())), vec!["AS-3"]);
+    assert_eq!(
+        ids(AttrOp::Gte, AttrValue::Number(3.0)),
+        vec!["AS-0", "AS-1"]
+    );
+    assert_eq!(
+        ids(AttrOp::Contains, AttrValue::String("open".into())),
+        vec!["AS-4", "AS-5"]
+    );
+    assert_eq!(ids(AttrOp::Contains, AttrValue::Number(3.0)), vec!["AS-9"]);
+    assert_eq!(ids(AttrOp::Lt, AttrValue::Bool(true)), vec!["AS-7"]);
+    // Missing keys only match exists=false; JSON null counts as present
+    assert_eq!(ids(AttrOp::Exists, AttrValue::Bool(false)), vec!["AS-bare"]);
+    assert!(!ids(AttrOp::Ne, AttrValue::Number(3.0)).contains(&"AS-bare".to_string()));
+    assert!(ids(AttrOp::Ne, AttrValue::Number(3.0)).contains(&"AS-8".to_string()));
+}
+
+#[test]
+fn test_query_attribute_post_filter_applies_limit_over_and_summary() {
+    let mut store = SqliteStore::in_memory().unwrap();
+    let mut memory = MemoryStore::new();
+    for a in attribute_dataset() {
+        store.put(a.clone()).unwrap();
+        memory.put(a).unwrap();
+    }
+
+    let mut results = Vec::new();
+    for key in ["level", UNPUSHABLE] {
+        let filter = AxFilter {
+            subjects: vec!["ALICE".to_string()],
+            attribute_conditions: vec![AttrCondition::new(
+                key,
+                AttrOp::Exists,
+                AttrValue::Bool(true),
+            )],
+            over: Some(OverFilter::new(5 * ATTRIBUTE_STEP_MS)),
+            limit: Some(3),
+            include_summary: true,
+            ..Default::default()
+        };
+        let result = store.query(&filter).unwrap();
+        let matching = result.summary.matching.clone().unwrap();
+        assert_eq!(matching.total, 11, "{}", key);
+        assert_eq!(
+            matching,
+            memory.query(&filter).unwrap().summary.matching.unwrap()
+        );
+        let ids: Vec<String> = result.attestations.into_iter().map(|a| a.id).collect();
+        assert_eq!(ids.len(), 3, "{}", key);
+        results.push(ids);
+    }
+    // Limit applies after the post-filter, in the same order as in SQL
+    assert_eq!(results[0], results[1]);
+}
+
+#[test]
+fn test_query_rejects_unexpanded_actor_aliases() {
+    let attestation = create_attestation("AS-1", "ALICE", "knows", "work", "alice", 1000);
+    let mut sqlite = SqliteStore::in_memory().unwrap();
+    sqlite.put(attestation.clone()).unwrap();
+    let memory = MemoryStore::with_attestations(vec![attestation]);
+
+    let filter = AxFilter {
+        actors: vec!["alice".into()],
+        expand_actor_aliases: true,
+        ..Default::default()
+    };
+    for result in [sqlite.query(&filter), memory.query(&filter)] {
+        let err = result.unwrap_err();
+        assert!(err.to_string().contains("expand_actor_aliases"), "{err}");
+    }
+}
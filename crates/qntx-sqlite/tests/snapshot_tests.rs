@@ -0,0 +1,177 @@
+//! Read-only snapshot tests (validation, write rejection, refresh)
+
+use std::fs;
+use std::path::Path;
+use std::sync::{mpsc, Arc};
+use std::time::Duration;
+
+use qntx_core::{
+    storage::{AttestationStore, QueryStore},
+    AttestationBuilder, AxFilter,
+};
+use qntx_sqlite::{CheckpointMode, SqliteError, SqliteStore};
+use tempfile::TempDir;
+
+/// Write `count` attestations to a fresh database at `path`, fully checkpointed
+/// so the main file alone is a complete copy.
+fn write_source(path: &Path, count: usize) {
+    let mut store = SqliteStore::open(path).unwrap();
+    for i in 0..count {
+        store
+            .put(
+                AttestationBuilder::new()
+                    .id(format!("AS-{}", i))
+                    .subject(format!("SUBJECT-{}", i))
+                    .predicate("knows")
+                    .context("work")
+                    .attribute("note", serde_json::json!("x".repeat(200)))
+                    .build(),
+            )
+            .unwrap();
+    }
+    store.checkpoint(CheckpointMode::Truncate).unwrap();
+}
+
+/// Copy only the main database file, as a deployment rsync would.
+fn copy_snapshot(source: &Path, dest: &Path) {
+    fs::copy(source, dest).unwrap();
+}
+
+#[test]
+fn test_open_read_only_serves_queries() {
+    let dir = TempDir::new().unwrap();
+    let source = dir.path().join("source.db");
+    write_source(&source, 5);
+    let copy = dir.path().join("copy.db");
+    copy_snapshot(&source, &copy);
+
+    let store = SqliteStore::open_read_only(&copy).unwrap();
+    assert_eq!(store.count().unwrap(), 5);
+    assert!(store.get("AS-3").unwrap().is_some());
+    let result = store
+        .query(&AxFilter {
+            predicates: vec!["knows".into()],
+            ..Default::default()
+        })
+        .unwrap();
+    assert_eq!(result.attestations.len(), 5);
+
+    let info = store.info();
+    assert_eq!(info.rows, 5);
+    assert!(info.schema_version.is_some());
+}
+
+#[test]
+fn test_writes_rejected_with_read_only_error() {
+    let dir = TempDir::new().unwrap();
+    let source = dir.path().join("source.db");
+    write_source(&source, 2);
+
+    let mut store = SqliteStore::open_read_only(&source).unwrap();
+    let err = store
+        .put(AttestationBuilder::new().id("AS-new").subject("X").build())
+        .unwrap_err();
+    assert_eq!(err.code(), "invalid_input");
+    assert!(err.to_string().contains("read-only"), "{}", err);
+
+    assert!(store.delete("AS-0").is_err());
+    assert!(store.clear().is_err());
+    assert!(store
+        .put_if_revision(AttestationBuilder::new().id("AS-0").build(), 1)
+        .is_err());
+    assert_eq!(store.count().unwrap(), 2);
+}
+
+#[test]
+fn test_refresh_picks_up_rows_from_new_copy() {
+    let dir = TempDir::new().unwrap();
+    let first = dir.path().join("first.db");
+    let second = dir.path().join("second.db");
+    write_source(&first, 3);
+    write_source(&second, 7);
+
+    let store = SqliteStore::open_read_only(&first).unwrap();
+    assert_eq!(store.count().unwrap(), 3);
+
+    let info = store.refresh_from(&second).unwrap();
+    assert_eq!(info.rows, 7);
+    assert_eq!(store.count().unwrap(), 7);
+    assert!(store.get("AS-6").unwrap().is_some());
+    assert_eq!(store.path(), second);
+
+    // Same-path refresh after the file is replaced in place
+    let served = dir.path().join("served.db");
+    copy_snapshot(&first, &served);
+    store.refresh_from(&served).unwrap();
+    assert_eq!(store.count().unwrap(), 3);
+    copy_snapshot(&second, &served);
+    assert_eq!(store.refresh().unwrap().rows, 7);
+}
+
+#[test]
+fn test_truncated_file_fails_validation() {
+    let dir = TempDir::new().unwrap();
+    let source = dir.path().join("source.db");
+    write_source(&source, 200);
+
+    let bytes = fs::read(&source).unwrap();
+    let truncated = dir.path().join("truncated.db");
+    fs::write(&truncated, &bytes[..bytes.len() / 2]).unwrap();
+
+    match SqliteStore::open_read_only(&truncated) {
+        Err(SqliteError::InvalidSnapshot(msg)) => assert!(msg.contains("truncated"), "{}", msg),
+        Err(e) => panic!("expected InvalidSnapshot, got {}", e),
+        Ok(_) => panic!("truncated file was served"),
+    }
+
+    let garbage = dir.path().join("garbage.db");
+    fs::write(&garbage, b"definitely not a database").unwrap();
+    assert!(matches!(
+        SqliteStore::open_read_only(&garbage),
+        Err(SqliteError::InvalidSnapshot(_))
+    ));
+}
+
+#[test]
+fn test_failed_refresh_keeps_serving_previous_snapshot() {
+    let dir = TempDir::new().unwrap();
+    let source = dir.path().join("source.db");
+    write_source(&source, 4);
+    let served = dir.path().join("served.db");
+    copy_snapshot(&source, &served);
+
+    let store = SqliteStore::open_read_only(&served).unwrap();
+    let bytes = fs::read(&source).unwrap();
+    fs::write(&served, &bytes[..bytes.len() - 4096]).unwrap();
+
+    assert!(matches!(
+        store.refresh(),
+        Err(SqliteError::InvalidSnapshot(_))
+    ));
+    assert_eq!(store.info().rows, 4);
+}
+
+#[test]
+fn test_watch_for_changes_swaps_and_reports() {
+    let dir = TempDir::new().unwrap();
+    let first = dir.path().join("first.db");
+    let second = dir.path().join("second.db");
+    write_source(&first, 2);
+    write_source(&second, 9);
+    let served = dir.path().join("served.db");
+    copy_snapshot(&first, &served);
+
+    let store = Arc::new(SqliteStore::open_read_only(&served).unwrap());
+    let (tx, rx) = mpsc::channel();
+    let _watcher = store.watch_for_changes(
+        Duration::from_millis(20),
+        Some(Box::new(move |info| {
+            let _ = tx.send(info.rows);
+        })),
+    );
+
+    copy_snapshot(&second, &served);
+    let rows = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+    assert_eq!(rows, 9);
+    assert_eq!(store.count().unwrap(), 9);
+}
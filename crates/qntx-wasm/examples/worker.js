@@ -0,0 +1,49 @@
+// Minimal Web Worker harness for the browser build of qntx-wasm.
+//
+// Build:  cd crates/qntx-wasm && wasm-pack build --target web --features browser
+// Use:    new Worker(new URL('./worker.js', import.meta.url), { type: 'module' })
+//
+// Each worker gets its own module instance and its own IndexedDB handle;
+// nothing initialized on the main thread is visible here.
+
+import init, {
+    init_worker,
+    query_attestations_bytes,
+    classify_claims_bytes,
+    similarity_search_bytes,
+} from '../pkg/qntx_wasm.js';
+
+const ready = (async () => {
+    await init();
+    await init_worker('qntx', true);
+})();
+
+self.onmessage = async (event) => {
+    const { id, op, args } = event.data;
+    try {
+        await ready;
+        let bytes;
+        switch (op) {
+            case 'query':
+                bytes = await query_attestations_bytes(args.filterJson);
+                break;
+            case 'classify':
+                bytes = classify_claims_bytes(args.inputJson);
+                break;
+            case 'similarity':
+                bytes = similarity_search_bytes(args.query, args.candidates, args.limit, args.threshold);
+                break;
+            default:
+                throw new Error(`unknown op: ${op}`);
+        }
+        // Transfer the underlying buffer instead of copying it.
+        self.postMessage({ id, bytes }, [bytes.buffer]);
+    } catch (err) {
+        self.postMessage({ id, error: String(err) });
+    }
+};
+
+// Main thread side:
+//   worker.onmessage = ({ data }) => {
+//       const json = JSON.parse(new TextDecoder().decode(data.bytes));
+//   };
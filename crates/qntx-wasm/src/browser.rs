@@ -2,7 +2,10 @@
 //!
 //! Provides browser-compatible functions for:
 //! - Parsing AX queries (same as wazero target)
+//! - Building AX queries slot by slot through `query_builder_*` handles
 //! - Storing and retrieving attestations using IndexedDB
+//! - Queueing local writes in an outbox for upload once online (`enable_outbox`)
+//! - Running startup calls in one boundary crossing (`execute_batch`, `startup_bundle`)
 //!
 //! Unlike the wazero target which uses raw memory passing, these functions
 //! use wasm-bindgen for seamless JavaScript interop.
@@ -13,35 +16,68 @@
 //! - TypeScript uses proto-generated `Attestation` interface
 //! - JSON matches proto schema (timestamps as numbers, attributes as object)
 //! - Converted to qntx_core::Attestation for internal storage operations
+//!
+//! ## Web Workers
+//!
+//! All state in this module (the IndexedDB `STORE` handle) is thread-local, which
+//! in the browser means per-instance: every Worker that instantiates the module
+//! gets its own copy, and nothing initialized on the main thread is visible to a
+//! worker. Call `init_worker()` once inside the worker before anything else.
+//!
+//! The outbox switch and policy are per-instance too, but the queued entries
+//! live in IndexedDB and are shared by every instance opening the same database.
+//!
+//! Hot paths have `*_bytes` variants returning `Uint8Array` (UTF-8 JSON) so the
+//! result buffer can be posted back to the main thread as a transferable instead
+//! of copying a JS string twice. See `examples/worker.js`.
 
-use qntx_core::parser::Parser;
-use qntx_indexeddb::IndexedDbStore;
+use qntx_core::parser::{AxQueryBuilder, Parser};
+use qntx_core::slow_ops::{self, SlowOp};
+#[cfg(feature = "storage")]
+use qntx_core::slow_ops::{SlowOpCategory, SlowOpDetail};
+#[cfg(feature = "storage")]
+use qntx_core::storage::StoreError;
+#[cfg(feature = "storage")]
+use qntx_indexeddb::{
+    ImportFailure, ImportMode, IndexedDbError, IndexedDbStore, OutboxAdmission, OutboxPolicy,
+};
+#[cfg(feature = "storage")]
 use qntx_proto::Attestation as ProtoAttestation;
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+#[cfg(feature = "storage")]
+use std::collections::HashSet;
+#[cfg(feature = "storage")]
 use std::rc::Rc;
 use wasm_bindgen::prelude::*;
 
 /// Global store instance (initialized via init_store)
 /// Using Rc<RefCell<>> because WASM is single-threaded and we need to share across async boundaries
+#[cfg(feature = "storage")]
 thread_local! {
     static STORE: RefCell<Option<Rc<IndexedDbStore>>> = RefCell::new(None);
+    /// Policy of the outbox, None while local writes are not queued
+    static OUTBOX: RefCell<Option<OutboxPolicy>> = const { RefCell::new(None) };
+    /// Set while `drain_outbox` runs, so overlapping drains cannot send an entry twice
+    static DRAINING: Cell<bool> = const { Cell::new(false) };
 }
 
 /// Default database name for browser IndexedDB storage
+#[cfg(feature = "storage")]
 const DEFAULT_DB_NAME: &str = "qntx";
 
 /// Initialize the IndexedDB store. Must be called before any storage operations.
 /// Returns a Promise that resolves when initialization is complete.
+#[cfg(feature = "storage")]
 #[wasm_bindgen]
 pub async fn init_store(db_name: Option<String>) -> Result<(), JsValue> {
     // Route Rust panics to console.error instead of "RuntimeError: unreachable"
     console_error_panic_hook::set_once();
+    install_slow_op_hooks();
 
     let name = db_name.unwrap_or_else(|| DEFAULT_DB_NAME.to_string());
 
-    let store = IndexedDbStore::open(&name)
-        .await
-        .map_err(|e| JsValue::from_str(&format!("Failed to open IndexedDB: {:?}", e)))?;
+    let store = IndexedDbStore::open(&name).await.map_err(store_error)?;
 
     STORE.with(|s| {
         let mut s = s.borrow_mut();
@@ -53,7 +89,44 @@ pub async fn init_store(db_name: Option<String>) -> Result<(), JsValue> {
     })
 }
 
+/// Initialize this module inside a Web Worker (or any fresh instance).
+///
+/// Installs the panic hook and, when `open_store` is true, opens the IndexedDB
+/// store in the same call. Idempotent: a store already opened in this instance
+/// is kept rather than rejected, so a restarted worker script can call it again.
+/// Builds without the `storage` group reject `open_store`.
+#[wasm_bindgen]
+pub async fn init_worker(db_name: Option<String>, open_store: bool) -> Result<(), JsValue> {
+    console_error_panic_hook::set_once();
+    install_slow_op_hooks();
+
+    if !open_store || is_store_initialized() {
+        return Ok(());
+    }
+
+    #[cfg(feature = "storage")]
+    return init_store(db_name).await;
+
+    #[cfg(not(feature = "storage"))]
+    {
+        let _ = db_name;
+        Err(JsValue::from_str(
+            "this build has no storage group; call init_worker(name, false)",
+        ))
+    }
+}
+
+/// Convert a storage error into a JS exception carrying `{"code","message","hint"}`
+/// JSON, so callers can branch on `code` (see `StorageErrorKind`) and offer the
+/// recovery in `hint` (see `RecoveryHint`, null when there is none) instead of
+/// parsing the text.
+#[cfg(feature = "storage")]
+fn store_error(e: impl Into<IndexedDbError>) -> JsValue {
+    JsValue::from_str(&e.into().to_json())
+}
+
 /// Get a clone of the store Rc. Panics if not initialized.
+#[cfg(feature = "storage")]
 fn get_store() -> Rc<IndexedDbStore> {
     STORE.with(|s| {
         s.borrow()
@@ -77,8 +150,8 @@ pub fn parse_query(input: &str) -> String {
         Ok(query) => {
             // Same validation hack as wazero target for bug-for-bug compatibility
             if let Some(qntx_core::parser::TemporalClause::Over(ref dur)) = query.temporal {
-                if dur.value.is_some() && dur.unit.is_none() {
-                    return format!(r#"{{"error":"missing unit in '{}'"}}"#, dur.raw);
+                if let Err(e @ qntx_core::DurationError::MissingUnit(_)) = dur.duration() {
+                    return format!(r#"{{"error":"{}"}}"#, e);
                 }
             }
 
@@ -91,8 +164,138 @@ pub fn parse_query(input: &str) -> String {
     }
 }
 
+/// Lint an AX query against the vocabulary in IndexedDB: terms no
+/// attestation uses (with did-you-mean suggestions), values that belong to
+/// another slot, empty or future time ranges. Rules disabled in the `lint`
+/// section of `load_core_config` are skipped.
+///
+/// Returns `{"warnings":[{"code","severity","message","slot","term","position","suggestions"}]}`
+/// (`position` is the term's byte offset in `input`), or `{"error":"..."}` when
+/// the query doesn't parse. Reads the store's distinct-term indexes on every
+/// call, so debounce it while typing.
+#[cfg(feature = "storage")]
+#[wasm_bindgen]
+pub async fn lint_ax_query(input: &str) -> Result<String, JsValue> {
+    use qntx_core::LintContext;
+
+    let store = get_store();
+    let set = |terms: Vec<String>| terms.into_iter().collect();
+    let context = LintContext {
+        subjects: set(store.subjects().await.map_err(store_error)?),
+        predicates: set(store.predicates().await.map_err(store_error)?),
+        contexts: set(store.contexts().await.map_err(store_error)?),
+        actors: set(store.actors().await.map_err(store_error)?),
+        ..LintContext::new(now_ms())
+    }
+    .with_config(crate::core_config::lint_config());
+    Ok(qntx_core::lint_query_json(input, &context))
+}
+
+/// Parse an AX query together with up to `max_alternates` other readings
+/// of its ambiguous spots (`Is` as a term, `summary of` as `summary_of`, ...),
+/// for "did you mean" chips. Ranking uses the IndexedDB vocabulary when the
+/// store is open.
+///
+/// Returns `{"primary":{...},"confidence":0.6,"alternates":[{"text","query","confidence","ambiguities"}]}`
+/// with alternates most confident first, or `{"error":"..."}` when the query
+/// doesn't parse.
+#[wasm_bindgen]
+pub async fn parse_query_alternates(input: &str, max_alternates: usize) -> Result<String, JsValue> {
+    #[cfg(feature = "storage")]
+    if is_store_initialized() {
+        use qntx_core::LintContext;
+
+        let store = get_store();
+        let set = |terms: Vec<String>| terms.into_iter().collect();
+        let vocabulary = LintContext {
+            subjects: set(store.subjects().await.map_err(store_error)?),
+            predicates: set(store.predicates().await.map_err(store_error)?),
+            contexts: set(store.contexts().await.map_err(store_error)?),
+            actors: set(store.actors().await.map_err(store_error)?),
+            ..LintContext::new(now_ms())
+        };
+        return Ok(qntx_core::parser::parse_query_alternates_json(
+            input,
+            max_alternates,
+            Some(&vocabulary),
+        ));
+    }
+
+    Ok(qntx_core::parser::parse_query_alternates_json(
+        input,
+        max_alternates,
+        None,
+    ))
+}
+
+/// Parse a duration string ("18m", "1y6m", "2w3d") for display.
+///
+/// Returns: `{"raw":"18m","normalized":"1y6m","ms":46656000000}` on success
+///          `{"error":"description"}` on error
+#[wasm_bindgen]
+pub fn parse_duration(input: &str) -> String {
+    qntx_core::duration::parse_duration_json(input)
+}
+
 // ============================================================================
-// Storage operations
+// Query builder
+// ============================================================================
+
+thread_local! {
+    /// Open `AxQueryBuilder` handles by id
+    static QUERY_BUILDERS: RefCell<HashMap<u32, AxQueryBuilder>> = RefCell::new(HashMap::new());
+    static NEXT_QUERY_BUILDER: Cell<u32> = const { Cell::new(1) };
+}
+
+/// Open an empty query builder and return its handle. Free it with
+/// `query_builder_free` when the builder UI closes.
+#[wasm_bindgen]
+pub fn query_builder_new() -> u32 {
+    let id = NEXT_QUERY_BUILDER.with(|next| {
+        let id = next.get();
+        next.set(id.wrapping_add(1).max(1));
+        id
+    });
+    QUERY_BUILDERS.with(|b| b.borrow_mut().insert(id, AxQueryBuilder::new()));
+    id
+}
+
+/// Apply one edit to builder `id`, e.g. `{"op":"add","slot":"subjects","value":"ALICE"}`
+/// (ops: `add`, `remove`, `set`, `set_temporal`, `add_condition`,
+/// `remove_condition`, `clear_conditions`, `clear`).
+///
+/// Returns the new preview as `query_builder_preview` does, or
+/// `{"error":"..."}` with the builder unchanged.
+#[wasm_bindgen]
+pub fn query_builder_set(id: u32, op_json: &str) -> String {
+    with_query_builder(id, |builder| builder.apply_json(op_json))
+}
+
+/// Current state of builder `id`.
+///
+/// Returns `{"query":{...},"query_string":"...","warnings":[{"slot","value","code","message"}]}`,
+/// where `query_string` parses back to `query` and `warnings` lists values
+/// quoted because they read as keywords; or `{"error":"..."}` for an unknown id.
+#[wasm_bindgen]
+pub fn query_builder_preview(id: u32) -> String {
+    with_query_builder(id, |builder| builder.preview_json())
+}
+
+/// Drop builder `id`. Returns false when it was not open.
+#[wasm_bindgen]
+pub fn query_builder_free(id: u32) -> bool {
+    QUERY_BUILDERS.with(|b| b.borrow_mut().remove(&id).is_some())
+}
+
+fn with_query_builder(id: u32, f: impl FnOnce(&mut AxQueryBuilder) -> String) -> String {
+    QUERY_BUILDERS.with(|b| match b.borrow_mut().get_mut(&id) {
+        Some(builder) => f(builder),
+        None => serde_json::json!({ "error": format!("unknown query builder {}", id) }).to_string(),
+    })
+}
+
+// ============================================================================
+// Storage operations (feature = "storage")
 // ============================================================================
 
 /// Store an attestation in IndexedDB.
@@ -100,36 +303,72 @@ pub fn parse_query(input: &str) -> String {
 ///
 /// Expects JSON matching proto schema (timestamps as numbers, attributes as JSON object).
 /// Converts to internal core::Attestation format before storage.
+#[cfg(feature = "storage")]
 #[wasm_bindgen]
 pub async fn put_attestation(json: &str) -> Result<(), JsValue> {
     // Deserialize from proto-compliant JSON
     let proto_attestation: ProtoAttestation = serde_json::from_str(json)
-        .map_err(|e| JsValue::from_str(&format!("Invalid JSON: {}", e)))?;
+        .map_err(|e| store_error(StoreError::InvalidData(format!("Invalid JSON: {}", e))))?;
 
     // Convert to core type for storage
     let core_attestation = qntx_proto::proto_convert::from_proto(proto_attestation);
 
+    let timer = slow_ops::start(SlowOpCategory::StoreWrite);
     let store = get_store();
-    store
-        .put(core_attestation)
-        .await
-        .map_err(|e| JsValue::from_str(&format!("Store error: {:?}", e)))?;
+    match outbox_policy() {
+        Some(policy) => {
+            let admission = store
+                .put_queued(core_attestation, now_ms(), &policy)
+                .await
+                .map_err(store_error)?;
+            warn_outbox(&admission);
+        }
+        None => store.put(core_attestation).await.map_err(store_error)?,
+    }
+    if let Some(timer) = timer {
+        timer.finish(SlowOpDetail {
+            input_len: Some(json.len()),
+            result_count: None,
+        });
+    }
 
     Ok(())
 }
 
+/// Store an attestation only if its stored revision equals `expected_revision`
+/// (0 = must not exist yet). Plain `put_attestation` is unaffected.
+///
+/// Resolves to `{"status":"written","revision":N}`, or on a lost race to
+/// `{"status":"conflict","current":{...}|null}` with the winner's attestation
+/// in proto schema.
+#[cfg(feature = "storage")]
+#[wasm_bindgen]
+pub async fn put_attestation_if_revision(
+    json: &str,
+    expected_revision: u64,
+) -> Result<String, JsValue> {
+    let proto_attestation: ProtoAttestation = serde_json::from_str(json)
+        .map_err(|e| store_error(StoreError::InvalidData(format!("Invalid JSON: {}", e))))?;
+    let core_attestation = qntx_proto::proto_convert::from_proto(proto_attestation);
+
+    let store = get_store();
+    let outcome = store
+        .put_if_revision(core_attestation, expected_revision)
+        .await
+        .map_err(store_error)?;
+    Ok(qntx_proto::proto_convert::put_outcome_to_json(outcome))
+}
+
 /// Retrieve an attestation by ID from IndexedDB.
 /// Returns a Promise that resolves to JSON-serialized attestation or null if not found.
 ///
 /// Returns JSON matching proto schema (timestamps as numbers, attributes as JSON object).
 /// Converts from internal core::Attestation format before serialization.
+#[cfg(feature = "storage")]
 #[wasm_bindgen]
 pub async fn get_attestation(id: &str) -> Result<Option<String>, JsValue> {
     let store = get_store();
-    let result = store
-        .get(id)
-        .await
-        .map_err(|e| JsValue::from_str(&format!("Store error: {:?}", e)))?;
+    let result = store.get(id).await.map_err(store_error)?;
 
     match result {
         Some(core_attestation) => {
@@ -145,40 +384,115 @@ pub async fn get_attestation(id: &str) -> Result<Option<String>, JsValue> {
 
 /// Delete an attestation by ID from IndexedDB.
 /// Returns a Promise that resolves to true if deleted, false if not found.
+#[cfg(feature = "storage")]
 #[wasm_bindgen]
 pub async fn delete_attestation(id: &str) -> Result<bool, JsValue> {
     let store = get_store();
-    store
-        .delete(id)
-        .await
-        .map_err(|e| JsValue::from_str(&format!("Store error: {:?}", e)))
+    match outbox_policy() {
+        Some(policy) => {
+            let admission = store
+                .delete_queued(id, now_ms(), &policy)
+                .await
+                .map_err(store_error)?;
+            if let Some(admission) = &admission {
+                warn_outbox(admission);
+            }
+            Ok(admission.is_some())
+        }
+        None => store.delete(id).await.map_err(store_error),
+    }
+}
+
+/// Delete an attestation through the delete cascade. `options_json` is a
+/// `DeleteOptions` (`{"dry_run","find_orphans","scan_attributes"}`, empty for
+/// defaults). `find_orphans` reports attestations with `id` as a subject;
+/// `scan_attributes` also reads every stored attestation for it in attributes.
+/// The removal itself goes through `delete_attestation`, outbox included.
+///
+/// Returns `{"id","dry_run","found","orphans":[...],"hooks":[]}`.
+#[cfg(feature = "storage")]
+#[wasm_bindgen]
+pub async fn delete_attestation_cascade(id: &str, options_json: &str) -> Result<String, JsValue> {
+    use qntx_core::attestation::AxFilter;
+    use qntx_core::storage::{references_id, DeleteOptions, DeleteReport};
+
+    let options: DeleteOptions = match options_json.trim() {
+        "" | "null" => DeleteOptions::default(),
+        json => serde_json::from_str(json).map_err(|e| {
+            store_error(StoreError::InvalidData(format!(
+                "invalid delete options: {}",
+                e
+            )))
+        })?,
+    };
+    let store = get_store();
+    let mut report = DeleteReport {
+        id: id.to_string(),
+        dry_run: options.dry_run,
+        found: store.exists(id).await.map_err(store_error)?,
+        ..Default::default()
+    };
+    if report.found {
+        if options.find_orphans {
+            let candidates = if options.scan_attributes {
+                AxFilter::default()
+            } else {
+                AxFilter {
+                    subjects: vec![id.to_string()],
+                    ..Default::default()
+                }
+            };
+            let matching = store.query(&candidates).await.map_err(store_error)?;
+            report.orphans = matching
+                .attestations
+                .into_iter()
+                .filter(|a| references_id(a, id))
+                .map(|a| a.id)
+                .collect();
+            report.orphans.sort();
+        }
+        if !options.dry_run {
+            delete_attestation(id).await?;
+        }
+    }
+    serde_json::to_string(&report)
+        .map_err(|e| JsValue::from_str(&format!("Serialization error: {}", e)))
 }
 
 /// Check if an attestation exists in IndexedDB.
 /// Returns a Promise that resolves to true if exists, false otherwise.
+#[cfg(feature = "storage")]
 #[wasm_bindgen]
 pub async fn exists_attestation(id: &str) -> Result<bool, JsValue> {
     let store = get_store();
-    store
-        .exists(id)
-        .await
-        .map_err(|e| JsValue::from_str(&format!("Store error: {:?}", e)))
+    store.exists(id).await.map_err(store_error)
 }
 
 /// Query attestations from IndexedDB using an AxFilter.
-/// Expects JSON-serialized AxFilter. Returns JSON array of proto-format attestations.
+/// Expects JSON-serialized AxFilter. Returns JSON array of proto-format attestations,
+/// or `{"attestations": [...], "summary": {...}}` when the filter sets `include_summary`.
+#[cfg(feature = "storage")]
 #[wasm_bindgen]
 pub async fn query_attestations(filter_json: &str) -> Result<String, JsValue> {
     use qntx_core::attestation::AxFilter;
 
     let filter: AxFilter = serde_json::from_str(filter_json)
-        .map_err(|e| JsValue::from_str(&format!("Invalid filter JSON: {}", e)))?;
+        .map_err(|e| store_error(StoreError::Query(format!("Invalid filter JSON: {}", e))))?;
+    let filter = if filter.expand_actor_aliases {
+        crate::core_config::actor_aliases().expand_filter(&filter)
+    } else {
+        filter
+    };
 
+    let timer = slow_ops::start(SlowOpCategory::StoreQuery);
     let store = get_store();
-    let result = store
-        .query(&filter)
-        .await
-        .map_err(|e| JsValue::from_str(&format!("Query error: {:?}", e)))?;
+    let result = store.query(&filter).await.map_err(store_error)?;
+    if let Some(timer) = timer {
+        timer.finish(SlowOpDetail {
+            input_len: Some(filter_json.len()),
+            result_count: Some(result.attestations.len()),
+        });
+    }
 
     let proto_attestations: Vec<ProtoAttestation> = result
         .attestations
@@ -186,26 +500,417 @@ pub async fn query_attestations(filter_json: &str) -> Result<String, JsValue> {
         .map(qntx_proto::proto_convert::to_proto)
         .collect();
 
-    serde_json::to_string(&proto_attestations)
-        .map_err(|e| JsValue::from_str(&format!("Serialization error: {}", e)))
+    let json = if filter.include_summary {
+        serde_json::to_string(&serde_json::json!({
+            "attestations": proto_attestations,
+            "summary": result.summary,
+        }))
+    } else {
+        serde_json::to_string(&proto_attestations)
+    };
+    json.map_err(|e| JsValue::from_str(&format!("Serialization error: {}", e)))
+}
+
+/// Same as `query_attestations`, but returns the UTF-8 JSON as a `Uint8Array`
+/// so a worker can hand the buffer to `postMessage` as a transferable.
+#[cfg(feature = "storage")]
+#[wasm_bindgen]
+pub async fn query_attestations_bytes(filter_json: &str) -> Result<Vec<u8>, JsValue> {
+    query_attestations(filter_json)
+        .await
+        .map(String::into_bytes)
+}
+
+/// One page of `query_attestations`, in ID order. Pass `cursor` as null for
+/// the first page, then each page's `next_cursor` until it is null.
+/// Returns a Promise that resolves to JSON
+/// `{"items":[...],"next_cursor":string|null}`; an unrecognized cursor
+/// rejects with an `invalid_input` error instead of starting over.
+#[cfg(feature = "storage")]
+#[wasm_bindgen]
+pub async fn query_attestations_page(
+    filter_json: &str,
+    cursor: Option<String>,
+    page_size: usize,
+) -> Result<String, JsValue> {
+    use qntx_core::attestation::AxFilter;
+
+    let filter: AxFilter = serde_json::from_str(filter_json)
+        .map_err(|e| store_error(StoreError::Query(format!("Invalid filter JSON: {}", e))))?;
+    let filter = if filter.expand_actor_aliases {
+        crate::core_config::actor_aliases().expand_filter(&filter)
+    } else {
+        filter
+    };
+
+    let store = get_store();
+    let page = store
+        .query_page(&filter, cursor, page_size)
+        .await
+        .map_err(store_error)?;
+
+    let items: Vec<ProtoAttestation> = page
+        .items
+        .into_iter()
+        .map(qntx_proto::proto_convert::to_proto)
+        .collect();
+    serde_json::to_string(&serde_json::json!({
+        "items": items,
+        "next_cursor": page.next_cursor,
+    }))
+    .map_err(|e| JsValue::from_str(&format!("Serialization error: {}", e)))
 }
 
 /// Get all attestation IDs from IndexedDB.
 /// Returns a Promise that resolves to JSON array of IDs.
+#[cfg(feature = "storage")]
 #[wasm_bindgen]
 pub async fn list_attestation_ids() -> Result<String, JsValue> {
     let store = get_store();
-    let ids = store
-        .ids()
-        .await
-        .map_err(|e| JsValue::from_str(&format!("Store error: {:?}", e)))?;
+    let ids = store.ids().await.map_err(store_error)?;
 
     serde_json::to_string(&ids)
         .map_err(|e| JsValue::from_str(&format!("Serialization error: {}", e)))
 }
 
+/// Export every stored attestation as one JSON array in proto schema, in ID
+/// order, e.g. to move a profile with `import_attestations`. The store is
+/// read and serialized batch by batch.
+#[cfg(feature = "storage")]
+#[wasm_bindgen]
+pub async fn export_attestations() -> Result<String, JsValue> {
+    let mut json = String::from("[");
+    get_store()
+        .export_batches(|batch| {
+            for attestation in batch {
+                if json.len() > 1 {
+                    json.push(',');
+                }
+                let proto_attestation = qntx_proto::proto_convert::to_proto(attestation);
+                let record = serde_json::to_string(&proto_attestation)
+                    .map_err(|e| StoreError::Serialization(e.to_string()))?;
+                json.push_str(&record);
+            }
+            Ok(())
+        })
+        .await
+        .map_err(store_error)?;
+    json.push(']');
+    Ok(json)
+}
+
+/// Store a JSON array of proto-schema attestations (as from
+/// `export_attestations`) in as few IndexedDB transactions as batching
+/// allows. `mode` is `"skip_existing"` or `"overwrite"` and decides what
+/// happens to IDs already stored. Records that don't parse or that the store
+/// rejects are listed in `errors` by array index; the others are still
+/// imported. Imports are not queued in the outbox.
+///
+/// Returns a Promise that resolves to JSON
+/// `{"imported":N,"skipped":N,"errors":[{"index":N,"error":"..."}]}`.
+#[cfg(feature = "storage")]
+#[wasm_bindgen]
+pub async fn import_attestations(json: &str, mode: &str) -> Result<String, JsValue> {
+    let import_mode = ImportMode::parse(mode).ok_or_else(|| {
+        store_error(StoreError::InvalidData(format!(
+            "unknown import mode '{}' (expected skip_existing or overwrite)",
+            mode
+        )))
+    })?;
+    let (records, failures) = import_records(json).map_err(store_error)?;
+
+    let timer = slow_ops::start(SlowOpCategory::StoreWrite);
+    let mut report = get_store()
+        .import(records, import_mode)
+        .await
+        .map_err(store_error)?;
+    if let Some(timer) = timer {
+        timer.finish(SlowOpDetail {
+            input_len: Some(json.len()),
+            result_count: Some(report.imported),
+        });
+    }
+
+    report.errors.extend(failures);
+    report.errors.sort_by_key(|failure| failure.index);
+    serde_json::to_string(&report)
+        .map_err(|e| JsValue::from_str(&format!("Serialization error: {}", e)))
+}
+
+/// Indexed attestations of an import array, and the entries that failed to parse
+#[cfg(feature = "storage")]
+type ImportRecords = (Vec<(usize, qntx_core::Attestation)>, Vec<ImportFailure>);
+
+/// The attestations of an `import_attestations` array, each with its index,
+/// and the records that aren't attestations. Fails only when `json` is not
+/// an array.
+#[cfg(feature = "storage")]
+fn import_records(json: &str) -> Result<ImportRecords, StoreError> {
+    let values: Vec<serde_json::Value> = serde_json::from_str(json).map_err(|e| {
+        StoreError::InvalidData(format!("expected a JSON array of attestations: {}", e))
+    })?;
+
+    let mut records = Vec::with_capacity(values.len());
+    let mut failures = Vec::new();
+    for (index, value) in values.into_iter().enumerate() {
+        let error = match serde_json::from_value::<ProtoAttestation>(value) {
+            Ok(proto_attestation) if !proto_attestation.id.is_empty() => {
+                let attestation = qntx_proto::proto_convert::from_proto(proto_attestation);
+                records.push((index, attestation));
+                continue;
+            }
+            Ok(_) => "attestation has no id".to_string(),
+            Err(e) => format!("invalid attestation: {}", e),
+        };
+        failures.push(ImportFailure { index, error });
+    }
+    Ok((records, failures))
+}
+
+// ============================================================================
+// Outbox (offline-first writes) (feature = "storage")
+// ============================================================================
+
+/// Queue every later `put_attestation`/`delete_attestation` in the IndexedDB
+/// outbox (in the same transaction as the local write) until disabled.
+/// Entries already queued stay queued either way.
+#[cfg(feature = "storage")]
+#[wasm_bindgen]
+pub fn enable_outbox(enabled: bool) {
+    OUTBOX.with(|o| {
+        let mut o = o.borrow_mut();
+        if !enabled {
+            *o = None;
+        } else if o.is_none() {
+            *o = Some(OutboxPolicy::default());
+        }
+    });
+}
+
+/// Set the outbox size policy. Omitted fields keep their defaults:
+/// `{"max_entries":10000,"warn_entries":8000,"on_full":"block"|"drop_oldest"}`.
+/// With `block`, writes fail with code `quota_exceeded` once the outbox is full;
+/// with `drop_oldest`, the oldest entries are discarded unsent.
+/// Also enables the outbox.
+#[cfg(feature = "storage")]
+#[wasm_bindgen]
+pub fn set_outbox_policy(json: &str) -> Result<(), JsValue> {
+    let policy: OutboxPolicy = serde_json::from_str(json).map_err(|e| {
+        store_error(StoreError::InvalidData(format!(
+            "Invalid outbox policy: {}",
+            e
+        )))
+    })?;
+    OUTBOX.with(|o| *o.borrow_mut() = Some(policy));
+    Ok(())
+}
+
+#[cfg(feature = "storage")]
+fn outbox_policy() -> Option<OutboxPolicy> {
+    OUTBOX.with(|o| o.borrow().clone())
+}
+
+#[cfg(feature = "storage")]
+fn now_ms() -> i64 {
+    js_sys::Date::now() as i64
+}
+
+#[cfg(feature = "storage")]
+fn warn_outbox(admission: &OutboxAdmission) {
+    if admission.dropped > 0 {
+        web_sys::console::warn_1(
+            &format!(
+                "[qntx-wasm] outbox full: dropped {} oldest unsent change(s)",
+                admission.dropped
+            )
+            .into(),
+        );
+    } else if admission.warn {
+        web_sys::console::warn_1(
+            &format!(
+                "[qntx-wasm] outbox has {} unsent changes",
+                admission.pending
+            )
+            .into(),
+        );
+    }
+}
+
+/// Hand due outbox entries, oldest first, to `sender` and remove the ones it
+/// accepts. Works whether or not the outbox is currently enabled.
+///
+/// `sender(entry)` gets one entry object at a time
+/// (`{seq, op: "put"|"delete", id, attestation?, content_hash, queued_at, attempts, ...}`,
+/// the attestation in proto schema) and returns, or resolves to, `true` /
+/// `{ok: true}` on success or `false` / `{ok: false, error}` on failure; a throw
+/// or rejection is a failure too. Failed entries stay queued with exponential
+/// backoff, and later entries for the same attestation ID are held back so the
+/// server sees each ID's writes in order. An entry is removed only after
+/// `sender` succeeds, so after a crash mid-drain it is sent again; the server
+/// should dedup on `content_hash`, which is unique per queued write.
+///
+/// Resolves to `{"sent":N,"failed":N,"deferred":N,"remaining":N}`.
+#[cfg(feature = "storage")]
+#[wasm_bindgen]
+pub async fn drain_outbox(sender: js_sys::Function) -> Result<String, JsValue> {
+    if DRAINING.with(|d| d.replace(true)) {
+        return Err(store_error(StoreError::Backend(
+            "outbox drain already in progress".into(),
+        )));
+    }
+    struct DrainGuard;
+    impl Drop for DrainGuard {
+        fn drop(&mut self) {
+            DRAINING.with(|d| d.set(false));
+        }
+    }
+    let _guard = DrainGuard;
+
+    let store = get_store();
+    let entries = store.outbox_entries().await.map_err(store_error)?;
+    let now = now_ms();
+
+    let (mut sent, mut failed, mut deferred) = (0, 0, 0);
+    // IDs with an earlier entry still queued
+    let mut held: HashSet<String> = HashSet::new();
+    for mut entry in entries {
+        if held.contains(&entry.id) || !entry.is_due(now) {
+            deferred += 1;
+            held.insert(entry.id);
+            continue;
+        }
+
+        match send_outbox_entry(&sender, &entry).await {
+            Ok(()) => {
+                if let Some(seq) = entry.seq {
+                    store.outbox_remove(seq).await.map_err(store_error)?;
+                }
+                sent += 1;
+            }
+            Err(error) => {
+                entry.record_failure(error, now_ms());
+                store.outbox_update(&entry).await.map_err(store_error)?;
+                failed += 1;
+                held.insert(entry.id);
+            }
+        }
+    }
+
+    Ok(serde_json::json!({
+        "sent": sent,
+        "failed": failed,
+        "deferred": deferred,
+        "remaining": failed + deferred,
+    })
+    .to_string())
+}
+
+/// Call `sender` with one entry and interpret its (possibly async) answer.
+#[cfg(feature = "storage")]
+async fn send_outbox_entry(
+    sender: &js_sys::Function,
+    entry: &qntx_indexeddb::OutboxEntry,
+) -> Result<(), String> {
+    let json = serde_json::to_string(entry).map_err(|e| e.to_string())?;
+    let arg =
+        js_sys::JSON::parse(&json).map_err(|e| qntx_indexeddb::error::js_error_message(&e))?;
+
+    let answer = sender
+        .call1(&JsValue::NULL, &arg)
+        .map_err(|e| qntx_indexeddb::error::js_error_message(&e))?;
+    let answer = wasm_bindgen_futures::JsFuture::from(js_sys::Promise::resolve(&answer))
+        .await
+        .map_err(|e| qntx_indexeddb::error::js_error_message(&e))?;
+
+    let ok = answer.as_bool().unwrap_or_else(|| {
+        js_sys::Reflect::get(&answer, &"ok".into())
+            .ok()
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false)
+    });
+    if ok {
+        return Ok(());
+    }
+    let error = js_sys::Reflect::get(&answer, &"error".into())
+        .ok()
+        .and_then(|v| v.as_string());
+    Err(error.unwrap_or_else(|| "upload rejected".to_string()))
+}
+
+/// Outbox summary for an "N changes pending" badge:
+/// `{"pending":N,"oldest_age_ms":N|null,"failing":N,"next_attempt_at":N|null,"warn":bool,"full":bool,"enabled":bool}`.
+#[cfg(feature = "storage")]
+#[wasm_bindgen]
+pub async fn outbox_status() -> Result<String, JsValue> {
+    let policy = outbox_policy();
+    let status = get_store()
+        .outbox_status(now_ms(), &policy.clone().unwrap_or_default())
+        .await
+        .map_err(store_error)?;
+
+    let mut json = serde_json::to_value(status)
+        .map_err(|e| JsValue::from_str(&format!("Serialization error: {}", e)))?;
+    json["enabled"] = policy.is_some().into();
+    Ok(json.to_string())
+}
+
+/// Make every queued entry due now, e.g. on the browser's `online` event,
+/// instead of waiting out its backoff.
+#[cfg(feature = "storage")]
+#[wasm_bindgen]
+pub async fn reset_outbox_backoff() -> Result<(), JsValue> {
+    let store = get_store();
+    let now = now_ms();
+    for mut entry in store.outbox_entries().await.map_err(store_error)? {
+        if !entry.is_due(now) {
+            entry.next_attempt_at = now;
+            store.outbox_update(&entry).await.map_err(store_error)?;
+        }
+    }
+    Ok(())
+}
+
+/// Discard every queued entry unsent. Local data is untouched.
+#[cfg(feature = "storage")]
+#[wasm_bindgen]
+pub async fn clear_outbox() -> Result<(), JsValue> {
+    get_store().outbox_clear().await.map_err(store_error)
+}
+
+/// Writes after sequence number `since` (0 = from the oldest kept), at most
+/// `limit` of them:
+/// `{"events":[{"seq":N,"op":"put"|"update"|"delete","attestation_id":"...","content_hash":"...","at":N}],"latest_seq":N,"missed":bool}`.
+/// `missed` means events after `since` were pruned; rescan the store.
+#[cfg(feature = "storage")]
+#[wasm_bindgen]
+pub async fn changes_since(since: u64, limit: u32) -> Result<String, JsValue> {
+    let batch = get_store()
+        .changes_since(since, limit as usize)
+        .await
+        .map_err(store_error)?;
+    let missed = batch.missed;
+    let mut json = serde_json::to_value(batch)
+        .map_err(|e| JsValue::from_str(&format!("Serialization error: {}", e)))?;
+    json["missed"] = missed.into();
+    Ok(json.to_string())
+}
+
+/// Drop change events older than `{"max_age_ms":N}` and/or beyond the newest
+/// `{"max_events":N}`. Resolves to the number removed.
+#[cfg(feature = "storage")]
+#[wasm_bindgen]
+pub async fn prune_changes(retention_json: &str) -> Result<u32, JsValue> {
+    let retention: qntx_core::storage::ChangeRetention = serde_json::from_str(retention_json)
+        .map_err(|e| store_error(StoreError::InvalidData(format!("Invalid JSON: {}", e))))?;
+    let removed = get_store()
+        .prune_changes(&retention, now_ms())
+        .await
+        .map_err(store_error)?;
+    Ok(removed as u32)
+}
+
 // ============================================================================
-// Classification
+// Classification (feature = "classify")
 // ============================================================================
 
 /// Classify claim conflicts. Takes JSON input with claim groups, temporal config,
@@ -222,23 +927,426 @@ pub async fn list_attestation_ids() -> Result<String, JsValue> {
 /// ```
 ///
 /// Returns JSON with conflicts, auto_resolved count, review_required count.
+/// Windows missing from `config` fall back to the config set by `load_core_config`.
+#[cfg(feature = "classify")]
 #[wasm_bindgen]
 pub fn classify_claims(input: &str) -> String {
-    qntx_core::classify_claims(input)
+    crate::core_config::classify_claims_impl(input)
+}
+
+/// Time windows `classify_claims` uses when its config omits them, as JSON
+/// `{"verification_window_ms":N,"evolution_window_ms":N,"obsolescence_window_ms":N}`.
+/// Reflects `load_core_config`, so clients need not hardcode durations.
+#[cfg(feature = "classify")]
+#[wasm_bindgen]
+pub fn classify_default_config() -> String {
+    crate::core_config::classify_default_config_impl()
+}
+
+/// Same as `classify_claims`, but returns the UTF-8 JSON as a `Uint8Array`.
+#[cfg(feature = "classify")]
+#[wasm_bindgen]
+pub fn classify_claims_bytes(input: &str) -> Vec<u8> {
+    crate::core_config::classify_claims_impl(input).into_bytes()
+}
+
+/// Set the actor aliases used by `classify_claims` and by queries whose
+/// filter sets `expand_actor_aliases`, e.g.
+/// `{"human:alice": ["alice", "human:alice@verified"]}`.
+/// Returns `{"ok":true,"identities":N}` or `{"error":"..."}`; on error the
+/// previous aliases stay. A per-call `config.actor_aliases` adds to these.
+#[cfg(feature = "classify")]
+#[wasm_bindgen]
+pub fn set_actor_aliases(input: &str) -> String {
+    crate::core_config::set_actor_aliases_impl(input)
+}
+
+/// Replace the actor aliases with those attested in IndexedDB
+/// (`[alias] actor_alias [canonical]`, newest claim per alias wins).
+/// Resolves to `{"ok":true,"identities":N}`.
+#[cfg(all(feature = "storage", feature = "classify"))]
+#[wasm_bindgen]
+pub async fn load_actor_aliases() -> Result<String, JsValue> {
+    use qntx_core::attestation::AxFilter;
+
+    let filter = AxFilter {
+        predicates: vec![qntx_core::ACTOR_ALIAS_PREDICATE.to_string()],
+        ..Default::default()
+    };
+    let result = get_store().query(&filter).await.map_err(store_error)?;
+    Ok(crate::core_config::install_actor_aliases(
+        qntx_core::ActorAliasMap::from_attestations(&result.attestations),
+    ))
+}
+
+/// Resolved claims that the stored attestation `id` supports, classified
+/// under the loaded config among every attestation sharing its subjects,
+/// predicates and contexts. Claims `id` lost to another source are left out.
+///
+/// Resolves to `{"id","claims":[{"subject","predicate","context","conflict_type",
+/// "confidence","supporting_source_ids","superseded_source_ids"}]}`;
+/// `conflict_type` is null for claims with a single source. Empty if `id`
+/// isn't stored.
+#[cfg(all(feature = "storage", feature = "classify"))]
+#[wasm_bindgen]
+pub async fn claims_supported_by(id: &str) -> Result<String, JsValue> {
+    use qntx_core::classify::provenance::{supported_claims, supporting_filter};
+
+    let store = get_store();
+    let claims = match store.get(id).await.map_err(store_error)? {
+        Some(target) => {
+            let result = store
+                .query(&supporting_filter(&target))
+                .await
+                .map_err(store_error)?;
+            supported_claims(
+                id,
+                &result.attestations,
+                &crate::core_config::classify_policy(),
+                now_ms(),
+            )
+        }
+        None => Vec::new(),
+    };
+    serde_json::to_string(&serde_json::json!({ "id": id, "claims": claims }))
+        .map_err(|e| JsValue::from_str(&format!("Serialization error: {}", e)))
+}
+
+/// Load a qntx-core config document (e.g. `{"classify": {...}}`) as the
+/// defaults for this module. Returns `{"ok":true}` or `{"error":"..."}`
+/// listing every validation problem; on error the previous config stays.
+#[wasm_bindgen]
+pub fn load_core_config(input: &str) -> String {
+    crate::core_config::load_core_config_impl(input)
+}
+
+// ============================================================================
+// Cartesian expansion (feature = "classify")
+// ============================================================================
+
+/// Expand compact attestations into individual claims via cartesian product.
+/// Same JSON contract as the wazero `expand_cartesian_claims` export.
+///
+/// Input: `{"attestations": [{"id", "subjects", "predicates", "contexts", "actors", "timestamp_ms"}]}`
+///
+/// Returns `{"claims": [...], "total": N}` or `{"error": "..."}`.
+#[cfg(feature = "classify")]
+#[wasm_bindgen]
+pub fn expand_cartesian_claims(input: &str) -> String {
+    qntx_core::expand_claims_json(input)
+}
+
+/// Group individual claims by (subject, predicate, context).
+/// Same JSON contract as the wazero `group_claims` export.
+///
+/// Input: `{"claims": [...], "key_format": "json" | "legacy"}` (`key_format` optional).
+///
+/// Returns `{"groups": [{"key", "claims"}], "total_groups": N, "key_format": "..."}`.
+/// `json` keys are `JSON.parse`-able `[subject, predicate, context]` arrays;
+/// `legacy` keys are `subject|predicate|context` and are ambiguous when a value contains `|`.
+#[cfg(feature = "classify")]
+#[wasm_bindgen]
+pub fn group_claims(input: &str) -> String {
+    qntx_core::group_claims_json(input)
+}
+
+/// Deduplicate claims to unique source attestation IDs, preserving order.
+/// Same JSON contract as the wazero `dedup_source_ids` export.
+///
+/// Input: `{"claims": [...]}`. Returns `{"ids": [...], "total": N}`.
+#[cfg(feature = "classify")]
+#[wasm_bindgen]
+pub fn dedup_source_ids(input: &str) -> String {
+    qntx_core::dedup_source_ids_json(input)
+}
+
+// ============================================================================
+// Graph Projection
+// ============================================================================
+
+/// Project a JSON array of attestations into graph nodes and merged edges.
+/// `config_json` is a `GraphProjection` (empty string or `null` for defaults).
+///
+/// Returns `{"nodes":[...],"edges":[...]}` or `{"error":"..."}`.
+#[wasm_bindgen]
+pub fn project_graph(attestations_json: &str, config_json: &str) -> String {
+    qntx_core::project_graph_json(attestations_json, config_json)
+}
+
+/// Like `project_graph`, but returns the force layout with index-based links:
+/// `{"nodes":[{"id","label"}],"links":[{"source":0,"target":1,"weight","label"}]}`.
+#[wasm_bindgen]
+pub fn project_force_graph(attestations_json: &str, config_json: &str) -> String {
+    qntx_core::project_force_graph_json(attestations_json, config_json)
+}
+
+// ============================================================================
+// Pseudonymization
+// ============================================================================
+
+/// Pseudonymize a JSON array of attestations for sharing, e.g. before a
+/// browser-side export. `config_json` is `{"key": "...", ...}` with the
+/// secret key and any `PseudonymizeConfig` overrides; the same key maps the
+/// same values to the same tokens.
+///
+/// Returns `{"attestations":[...],"total":N}` or `{"error":"..."}`.
+#[wasm_bindgen]
+pub fn pseudonymize_attestations(attestations_json: &str, config_json: &str) -> String {
+    qntx_core::pseudonymize_attestations_json(attestations_json, config_json)
+}
+
+// ============================================================================
+// Attestation diff / patch
+// ============================================================================
+
+/// Field-level diff taking attestation `a_json` to `b_json`: per-slot
+/// added/removed values, attribute changes and timestamp/source changes.
+///
+/// Returns the diff JSON or `{"error":"..."}`.
+#[wasm_bindgen]
+pub fn diff_attestations(a_json: &str, b_json: &str) -> String {
+    qntx_core::diff_attestations_json(a_json, b_json)
+}
+
+/// Apply a diff from `diff_attestations` (possibly trimmed to the accepted
+/// changes) to `base_json`.
+///
+/// Returns the patched attestation, or
+/// `{"error":"...","conflict":{"mismatches":[{"field","expected","actual"}]}}`
+/// when the base no longer holds the values the diff expects.
+#[wasm_bindgen]
+pub fn apply_attestation_patch(base_json: &str, diff_json: &str) -> String {
+    qntx_core::apply_attestation_patch_json(base_json, diff_json)
+}
+
+// ============================================================================
+// Type Registry
+// ============================================================================
+
+/// Build the type registry from a JSON array of attestations; anything that
+/// isn't a `type` definition is ignored.
+///
+/// Returns `{"types":[...],"warnings":[...]}` or `{"error":"..."}`.
+#[wasm_bindgen]
+pub fn type_registry_from(attestations_json: &str) -> String {
+    qntx_core::type_registry_json(attestations_json)
+}
+
+/// Build the type registry from the type definitions in IndexedDB, for the
+/// types panel. Returns `{"types":[...],"warnings":[...]}`.
+#[cfg(feature = "storage")]
+#[wasm_bindgen]
+pub async fn type_registry() -> Result<String, JsValue> {
+    use qntx_core::attestation::AxFilter;
+    use qntx_core::TypeRegistry;
+
+    let filter = AxFilter {
+        predicates: vec![qntx_core::type_registry::TYPE_PREDICATE.to_string()],
+        ..Default::default()
+    };
+    let result = get_store().query(&filter).await.map_err(store_error)?;
+    let registry = TypeRegistry::from_attestations(&result.attestations);
+    serde_json::to_string(&serde_json::json!({
+        "types": registry.types().collect::<Vec<_>>(),
+        "warnings": registry.warnings(),
+    }))
+    .map_err(|e| JsValue::from_str(&format!("Serialization error: {}", e)))
+}
+
+// ============================================================================
+// Templates (feature = "storage")
+// ============================================================================
+
+/// Read the template definitions in IndexedDB.
+///
+/// Returns `{"templates":[...],"errors":[{"attestation_id","message"}]}`,
+/// newest definition per name, sorted by name.
+#[cfg(feature = "storage")]
+#[wasm_bindgen]
+pub async fn list_templates() -> Result<String, JsValue> {
+    let library = load_templates().await?;
+    serde_json::to_string(&serde_json::json!({
+        "templates": library.templates(),
+        "errors": library.errors(),
+    }))
+    .map_err(|e| JsValue::from_str(&format!("Serialization error: {}", e)))
+}
+
+/// Instantiate the stored template `name` with `params_json` (an object of
+/// string values), assigning a fresh ASUID and the current time.
+///
+/// Resolves to the attestation in proto schema, ready for `put_attestation`;
+/// with `put` it is stored as well. Template problems reject with
+/// `{"code":"template","message":"...","errors":[{"kind":...}]}`.
+#[cfg(feature = "storage")]
+#[wasm_bindgen]
+pub async fn instantiate_template(
+    name: &str,
+    params_json: &str,
+    put: bool,
+) -> Result<String, JsValue> {
+    let params: std::collections::HashMap<String, String> = serde_json::from_str(params_json)
+        .map_err(|e| store_error(StoreError::InvalidData(format!("Invalid params: {}", e))))?;
+    let library = load_templates().await?;
+    let template = library
+        .get(name)
+        .ok_or_else(|| store_error(StoreError::NotFound(format!("template {}", name))))?;
+
+    let mut attestation = template.instantiate(&params).map_err(|errors| {
+        let message = errors
+            .iter()
+            .map(|e| e.to_string())
+            .collect::<Vec<_>>()
+            .join("; ");
+        JsValue::from_str(
+            &serde_json::json!({"code": "template", "message": message, "errors": errors})
+                .to_string(),
+        )
+    })?;
+    let random_bytes: Vec<u8> = (0..8)
+        .map(|_| (js_sys::Math::random() * 256.0) as u8)
+        .collect();
+    attestation.id = qntx_id::Asuid::new(
+        "AS",
+        &attestation.subjects[0],
+        &attestation.predicates[0],
+        &attestation.contexts[0],
+        &random_bytes,
+    )
+    .map(|id| id.full())
+    .ok_or_else(|| JsValue::from_str("ASUID generation failed"))?;
+    attestation.timestamp = now_ms();
+
+    let json = serde_json::to_string(&qntx_proto::proto_convert::to_proto(attestation))
+        .map_err(|e| JsValue::from_str(&format!("Serialization error: {}", e)))?;
+    if put {
+        put_attestation(&json).await?;
+    }
+    Ok(json)
+}
+
+#[cfg(feature = "storage")]
+async fn load_templates() -> Result<qntx_core::TemplateLibrary, JsValue> {
+    use qntx_core::attestation::AxFilter;
+    use qntx_core::template::{TEMPLATE_CONTEXT, TEMPLATE_PREDICATE};
+
+    let filter = AxFilter {
+        predicates: vec![TEMPLATE_PREDICATE.to_string()],
+        contexts: vec![TEMPLATE_CONTEXT.to_string()],
+        ..Default::default()
+    };
+    let result = get_store().query(&filter).await.map_err(store_error)?;
+    Ok(qntx_core::TemplateLibrary::from_attestations(
+        &result.attestations,
+    ))
 }
 
 // ============================================================================
-// Cosine Similarity
+// Cosine Similarity (feature = "similarity")
 // ============================================================================
 
 /// Compute cosine similarity between two f32 vectors.
 /// Uses typed arrays directly from JavaScript (no JSON overhead).
 /// Throws JS exception if vectors have different dimensions.
+#[cfg(feature = "similarity")]
 #[wasm_bindgen]
 pub fn cosine_similarity_f32(query: &[f32], candidate: &[f32]) -> Result<f32, JsValue> {
     qntx_core::similarity::cosine_similarity(query, candidate).map_err(|e| JsValue::from_str(&e))
 }
 
+/// Rank a packed batch of candidate vectors against a query in one call.
+///
+/// `candidates` is `n * query.len()` floats laid out row by row. Returns UTF-8
+/// JSON bytes `[{"index":3,"similarity":0.91},...]`, sorted by similarity
+/// descending, keeping at most `limit` entries at or above `threshold`.
+/// Throws if `candidates` is not a whole number of rows.
+#[cfg(feature = "similarity")]
+#[wasm_bindgen]
+pub fn similarity_search_bytes(
+    query: &[f32],
+    candidates: &[f32],
+    limit: usize,
+    threshold: f32,
+) -> Result<Vec<u8>, JsValue> {
+    let dim = query.len();
+    if dim == 0 || !candidates.len().is_multiple_of(dim) {
+        return Err(JsValue::from_str(&format!(
+            "candidates length {} is not a multiple of query dimension {}",
+            candidates.len(),
+            dim
+        )));
+    }
+
+    let mut hits: Vec<(usize, f32)> = Vec::new();
+    for (index, row) in candidates.chunks_exact(dim).enumerate() {
+        let similarity = qntx_core::similarity::cosine_similarity(query, row)
+            .map_err(|e| JsValue::from_str(&e))?;
+        if similarity >= threshold {
+            hits.push((index, similarity));
+        }
+    }
+    hits.sort_by(|a, b| b.1.total_cmp(&a.1));
+    hits.truncate(limit);
+
+    let json: Vec<serde_json::Value> = hits
+        .into_iter()
+        .map(|(index, similarity)| serde_json::json!({"index": index, "similarity": similarity}))
+        .collect();
+    serde_json::to_vec(&json).map_err(|e| JsValue::from_str(&format!("Serialization error: {}", e)))
+}
+
+/// Diverse top-`k` of a packed batch of candidate vectors (maximal marginal
+/// relevance, see `qntx_core::similarity::mmr_rerank`).
+///
+/// `candidates` is laid out as for `similarity_search_bytes`; pass a pool a
+/// few times larger than `k`. `lambda` trades relevance (1.0) against
+/// diversity (0.0). Returns UTF-8 JSON bytes
+/// `{"picks":[{"id":3,"relevance":0.91,"marginal":0.42},...],"skipped":[7]}`
+/// in pick order; `skipped` lists all-zero rows. Throws on a ragged batch.
+#[cfg(feature = "similarity")]
+#[wasm_bindgen]
+pub fn similarity_search_diverse(
+    query: &[f32],
+    candidates: &[f32],
+    k: usize,
+    lambda: f32,
+) -> Result<Vec<u8>, JsValue> {
+    let result = qntx_core::similarity::mmr_search(query, candidates, lambda, k)
+        .map_err(|e| JsValue::from_str(&e))?;
+    serde_json::to_vec(&result)
+        .map_err(|e| JsValue::from_str(&format!("Serialization error: {}", e)))
+}
+
+// ============================================================================
+// Self-benchmark (feature = "bench")
+// ============================================================================
+
+/// `performance.now()` from the window or worker global, falling back to
+/// `Date.now()` where there is no `performance`.
+#[cfg(feature = "bench")]
+fn performance_now() -> f64 {
+    js_sys::Reflect::get(&js_sys::global(), &JsValue::from_str("performance"))
+        .ok()
+        .and_then(|perf| {
+            let now: js_sys::Function = js_sys::Reflect::get(&perf, &JsValue::from_str("now"))
+                .ok()?
+                .dyn_into()
+                .ok()?;
+            now.call0(&perf).ok()?.as_f64()
+        })
+        .unwrap_or_else(js_sys::Date::now)
+}
+
+/// Run the seeded self-benchmark workloads (about 1s by default).
+/// `config_json` is a `BenchConfig` (empty string or `null` for defaults).
+///
+/// Returns `BenchReport` JSON or `{"error":"..."}`.
+#[cfg(feature = "bench")]
+#[wasm_bindgen]
+pub fn run_benchmarks(config_json: &str) -> String {
+    qntx_core::run_benchmarks_json(config_json, performance_now)
+}
+
 // ============================================================================
 // Identity (qntx-id)
 // ============================================================================
@@ -271,6 +1379,386 @@ pub fn id_normalize_for_lookup(input: &str) -> String {
     qntx_id::normalize_for_lookup(input)
 }
 
+// ============================================================================
+// Slow operations
+// ============================================================================
+
+/// Time slow operations with `Date.now` and log them to `console.warn`.
+fn install_slow_op_hooks() {
+    slow_ops::set_slow_op_clock(Some(js_sys::Date::now));
+    slow_ops::set_slow_op_hook(Some(warn_slow_op));
+}
+
+fn warn_slow_op(op: &SlowOp) {
+    let detail = serde_json::to_string(&op.detail).unwrap_or_default();
+    web_sys::console::warn_1(&JsValue::from_str(&format!(
+        "[qntx-wasm] slow {}: {:.0}ms {}",
+        op.category, op.duration_ms, detail
+    )));
+}
+
+/// Set slow-operation thresholds in milliseconds (0 disables a category).
+/// Omitted fields keep their current value:
+/// `{"store_query_ms":200,"store_write_ms":200,"classify_ms":100,"hash_ms":0,"ring_capacity":100}`.
+/// The `slow_ops` section of `load_core_config` sets the same values.
+/// Returns `{"ok":true}` or `{"error":"..."}`.
+#[wasm_bindgen]
+pub fn set_slow_op_thresholds(json: &str) -> String {
+    install_slow_op_hooks();
+    slow_ops::set_slow_op_thresholds_json(json)
+}
+
+/// Recent operations that exceeded their threshold, oldest first:
+/// `[{"category":"store_query","duration_ms":312,"detail":{"input_len":48,"result_count":900},"at":1700000000000}]`.
+#[wasm_bindgen]
+pub fn slow_ops_report() -> String {
+    slow_ops::slow_ops_report_json()
+}
+
+// ============================================================================
+// Batched calls
+// ============================================================================
+
+/// Run several calls in one boundary crossing, in order, awaiting each inside
+/// WASM so the caller makes a single `await`.
+///
+/// `requests_json` is `[{"id":"...","op":"...","params":{...}}, ...]`
+/// (`params` optional). Resolves to `[{"id":"...","result":...}` or
+/// `{"id":"...","error":...}, ...]` in request order; a failing or unknown op
+/// only fails its own entry. `error` is the op's `{code, message, hint}` object
+/// for storage errors, a string otherwise. Rejects only if `requests_json` is
+/// not a JSON array.
+///
+/// Allowed ops and their params:
+/// - `init_store` `{db_name?}`, `init_worker` `{db_name?, open_store?}` and
+///   `load_core_config` `{config}`: change this instance's state, so later
+///   entries see their effect. Order them first.
+/// - `version`, `is_store_initialized`, `parse_query` `{query}`,
+///   `parse_duration` `{input}`
+/// - Store reads: `get_attestation` `{id}`, `exists_attestation` `{id}`,
+///   `query_attestations` `{filter}`, `list_attestation_ids`, `outbox_status`,
+///   `type_registry`, `lint_ax_query` `{query}`
+///
+/// Results are what the matching export returns, parsed from JSON where it
+/// returns JSON; exports reporting `{"error":"..."}` become entry errors.
+#[wasm_bindgen]
+pub async fn execute_batch(requests_json: &str) -> Result<String, JsValue> {
+    let requests: Vec<serde_json::Value> = serde_json::from_str(requests_json)
+        .map_err(|e| JsValue::from_str(&format!("Invalid batch JSON: {}", e)))?;
+    Ok(serde_json::Value::Array(run_batch(&requests).await).to_string())
+}
+
+/// Everything the UI needs at startup, in one call: opens the store if this
+/// instance hasn't yet (like `init_worker`), then reads the version, outbox
+/// status and type registry, plus the result of `initial_filter_json` (an
+/// AxFilter) when given.
+///
+/// Resolves to `{"init":{...},"version":{...},"outbox_status":{...},
+/// "type_registry":{...},"query":{...}}`, each `{"result":...}` or
+/// `{"error":...}` as in `execute_batch`.
+#[cfg(feature = "storage")]
+#[wasm_bindgen]
+pub async fn startup_bundle(
+    db_name: Option<String>,
+    initial_filter_json: Option<String>,
+) -> Result<String, JsValue> {
+    let mut requests = vec![
+        serde_json::json!({"id": "init", "op": "init_worker", "params": {"db_name": db_name}}),
+        serde_json::json!({"id": "version", "op": "version"}),
+        serde_json::json!({"id": "outbox_status", "op": "outbox_status"}),
+        serde_json::json!({"id": "type_registry", "op": "type_registry"}),
+    ];
+    if let Some(filter) = initial_filter_json {
+        let filter: serde_json::Value = serde_json::from_str(&filter)
+            .map_err(|e| store_error(StoreError::Query(format!("Invalid filter JSON: {}", e))))?;
+        requests.push(serde_json::json!({
+            "id": "query",
+            "op": "query_attestations",
+            "params": {"filter": filter},
+        }));
+    }
+
+    let bundle: serde_json::Map<String, serde_json::Value> = run_batch(&requests)
+        .await
+        .into_iter()
+        .filter_map(|mut entry| {
+            let id = entry.as_object_mut()?.remove("id")?;
+            Some((id.as_str()?.to_string(), entry))
+        })
+        .collect();
+    Ok(serde_json::Value::Object(bundle).to_string())
+}
+
+/// Run `requests` in order, one result entry per request.
+async fn run_batch(requests: &[serde_json::Value]) -> Vec<serde_json::Value> {
+    let mut results = Vec::with_capacity(requests.len());
+    for request in requests {
+        let id = request
+            .get("id")
+            .cloned()
+            .unwrap_or(serde_json::Value::Null);
+        let outcome = match request.get("op").and_then(|op| op.as_str()) {
+            Some(op) => {
+                let params = request.get("params").unwrap_or(&serde_json::Value::Null);
+                batch_op(op, params).await
+            }
+            None => Err("missing op".into()),
+        };
+        results.push(match outcome {
+            Ok(result) => serde_json::json!({"id": id, "result": result}),
+            Err(error) => serde_json::json!({"id": id, "error": error}),
+        });
+    }
+    results
+}
+
+/// The op table of `execute_batch`.
+async fn batch_op(
+    op: &str,
+    params: &serde_json::Value,
+) -> Result<serde_json::Value, serde_json::Value> {
+    use serde_json::Value;
+
+    let str_param = |name: &str| {
+        params
+            .get(name)
+            .and_then(Value::as_str)
+            .ok_or_else(|| Value::from(format!("{}: missing string param {}", op, name)))
+    };
+    let json_param = |name: &str| {
+        params
+            .get(name)
+            .map(Value::to_string)
+            .ok_or_else(|| Value::from(format!("{}: missing param {}", op, name)))
+    };
+    let db_name = || {
+        params
+            .get("db_name")
+            .and_then(Value::as_str)
+            .map(str::to_string)
+    };
+    #[cfg(feature = "storage")]
+    let needs_store = || {
+        if is_store_initialized() {
+            Ok(())
+        } else {
+            Err(Value::from(format!(
+                "{}: store not initialized, run init_store first",
+                op
+            )))
+        }
+    };
+
+    match op {
+        #[cfg(feature = "storage")]
+        "init_store" => init_store(db_name())
+            .await
+            .map(|()| Value::Null)
+            .map_err(js_error),
+        "init_worker" => {
+            let open_store = params
+                .get("open_store")
+                .and_then(Value::as_bool)
+                .unwrap_or(true);
+            init_worker(db_name(), open_store)
+                .await
+                .map(|()| Value::Null)
+                .map_err(js_error)
+        }
+        "load_core_config" => json_result(&load_core_config(&json_param("config")?)),
+        "version" => Ok(version().into()),
+        "is_store_initialized" => Ok(is_store_initialized().into()),
+        "parse_query" => json_result(&parse_query(str_param("query")?)),
+        "parse_duration" => json_result(&parse_duration(str_param("input")?)),
+        #[cfg(feature = "storage")]
+        "get_attestation" => {
+            needs_store()?;
+            match get_attestation(str_param("id")?).await.map_err(js_error)? {
+                Some(json) => json_result(&json),
+                None => Ok(Value::Null),
+            }
+        }
+        #[cfg(feature = "storage")]
+        "exists_attestation" => {
+            needs_store()?;
+            exists_attestation(str_param("id")?)
+                .await
+                .map(Value::from)
+                .map_err(js_error)
+        }
+        #[cfg(feature = "storage")]
+        "query_attestations" => {
+            needs_store()?;
+            json_result(
+                &query_attestations(&json_param("filter")?)
+                    .await
+                    .map_err(js_error)?,
+            )
+        }
+        #[cfg(feature = "storage")]
+        "list_attestation_ids" => {
+            needs_store()?;
+            json_result(&list_attestation_ids().await.map_err(js_error)?)
+        }
+        #[cfg(feature = "storage")]
+        "outbox_status" => {
+            needs_store()?;
+            json_result(&outbox_status().await.map_err(js_error)?)
+        }
+        #[cfg(feature = "storage")]
+        "type_registry" => {
+            needs_store()?;
+            json_result(&type_registry().await.map_err(js_error)?)
+        }
+        #[cfg(feature = "storage")]
+        "lint_ax_query" => {
+            needs_store()?;
+            json_result(&lint_ax_query(str_param("query")?).await.map_err(js_error)?)
+        }
+        _ => Err(format!("unknown op: {}", op).into()),
+    }
+}
+
+/// Parse an export's JSON output, turning `{"error":...}` into an entry error.
+fn json_result(json: &str) -> Result<serde_json::Value, serde_json::Value> {
+    let mut value: serde_json::Value =
+        serde_json::from_str(json).map_err(|e| format!("invalid result JSON: {}", e))?;
+    match value.as_object_mut().and_then(|o| o.remove("error")) {
+        Some(error) => Err(error),
+        None => Ok(value),
+    }
+}
+
+/// Entry error for a rejected export: the structured `{code, message, hint}`
+/// of storage errors, or the plain message.
+fn js_error(e: JsValue) -> serde_json::Value {
+    let message = e.as_string().unwrap_or_else(|| format!("{:?}", e));
+    serde_json::from_str(&message).unwrap_or(serde_json::Value::String(message))
+}
+
+// ============================================================================
+// Capabilities
+// ============================================================================
+
+/// Exports of each export group, in source order. A group is compiled in when
+/// its features are on; `browser-core` always is.
+const EXPORT_GROUPS: &[(&str, bool, &[&str])] = &[
+    (
+        "browser-core",
+        true,
+        &[
+            "init_worker",
+            "parse_query",
+            "parse_query_alternates",
+            "parse_duration",
+            "query_builder_new",
+            "query_builder_set",
+            "query_builder_preview",
+            "query_builder_free",
+            "load_core_config",
+            "project_graph",
+            "project_force_graph",
+            "pseudonymize_attestations",
+            "diff_attestations",
+            "apply_attestation_patch",
+            "type_registry_from",
+            "generate_asuid",
+            "generate_random_id",
+            "id_clean_seed",
+            "id_normalize_for_lookup",
+            "set_slow_op_thresholds",
+            "slow_ops_report",
+            "execute_batch",
+            "capabilities",
+            "version",
+            "is_store_initialized",
+        ],
+    ),
+    (
+        "storage",
+        cfg!(feature = "storage"),
+        &[
+            "init_store",
+            "lint_ax_query",
+            "put_attestation",
+            "put_attestation_if_revision",
+            "get_attestation",
+            "delete_attestation",
+            "delete_attestation_cascade",
+            "exists_attestation",
+            "query_attestations",
+            "query_attestations_bytes",
+            "query_attestations_page",
+            "list_attestation_ids",
+            "export_attestations",
+            "import_attestations",
+            "enable_outbox",
+            "set_outbox_policy",
+            "drain_outbox",
+            "outbox_status",
+            "reset_outbox_backoff",
+            "clear_outbox",
+            "changes_since",
+            "prune_changes",
+            "type_registry",
+            "list_templates",
+            "instantiate_template",
+            "startup_bundle",
+        ],
+    ),
+    (
+        "classify",
+        cfg!(feature = "classify"),
+        &[
+            "classify_claims",
+            "classify_claims_bytes",
+            "classify_default_config",
+            "set_actor_aliases",
+            "expand_cartesian_claims",
+            "group_claims",
+            "dedup_source_ids",
+        ],
+    ),
+    (
+        "storage+classify",
+        cfg!(all(feature = "storage", feature = "classify")),
+        &["load_actor_aliases", "claims_supported_by"],
+    ),
+    (
+        "similarity",
+        cfg!(feature = "similarity"),
+        &[
+            "cosine_similarity_f32",
+            "similarity_search_bytes",
+            "similarity_search_diverse",
+        ],
+    ),
+    ("bench", cfg!(feature = "bench"), &["run_benchmarks"]),
+];
+
+/// Report which export groups this build contains, so callers can
+/// feature-detect instead of calling a missing export.
+///
+/// Returns `{"storage":true,"classify":true,"similarity":true,"bench":false,"exports":[...]}`
+/// with `exports` sorted.
+#[wasm_bindgen]
+pub fn capabilities() -> String {
+    let mut exports: Vec<&str> = EXPORT_GROUPS
+        .iter()
+        .filter(|(_, enabled, _)| *enabled)
+        .flat_map(|(_, _, names)| names.iter().copied())
+        .collect();
+    exports.sort_unstable();
+    serde_json::json!({
+        "storage": cfg!(feature = "storage"),
+        "classify": cfg!(feature = "classify"),
+        "similarity": cfg!(feature = "similarity"),
+        "bench": cfg!(feature = "bench"),
+        "exports": exports,
+    })
+    .to_string()
+}
+
 // ============================================================================
 // Utilities
 // ============================================================================
@@ -284,5 +1772,344 @@ pub fn version() -> String {
 /// Check if the store is initialized.
 #[wasm_bindgen]
 pub fn is_store_initialized() -> bool {
-    STORE.with(|s| s.borrow().is_some())
+    #[cfg(feature = "storage")]
+    return STORE.with(|s| s.borrow().is_some());
+
+    #[cfg(not(feature = "storage"))]
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The browser exports must produce exactly what the wazero exports produce
+    /// for the shared fixture (checked against the same expected file there).
+    #[cfg(feature = "classify")]
+    #[test]
+    fn claim_pipeline_matches_shared_fixture() {
+        let input = include_str!("../tests/fixtures/claim_pipeline.json");
+        let expected: serde_json::Value = serde_json::from_str(include_str!(
+            "../tests/fixtures/claim_pipeline.expected.json"
+        ))
+        .unwrap();
+
+        let expanded: serde_json::Value =
+            serde_json::from_str(&expand_cartesian_claims(input)).unwrap();
+        assert_eq!(expanded, expected["expand"]);
+
+        let claims = serde_json::json!({"claims": expanded["claims"]}).to_string();
+        let grouped: serde_json::Value = serde_json::from_str(&group_claims(&claims)).unwrap();
+        assert_eq!(grouped, expected["group"]);
+
+        let deduped: serde_json::Value = serde_json::from_str(&dedup_source_ids(&claims)).unwrap();
+        assert_eq!(deduped, expected["dedup"]);
+    }
+
+    #[cfg(feature = "classify")]
+    #[test]
+    fn classify_claims_bytes_matches_string_export() {
+        let input = serde_json::json!({
+            "claim_groups": [{
+                "key": "ALICE|role|GitHub",
+                "claims": [
+                    {"subject": "ALICE", "predicate": "is_junior", "context": "GitHub", "actor": "human:alice", "timestamp_ms": 1_000, "source_id": "as-1"},
+                    {"subject": "ALICE", "predicate": "is_senior", "context": "GitHub", "actor": "human:alice", "timestamp_ms": 90_000, "source_id": "as-2"}
+                ]
+            }],
+            "now_ms": 100_000
+        })
+        .to_string();
+        let bytes = classify_claims_bytes(&input);
+        assert_eq!(bytes, classify_claims(&input).into_bytes());
+        let parsed: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(parsed["total_analyzed"], 1);
+
+        // Errors travel in the JSON, as for the string export
+        let parsed: serde_json::Value =
+            serde_json::from_slice(&classify_claims_bytes("not json")).unwrap();
+        assert!(parsed["error"].is_string());
+    }
+
+    #[cfg(feature = "similarity")]
+    #[test]
+    fn similarity_search_bytes_ranks_filters_and_limits() {
+        let query = [1.0, 0.0];
+        // Rows: orthogonal, identical, 45 degrees, opposite
+        let candidates = [0.0, 1.0, 2.0, 0.0, 1.0, 1.0, -1.0, 0.0];
+
+        let hits: serde_json::Value =
+            serde_json::from_slice(&similarity_search_bytes(&query, &candidates, 10, 0.5).unwrap())
+                .unwrap();
+        let indexes: Vec<u64> = hits
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|h| h["index"].as_u64().unwrap())
+            .collect();
+        assert_eq!(indexes, [1, 2]);
+        assert!((hits[0]["similarity"].as_f64().unwrap() - 1.0).abs() < 1e-6);
+
+        let hits: serde_json::Value =
+            serde_json::from_slice(&similarity_search_bytes(&query, &candidates, 1, -1.0).unwrap())
+                .unwrap();
+        assert_eq!(hits, serde_json::json!([{"index": 1, "similarity": 1.0}]));
+    }
+
+    /// Drive a future that never waits on JS to completion.
+    fn ready<F: std::future::Future>(future: F) -> F::Output {
+        let mut cx = std::task::Context::from_waker(std::task::Waker::noop());
+        match std::pin::pin!(future).poll(&mut cx) {
+            std::task::Poll::Ready(output) => output,
+            std::task::Poll::Pending => panic!("future waited on JS"),
+        }
+    }
+
+    #[test]
+    fn batch_isolates_per_entry_errors() {
+        let requests = serde_json::json!([
+            {"id": "v", "op": "version"},
+            {"id": "bogus", "op": "fuzzy_status"},
+            {"id": "q", "op": "parse_query", "params": {"query": "ALICE is author"}},
+            {"id": "no-param", "op": "parse_query"},
+            {"id": "bad-query", "op": "parse_query", "params": {"query": "ALICE over 5"}},
+            {"id": "no-store", "op": "get_attestation", "params": {"id": "AS-1"}},
+            {"id": "no-op"},
+            {"id": "d", "op": "parse_duration", "params": {"input": "18m"}},
+        ]);
+        let requests: Vec<serde_json::Value> = serde_json::from_value(requests).unwrap();
+        let results = ready(run_batch(&requests));
+
+        let ids: Vec<&str> = results.iter().map(|r| r["id"].as_str().unwrap()).collect();
+        assert_eq!(
+            ids,
+            [
+                "v",
+                "bogus",
+                "q",
+                "no-param",
+                "bad-query",
+                "no-store",
+                "no-op",
+                "d"
+            ]
+        );
+        let failed: Vec<&str> = results
+            .iter()
+            .filter(|r| r.get("error").is_some())
+            .map(|r| r["id"].as_str().unwrap())
+            .collect();
+        assert_eq!(
+            failed,
+            ["bogus", "no-param", "bad-query", "no-store", "no-op"]
+        );
+        assert_eq!(results[1]["error"], "unknown op: fuzzy_status");
+
+        // Successful entries carry exactly what the individual exports return
+        let parsed = |json: String| serde_json::from_str::<serde_json::Value>(&json).unwrap();
+        assert_eq!(results[0]["result"], version());
+        assert_eq!(results[2]["result"], parsed(parse_query("ALICE is author")));
+        assert_eq!(
+            results[4]["error"],
+            parsed(parse_query("ALICE over 5"))["error"]
+        );
+        assert_eq!(results[7]["result"], parsed(parse_duration("18m")));
+    }
+
+    /// `#[wasm_bindgen]` exports in this file with the cfg gating each one.
+    fn scanned_exports() -> Vec<(String, String)> {
+        let source = include_str!("browser.rs");
+        let lines: Vec<&str> = source.lines().collect();
+        let mut exports = Vec::new();
+        for (i, line) in lines.iter().enumerate() {
+            if *line != "#[wasm_bindgen]" {
+                continue;
+            }
+            let name = lines[i + 1]
+                .trim_start_matches("pub ")
+                .trim_start_matches("async ")
+                .trim_start_matches("fn ")
+                .split('(')
+                .next()
+                .unwrap()
+                .to_string();
+            let gate = lines[i - 1]
+                .strip_prefix("#[cfg(")
+                .map(|cfg| cfg.trim_end_matches(")]").to_string())
+                .unwrap_or_default();
+            exports.push((name, gate));
+        }
+        exports
+    }
+
+    #[test]
+    fn query_builder_handle_round_trips_keyword_values() {
+        let id = query_builder_new();
+        for op in [
+            r#"{"op":"add","slot":"subjects","value":"ALICE"}"#,
+            r#"{"op":"add","slot":"subjects","value":"is"}"#,
+            r#"{"op":"set","slot":"predicates","values":["is","author_of"]}"#,
+            r#"{"op":"add","slot":"contexts","value":"from"}"#,
+            r#"{"op":"add","slot":"actors","value":"human:bob"}"#,
+            r#"{"op":"set_temporal","temporal":{"Since":"2024-01-01"}}"#,
+            r#"{"op":"add_condition","condition":{"key":"severity","op":"gte","value":3}}"#,
+            r#"{"op":"add","slot":"actions","value":"notify"}"#,
+        ] {
+            let result: serde_json::Value =
+                serde_json::from_str(&query_builder_set(id, op)).unwrap();
+            assert!(result.get("error").is_none(), "{}: {}", op, result);
+        }
+        let rejected: serde_json::Value = serde_json::from_str(&query_builder_set(
+            id,
+            r#"{"op":"add","slot":"actors","value":""}"#,
+        ))
+        .unwrap();
+        assert!(rejected["error"].is_string());
+
+        let preview: serde_json::Value = serde_json::from_str(&query_builder_preview(id)).unwrap();
+        let text = preview["query_string"].as_str().unwrap();
+        assert_eq!(
+            text,
+            "ALICE 'is' is 'is' author_of of 'from' by human:bob since 2024-01-01 \
+             where severity >= 3 so notify"
+        );
+        let reparsed: serde_json::Value = serde_json::from_str(&parse_query(text)).unwrap();
+        assert_eq!(reparsed, preview["query"]);
+        assert_eq!(preview["query"]["actors"], serde_json::json!(["human:bob"]));
+        let flagged: Vec<&str> = preview["warnings"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|w| w["value"].as_str().unwrap())
+            .collect();
+        assert_eq!(flagged, ["is", "is", "from"]);
+
+        assert!(query_builder_free(id));
+        assert!(!query_builder_free(id));
+        let gone: serde_json::Value = serde_json::from_str(&query_builder_preview(id)).unwrap();
+        assert!(gone["error"]
+            .as_str()
+            .unwrap()
+            .contains("unknown query builder"));
+    }
+
+    #[test]
+    fn parse_query_reports_or_groups() {
+        let parsed: serde_json::Value =
+            serde_json::from_str(&parse_query("ALICE or BOB is author of GitHub or 'or'")).unwrap();
+        assert_eq!(parsed["subjects"], serde_json::json!(["ALICE", "BOB"]));
+        assert_eq!(
+            parsed["subject_groups"],
+            serde_json::json!([["ALICE", "BOB"]])
+        );
+        assert_eq!(
+            parsed["context_groups"],
+            serde_json::json!([["GitHub", "or"]])
+        );
+
+        let dangling: serde_json::Value =
+            serde_json::from_str(&parse_query("ALICE or is author")).unwrap();
+        assert!(dangling["error"].as_str().unwrap().contains("after 'or'"));
+    }
+
+    #[cfg(feature = "storage")]
+    #[test]
+    fn import_records_collects_malformed_entries() {
+        let (records, failures) = import_records(
+            r#"[
+                {"id":"AS-1","subjects":["ALICE"],"predicates":["knows"],"timestamp":1000},
+                {"id":"AS-2","subjects":"ALICE"},
+                {"subjects":["BOB"]},
+                42,
+                {"id":"AS-3","actors":["human:bob"],"revision":4}
+            ]"#,
+        )
+        .unwrap();
+
+        let ids: Vec<(usize, &str)> = records.iter().map(|(i, a)| (*i, a.id.as_str())).collect();
+        assert_eq!(ids, [(0, "AS-1"), (4, "AS-3")]);
+        assert_eq!(records[0].1.subjects, ["ALICE"]);
+        assert_eq!(records[1].1.revision, 4);
+
+        let indexes: Vec<usize> = failures.iter().map(|f| f.index).collect();
+        assert_eq!(indexes, [1, 2, 3]);
+        assert_eq!(failures[1].error, "attestation has no id");
+        assert!(failures[2].error.starts_with("invalid attestation"));
+
+        let err = import_records(r#"{"id":"AS-1"}"#).unwrap_err();
+        assert!(err.to_string().contains("expected a JSON array"));
+        assert!(import_records("[]").unwrap().0.is_empty());
+    }
+
+    #[test]
+    fn parse_query_reports_exclusions() {
+        let parsed: serde_json::Value =
+            serde_json::from_str(&parse_query("ALICE is author of GitHub not by system:ci"))
+                .unwrap();
+        assert_eq!(parsed["actors"], serde_json::json!([]));
+        assert_eq!(parsed["excluded_actors"], serde_json::json!(["system:ci"]));
+        assert_eq!(parsed["excluded_subjects"], serde_json::json!([]));
+        assert_eq!(parsed["excluded_contexts"], serde_json::json!([]));
+
+        let dangling: serde_json::Value =
+            serde_json::from_str(&parse_query("ALICE is author not")).unwrap();
+        assert!(dangling["error"].as_str().unwrap().contains("after 'not'"));
+    }
+
+    #[test]
+    fn export_groups_match_source() {
+        let gate_of_group = |group: &str| match group {
+            "browser-core" => String::new(),
+            "storage+classify" => r#"all(feature = "storage", feature = "classify")"#.into(),
+            other => format!(r#"feature = "{}""#, other),
+        };
+        let mut listed: Vec<(String, String)> = EXPORT_GROUPS
+            .iter()
+            .flat_map(|(group, _, names)| {
+                names.iter().map(|n| (n.to_string(), gate_of_group(group)))
+            })
+            .collect();
+        let mut scanned = scanned_exports();
+        listed.sort();
+        scanned.sort();
+        assert_eq!(listed, scanned);
+    }
+
+    /// The full browser build keeps the export surface it had before the
+    /// export groups were split out (plus `capabilities`).
+    #[cfg(feature = "browser")]
+    #[test]
+    fn full_build_reports_every_group() {
+        let caps: serde_json::Value = serde_json::from_str(&capabilities()).unwrap();
+        assert_eq!(caps["storage"], true);
+        assert_eq!(caps["classify"], true);
+        assert_eq!(caps["similarity"], true);
+
+        let mut expected: Vec<String> = scanned_exports()
+            .into_iter()
+            .filter(|(_, gate)| gate != r#"feature = "bench""#)
+            .map(|(name, _)| name)
+            .collect();
+        if cfg!(feature = "bench") {
+            expected.push("run_benchmarks".into());
+        }
+        expected.sort();
+        assert_eq!(caps["exports"], serde_json::json!(expected));
+        assert_eq!(expected.len(), 63 + usize::from(cfg!(feature = "bench")));
+    }
+
+    #[cfg(not(any(feature = "storage", feature = "classify", feature = "similarity")))]
+    #[test]
+    fn minimal_build_reports_core_only() {
+        let caps: serde_json::Value = serde_json::from_str(&capabilities()).unwrap();
+        assert_eq!(caps["storage"], false);
+        assert_eq!(caps["classify"], false);
+        assert_eq!(caps["similarity"], false);
+        assert!(!is_store_initialized());
+
+        let exports = caps["exports"].as_array().unwrap();
+        assert_eq!(exports.len(), EXPORT_GROUPS[0].2.len());
+        assert!(exports.contains(&"parse_query".into()));
+        assert!(!exports.contains(&"put_attestation".into()));
+    }
 }
@@ -0,0 +1,100 @@
+//! Module-wide qntx-core config for both WASM targets.
+//!
+//! `load_core_config` stores a validated [`QntxCoreConfig`] in a thread-local
+//! (each WASM instance is single-threaded). Functions whose per-call config is
+//! absent or partial fall back to it instead of the built-in defaults.
+
+use std::cell::RefCell;
+
+use qntx_core::QntxCoreConfig;
+
+thread_local! {
+    static CORE_CONFIG: RefCell<QntxCoreConfig> = RefCell::new(QntxCoreConfig::default());
+}
+
+/// Validate and install a config document. On failure the current config is
+/// kept. Returns `{"ok":true}` or `{"error":"..."}` listing every violation.
+pub(crate) fn load_core_config_impl(input: &str) -> String {
+    match QntxCoreConfig::from_json(input) {
+        Ok(config) => {
+            qntx_core::set_slow_op_config(&config.slow_ops);
+            CORE_CONFIG.with(|c| *c.borrow_mut() = config);
+            r#"{"ok":true}"#.to_string()
+        }
+        Err(e) => serde_json::json!({ "error": e.to_string() }).to_string(),
+    }
+}
+
+/// Classify claims, defaulting missing windows to the loaded config.
+#[cfg(any(not(feature = "browser-core"), feature = "classify"))]
+pub(crate) fn classify_claims_impl(input: &str) -> String {
+    CORE_CONFIG.with(|c| qntx_core::classify_claims_with_defaults(input, &c.borrow().classify))
+}
+
+/// Time windows `classify_claims` falls back to, as `TemporalConfig` JSON:
+/// the built-in defaults unless a loaded config changed them.
+#[cfg(any(not(feature = "browser-core"), feature = "classify"))]
+pub(crate) fn classify_default_config_impl() -> String {
+    CORE_CONFIG.with(|c| {
+        serde_json::to_string(&c.borrow().classify.temporal)
+            .unwrap_or_else(|e| serde_json::json!({ "error": e.to_string() }).to_string())
+    })
+}
+
+/// Replace the loaded config's actor aliases with `input`
+/// (`{"canonical": ["alias", ...]}`). On failure the current aliases are
+/// kept. Returns `{"ok":true,"identities":N}` or `{"error":"..."}`.
+#[cfg(feature = "classify")]
+pub(crate) fn set_actor_aliases_impl(input: &str) -> String {
+    match qntx_core::ActorAliasMap::from_json(input) {
+        Ok(aliases) => install_actor_aliases(aliases),
+        Err(e) => serde_json::json!({ "error": e.to_string() }).to_string(),
+    }
+}
+
+/// Install `aliases` for classification and query expansion.
+#[cfg(feature = "classify")]
+pub(crate) fn install_actor_aliases(aliases: qntx_core::ActorAliasMap) -> String {
+    let identities = aliases.len();
+    CORE_CONFIG.with(|c| c.borrow_mut().classify.actor_aliases = aliases);
+    serde_json::json!({ "ok": true, "identities": identities }).to_string()
+}
+
+/// Actor aliases from the loaded config.
+#[cfg(feature = "storage")]
+pub(crate) fn actor_aliases() -> qntx_core::ActorAliasMap {
+    CORE_CONFIG.with(|c| c.borrow().classify.actor_aliases.clone())
+}
+
+/// Classification policy from the loaded config.
+#[cfg(all(feature = "storage", feature = "classify"))]
+pub(crate) fn classify_policy() -> qntx_core::ClassificationPolicy {
+    CORE_CONFIG.with(|c| c.borrow().classify.clone())
+}
+
+/// Lint rules from the loaded config.
+#[cfg(feature = "storage")]
+pub(crate) fn lint_config() -> qntx_core::LintConfig {
+    CORE_CONFIG.with(|c| c.borrow().lint.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn invalid_config_is_rejected_and_previous_kept() {
+        let ok = load_core_config_impl(r#"{"classify": {"verification_window_ms": 5000}}"#);
+        assert_eq!(ok, r#"{"ok":true}"#);
+
+        let err = load_core_config_impl(
+            r#"{"classify": {"verification_window_ms": -1, "obsolescence_window_ms": 0}}"#,
+        );
+        let parsed: serde_json::Value = serde_json::from_str(&err).unwrap();
+        let message = parsed["error"].as_str().unwrap();
+        assert!(message.contains("classify.verification_window_ms"));
+        assert!(message.contains("classify.obsolescence_window_ms"));
+
+        CORE_CONFIG.with(|c| assert_eq!(c.borrow().classify.temporal.verification_window_ms, 5000));
+    }
+}
@@ -5,7 +5,8 @@
 //! ## Default (wazero/Go)
 //! Exposes qntx-core functions through a raw memory ABI for use with
 //! wazero (pure Go WebAssembly runtime). No WASI imports needed — all
-//! functions are pure computation with shared memory string passing.
+//! functions are pure computation with shared memory string passing. The one
+//! import is the host clock `env.qntx_now_ms`, only with feature `bench`.
 //!
 //! Strings cross the WASM boundary as (ptr, len) pairs in linear memory.
 //! The host allocates via [`wasm_alloc`], writes bytes, calls the function,
@@ -19,6 +20,25 @@
 //! for browser-based persistent storage. Provides async functions for
 //! parsing queries and storing/retrieving attestations.
 //!
+//! `browser` turns on every export group. Embedders that only parse can
+//! build a smaller bundle from the groups they need:
+//!
+//! ```text
+//! browser ──┬── storage ────┐
+//!           ├── classify ───┼── browser-core (wasm-bindgen, parse, identity, config, ...)
+//!           └── similarity ─┘
+//! ```
+//!
+//! e.g. `--no-default-features --features browser-core,classify`. Actor alias
+//! loading from the store needs both `storage` and `classify`. The
+//! `capabilities()` export reports the groups a build contains, so
+//! TypeScript can feature-detect instead of calling a missing export.
+//!
+//! Release sizes of `qntx_wasm.wasm` before wasm-bindgen, from
+//! `cargo test -p qntx-wasm --test bundle_size -- --ignored --nocapture`:
+//!
+#![doc = include_str!("../BUNDLE_SIZES.md")]
+//!
 //! ## Proto Migration Note (ADR-006)
 //!
 //! This crate includes qntx-proto for potential future use of proto types.
@@ -30,24 +50,44 @@
 // Shared identity logic (used by both wazero and browser targets)
 mod identity;
 
+// Module-wide qntx-core config (used by both wazero and browser targets)
+mod core_config;
+
+// Export groups are additive on top of browser-core; Cargo.toml encodes the
+// graph, these catch a manifest edit that breaks it.
+#[cfg(all(
+    any(feature = "storage", feature = "classify", feature = "similarity"),
+    not(feature = "browser-core")
+))]
+compile_error!(
+    "storage, classify and similarity are browser export groups and require browser-core"
+);
+
+#[cfg(all(
+    feature = "browser",
+    not(all(feature = "storage", feature = "classify", feature = "similarity"))
+))]
+compile_error!("browser must enable every export group (storage, classify, similarity)");
+
 // Browser-specific module (wasm-bindgen + IndexedDB)
-#[cfg(feature = "browser")]
+#[cfg(feature = "browser-core")]
 pub mod browser;
 
 // Re-export browser functions at crate root for convenience
-#[cfg(feature = "browser")]
+#[cfg(feature = "browser-core")]
 pub use browser::*;
 
 // ============================================================================
 // Wazero/Go target (raw memory ABI) - only when browser feature is disabled
 // ============================================================================
 
-#[cfg(not(feature = "browser"))]
+#[cfg(not(feature = "browser-core"))]
 use qntx_core::parser::Parser;
 
-#[cfg(not(feature = "browser"))]
+#[cfg(not(feature = "browser-core"))]
 mod wazero {
     use super::*;
+    use qntx_core::duration::{DurationError, CALENDAR_POLICY, MS_PER_DAY};
 
     // ============================================================================
     // Memory management
@@ -97,6 +137,14 @@ mod wazero {
         std::str::from_utf8_unchecked(slice)
     }
 
+    /// Borrow raw bytes from WASM linear memory at (ptr, len).
+    unsafe fn read_bytes(ptr: u32, len: u32) -> &'static [u8] {
+        if len == 0 {
+            return &[];
+        }
+        std::slice::from_raw_parts(ptr as *const u8, len as usize)
+    }
+
     /// Write a string into newly allocated WASM memory and return packed u64.
     /// The caller (host) is responsible for freeing via `wasm_free`.
     fn write_result(s: &str) -> u64 {
@@ -141,7 +189,10 @@ mod wazero {
     /// string in WASM memory. Returns a packed u64 (ptr << 32 | len) pointing
     /// to a JSON-serialized AxQuery result.
     ///
-    /// On success: `{"subjects":["ALICE"],"predicates":["author"],...}`
+    /// On success: `{"subjects":["ALICE"],"predicates":["author"],...}`, plus
+    /// `subject_groups` / `context_groups` when the query uses `or`.
+    /// `excluded_subjects` / `excluded_contexts` / `excluded_actors` are always
+    /// present, empty unless the query uses `not`/`except`.
     /// On error: `{"error":"description"}`
     #[no_mangle]
     pub extern "C" fn parse_ax_query(ptr: u32, len: u32) -> u64 {
@@ -154,9 +205,9 @@ mod wazero {
                 // A proper design would validate during parsing, not as a separate step.
                 // This is a hack to achieve bug-for-bug compatibility with the Go parser.
                 if let Some(qntx_core::parser::TemporalClause::Over(ref dur)) = query.temporal {
-                    if dur.value.is_some() && dur.unit.is_none() {
-                        // Has a number but invalid unit (like "5q")
-                        return write_error(&format!("missing unit in '{}'", dur.raw));
+                    // Has a number but invalid unit (like "5q")
+                    if let Err(e @ DurationError::MissingUnit(_)) = dur.duration() {
+                        return write_error(&e.to_string());
                     }
                 }
 
@@ -204,8 +255,8 @@ mod wazero {
 
         // Validate "over" unit
         if let Some(qntx_core::parser::TemporalClause::Over(ref dur)) = query.temporal {
-            if dur.value.is_some() && dur.unit.is_none() {
-                return write_error(&format!("missing unit in '{}'", dur.raw));
+            if let Err(e @ DurationError::MissingUnit(_)) = dur.duration() {
+                return write_error(&e.to_string());
             }
         }
 
@@ -237,7 +288,7 @@ mod wazero {
                 match resolve_temporal(expr, parsed_input.now_ms) {
                     Some(ms) => Some(ResolvedTemporal::On {
                         start_ms: ms,
-                        end_ms: ms + 86_400_000,
+                        end_ms: ms + MS_PER_DAY,
                     }),
                     None => {
                         return write_error(&format!(
@@ -270,8 +321,8 @@ mod wazero {
             }
             Some(qntx_core::parser::TemporalClause::Over(dur)) => Some(ResolvedTemporal::Over {
                 raw: dur.raw.to_string(),
-                value: dur.value,
-                unit: dur.unit.map(|u| u.to_string()),
+                ms: dur.to_millis(CALENDAR_POLICY),
+                normalized: dur.normalized(),
             }),
             None => None,
         };
@@ -279,21 +330,44 @@ mod wazero {
         #[derive(serde::Serialize)]
         struct Output {
             subjects: Vec<String>,
+            #[serde(skip_serializing_if = "Vec::is_empty")]
+            subject_groups: Vec<Vec<String>>,
+            excluded_subjects: Vec<String>,
             predicates: Vec<String>,
             contexts: Vec<String>,
+            #[serde(skip_serializing_if = "Vec::is_empty")]
+            context_groups: Vec<Vec<String>>,
+            excluded_contexts: Vec<String>,
             actors: Vec<String>,
+            excluded_actors: Vec<String>,
             #[serde(skip_serializing_if = "Option::is_none")]
             temporal: Option<ResolvedTemporal>,
             actions: Vec<String>,
+            #[serde(skip_serializing_if = "Vec::is_empty")]
+            attribute_conditions: Vec<qntx_core::AttrCondition>,
         }
 
+        let owned =
+            |terms: &[&str]| -> Vec<String> { terms.iter().map(|s| s.to_string()).collect() };
+        let groups = |groups: &[Vec<&str>]| -> Vec<Vec<String>> {
+            groups
+                .iter()
+                .map(|g| g.iter().map(|s| s.to_string()).collect())
+                .collect()
+        };
         let output = Output {
             subjects: query.subjects.iter().map(|s| s.to_string()).collect(),
+            subject_groups: groups(&query.subject_groups),
+            excluded_subjects: owned(&query.excluded_subjects),
             predicates: query.predicates.iter().map(|s| s.to_string()).collect(),
             contexts: query.contexts.iter().map(|s| s.to_string()).collect(),
+            context_groups: groups(&query.context_groups),
+            excluded_contexts: owned(&query.excluded_contexts),
             actors: query.actors.iter().map(|s| s.to_string()).collect(),
+            excluded_actors: owned(&query.excluded_actors),
             temporal: resolved_temporal,
             actions: query.actions.iter().map(|s| s.to_string()).collect(),
+            attribute_conditions: query.attribute_conditions,
         };
 
         match serde_json::to_string(&output) {
@@ -302,6 +376,15 @@ mod wazero {
         }
     }
 
+    /// Parse a duration string ("18m", "1y6m", "2w3d") for display.
+    /// Returns `{"raw":"18m","normalized":"1y6m","ms":46656000000}` or
+    /// `{"error":"..."}`.
+    #[no_mangle]
+    pub extern "C" fn parse_duration(ptr: u32, len: u32) -> u64 {
+        let input = unsafe { read_str(ptr, len) };
+        write_result(&qntx_core::duration::parse_duration_json(input))
+    }
+
     // ============================================================================
     // Watcher Matching
     // ============================================================================
@@ -327,7 +410,28 @@ mod wazero {
 
     /// Inner logic for classify_claims — testable without WASM memory ABI.
     fn classify_claims_impl(input: &str) -> String {
-        qntx_core::classify_claims(input)
+        crate::core_config::classify_claims_impl(input)
+    }
+
+    /// Load a qntx-core config document as the module-wide defaults. Takes
+    /// (ptr, len) pointing to JSON such as `{"classify": {"verification_window_ms": 30000}}`.
+    /// Windows missing from a later `classify_claims` config fall back to it.
+    ///
+    /// Returns `{"ok":true}`, or `{"error":"..."}` listing every validation
+    /// problem; on error the previous config stays in effect.
+    #[no_mangle]
+    pub extern "C" fn load_core_config(ptr: u32, len: u32) -> u64 {
+        let input = unsafe { read_str(ptr, len) };
+        write_result(&crate::core_config::load_core_config_impl(input))
+    }
+
+    /// Time windows `classify_claims` uses when its config omits them.
+    /// Returns packed u64 pointing to JSON such as
+    /// `{"verification_window_ms":60000,"evolution_window_ms":86400000,...}`,
+    /// reflecting `load_core_config`.
+    #[no_mangle]
+    pub extern "C" fn classify_default_config() -> u64 {
+        write_result(&crate::core_config::classify_default_config_impl())
     }
 
     /// Classify claim conflicts. Takes (ptr, len) pointing to a JSON string:
@@ -399,10 +503,13 @@ mod wazero {
 
     /// Group individual claims by (subject, predicate, context) key.
     /// Takes (ptr, len) pointing to a JSON string:
-    /// `{"claims": [...]}`
+    /// `{"claims": [...], "key_format": "json" | "legacy"}` (`key_format` optional)
     ///
     /// Returns packed u64 pointing to JSON:
-    /// `{"groups": [{"key": "...", "claims": [...]}], "total_groups": N}`
+    /// `{"groups": [{"key": "...", "claims": [...]}], "total_groups": N, "key_format": "..."}`
+    ///
+    /// `json` keys (the default) are `["subject","predicate","context"]` arrays;
+    /// `legacy` keys are `subject|predicate|context`, ambiguous when a value contains `|`.
     #[no_mangle]
     pub extern "C" fn group_claims(ptr: u32, len: u32) -> u64 {
         let input = unsafe { read_str(ptr, len) };
@@ -421,6 +528,293 @@ mod wazero {
         write_result(&dedup_source_ids_impl(input))
     }
 
+    // ============================================================================
+    // Vocabulary extraction
+    // ============================================================================
+
+    std::thread_local! {
+        /// In-progress chunked extraction (begin → add* → finish).
+        static VOCABULARY: std::cell::RefCell<Option<qntx_core::VocabularyExtractor>> =
+            const { std::cell::RefCell::new(None) };
+    }
+
+    /// Inner logic for extract_vocabulary — testable without WASM memory ABI.
+    fn extract_vocabulary_impl(input: &str) -> String {
+        qntx_core::extract_vocabulary_json(input)
+    }
+
+    /// Inner logic for extract_vocabulary_begin.
+    fn extract_vocabulary_begin_impl() -> String {
+        VOCABULARY.with(|v| *v.borrow_mut() = Some(qntx_core::VocabularyExtractor::new()));
+        r#"{"ok":true}"#.to_string()
+    }
+
+    /// Inner logic for extract_vocabulary_add.
+    fn extract_vocabulary_add_impl(input: &str) -> String {
+        let batch: Vec<qntx_core::VocabularyAttestation> = match serde_json::from_str(input) {
+            Ok(v) => v,
+            Err(e) => return error_json(&format!("invalid vocabulary input: {}", e)),
+        };
+        VOCABULARY.with(|v| match v.borrow_mut().as_mut() {
+            Some(extractor) => {
+                extractor.add_all(&batch);
+                format!(r#"{{"added":{}}}"#, batch.len())
+            }
+            None => error_json("extract_vocabulary_add called before extract_vocabulary_begin"),
+        })
+    }
+
+    /// Inner logic for extract_vocabulary_finish.
+    fn extract_vocabulary_finish_impl() -> String {
+        let Some(extractor) = VOCABULARY.with(|v| v.borrow_mut().take()) else {
+            return error_json("extract_vocabulary_finish called before extract_vocabulary_begin");
+        };
+        match serde_json::to_string(&extractor.finish()) {
+            Ok(json) => json,
+            Err(e) => error_json(&format!("serialization failed: {}", e)),
+        }
+    }
+
+    /// Extract distinct subjects/predicates/contexts/actors from attestations.
+    /// Takes (ptr, len) pointing to a JSON array of proto-schema attestations.
+    ///
+    /// Returns packed u64 pointing to JSON:
+    /// ```json
+    /// {
+    ///   "subjects": ["..."], "predicates": ["..."], "contexts": ["..."], "actors": ["..."],
+    ///   "counts": {"subjects": [N], "predicates": [N], "contexts": [N], "actors": [N]},
+    ///   "attestations": N
+    /// }
+    /// ```
+    /// Values are trimmed, empties dropped, case preserved, first-seen order kept.
+    #[no_mangle]
+    pub extern "C" fn extract_vocabulary(ptr: u32, len: u32) -> u64 {
+        let input = unsafe { read_str(ptr, len) };
+        write_result(&extract_vocabulary_impl(input))
+    }
+
+    /// Start a chunked extraction, discarding any unfinished one.
+    /// Returns packed u64 pointing to `{"ok":true}`.
+    #[no_mangle]
+    pub extern "C" fn extract_vocabulary_begin() -> u64 {
+        write_result(&extract_vocabulary_begin_impl())
+    }
+
+    /// Feed one chunk (JSON array of attestations) into the running extraction.
+    /// Returns packed u64 pointing to `{"added":N}` or `{"error":"..."}`.
+    #[no_mangle]
+    pub extern "C" fn extract_vocabulary_add(ptr: u32, len: u32) -> u64 {
+        let input = unsafe { read_str(ptr, len) };
+        write_result(&extract_vocabulary_add_impl(input))
+    }
+
+    /// Finish the chunked extraction. Returns the same JSON as `extract_vocabulary`
+    /// over all chunks added since `extract_vocabulary_begin`.
+    #[no_mangle]
+    pub extern "C" fn extract_vocabulary_finish() -> u64 {
+        write_result(&extract_vocabulary_finish_impl())
+    }
+
+    // ============================================================================
+    // Graph projection
+    // ============================================================================
+
+    /// Input envelope for the graph exports: the attestations plus an optional projection.
+    #[derive(serde::Deserialize)]
+    struct ProjectGraphInput {
+        attestations: Vec<qntx_core::Attestation>,
+        #[serde(default)]
+        config: qntx_core::GraphProjection,
+    }
+
+    fn parse_project_graph_input(input: &str) -> Result<ProjectGraphInput, String> {
+        serde_json::from_str(input).map_err(|e| error_json(&format!("invalid graph input: {}", e)))
+    }
+
+    /// Inner logic for project_graph — testable without WASM memory ABI.
+    fn project_graph_impl(input: &str) -> String {
+        let input = match parse_project_graph_input(input) {
+            Ok(i) => i,
+            Err(e) => return e,
+        };
+        let graph = qntx_core::project(&input.attestations, &input.config);
+        match serde_json::to_string(&graph) {
+            Ok(json) => json,
+            Err(e) => error_json(&format!("serialization failed: {}", e)),
+        }
+    }
+
+    /// Inner logic for project_force_graph.
+    fn project_force_graph_impl(input: &str) -> String {
+        let input = match parse_project_graph_input(input) {
+            Ok(i) => i,
+            Err(e) => return e,
+        };
+        let graph = qntx_core::project(&input.attestations, &input.config).to_force_layout();
+        match serde_json::to_string(&graph) {
+            Ok(json) => json,
+            Err(e) => error_json(&format!("serialization failed: {}", e)),
+        }
+    }
+
+    /// Project attestations into graph nodes and merged edges.
+    /// Input: `{"attestations":[...],"config":{"source":"subject","target":"context",...}}`
+    /// (`config` optional). Returns packed u64 pointing to
+    /// `{"nodes":[{"id","label","slots","count"}],"edges":[{"source","target","label","count","weight","actors"}]}`
+    /// or `{"error":"..."}`.
+    #[no_mangle]
+    pub extern "C" fn project_graph(ptr: u32, len: u32) -> u64 {
+        let input = unsafe { read_str(ptr, len) };
+        write_result(&project_graph_impl(input))
+    }
+
+    /// Same input as project_graph; returns the index-based force layout
+    /// `{"nodes":[{"id","label"}],"links":[{"source":0,"target":1,"weight","label"}]}`.
+    #[no_mangle]
+    pub extern "C" fn project_force_graph(ptr: u32, len: u32) -> u64 {
+        let input = unsafe { read_str(ptr, len) };
+        write_result(&project_force_graph_impl(input))
+    }
+
+    // ============================================================================
+    // Attestation diff / patch
+    // ============================================================================
+
+    /// Input envelope for diff_attestations.
+    #[derive(serde::Deserialize)]
+    struct DiffInput {
+        a: serde_json::Value,
+        b: serde_json::Value,
+    }
+
+    /// Input envelope for apply_attestation_patch.
+    #[derive(serde::Deserialize)]
+    struct PatchInput {
+        base: serde_json::Value,
+        diff: serde_json::Value,
+    }
+
+    /// Inner logic for diff_attestations.
+    fn diff_attestations_impl(input: &str) -> String {
+        match serde_json::from_str::<DiffInput>(input) {
+            Ok(i) => qntx_core::diff_attestations_json(&i.a.to_string(), &i.b.to_string()),
+            Err(e) => error_json(&format!("invalid diff input: {}", e)),
+        }
+    }
+
+    /// Inner logic for apply_attestation_patch.
+    fn apply_attestation_patch_impl(input: &str) -> String {
+        match serde_json::from_str::<PatchInput>(input) {
+            Ok(i) => {
+                qntx_core::apply_attestation_patch_json(&i.base.to_string(), &i.diff.to_string())
+            }
+            Err(e) => error_json(&format!("invalid patch input: {}", e)),
+        }
+    }
+
+    /// Field-level diff between two attestations.
+    /// Input: `{"a":{...},"b":{...}}`. Returns packed u64 pointing to
+    /// `{"subjects":{"added","removed"},...,"attributes":{"added","removed","modified"},"timestamp","source"}`
+    /// or `{"error":"..."}`.
+    #[no_mangle]
+    pub extern "C" fn diff_attestations(ptr: u32, len: u32) -> u64 {
+        let input = unsafe { read_str(ptr, len) };
+        write_result(&diff_attestations_impl(input))
+    }
+
+    /// Apply a diff from diff_attestations to a base attestation.
+    /// Input: `{"base":{...},"diff":{...}}`. Returns packed u64 pointing to the
+    /// patched attestation, or `{"error":"...","conflict":{"mismatches":[...]}}`.
+    #[no_mangle]
+    pub extern "C" fn apply_attestation_patch(ptr: u32, len: u32) -> u64 {
+        let input = unsafe { read_str(ptr, len) };
+        write_result(&apply_attestation_patch_impl(input))
+    }
+
+    // ============================================================================
+    // Similarity
+    // ============================================================================
+
+    /// Inner logic for similarity_mmr. Input is little-endian:
+    /// `dim: u32, k: u32, lambda: f32`, then `dim` query floats, then the
+    /// candidate rows (`n * dim` floats).
+    fn similarity_mmr_impl(input: &[u8]) -> String {
+        if input.len() < 12 || !input.len().is_multiple_of(4) {
+            return error_json("similarity input must be a 12-byte header plus f32 values");
+        }
+        let words: Vec<[u8; 4]> = input
+            .chunks_exact(4)
+            .map(|c| [c[0], c[1], c[2], c[3]])
+            .collect();
+        let dim = u32::from_le_bytes(words[0]) as usize;
+        let k = u32::from_le_bytes(words[1]) as usize;
+        let lambda = f32::from_le_bytes(words[2]);
+        let floats: Vec<f32> = words[3..].iter().map(|w| f32::from_le_bytes(*w)).collect();
+        if floats.len() < dim {
+            return error_json(&format!(
+                "similarity input has {} floats, query dimension is {}",
+                floats.len(),
+                dim
+            ));
+        }
+        let (query, rows) = floats.split_at(dim);
+        match qntx_core::similarity::mmr_search(query, rows, lambda, k) {
+            Ok(result) => match serde_json::to_string(&result) {
+                Ok(json) => json,
+                Err(e) => error_json(&format!("serialization failed: {}", e)),
+            },
+            Err(e) => error_json(&e),
+        }
+    }
+
+    /// Diverse top-k by maximal marginal relevance over packed f32 buffers
+    /// (layout in `similarity_mmr_impl`); relevance is cosine to the query.
+    /// Returns packed u64 pointing to
+    /// `{"picks":[{"id":3,"relevance":0.91,"marginal":0.42}],"skipped":[7]}`
+    /// (ids are row indexes, in pick order) or `{"error":"..."}`.
+    #[no_mangle]
+    pub extern "C" fn similarity_mmr(ptr: u32, len: u32) -> u64 {
+        let input = unsafe { read_bytes(ptr, len) };
+        write_result(&similarity_mmr_impl(input))
+    }
+
+    // ============================================================================
+    // Self-benchmark (feature = "bench")
+    // ============================================================================
+
+    #[cfg(all(feature = "bench", target_arch = "wasm32"))]
+    #[link(wasm_import_module = "env")]
+    extern "C" {
+        /// Host monotonic clock in milliseconds (wazero has no clock without WASI)
+        fn qntx_now_ms() -> f64;
+    }
+
+    /// Native builds (tests) time with `std::time::Instant` instead of the host
+    #[cfg(all(feature = "bench", not(target_arch = "wasm32")))]
+    unsafe fn qntx_now_ms() -> f64 {
+        static ORIGIN: std::sync::OnceLock<std::time::Instant> = std::sync::OnceLock::new();
+        ORIGIN
+            .get_or_init(std::time::Instant::now)
+            .elapsed()
+            .as_secs_f64()
+            * 1000.0
+    }
+
+    /// Run the seeded self-benchmark workloads, timed with the host clock.
+    /// Takes (ptr, len) pointing to `BenchConfig` JSON (empty for defaults).
+    ///
+    /// Returns packed u64 pointing to `BenchReport` JSON:
+    /// `{"environment":{...},"config":{...},"workloads":[{"name","size","iterations",
+    /// "median_ms","p95_ms","min_ms"}],"total_ms":N,"budget_exhausted":false}`
+    #[cfg(feature = "bench")]
+    #[no_mangle]
+    pub extern "C" fn run_benchmarks(ptr: u32, len: u32) -> u64 {
+        let input = unsafe { read_str(ptr, len) };
+        write_result(&qntx_core::run_benchmarks_json(input, || unsafe {
+            qntx_now_ms()
+        }))
+    }
+
     // ============================================================================
     // Identity (qntx-id)
     // ============================================================================
@@ -480,6 +874,33 @@ mod wazero {
     mod tests {
         use super::*;
 
+        fn mmr_input(dim: u32, k: u32, lambda: f32, floats: &[f32]) -> Vec<u8> {
+            let mut bytes = Vec::new();
+            bytes.extend(dim.to_le_bytes());
+            bytes.extend(k.to_le_bytes());
+            bytes.extend(lambda.to_le_bytes());
+            for f in floats {
+                bytes.extend(f.to_le_bytes());
+            }
+            bytes
+        }
+
+        #[test]
+        fn similarity_mmr_packed() {
+            // Query, then a near-duplicate pair and an orthogonal row
+            let input = mmr_input(2, 2, 0.3, &[1.0, 0.0, 1.0, 0.0, 0.99, 0.1, 0.0, 1.0]);
+            let parsed: serde_json::Value =
+                serde_json::from_str(&similarity_mmr_impl(&input)).unwrap();
+            assert_eq!(parsed["picks"][0]["id"], 0);
+            assert_eq!(parsed["picks"][1]["id"], 2);
+
+            let ragged = mmr_input(2, 2, 0.3, &[1.0, 0.0, 1.0]);
+            let parsed: serde_json::Value =
+                serde_json::from_str(&similarity_mmr_impl(&ragged)).unwrap();
+            assert!(parsed["error"].as_str().unwrap().contains("not a multiple"));
+            assert!(similarity_mmr_impl(&[1, 2, 3]).contains("error"));
+        }
+
         #[test]
         fn classify_claims_evolution() {
             let now = 1_000_000_000_i64;
@@ -498,7 +919,22 @@ mod wazero {
             let parsed: serde_json::Value = serde_json::from_str(&result).unwrap();
             assert!(parsed["error"].is_null(), "unexpected error: {}", result);
             assert_eq!(parsed["total_analyzed"], 1);
-            assert_eq!(parsed["conflicts"][0]["conflict_type"], "Evolution");
+            let conflict = &parsed["conflicts"][0];
+            assert_eq!(conflict["conflict_type"], "Evolution");
+            let confidence = conflict["confidence"].as_f64().unwrap();
+            assert!(
+                (0.0..=1.0).contains(&confidence),
+                "confidence={}",
+                confidence
+            );
+            assert_eq!(conflict["winning_claim"], "as-2");
+        }
+
+        #[test]
+        fn classify_default_config_matches_core() {
+            let parsed: qntx_core::TemporalConfig =
+                serde_json::from_str(&crate::core_config::classify_default_config_impl()).unwrap();
+            assert_eq!(parsed, qntx_core::TemporalConfig::default());
         }
 
         #[test]
@@ -559,6 +995,29 @@ mod wazero {
             assert_eq!(parsed["total_groups"], 2);
         }
 
+        /// Same fixture and expected output as the browser target's test.
+        #[test]
+        fn claim_pipeline_matches_shared_fixture() {
+            let input = include_str!("../tests/fixtures/claim_pipeline.json");
+            let expected: serde_json::Value = serde_json::from_str(include_str!(
+                "../tests/fixtures/claim_pipeline.expected.json"
+            ))
+            .unwrap();
+
+            let expanded: serde_json::Value =
+                serde_json::from_str(&expand_cartesian_claims_impl(input)).unwrap();
+            assert_eq!(expanded, expected["expand"]);
+
+            let claims = serde_json::json!({"claims": expanded["claims"]}).to_string();
+            let grouped: serde_json::Value =
+                serde_json::from_str(&group_claims_impl(&claims)).unwrap();
+            assert_eq!(grouped, expected["group"]);
+
+            let deduped: serde_json::Value =
+                serde_json::from_str(&dedup_source_ids_impl(&claims)).unwrap();
+            assert_eq!(deduped, expected["dedup"]);
+        }
+
         #[test]
         fn dedup_source_ids_basic() {
             // Luke's rescue plan covers both the droid delivery and Lando's infiltration;
@@ -579,9 +1038,88 @@ mod wazero {
                 &["rescue-plan", "carbonite-heist"]
             );
         }
+
+        #[test]
+        fn extract_vocabulary_chunked_matches_direct() {
+            let chunk_a = serde_json::json!([
+                {"id": "SW001", "subjects": ["LUKE", " "], "predicates": ["trains_with"], "contexts": ["DAGOBAH"], "actors": ["rebel-intelligence"]},
+            ]);
+            let chunk_b = serde_json::json!([
+                {"id": "SW002", "subjects": [" YODA ", "LUKE"], "predicates": ["trains_with"], "contexts": ["DAGOBAH"], "actors": ["rebel-intelligence"]},
+            ]);
+            let all = serde_json::json!([chunk_a[0], chunk_b[0]]);
+
+            let direct = extract_vocabulary_impl(&all.to_string());
+
+            extract_vocabulary_begin_impl();
+            let added = extract_vocabulary_add_impl(&chunk_a.to_string());
+            assert_eq!(added, r#"{"added":1}"#);
+            extract_vocabulary_add_impl(&chunk_b.to_string());
+            let chunked = extract_vocabulary_finish_impl();
+
+            assert_eq!(chunked, direct);
+            let parsed: serde_json::Value = serde_json::from_str(&direct).unwrap();
+            assert_eq!(parsed["subjects"], serde_json::json!(["LUKE", "YODA"]));
+            assert_eq!(parsed["counts"]["subjects"], serde_json::json!([2, 1]));
+        }
+
+        #[test]
+        fn extract_vocabulary_finish_without_begin() {
+            let result = extract_vocabulary_finish_impl();
+            assert!(result.contains("before extract_vocabulary_begin"));
+        }
+
+        #[test]
+        fn project_graph_default_config() {
+            let input = serde_json::json!({
+                "attestations": [
+                    {"id": "AS-1", "subjects": ["LUKE"], "predicates": ["trained_by"], "contexts": ["YODA"], "actors": ["human:r2"], "timestamp": 1000, "source": "test"},
+                    {"id": "AS-2", "subjects": ["LUKE"], "predicates": ["trained_by"], "contexts": ["YODA"], "actors": ["human:r2"], "timestamp": 2000, "source": "test"}
+                ]
+            });
+            let parsed: serde_json::Value =
+                serde_json::from_str(&project_graph_impl(&input.to_string())).unwrap();
+            assert_eq!(parsed["nodes"][0]["id"], "subject:LUKE");
+            assert_eq!(parsed["edges"][0]["count"], 2);
+
+            let force: serde_json::Value =
+                serde_json::from_str(&project_force_graph_impl(&input.to_string())).unwrap();
+            assert_eq!(force["links"][0]["source"], 0);
+            assert_eq!(force["links"][0]["target"], 1);
+        }
+
+        #[test]
+        fn diff_and_patch_attestations() {
+            let a = serde_json::json!({"id": "AS-1", "subjects": ["LUKE"], "predicates": ["trained_by"], "contexts": ["YODA"], "actors": ["human:r2"], "timestamp": 1000, "source": "test"});
+            let b = serde_json::json!({"id": "AS-1", "subjects": ["LUKE"], "predicates": ["trained_by"], "contexts": ["OBIWAN"], "actors": ["human:r2"], "timestamp": 1000, "source": "test"});
+            let diff: serde_json::Value = serde_json::from_str(&diff_attestations_impl(
+                &serde_json::json!({"a": a, "b": b}).to_string(),
+            ))
+            .unwrap();
+            assert_eq!(diff["contexts"]["added"], serde_json::json!(["OBIWAN"]));
+
+            let patched: serde_json::Value = serde_json::from_str(&apply_attestation_patch_impl(
+                &serde_json::json!({"base": a, "diff": diff}).to_string(),
+            ))
+            .unwrap();
+            assert_eq!(patched["contexts"], b["contexts"]);
+
+            let conflict: serde_json::Value = serde_json::from_str(&apply_attestation_patch_impl(
+                &serde_json::json!({"base": b, "diff": diff}).to_string(),
+            ))
+            .unwrap();
+            assert_eq!(conflict["conflict"]["mismatches"][0]["field"], "contexts");
+            assert!(diff_attestations_impl("{}").contains("invalid diff input"));
+        }
+
+        #[test]
+        fn project_graph_invalid() {
+            let result = project_graph_impl(r#"{"attestations": 5}"#);
+            assert!(result.contains("invalid graph input"));
+        }
     }
 } // end mod wazero
 
 // Re-export wazero functions at crate root for backward compatibility
-#[cfg(not(feature = "browser"))]
+#[cfg(not(feature = "browser-core"))]
 pub use wazero::*;
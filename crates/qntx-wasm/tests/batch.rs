@@ -0,0 +1,94 @@
+//! Browser batch tests: startup bundle vs individual calls, per-entry errors.
+//!
+//! Run with `wasm-pack test --headless --firefox crates/qntx-wasm -- --features browser`.
+#![cfg(all(target_arch = "wasm32", feature = "browser"))]
+
+use qntx_wasm::browser::*;
+use wasm_bindgen_test::*;
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+const DB_NAME: &str = "qntx-batch-test";
+
+fn json(s: &str) -> serde_json::Value {
+    serde_json::from_str(s).unwrap()
+}
+
+fn attestation(id: &str) -> String {
+    serde_json::json!({
+        "id": id,
+        "subjects": ["ALICE"],
+        "predicates": ["knows"],
+        "contexts": ["batch"],
+        "actors": ["human:alice"],
+        "timestamp": 1_700_000_000_000_i64,
+        "source": "test",
+    })
+    .to_string()
+}
+
+#[wasm_bindgen_test]
+async fn startup_bundle_matches_individual_calls() {
+    let filter = r#"{"contexts":["batch"]}"#;
+    let bundle = json(
+        &startup_bundle(Some(DB_NAME.to_string()), Some(filter.to_string()))
+            .await
+            .unwrap(),
+    );
+    assert!(bundle["init"]["error"].is_null(), "{}", bundle);
+    put_attestation(&attestation("BATCH-1")).await.unwrap();
+
+    // Running it again on the open store is fine and sees the new data
+    let bundle = json(
+        &startup_bundle(Some(DB_NAME.to_string()), Some(filter.to_string()))
+            .await
+            .unwrap(),
+    );
+    assert_eq!(bundle["init"]["result"], serde_json::Value::Null);
+    assert_eq!(bundle["version"]["result"], version());
+    assert_eq!(
+        bundle["outbox_status"]["result"],
+        json(&outbox_status().await.unwrap())
+    );
+    assert_eq!(
+        bundle["type_registry"]["result"],
+        json(&type_registry().await.unwrap())
+    );
+    assert_eq!(
+        bundle["query"]["result"],
+        json(&query_attestations(filter).await.unwrap())
+    );
+    assert!(bundle["query"]["result"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .any(|a| a["id"] == "BATCH-1"));
+
+    delete_attestation("BATCH-1").await.unwrap();
+}
+
+#[wasm_bindgen_test]
+async fn batch_errors_stay_per_entry() {
+    init_worker(Some(DB_NAME.to_string()), true).await.unwrap();
+    put_attestation(&attestation("BATCH-2")).await.unwrap();
+
+    let requests = serde_json::json!([
+        {"id": "again", "op": "init_store", "params": {"db_name": DB_NAME}},
+        {"id": "get", "op": "get_attestation", "params": {"id": "BATCH-2"}},
+        {"id": "filter", "op": "query_attestations", "params": {"filter": {"limit": "ten"}}},
+        {"id": "exists", "op": "exists_attestation", "params": {"id": "BATCH-2"}},
+    ]);
+    let results = json(&execute_batch(&requests.to_string()).await.unwrap());
+
+    // Opening twice is rejected, without stopping the rest
+    assert!(results[0]["error"].is_string(), "{}", results[0]);
+    assert_eq!(
+        results[1]["result"],
+        json(&get_attestation("BATCH-2").await.unwrap().unwrap())
+    );
+    assert_eq!(results[2]["error"]["code"], "invalid_input");
+    assert_eq!(results[3]["result"], true);
+
+    assert!(execute_batch("{}").await.is_err());
+    delete_attestation("BATCH-2").await.unwrap();
+}
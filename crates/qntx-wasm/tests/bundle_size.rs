@@ -0,0 +1,93 @@
+//! Browser bundle size check: builds the release wasm for the minimal and
+//! per-group feature sets and fails when the minimal build outgrows its budget.
+//!
+//! Run with `cargo test -p qntx-wasm --test bundle_size -- --ignored --nocapture`
+//! (needs the `wasm32-unknown-unknown` target). Set `QNTX_WRITE_BUNDLE_SIZES=1`
+//! to regenerate `BUNDLE_SIZES.md`, which the crate docs include.
+#![cfg(not(target_arch = "wasm32"))]
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Budget for `browser-core` alone, in bytes (945 KiB when this was set)
+const MINIMAL_BUDGET: u64 = 1_100_000;
+
+/// Feature sets measured, smallest first. The last one is the full build.
+const BUILDS: &[(&str, &str)] = &[
+    ("browser-core", "browser-core"),
+    ("+ storage", "browser-core,storage"),
+    ("+ classify", "browser-core,classify"),
+    ("+ similarity", "browser-core,similarity"),
+    ("browser (all)", "browser"),
+];
+
+fn crate_dir() -> &'static Path {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+}
+
+/// Release-build the cdylib with `features` and return the .wasm size.
+fn wasm_size(features: &str) -> u64 {
+    // One target dir per feature set keeps the builds from invalidating each other
+    let target_dir: PathBuf = crate_dir()
+        .join("../../target/bundle-size")
+        .join(features.replace(',', "+"));
+    // The crate's [profile.release] is ignored inside the workspace; apply it here
+    let status = Command::new(env!("CARGO"))
+        .current_dir(crate_dir())
+        .env("CARGO_PROFILE_RELEASE_OPT_LEVEL", "s")
+        .env("CARGO_PROFILE_RELEASE_LTO", "true")
+        .env("CARGO_PROFILE_RELEASE_STRIP", "true")
+        .args([
+            "build",
+            "--release",
+            "--lib",
+            "--target",
+            "wasm32-unknown-unknown",
+            "--no-default-features",
+            "--features",
+            features,
+            "--target-dir",
+        ])
+        .arg(&target_dir)
+        .status()
+        .expect("failed to run cargo");
+    assert!(status.success(), "wasm build failed for {}", features);
+
+    let wasm = target_dir.join("wasm32-unknown-unknown/release/qntx_wasm.wasm");
+    std::fs::metadata(&wasm)
+        .unwrap_or_else(|e| panic!("{}: {}", wasm.display(), e))
+        .len()
+}
+
+fn kib(bytes: u64) -> String {
+    format!("{:.0} KiB", bytes as f64 / 1024.0)
+}
+
+#[test]
+#[ignore = "builds the wasm32 release bundle several times"]
+fn minimal_bundle_within_budget() {
+    let sizes: Vec<u64> = BUILDS.iter().map(|(_, f)| wasm_size(f)).collect();
+    let minimal = sizes[0];
+
+    let mut table = String::from("| Features | Size | vs. browser-core |\n|---|---:|---:|\n");
+    for ((label, _), size) in BUILDS.iter().zip(&sizes) {
+        let delta = if *size == minimal {
+            "".to_string()
+        } else {
+            format!("+{}", kib(size - minimal))
+        };
+        table.push_str(&format!("| `{}` | {} | {} |\n", label, kib(*size), delta));
+    }
+    println!("{}", table);
+
+    if std::env::var_os("QNTX_WRITE_BUNDLE_SIZES").is_some() {
+        std::fs::write(crate_dir().join("BUNDLE_SIZES.md"), &table).unwrap();
+    }
+
+    assert!(
+        minimal <= MINIMAL_BUDGET,
+        "browser-core bundle is {} bytes, budget {}",
+        minimal,
+        MINIMAL_BUDGET
+    );
+}
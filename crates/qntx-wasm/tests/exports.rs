@@ -0,0 +1,101 @@
+//! Browser worker start-up and `*_bytes` export tests.
+//!
+//! Run with `wasm-pack test --headless --firefox crates/qntx-wasm -- --features browser`.
+#![cfg(all(target_arch = "wasm32", feature = "browser"))]
+
+use qntx_wasm::browser::*;
+use wasm_bindgen_test::*;
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+const DB_NAME: &str = "qntx-exports-test";
+
+fn json(bytes: &[u8]) -> serde_json::Value {
+    serde_json::from_slice(bytes).unwrap()
+}
+
+#[wasm_bindgen_test]
+async fn init_worker_opens_store_once() {
+    // Without open_store nothing is opened
+    init_worker(None, false).await.unwrap();
+
+    init_worker(Some(DB_NAME.to_string()), true).await.unwrap();
+    assert!(is_store_initialized());
+    // A restarted worker script calling it again keeps the open store
+    init_worker(Some(DB_NAME.to_string()), true).await.unwrap();
+    assert!(is_store_initialized());
+}
+
+#[wasm_bindgen_test]
+async fn query_attestations_bytes_matches_string_export() {
+    init_worker(Some(DB_NAME.to_string()), true).await.unwrap();
+    delete_attestation("AS-bytes").await.unwrap();
+    let attestation = serde_json::json!({
+        "id": "AS-bytes",
+        "subjects": ["ALICE"],
+        "predicates": ["knows"],
+        "contexts": ["bytes"],
+        "actors": ["human:alice"],
+        "timestamp": 1_700_000_000_000_i64,
+        "source": "test",
+    });
+    put_attestation(&attestation.to_string()).await.unwrap();
+
+    let filter = r#"{"contexts":["bytes"]}"#;
+    let bytes = query_attestations_bytes(filter).await.unwrap();
+    assert_eq!(
+        bytes,
+        query_attestations(filter).await.unwrap().into_bytes()
+    );
+    let ids: Vec<String> = json(&bytes)
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|a| a["id"].as_str().unwrap().to_string())
+        .collect();
+    assert_eq!(ids, ["AS-bytes"]);
+
+    let summary = json(
+        &query_attestations_bytes(r#"{"contexts":["bytes"],"include_summary":true}"#)
+            .await
+            .unwrap(),
+    );
+    assert_eq!(summary["summary"]["matching"]["total"], 1);
+
+    let err = query_attestations_bytes("{not json").await.unwrap_err();
+    let err: serde_json::Value = serde_json::from_str(&err.as_string().unwrap()).unwrap();
+    assert_eq!(err["code"], "invalid_input");
+
+    delete_attestation("AS-bytes").await.unwrap();
+}
+
+#[wasm_bindgen_test]
+fn classify_claims_bytes_returns_utf8_json() {
+    let input = serde_json::json!({
+        "claim_groups": [{
+            "key": "ALICE|role|GitHub",
+            "claims": [
+                {"subject": "ALICE", "predicate": "is_junior", "context": "GitHub", "actor": "human:alice", "timestamp_ms": 1_000, "source_id": "as-1"},
+                {"subject": "ALICE", "predicate": "is_senior", "context": "GitHub", "actor": "human:alice", "timestamp_ms": 90_000, "source_id": "as-2"}
+            ]
+        }],
+        "now_ms": 100_000
+    })
+    .to_string();
+    let bytes = classify_claims_bytes(&input);
+    assert_eq!(bytes, classify_claims(&input).into_bytes());
+    assert_eq!(json(&bytes)["total_analyzed"], 1);
+}
+
+#[wasm_bindgen_test]
+fn similarity_search_bytes_rejects_ragged_candidates() {
+    let hits = similarity_search_bytes(&[1.0, 0.0], &[0.0, 1.0, 1.0, 0.0], 1, 0.0).unwrap();
+    assert_eq!(json(&hits)[0]["index"], 1);
+
+    let err = similarity_search_bytes(&[1.0, 0.0], &[1.0, 0.0, 1.0], 10, 0.0).unwrap_err();
+    assert!(err
+        .as_string()
+        .unwrap()
+        .contains("not a multiple of query dimension"));
+    assert!(similarity_search_bytes(&[], &[], 10, 0.0).is_err());
+}
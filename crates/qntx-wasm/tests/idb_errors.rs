@@ -0,0 +1,81 @@
+//! Browser error mapping tests: blocked upgrades, version conflicts and the
+//! `{code, message, hint}` JSON reaching JS.
+//!
+//! Run with `wasm-pack test --headless --firefox crates/qntx-wasm -- --features browser`.
+#![cfg(all(target_arch = "wasm32", feature = "browser"))]
+
+use qntx_indexeddb::idb::{open_database_version, OPEN_BLOCKED_TIMEOUT_MS};
+use qntx_indexeddb::{IndexedDbError, IndexedDbStore, RecoveryHint};
+use qntx_wasm::browser::*;
+use wasm_bindgen_test::*;
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+fn json(s: &str) -> serde_json::Value {
+    serde_json::from_str(s).unwrap()
+}
+
+#[wasm_bindgen_test]
+async fn upgrade_blocked_by_open_connection() {
+    let name = "qntx-errors-blocked";
+    let _ = IndexedDbStore::delete_database(name).await;
+    let holder = open_database_version(name, 2, OPEN_BLOCKED_TIMEOUT_MS)
+        .await
+        .unwrap();
+
+    let err = open_database_version(name, 3, 50).await.unwrap_err();
+    assert!(matches!(err, IndexedDbError::Blocked(_)), "{}", err);
+    assert_eq!(err.recovery_hint(), Some(RecoveryHint::CloseOtherTabs));
+
+    // The given-up open completes once the holder closes, and is closed in
+    // turn: otherwise deleting the database would hang
+    holder.close();
+    IndexedDbStore::delete_database(name).await.unwrap();
+}
+
+#[wasm_bindgen_test]
+async fn older_build_gets_version_conflict() {
+    let name = "qntx-errors-version";
+    let _ = IndexedDbStore::delete_database(name).await;
+    open_database_version(name, 5, OPEN_BLOCKED_TIMEOUT_MS)
+        .await
+        .unwrap()
+        .close();
+
+    let err = init_store(Some(name.to_string()))
+        .await
+        .unwrap_err()
+        .as_string()
+        .unwrap();
+    let err = json(&err);
+    assert_eq!(err["code"], "backend");
+    assert_eq!(err["hint"], "reload_page");
+    assert!(err["message"]
+        .as_str()
+        .unwrap()
+        .starts_with("IndexedDB version conflict"));
+    IndexedDbStore::delete_database(name).await.unwrap();
+}
+
+#[wasm_bindgen_test]
+async fn blocked_open_reaches_js_as_json() {
+    let name = "qntx-errors-blocked-js";
+    let _ = IndexedDbStore::delete_database(name).await;
+    // An older build's connection: init_store has to upgrade past it
+    let holder = open_database_version(name, 1, OPEN_BLOCKED_TIMEOUT_MS)
+        .await
+        .unwrap();
+
+    let err = init_store(Some(name.to_string()))
+        .await
+        .unwrap_err()
+        .as_string()
+        .unwrap();
+    let err = json(&err);
+    assert_eq!(err["code"], "backend");
+    assert_eq!(err["hint"], "close_other_tabs");
+    assert!(!is_store_initialized());
+
+    holder.close();
+    IndexedDbStore::delete_database(name).await.unwrap();
+}
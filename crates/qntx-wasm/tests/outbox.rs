@@ -0,0 +1,161 @@
+//! Browser outbox tests: enqueue → drain → partial failure → retry ordering.
+//!
+//! Run with `wasm-pack test --headless --firefox crates/qntx-wasm -- --features browser`.
+#![cfg(all(target_arch = "wasm32", feature = "browser"))]
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use qntx_wasm::browser::*;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use wasm_bindgen_test::*;
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+/// Open the test database, remove leftovers of `ids` from earlier runs and
+/// start with an empty, enabled outbox.
+async fn setup(ids: &[&str]) {
+    init_worker(Some("qntx-outbox-test".to_string()), true)
+        .await
+        .unwrap();
+    enable_outbox(false);
+    for id in ids {
+        delete_attestation(id).await.unwrap();
+    }
+    clear_outbox().await.unwrap();
+    set_outbox_policy("{}").unwrap();
+}
+
+fn attestation(id: &str) -> String {
+    serde_json::json!({
+        "id": id,
+        "subjects": ["ALICE"],
+        "predicates": ["knows"],
+        "contexts": ["outbox"],
+        "actors": ["human:alice"],
+        "timestamp": 1_700_000_000_000_i64,
+        "source": "test",
+    })
+    .to_string()
+}
+
+/// Uploader recording `op:id` of every entry it is handed; entries whose
+/// `op:id` is in `fail` are rejected.
+struct Uploader {
+    calls: Rc<RefCell<Vec<String>>>,
+    hashes: Rc<RefCell<Vec<String>>>,
+    _closure: Closure<dyn FnMut(JsValue) -> JsValue>,
+    function: js_sys::Function,
+}
+
+fn uploader(fail: &[&str]) -> Uploader {
+    let fail: Vec<String> = fail.iter().map(|s| s.to_string()).collect();
+    let calls = Rc::new(RefCell::new(Vec::new()));
+    let hashes = Rc::new(RefCell::new(Vec::new()));
+    let (c, h) = (calls.clone(), hashes.clone());
+    let closure = Closure::wrap(Box::new(move |entry: JsValue| -> JsValue {
+        let get = |key: &str| {
+            js_sys::Reflect::get(&entry, &key.into())
+                .unwrap()
+                .as_string()
+                .unwrap()
+        };
+        let call = format!("{}:{}", get("op"), get("id"));
+        h.borrow_mut().push(get("content_hash"));
+        let ok = !fail.contains(&call);
+        c.borrow_mut().push(call);
+        js_sys::Promise::resolve(&JsValue::from_bool(ok)).into()
+    }) as Box<dyn FnMut(JsValue) -> JsValue>);
+    let function = closure.as_ref().unchecked_ref::<js_sys::Function>().clone();
+    Uploader {
+        calls,
+        hashes,
+        _closure: closure,
+        function,
+    }
+}
+
+fn json(s: &str) -> serde_json::Value {
+    serde_json::from_str(s).unwrap()
+}
+
+#[wasm_bindgen_test]
+async fn drain_retries_failures_in_order() {
+    setup(&["OB-1", "OB-2", "OB-3"]).await;
+    put_attestation(&attestation("OB-1")).await.unwrap();
+    put_attestation(&attestation("OB-2")).await.unwrap();
+    assert!(delete_attestation("OB-1").await.unwrap());
+    put_attestation(&attestation("OB-3")).await.unwrap();
+
+    let status = json(&outbox_status().await.unwrap());
+    assert_eq!(status["pending"], 4);
+    assert_eq!(status["enabled"], true);
+
+    // The put of OB-1 fails, so its delete must wait behind it
+    let first = uploader(&["put:OB-1"]);
+    let report = json(&drain_outbox(first.function.clone()).await.unwrap());
+    assert_eq!(
+        *first.calls.borrow(),
+        vec!["put:OB-1", "put:OB-2", "put:OB-3"]
+    );
+    assert_eq!(report["sent"], 2);
+    assert_eq!(report["failed"], 1);
+    assert_eq!(report["deferred"], 1);
+    assert_eq!(report["remaining"], 2);
+
+    let status = json(&outbox_status().await.unwrap());
+    assert_eq!(status["pending"], 2);
+    assert_eq!(status["failing"], 1);
+
+    // Still backing off: nothing is sent
+    let idle = uploader(&[]);
+    let report = json(&drain_outbox(idle.function.clone()).await.unwrap());
+    assert!(idle.calls.borrow().is_empty());
+    assert_eq!(report["deferred"], 2);
+
+    // Once due, the retry goes first and carries the same content hash
+    reset_outbox_backoff().await.unwrap();
+    let retry = uploader(&[]);
+    let report = json(&drain_outbox(retry.function.clone()).await.unwrap());
+    assert_eq!(*retry.calls.borrow(), vec!["put:OB-1", "delete:OB-1"]);
+    assert_eq!(retry.hashes.borrow()[0], first.hashes.borrow()[0]);
+    assert_eq!(report["remaining"], 0);
+    assert_eq!(json(&outbox_status().await.unwrap())["pending"], 0);
+}
+
+#[wasm_bindgen_test]
+async fn full_outbox_blocks_or_drops_oldest() {
+    setup(&["OB-FULL-1", "OB-FULL-2", "OB-FULL-3"]).await;
+    set_outbox_policy(r#"{"max_entries":2,"warn_entries":1,"on_full":"block"}"#).unwrap();
+    put_attestation(&attestation("OB-FULL-1")).await.unwrap();
+    put_attestation(&attestation("OB-FULL-2")).await.unwrap();
+
+    let err = put_attestation(&attestation("OB-FULL-3"))
+        .await
+        .unwrap_err()
+        .as_string()
+        .unwrap();
+    assert_eq!(json(&err)["code"], "quota_exceeded");
+    // Blocked writes are not stored locally either
+    assert!(!exists_attestation("OB-FULL-3").await.unwrap());
+
+    set_outbox_policy(r#"{"max_entries":2,"on_full":"drop_oldest"}"#).unwrap();
+    put_attestation(&attestation("OB-FULL-3")).await.unwrap();
+    assert_eq!(json(&outbox_status().await.unwrap())["pending"], 2);
+
+    let all = uploader(&[]);
+    drain_outbox(all.function.clone()).await.unwrap();
+    assert_eq!(*all.calls.borrow(), vec!["put:OB-FULL-2", "put:OB-FULL-3"]);
+}
+
+#[wasm_bindgen_test]
+async fn disabled_outbox_queues_nothing() {
+    setup(&["OB-OFF-1"]).await;
+    enable_outbox(false);
+    put_attestation(&attestation("OB-OFF-1")).await.unwrap();
+
+    let status = json(&outbox_status().await.unwrap());
+    assert_eq!(status["pending"], 0);
+    assert_eq!(status["enabled"], false);
+}
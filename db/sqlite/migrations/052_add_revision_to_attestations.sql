@@ -0,0 +1,3 @@
+-- Storage revision for optimistic concurrency (put_if_revision).
+-- Existing rows start at revision 1; every update increments it.
+ALTER TABLE attestations ADD COLUMN revision INTEGER NOT NULL DEFAULT 1;
@@ -0,0 +1,102 @@
+-- Workspace namespaces for attestations.
+-- Several workspaces can share one database file; each attestation belongs to
+-- exactly one namespace and IDs are unique per namespace: the primary key
+-- becomes (namespace, id). Existing rows are backfilled into 'default'.
+--
+-- Junction tables carry the namespace too, so ON DELETE CASCADE follows the
+-- composite key. The new tables are built alongside the old ones and the old
+-- ones are dropped children-first, which keeps this safe with foreign_keys ON.
+
+CREATE TABLE attestations_new (
+    id TEXT NOT NULL,
+    subjects JSON NOT NULL,
+    predicates JSON NOT NULL,
+    contexts JSON NOT NULL,
+    actors JSON NOT NULL,
+    timestamp DATETIME NOT NULL,
+    source TEXT NOT NULL DEFAULT 'cli',
+    attributes JSON,
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    signature BLOB,
+    signer_did TEXT,
+    revision INTEGER NOT NULL DEFAULT 1,
+    namespace TEXT NOT NULL DEFAULT 'default',
+    PRIMARY KEY (namespace, id)
+);
+INSERT INTO attestations_new (id, subjects, predicates, contexts, actors, timestamp, source, attributes, created_at, signature, signer_did, revision, namespace)
+SELECT id, subjects, predicates, contexts, actors, timestamp, source, attributes, created_at, signature, signer_did, revision, 'default'
+FROM attestations ORDER BY rowid;
+
+-- attestation_predicates
+CREATE TABLE attestation_predicates_new (
+    attestation_id TEXT NOT NULL,
+    predicate TEXT NOT NULL COLLATE NOCASE,
+    namespace TEXT NOT NULL DEFAULT 'default',
+    FOREIGN KEY (namespace, attestation_id) REFERENCES attestations_new(namespace, id) ON DELETE CASCADE
+);
+INSERT INTO attestation_predicates_new (attestation_id, predicate, namespace)
+SELECT attestation_id, predicate, 'default' FROM attestation_predicates;
+DROP TABLE attestation_predicates;
+
+-- attestation_subjects
+CREATE TABLE attestation_subjects_new (
+    attestation_id TEXT NOT NULL,
+    subject TEXT NOT NULL COLLATE NOCASE,
+    namespace TEXT NOT NULL DEFAULT 'default',
+    FOREIGN KEY (namespace, attestation_id) REFERENCES attestations_new(namespace, id) ON DELETE CASCADE
+);
+INSERT INTO attestation_subjects_new (attestation_id, subject, namespace)
+SELECT attestation_id, subject, 'default' FROM attestation_subjects;
+DROP TABLE attestation_subjects;
+
+-- attestation_actors
+CREATE TABLE attestation_actors_new (
+    attestation_id TEXT NOT NULL,
+    actor TEXT NOT NULL COLLATE NOCASE,
+    namespace TEXT NOT NULL DEFAULT 'default',
+    FOREIGN KEY (namespace, attestation_id) REFERENCES attestations_new(namespace, id) ON DELETE CASCADE
+);
+INSERT INTO attestation_actors_new (attestation_id, actor, namespace)
+SELECT attestation_id, actor, 'default' FROM attestation_actors;
+DROP TABLE attestation_actors;
+
+-- attestation_contexts
+CREATE TABLE attestation_contexts_new (
+    attestation_id TEXT NOT NULL,
+    context TEXT NOT NULL COLLATE NOCASE,
+    namespace TEXT NOT NULL DEFAULT 'default',
+    FOREIGN KEY (namespace, attestation_id) REFERENCES attestations_new(namespace, id) ON DELETE CASCADE
+);
+INSERT INTO attestation_contexts_new (attestation_id, context, namespace)
+SELECT attestation_id, context, 'default' FROM attestation_contexts;
+DROP TABLE attestation_contexts;
+
+-- No children reference the old table any more; renaming rewrites the
+-- junction tables' foreign keys to point at the new one.
+DROP TABLE attestations;
+ALTER TABLE attestations_new RENAME TO attestations;
+ALTER TABLE attestation_predicates_new RENAME TO attestation_predicates;
+ALTER TABLE attestation_subjects_new RENAME TO attestation_subjects;
+ALTER TABLE attestation_actors_new RENAME TO attestation_actors;
+ALTER TABLE attestation_contexts_new RENAME TO attestation_contexts;
+
+CREATE INDEX idx_attestations_subjects ON attestations(json_extract(subjects, '$'));
+CREATE INDEX idx_attestations_predicates ON attestations(json_extract(predicates, '$'));
+CREATE INDEX idx_attestations_contexts ON attestations(json_extract(contexts, '$'));
+CREATE INDEX idx_attestations_timestamp ON attestations(timestamp DESC);
+CREATE INDEX idx_attestations_actors ON attestations(json_extract(actors, '$'));
+CREATE INDEX idx_attestations_actors_timestamp ON attestations(json_extract(actors, '$'), timestamp DESC);
+CREATE INDEX idx_attestations_actors_context_timestamp ON attestations(json_extract(actors, '$'), json_extract(contexts, '$'), timestamp DESC);
+CREATE INDEX idx_attestations_namespace_created ON attestations(namespace, created_at DESC);
+
+-- Value-first indexes keep unscoped lookups (Go query builder) fast and cover
+-- per-namespace vocabulary scans; (attestation_id, namespace) serves joins and cascades.
+CREATE INDEX idx_junc_predicate ON attestation_predicates(predicate, namespace);
+CREATE INDEX idx_junc_predicate_id ON attestation_predicates(attestation_id, namespace);
+CREATE INDEX idx_junc_subject ON attestation_subjects(subject, namespace);
+CREATE INDEX idx_junc_subject_id ON attestation_subjects(attestation_id, namespace);
+CREATE INDEX idx_junc_actor ON attestation_actors(actor, namespace);
+CREATE INDEX idx_junc_actor_id ON attestation_actors(attestation_id, namespace);
+CREATE INDEX idx_junc_actor_context ON attestation_actors(actor, attestation_id);
+CREATE INDEX idx_junc_context ON attestation_contexts(context, namespace);
+CREATE INDEX idx_junc_context_id ON attestation_contexts(attestation_id, namespace);
@@ -0,0 +1,13 @@
+-- What each named mirror has copied out of this store, for incremental runs.
+-- content_hash is the hash the attestation had when it was written to the
+-- destination; a later run skips unchanged attestations and only propagates
+-- deletions of copies that still carry that hash. run_id is the last run that
+-- wrote or confirmed the row.
+CREATE TABLE IF NOT EXISTS mirror_state (
+    namespace TEXT NOT NULL,
+    mirror TEXT NOT NULL,
+    attestation_id TEXT NOT NULL,
+    content_hash TEXT NOT NULL,
+    run_id TEXT NOT NULL,
+    PRIMARY KEY (namespace, mirror, attestation_id)
+);
@@ -0,0 +1,26 @@
+-- Append-only change log for time-travel queries (see qntx-sqlite history).
+-- Only namespaces listed in attestation_history_namespaces are recorded.
+-- Every put, update and delete appends one row: attestation holds the full
+-- attestation JSON as stored (NULL for deletes), applied_at the wall-clock
+-- time in Unix milliseconds, seq the global order of changes. 'baseline'
+-- rows capture what the namespace held when history was enabled.
+CREATE TABLE IF NOT EXISTS attestation_history (
+    seq INTEGER PRIMARY KEY AUTOINCREMENT,
+    namespace TEXT NOT NULL,
+    attestation_id TEXT NOT NULL,
+    operation TEXT NOT NULL CHECK (operation IN ('baseline', 'put', 'update', 'delete')),
+    attestation JSON,
+    applied_at INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS idx_attestation_history_id
+    ON attestation_history (namespace, attestation_id, seq);
+CREATE INDEX IF NOT EXISTS idx_attestation_history_applied
+    ON attestation_history (namespace, applied_at);
+
+-- horizon_ms: the earliest instant history can reconstruct; starts at the
+-- time history was enabled and moves forward when old entries are pruned.
+CREATE TABLE IF NOT EXISTS attestation_history_namespaces (
+    namespace TEXT PRIMARY KEY,
+    enabled_at INTEGER NOT NULL,
+    horizon_ms INTEGER NOT NULL
+);
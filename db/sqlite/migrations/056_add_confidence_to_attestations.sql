@@ -0,0 +1,3 @@
+-- Creator-assigned confidence (0.0-1.0), e.g. a detector score.
+-- NULL when the creator stated none; metadata, not part of content identity.
+ALTER TABLE attestations ADD COLUMN confidence REAL;
@@ -0,0 +1,23 @@
+-- Changefeed (see qntx-sqlite changes): every put, update and delete of an
+-- attestation appends one row under the next seq of its namespace. seq is
+-- dense and strictly increasing per namespace; content_hash is the written
+-- attestation's content hash (NULL for deletes), at the wall-clock time in
+-- Unix milliseconds. Pruning removes old rows only.
+CREATE TABLE IF NOT EXISTS attestation_changes (
+    namespace TEXT NOT NULL,
+    seq INTEGER NOT NULL,
+    operation TEXT NOT NULL CHECK (operation IN ('put', 'update', 'delete')),
+    attestation_id TEXT NOT NULL,
+    content_hash TEXT,
+    at INTEGER NOT NULL,
+    PRIMARY KEY (namespace, seq)
+);
+CREATE INDEX IF NOT EXISTS idx_attestation_changes_at
+    ON attestation_changes (namespace, at);
+
+-- Last seq assigned per namespace. Kept apart from attestation_changes so it
+-- survives pruning every event.
+CREATE TABLE IF NOT EXISTS attestation_change_seq (
+    namespace TEXT PRIMARY KEY,
+    latest_seq INTEGER NOT NULL
+);
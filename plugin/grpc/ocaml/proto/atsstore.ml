@@ -65,11 +65,25 @@ module rec Protocol : sig
       (**
 {%html:
 <p>did:key of the signing node (optional)</p>
+%}
+      *)
+
+      revision:int;
+      (**
+{%html:
+<p>Storage revision: 1 when first stored, +1 per update; excluded from content identity</p>
+%}
+      *)
+
+      confidence:float option;
+      (**
+{%html:
+<p>Creator-assigned confidence 0.0-1.0 (optional); excluded from content identity</p>
 %}
       *)
 
     }
-    val make: ?id:string -> ?subjects:string list -> ?predicates:string list -> ?contexts:string list -> ?actors:string list -> ?timestamp:int -> ?source:string -> ?attributes:Imported'modules.Struct.Google.Protobuf.Struct.t -> ?created_at:int -> ?signature:bytes -> ?signer_did:string -> unit -> t
+    val make: ?id:string -> ?subjects:string list -> ?predicates:string list -> ?contexts:string list -> ?actors:string list -> ?timestamp:int -> ?source:string -> ?attributes:Imported'modules.Struct.Google.Protobuf.Struct.t -> ?created_at:int -> ?signature:bytes -> ?signer_did:string -> ?revision:int -> ?confidence:float -> unit -> t
     (** Helper function to generate a message using default values *)
 
     val to_proto: t -> Runtime'.Writer.t
@@ -88,7 +102,7 @@ module rec Protocol : sig
     (** Fully qualified protobuf name of this message *)
 
     (**/**)
-    type make_t = ?id:string -> ?subjects:string list -> ?predicates:string list -> ?contexts:string list -> ?actors:string list -> ?timestamp:int -> ?source:string -> ?attributes:Imported'modules.Struct.Google.Protobuf.Struct.t -> ?created_at:int -> ?signature:bytes -> ?signer_did:string -> unit -> t
+    type make_t = ?id:string -> ?subjects:string list -> ?predicates:string list -> ?contexts:string list -> ?actors:string list -> ?timestamp:int -> ?source:string -> ?attributes:Imported'modules.Struct.Google.Protobuf.Struct.t -> ?created_at:int -> ?signature:bytes -> ?signer_did:string -> ?revision:int -> ?confidence:float -> unit -> t
     val merge: t -> t -> t
     val to_proto': Runtime'.Writer.t -> t -> unit
     val from_proto_exn: Runtime'.Reader.t -> t
@@ -667,11 +681,25 @@ end = struct
       (**
 {%html:
 <p>did:key of the signing node (optional)</p>
+%}
+      *)
+
+      revision:int;
+      (**
+{%html:
+<p>Storage revision: 1 when first stored, +1 per update; excluded from content identity</p>
+%}
+      *)
+
+      confidence:float option;
+      (**
+{%html:
+<p>Creator-assigned confidence 0.0-1.0 (optional); excluded from content identity</p>
 %}
       *)
 
     }
-    val make: ?id:string -> ?subjects:string list -> ?predicates:string list -> ?contexts:string list -> ?actors:string list -> ?timestamp:int -> ?source:string -> ?attributes:Imported'modules.Struct.Google.Protobuf.Struct.t -> ?created_at:int -> ?signature:bytes -> ?signer_did:string -> unit -> t
+    val make: ?id:string -> ?subjects:string list -> ?predicates:string list -> ?contexts:string list -> ?actors:string list -> ?timestamp:int -> ?source:string -> ?attributes:Imported'modules.Struct.Google.Protobuf.Struct.t -> ?created_at:int -> ?signature:bytes -> ?signer_did:string -> ?revision:int -> ?confidence:float -> unit -> t
     (** Helper function to generate a message using default values *)
 
     val to_proto: t -> Runtime'.Writer.t
@@ -690,7 +718,7 @@ end = struct
     (** Fully qualified protobuf name of this message *)
 
     (**/**)
-    type make_t = ?id:string -> ?subjects:string list -> ?predicates:string list -> ?contexts:string list -> ?actors:string list -> ?timestamp:int -> ?source:string -> ?attributes:Imported'modules.Struct.Google.Protobuf.Struct.t -> ?created_at:int -> ?signature:bytes -> ?signer_did:string -> unit -> t
+    type make_t = ?id:string -> ?subjects:string list -> ?predicates:string list -> ?contexts:string list -> ?actors:string list -> ?timestamp:int -> ?source:string -> ?attributes:Imported'modules.Struct.Google.Protobuf.Struct.t -> ?created_at:int -> ?signature:bytes -> ?signer_did:string -> ?revision:int -> ?confidence:float -> unit -> t
     val merge: t -> t -> t
     val to_proto': Runtime'.Writer.t -> t -> unit
     val from_proto_exn: Runtime'.Reader.t -> t
@@ -711,9 +739,11 @@ end = struct
       created_at:int;
       signature:bytes;
       signer_did:string;
+      revision:int;
+      confidence:float option;
     }
-    type make_t = ?id:string -> ?subjects:string list -> ?predicates:string list -> ?contexts:string list -> ?actors:string list -> ?timestamp:int -> ?source:string -> ?attributes:Imported'modules.Struct.Google.Protobuf.Struct.t -> ?created_at:int -> ?signature:bytes -> ?signer_did:string -> unit -> t
-    let make ?(id = {||}) ?(subjects = []) ?(predicates = []) ?(contexts = []) ?(actors = []) ?(timestamp = 0) ?(source = {||}) ?attributes ?(created_at = 0) ?(signature = (Bytes.of_string {||})) ?(signer_did = {||}) () = { id; subjects; predicates; contexts; actors; timestamp; source; attributes; created_at; signature; signer_did }
+    type make_t = ?id:string -> ?subjects:string list -> ?predicates:string list -> ?contexts:string list -> ?actors:string list -> ?timestamp:int -> ?source:string -> ?attributes:Imported'modules.Struct.Google.Protobuf.Struct.t -> ?created_at:int -> ?signature:bytes -> ?signer_did:string -> ?revision:int -> ?confidence:float -> unit -> t
+    let make ?(id = {||}) ?(subjects = []) ?(predicates = []) ?(contexts = []) ?(actors = []) ?(timestamp = 0) ?(source = {||}) ?attributes ?(created_at = 0) ?(signature = (Bytes.of_string {||})) ?(signer_did = {||}) ?(revision = 0) ?confidence () = { id; subjects; predicates; contexts; actors; timestamp; source; attributes; created_at; signature; signer_did; revision; confidence }
     let merge =
     let merge_id = Runtime'.Merge.merge Runtime'.Spec.( basic ((1, "id", "id"), string, ({||})) ) in
     let merge_subjects = Runtime'.Merge.merge Runtime'.Spec.( repeated ((2, "subjects", "subjects"), string, not_packed) ) in
@@ -726,6 +756,8 @@ end = struct
     let merge_created_at = Runtime'.Merge.merge Runtime'.Spec.( basic ((9, "created_at", "createdAt"), int64_int, (0)) ) in
     let merge_signature = Runtime'.Merge.merge Runtime'.Spec.( basic ((10, "signature", "signature"), bytes, ((Bytes.of_string {||}))) ) in
     let merge_signer_did = Runtime'.Merge.merge Runtime'.Spec.( basic ((11, "signer_did", "signerDid"), string, ({||})) ) in
+    let merge_revision = Runtime'.Merge.merge Runtime'.Spec.( basic ((12, "revision", "revision"), uint64_int, (0)) ) in
+    let merge_confidence = Runtime'.Merge.merge Runtime'.Spec.( basic_opt ((13, "confidence", "confidence"), float) ) in
     fun t1 t2 -> {
     	id = (merge_id t1.id t2.id);
     	subjects = (merge_subjects t1.subjects t2.subjects);
@@ -738,22 +770,24 @@ end = struct
     	created_at = (merge_created_at t1.created_at t2.created_at);
     	signature = (merge_signature t1.signature t2.signature);
     	signer_did = (merge_signer_did t1.signer_did t2.signer_did);
+    	revision = (merge_revision t1.revision t2.revision);
+    	confidence = (merge_confidence t1.confidence t2.confidence);
      }
-    let spec () = Runtime'.Spec.( basic ((1, "id", "id"), string, ({||})) ^:: repeated ((2, "subjects", "subjects"), string, not_packed) ^:: repeated ((3, "predicates", "predicates"), string, not_packed) ^:: repeated ((4, "contexts", "contexts"), string, not_packed) ^:: repeated ((5, "actors", "actors"), string, not_packed) ^:: basic ((6, "timestamp", "timestamp"), int64_int, (0)) ^:: basic ((7, "source", "source"), string, ({||})) ^:: basic_opt ((8, "attributes", "attributes"), (message (module Imported'modules.Struct.Google.Protobuf.Struct))) ^:: basic ((9, "created_at", "createdAt"), int64_int, (0)) ^:: basic ((10, "signature", "signature"), bytes, ((Bytes.of_string {||}))) ^:: basic ((11, "signer_did", "signerDid"), string, ({||})) ^:: nil )
+    let spec () = Runtime'.Spec.( basic ((1, "id", "id"), string, ({||})) ^:: repeated ((2, "subjects", "subjects"), string, not_packed) ^:: repeated ((3, "predicates", "predicates"), string, not_packed) ^:: repeated ((4, "contexts", "contexts"), string, not_packed) ^:: repeated ((5, "actors", "actors"), string, not_packed) ^:: basic ((6, "timestamp", "timestamp"), int64_int, (0)) ^:: basic ((7, "source", "source"), string, ({||})) ^:: basic_opt ((8, "attributes", "attributes"), (message (module Imported'modules.Struct.Google.Protobuf.Struct))) ^:: basic ((9, "created_at", "createdAt"), int64_int, (0)) ^:: basic ((10, "signature", "signature"), bytes, ((Bytes.of_string {||}))) ^:: basic ((11, "signer_did", "signerDid"), string, ({||})) ^:: basic ((12, "revision", "revision"), uint64_int, (0)) ^:: basic_opt ((13, "confidence", "confidence"), float) ^:: nil )
     let to_proto' =
       let serialize = Runtime'.apply_lazy (fun () -> Runtime'.Serialize.serialize (spec ())) in
-      fun writer { id; subjects; predicates; contexts; actors; timestamp; source; attributes; created_at; signature; signer_did } -> serialize writer id subjects predicates contexts actors timestamp source attributes created_at signature signer_did
+      fun writer { id; subjects; predicates; contexts; actors; timestamp; source; attributes; created_at; signature; signer_did; revision; confidence } -> serialize writer id subjects predicates contexts actors timestamp source attributes created_at signature signer_did revision confidence
 
     let to_proto t = let writer = Runtime'.Writer.init () in to_proto' writer t; writer
     let from_proto_exn =
-      let constructor id subjects predicates contexts actors timestamp source attributes created_at signature signer_did = { id; subjects; predicates; contexts; actors; timestamp; source; attributes; created_at; signature; signer_did } in
+      let constructor id subjects predicates contexts actors timestamp source attributes created_at signature signer_did revision confidence = { id; subjects; predicates; contexts; actors; timestamp; source; attributes; created_at; signature; signer_did; revision; confidence } in
       Runtime'.apply_lazy (fun () -> Runtime'.Deserialize.deserialize (spec ()) constructor)
     let from_proto writer = Runtime'.Result.catch (fun () -> from_proto_exn writer)
     let to_json options =
       let serialize = Runtime'.Serialize_json.serialize ~message_name:(name ()) (spec ()) options in
-      fun { id; subjects; predicates; contexts; actors; timestamp; source; attributes; created_at; signature; signer_did } -> serialize id subjects predicates contexts actors timestamp source attributes created_at signature signer_did
+      fun { id; subjects; predicates; contexts; actors; timestamp; source; attributes; created_at; signature; signer_did; revision; confidence } -> serialize id subjects predicates contexts actors timestamp source attributes created_at signature signer_did revision confidence
     let from_json_exn =
-      let constructor id subjects predicates contexts actors timestamp source attributes created_at signature signer_did = { id; subjects; predicates; contexts; actors; timestamp; source; attributes; created_at; signature; signer_did } in
+      let constructor id subjects predicates contexts actors timestamp source attributes created_at signature signer_did revision confidence = { id; subjects; predicates; contexts; actors; timestamp; source; attributes; created_at; signature; signer_did; revision; confidence } in
       Runtime'.apply_lazy (fun () -> Runtime'.Deserialize_json.deserialize ~message_name:(name ()) (spec ()) constructor)
     let from_json json = Runtime'.Result.catch (fun () -> from_json_exn json)
   end
@@ -61,8 +61,43 @@ module rec Protocol : sig
       description:string;
       author:string;
       license:string;
+      commit_hash:string;
+      (**
+{%html:
+<p>Commit the plugin binary was built from (&quot;unknown&quot; when not provided at build time)</p>
+%}
+      *)
+
+      job_types:string list;
+      (**
+{%html:
+<p>Job handler names the plugin can execute</p>
+%}
+      *)
+
+      http_routes:string list;
+      (**
+{%html:
+<p>HTTP routes served through HandleHTTP, as &quot;METHOD /path&quot;</p>
+%}
+      *)
+
+      uptime_seconds:int;
+      (**
+{%html:
+<p>Seconds since the plugin process started serving</p>
+%}
+      *)
+
+      extras:(string * string) list;
+      (**
+{%html:
+<p>Plugin-specific descriptive fields</p>
+%}
+      *)
+
     }
-    val make: ?name:string -> ?version:string -> ?qntx_version:string -> ?description:string -> ?author:string -> ?license:string -> unit -> t
+    val make: ?name:string -> ?version:string -> ?qntx_version:string -> ?description:string -> ?author:string -> ?license:string -> ?commit_hash:string -> ?job_types:string list -> ?http_routes:string list -> ?uptime_seconds:int -> ?extras:(string * string) list -> unit -> t
     (** Helper function to generate a message using default values *)
 
     val to_proto: t -> Runtime'.Writer.t
@@ -81,7 +116,7 @@ module rec Protocol : sig
     (** Fully qualified protobuf name of this message *)
 
     (**/**)
-    type make_t = ?name:string -> ?version:string -> ?qntx_version:string -> ?description:string -> ?author:string -> ?license:string -> unit -> t
+    type make_t = ?name:string -> ?version:string -> ?qntx_version:string -> ?description:string -> ?author:string -> ?license:string -> ?commit_hash:string -> ?job_types:string list -> ?http_routes:string list -> ?uptime_seconds:int -> ?extras:(string * string) list -> unit -> t
     val merge: t -> t -> t
     val to_proto': Runtime'.Writer.t -> t -> unit
     val from_proto_exn: Runtime'.Reader.t -> t
@@ -1492,8 +1527,43 @@ end = struct
       description:string;
       author:string;
       license:string;
+      commit_hash:string;
+      (**
+{%html:
+<p>Commit the plugin binary was built from (&quot;unknown&quot; when not provided at build time)</p>
+%}
+      *)
+
+      job_types:string list;
+      (**
+{%html:
+<p>Job handler names the plugin can execute</p>
+%}
+      *)
+
+      http_routes:string list;
+      (**
+{%html:
+<p>HTTP routes served through HandleHTTP, as &quot;METHOD /path&quot;</p>
+%}
+      *)
+
+      uptime_seconds:int;
+      (**
+{%html:
+<p>Seconds since the plugin process started serving</p>
+%}
+      *)
+
+      extras:(string * string) list;
+      (**
+{%html:
+<p>Plugin-specific descriptive fields</p>
+%}
+      *)
+
     }
-    val make: ?name:string -> ?version:string -> ?qntx_version:string -> ?description:string -> ?author:string -> ?license:string -> unit -> t
+    val make: ?name:string -> ?version:string -> ?qntx_version:string -> ?description:string -> ?author:string -> ?license:string -> ?commit_hash:string -> ?job_types:string list -> ?http_routes:string list -> ?uptime_seconds:int -> ?extras:(string * string) list -> unit -> t
     (** Helper function to generate a message using default values *)
 
     val to_proto: t -> Runtime'.Writer.t
@@ -1512,7 +1582,7 @@ end = struct
     (** Fully qualified protobuf name of this message *)
 
     (**/**)
-    type make_t = ?name:string -> ?version:string -> ?qntx_version:string -> ?description:string -> ?author:string -> ?license:string -> unit -> t
+    type make_t = ?name:string -> ?version:string -> ?qntx_version:string -> ?description:string -> ?author:string -> ?license:string -> ?commit_hash:string -> ?job_types:string list -> ?http_routes:string list -> ?uptime_seconds:int -> ?extras:(string * string) list -> unit -> t
     val merge: t -> t -> t
     val to_proto': Runtime'.Writer.t -> t -> unit
     val from_proto_exn: Runtime'.Reader.t -> t
@@ -1528,9 +1598,14 @@ end = struct
       description:string;
       author:string;
       license:string;
+      commit_hash:string;
+      job_types:string list;
+      http_routes:string list;
+      uptime_seconds:int;
+      extras:(string * string) list;
     }
-    type make_t = ?name:string -> ?version:string -> ?qntx_version:string -> ?description:string -> ?author:string -> ?license:string -> unit -> t
-    let make ?(name = {||}) ?(version = {||}) ?(qntx_version = {||}) ?(description = {||}) ?(author = {||}) ?(license = {||}) () = { name; version; qntx_version; description; author; license }
+    type make_t = ?name:string -> ?version:string -> ?qntx_version:string -> ?description:string -> ?author:string -> ?license:string -> ?commit_hash:string -> ?job_types:string list -> ?http_routes:string list -> ?uptime_seconds:int -> ?extras:(string * string) list -> unit -> t
+    let make ?(name = {||}) ?(version = {||}) ?(qntx_version = {||}) ?(description = {||}) ?(author = {||}) ?(license = {||}) ?(commit_hash = {||}) ?(job_types = []) ?(http_routes = []) ?(uptime_seconds = 0) ?(extras = []) () = { name; version; qntx_version; description; author; license; commit_hash; job_types; http_routes; uptime_seconds; extras }
     let merge =
     let merge_name = Runtime'.Merge.merge Runtime'.Spec.( basic ((1, "name", "name"), string, ({||})) ) in
     let merge_version = Runtime'.Merge.merge Runtime'.Spec.( basic ((2, "version", "version"), string, ({||})) ) in
@@ -1538,6 +1613,11 @@ end = struct
     let merge_description = Runtime'.Merge.merge Runtime'.Spec.( basic ((4, "description", "description"), string, ({||})) ) in
     let merge_author = Runtime'.Merge.merge Runtime'.Spec.( basic ((5, "author", "author"), string, ({||})) ) in
     let merge_license = Runtime'.Merge.merge Runtime'.Spec.( basic ((6, "license", "license"), string, ({||})) ) in
+    let merge_commit_hash = Runtime'.Merge.merge Runtime'.Spec.( basic ((7, "commit_hash", "commitHash"), string, ({||})) ) in
+    let merge_job_types = Runtime'.Merge.merge Runtime'.Spec.( repeated ((8, "job_types", "jobTypes"), string, not_packed) ) in
+    let merge_http_routes = Runtime'.Merge.merge Runtime'.Spec.( repeated ((9, "http_routes", "httpRoutes"), string, not_packed) ) in
+    let merge_uptime_seconds = Runtime'.Merge.merge Runtime'.Spec.( basic ((10, "uptime_seconds", "uptimeSeconds"), uint64_int, (0)) ) in
+    let merge_extras = Runtime'.Merge.merge Runtime'.Spec.( map ((11, "extras", "extras"), (string, basic ((2, "value", "value"), string, ({||})))) ) in
     fun t1 t2 -> {
     	name = (merge_name t1.name t2.name);
     	version = (merge_version t1.version t2.version);
@@ -1545,22 +1625,27 @@ end = struct
     	description = (merge_description t1.description t2.description);
     	author = (merge_author t1.author t2.author);
     	license = (merge_license t1.license t2.license);
+    	commit_hash = (merge_commit_hash t1.commit_hash t2.commit_hash);
+    	job_types = (merge_job_types t1.job_types t2.job_types);
+    	http_routes = (merge_http_routes t1.http_routes t2.http_routes);
+    	uptime_seconds = (merge_uptime_seconds t1.uptime_seconds t2.uptime_seconds);
+    	extras = (merge_extras t1.extras t2.extras);
      }
-    let spec () = Runtime'.Spec.( basic ((1, "name", "name"), string, ({||})) ^:: basic ((2, "version", "version"), string, ({||})) ^:: basic ((3, "qntx_version", "qntxVersion"), string, ({||})) ^:: basic ((4, "description", "description"), string, ({||})) ^:: basic ((5, "author", "author"), string, ({||})) ^:: basic ((6, "license", "license"), string, ({||})) ^:: nil )
+    let spec () = Runtime'.Spec.( basic ((1, "name", "name"), string, ({||})) ^:: basic ((2, "version", "version"), string, ({||})) ^:: basic ((3, "qntx_version", "qntxVersion"), string, ({||})) ^:: basic ((4, "description", "description"), string, ({||})) ^:: basic ((5, "author", "author"), string, ({||})) ^:: basic ((6, "license", "license"), string, ({||})) ^:: basic ((7, "commit_hash", "commitHash"), string, ({||})) ^:: repeated ((8, "job_types", "jobTypes"), string, not_packed) ^:: repeated ((9, "http_routes", "httpRoutes"), string, not_packed) ^:: basic ((10, "uptime_seconds", "uptimeSeconds"), uint64_int, (0)) ^:: map ((11, "extras", "extras"), (string, basic ((2, "value", "value"), string, ({||})))) ^:: nil )
     let to_proto' =
       let serialize = Runtime'.apply_lazy (fun () -> Runtime'.Serialize.serialize (spec ())) in
-      fun writer { name; version; qntx_version; description; author; license } -> serialize writer name version qntx_version description author license
+      fun writer { name; version; qntx_version; description; author; license; commit_hash; job_types; http_routes; uptime_seconds; extras } -> serialize writer name version qntx_version description author license commit_hash job_types http_routes uptime_seconds extras
 
     let to_proto t = let writer = Runtime'.Writer.init () in to_proto' writer t; writer
     let from_proto_exn =
-      let constructor name version qntx_version description author license = { name; version; qntx_version; description; author; license } in
+      let constructor name version qntx_version description author license commit_hash job_types http_routes uptime_seconds extras = { name; version; qntx_version; description; author; license; commit_hash; job_types; http_routes; uptime_seconds; extras } in
       Runtime'.apply_lazy (fun () -> Runtime'.Deserialize.deserialize (spec ()) constructor)
     let from_proto writer = Runtime'.Result.catch (fun () -> from_proto_exn writer)
     let to_json options =
       let serialize = Runtime'.Serialize_json.serialize ~message_name:(name ()) (spec ()) options in
-      fun { name; version; qntx_version; description; author; license } -> serialize name version qntx_version description author license
+      fun { name; version; qntx_version; description; author; license; commit_hash; job_types; http_routes; uptime_seconds; extras } -> serialize name version qntx_version description author license commit_hash job_types http_routes uptime_seconds extras
     let from_json_exn =
-      let constructor name version qntx_version description author license = { name; version; qntx_version; description; author; license } in
+      let constructor name version qntx_version description author license commit_hash job_types http_routes uptime_seconds extras = { name; version; qntx_version; description; author; license; commit_hash; job_types; http_routes; uptime_seconds; extras } in
       Runtime'.apply_lazy (fun () -> Runtime'.Deserialize_json.deserialize ~message_name:(name ()) (spec ()) constructor)
     let from_json json = Runtime'.Result.catch (fun () -> from_json_exn json)
   end
@@ -36,6 +36,8 @@ type Attestation struct {
 	CreatedAt     int64                  `protobuf:"varint,9,opt,name=created_at,json=createdAt,proto3" json:"created_at,omitempty"` // Unix timestamp in milliseconds
 	Signature     []byte                 `protobuf:"bytes,10,opt,name=signature,proto3" json:"signature,omitempty"`                  // Ed25519 signature over canonical JSON (optional)
 	SignerDid     string                 `protobuf:"bytes,11,opt,name=signer_did,json=signerDid,proto3" json:"signer_did,omitempty"` // did:key of the signing node (optional)
+	Revision      uint64                 `protobuf:"varint,12,opt,name=revision,proto3" json:"revision,omitempty"`                   // Storage revision: 1 when first stored, +1 per update; excluded from content identity
+	Confidence    *float32               `protobuf:"fixed32,13,opt,name=confidence,proto3,oneof" json:"confidence,omitempty"`        // Creator-assigned confidence 0.0-1.0 (optional); excluded from content identity
 	unknownFields protoimpl.UnknownFields
 	sizeCache     protoimpl.SizeCache
 }
@@ -147,6 +149,20 @@ func (x *Attestation) GetSignerDid() string {
 	return ""
 }
 
+func (x *Attestation) GetRevision() uint64 {
+	if x != nil {
+		return x.Revision
+	}
+	return 0
+}
+
+func (x *Attestation) GetConfidence() float32 {
+	if x != nil && x.Confidence != nil {
+		return *x.Confidence
+	}
+	return 0
+}
+
 // AttestationCommand is used for creating attestations
 type AttestationCommand struct {
 	state         protoimpl.MessageState `protogen:"open.v1"`
@@ -881,7 +897,7 @@ var File_plugin_grpc_protocol_atsstore_proto protoreflect.FileDescriptor
 
 const file_plugin_grpc_protocol_atsstore_proto_rawDesc = "" +
 	"\n" +
-	"#plugin/grpc/protocol/atsstore.proto\x12\bprotocol\x1a\x1cgoogle/protobuf/struct.proto\"\xd8\x02\n" +
+	"#plugin/grpc/protocol/atsstore.proto\x12\bprotocol\x1a\x1cgoogle/protobuf/struct.proto\"\xa8\x03\n" +
 	"\vAttestation\x12\x0e\n" +
 	"\x02id\x18\x01 \x01(\tR\x02id\x12\x1a\n" +
 	"\bsubjects\x18\x02 \x03(\tR\bsubjects\x12\x1e\n" +
@@ -900,7 +916,12 @@ const file_plugin_grpc_protocol_atsstore_proto_rawDesc = "" +
 	"\tsignature\x18\n" +
 	" \x01(\fR\tsignature\x12\x1d\n" +
 	"\n" +
-	"signer_did\x18\v \x01(\tR\tsignerDid\"\xad\x02\n" +
+	"signer_did\x18\v \x01(\tR\tsignerDid\x12\x1a\n" +
+	"\brevision\x18\f \x01(\x04R\brevision\x12#\n" +
+	"\n" +
+	"confidence\x18\r \x01(\x02H\x00R\n" +
+	"confidence\x88\x01\x01B\r\n" +
+	"\v_confidence\"\xad\x02\n" +
 	"\x12AttestationCommand\x12\x1a\n" +
 	"\bsubjects\x18\x01 \x03(\tR\bsubjects\x12\x1e\n" +
 	"\n" +
@@ -1037,6 +1058,7 @@ func file_plugin_grpc_protocol_atsstore_proto_init() {
 	if File_plugin_grpc_protocol_atsstore_proto != nil {
 		return
 	}
+	file_plugin_grpc_protocol_atsstore_proto_msgTypes[0].OneofWrappers = []any{}
 	file_plugin_grpc_protocol_atsstore_proto_msgTypes[1].OneofWrappers = []any{}
 	file_plugin_grpc_protocol_atsstore_proto_msgTypes[2].OneofWrappers = []any{}
 	type x struct{}
@@ -123,6 +123,16 @@ type MetadataResponse struct {
 	Description   string                 `protobuf:"bytes,4,opt,name=description,proto3" json:"description,omitempty"`
 	Author        string                 `protobuf:"bytes,5,opt,name=author,proto3" json:"author,omitempty"`
 	License       string                 `protobuf:"bytes,6,opt,name=license,proto3" json:"license,omitempty"`
+	// Commit the plugin binary was built from ("unknown" when not provided at build time)
+	CommitHash string `protobuf:"bytes,7,opt,name=commit_hash,json=commitHash,proto3" json:"commit_hash,omitempty"`
+	// Job handler names the plugin can execute
+	JobTypes []string `protobuf:"bytes,8,rep,name=job_types,json=jobTypes,proto3" json:"job_types,omitempty"`
+	// HTTP routes served through HandleHTTP, as "METHOD /path"
+	HttpRoutes []string `protobuf:"bytes,9,rep,name=http_routes,json=httpRoutes,proto3" json:"http_routes,omitempty"`
+	// Seconds since the plugin process started serving
+	UptimeSeconds uint64 `protobuf:"varint,10,opt,name=uptime_seconds,json=uptimeSeconds,proto3" json:"uptime_seconds,omitempty"`
+	// Plugin-specific descriptive fields
+	Extras        map[string]string `protobuf:"bytes,11,rep,name=extras,proto3" json:"extras,omitempty" protobuf_key:"bytes,1,opt,name=key" protobuf_val:"bytes,2,opt,name=value"`
 	unknownFields protoimpl.UnknownFields
 	sizeCache     protoimpl.SizeCache
 }
@@ -199,6 +209,41 @@ func (x *MetadataResponse) GetLicense() string {
 	return ""
 }
 
+func (x *MetadataResponse) GetCommitHash() string {
+	if x != nil {
+		return x.CommitHash
+	}
+	return ""
+}
+
+func (x *MetadataResponse) GetJobTypes() []string {
+	if x != nil {
+		return x.JobTypes
+	}
+	return nil
+}
+
+func (x *MetadataResponse) GetHttpRoutes() []string {
+	if x != nil {
+		return x.HttpRoutes
+	}
+	return nil
+}
+
+func (x *MetadataResponse) GetUptimeSeconds() uint64 {
+	if x != nil {
+		return x.UptimeSeconds
+	}
+	return 0
+}
+
+func (x *MetadataResponse) GetExtras() map[string]string {
+	if x != nil {
+		return x.Extras
+	}
+	return nil
+}
+
 type InitializeRequest struct {
 	state protoimpl.MessageState `protogen:"open.v1"`
 	// ats_store_endpoint: gRPC endpoint for [ATSStoreService](https://qntx.sbvh.nl/api/grpc-atsstore.html)
@@ -1693,14 +1738,25 @@ var File_plugin_grpc_protocol_domain_proto protoreflect.FileDescriptor
 const file_plugin_grpc_protocol_domain_proto_rawDesc = "" +
 	"\n" +
 	"!plugin/grpc/protocol/domain.proto\x12\bprotocol\"\a\n" +
-	"\x05Empty\"\xb7\x01\n" +
+	"\x05Empty\"\xb8\x03\n" +
 	"\x10MetadataResponse\x12\x12\n" +
 	"\x04name\x18\x01 \x01(\tR\x04name\x12\x18\n" +
 	"\aversion\x18\x02 \x01(\tR\aversion\x12!\n" +
 	"\fqntx_version\x18\x03 \x01(\tR\vqntxVersion\x12 \n" +
 	"\vdescription\x18\x04 \x01(\tR\vdescription\x12\x16\n" +
 	"\x06author\x18\x05 \x01(\tR\x06author\x12\x18\n" +
-	"\alicense\x18\x06 \x01(\tR\alicense\"\xe5\x04\n" +
+	"\alicense\x18\x06 \x01(\tR\alicense\x12\x1f\n" +
+	"\vcommit_hash\x18\a \x01(\tR\n" +
+	"commitHash\x12\x1b\n" +
+	"\tjob_types\x18\b \x03(\tR\bjobTypes\x12\x1f\n" +
+	"\vhttp_routes\x18\t \x03(\tR\n" +
+	"httpRoutes\x12%\n" +
+	"\x0euptime_seconds\x18\n" +
+	" \x01(\x04R\ruptimeSeconds\x12>\n" +
+	"\x06extras\x18\v \x03(\v2&.protocol.MetadataResponse.ExtrasEntryR\x06extras\x1a9\n" +
+	"\vExtrasEntry\x12\x10\n" +
+	"\x03key\x18\x01 \x01(\tR\x03key\x12\x14\n" +
+	"\x05value\x18\x02 \x01(\tR\x05value:\x028\x01\"\xe5\x04\n" +
 	"\x11InitializeRequest\x12,\n" +
 	"\x12ats_store_endpoint\x18\x01 \x01(\tR\x10atsStoreEndpoint\x12%\n" +
 	"\x0equeue_endpoint\x18\x02 \x01(\tR\rqueueEndpoint\x12\x1d\n" +
@@ -1868,7 +1924,7 @@ func file_plugin_grpc_protocol_domain_proto_rawDescGZIP() []byte {
 }
 
 var file_plugin_grpc_protocol_domain_proto_enumTypes = make([]protoimpl.EnumInfo, 1)
-var file_plugin_grpc_protocol_domain_proto_msgTypes = make([]protoimpl.MessageInfo, 25)
+var file_plugin_grpc_protocol_domain_proto_msgTypes = make([]protoimpl.MessageInfo, 26)
 var file_plugin_grpc_protocol_domain_proto_goTypes = []any{
 	(WebSocketMessage_Type)(0),   // 0: protocol.WebSocketMessage.Type
 	(*Empty)(nil),                // 1: protocol.Empty
@@ -1892,50 +1948,52 @@ var file_plugin_grpc_protocol_domain_proto_goTypes = []any{
 	(*GlyphDef)(nil),             // 19: protocol.GlyphDef
 	(*ParseAxQueryRequest)(nil),  // 20: protocol.ParseAxQueryRequest
 	(*ParseAxQueryResponse)(nil), // 21: protocol.ParseAxQueryResponse
-	nil,                          // 22: protocol.InitializeRequest.ConfigEntry
-	nil,                          // 23: protocol.WebSocketMessage.HeadersEntry
-	nil,                          // 24: protocol.HealthResponse.DetailsEntry
-	nil,                          // 25: protocol.ConfigSchemaResponse.FieldsEntry
+	nil,                          // 22: protocol.MetadataResponse.ExtrasEntry
+	nil,                          // 23: protocol.InitializeRequest.ConfigEntry
+	nil,                          // 24: protocol.WebSocketMessage.HeadersEntry
+	nil,                          // 25: protocol.HealthResponse.DetailsEntry
+	nil,                          // 26: protocol.ConfigSchemaResponse.FieldsEntry
 }
 var file_plugin_grpc_protocol_domain_proto_depIdxs = []int32{
-	22, // 0: protocol.InitializeRequest.config:type_name -> protocol.InitializeRequest.ConfigEntry
-	6,  // 1: protocol.HTTPRequest.headers:type_name -> protocol.HTTPHeader
-	6,  // 2: protocol.HTTPResponse.headers:type_name -> protocol.HTTPHeader
-	0,  // 3: protocol.WebSocketMessage.type:type_name -> protocol.WebSocketMessage.Type
-	23, // 4: protocol.WebSocketMessage.headers:type_name -> protocol.WebSocketMessage.HeadersEntry
-	24, // 5: protocol.HealthResponse.details:type_name -> protocol.HealthResponse.DetailsEntry
-	25, // 6: protocol.ConfigSchemaResponse.fields:type_name -> protocol.ConfigSchemaResponse.FieldsEntry
-	11, // 7: protocol.InitializeResponse.schedules:type_name -> protocol.ScheduleInfo
-	14, // 8: protocol.InitializeResponse.watchers:type_name -> protocol.WatcherRegistration
-	13, // 9: protocol.InitializeResponse.http_routes:type_name -> protocol.RouteInfo
-	17, // 10: protocol.ExecuteJobResponse.log_entries:type_name -> protocol.JobLogEntry
-	19, // 11: protocol.GlyphDefResponse.glyphs:type_name -> protocol.GlyphDef
-	10, // 12: protocol.ConfigSchemaResponse.FieldsEntry.value:type_name -> protocol.ConfigFieldSchema
-	1,  // 13: protocol.DomainPluginService.Metadata:input_type -> protocol.Empty
-	3,  // 14: protocol.DomainPluginService.Initialize:input_type -> protocol.InitializeRequest
-	1,  // 15: protocol.DomainPluginService.Shutdown:input_type -> protocol.Empty
-	4,  // 16: protocol.DomainPluginService.HandleHTTP:input_type -> protocol.HTTPRequest
-	7,  // 17: protocol.DomainPluginService.HandleWebSocket:input_type -> protocol.WebSocketMessage
-	1,  // 18: protocol.DomainPluginService.Health:input_type -> protocol.Empty
-	1,  // 19: protocol.DomainPluginService.ConfigSchema:input_type -> protocol.Empty
-	1,  // 20: protocol.DomainPluginService.RegisterGlyphs:input_type -> protocol.Empty
-	15, // 21: protocol.DomainPluginService.ExecuteJob:input_type -> protocol.ExecuteJobRequest
-	20, // 22: protocol.DomainPluginService.ParseAxQuery:input_type -> protocol.ParseAxQueryRequest
-	2,  // 23: protocol.DomainPluginService.Metadata:output_type -> protocol.MetadataResponse
-	12, // 24: protocol.DomainPluginService.Initialize:output_type -> protocol.InitializeResponse
-	1,  // 25: protocol.DomainPluginService.Shutdown:output_type -> protocol.Empty
-	5,  // 26: protocol.DomainPluginService.HandleHTTP:output_type -> protocol.HTTPResponse
-	7,  // 27: protocol.DomainPluginService.HandleWebSocket:output_type -> protocol.WebSocketMessage
-	8,  // 28: protocol.DomainPluginService.Health:output_type -> protocol.HealthResponse
-	9,  // 29: protocol.DomainPluginService.ConfigSchema:output_type -> protocol.ConfigSchemaResponse
-	18, // 30: protocol.DomainPluginService.RegisterGlyphs:output_type -> protocol.GlyphDefResponse
-	16, // 31: protocol.DomainPluginService.ExecuteJob:output_type -> protocol.ExecuteJobResponse
-	21, // 32: protocol.DomainPluginService.ParseAxQuery:output_type -> protocol.ParseAxQueryResponse
-	23, // [23:33] is the sub-list for method output_type
-	13, // [13:23] is the sub-list for method input_type
-	13, // [13:13] is the sub-list for extension type_name
-	13, // [13:13] is the sub-list for extension extendee
-	0,  // [0:13] is the sub-list for field type_name
+	22, // 0: protocol.MetadataResponse.extras:type_name -> protocol.MetadataResponse.ExtrasEntry
+	23, // 1: protocol.InitializeRequest.config:type_name -> protocol.InitializeRequest.ConfigEntry
+	6,  // 2: protocol.HTTPRequest.headers:type_name -> protocol.HTTPHeader
+	6,  // 3: protocol.HTTPResponse.headers:type_name -> protocol.HTTPHeader
+	0,  // 4: protocol.WebSocketMessage.type:type_name -> protocol.WebSocketMessage.Type
+	24, // 5: protocol.WebSocketMessage.headers:type_name -> protocol.WebSocketMessage.HeadersEntry
+	25, // 6: protocol.HealthResponse.details:type_name -> protocol.HealthResponse.DetailsEntry
+	26, // 7: protocol.ConfigSchemaResponse.fields:type_name -> protocol.ConfigSchemaResponse.FieldsEntry
+	11, // 8: protocol.InitializeResponse.schedules:type_name -> protocol.ScheduleInfo
+	14, // 9: protocol.InitializeResponse.watchers:type_name -> protocol.WatcherRegistration
+	13, // 10: protocol.InitializeResponse.http_routes:type_name -> protocol.RouteInfo
+	17, // 11: protocol.ExecuteJobResponse.log_entries:type_name -> protocol.JobLogEntry
+	19, // 12: protocol.GlyphDefResponse.glyphs:type_name -> protocol.GlyphDef
+	10, // 13: protocol.ConfigSchemaResponse.FieldsEntry.value:type_name -> protocol.ConfigFieldSchema
+	1,  // 14: protocol.DomainPluginService.Metadata:input_type -> protocol.Empty
+	3,  // 15: protocol.DomainPluginService.Initialize:input_type -> protocol.InitializeRequest
+	1,  // 16: protocol.DomainPluginService.Shutdown:input_type -> protocol.Empty
+	4,  // 17: protocol.DomainPluginService.HandleHTTP:input_type -> protocol.HTTPRequest
+	7,  // 18: protocol.DomainPluginService.HandleWebSocket:input_type -> protocol.WebSocketMessage
+	1,  // 19: protocol.DomainPluginService.Health:input_type -> protocol.Empty
+	1,  // 20: protocol.DomainPluginService.ConfigSchema:input_type -> protocol.Empty
+	1,  // 21: protocol.DomainPluginService.RegisterGlyphs:input_type -> protocol.Empty
+	15, // 22: protocol.DomainPluginService.ExecuteJob:input_type -> protocol.ExecuteJobRequest
+	20, // 23: protocol.DomainPluginService.ParseAxQuery:input_type -> protocol.ParseAxQueryRequest
+	2,  // 24: protocol.DomainPluginService.Metadata:output_type -> protocol.MetadataResponse
+	12, // 25: protocol.DomainPluginService.Initialize:output_type -> protocol.InitializeResponse
+	1,  // 26: protocol.DomainPluginService.Shutdown:output_type -> protocol.Empty
+	5,  // 27: protocol.DomainPluginService.HandleHTTP:output_type -> protocol.HTTPResponse
+	7,  // 28: protocol.DomainPluginService.HandleWebSocket:output_type -> protocol.WebSocketMessage
+	8,  // 29: protocol.DomainPluginService.Health:output_type -> protocol.HealthResponse
+	9,  // 30: protocol.DomainPluginService.ConfigSchema:output_type -> protocol.ConfigSchemaResponse
+	18, // 31: protocol.DomainPluginService.RegisterGlyphs:output_type -> protocol.GlyphDefResponse
+	16, // 32: protocol.DomainPluginService.ExecuteJob:output_type -> protocol.ExecuteJobResponse
+	21, // 33: protocol.DomainPluginService.ParseAxQuery:output_type -> protocol.ParseAxQueryResponse
+	24, // [24:34] is the sub-list for method output_type
+	14, // [14:24] is the sub-list for method input_type
+	14, // [14:14] is the sub-list for extension type_name
+	14, // [14:14] is the sub-list for extension extendee
+	0,  // [0:14] is the sub-list for field type_name
 }
 
 func init() { file_plugin_grpc_protocol_domain_proto_init() }
@@ -1950,7 +2008,7 @@ func file_plugin_grpc_protocol_domain_proto_init() {
 			GoPackagePath: reflect.TypeOf(x{}).PkgPath(),
 			RawDescriptor: unsafe.Slice(unsafe.StringData(file_plugin_grpc_protocol_domain_proto_rawDesc), len(file_plugin_grpc_protocol_domain_proto_rawDesc)),
 			NumEnums:      1,
-			NumMessages:   25,
+			NumMessages:   26,
 			NumExtensions: 0,
 			NumServices:   1,
 		},
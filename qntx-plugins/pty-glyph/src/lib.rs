@@ -8,6 +8,7 @@ mod websocket;
 
 use parking_lot::RwLock;
 use pty::PTYManager;
+use qntx_grpc::plugin::Router;
 use std::collections::HashMap;
 use std::sync::Arc;
 use tonic::{Request, Response, Status, Streaming};
@@ -16,9 +17,29 @@ use tracing::{debug, info};
 use proto::domain_plugin_service_server::DomainPluginService;
 use proto::*;
 
+/// HTTP endpoints served under `/api/pty-glyph`
+#[derive(Debug, Clone, Copy)]
+enum Route {
+    Module,
+    Css,
+    Create,
+    GetSession,
+    KillSession,
+}
+
+fn routes() -> Router<Route> {
+    Router::new()
+        .route("GET", "/api/pty-glyph/pty-glyph-module.js", Route::Module)
+        .route("GET", "/api/pty-glyph/xterm.css", Route::Css)
+        .route("POST", "/api/pty-glyph/create", Route::Create)
+        .route("GET", "/api/pty-glyph/session/{id}", Route::GetSession)
+        .route("DELETE", "/api/pty-glyph/session/{id}", Route::KillSession)
+}
+
 /// PTY Glyph Plugin Service
 pub struct PTYGlyphService {
     pty_manager: Arc<RwLock<PTYManager>>,
+    router: Router<Route>,
 }
 
 impl Default for PTYGlyphService {
@@ -37,6 +58,7 @@ impl PTYGlyphService {
 
         Self {
             pty_manager: Arc::new(RwLock::new(PTYManager::new(default_home))),
+            router: routes(),
         }
     }
 }
@@ -59,6 +81,7 @@ impl DomainPluginService for PTYGlyphService {
             description: "Persistent terminal glyphs with full PTY support".to_string(),
             author: "QNTX Team".to_string(),
             license: "MIT".to_string(),
+            ..Default::default()
         }))
     }
 
@@ -124,22 +147,18 @@ impl DomainPluginService for PTYGlyphService {
         let req = request.into_inner();
         debug!("Handling HTTP request: {} {}", req.method, req.path);
 
-        // Route HTTP requests
-        match (req.method.as_str(), req.path.as_str()) {
-            ("GET", "/api/pty-glyph/pty-glyph-module.js") => self.handle_glyph_module().await,
-            ("GET", "/api/pty-glyph/xterm.css") => self.handle_xterm_css().await,
-            ("POST", "/api/pty-glyph/create") => self.handle_create_pty(req).await,
-            ("GET", path) if path.starts_with("/api/pty-glyph/session/") => {
-                self.handle_get_session(req).await
-            }
-            ("DELETE", path) if path.starts_with("/api/pty-glyph/session/") => {
-                self.handle_kill_session(req).await
-            }
-            _ => Ok(Response::new(HttpResponse {
-                status_code: 404,
-                headers: vec![],
-                body: b"Not Found".to_vec(),
-            })),
+        let matched = match self.router.resolve(&req) {
+            Ok(matched) => matched,
+            Err(response) => return Ok(Response::new(response)),
+        };
+        // Both session routes have an {id} param
+        let session_id = matched.param("id").unwrap_or_default();
+        match matched.route {
+            Route::Module => self.handle_glyph_module().await,
+            Route::Css => self.handle_xterm_css().await,
+            Route::Create => self.handle_create_pty(req).await,
+            Route::GetSession => self.handle_get_session(session_id).await,
+            Route::KillSession => self.handle_kill_session(session_id).await,
         }
     }
 
@@ -282,13 +301,7 @@ impl PTYGlyphService {
         }))
     }
 
-    async fn handle_get_session(&self, req: HttpRequest) -> Result<Response<HttpResponse>, Status> {
-        // Extract session ID from path
-        let id = req
-            .path
-            .strip_prefix("/api/pty-glyph/session/")
-            .ok_or_else(|| Status::invalid_argument("Invalid path"))?;
-
+    async fn handle_get_session(&self, id: &str) -> Result<Response<HttpResponse>, Status> {
         let session = self
             .pty_manager
             .read()
@@ -306,16 +319,7 @@ impl PTYGlyphService {
         }))
     }
 
-    async fn handle_kill_session(
-        &self,
-        req: HttpRequest,
-    ) -> Result<Response<HttpResponse>, Status> {
-        // Extract session ID from path
-        let id = req
-            .path
-            .strip_prefix("/api/pty-glyph/session/")
-            .ok_or_else(|| Status::invalid_argument("Invalid path"))?;
-
+    async fn handle_kill_session(&self, id: &str) -> Result<Response<HttpResponse>, Status> {
         self.pty_manager
             .write()
             .kill_session(id)
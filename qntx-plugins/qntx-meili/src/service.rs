@@ -39,6 +39,7 @@ impl DomainPluginService for MeiliPluginService {
             description: "Search provider plugin — MeiliSearch backend".to_string(),
             author: "QNTX Contributors".to_string(),
             license: "MIT".to_string(),
+            ..Default::default()
         }))
     }
 
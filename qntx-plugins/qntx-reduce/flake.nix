@@ -18,6 +18,7 @@
           umap-learn
           numpy
           scikit-learn
+          threadpoolctl
         ]);
 
         # Build qntx-reduce plugin binary (Rust + PyO3)
@@ -0,0 +1,570 @@
+//! Optional enrichment of a reduction: cluster labels and quality metrics.
+//!
+//! Both run in Rust on the fit output so the UI gets labels and scores that
+//! agree with the embedding instead of re-deriving them in JS. Everything here
+//! is deterministic for a given seed: k-means++ initialisation and the metric
+//! sample both draw from a seeded SplitMix64 stream.
+
+use serde::{Deserialize, Serialize};
+use std::time::Instant;
+
+/// `cluster` options of a fit request.
+///
+/// With `k` set, k-means runs once with that many clusters. Otherwise every k in
+/// `k_min..=k_max` is tried and the one with the best silhouette score wins.
+#[derive(Clone, Debug, Deserialize)]
+pub struct ClusterOptions {
+    #[serde(default)]
+    pub k: Option<usize>,
+    #[serde(default = "default_k_min")]
+    pub k_min: usize,
+    #[serde(default = "default_k_max")]
+    pub k_max: usize,
+    #[serde(default = "default_max_iter")]
+    pub max_iter: usize,
+    /// Points used to score each candidate k; silhouette is quadratic in this.
+    #[serde(default = "default_silhouette_sample")]
+    pub silhouette_sample: usize,
+}
+
+impl ClusterOptions {
+    /// Check the options against a dataset of `n` points before any work is done.
+    pub fn validate(&self, n: usize) -> Result<(), String> {
+        if self.silhouette_sample == 0 {
+            return Err("cluster.silhouette_sample must be at least 1".to_string());
+        }
+        match self.k {
+            Some(k) if k == 0 || k > n => {
+                Err(format!("cluster.k must be between 1 and {}, got {}", n, k))
+            }
+            Some(_) => Ok(()),
+            None if self.k_min < 2 || self.k_min > self.k_max.min(n.saturating_sub(1)) => {
+                Err(format!(
+                    "cluster k range {}..={} is empty for {} points (k_min must be at least 2)",
+                    self.k_min, self.k_max, n
+                ))
+            }
+            None => Ok(()),
+        }
+    }
+}
+
+fn default_k_min() -> usize {
+    2
+}
+fn default_k_max() -> usize {
+    8
+}
+fn default_max_iter() -> usize {
+    100
+}
+fn default_silhouette_sample() -> usize {
+    1000
+}
+
+/// `metrics` options of a fit request.
+#[derive(Clone, Debug, Deserialize)]
+pub struct MetricsOptions {
+    #[serde(default = "default_knn_k")]
+    pub knn_k: usize,
+    /// Points whose neighborhoods are compared; all points if larger than the dataset.
+    #[serde(default = "default_metrics_sample")]
+    pub sample: usize,
+}
+
+impl MetricsOptions {
+    /// Check the options against a dataset of `n` points before any work is done.
+    pub fn validate(&self, n: usize) -> Result<(), String> {
+        if n < 3 {
+            return Err(format!("quality metrics need at least 3 points, got {}", n));
+        }
+        if self.knn_k == 0 || self.sample == 0 {
+            return Err("metrics.knn_k and metrics.sample must be at least 1".to_string());
+        }
+        Ok(())
+    }
+}
+
+fn default_knn_k() -> usize {
+    15
+}
+fn default_metrics_sample() -> usize {
+    1000
+}
+
+/// Cluster assignment for every projected point.
+#[derive(Debug, Serialize)]
+pub struct ClusterResult {
+    pub k: usize,
+    /// Cluster id per point, numbered in order of first appearance.
+    pub labels: Vec<usize>,
+    /// Mean silhouette over the scoring sample; absent for k = 1.
+    pub silhouette: Option<f64>,
+    pub compute_ms: u64,
+}
+
+/// Neighborhood preservation between the input embeddings and the projection.
+#[derive(Debug, Serialize)]
+pub struct QualityMetrics {
+    /// Neighborhood size actually used (clamped for small datasets).
+    pub knn_k: usize,
+    pub sample: usize,
+    /// Mean fraction of each point's k input-space neighbors that are also projected neighbors.
+    pub knn_preservation: f64,
+    /// Penalizes projected neighbors that were far apart in input space (1 = none).
+    pub trustworthiness: f64,
+    /// Penalizes input-space neighbors that were pulled apart by the projection (1 = none).
+    pub continuity: f64,
+    pub compute_ms: u64,
+}
+
+/// Distance used in the input space, following the fit request's `metric`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Distance {
+    Euclidean,
+    Cosine,
+    Manhattan,
+}
+
+impl Distance {
+    /// Map a UMAP metric name; metrics without a Rust counterpart fall back to Euclidean.
+    pub fn from_metric(metric: &str) -> Self {
+        match metric.to_lowercase().as_str() {
+            "cosine" => Distance::Cosine,
+            "manhattan" | "l1" | "cityblock" | "taxicab" => Distance::Manhattan,
+            _ => Distance::Euclidean,
+        }
+    }
+
+    fn between(self, a: &[f32], b: &[f32]) -> f64 {
+        match self {
+            Distance::Euclidean => squared_euclidean(a, b).sqrt(),
+            Distance::Manhattan => a
+                .iter()
+                .zip(b)
+                .map(|(x, y)| (*x as f64 - *y as f64).abs())
+                .sum(),
+            Distance::Cosine => {
+                let (mut dot, mut na, mut nb) = (0.0, 0.0, 0.0);
+                for (x, y) in a.iter().zip(b) {
+                    let (x, y) = (*x as f64, *y as f64);
+                    dot += x * y;
+                    na += x * x;
+                    nb += y * y;
+                }
+                if na == 0.0 || nb == 0.0 {
+                    1.0
+                } else {
+                    1.0 - dot / (na.sqrt() * nb.sqrt())
+                }
+            }
+        }
+    }
+}
+
+fn squared_euclidean(a: &[f32], b: &[f32]) -> f64 {
+    a.iter()
+        .zip(b)
+        .map(|(x, y)| {
+            let d = *x as f64 - *y as f64;
+            d * d
+        })
+        .sum()
+}
+
+/// Cluster the projected points with k-means.
+pub fn cluster(
+    points: &[Vec<f32>],
+    opts: &ClusterOptions,
+    seed: u64,
+) -> Result<ClusterResult, String> {
+    let start = Instant::now();
+    let n = points.len();
+    opts.validate(n)?;
+
+    let mut rng = SplitMix64::new(seed);
+    let scoring = sample_indices(n, opts.silhouette_sample, &mut rng);
+
+    let (k, labels, silhouette) = match opts.k {
+        Some(k) => {
+            let labels = kmeans(points, k, opts.max_iter, &mut rng);
+            let silhouette = (k > 1).then(|| silhouette(points, &labels, k, &scoring));
+            (k, labels, silhouette)
+        }
+        None => {
+            let k_max = opts.k_max.min(n - 1);
+            let mut best: Option<(usize, Vec<usize>, f64)> = None;
+            for k in opts.k_min..=k_max {
+                let labels = kmeans(points, k, opts.max_iter, &mut rng);
+                let score = silhouette(points, &labels, k, &scoring);
+                // Strictly better only: ties keep the smaller k.
+                if best.as_ref().is_none_or(|(_, _, s)| score > *s) {
+                    best = Some((k, labels, score));
+                }
+            }
+            let (k, labels, score) = best.expect("k range is non-empty");
+            (k, labels, Some(score))
+        }
+    };
+
+    Ok(ClusterResult {
+        k,
+        labels: relabel_by_first_appearance(&labels),
+        silhouette,
+        compute_ms: start.elapsed().as_millis() as u64,
+    })
+}
+
+/// Lloyd's k-means with k-means++ seeding. Returns a cluster index per point.
+fn kmeans(points: &[Vec<f32>], k: usize, max_iter: usize, rng: &mut SplitMix64) -> Vec<usize> {
+    let n = points.len();
+    let dim = points[0].len();
+
+    // k-means++: each next centroid is drawn proportionally to squared distance
+    // from the nearest centroid chosen so far.
+    let mut centroids: Vec<Vec<f32>> = vec![points[rng.below(n)].clone()];
+    let mut nearest: Vec<f64> = points
+        .iter()
+        .map(|p| squared_euclidean(p, &centroids[0]))
+        .collect();
+    while centroids.len() < k {
+        let total: f64 = nearest.iter().sum();
+        let next = if total == 0.0 {
+            rng.below(n)
+        } else {
+            let mut target = rng.unit() * total;
+            let mut chosen = n - 1;
+            for (i, d) in nearest.iter().enumerate() {
+                if target < *d {
+                    chosen = i;
+                    break;
+                }
+                target -= d;
+            }
+            chosen
+        };
+        centroids.push(points[next].clone());
+        let c = centroids.last().unwrap();
+        for (i, p) in points.iter().enumerate() {
+            nearest[i] = nearest[i].min(squared_euclidean(p, c));
+        }
+    }
+
+    let mut labels = vec![usize::MAX; n];
+    for _ in 0..max_iter.max(1) {
+        let mut changed = false;
+        for (i, p) in points.iter().enumerate() {
+            let best = closest(p, &centroids);
+            if labels[i] != best {
+                labels[i] = best;
+                changed = true;
+            }
+        }
+        if !changed {
+            break;
+        }
+
+        let mut sums = vec![vec![0.0f64; dim]; k];
+        let mut counts = vec![0usize; k];
+        for (p, &l) in points.iter().zip(&labels) {
+            counts[l] += 1;
+            for (s, v) in sums[l].iter_mut().zip(p) {
+                *s += *v as f64;
+            }
+        }
+        for c in 0..k {
+            if counts[c] == 0 {
+                // Re-seed an empty cluster with the point farthest from its centroid.
+                let far = (0..n)
+                    .max_by(|&a, &b| {
+                        let da = squared_euclidean(&points[a], &centroids[labels[a]]);
+                        let db = squared_euclidean(&points[b], &centroids[labels[b]]);
+                        da.total_cmp(&db).then(b.cmp(&a))
+                    })
+                    .unwrap();
+                centroids[c] = points[far].clone();
+            } else {
+                centroids[c] = sums[c]
+                    .iter()
+                    .map(|s| (*s / counts[c] as f64) as f32)
+                    .collect();
+            }
+        }
+    }
+    labels
+}
+
+fn closest(p: &[f32], centroids: &[Vec<f32>]) -> usize {
+    let mut best = 0;
+    let mut best_d = f64::INFINITY;
+    for (c, centroid) in centroids.iter().enumerate() {
+        let d = squared_euclidean(p, centroid);
+        if d < best_d {
+            best = c;
+            best_d = d;
+        }
+    }
+    best
+}
+
+/// Mean silhouette over `sample`, measured against the sampled points only.
+fn silhouette(points: &[Vec<f32>], labels: &[usize], k: usize, sample: &[usize]) -> f64 {
+    let mut total = 0.0;
+    for &i in sample {
+        let mut sums = vec![0.0f64; k];
+        let mut counts = vec![0usize; k];
+        for &j in sample {
+            if i != j {
+                sums[labels[j]] += Distance::Euclidean.between(&points[i], &points[j]);
+                counts[labels[j]] += 1;
+            }
+        }
+        let own = labels[i];
+        // Singleton clusters score 0 by convention.
+        if counts[own] == 0 {
+            continue;
+        }
+        let a = sums[own] / counts[own] as f64;
+        let b = (0..k)
+            .filter(|&c| c != own && counts[c] > 0)
+            .map(|c| sums[c] / counts[c] as f64)
+            .fold(f64::INFINITY, f64::min);
+        if b.is_finite() {
+            total += (b - a) / a.max(b).max(f64::MIN_POSITIVE);
+        }
+    }
+    total / sample.len() as f64
+}
+
+fn relabel_by_first_appearance(labels: &[usize]) -> Vec<usize> {
+    let mut mapping = std::collections::HashMap::new();
+    labels
+        .iter()
+        .map(|&l| {
+            let next = mapping.len();
+            *mapping.entry(l).or_insert(next)
+        })
+        .collect()
+}
+
+/// Compare each sampled point's neighbors in the input space with its neighbors
+/// in the projection: k-NN preservation, trustworthiness and continuity
+/// (Venna & Kaski), with the sum normalized by the sample size instead of n.
+pub fn quality_metrics(
+    embeddings: &[Vec<f32>],
+    projections: &[Vec<f32>],
+    distance: Distance,
+    opts: &MetricsOptions,
+    seed: u64,
+) -> Result<QualityMetrics, String> {
+    let start = Instant::now();
+    let n = embeddings.len();
+    opts.validate(n)?;
+    // The trustworthiness normalization requires 2n - 3k - 1 > 0.
+    let k = opts.knn_k.min((2 * n - 2) / 3).max(1);
+
+    // A different stream from clustering, so enabling one does not shift the other.
+    let mut rng = SplitMix64::new(seed.wrapping_add(1));
+    let sample = sample_indices(n, opts.sample, &mut rng);
+
+    let mut preserved = 0usize;
+    let mut trust_penalty = 0usize;
+    let mut cont_penalty = 0usize;
+    for &i in &sample {
+        let high = ranks(n, i, |j| distance.between(&embeddings[i], &embeddings[j]));
+        let low = ranks(n, i, |j| {
+            Distance::Euclidean.between(&projections[i], &projections[j])
+        });
+        for j in 0..n {
+            if j == i {
+                continue;
+            }
+            let in_high = high[j] <= k;
+            let in_low = low[j] <= k;
+            match (in_high, in_low) {
+                (true, true) => preserved += 1,
+                (false, true) => trust_penalty += high[j] - k,
+                (true, false) => cont_penalty += low[j] - k,
+                (false, false) => {}
+            }
+        }
+    }
+
+    let s = sample.len() as f64;
+    let norm = 2.0 / (s * k as f64 * (2.0 * n as f64 - 3.0 * k as f64 - 1.0));
+    Ok(QualityMetrics {
+        knn_k: k,
+        sample: sample.len(),
+        knn_preservation: preserved as f64 / (s * k as f64),
+        trustworthiness: 1.0 - norm * trust_penalty as f64,
+        continuity: 1.0 - norm * cont_penalty as f64,
+        compute_ms: start.elapsed().as_millis() as u64,
+    })
+}
+
+/// Neighbor rank of every point relative to `i` (1 = nearest; `i` itself gets 0).
+/// Ties break by index so results do not depend on sort stability.
+fn ranks(n: usize, i: usize, dist: impl Fn(usize) -> f64) -> Vec<usize> {
+    let mut order: Vec<(f64, usize)> = (0..n).filter(|&j| j != i).map(|j| (dist(j), j)).collect();
+    order.sort_by(|a, b| a.0.total_cmp(&b.0).then(a.1.cmp(&b.1)));
+    let mut rank = vec![0; n];
+    for (r, (_, j)) in order.into_iter().enumerate() {
+        rank[j] = r + 1;
+    }
+    rank
+}
+
+/// `size` distinct indices out of `0..n` (all of them, in order, if `size >= n`).
+fn sample_indices(n: usize, size: usize, rng: &mut SplitMix64) -> Vec<usize> {
+    let mut indices: Vec<usize> = (0..n).collect();
+    if size >= n {
+        return indices;
+    }
+    // Partial Fisher–Yates shuffle.
+    for i in 0..size {
+        let j = i + rng.below(n - i);
+        indices.swap(i, j);
+    }
+    indices.truncate(size);
+    indices.sort_unstable();
+    indices
+}
+
+/// SplitMix64: small, seedable, and good enough for sampling and initialisation.
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in [0, 1).
+    fn unit(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Uniform in 0..n.
+    fn below(&mut self, n: usize) -> usize {
+        (self.unit() * n as f64) as usize % n
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Two well-separated Gaussian-ish blobs: points 0..n are blob A, n..2n blob B.
+    fn two_blobs(n: usize, dim: usize, seed: u64) -> Vec<Vec<f32>> {
+        let mut rng = SplitMix64::new(seed);
+        let mut points = Vec::with_capacity(2 * n);
+        for center in [-10.0f32, 10.0] {
+            for _ in 0..n {
+                points.push(
+                    (0..dim)
+                        .map(|_| center + (rng.unit() as f32 - 0.5))
+                        .collect(),
+                );
+            }
+        }
+        points
+    }
+
+    fn auto_k() -> ClusterOptions {
+        serde_json::from_value(serde_json::json!({})).unwrap()
+    }
+
+    #[test]
+    fn test_two_blobs_cluster_by_silhouette() {
+        let points = two_blobs(40, 2, 7);
+        let result = cluster(&points, &auto_k(), 42).unwrap();
+
+        assert_eq!(result.k, 2);
+        assert!(result.labels[..40].iter().all(|&l| l == 0));
+        assert!(result.labels[40..].iter().all(|&l| l == 1));
+        assert!(result.silhouette.unwrap() > 0.9);
+    }
+
+    #[test]
+    fn test_fixed_k() {
+        let points = two_blobs(20, 2, 3);
+        let opts: ClusterOptions = serde_json::from_value(serde_json::json!({"k": 2})).unwrap();
+        let result = cluster(&points, &opts, 1).unwrap();
+        assert_eq!(result.k, 2);
+        assert_eq!(result.labels.iter().filter(|&&l| l == 0).count(), 20);
+
+        let too_many: ClusterOptions =
+            serde_json::from_value(serde_json::json!({"k": 41})).unwrap();
+        assert!(cluster(&points, &too_many, 1).is_err());
+    }
+
+    #[test]
+    fn test_seed_makes_results_deterministic() {
+        let points = two_blobs(30, 2, 11);
+        let opts: ClusterOptions = serde_json::from_value(serde_json::json!({"k": 5})).unwrap();
+        let a = cluster(&points, &opts, 99).unwrap();
+        let b = cluster(&points, &opts, 99).unwrap();
+        assert_eq!(a.labels, b.labels);
+        assert_eq!(a.silhouette, b.silhouette);
+
+        let high = two_blobs(30, 8, 12);
+        let metrics = MetricsOptions {
+            knn_k: 5,
+            sample: 10,
+        };
+        let m1 = quality_metrics(&high, &points, Distance::Euclidean, &metrics, 5).unwrap();
+        let m2 = quality_metrics(&high, &points, Distance::Euclidean, &metrics, 5).unwrap();
+        assert_eq!(m1.knn_preservation, m2.knn_preservation);
+        assert_eq!(m1.trustworthiness, m2.trustworthiness);
+        assert_eq!(m1.sample, 10);
+    }
+
+    #[test]
+    fn test_metrics_perfect_for_identity_projection() {
+        let points = two_blobs(25, 2, 5);
+        let opts = MetricsOptions {
+            knn_k: 15,
+            sample: 1000,
+        };
+        let m = quality_metrics(&points, &points, Distance::Euclidean, &opts, 0).unwrap();
+        assert_eq!(m.sample, 50);
+        assert_eq!(m.knn_k, 15);
+        assert!((m.knn_preservation - 1.0).abs() < 1e-12);
+        assert!((m.trustworthiness - 1.0).abs() < 1e-12);
+        assert!((m.continuity - 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_metrics_drop_when_blobs_are_scrambled() {
+        let high = two_blobs(25, 6, 8);
+        // Project blob membership away: interleave points from both blobs on a line.
+        let scrambled: Vec<Vec<f32>> = (0..50)
+            .map(|i| {
+                let pos = if i < 25 { 2 * i } else { 2 * (i - 25) + 1 };
+                vec![pos as f32, 0.0]
+            })
+            .collect();
+        let opts = MetricsOptions {
+            knn_k: 10,
+            sample: 1000,
+        };
+        let m = quality_metrics(&high, &scrambled, Distance::Euclidean, &opts, 0).unwrap();
+        assert!(m.knn_preservation < 0.7, "got {}", m.knn_preservation);
+        assert!(m.trustworthiness < 0.9, "got {}", m.trustworthiness);
+    }
+
+    #[test]
+    fn test_cosine_distance() {
+        assert!(Distance::Cosine.between(&[1.0, 0.0], &[2.0, 0.0]).abs() < 1e-12);
+        assert!((Distance::Cosine.between(&[1.0, 0.0], &[0.0, 1.0]) - 1.0).abs() < 1e-12);
+        assert_eq!(Distance::from_metric("Cosine"), Distance::Cosine);
+        assert_eq!(Distance::from_metric("correlation"), Distance::Euclidean);
+    }
+}
@@ -1,3 +1,6 @@
+use crate::enrich::{
+    self, ClusterOptions, ClusterResult, Distance, MetricsOptions, QualityMetrics,
+};
 use crate::proto::{HttpHeader, HttpResponse};
 use parking_lot::RwLock;
 use pyo3::prelude::*;
@@ -12,6 +15,63 @@ use tracing::{error, info, warn};
 /// Known reduction methods.
 const KNOWN_METHODS: &[&str] = &["umap", "tsne", "pca"];
 
+/// Seed for clustering and metric sampling when the request does not give one,
+/// so enrichment is reproducible even for unseeded fits.
+const DEFAULT_ENRICH_SEED: u64 = 0;
+
+/// Plugin config key capping the threads a reduction may use.
+pub(crate) const CONFIG_MAX_THREADS: &str = "reduce_max_threads";
+
+/// Threads available to reductions when the plugin config sets no cap.
+pub(crate) fn default_max_threads() -> usize {
+    std::thread::available_parallelism().map_or(1, |n| n.get())
+}
+
+/// Seed and thread count a fit actually runs with, echoed in the response.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub(crate) struct Execution {
+    /// Reducer `random_state`; None leaves the reducer unseeded
+    pub seed: Option<u64>,
+    pub threads: usize,
+    pub strict_determinism: bool,
+}
+
+impl Execution {
+    /// Resolve a request's seed and thread settings against the plugin's cap.
+    ///
+    /// The requested thread count is clamped to `1..=max_threads`. Seeded
+    /// UMAP runs single-threaded regardless, because umap-learn drops
+    /// `n_jobs` to 1 whenever `random_state` is set. With multiple threads
+    /// t-SNE neighbor search and the BLAS reductions may differ in the last
+    /// bits between runs; `strict_determinism` forces one thread (and seed 0
+    /// when none is given) so identical inputs give identical coordinates.
+    pub(crate) fn resolve(
+        method: &str,
+        seed: Option<u64>,
+        strict_determinism: bool,
+        requested_threads: Option<usize>,
+        max_threads: usize,
+    ) -> Self {
+        let max_threads = max_threads.max(1);
+        let mut threads = requested_threads
+            .unwrap_or(max_threads)
+            .clamp(1, max_threads);
+        let mut seed = seed;
+        if strict_determinism {
+            seed = Some(seed.unwrap_or(DEFAULT_ENRICH_SEED));
+            threads = 1;
+        }
+        if method == "umap" && seed.is_some() {
+            threads = 1;
+        }
+        Self {
+            seed,
+            threads,
+            strict_determinism,
+        }
+    }
+}
+
 /// Per-method fit state.
 #[derive(Clone)]
 pub(crate) struct MethodState {
@@ -23,6 +83,8 @@ pub(crate) struct MethodState {
 /// Plugin state holding per-method fitted model references.
 pub(crate) struct ReduceState {
     pub fitted: HashMap<String, MethodState>,
+    /// Thread cap from the plugin config ([`CONFIG_MAX_THREADS`])
+    pub max_threads: usize,
 }
 
 /// Handler context wrapping shared state.
@@ -54,6 +116,22 @@ impl HandlerContext {
             metric: String,
             #[serde(default = "default_perplexity")]
             perplexity: f64,
+            /// Seeds the reducer (`random_state`) and the enrichment steps.
+            /// Must fit in a u32: numpy rejects larger `random_state` seeds.
+            #[serde(default)]
+            seed: Option<u64>,
+            /// Force a single-threaded, seeded fit for bit-identical output.
+            #[serde(default)]
+            strict_determinism: bool,
+            /// Threads for this fit, capped at the plugin's `reduce_max_threads`.
+            #[serde(default)]
+            max_threads: Option<usize>,
+            /// Cluster the projections; omitted means no clustering.
+            #[serde(default)]
+            cluster: Option<ClusterOptions>,
+            /// Compute neighborhood preservation metrics; omitted means none.
+            #[serde(default)]
+            metrics: Option<MetricsOptions>,
         }
 
         fn default_method() -> String {
@@ -81,6 +159,13 @@ impl HandlerContext {
         if req.embeddings.is_empty() {
             return Err(Status::invalid_argument("embeddings array is empty"));
         }
+        if let Some(seed) = req.seed.filter(|&s| s > u64::from(u32::MAX)) {
+            return Err(Status::invalid_argument(format!(
+                "seed {} is above {}, the largest random_state numpy accepts",
+                seed,
+                u32::MAX
+            )));
+        }
 
         let method = req.method.to_lowercase();
         if !KNOWN_METHODS.contains(&method.as_str()) {
@@ -92,6 +177,19 @@ impl HandlerContext {
         }
 
         let n_points = req.embeddings.len();
+        if let Some(opts) = &req.cluster {
+            opts.validate(n_points).map_err(Status::invalid_argument)?;
+        }
+        if let Some(opts) = &req.metrics {
+            opts.validate(n_points).map_err(Status::invalid_argument)?;
+        }
+        let execution = Execution::resolve(
+            &method,
+            req.seed,
+            req.strict_determinism,
+            req.max_threads,
+            self.state.read().max_threads,
+        );
         let start = Instant::now();
 
         let n_components = req.n_components;
@@ -107,6 +205,7 @@ impl HandlerContext {
             let py_list = PyList::new(py, inner_lists.iter())?;
             let np_array = np.call_method1("array", (py_list, "float32"))?;
 
+            let _limits = ThreadLimits::new(py, execution.threads)?;
             let result = match method.as_str() {
                 "umap" => {
                     let umap_mod = py.import("umap")?;
@@ -115,6 +214,10 @@ impl HandlerContext {
                     kwargs.set_item("min_dist", req.min_dist)?;
                     kwargs.set_item("metric", &req.metric)?;
                     kwargs.set_item("n_components", n_components)?;
+                    kwargs.set_item("n_jobs", execution.threads)?;
+                    if let Some(seed) = execution.seed {
+                        kwargs.set_item("random_state", seed)?;
+                    }
                     let reducer = umap_mod.getattr("UMAP")?.call((), Some(&kwargs))?;
                     let result = reducer.call_method1("fit_transform", (np_array,))?;
                     let builtins = py.import("builtins")?;
@@ -126,6 +229,10 @@ impl HandlerContext {
                     let kwargs = pyo3::types::PyDict::new(py);
                     kwargs.set_item("n_components", n_components)?;
                     kwargs.set_item("perplexity", req.perplexity)?;
+                    kwargs.set_item("n_jobs", execution.threads)?;
+                    if let Some(seed) = execution.seed {
+                        kwargs.set_item("random_state", seed)?;
+                    }
                     let tsne = manifold.getattr("TSNE")?.call((), Some(&kwargs))?;
                     tsne.call_method1("fit_transform", (np_array,))?
                 }
@@ -133,6 +240,9 @@ impl HandlerContext {
                     let decomposition = py.import("sklearn.decomposition")?;
                     let kwargs = pyo3::types::PyDict::new(py);
                     kwargs.set_item("n_components", n_components)?;
+                    if let Some(seed) = execution.seed {
+                        kwargs.set_item("random_state", seed)?;
+                    }
                     let pca = decomposition.getattr("PCA")?.call((), Some(&kwargs))?;
                     let result = pca.call_method1("fit_transform", (np_array,))?;
                     let builtins = py.import("builtins")?;
@@ -162,6 +272,28 @@ impl HandlerContext {
 
         let fit_ms = start.elapsed().as_millis() as u64;
 
+        let seed = execution.seed.unwrap_or(DEFAULT_ENRICH_SEED);
+        let cluster = req
+            .cluster
+            .as_ref()
+            .map(|opts| enrich::cluster(&projections, opts, seed))
+            .transpose()
+            .map_err(Status::invalid_argument)?;
+        let metrics = req
+            .metrics
+            .as_ref()
+            .map(|opts| {
+                enrich::quality_metrics(
+                    &req.embeddings,
+                    &projections,
+                    Distance::from_metric(&req.metric),
+                    opts,
+                    seed,
+                )
+            })
+            .transpose()
+            .map_err(Status::invalid_argument)?;
+
         // Update state
         {
             let mut state = self.state.write();
@@ -176,8 +308,8 @@ impl HandlerContext {
         }
 
         info!(
-            "{} fit complete: {} points in {}ms",
-            method, n_points, fit_ms
+            "{} fit complete: {} points in {}ms ({} threads)",
+            method, n_points, fit_ms, execution.threads
         );
 
         #[derive(Serialize)]
@@ -187,6 +319,13 @@ impl HandlerContext {
             projections: Vec<Vec<f32>>,
             n_points: usize,
             fit_ms: u64,
+            seed: Option<u64>,
+            threads: usize,
+            strict_determinism: bool,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            cluster: Option<ClusterResult>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            metrics: Option<QualityMetrics>,
         }
 
         json_response(
@@ -197,6 +336,11 @@ impl HandlerContext {
                 projections,
                 n_points,
                 fit_ms,
+                seed: execution.seed,
+                threads: execution.threads,
+                strict_determinism: execution.strict_determinism,
+                cluster,
+                metrics,
             },
         )
     }
@@ -244,9 +388,12 @@ impl HandlerContext {
         let start = Instant::now();
 
         // Get n_components from the fitted model's state
-        let n_components = {
+        let (n_components, max_threads) = {
             let state = self.state.read();
-            state.fitted.get(&method).map_or(2, |s| s.n_components)
+            (
+                state.fitted.get(&method).map_or(2, |s| s.n_components),
+                state.max_threads,
+            )
         };
 
         let projections = Python::with_gil(|py| -> PyResult<Vec<Vec<f32>>> {
@@ -265,6 +412,7 @@ impl HandlerContext {
             let py_list = PyList::new(py, inner_lists.iter())?;
             let np_array = np.call_method1("array", (py_list, "float32"))?;
 
+            let _limits = ThreadLimits::new(py, max_threads)?;
             let result = reducer.call_method1("transform", (np_array,))?;
 
             let mut projections = Vec::with_capacity(n_points);
@@ -364,6 +512,25 @@ impl HandlerContext {
     }
 }
 
+/// Caps the BLAS/OpenMP pools numpy and scikit-learn use until dropped.
+struct ThreadLimits<'py>(Bound<'py, PyAny>);
+
+impl<'py> ThreadLimits<'py> {
+    fn new(py: Python<'py>, threads: usize) -> PyResult<Self> {
+        py.import("threadpoolctl")?
+            .call_method1("threadpool_limits", (threads,))
+            .map(Self)
+    }
+}
+
+impl Drop for ThreadLimits<'_> {
+    fn drop(&mut self) {
+        if let Err(e) = self.0.call_method0("restore_original_limits") {
+            warn!("Failed to restore thread pool limits: {}", e);
+        }
+    }
+}
+
 /// Create a JSON HTTP response.
 #[allow(clippy::result_large_err)]
 fn json_response<T: Serialize>(status_code: i32, data: &T) -> Result<HttpResponse, Status> {
@@ -379,3 +546,69 @@ fn json_response<T: Serialize>(status_code: i32, data: &T) -> Result<HttpRespons
         body,
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_threads_capped_at_plugin_limit() {
+        let e = Execution::resolve("tsne", None, false, Some(16), 4);
+        assert_eq!(e.threads, 4);
+        assert_eq!(e.seed, None);
+
+        assert_eq!(Execution::resolve("tsne", None, false, None, 4).threads, 4);
+        assert_eq!(
+            Execution::resolve("tsne", None, false, Some(2), 4).threads,
+            2
+        );
+        assert_eq!(
+            Execution::resolve("pca", None, false, Some(0), 4).threads,
+            1
+        );
+        assert_eq!(Execution::resolve("pca", None, false, None, 0).threads, 1);
+    }
+
+    #[test]
+    fn test_strict_determinism_is_seeded_and_single_threaded() {
+        let e = Execution::resolve("tsne", None, true, Some(8), 8);
+        assert_eq!(e.seed, Some(DEFAULT_ENRICH_SEED));
+        assert_eq!(e.threads, 1);
+        assert!(e.strict_determinism);
+
+        let e = Execution::resolve("pca", Some(7), true, None, 8);
+        assert_eq!(e.seed, Some(7));
+        assert_eq!(e.threads, 1);
+    }
+
+    #[test]
+    fn test_seed_above_u32_is_rejected() {
+        let handlers = HandlerContext::new(Arc::new(RwLock::new(ReduceState {
+            fitted: HashMap::new(),
+            max_threads: 1,
+        })));
+        let err = handlers
+            .handle_fit(serde_json::json!({
+                "embeddings": [[0.0, 1.0]],
+                "method": "pca",
+                "seed": u64::from(u32::MAX) + 1,
+            }))
+            .unwrap_err();
+        assert_eq!(err.code(), tonic::Code::InvalidArgument);
+        assert!(err.message().contains("random_state"), "{}", err.message());
+    }
+
+    #[test]
+    fn test_seeded_umap_reports_single_thread() {
+        // umap-learn ignores n_jobs once random_state is set
+        assert_eq!(
+            Execution::resolve("umap", Some(3), false, None, 8).threads,
+            1
+        );
+        assert_eq!(Execution::resolve("umap", None, false, None, 8).threads, 8);
+        assert_eq!(
+            Execution::resolve("tsne", Some(3), false, None, 8).threads,
+            8
+        );
+    }
+}
@@ -1,3 +1,4 @@
+pub mod enrich;
 pub mod handlers;
 pub mod proto;
 pub mod service;
@@ -1,11 +1,9 @@
 use clap::Parser;
+use qntx_grpc::plugin::{PluginServer, Recording, ResourceBudget};
 use qntx_reduce_plugin::proto::domain_plugin_service_server::DomainPluginServiceServer;
 use qntx_reduce_plugin::ReducePluginService;
 use std::net::SocketAddr;
 use tokio::net::TcpListener;
-use tokio::signal;
-use tokio_stream::wrappers::TcpListenerStream;
-use tonic::transport::Server;
 use tracing::{info, warn, Level};
 use tracing_subscriber::FmtSubscriber;
 
@@ -30,8 +28,18 @@ struct Args {
 /// Max port retries when the requested port is occupied (multi-session conflicts).
 const MAX_PORT_RETRIES: u16 = 10;
 
-#[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error>> {
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // Worker count comes from QNTX_PLUGIN_MAX_THREADS, so the runtime is built by hand
+    let budget = ResourceBudget::from_env();
+    // Niceness is per thread: set it before the workers spawn so they inherit it
+    let niced = budget.apply_nice();
+    budget.runtime()?.block_on(run(budget, niced))
+}
+
+async fn run(
+    budget: ResourceBudget,
+    niced: std::io::Result<()>,
+) -> Result<(), Box<dyn std::error::Error>> {
     std::panic::set_hook(Box::new(|panic_info| {
         eprintln!("PANIC: Plugin panicked during startup or execution");
         eprintln!(
@@ -69,6 +77,9 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     info!("Initializing QNTX Reduce Plugin");
     info!("  Version: {}", env!("CARGO_PKG_VERSION"));
+    if let Err(e) = niced {
+        warn!("  Could not apply niceness {:?}: {}", budget.nice, e);
+    }
 
     // Bind with port retry to handle multi-session port conflicts.
     // When multiple QNTX sessions run concurrently, they each allocate ports
@@ -116,47 +127,24 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("QNTX_PLUGIN_PORT={}", local_addr.port());
 
     let service = ReducePluginService::new();
-
-    info!("Starting gRPC server on {}", local_addr);
-
-    let incoming = TcpListenerStream::new(listener);
-    Server::builder()
-        .add_service(
-            DomainPluginServiceServer::new(service)
+    let server = PluginServer::new("reduce", env!("CARGO_PKG_VERSION"))
+        .with_metadata(service.plugin_metadata())
+        .budget(budget)
+        .on_drain("release fitted models", service.release_models_hook());
+    let service = service
+        .with_drain(server.drain_handle())
+        .with_auth(server.auth_handle())
+        .with_budget(server.budget_handle());
+
+    server
+        .serve_listener(
+            listener,
+            // Records the session to QNTX_PLUGIN_RECORD when set (fixture replay tests)
+            DomainPluginServiceServer::new(Recording::from_env(service, "reduce"))
                 .max_decoding_message_size(100 * 1024 * 1024)
                 .max_encoding_message_size(100 * 1024 * 1024),
         )
-        .serve_with_incoming_shutdown(incoming, shutdown_signal())
         .await?;
 
-    info!("Plugin shutdown complete");
     Ok(())
 }
-
-async fn shutdown_signal() {
-    let ctrl_c = async {
-        signal::ctrl_c()
-            .await
-            .expect("Failed to install Ctrl+C handler");
-    };
-
-    #[cfg(unix)]
-    let terminate = async {
-        signal::unix::signal(signal::unix::SignalKind::terminate())
-            .expect("Failed to install signal handler")
-            .recv()
-            .await;
-    };
-
-    #[cfg(not(unix))]
-    let terminate = std::future::pending::<()>();
-
-    tokio::select! {
-        _ = ctrl_c => {
-            info!("Received Ctrl+C, shutting down");
-        }
-        _ = terminate => {
-            info!("Received terminate signal, shutting down");
-        }
-    }
-}
@@ -1,11 +1,15 @@
-use crate::handlers::{HandlerContext, ReduceState};
+use crate::handlers::{default_max_threads, HandlerContext, ReduceState, CONFIG_MAX_THREADS};
 use crate::proto::{
-    domain_plugin_service_server::DomainPluginService, ConfigSchemaResponse, Empty,
-    ExecuteJobRequest, ExecuteJobResponse, GlyphDefResponse, HealthResponse, HttpRequest,
+    domain_plugin_service_server::DomainPluginService, ConfigFieldSchema, ConfigSchemaResponse,
+    Empty, ExecuteJobRequest, ExecuteJobResponse, GlyphDefResponse, HealthResponse, HttpRequest,
     HttpResponse, InitializeRequest, InitializeResponse, MetadataResponse, ParseAxQueryRequest,
     ParseAxQueryResponse, WebSocketMessage,
 };
 use parking_lot::RwLock;
+use qntx_grpc::plugin::{
+    BudgetHandle, DrainHandle, HttpGuard, HttpLimits, PluginAuth, PluginMetadata, PressureEvent,
+    Router,
+};
 use std::collections::HashMap;
 use std::pin::Pin;
 use std::sync::Arc;
@@ -13,24 +17,123 @@ use tokio_stream::Stream;
 use tonic::{Request, Response, Status, Streaming};
 use tracing::{debug, info, warn};
 
-/// Routes that hold the Python GIL for extended computation.
-/// Run on tokio's blocking pool so gRPC health checks still respond.
-const BLOCKING_ROUTES: &[(&str, &str)] = &[("POST", "/fit"), ("POST", "/transform")];
+/// HTTP endpoints of the plugin
+#[derive(Debug, Clone, Copy)]
+enum Route {
+    Fit,
+    Transform,
+    Status,
+}
+
+impl Route {
+    /// Routes that hold the Python GIL for extended computation.
+    /// Run on tokio's blocking pool so gRPC health checks still respond.
+    fn is_blocking(self) -> bool {
+        matches!(self, Route::Fit | Route::Transform)
+    }
+}
+
+fn routes(guard: Arc<HttpGuard>) -> Router<Route> {
+    Router::new()
+        .route("POST", "/fit", Route::Fit)
+        .route("POST", "/transform", Route::Transform)
+        .route("GET", "/status", Route::Status)
+        .wrap_all(guard)
+}
+
+/// Job handlers registered at Initialize.
+const JOB_TYPES: &[&str] = &["reduce.umap", "reduce.tsne", "reduce.pca"];
 
 /// Dimensionality reduction plugin gRPC service.
 pub struct ReducePluginService {
     handlers: HandlerContext,
+    guard: Arc<HttpGuard>,
+    router: Router<Route>,
+    auth: PluginAuth,
+    metadata: PluginMetadata,
+    drain: DrainHandle,
+    budget: BudgetHandle,
 }
 
 impl ReducePluginService {
     pub fn new() -> Self {
         let state = Arc::new(RwLock::new(ReduceState {
             fitted: HashMap::new(),
+            max_threads: default_max_threads(),
         }));
 
+        let guard = Arc::new(HttpGuard::new(HttpLimits::default()));
+        let router = routes(guard.clone());
         Self {
             handlers: HandlerContext::new(state),
+            metadata: Self::describe(&router),
+            guard,
+            router,
+            auth: PluginAuth::disabled(),
+            drain: DrainHandle::new(),
+            budget: BudgetHandle::default(),
+        }
+    }
+
+    /// Report unhealthy once the `PluginServer` behind `drain` starts draining.
+    pub fn with_drain(mut self, drain: DrainHandle) -> Self {
+        self.drain = drain;
+        self
+    }
+
+    /// Follow the resource budget of the `PluginServer` behind `budget`: cap
+    /// reduction threads at its thread limit, refuse fits while over the
+    /// memory ceiling and drop fitted models when pressure starts.
+    pub fn with_budget(mut self, budget: BudgetHandle) -> Self {
+        if let Some(cap) = budget.budget().max_threads {
+            let mut state = self.handlers.state.write();
+            state.max_threads = state.max_threads.min(cap);
         }
+        let handlers = self.handlers.clone();
+        budget.on_pressure("release fitted models", move |event| {
+            if let PressureEvent::Entered { .. } = event {
+                // clear_models takes the GIL, which a running fit may hold for long
+                let handlers = handlers.clone();
+                std::thread::spawn(move || handlers.clear_models());
+            }
+        });
+        self.budget = budget;
+        self
+    }
+
+    /// Check proxied HTTP requests against the tokens of the `PluginServer`
+    /// behind `auth`.
+    pub fn with_auth(mut self, auth: PluginAuth) -> Self {
+        self.auth = auth;
+        self
+    }
+
+    /// Drop fitted models; run as a drain hook once in-flight fits are done.
+    pub fn release_models_hook(&self) -> impl FnOnce() -> std::future::Ready<()> + Send + 'static {
+        let handlers = self.handlers.clone();
+        move || {
+            handlers.clear_models();
+            std::future::ready(())
+        }
+    }
+
+    /// Metadata shared with the `PluginServer`; uptime counts from service creation.
+    pub fn plugin_metadata(&self) -> PluginMetadata {
+        self.metadata.clone()
+    }
+
+    fn describe(router: &Router<Route>) -> PluginMetadata {
+        let mut metadata = PluginMetadata::new("reduce", env!("CARGO_PKG_VERSION"))
+            .description(
+                "Dimensionality reduction plugin (UMAP, t-SNE, PCA) for embedding visualization",
+            )
+            .job_types(JOB_TYPES.iter().copied());
+        for route in router.routes() {
+            if let Some((method, path)) = route.split_once(' ') {
+                metadata = metadata.http_route(method, path);
+            }
+        }
+        metadata
     }
 }
 
@@ -47,30 +150,46 @@ impl DomainPluginService for ReducePluginService {
         _request: Request<Empty>,
     ) -> Result<Response<MetadataResponse>, Status> {
         debug!("Metadata request received");
-        Ok(Response::new(MetadataResponse {
-            name: "reduce".to_string(),
-            version: env!("CARGO_PKG_VERSION").to_string(),
-            qntx_version: ">=0.1.0".to_string(),
-            description:
-                "Dimensionality reduction plugin (UMAP, t-SNE, PCA) for embedding visualization"
-                    .to_string(),
-            author: "QNTX Contributors".to_string(),
-            license: "MIT".to_string(),
-        }))
+        let fitted: Vec<String> = self.handlers.state.read().fitted.keys().cloned().collect();
+        let mut extras = self.budget.details();
+        extras.insert("fitted_methods".to_string(), fitted.join(","));
+        Ok(Response::new(self.metadata.response_with(extras)))
     }
 
     async fn initialize(
         &self,
-        _request: Request<InitializeRequest>,
+        request: Request<InitializeRequest>,
     ) -> Result<Response<InitializeResponse>, Status> {
         info!("Initializing Reduce plugin");
 
+        let config = request.into_inner().config;
+        let limits = HttpLimits::from_config(&config)
+            .map_err(|e| Status::invalid_argument(format!("invalid HTTP limits: {}", e)))?;
+        self.guard.set_limits(limits);
+        self.auth.update_from_config(&config);
+        let max_threads = match config.get(CONFIG_MAX_THREADS).filter(|v| !v.is_empty()) {
+            Some(v) => v
+                .trim()
+                .parse::<usize>()
+                .ok()
+                .filter(|n| *n > 0)
+                .ok_or_else(|| {
+                    Status::invalid_argument(format!(
+                        "{} must be a positive integer, got {:?}",
+                        CONFIG_MAX_THREADS, v
+                    ))
+                })?,
+            None => default_max_threads(),
+        };
+        let max_threads = self
+            .budget
+            .budget()
+            .max_threads
+            .map_or(max_threads, |cap| max_threads.min(cap));
+        self.handlers.state.write().max_threads = max_threads;
+
         Ok(Response::new(InitializeResponse {
-            handler_names: vec![
-                "reduce.umap".to_string(),
-                "reduce.tsne".to_string(),
-                "reduce.pca".to_string(),
-            ],
+            handler_names: JOB_TYPES.iter().map(|s| s.to_string()).collect(),
             schedules: vec![],
             llm_provider: false,
             search_provider: false,
@@ -103,6 +222,27 @@ impl DomainPluginService for ReducePluginService {
 
         debug!("HTTP request: {} {}", method, path);
 
+        if let Err(rejection) = self.auth.check_http(&req) {
+            warn!(
+                "Rejected {} {} with {}",
+                method, path, rejection.status_code
+            );
+            return Ok(Response::new(rejection));
+        }
+
+        // Held until the response is built so the route's concurrency slot stays claimed
+        let matched = match self.router.resolve(&req) {
+            Ok(matched) => matched,
+            Err(rejection) => {
+                warn!(
+                    "Rejected {} {} with {}",
+                    method, path, rejection.status_code
+                );
+                return Ok(Response::new(rejection));
+            }
+        };
+        let route = matched.route;
+
         let body: serde_json::Value = if req.body.is_empty() {
             serde_json::Value::Null
         } else {
@@ -110,30 +250,17 @@ impl DomainPluginService for ReducePluginService {
                 .map_err(|e| Status::invalid_argument(format!("Invalid JSON body: {}", e)))?
         };
 
-        let is_blocking = BLOCKING_ROUTES
-            .iter()
-            .any(|(m, p)| *m == method.as_str() && *p == path.as_str());
-
-        let result = if is_blocking {
+        let result = if !route.is_blocking() {
+            dispatch(&self.handlers, route, body)
+        } else if let Err(refused) = self.budget.admit() {
+            // New fits would only grow memory further
+            Err(refused)
+        } else {
             let handlers = self.handlers.clone();
-            let m = method.clone();
-            let p = path.clone();
             #[allow(clippy::result_large_err)]
-            tokio::task::spawn_blocking(move || match (m.as_str(), p.as_str()) {
-                ("POST", "/fit") => handlers.handle_fit(body),
-                ("POST", "/transform") => handlers.handle_transform(body),
-                _ => unreachable!(),
-            })
-            .await
-            .map_err(|e| Status::internal(format!("Blocking task failed: {}", e)))?
-        } else {
-            match (method.as_str(), path.as_str()) {
-                ("GET", "/status") => self.handlers.handle_status(),
-                _ => Err(Status::not_found(format!(
-                    "Unknown endpoint: {} {}",
-                    method, path
-                ))),
-            }
+            tokio::task::spawn_blocking(move || dispatch(&handlers, route, body))
+                .await
+                .map_err(|e| Status::internal(format!("Blocking task failed: {}", e)))?
         };
 
         match result {
@@ -147,6 +274,7 @@ impl DomainPluginService for ReducePluginService {
                         tonic::Code::NotFound => 404,
                         tonic::Code::InvalidArgument => 400,
                         tonic::Code::FailedPrecondition => 412,
+                        tonic::Code::ResourceExhausted => 503,
                         tonic::Code::Internal => 500,
                         _ => 500,
                     },
@@ -176,14 +304,41 @@ impl DomainPluginService for ReducePluginService {
     async fn health(&self, _request: Request<Empty>) -> Result<Response<HealthResponse>, Status> {
         let state = self.handlers.state.read();
 
-        let mut details = HashMap::new();
+        let mut details = self.budget.details();
         let fitted_methods: Vec<String> = state.fitted.keys().cloned().collect();
         details.insert("fitted_methods".to_string(), fitted_methods.join(","));
         details.insert("n_methods".to_string(), state.fitted.len().to_string());
 
+        let rejections = self.guard.rejections();
+        details.insert(
+            "http_rejected_body_too_large".to_string(),
+            rejections.body_too_large.to_string(),
+        );
+        details.insert(
+            "http_rejected_rate_limited".to_string(),
+            rejections.rate_limited.to_string(),
+        );
+        details.insert(
+            "http_rejected_concurrency_limited".to_string(),
+            rejections.concurrency_limited.to_string(),
+        );
+
+        let draining = self.drain.is_draining();
+        details.insert(
+            "active_calls".to_string(),
+            self.drain.active_calls().to_string(),
+        );
+
+        let message = if draining {
+            "draining"
+        } else if self.budget.is_under_pressure() {
+            "over memory budget"
+        } else {
+            "OK"
+        };
         Ok(Response::new(HealthResponse {
-            healthy: true,
-            message: "OK".to_string(),
+            healthy: !draining,
+            message: message.to_string(),
             details,
         }))
     }
@@ -192,9 +347,22 @@ impl DomainPluginService for ReducePluginService {
         &self,
         _request: Request<Empty>,
     ) -> Result<Response<ConfigSchemaResponse>, Status> {
-        // No configurable fields for now
         Ok(Response::new(ConfigSchemaResponse {
-            fields: HashMap::new(),
+            fields: HttpLimits::schema_fields()
+                .into_iter()
+                .chain(PluginAuth::schema_fields())
+                .chain([(
+                    CONFIG_MAX_THREADS.to_string(),
+                    ConfigFieldSchema {
+                        r#type: "number".to_string(),
+                        description: "Most threads a reduction may use; requests can ask for \
+                                      fewer (default: all cores)"
+                            .to_string(),
+                        min_value: "1".to_string(),
+                        ..Default::default()
+                    },
+                )])
+                .collect(),
         }))
     }
 
@@ -214,3 +382,261 @@ impl DomainPluginService for ReducePluginService {
         ))
     }
 }
+
+#[allow(clippy::result_large_err)]
+fn dispatch(
+    handlers: &HandlerContext,
+    route: Route,
+    body: serde_json::Value,
+) -> Result<HttpResponse, Status> {
+    match route {
+        Route::Fit => handlers.handle_fit(body),
+        Route::Transform => handlers.handle_transform(body),
+        Route::Status => handlers.handle_status(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(method: &str, path: &str, body: &[u8]) -> Request<HttpRequest> {
+        Request::new(HttpRequest {
+            method: method.to_string(),
+            path: path.to_string(),
+            headers: vec![],
+            body: body.to_vec(),
+        })
+    }
+
+    #[tokio::test]
+    async fn test_http_limits_from_initialize() {
+        let service = ReducePluginService::new();
+        let config = HashMap::from([
+            ("http_max_body_bytes".to_string(), "16".to_string()),
+            ("http_rate_per_second".to_string(), "0.001".to_string()),
+            ("http_burst".to_string(), "2".to_string()),
+        ]);
+        service
+            .initialize(Request::new(InitializeRequest {
+                config,
+                ..Default::default()
+            }))
+            .await
+            .unwrap();
+
+        let oversized = service
+            .handle_http(request("POST", "/fit", &[b' '; 17]))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(oversized.status_code, 413);
+
+        for _ in 0..2 {
+            let ok = service
+                .handle_http(request("GET", "/status", b""))
+                .await
+                .unwrap()
+                .into_inner();
+            assert_eq!(ok.status_code, 200);
+        }
+        let limited = service
+            .handle_http(request("GET", "/status", b""))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(limited.status_code, 429);
+        assert!(limited.headers.iter().any(|h| h.name == "Retry-After"));
+
+        let health = service
+            .health(Request::new(Empty {}))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(health.details["http_rejected_rate_limited"], "1");
+        assert_eq!(health.details["http_rejected_body_too_large"], "1");
+    }
+
+    #[tokio::test]
+    async fn test_unknown_path_is_404_and_wrong_method_is_405() {
+        let service = ReducePluginService::new();
+        let missing = service
+            .handle_http(request("GET", "/missing", b""))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(missing.status_code, 404);
+
+        let wrong_method = service
+            .handle_http(request("GET", "/fit", b""))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(wrong_method.status_code, 405);
+        let allow = wrong_method
+            .headers
+            .iter()
+            .find(|h| h.name == "Allow")
+            .unwrap();
+        assert_eq!(allow.values, vec!["POST"]);
+
+        let routes = service.plugin_metadata().response().http_routes;
+        assert_eq!(routes, vec!["POST /fit", "POST /transform", "GET /status"]);
+    }
+
+    #[tokio::test]
+    async fn test_max_threads_from_initialize() {
+        let service = ReducePluginService::new();
+        assert_eq!(
+            service.handlers.state.read().max_threads,
+            default_max_threads()
+        );
+
+        let config = HashMap::from([(CONFIG_MAX_THREADS.to_string(), "2".to_string())]);
+        service
+            .initialize(Request::new(InitializeRequest {
+                config,
+                ..Default::default()
+            }))
+            .await
+            .unwrap();
+        assert_eq!(service.handlers.state.read().max_threads, 2);
+
+        for bad in ["0", "many"] {
+            let config = HashMap::from([(CONFIG_MAX_THREADS.to_string(), bad.to_string())]);
+            let err = service
+                .initialize(Request::new(InitializeRequest {
+                    config,
+                    ..Default::default()
+                }))
+                .await
+                .unwrap_err();
+            assert_eq!(err.code(), tonic::Code::InvalidArgument);
+        }
+
+        let schema = service
+            .config_schema(Request::new(Empty {}))
+            .await
+            .unwrap()
+            .into_inner();
+        assert!(schema.fields.contains_key(CONFIG_MAX_THREADS));
+    }
+
+    #[tokio::test]
+    async fn test_budget_caps_threads_and_refuses_fits_under_pressure() {
+        use qntx_grpc::plugin::ResourceBudget;
+
+        let budget = BudgetHandle::new(ResourceBudget {
+            max_memory_bytes: Some(100 << 20),
+            max_threads: Some(1),
+            nice: None,
+        });
+        let service = ReducePluginService::new().with_budget(budget.clone());
+        assert_eq!(service.handlers.state.read().max_threads, 1);
+
+        let config = HashMap::from([(CONFIG_MAX_THREADS.to_string(), "8".to_string())]);
+        service
+            .initialize(Request::new(InitializeRequest {
+                config,
+                ..Default::default()
+            }))
+            .await
+            .unwrap();
+        assert_eq!(service.handlers.state.read().max_threads, 1);
+
+        budget.observe(200 << 20);
+        let refused = service
+            .handle_http(request("POST", "/fit", b"{}"))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(refused.status_code, 503);
+        let status = service
+            .handle_http(request("GET", "/status", b""))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(status.status_code, 200);
+
+        let health = service
+            .health(Request::new(Empty {}))
+            .await
+            .unwrap()
+            .into_inner();
+        assert!(health.healthy);
+        assert_eq!(health.message, "over memory budget");
+        assert_eq!(health.details["budget_under_pressure"], "true");
+        assert_eq!(health.details["budget_max_memory_mb"], "100");
+
+        budget.observe(50 << 20);
+        let health = service
+            .health(Request::new(Empty {}))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(health.message, "OK");
+    }
+
+    #[tokio::test]
+    async fn test_http_auth_from_initialize() {
+        use crate::proto::HttpHeader;
+
+        let service = ReducePluginService::new();
+        let config = HashMap::from([("plugin_auth_tokens".to_string(), "s3cret".to_string())]);
+        service
+            .initialize(Request::new(InitializeRequest {
+                config,
+                ..Default::default()
+            }))
+            .await
+            .unwrap();
+
+        let denied = service
+            .handle_http(request("GET", "/status", b""))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(denied.status_code, 401);
+
+        let mut authorized = request("GET", "/status", b"");
+        authorized.get_mut().headers.push(HttpHeader {
+            name: "Authorization".to_string(),
+            values: vec!["Bearer s3cret".to_string()],
+        });
+        let ok = service.handle_http(authorized).await.unwrap().into_inner();
+        assert_eq!(ok.status_code, 200);
+    }
+
+    #[tokio::test]
+    async fn test_metadata_over_plugin_server() {
+        use crate::proto::domain_plugin_service_client::DomainPluginServiceClient;
+        use crate::proto::domain_plugin_service_server::DomainPluginServiceServer;
+        use qntx_grpc::plugin::{PluginServer, BUILD_COMMIT_HASH};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let service = ReducePluginService::new();
+        let server = PluginServer::new("reduce", env!("CARGO_PKG_VERSION"))
+            .with_metadata(service.plugin_metadata());
+        let handle =
+            tokio::spawn(server.serve_listener(listener, DomainPluginServiceServer::new(service)));
+
+        let mut client = DomainPluginServiceClient::connect(format!("http://{}", addr))
+            .await
+            .unwrap();
+        let metadata = client.metadata(Empty {}).await.unwrap().into_inner();
+        handle.abort();
+
+        assert_eq!(metadata.name, "reduce");
+        assert_eq!(metadata.version, env!("CARGO_PKG_VERSION"));
+        assert_eq!(metadata.commit_hash, BUILD_COMMIT_HASH);
+        assert!(!metadata.commit_hash.is_empty());
+        assert_eq!(metadata.job_types, JOB_TYPES);
+        assert!(metadata.http_routes.contains(&"POST /fit".to_string()));
+        assert!(metadata.http_routes.contains(&"GET /status".to_string()));
+        assert_eq!(metadata.extras["fitted_methods"], "");
+        // The server is seconds old at most; uptime is whole seconds since creation
+        assert!(metadata.uptime_seconds < 60);
+    }
+}
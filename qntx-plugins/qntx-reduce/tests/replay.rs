@@ -0,0 +1,111 @@
+//! Replays a recorded host↔plugin session against the current service.
+//!
+//! The fixture was recorded through `Recording`, the same wrapper the plugin
+//! binary installs when `QNTX_PLUGIN_RECORD` is set. After an intended
+//! behaviour change, regenerate it with
+//! `cargo test -p qntx-reduce-plugin --test replay -- --ignored` and review
+//! the diff.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use qntx_grpc::plugin::recording::Redactor;
+use qntx_grpc::plugin::{replay, Recording, ReplayOptions, Session, SessionRecorder};
+use qntx_reduce_plugin::proto::domain_plugin_service_server::DomainPluginService;
+use qntx_reduce_plugin::proto::{
+    Empty, ExecuteJobRequest, HttpHeader, HttpRequest, InitializeRequest,
+};
+use qntx_reduce_plugin::ReducePluginService;
+use tonic::Request;
+
+fn fixture() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/reduce_session.jsonl")
+}
+
+/// Fields that differ between runs or machines
+fn options() -> ReplayOptions {
+    [
+        "uptime_seconds",
+        "version",
+        "commit_hash",
+        "budget_threads",
+        "rss_mb",
+    ]
+    .into_iter()
+    .fold(ReplayOptions::default(), ReplayOptions::ignore)
+}
+
+fn http(method: &str, path: &str, body: &str) -> Request<HttpRequest> {
+    Request::new(HttpRequest {
+        method: method.to_string(),
+        path: path.to_string(),
+        headers: vec![HttpHeader {
+            name: "Authorization".to_string(),
+            values: vec!["Bearer not-a-real-token".to_string()],
+        }],
+        body: body.as_bytes().to_vec(),
+    })
+}
+
+#[tokio::test]
+async fn replay_recorded_session() {
+    let session = Session::load(fixture()).unwrap();
+    assert_eq!(session.header.plugin, "reduce");
+
+    let report = replay(ReducePluginService::new(), &session, &options())
+        .await
+        .unwrap();
+    report.assert_match();
+    assert_eq!(report.replayed, session.events.len());
+}
+
+/// Regenerates the fixture. Fits are limited to requests that fail before
+/// reaching Python, since umap-learn is only available in the Nix build.
+#[tokio::test]
+#[ignore = "rewrites tests/fixtures/reduce_session.jsonl"]
+async fn record_session() {
+    let recorder = SessionRecorder::create(fixture(), "reduce", Redactor::default()).unwrap();
+    let service = Recording::new(ReducePluginService::new(), Some(Arc::new(recorder)));
+
+    service.metadata(Request::new(Empty {})).await.unwrap();
+    // No auth tokens: they are redacted, so a replay could not present them
+    let config = HashMap::from([("reduce_max_threads".to_string(), "2".to_string())]);
+    service
+        .initialize(Request::new(InitializeRequest {
+            config,
+            ..Default::default()
+        }))
+        .await
+        .unwrap();
+    service.config_schema(Request::new(Empty {})).await.unwrap();
+    service.health(Request::new(Empty {})).await.unwrap();
+    service
+        .handle_http(http("GET", "/status", ""))
+        .await
+        .unwrap();
+    service
+        .handle_http(http(
+            "POST",
+            "/fit",
+            r#"{"method":"isomap","embeddings":[[0.1,0.2]]}"#,
+        ))
+        .await
+        .unwrap();
+    service
+        .handle_http(http("POST", "/fit", r#"{"embeddings":[]}"#))
+        .await
+        .unwrap();
+    service
+        .handle_http(http("POST", "/transform", r#"{"embeddings":[[0.1,0.2]]}"#))
+        .await
+        .unwrap();
+    service
+        .handle_http(http("GET", "/missing", ""))
+        .await
+        .unwrap();
+    service
+        .execute_job(Request::new(ExecuteJobRequest::default()))
+        .await
+        .unwrap_err();
+}
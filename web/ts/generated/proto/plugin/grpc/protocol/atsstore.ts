@@ -27,6 +27,10 @@ export interface Attestation {
   signature: Uint8Array;
   /** did:key of the signing node (optional) */
   signer_did: string;
+  /** Storage revision: 1 when first stored, +1 per update; excluded from content identity */
+  revision: number;
+  /** Creator-assigned confidence 0.0-1.0 (optional); excluded from content identity */
+  confidence?: number | undefined;
 }
 
 /** AttestationCommand is used for creating attestations */
@@ -18,6 +18,21 @@ export interface MetadataResponse {
   description: string;
   author: string;
   license: string;
+  /** Commit the plugin binary was built from ("unknown" when not provided at build time) */
+  commit_hash: string;
+  /** Job handler names the plugin can execute */
+  job_types: string[];
+  /** HTTP routes served through HandleHTTP, as "METHOD /path" */
+  http_routes: string[];
+  /** Seconds since the plugin process started serving */
+  uptime_seconds: number;
+  /** Plugin-specific descriptive fields */
+  extras: { [key: string]: string };
+}
+
+export interface MetadataResponse_ExtrasEntry {
+  key: string;
+  value: string;
 }
 
 /**
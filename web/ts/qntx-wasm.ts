@@ -18,18 +18,61 @@ export type { Attestation };
 /** Parsed AX query */
 export interface AxQuery {
     subjects: string[];
+    /** `subjects` split into `or` alternatives; present only when the query uses `or` */
+    subject_groups?: string[][];
+    /** Subjects after `not`/`except`; empty unless the query negates */
+    excluded_subjects: string[];
     predicates: string[];
     contexts: string[];
+    /** `contexts` split into `or` alternatives, like `subject_groups` */
+    context_groups?: string[][];
+    excluded_contexts: string[];
     actors: string[];
+    excluded_actors: string[];
     temporal?: unknown;
+    /** From a `where` clause; also read when the query is used as a filter */
+    attribute_conditions?: AttrCondition[];
     [key: string]: unknown;
 }
 
+/** Condition on one attribute; the value's type decides how it compares */
+export interface AttrCondition {
+    key: string;
+    op: 'eq' | 'ne' | 'gt' | 'gte' | 'lt' | 'lte' | 'contains' | 'exists';
+    value: string | number | boolean;
+}
+
 /** Query parse result */
 export type ParseResult =
     | { ok: true; query: AxQuery }
     | { ok: false; error: string };
 
+/** Recovery the UI can offer for a storage error */
+export type RecoveryHint = 'free_space' | 'close_other_tabs' | 'reload_page' | 'retry_later';
+
+/** Storage calls reject with this as JSON: `{code, message, hint}` */
+export interface StorageError {
+    code: string;
+    message: string;
+    hint: RecoveryHint | null;
+}
+
+/** Parse a storage call rejection; null if it isn't a structured storage error. */
+export function parseStorageError(error: unknown): StorageError | null {
+    if (typeof error !== 'string') {
+        return null;
+    }
+    try {
+        const parsed = JSON.parse(error);
+        if (typeof parsed?.code === 'string' && typeof parsed?.message === 'string') {
+            return { code: parsed.code, message: parsed.message, hint: parsed.hint ?? null };
+        }
+    } catch {
+        // Plain-text rejection
+    }
+    return null;
+}
+
 /** Promise that resolves when WASM is initialized */
 let initPromise: Promise<void> | null = null;
 
@@ -112,6 +155,192 @@ export function parseQuery(input: string): ParseResult {
     return { ok: true, query: parsed };
 }
 
+/** Parsed duration, e.g. "18m" → normalized "1y6m" */
+export interface ParsedDuration {
+    raw: string;
+    normalized: string;
+    /** Length in milliseconds (month = 30 days, year = 365 days) */
+    ms: number;
+}
+
+/** Duration parse result */
+export type DurationResult =
+    | { ok: true; duration: ParsedDuration }
+    | { ok: false; error: string };
+
+/**
+ * Parse a duration such as "5y", "1y6m" or "2w3d".
+ * Synchronous operation, no initialization required.
+ */
+export function parseDuration(input: string): DurationResult {
+    const parsed = JSON.parse(wasm.parse_duration(input));
+
+    if ('error' in parsed) {
+        return { ok: false, error: parsed.error };
+    }
+
+    return { ok: true, duration: parsed };
+}
+
+/** Lint rule identifiers, usable in `CoreConfig.lint.disabled` */
+export type LintCode =
+    | 'unknown_subject'
+    | 'unknown_predicate'
+    | 'unknown_context'
+    | 'unknown_actor'
+    | 'slot_value_looks_like_other_slot'
+    | 'predicate_variant_mix'
+    | 'empty_time_range'
+    | 'future_date';
+
+/** A query that parses but will likely return nothing */
+export interface LintWarning {
+    code: LintCode;
+    severity: 'hint' | 'warning';
+    message: string;
+    slot?: 'subject' | 'predicate' | 'context' | 'actor';
+    term?: string;
+    /** UTF-8 byte offset of `term` in the query text (equals the string index for ASCII) */
+    position?: number;
+    suggestions?: string[];
+}
+
+/** Query lint result; `error` means the query doesn't parse */
+export type LintResult =
+    | { ok: true; warnings: LintWarning[] }
+    | { ok: false; error: string };
+
+/**
+ * Lint an AX query against the stored vocabulary.
+ * Reads the store's term indexes on each call: debounce while typing.
+ */
+export async function lintQuery(input: string): Promise<LintResult> {
+    await ensureInit();
+    const parsed = JSON.parse(await wasm.lint_ax_query(input));
+
+    if ('error' in parsed) {
+        return { ok: false, error: parsed.error };
+    }
+
+    return { ok: true, warnings: parsed.warnings };
+}
+
+/** Where a query can be read another way */
+export interface Ambiguity {
+    kind: 'keyword_case' | 'compound_predicate' | 'implicit_predicate' | 'slot_boundary';
+    term: string;
+    /** UTF-8 byte offset of `term` in the query text */
+    position: number;
+}
+
+/** Another reading of a query, for "did you mean" chips */
+export interface QueryAlternate {
+    /** Query text to insert when the chip is picked */
+    text: string;
+    query: AxQuery;
+    confidence: number;
+    ambiguities: Ambiguity[];
+}
+
+export type AlternatesResult =
+    | { ok: true; query: AxQuery; confidence: number; alternates: QueryAlternate[] }
+    | { ok: false; error: string };
+
+/**
+ * Parse an AX query and up to `maxAlternates` other readings of it, most
+ * confident first. Ranked against the stored vocabulary: debounce while typing.
+ */
+export async function parseQueryAlternates(
+    input: string,
+    maxAlternates: number = 3,
+): Promise<AlternatesResult> {
+    await ensureInit();
+    const parsed = JSON.parse(await wasm.parse_query_alternates(input, maxAlternates));
+
+    if ('error' in parsed) {
+        return { ok: false, error: parsed.error };
+    }
+
+    return {
+        ok: true,
+        query: parsed.primary,
+        confidence: parsed.confidence,
+        alternates: parsed.alternates,
+    };
+}
+
+/** Term slot a query builder edits */
+export type QuerySlot = 'subjects' | 'predicates' | 'contexts' | 'actors' | 'actions';
+
+/** Temporal clause as serialized by the parser */
+export type TemporalSpec =
+    | { Since: string }
+    | { Until: string }
+    | { On: string }
+    | { Between: [string, string] }
+    | { Over: string };
+
+/** One structured edit of a query builder */
+export type QueryBuilderOp =
+    | { op: 'add'; slot: QuerySlot; value: string }
+    | { op: 'remove'; slot: QuerySlot; value: string }
+    | { op: 'set'; slot: QuerySlot; values: string[] }
+    | { op: 'set_temporal'; temporal: TemporalSpec | null }
+    | { op: 'add_condition'; condition: AttrCondition }
+    | { op: 'remove_condition'; index: number }
+    | { op: 'clear_conditions' }
+    | { op: 'clear' };
+
+/** A value the query text quotes because it reads as a keyword */
+export interface QueryBuilderWarning {
+    slot: QuerySlot | 'temporal' | 'attribute_conditions';
+    value: string;
+    code: 'keyword';
+    message: string;
+}
+
+/** Builder state; `query_string` parses back to `query` */
+export interface QueryBuilderPreview {
+    query: AxQuery;
+    query_string: string;
+    warnings: QueryBuilderWarning[];
+}
+
+/**
+ * Builds an AX query from structured edits instead of string splicing.
+ * Synchronous, no initialization required. Call `free()` when done.
+ *
+ * @example
+ * const builder = new QueryBuilder();
+ * builder.apply({ op: 'add', slot: 'subjects', value: 'since' });
+ * builder.preview().query_string; // "'since'"
+ */
+export class QueryBuilder {
+    private readonly id = wasm.query_builder_new();
+
+    /** Apply an edit and return the new preview. Throws, leaving the builder unchanged, if the edit is invalid. */
+    apply(op: QueryBuilderOp): QueryBuilderPreview {
+        const result = JSON.parse(wasm.query_builder_set(this.id, JSON.stringify(op)));
+        if (result.error) {
+            throw new Error(`query_builder_set failed: ${result.error}`);
+        }
+        return result;
+    }
+
+    preview(): QueryBuilderPreview {
+        const result = JSON.parse(wasm.query_builder_preview(this.id));
+        if (result.error) {
+            throw new Error(`query_builder_preview failed: ${result.error}`);
+        }
+        return result;
+    }
+
+    /** Release the WASM-side builder; the object is unusable afterwards. */
+    free(): void {
+        wasm.query_builder_free(this.id);
+    }
+}
+
 /**
  * Store an attestation in IndexedDB.
  * Returns the attestation on success.
@@ -142,6 +371,36 @@ export async function deleteAttestation(id: string): Promise<boolean> {
     return await wasm.delete_attestation(id);
 }
 
+export interface DeleteOptions {
+    /** List the cascade without deleting anything */
+    dry_run?: boolean;
+    /** Report attestations naming the deleted ID as a subject */
+    find_orphans?: boolean;
+    /** With find_orphans, also check attribute values; reads every attestation */
+    scan_attributes?: boolean;
+}
+
+export interface DeleteReport {
+    id: string;
+    dry_run: boolean;
+    found: boolean;
+    /** IDs of attestations whose subjects or attributes reference the deleted one */
+    orphans: string[];
+    hooks: { name: string; touched: string[]; error?: string }[];
+}
+
+/**
+ * Delete an attestation and report what referenced it.
+ * With `dry_run`, nothing is deleted.
+ */
+export async function deleteAttestationCascade(
+    id: string,
+    options: DeleteOptions = {},
+): Promise<DeleteReport> {
+    await ensureInit();
+    return JSON.parse(await wasm.delete_attestation_cascade(id, JSON.stringify(options)));
+}
+
 /**
  * Check if an attestation exists in IndexedDB.
  */
@@ -160,6 +419,28 @@ export async function queryAttestations(filter: AxQuery): Promise<Attestation[]>
     return JSON.parse(json);
 }
 
+/** One page of {@link queryAttestationsPage} */
+export interface AttestationPage {
+    items: Attestation[];
+    /** Pass back for the next page; null once the last page was returned */
+    next_cursor: string | null;
+}
+
+/**
+ * Query attestations one page at a time, in ID order. Start with a null
+ * cursor and pass each page's `next_cursor` until it is null. Rejects on a
+ * cursor this build didn't issue rather than restarting.
+ */
+export async function queryAttestationsPage(
+    filter: AxQuery,
+    cursor: string | null,
+    pageSize: number,
+): Promise<AttestationPage> {
+    await ensureInit();
+    const json = await wasm.query_attestations_page(JSON.stringify(filter), cursor ?? undefined, pageSize);
+    return JSON.parse(json);
+}
+
 /**
  * List all attestation IDs in IndexedDB.
  */
@@ -169,6 +450,293 @@ export async function listAttestationIds(): Promise<string[]> {
     return JSON.parse(json);
 }
 
+/**
+ * Export every stored attestation as a JSON array in proto format, in ID
+ * order. The string is what {@link importAttestations} takes.
+ */
+export async function exportAttestations(): Promise<string> {
+    await ensureInit();
+    return await wasm.export_attestations();
+}
+
+/** What to do with imported IDs that are already stored */
+export type ImportMode = 'skip_existing' | 'overwrite';
+
+/** Outcome of {@link importAttestations} */
+export interface ImportReport {
+    imported: number;
+    skipped: number;
+    /** Records that weren't stored, by index in the imported array */
+    errors: { index: number; error: string }[];
+}
+
+/**
+ * Store a JSON array of proto-format attestations in batched transactions.
+ * Bad records are reported in `errors` without failing the rest. Imports are
+ * not queued in the outbox.
+ */
+export async function importAttestations(json: string, mode: ImportMode): Promise<ImportReport> {
+    await ensureInit();
+    const report = await wasm.import_attestations(json, mode);
+    return JSON.parse(report);
+}
+
+// ============================================================================
+// Outbox (offline-first writes)
+// ============================================================================
+
+/** A local write queued for upload */
+export interface OutboxEntry {
+    seq: number;
+    op: 'put' | 'delete';
+    id: string;
+    /** Proto-schema attestation (puts only) */
+    attestation?: Attestation;
+    /** SHA-256 of seq, queued_at, op, id and attestation; resends after a crash repeat it */
+    content_hash: string;
+    queued_at: number;
+    attempts: number;
+    next_attempt_at: number;
+    last_error?: string;
+}
+
+/** Uploader verdict for one entry */
+export type OutboxSendResult = boolean | { ok: boolean; error?: string };
+
+/** What to do once the outbox holds `max_entries` */
+export interface OutboxPolicy {
+    max_entries?: number;
+    warn_entries?: number;
+    on_full?: 'block' | 'drop_oldest';
+}
+
+export interface OutboxStatus {
+    pending: number;
+    oldest_age_ms: number | null;
+    failing: number;
+    next_attempt_at: number | null;
+    warn: boolean;
+    full: boolean;
+    enabled: boolean;
+}
+
+export interface OutboxDrainReport {
+    sent: number;
+    failed: number;
+    deferred: number;
+    remaining: number;
+}
+
+/**
+ * Queue every later put/delete in the IndexedDB outbox until disabled.
+ * Already queued entries are kept either way.
+ */
+export async function enableOutbox(enabled: boolean = true): Promise<void> {
+    await ensureInit();
+    wasm.enable_outbox(enabled);
+}
+
+/** Set the outbox size policy (also enables the outbox). */
+export async function setOutboxPolicy(policy: OutboxPolicy): Promise<void> {
+    await ensureInit();
+    wasm.set_outbox_policy(JSON.stringify(policy));
+}
+
+/**
+ * Hand due entries, oldest first, to `send` and drop the ones it accepts.
+ * Failures are retried with backoff; later writes to the same ID wait behind them.
+ */
+export async function drainOutbox(
+    send: (entry: OutboxEntry) => OutboxSendResult | Promise<OutboxSendResult>,
+): Promise<OutboxDrainReport> {
+    await ensureInit();
+    return JSON.parse(await wasm.drain_outbox(send));
+}
+
+/** Pending count and oldest entry age, for an "N changes pending" badge. */
+export async function outboxStatus(): Promise<OutboxStatus> {
+    await ensureInit();
+    return JSON.parse(await wasm.outbox_status());
+}
+
+/** Make every entry due now (call on the `online` event). */
+export async function resetOutboxBackoff(): Promise<void> {
+    await ensureInit();
+    await wasm.reset_outbox_backoff();
+}
+
+/** Discard every queued entry unsent. */
+export async function clearOutbox(): Promise<void> {
+    await ensureInit();
+    await wasm.clear_outbox();
+}
+
+// ============================================================================
+// Changefeed
+// ============================================================================
+
+export interface ChangeEvent {
+    seq: number;
+    op: 'put' | 'update' | 'delete';
+    attestation_id: string;
+    /** Content hash of the written attestation; absent for deletes */
+    content_hash?: string;
+    at: number;
+}
+
+export interface ChangeBatch {
+    events: ChangeEvent[];
+    latest_seq: number;
+    /** Events after `since` were pruned; rescan the store */
+    missed: boolean;
+}
+
+export interface ChangeRetention {
+    max_age_ms?: number;
+    max_events?: number;
+}
+
+/** Writes after sequence number `since` (0 = from the oldest kept), oldest first. */
+export async function changesSince(since: number, limit = 500): Promise<ChangeBatch> {
+    await ensureInit();
+    return JSON.parse(await wasm.changes_since(BigInt(since), limit));
+}
+
+/** Drop old change events. Returns how many were removed. */
+export async function pruneChanges(retention: ChangeRetention): Promise<number> {
+    await ensureInit();
+    return wasm.prune_changes(JSON.stringify(retention));
+}
+
+// ============================================================================
+// Type Registry
+// ============================================================================
+
+export type AttributeType = 'string' | 'number' | 'boolean' | 'timestamp' | 'array' | 'object';
+
+/** A node type parsed from its `[name] is type` attestation */
+export interface TypeDef {
+    name: string;
+    label: string;
+    color?: string;
+    icon?: string;
+    opacity?: number;
+    deprecated: boolean;
+    rich_string_fields: string[];
+    array_fields: string[];
+    attribute_types: Record<string, AttributeType>;
+    attestation_id: string;
+    timestamp: number;
+}
+
+/** A broken type definition; the type is still listed with what could be read */
+export interface TypeWarning {
+    type_name: string;
+    attestation_id: string;
+    kind: 'duplicate_type' | 'invalid_field_list' | 'unknown_attribute_type' | 'invalid_attribute';
+    message: string;
+}
+
+export interface TypeRegistry {
+    types: TypeDef[];
+    warnings: TypeWarning[];
+}
+
+/** Type definitions in the local store, with validation warnings for the types panel. */
+export async function typeRegistry(): Promise<TypeRegistry> {
+    await ensureInit();
+    return JSON.parse(await wasm.type_registry());
+}
+
+// ============================================================================
+// Templates
+// ============================================================================
+
+export interface TemplateParam {
+    name: string;
+    kind: 'string' | 'number' | 'boolean';
+    required: boolean;
+    default?: string;
+    validation?: string;
+}
+
+/** A parameterized attestation, stored as `[name] template qntx` */
+export interface AttestationTemplate {
+    name: string;
+    description?: string;
+    params: TemplateParam[];
+    slots: { slot: 'subject' | 'predicate' | 'context' | 'actor'; value: string }[];
+    attributes?: Record<string, unknown>;
+}
+
+export interface TemplateList {
+    templates: AttestationTemplate[];
+    errors: { attestation_id: string; message: string }[];
+}
+
+/** Template definitions in the local store, newest per name. */
+export async function listTemplates(): Promise<TemplateList> {
+    await ensureInit();
+    return JSON.parse(await wasm.list_templates());
+}
+
+/**
+ * Fill in a stored template, with a fresh ID and timestamp. With `put` the
+ * attestation is also stored. Rejects with `{code:"template",errors:[...]}`
+ * listing every missing or invalid param.
+ */
+export async function instantiateTemplate(
+    name: string,
+    params: Record<string, string>,
+    put: boolean = false,
+): Promise<Attestation> {
+    await ensureInit();
+    return JSON.parse(await wasm.instantiate_template(name, JSON.stringify(params), put));
+}
+
+// ============================================================================
+// Self-benchmark
+// ============================================================================
+
+export interface BenchConfig {
+    seed?: number;
+    iterations?: number;
+    budget_ms?: number;
+    scale?: number;
+    /** Run only these workloads; empty runs all of them */
+    workloads?: string[];
+}
+
+export interface WorkloadTiming {
+    name: string;
+    size: number;
+    iterations: number;
+    median_ms: number;
+    p95_ms: number;
+    min_ms: number;
+}
+
+export interface BenchReport {
+    environment: { target: 'wasm' | 'native'; arch: string; features: string[]; version: string };
+    config: Required<BenchConfig>;
+    workloads: WorkloadTiming[];
+    total_ms: number;
+    budget_exhausted: boolean;
+}
+
+/**
+ * Time the seeded qntx-core workloads on this device (about 1s by default),
+ * e.g. once on first load to size limits for slow devices.
+ */
+export async function runBenchmarks(config: BenchConfig = {}): Promise<BenchReport> {
+    await ensureInit();
+    const report = JSON.parse(wasm.run_benchmarks(JSON.stringify(config)));
+    if (report.error) {
+        throw new Error(report.error);
+    }
+    return report;
+}
+
 // ============================================================================
 // Cosine Similarity
 // ============================================================================
@@ -183,6 +751,314 @@ export function cosineSimilarity(query: Float32Array, candidate: Float32Array):
     return wasm.cosine_similarity_f32(query, candidate);
 }
 
+/** One row picked by {@link similaritySearchDiverse} */
+export interface DiversePick {
+    /** Row index into the candidates batch */
+    id: number;
+    /** Cosine similarity to the query */
+    relevance: number;
+    /** Relevance after the penalty for resembling earlier picks */
+    marginal: number;
+}
+
+export interface DiverseSearchResult {
+    /** Picks in selection order */
+    picks: DiversePick[];
+    /** Rows skipped because they are all zeros */
+    skipped: number[];
+}
+
+/**
+ * Diverse top-k via maximal marginal relevance: near-duplicates of earlier
+ * picks give way to distinct but relevant rows.
+ *
+ * `candidates` packs the rows back to back (`n * query.length` floats); pass a
+ * pool a few times larger than `k`. `lambda` 1 ranks by relevance alone, 0 by
+ * diversity alone.
+ *
+ * @throws {Error} If `candidates` is not a whole number of rows
+ */
+export function similaritySearchDiverse(
+    query: Float32Array,
+    candidates: Float32Array,
+    k: number,
+    lambda = 0.5,
+): DiverseSearchResult {
+    const bytes = wasm.similarity_search_diverse(query, candidates, k, lambda);
+    return JSON.parse(new TextDecoder().decode(bytes));
+}
+
+// ============================================================================
+// Claims
+// ============================================================================
+
+/** A single (subject, predicate, context, actor) claim expanded from an attestation */
+export interface IndividualClaim {
+    subject: string;
+    predicate: string;
+    context: string;
+    actor: string;
+    timestamp_ms: number;
+    source_id: string;
+    /** Confidence of the source attestation (0-1), if its creator stated one */
+    confidence?: number;
+}
+
+/** Compact attestation input for cartesian expansion */
+export interface ExpandAttestation {
+    id: string;
+    subjects: string[];
+    predicates: string[];
+    contexts: string[];
+    actors: string[];
+    timestamp_ms: number;
+    /** Creator-assigned confidence (0-1) */
+    confidence?: number;
+}
+
+/**
+ * Group key format. `json` keys are `JSON.parse`-able `[subject, predicate, context]`
+ * arrays; `legacy` keys are `subject|predicate|context` and collide when a value contains `|`.
+ */
+export type ClaimKeyFormat = 'json' | 'legacy';
+
+/** Claims sharing one (subject, predicate, context) */
+export interface ClaimGroup {
+    key: string;
+    claims: IndividualClaim[];
+}
+
+function callClaimsWasm<T>(name: string, fn: (input: string) => string, input: unknown): T {
+    const result = JSON.parse(fn(JSON.stringify(input)));
+    if (result.error) {
+        throw new Error(`${name} failed: ${result.error}`);
+    }
+    return result;
+}
+
+/** Expand compact attestations into individual claims (S × P × C × A per attestation). */
+export function expandCartesianClaims(attestations: ExpandAttestation[]): IndividualClaim[] {
+    return callClaimsWasm<{ claims: IndividualClaim[] }>(
+        'expand_cartesian_claims', wasm.expand_cartesian_claims, { attestations },
+    ).claims;
+}
+
+/** Group claims by (subject, predicate, context), sorted by those values. */
+export function groupClaims(claims: IndividualClaim[], keyFormat: ClaimKeyFormat = 'json'): ClaimGroup[] {
+    return callClaimsWasm<{ groups: ClaimGroup[] }>(
+        'group_claims', wasm.group_claims, { claims, key_format: keyFormat },
+    ).groups;
+}
+
+/** Unique source attestation IDs of the claims, in first-seen order. */
+export function dedupSourceIds(claims: IndividualClaim[]): string[] {
+    return callClaimsWasm<{ ids: string[] }>('dedup_source_ids', wasm.dedup_source_ids, { claims }).ids;
+}
+
+/** Source attestations of a classified claim, by role once its conflict is resolved */
+export interface ClaimProvenance {
+    /** Sources whose claims survive resolution, e.g. every source of a verification */
+    supporting_source_ids: string[];
+    /** Sources whose claims were set aside, e.g. an older evolution step */
+    superseded_source_ids: string[];
+}
+
+/**
+ * Unique source IDs of the claims partitioned by `roles` (a conflict's
+ * provenance), each list in first-seen order. Sources `roles` doesn't list
+ * count as supporting.
+ */
+export function dedupSourceIdsByRole(claims: IndividualClaim[], roles: ClaimProvenance): ClaimProvenance {
+    return callClaimsWasm<{ roles: ClaimProvenance }>(
+        'dedup_source_ids', wasm.dedup_source_ids, { claims, roles },
+    ).roles;
+}
+
+// ============================================================================
+// Pseudonymization
+// ============================================================================
+
+/** What to do with one kind of value: keyed token, keep, or `[redacted]` */
+export type ValuePolicy = 'hash' | 'preserve' | 'redact';
+
+/** Pseudonymization key and policies. Omitted policies keep their defaults. */
+export interface PseudonymizeConfig {
+    /** Secret key (at least 16 bytes); the same key yields the same tokens */
+    key: string;
+    subjects?: ValuePolicy;
+    predicates?: ValuePolicy;
+    contexts?: ValuePolicy;
+    actors?: ValuePolicy;
+    source?: ValuePolicy;
+    /** Per-attribute-key policies */
+    attributes?: Record<string, ValuePolicy>;
+    /** Attribute strings longer than this are redacted unless a policy names the key */
+    max_attribute_len?: number;
+    timestamps?:
+        | { mode: 'preserve' }
+        | { mode: 'bucket'; bucket_ms: number }
+        | { mode: 'jitter'; max_ms: number };
+}
+
+/** Replace personal data in attestations with keyed tokens before exporting them. */
+export function pseudonymizeAttestations(
+    attestations: Attestation[],
+    config: PseudonymizeConfig,
+): Attestation[] {
+    const result = JSON.parse(
+        wasm.pseudonymize_attestations(JSON.stringify(attestations), JSON.stringify(config)),
+    );
+    if (result.error) {
+        throw new Error(`pseudonymize_attestations failed: ${result.error}`);
+    }
+    return result.attestations;
+}
+
+// ============================================================================
+// Attestation diff / patch
+// ============================================================================
+
+/** Values added to and removed from one slot */
+export interface SlotDiff {
+    added: string[];
+    removed: string[];
+}
+
+/** A field that changed from `old` to `new` */
+export interface Change<T> {
+    old: T;
+    new: T;
+}
+
+/** Attribute changes by key; removed entries keep their old value */
+export interface AttributeDiff {
+    added: Record<string, unknown>;
+    removed: Record<string, unknown>;
+    modified: Record<string, Change<unknown>>;
+}
+
+/** Field-level differences between two attestations. Slots compare as sets. */
+export interface AttestationDiff {
+    subjects: SlotDiff;
+    predicates: SlotDiff;
+    contexts: SlotDiff;
+    actors: SlotDiff;
+    attributes: AttributeDiff;
+    timestamp: Change<number> | null;
+    source: Change<string> | null;
+}
+
+/** A change whose expected value did not hold on the base; `null` means absent */
+export interface PatchMismatch {
+    field: string;
+    expected: unknown;
+    actual: unknown;
+}
+
+/** Thrown by applyAttestationPatch when the base has moved on */
+export class PatchConflictError extends Error {
+    constructor(message: string, readonly mismatches: PatchMismatch[]) {
+        super(message);
+        this.name = 'PatchConflictError';
+    }
+}
+
+/** What changed from `a` to `b`: slot values, attributes, timestamp and source. */
+export function diffAttestations(a: Attestation, b: Attestation): AttestationDiff {
+    const result = JSON.parse(wasm.diff_attestations(JSON.stringify(a), JSON.stringify(b)));
+    if (result.error) {
+        throw new Error(`diff_attestations failed: ${result.error}`);
+    }
+    return result;
+}
+
+/**
+ * Apply a diff (or the accepted part of one) to `base`. Throws
+ * PatchConflictError listing every mismatch if `base` no longer holds the
+ * values the diff expects.
+ */
+export function applyAttestationPatch(base: Attestation, diff: Partial<AttestationDiff>): Attestation {
+    const result = JSON.parse(wasm.apply_attestation_patch(JSON.stringify(base), JSON.stringify(diff)));
+    if (result.conflict) {
+        throw new PatchConflictError(result.error, result.conflict.mismatches);
+    }
+    if (result.error) {
+        throw new Error(`apply_attestation_patch failed: ${result.error}`);
+    }
+    return result;
+}
+
+// ============================================================================
+// Core config
+// ============================================================================
+
+/** Classification time windows, in milliseconds */
+export interface ClassifyWindows {
+    verification_window_ms: number;
+    evolution_window_ms: number;
+    obsolescence_window_ms: number;
+}
+
+/** Windows classification falls back to: the built-in defaults, or those of {@link loadCoreConfig} */
+export function classifyDefaultConfig(): ClassifyWindows {
+    return JSON.parse(wasm.classify_default_config());
+}
+
+/** qntx-core config document. Omitted fields keep their built-in defaults. */
+export interface CoreConfig {
+    classify?: Partial<ClassifyWindows> & { actor_aliases?: ActorAliases };
+    lint?: { disabled?: LintCode[] };
+    slow_ops?: Partial<SlowOpThresholds>;
+}
+
+/**
+ * Set module-wide defaults for classification and other core functions.
+ * Throws listing every validation problem; the previous config then stays in effect.
+ */
+export function loadCoreConfig(config: CoreConfig): void {
+    callClaimsWasm('load_core_config', wasm.load_core_config, config);
+}
+
+/** Canonical actor → other spellings of the same identity */
+export type ActorAliases = Record<string, string[]>;
+
+/**
+ * Treat each canonical actor and its aliases as one identity when classifying,
+ * and when a query sets `expand_actor_aliases`. Returns the number of identities.
+ * Throws on conflicting or chained aliases; the previous aliases then stay.
+ */
+export function setActorAliases(aliases: ActorAliases): number {
+    return callClaimsWasm<{ identities: number }>(
+        'set_actor_aliases', wasm.set_actor_aliases, aliases,
+    ).identities;
+}
+
+/** Replace the actor aliases with those attested in IndexedDB (`[alias] actor_alias [canonical]`). */
+export async function loadActorAliases(): Promise<number> {
+    await ensureInit();
+    return JSON.parse(await wasm.load_actor_aliases()).identities;
+}
+
+/** A resolved (subject, predicate, context) claim and the attestations behind it */
+export interface SupportedClaim extends ClaimProvenance {
+    subject: string;
+    predicate: string;
+    context: string;
+    /** How the sources relate; null when the claim has a single source */
+    conflict_type: 'Evolution' | 'Verification' | 'Coexistence' | 'Supersession' | 'Review' | null;
+    confidence: number;
+}
+
+/**
+ * Resolved claims that the stored attestation `id` supports, e.g. to warn
+ * "deleting this will weaken 3 verified claims" before a delete or revocation.
+ * A claim whose only supporting source is `id` disappears with it.
+ */
+export async function claimsSupportedBy(id: string): Promise<SupportedClaim[]> {
+    await ensureInit();
+    return JSON.parse(await wasm.claims_supported_by(id)).claims;
+}
+
 // ============================================================================
 // Identity
 // ============================================================================
@@ -218,6 +1094,81 @@ export function generateASUID(prefix: string, subject: string, predicate: string
     return result;
 }
 
+// ============================================================================
+// Slow operations
+// ============================================================================
+
+/** Milliseconds before an operation is logged as slow; 0 disables the category */
+export interface SlowOpThresholds {
+    store_query_ms: number;
+    store_write_ms: number;
+    classify_ms: number;
+    hash_ms: number;
+    /** Entries kept for `slowOpsReport` */
+    ring_capacity: number;
+}
+
+export interface SlowOp {
+    category: 'store_query' | 'store_write' | 'classify' | 'hash';
+    duration_ms: number;
+    detail: { input_len?: number; result_count?: number };
+    /** Epoch milliseconds when the operation finished */
+    at: number;
+}
+
+/** Change slow-op thresholds; omitted fields keep their current value. */
+export function setSlowOpThresholds(thresholds: Partial<SlowOpThresholds>): void {
+    callClaimsWasm('set_slow_op_thresholds', wasm.set_slow_op_thresholds, thresholds);
+}
+
+/** Recent operations over their threshold (also logged via console.warn), oldest first. */
+export function slowOpsReport(): SlowOp[] {
+    return JSON.parse(wasm.slow_ops_report());
+}
+
+// ============================================================================
+// Batched calls
+// ============================================================================
+
+/** One call in an `executeBatch`; see `execute_batch` in browser.rs for the ops */
+export interface BatchRequest {
+    id: string;
+    op: string;
+    params?: Record<string, unknown>;
+}
+
+/** Outcome of one batched call; a failure never fails the other entries */
+export type BatchEntry =
+    | { id: string; result: unknown }
+    | { id: string; error: StorageError | string };
+
+/**
+ * Run several read/init calls in one WASM crossing, in order.
+ * `init_store`/`init_worker`/`load_core_config` entries affect later ones.
+ */
+export async function executeBatch(requests: BatchRequest[]): Promise<BatchEntry[]> {
+    await ensureInit();
+    return JSON.parse(await wasm.execute_batch(JSON.stringify(requests)));
+}
+
+type BundleEntry<T> = { result: T } | { error: StorageError | string };
+
+export interface StartupBundle {
+    init: BundleEntry<null>;
+    version: BundleEntry<string>;
+    outbox_status: BundleEntry<OutboxStatus>;
+    type_registry: BundleEntry<TypeRegistry>;
+    /** Present when an initial filter was passed */
+    query?: BundleEntry<Attestation[]>;
+}
+
+/** Version, outbox status, type registry and an optional initial query in one call. */
+export async function startupBundle(initialFilter?: AxQuery): Promise<StartupBundle> {
+    await ensureInit();
+    const filter = initialFilter ? JSON.stringify(initialFilter) : undefined;
+    return JSON.parse(await wasm.startup_bundle(undefined, filter));
+}
+
 // ============================================================================
 // Utilities
 // ============================================================================
@@ -236,5 +1187,20 @@ export function isInitialized(): boolean {
     return wasm.is_store_initialized();
 }
 
+/** Export groups compiled into the loaded WASM build (see the features in qntx-wasm's Cargo.toml) */
+export interface WasmCapabilities {
+    storage: boolean;
+    classify: boolean;
+    similarity: boolean;
+    bench: boolean;
+    /** Names of the wasm-bindgen exports present, sorted */
+    exports: string[];
+}
+
+/** Which export groups this build has; check before calling an optional export. */
+export function capabilities(): WasmCapabilities {
+    return JSON.parse(wasm.capabilities());
+}
+
 /** Export raw WASM module for advanced use */
 export { wasm };